@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A repo branch that was pushed to its shared remote before handoff.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoffRepo {
+    pub repo_name: String,
+    pub remote_url: Option<String>,
+    pub branch: String,
+    pub target_branch: String,
+    pub head_commit: Option<String>,
+}
+
+/// One coding agent turn from the source workspace's sessions.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoffTurn {
+    pub prompt: Option<String>,
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A follow-up that was queued on the source instance but not yet sent.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoffQueuedMessage {
+    pub message: String,
+    pub executor_config: Value,
+}
+
+/// Everything the receiving instance needs to recreate the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoffPayload {
+    pub workspace_name: Option<String>,
+    pub repos: Vec<WorkspaceHandoffRepo>,
+    pub transcript: Vec<WorkspaceHandoffTurn>,
+    pub queued_messages: Vec<WorkspaceHandoffQueuedMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoff {
+    pub id: Uuid,
+    pub owner_user_id: Uuid,
+    pub source_local_workspace_id: Uuid,
+    pub payload: WorkspaceHandoffPayload,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub claimed_local_workspace_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceHandoffRequest {
+    pub source_local_workspace_id: Uuid,
+    pub payload: WorkspaceHandoffPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ListWorkspaceHandoffsResponse {
+    pub handoffs: Vec<WorkspaceHandoff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimWorkspaceHandoffRequest {
+    pub local_workspace_id: Uuid,
+}
@@ -0,0 +1,25 @@
+CREATE TABLE doc_sources (
+    id                       BLOB PRIMARY KEY,
+    repo_id                  BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    name                     TEXT NOT NULL,
+    kind                     TEXT NOT NULL CHECK (kind IN ('url', 'local_folder', 'openapi')),
+    location                 TEXT NOT NULL,
+    refresh_interval_minutes INTEGER,
+    last_indexed_at          TEXT,
+    last_error               TEXT,
+    created_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_doc_sources_repo_id ON doc_sources(repo_id);
+
+CREATE TABLE doc_chunks (
+    id          BLOB PRIMARY KEY,
+    source_id   BLOB NOT NULL REFERENCES doc_sources(id) ON DELETE CASCADE,
+    ordinal     INTEGER NOT NULL,
+    title       TEXT NOT NULL,
+    content     TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_doc_chunks_source_id ON doc_chunks(source_id);
@@ -0,0 +1,23 @@
+-- Full-text index over execution process stdout/stderr lines. Logs live in
+-- JSONL files on disk; this table holds a searchable copy of each line.
+CREATE VIRTUAL TABLE execution_log_fts USING fts5(
+    content,
+    execution_id UNINDEXED,
+    line_number UNINDEXED,
+    stream UNINDEXED,
+    tokenize = 'unicode61'
+);
+
+-- Tracks which execution processes have been indexed so the backfill can
+-- pick up processes that finished before the index existed.
+CREATE TABLE execution_log_index_state (
+    execution_id BLOB PRIMARY KEY REFERENCES execution_processes(id) ON DELETE CASCADE,
+    line_count   INTEGER NOT NULL,
+    indexed_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE TRIGGER execution_log_fts_cleanup
+AFTER DELETE ON execution_processes
+BEGIN
+    DELETE FROM execution_log_fts WHERE execution_id = OLD.id;
+END;
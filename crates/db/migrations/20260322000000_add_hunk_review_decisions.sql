@@ -0,0 +1,17 @@
+-- Keep/discard decisions made on individual diff hunks during review.
+CREATE TABLE hunk_review_decisions (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    -- The coding agent run whose changes were being reviewed, if any.
+    execution_process_id BLOB REFERENCES execution_processes(id) ON DELETE SET NULL,
+    repo_id              BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    file_path            TEXT NOT NULL,
+    hunk_id              TEXT NOT NULL,
+    action               TEXT NOT NULL CHECK (action IN ('keep', 'discard')),
+    patch                TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_hunk_review_decisions_workspace_id ON hunk_review_decisions(workspace_id);
+CREATE INDEX idx_hunk_review_decisions_execution_process_id
+    ON hunk_review_decisions(execution_process_id);
@@ -0,0 +1,20 @@
+-- Agent-written explanations of a commit range, cached per repo and commit pair.
+CREATE TABLE change_explanations (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    repo_id              BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    base_commit          TEXT NOT NULL,
+    head_commit          TEXT NOT NULL,
+    -- The summarization run; NULL once the process has been deleted.
+    execution_process_id BLOB REFERENCES execution_processes(id) ON DELETE SET NULL,
+    -- Parsed ChangeExplanationContent JSON, set when the run finishes.
+    explanation          TEXT,
+    error                TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE UNIQUE INDEX idx_change_explanations_commit_pair
+    ON change_explanations(repo_id, base_commit, head_commit);
+CREATE INDEX idx_change_explanations_workspace_id_created_at
+    ON change_explanations(workspace_id, created_at);
@@ -0,0 +1,12 @@
+-- Links a local workspace to a handoff published to, or claimed from, the
+-- remote backend. An outgoing handoff makes the workspace read-only.
+CREATE TABLE workspace_handoffs (
+    id                BLOB PRIMARY KEY,
+    workspace_id      BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    remote_handoff_id BLOB NOT NULL,
+    direction         TEXT NOT NULL CHECK (direction IN ('outgoing', 'incoming')),
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE UNIQUE INDEX idx_workspace_handoffs_workspace_direction
+    ON workspace_handoffs(workspace_id, direction);
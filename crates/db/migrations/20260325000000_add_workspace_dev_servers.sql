@@ -0,0 +1,8 @@
+-- Per-workspace dev server override. A script replaces the repos' configured
+-- dev server scripts; a port tells the preview where the server listens.
+CREATE TABLE workspace_dev_servers (
+    workspace_id BLOB PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    script       TEXT,
+    port         INTEGER,
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
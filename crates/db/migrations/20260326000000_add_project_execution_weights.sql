@@ -0,0 +1,7 @@
+-- Fair-share weight of a project when coding agent executions are capped.
+-- Projects without a row use the default weight of 1.
+CREATE TABLE project_execution_weights (
+    project_id BLOB PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    weight     INTEGER NOT NULL CHECK (weight > 0),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,27 @@
+-- Reusable workspace recipes for recurring chores ("fix flaky tests",
+-- "update deps"). Starting a workspace from a template fills in everything
+-- the create-and-start request needs.
+CREATE TABLE task_templates (
+    id              BLOB PRIMARY KEY,
+    name            TEXT NOT NULL,
+    prompt          TEXT NOT NULL,
+    -- JSON ExecutorConfig; NULL uses the configured default executor
+    executor_config TEXT,
+    -- NULL uses each repo's default target branch
+    base_branch     TEXT,
+    -- JSON object of extra environment variables
+    env_vars        TEXT NOT NULL DEFAULT '{}',
+    -- Runs in the workspace root before the repos' setup scripts
+    setup_script    TEXT,
+    -- JSON array of the repo ids the workspace is created with
+    repo_ids        TEXT NOT NULL DEFAULT '[]',
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- Extra environment variables injected into every process of a workspace.
+CREATE TABLE workspace_env_vars (
+    workspace_id BLOB PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    vars         TEXT NOT NULL DEFAULT '{}',
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,64 @@
+-- Append-only history of the activity summarised by productivity reports.
+-- Reports fold rows past report_cursor into report_periods, so processed
+-- events are never read again.
+CREATE TABLE activity_events (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind             TEXT NOT NULL CHECK (kind IN ('workspace_merged',
+                                                   'approval_approved',
+                                                   'approval_denied',
+                                                   'coding_agent_started')),
+    workspace_id     BLOB,
+    executor         TEXT,
+    -- Seconds from workspace creation to its first merge
+    duration_seconds INTEGER,
+    occurred_at      TEXT NOT NULL
+);
+
+-- A workspace counts as completed once, at its first merge
+CREATE UNIQUE INDEX idx_activity_events_workspace_merged
+        ON activity_events(workspace_id)
+        WHERE kind = 'workspace_merged';
+
+-- Running totals per week (starting Monday) and calendar month
+CREATE TABLE report_periods (
+    period             TEXT NOT NULL CHECK (period IN ('week', 'month')),
+    period_start       TEXT NOT NULL,
+    tasks_completed    INTEGER NOT NULL DEFAULT 0,
+    approvals_approved INTEGER NOT NULL DEFAULT 0,
+    approvals_denied   INTEGER NOT NULL DEFAULT 0,
+    -- JSON array of time-to-merge samples in seconds, for the median
+    merge_durations    TEXT NOT NULL DEFAULT '[]',
+    -- JSON object of coding agent executions per executor
+    executor_counts    TEXT NOT NULL DEFAULT '{}',
+    delivered_at       TEXT,
+    PRIMARY KEY (period, period_start)
+);
+
+CREATE TABLE report_cursor (
+    id            INTEGER PRIMARY KEY CHECK (id = 1),
+    last_event_id INTEGER NOT NULL
+);
+
+INSERT INTO report_cursor (id, last_event_id) VALUES (1, 0);
+
+-- Backfill completed workspaces from existing merges and merged PRs
+INSERT OR IGNORE INTO activity_events (kind, workspace_id, duration_seconds, occurred_at)
+SELECT 'workspace_merged',
+       w.id,
+       CAST((julianday(m.merged_at) - julianday(w.created_at)) * 86400 AS INTEGER),
+       m.merged_at
+FROM workspaces w
+JOIN (
+    SELECT workspace_id, MIN(merged_at) AS merged_at
+    FROM (
+        SELECT workspace_id, created_at AS merged_at
+        FROM merges
+        WHERE merge_type = 'direct'
+        UNION ALL
+        SELECT workspace_id, merged_at
+        FROM pull_requests
+        WHERE merged_at IS NOT NULL AND workspace_id IS NOT NULL
+    )
+    GROUP BY workspace_id
+) m ON m.workspace_id = w.id
+ORDER BY m.merged_at ASC;
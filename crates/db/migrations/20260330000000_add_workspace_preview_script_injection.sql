@@ -0,0 +1,3 @@
+-- Per-workspace override of which scripts the preview proxy injects into the
+-- dev server's pages: 'none', 'local' or 'full'. NULL follows the config.
+ALTER TABLE workspace_dev_servers ADD COLUMN script_injection TEXT;
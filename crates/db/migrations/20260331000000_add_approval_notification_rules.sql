@@ -0,0 +1,16 @@
+-- Routing of approval notifications by tool category and repo. Rules are
+-- evaluated by position and the first match decides the channels; with no
+-- match the configured sound and push notifications are used.
+CREATE TABLE approval_notification_rules (
+    id            BLOB PRIMARY KEY,
+    position      INTEGER NOT NULL DEFAULT 0,
+    -- NULL matches any tool category
+    tool_category TEXT CHECK (tool_category IN
+        ('shell', 'file_edit', 'file_read', 'web', 'mcp', 'question', 'other')),
+    -- NULL matches any repo
+    repo_id       BLOB REFERENCES repos(id) ON DELETE CASCADE,
+    -- JSON array of notification channels
+    channels      TEXT NOT NULL DEFAULT '[]',
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,41 @@
+-- Per-session overrides of the configured coding agent timeouts. NULL
+-- inherits the configured limit, 0 disables it.
+CREATE TABLE session_timeouts (
+    session_id         BLOB PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+    wall_clock_minutes INTEGER CHECK (wall_clock_minutes >= 0),
+    idle_minutes       INTEGER CHECK (idle_minutes >= 0),
+    updated_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- Add 'timedout' to the status CHECK constraint
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('running',
+                          'completed',
+                          'failed',
+                          'killed',
+                          'timedout'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 3. Drop any indexes that reference status
+DROP INDEX IF EXISTS idx_execution_processes_status;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+
+-- 4. Remove the old column (requires 3.35+)
+ALTER TABLE execution_processes DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create the indexes
+CREATE INDEX idx_execution_processes_status
+        ON execution_processes(status);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+        ON execution_processes (session_id, status, run_reason);
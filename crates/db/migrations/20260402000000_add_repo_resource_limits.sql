@@ -0,0 +1,7 @@
+-- CPU and memory caps for processes started in a repo's worktree.
+CREATE TABLE repo_resource_limits (
+    repo_id           BLOB PRIMARY KEY REFERENCES repos(id) ON DELETE CASCADE,
+    memory_limit_mb   INTEGER CHECK (memory_limit_mb > 0),
+    cpu_limit_percent INTEGER CHECK (cpu_limit_percent > 0),
+    updated_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
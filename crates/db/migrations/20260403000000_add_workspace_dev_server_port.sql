@@ -0,0 +1,3 @@
+-- Port the workspace's running dev server was detected listening on, read
+-- from its output. NULL while no dev server is running or none was found.
+ALTER TABLE workspaces ADD COLUMN dev_server_port INTEGER;
@@ -0,0 +1,34 @@
+-- Add 'interrupted' to the status CHECK constraint, for coding agent runs
+-- stopped by a server shutdown that can be resumed with a follow-up
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('running',
+                          'completed',
+                          'failed',
+                          'killed',
+                          'timedout',
+                          'interrupted'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 3. Drop any indexes that reference status
+DROP INDEX IF EXISTS idx_execution_processes_status;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+
+-- 4. Remove the old column (requires 3.35+)
+ALTER TABLE execution_processes DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create the indexes
+CREATE INDEX idx_execution_processes_status
+        ON execution_processes(status);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+        ON execution_processes (session_id, status, run_reason);
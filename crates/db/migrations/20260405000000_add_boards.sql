@@ -0,0 +1,31 @@
+-- Kanban boards. Columns are ordered by position and each workspace sits in
+-- at most one column per board, ordered by position within the column.
+-- Positions are kept dense (0..n) by the application.
+CREATE TABLE boards (
+    id         BLOB PRIMARY KEY,
+    name       TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE TABLE board_columns (
+    id         BLOB PRIMARY KEY,
+    board_id   BLOB NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    name       TEXT NOT NULL,
+    position   INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_board_columns_board_position ON board_columns(board_id, position);
+
+CREATE TABLE board_cards (
+    board_id     BLOB NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    workspace_id BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    column_id    BLOB NOT NULL REFERENCES board_columns(id) ON DELETE CASCADE,
+    position     INTEGER NOT NULL DEFAULT 0,
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (board_id, workspace_id)
+);
+
+CREATE INDEX idx_board_cards_column_position ON board_cards(column_id, position);
@@ -0,0 +1,14 @@
+-- Sub-task decomposition: a parent workspace spawns child workspaces whose
+-- repos target the parent's branch, and later merges them back into it.
+CREATE TABLE workspace_children (
+    child_workspace_id  BLOB PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    parent_workspace_id BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    -- The part of the prompt specific to this child
+    prompt_fragment     TEXT NOT NULL,
+    -- Order in which children are merged into the parent
+    position            INTEGER NOT NULL DEFAULT 0,
+    merged_at           TEXT,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_workspace_children_parent ON workspace_children(parent_workspace_id, position);
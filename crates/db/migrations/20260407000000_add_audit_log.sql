@@ -0,0 +1,21 @@
+-- Who did what for destructive operations, so a host shared over the relay
+-- keeps a record of which paired client deleted or killed something.
+-- Entries outlive their targets, so target ids are not foreign keys.
+CREATE TABLE audit_log (
+    id              BLOB PRIMARY KEY,
+    action          TEXT NOT NULL CHECK (action IN
+        ('workspace_deleted', 'process_killed', 'branch_force_pushed',
+         'approval_policy_changed', 'relay_client_removed')),
+    actor_kind      TEXT NOT NULL CHECK (actor_kind IN ('local', 'relay')),
+    -- Paired relay client that made the request, if known
+    actor_client_id BLOB,
+    actor_name      TEXT,
+    target_type     TEXT NOT NULL,
+    target_id       TEXT,
+    -- JSON object with action-specific details
+    details         TEXT NOT NULL DEFAULT '{}',
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX idx_audit_log_action_created_at ON audit_log(action, created_at);
@@ -0,0 +1,8 @@
+-- Directories a repo's setup script warms up (node_modules, target) that are
+-- cached and copied into new worktrees, keyed by a hash of key files.
+CREATE TABLE repo_setup_caches (
+    repo_id     BLOB PRIMARY KEY REFERENCES repos(id) ON DELETE CASCADE,
+    cache_paths TEXT NOT NULL,
+    key_files   TEXT,
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,11 @@
+-- How far each workspace branch has drifted from its repo's target branch,
+-- refreshed in the background by the branch freshness monitor.
+CREATE TABLE workspace_branch_freshness (
+    workspace_id   BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    repo_id        BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    target_branch  TEXT NOT NULL,
+    commits_ahead  INTEGER NOT NULL,
+    commits_behind INTEGER NOT NULL,
+    checked_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (workspace_id, repo_id)
+);
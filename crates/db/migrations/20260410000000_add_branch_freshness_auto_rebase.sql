@@ -0,0 +1,7 @@
+-- Outcome of the latest automatic rebase of an idle workspace branch onto its
+-- target. A conflicting rebase is not retried until the target branch moves
+-- past auto_rebase_target_oid.
+ALTER TABLE workspace_branch_freshness
+    ADD COLUMN auto_rebase_status TEXT CHECK (auto_rebase_status IN ('rebased', 'conflicts'));
+ALTER TABLE workspace_branch_freshness ADD COLUMN auto_rebase_target_oid TEXT;
+ALTER TABLE workspace_branch_freshness ADD COLUMN auto_rebased_at TEXT;
@@ -0,0 +1,14 @@
+-- Responses to mutating API requests sent with an Idempotency-Key header, so
+-- a client retrying after a dropped relay connection gets the original result
+-- instead of running the action twice. `status` is NULL while the first
+-- request is still being handled.
+CREATE TABLE idempotency_keys (
+    key                 TEXT PRIMARY KEY,
+    request_fingerprint TEXT NOT NULL,
+    status              INTEGER,
+    content_type        TEXT,
+    body                BLOB,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
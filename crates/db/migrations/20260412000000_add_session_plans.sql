@@ -0,0 +1,18 @@
+-- The latest plan a coding agent in plan mode proposed for each session and
+-- whether it was accepted. A revised plan replaces the previous one and bumps
+-- `revision`. `approval_id` is the approval the agent is waiting on before it
+-- starts carrying the plan out.
+CREATE TABLE session_plans (
+    session_id           BLOB PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+    execution_process_id BLOB NOT NULL REFERENCES execution_processes(id) ON DELETE CASCADE,
+    plan                 TEXT NOT NULL,
+    revision             INTEGER NOT NULL DEFAULT 1,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                           CHECK (status IN ('pending', 'accepted', 'rejected', 'expired')),
+    approval_id          TEXT,
+    feedback             TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_session_plans_approval_id ON session_plans(approval_id);
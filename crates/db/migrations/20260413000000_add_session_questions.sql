@@ -0,0 +1,17 @@
+-- Questions coding agents asked during a session and the answers they got,
+-- kept for review after the run. Both are JSON; `answers` is NULL until the
+-- question is answered.
+CREATE TABLE session_questions (
+    id                   BLOB PRIMARY KEY,
+    session_id           BLOB NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    execution_process_id BLOB NOT NULL REFERENCES execution_processes(id) ON DELETE CASCADE,
+    approval_id          TEXT NOT NULL UNIQUE,
+    questions            TEXT NOT NULL,
+    answers              TEXT,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                           CHECK (status IN ('pending', 'answered', 'unanswered')),
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    resolved_at          TEXT
+);
+
+CREATE INDEX idx_session_questions_session_id ON session_questions(session_id, created_at);
@@ -0,0 +1,12 @@
+-- Read-only links to a session's transcript and live logs. Only a SHA-256
+-- hash of the link's token is stored; the token itself is shown once, when
+-- the link is created.
+CREATE TABLE session_share_links (
+    id          BLOB PRIMARY KEY,
+    session_id  BLOB NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    token_hash  TEXT NOT NULL UNIQUE,
+    expires_at  TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_session_share_links_session_id ON session_share_links(session_id);
@@ -0,0 +1,9 @@
+-- Docker image a repo's executors, scripts and dev servers run in when the
+-- Docker execution backend is selected.
+CREATE TABLE repo_container_images (
+    repo_id    BLOB PRIMARY KEY REFERENCES repos(id) ON DELETE CASCADE,
+    image      TEXT NOT NULL CHECK (image <> ''),
+    -- Comma-separated container ports published on 127.0.0.1
+    ports      TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,13 @@
+-- Workspaces this instance started on a paired host, so they can be followed
+-- and stopped from here. The ids refer to records on that host.
+CREATE TABLE remote_executions (
+    id                          BLOB PRIMARY KEY,
+    host_id                     BLOB NOT NULL,
+    remote_workspace_id         BLOB NOT NULL,
+    remote_execution_process_id BLOB NOT NULL,
+    name                        TEXT,
+    prompt                      TEXT NOT NULL,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_remote_executions_created_at ON remote_executions(created_at);
@@ -0,0 +1,7 @@
+-- Resources a repo's coding agent executions use heavily. The execution
+-- scheduler caps how many executions holding each tag run at once.
+CREATE TABLE repo_resource_tags (
+    repo_id BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    tag     TEXT NOT NULL CHECK (tag IN ('cpu_heavy', 'gpu', 'network')),
+    PRIMARY KEY (repo_id, tag)
+);
@@ -0,0 +1,10 @@
+-- Gitignore-style patterns for files in a repo that coding agents may only
+-- change with an approval, or not at all.
+CREATE TABLE repo_protected_paths (
+    id         BLOB PRIMARY KEY,
+    repo_id    BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    pattern    TEXT NOT NULL CHECK (pattern <> ''),
+    action     TEXT NOT NULL CHECK (action IN ('require_approval', 'deny')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    UNIQUE (repo_id, pattern)
+);
@@ -0,0 +1,18 @@
+-- Possible credentials found in the changes a workspace branch adds, from the
+-- scan before each push or pull request. Pushes wait until every finding is
+-- confirmed.
+CREATE TABLE workspace_secret_findings (
+    id           BLOB PRIMARY KEY,
+    workspace_id BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    repo_id      BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    file_path    TEXT NOT NULL,
+    line         INTEGER,
+    rule_id      TEXT NOT NULL,
+    -- Start of the matched text, the rest masked
+    redacted     TEXT NOT NULL,
+    confirmed_at TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_workspace_secret_findings_workspace_id
+    ON workspace_secret_findings(workspace_id);
@@ -0,0 +1,72 @@
+-- Commands (lint, typecheck, tests, ...) run in a repo when a coding agent
+-- session completes, before the workspace is reported ready for review.
+CREATE TABLE repo_check_commands (
+    id         BLOB PRIMARY KEY,
+    repo_id    BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    name       TEXT NOT NULL CHECK (name <> ''),
+    command    TEXT NOT NULL CHECK (command <> ''),
+    position   INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_repo_check_commands_repo_id ON repo_check_commands(repo_id, position);
+
+-- Outcome of each check in the latest check run of a workspace. Starting a
+-- run replaces the previous run's rows. Name and command are copied so the
+-- results stay readable after the repo's checks change.
+CREATE TABLE workspace_check_results (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    repo_id              BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    name                 TEXT NOT NULL,
+    command              TEXT NOT NULL,
+    position             INTEGER NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                           CHECK (status IN ('pending', 'running', 'passed', 'failed', 'skipped')),
+    execution_process_id BLOB REFERENCES execution_processes(id) ON DELETE SET NULL,
+    exit_code            INTEGER,
+    started_at           TEXT,
+    completed_at         TEXT
+);
+
+CREATE INDEX idx_workspace_check_results_workspace_id
+        ON workspace_check_results(workspace_id, position);
+
+-- Add 'checkcommand' to the run_reason CHECK constraint
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN run_reason_new TEXT NOT NULL DEFAULT 'setupscript'
+    CHECK (run_reason_new IN ('setupscript',
+                               'cleanupscript',
+                               'archivescript',
+                               'codingagent',
+                               'devserver',
+                               'repocommand',
+                               'checkcommand'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET run_reason_new = run_reason;
+
+-- 3. Drop any indexes that reference run_reason
+DROP INDEX IF EXISTS idx_execution_processes_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_session_run_reason_created;
+
+-- 4. Remove the old column (requires 3.35+)
+ALTER TABLE execution_processes DROP COLUMN run_reason;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN run_reason_new TO run_reason;
+
+-- 6. Re-create all indexes
+CREATE INDEX idx_execution_processes_run_reason
+        ON execution_processes(run_reason);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+        ON execution_processes (session_id, status, run_reason);
+
+CREATE INDEX idx_execution_processes_session_run_reason_created
+        ON execution_processes (session_id, run_reason, created_at DESC);
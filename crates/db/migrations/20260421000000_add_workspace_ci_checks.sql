@@ -0,0 +1,12 @@
+-- CI check states of workspace branches, reported by CI webhooks. One row per
+-- workspace and check name; a report for a new head commit drops the rows of
+-- older commits.
+CREATE TABLE workspace_ci_checks (
+    workspace_id BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name         TEXT NOT NULL,
+    state        TEXT NOT NULL CHECK (state IN ('pending', 'success', 'failure')),
+    head_sha     TEXT,
+    url          TEXT,
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (workspace_id, name)
+);
@@ -0,0 +1,30 @@
+-- The issue tracker a repo's work is planned in, for importing issues as
+-- workspaces. `project` is the GitHub `owner/name`, the Linear team key or the
+-- Jira project key. The token is encrypted with the config secrets key when
+-- one is available.
+CREATE TABLE repo_issue_trackers (
+    repo_id        BLOB PRIMARY KEY REFERENCES repos(id) ON DELETE CASCADE,
+    provider       TEXT NOT NULL CHECK (provider IN ('github', 'linear', 'jira')),
+    project        TEXT NOT NULL CHECK (project <> ''),
+    base_url       TEXT,
+    username       TEXT,
+    token          TEXT,
+    close_on_merge INTEGER NOT NULL DEFAULT 0,
+    updated_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- Issues imported as workspaces, so merged work can close them.
+CREATE TABLE workspace_issue_links (
+    id           BLOB PRIMARY KEY,
+    workspace_id BLOB NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    repo_id      BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    provider     TEXT NOT NULL CHECK (provider IN ('github', 'linear', 'jira')),
+    -- Number or key shown in the tracker, e.g. `42` or `ENG-42`
+    issue_key    TEXT NOT NULL,
+    title        TEXT NOT NULL,
+    url          TEXT NOT NULL,
+    closed_at    TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_workspace_issue_links_workspace_id ON workspace_issue_links(workspace_id);
@@ -0,0 +1,68 @@
+-- Documentation sources belong to a project rather than a single repo, so
+-- every workspace of the project searches the same corpus. Sources move to
+-- the project their repo belongs to; sources of repos outside any project are
+-- dropped. Relative local paths were resolved against the repo, so they are
+-- made absolute on the way.
+
+-- sqlx workaround: end auto-transaction to allow PRAGMA to take effect
+COMMIT;
+
+PRAGMA foreign_keys = OFF;
+
+BEGIN TRANSACTION;
+
+CREATE TABLE doc_sources_new (
+    id                       BLOB PRIMARY KEY,
+    project_id               BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    name                     TEXT NOT NULL,
+    kind                     TEXT NOT NULL CHECK (kind IN ('url', 'local_folder', 'openapi')),
+    location                 TEXT NOT NULL,
+    refresh_interval_minutes INTEGER,
+    last_indexed_at          TEXT,
+    last_error               TEXT,
+    created_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+INSERT INTO doc_sources_new (id, project_id, name, kind, location, refresh_interval_minutes,
+    last_indexed_at, last_error, created_at, updated_at)
+SELECT ds.id,
+       (SELECT pr.project_id FROM project_repos pr
+        WHERE pr.repo_id = ds.repo_id
+        ORDER BY pr.project_id
+        LIMIT 1),
+       ds.name,
+       ds.kind,
+       CASE
+           WHEN ds.kind = 'url'
+                OR ds.location LIKE 'http://%'
+                OR ds.location LIKE 'https://%'
+                OR ds.location LIKE '/%'
+                OR ds.location LIKE '_:%' THEN ds.location
+           ELSE rtrim(r.path, '/') || '/' || ds.location
+       END,
+       ds.refresh_interval_minutes,
+       ds.last_indexed_at,
+       ds.last_error,
+       ds.created_at,
+       ds.updated_at
+FROM doc_sources ds
+JOIN repos r ON r.id = ds.repo_id
+WHERE EXISTS (SELECT 1 FROM project_repos pr WHERE pr.repo_id = ds.repo_id);
+
+DELETE FROM doc_chunks WHERE source_id NOT IN (SELECT id FROM doc_sources_new);
+
+DROP TABLE doc_sources;
+ALTER TABLE doc_sources_new RENAME TO doc_sources;
+
+CREATE INDEX idx_doc_sources_project_id ON doc_sources(project_id);
+
+-- Verify foreign key constraints before committing
+PRAGMA foreign_key_check;
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
+
+-- sqlx workaround: start empty transaction for sqlx to close gracefully
+BEGIN TRANSACTION;
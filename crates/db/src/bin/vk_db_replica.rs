@@ -0,0 +1,117 @@
+//! Inspect and restore the database replica written when `VK_DB_REPLICA` is
+//! set. Stop Vibe Kanban before restoring over the live database.
+
+use std::{path::PathBuf, process::ExitCode};
+
+use chrono::{DateTime, Utc};
+use db::replication::{self, REPLICA_ENV, ReplicaTarget};
+
+const USAGE: &str = "\
+Usage:
+  vk-db-replica list [--replica <target>]
+  vk-db-replica restore [--replica <target>] [--at <time>] [--output <path>] [--force]
+
+The replica target defaults to $VK_DB_REPLICA: a directory or s3://bucket/prefix.
+--at takes an RFC 3339 time, e.g. 2026-01-31T14:05:00Z; the latest state is
+restored without it. --output defaults to the live database, which is only
+replaced with --force.";
+
+struct Args {
+    command: String,
+    replica: Option<String>,
+    at: Option<DateTime<Utc>>,
+    output: Option<PathBuf>,
+    force: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().ok_or("missing command")?;
+    let mut parsed = Args {
+        command,
+        replica: None,
+        at: None,
+        output: None,
+        force: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--replica" => parsed.replica = Some(value()?),
+            "--at" => {
+                let at = value()?;
+                let at = DateTime::parse_from_rfc3339(&at)
+                    .map_err(|e| format!("invalid time '{at}': {e}"))?;
+                parsed.at = Some(at.with_timezone(&Utc));
+            }
+            "--output" => parsed.output = Some(utils::path::expand_tilde(&value()?)),
+            "--force" => parsed.force = true,
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(parsed)
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let target: ReplicaTarget = match args.replica.or_else(|| std::env::var(REPLICA_ENV).ok()) {
+        Some(value) => value.parse().map_err(|e| format!("{e}"))?,
+        None => return Err(format!("no replica given and {REPLICA_ENV} is not set")),
+    };
+
+    match args.command.as_str() {
+        "list" => {
+            let generations = replication::list_generations(&target)
+                .await
+                .map_err(|e| e.to_string())?;
+            if generations.is_empty() {
+                println!("No generations in {target:?}");
+            }
+            for generation in generations {
+                println!(
+                    "{}  {} .. {}  {} WAL segments",
+                    generation.info.id,
+                    generation.info.created_at.to_rfc3339(),
+                    generation.latest.to_rfc3339(),
+                    generation.segments
+                );
+            }
+            Ok(())
+        }
+        "restore" => {
+            let output = args.output.unwrap_or_else(db::database_path);
+            if output.exists() && !args.force {
+                return Err(format!(
+                    "{} exists; pass --force to replace it (stop Vibe Kanban first)",
+                    output.display()
+                ));
+            }
+            let report = replication::restore(&target, args.at, &output)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!(
+                "Restored {} to {} from generation {} ({} WAL segments)",
+                output.display(),
+                report.restored_to.to_rfc3339(),
+                report.generation,
+                report.segments_applied
+            );
+            Ok(())
+        }
+        command => Err(format!("unknown command '{command}'")),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match parse_args() {
+        Ok(args) => run(args).await,
+        Err(e) => Err(format!("{e}\n\n{USAGE}")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,13 +1,48 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
 
 use sqlx::{
-    ConnectOptions, Error, Pool, Sqlite, SqlitePool,
+    ConnectOptions, Error, Pool, Sqlite,
     migrate::MigrateError,
     sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions},
 };
 use utils::assets::asset_dir;
 
 pub mod models;
+pub mod replication;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+/// Path of the live database file.
+pub fn database_path() -> PathBuf {
+    asset_dir().join("db.v2.sqlite")
+}
+
+/// Options shared by every connection to the database. With a replica
+/// configured the database runs in WAL mode, see [`replication`].
+pub(crate) fn connect_options() -> Result<SqliteConnectOptions, Error> {
+    let database_url = format!("sqlite://{}", database_path().to_string_lossy());
+    let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
+    Ok(if replication::ReplicaTarget::configured().is_some() {
+        options.journal_mode(SqliteJournalMode::Wal)
+    } else {
+        options.journal_mode(SqliteJournalMode::Delete)
+    })
+}
+
+/// Options shared by every pool. With a replica configured, connections
+/// leave checkpointing to the replicator only while it is running.
+fn pool_options() -> SqlitePoolOptions {
+    let options = SqlitePoolOptions::new();
+    if replication::ReplicaTarget::configured().is_none() {
+        return options;
+    }
+    options.before_acquire(|conn, _meta| {
+        Box::pin(async move {
+            replication::apply_autocheckpoint(conn).await?;
+            Ok(true)
+        })
+    })
+}
 
 async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), Error> {
     use std::collections::HashSet;
@@ -74,28 +109,15 @@ pub struct DBService {
 
 impl DBService {
     pub async fn new() -> Result<DBService, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.v2.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
-        let pool = SqlitePool::connect_with(options).await?;
+        let options = connect_options()?;
+        let pool = pool_options().connect_with(options).await?;
         run_migrations(&pool).await?;
         Ok(DBService { pool })
     }
 
     pub async fn new_migration_pool() -> Result<Pool<Sqlite>, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.v2.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete)
-            .disable_statement_logging();
-        SqlitePoolOptions::new()
+        let options = connect_options()?.disable_statement_logging();
+        pool_options()
             .max_connections(64)
             .connect_with(options)
             .await
@@ -125,16 +147,10 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.v2.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
+        let options = connect_options()?;
 
         let pool = if let Some(hook) = after_connect {
-            SqlitePoolOptions::new()
+            pool_options()
                 .after_connect(move |conn, _meta| {
                     let hook = hook.clone();
                     Box::pin(async move {
@@ -145,7 +161,7 @@ impl DBService {
                 .connect_with(options)
                 .await?
         } else {
-            SqlitePool::connect_with(options).await?
+            pool_options().connect_with(options).await?
         };
 
         run_migrations(&pool).await?;
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq)]
+#[sqlx(type_name = "activity_event_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityEventKind {
+    WorkspaceMerged,
+    ApprovalApproved,
+    ApprovalDenied,
+    CodingAgentStarted,
+}
+
+/// One entry in the append-only activity history that productivity reports
+/// are built from. Holds no prompts, names or code.
+#[derive(Debug, Clone, FromRow)]
+pub struct ActivityEvent {
+    pub id: i64,
+    pub kind: ActivityEventKind,
+    pub workspace_id: Option<Uuid>,
+    pub executor: Option<String>,
+    pub duration_seconds: Option<i64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl ActivityEvent {
+    /// Record the first merge of a workspace; later merges of the same
+    /// workspace are ignored.
+    pub async fn record_workspace_merged(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        merged_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let created_at = sqlx::query_scalar!(
+            r#"SELECT created_at as "created_at!: DateTime<Utc>" FROM workspaces WHERE id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some(created_at) = created_at else {
+            return Ok(());
+        };
+        let duration_seconds = (merged_at - created_at).num_seconds().max(0);
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO activity_events
+                 (kind, workspace_id, duration_seconds, occurred_at)
+             VALUES ($1, $2, $3, $4)",
+            ActivityEventKind::WorkspaceMerged,
+            workspace_id,
+            duration_seconds,
+            merged_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Recorded against the workspace of the process that asked, for its
+    /// activity feed.
+    pub async fn record_approval(
+        pool: &SqlitePool,
+        approved: bool,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let kind = if approved {
+            ActivityEventKind::ApprovalApproved
+        } else {
+            ActivityEventKind::ApprovalDenied
+        };
+        let now = Utc::now();
+        sqlx::query!(
+            "INSERT INTO activity_events (kind, workspace_id, occurred_at)
+             VALUES ($1, (SELECT s.workspace_id FROM execution_processes ep
+                          JOIN sessions s ON s.id = ep.session_id
+                          WHERE ep.id = $2), $3)",
+            kind,
+            execution_process_id,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_coding_agent_started(
+        pool: &SqlitePool,
+        executor: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "INSERT INTO activity_events (kind, executor, occurred_at) VALUES ($1, $2, $3)",
+            ActivityEventKind::CodingAgentStarted,
+            executor,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Events recorded after `after_id`, oldest first.
+    pub async fn find_after(
+        pool: &SqlitePool,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ActivityEvent,
+            r#"SELECT id as "id!: i64",
+                      kind as "kind!: ActivityEventKind",
+                      workspace_id as "workspace_id: Uuid",
+                      executor,
+                      duration_seconds,
+                      occurred_at as "occurred_at!: DateTime<Utc>"
+               FROM activity_events
+               WHERE id > $1
+               ORDER BY id ASC
+               LIMIT $2"#,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
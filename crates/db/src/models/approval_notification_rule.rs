@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What kind of tool an approval is for, derived from the tool name.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "tool_category", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    Shell,
+    FileEdit,
+    FileRead,
+    Web,
+    Mcp,
+    /// Questions the agent asks the user.
+    Question,
+    Other,
+}
+
+/// Where an approval notification is sent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// OS push notification.
+    Desktop,
+    Sound,
+    /// Slack incoming webhook. `channel` overrides the webhook's default
+    /// channel, e.g. `#infra`.
+    Slack {
+        webhook_url: String,
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// JSON `POST` of the title, message and workspace id.
+    Webhook {
+        url: String,
+    },
+}
+
+/// Sends approval notifications matching the tool category and repo to the
+/// given channels instead of the configured defaults.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalNotificationRule {
+    pub id: Uuid,
+    /// Rules are evaluated in ascending position; the first match wins.
+    pub position: i64,
+    /// `None` matches any category.
+    pub tool_category: Option<ToolCategory>,
+    /// `None` matches any repo. Otherwise the workspace must include it.
+    pub repo_id: Option<Uuid>,
+    /// An empty list silences matching approvals.
+    #[ts(type = "Array<NotificationChannel>")]
+    pub channels: Json<Vec<NotificationChannel>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateApprovalNotificationRule {
+    #[serde(default)]
+    #[ts(optional)]
+    pub position: Option<i64>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub tool_category: Option<ToolCategory>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_id: Option<Uuid>,
+    pub channels: Vec<NotificationChannel>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct UpdateApprovalNotificationRule {
+    #[serde(default)]
+    #[ts(optional)]
+    pub position: Option<i64>,
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "ToolCategory | null")]
+    pub tool_category: Option<Option<ToolCategory>>,
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "string | null")]
+    pub repo_id: Option<Option<Uuid>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub channels: Option<Vec<NotificationChannel>>,
+}
+
+impl ApprovalNotificationRule {
+    /// All rules in evaluation order.
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalNotificationRule,
+            r#"SELECT id as "id!: Uuid",
+                      position as "position!: i64",
+                      tool_category as "tool_category: ToolCategory",
+                      repo_id as "repo_id: Uuid",
+                      channels as "channels!: Json<Vec<NotificationChannel>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM approval_notification_rules
+               ORDER BY position ASC, created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalNotificationRule,
+            r#"SELECT id as "id!: Uuid",
+                      position as "position!: i64",
+                      tool_category as "tool_category: ToolCategory",
+                      repo_id as "repo_id: Uuid",
+                      channels as "channels!: Json<Vec<NotificationChannel>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM approval_notification_rules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Create a rule. Without a position it is evaluated after all others.
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateApprovalNotificationRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let channels = Json(&data.channels);
+        sqlx::query_as!(
+            ApprovalNotificationRule,
+            r#"INSERT INTO approval_notification_rules
+                   (id, position, tool_category, repo_id, channels)
+               VALUES ($1, COALESCE($2, (SELECT COALESCE(MAX(position) + 1, 0)
+                                         FROM approval_notification_rules)), $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         position as "position!: i64",
+                         tool_category as "tool_category: ToolCategory",
+                         repo_id as "repo_id: Uuid",
+                         channels as "channels!: Json<Vec<NotificationChannel>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.position,
+            data.tool_category,
+            data.repo_id,
+            channels
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateApprovalNotificationRule,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let position = data.position.unwrap_or(existing.position);
+        let tool_category = data.tool_category.unwrap_or(existing.tool_category);
+        let repo_id = data.repo_id.unwrap_or(existing.repo_id);
+        let channels = data.channels.clone().map(Json).unwrap_or(existing.channels);
+
+        sqlx::query_as!(
+            ApprovalNotificationRule,
+            r#"UPDATE approval_notification_rules
+               SET position = $1, tool_category = $2, repo_id = $3, channels = $4,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $5
+               RETURNING id as "id!: Uuid",
+                         position as "position!: i64",
+                         tool_category as "tool_category: ToolCategory",
+                         repo_id as "repo_id: Uuid",
+                         channels as "channels!: Json<Vec<NotificationChannel>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            position,
+            tool_category,
+            repo_id,
+            channels,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM approval_notification_rules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
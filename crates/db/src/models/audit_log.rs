@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    WorkspaceDeleted,
+    ProcessKilled,
+    BranchForcePushed,
+    /// Approval notification rules or executor profiles changed.
+    ApprovalPolicyChanged,
+    RelayClientRemoved,
+}
+
+/// `Relay` requests come from a paired client over the relay; everything
+/// else is made on this machine.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditActorKind {
+    Local,
+    Relay,
+}
+
+/// Who made a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditActor {
+    pub kind: AuditActorKind,
+    pub client_id: Option<Uuid>,
+    pub name: Option<String>,
+}
+
+impl AuditActor {
+    pub fn local() -> Self {
+        Self {
+            kind: AuditActorKind::Local,
+            client_id: None,
+            name: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub action: AuditAction,
+    pub actor_kind: AuditActorKind,
+    /// Paired relay client that made the request, if known.
+    pub actor_client_id: Option<Uuid>,
+    /// Name of the paired relay client.
+    pub actor_name: Option<String>,
+    /// Kind of thing acted on, e.g. `workspace` or `execution_process`.
+    pub target_type: String,
+    pub target_id: Option<String>,
+    #[ts(type = "JsonValue")]
+    pub details: Json<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateAuditLogEntry {
+    pub actor: AuditActor,
+    pub action: AuditAction,
+    pub target_type: &'static str,
+    pub target_id: Option<String>,
+    pub details: serde_json::Value,
+}
+
+/// Filters for listing audit log entries. All are optional and combined.
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct AuditLogQuery {
+    #[serde(default)]
+    #[ts(optional)]
+    pub action: Option<AuditAction>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub actor_kind: Option<AuditActorKind>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub actor_client_id: Option<Uuid>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub target_id: Option<String>,
+    /// Only entries at or after this time.
+    #[serde(default)]
+    #[ts(optional)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time, for paging back from the last entry
+    /// of the previous page.
+    #[serde(default)]
+    #[ts(optional)]
+    pub before: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub limit: Option<u32>,
+}
+
+impl AuditLogEntry {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateAuditLogEntry,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let details = Json(&data.details);
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"INSERT INTO audit_log
+                   (id, action, actor_kind, actor_client_id, actor_name, target_type, target_id,
+                    details)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid",
+                         action as "action!: AuditAction",
+                         actor_kind as "actor_kind!: AuditActorKind",
+                         actor_client_id as "actor_client_id: Uuid",
+                         actor_name,
+                         target_type,
+                         target_id,
+                         details as "details!: Json<serde_json::Value>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.action,
+            data.actor.kind,
+            data.actor.client_id,
+            data.actor.name,
+            data.target_type,
+            data.target_id,
+            details
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Matching entries, newest first, at most `limit`.
+    pub async fn find(
+        pool: &SqlitePool,
+        query: &AuditLogQuery,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"SELECT id as "id!: Uuid",
+                      action as "action!: AuditAction",
+                      actor_kind as "actor_kind!: AuditActorKind",
+                      actor_client_id as "actor_client_id: Uuid",
+                      actor_name,
+                      target_type,
+                      target_id,
+                      details as "details!: Json<serde_json::Value>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM audit_log
+               WHERE ($1 IS NULL OR action = $1)
+                 AND ($2 IS NULL OR actor_kind = $2)
+                 AND ($3 IS NULL OR actor_client_id = $3)
+                 AND ($4 IS NULL OR target_id = $4)
+                 AND ($5 IS NULL OR created_at >= datetime($5, 'subsec'))
+                 AND ($6 IS NULL OR created_at < datetime($6, 'subsec'))
+               ORDER BY created_at DESC
+               LIMIT $7"#,
+            query.action,
+            query.actor_kind,
+            query.actor_client_id,
+            query.target_id,
+            query.since,
+            query.before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
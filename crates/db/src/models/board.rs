@@ -0,0 +1,453 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Sqlite, SqlitePool, Transaction};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A kanban board. Workspaces are placed on it as cards in ordered columns.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Board {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct BoardColumn {
+    pub id: Uuid,
+    pub board_id: Uuid,
+    pub name: String,
+    /// Columns are shown in ascending position, starting at 0.
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A workspace's place on a board.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct BoardCard {
+    pub board_id: Uuid,
+    pub workspace_id: Uuid,
+    pub column_id: Uuid,
+    /// Position within the column, starting at 0.
+    pub position: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Everything needed to render a board.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardState {
+    pub board: Board,
+    pub columns: Vec<BoardColumn>,
+    pub cards: Vec<BoardCard>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateBoard {
+    pub name: String,
+    /// Names of the columns to create, in order.
+    #[serde(default)]
+    #[ts(optional)]
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateBoard {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateBoardColumn {
+    pub name: String,
+    /// Where to insert the column. Defaults to the end.
+    #[serde(default)]
+    #[ts(optional)]
+    pub position: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateBoardColumn {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ReorderBoardColumns {
+    /// Every column of the board, in the new order.
+    pub column_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct MoveBoardCard {
+    pub column_id: Uuid,
+    /// Where to insert the card in the column. Defaults to the end.
+    #[serde(default)]
+    #[ts(optional)]
+    pub position: Option<i64>,
+}
+
+impl Board {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Board,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM boards
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Board,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM boards
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Create a board with the given columns in order.
+    pub async fn create(pool: &SqlitePool, data: &CreateBoard) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let id = Uuid::new_v4();
+        let board = sqlx::query_as!(
+            Board,
+            r#"INSERT INTO boards (id, name)
+               VALUES ($1, $2)
+               RETURNING id as "id!: Uuid",
+                         name,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (position, name) in data.columns.iter().flatten().enumerate() {
+            let column_id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                "INSERT INTO board_columns (id, board_id, name, position) VALUES ($1, $2, $3, $4)",
+                column_id,
+                board.id,
+                name,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(board)
+    }
+
+    pub async fn update(pool: &SqlitePool, id: Uuid, name: &str) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Board,
+            r#"UPDATE boards SET name = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2
+               RETURNING id as "id!: Uuid",
+                         name,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            name,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM boards WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl BoardState {
+    pub async fn load(pool: &SqlitePool, board_id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let Some(board) = Board::find_by_id(pool, board_id).await? else {
+            return Ok(None);
+        };
+        let columns = BoardColumn::find_by_board_id(pool, board_id).await?;
+        let cards = BoardCard::find_by_board_id(pool, board_id).await?;
+        Ok(Some(Self {
+            board,
+            columns,
+            cards,
+        }))
+    }
+}
+
+impl BoardColumn {
+    pub async fn find_by_board_id(
+        pool: &SqlitePool,
+        board_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardColumn,
+            r#"SELECT id as "id!: Uuid",
+                      board_id as "board_id!: Uuid",
+                      name,
+                      position,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM board_columns
+               WHERE board_id = $1
+               ORDER BY position ASC"#,
+            board_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardColumn,
+            r#"SELECT id as "id!: Uuid",
+                      board_id as "board_id!: Uuid",
+                      name,
+                      position,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM board_columns
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Insert a column at `position`, clamped to the end of the board, moving
+    /// later columns one to the right.
+    pub async fn create(
+        pool: &SqlitePool,
+        board_id: Uuid,
+        data: &CreateBoardColumn,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM board_columns WHERE board_id = $1"#,
+            board_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let position = data.position.map_or(count, |p| p.clamp(0, count));
+
+        sqlx::query!(
+            "UPDATE board_columns SET position = position + 1
+             WHERE board_id = $1 AND position >= $2",
+            board_id,
+            position
+        )
+        .execute(&mut *tx)
+        .await?;
+        let id = Uuid::new_v4();
+        let column = sqlx::query_as!(
+            BoardColumn,
+            r#"INSERT INTO board_columns (id, board_id, name, position)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         board_id as "board_id!: Uuid",
+                         name,
+                         position,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            board_id,
+            data.name,
+            position
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(column)
+    }
+
+    pub async fn update(pool: &SqlitePool, id: Uuid, name: &str) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            BoardColumn,
+            r#"UPDATE board_columns SET name = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2
+               RETURNING id as "id!: Uuid",
+                         board_id as "board_id!: Uuid",
+                         name,
+                         position,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            name,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Delete a column and the cards in it, closing the gap it leaves.
+    pub async fn delete(pool: &SqlitePool, column: &BoardColumn) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query!("DELETE FROM board_columns WHERE id = $1", column.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "UPDATE board_columns SET position = position - 1
+             WHERE board_id = $1 AND position > $2",
+            column.board_id,
+            column.position
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Give the columns positions in the order of `column_ids`, which must
+    /// list every column of the board exactly once.
+    pub async fn reorder(
+        pool: &SqlitePool,
+        board_id: Uuid,
+        column_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (position, column_id) in column_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE board_columns SET position = $1, updated_at = datetime('now', 'subsec')
+                 WHERE id = $2 AND board_id = $3",
+                position,
+                column_id,
+                board_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+}
+
+impl BoardCard {
+    pub async fn find_by_board_id(
+        pool: &SqlitePool,
+        board_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardCard,
+            r#"SELECT board_id as "board_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      column_id as "column_id!: Uuid",
+                      position,
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM board_cards
+               WHERE board_id = $1
+               ORDER BY column_id, position ASC"#,
+            board_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Place a workspace in a column at `position`, clamped to the end of the
+    /// column, taking it out of the column it was in before.
+    pub async fn move_to(
+        pool: &SqlitePool,
+        board_id: Uuid,
+        workspace_id: Uuid,
+        data: &MoveBoardCard,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        Self::take_out(&mut tx, board_id, workspace_id).await?;
+
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM board_cards WHERE column_id = $1"#,
+            data.column_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let position = data.position.map_or(count, |p| p.clamp(0, count));
+
+        sqlx::query!(
+            "UPDATE board_cards SET position = position + 1
+             WHERE column_id = $1 AND position >= $2",
+            data.column_id,
+            position
+        )
+        .execute(&mut *tx)
+        .await?;
+        let card = sqlx::query_as!(
+            BoardCard,
+            r#"INSERT INTO board_cards (board_id, workspace_id, column_id, position)
+               VALUES ($1, $2, $3, $4)
+               RETURNING board_id as "board_id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         column_id as "column_id!: Uuid",
+                         position,
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            board_id,
+            workspace_id,
+            data.column_id,
+            position
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(card)
+    }
+
+    pub async fn remove(
+        pool: &SqlitePool,
+        board_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let removed = Self::take_out(&mut tx, board_id, workspace_id).await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Delete the card and close the gap in its column.
+    async fn take_out(
+        tx: &mut Transaction<'_, Sqlite>,
+        board_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let existing = sqlx::query_as!(
+            BoardCard,
+            r#"DELETE FROM board_cards WHERE board_id = $1 AND workspace_id = $2
+               RETURNING board_id as "board_id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         column_id as "column_id!: Uuid",
+                         position,
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            board_id,
+            workspace_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        let Some(existing) = existing else {
+            return Ok(0);
+        };
+
+        sqlx::query!(
+            "UPDATE board_cards SET position = position - 1
+             WHERE column_id = $1 AND position > $2",
+            existing.column_id,
+            existing.position
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(1)
+    }
+}
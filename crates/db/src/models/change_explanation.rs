@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct FileRationale {
+    pub path: String,
+    pub rationale: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct RiskCallout {
+    pub path: Option<String>,
+    pub severity: RiskSeverity,
+    pub description: String,
+}
+
+/// The structured explanation an agent returns for a commit range.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct ChangeExplanationContent {
+    pub summary: String,
+    #[serde(default)]
+    pub files: Vec<FileRationale>,
+    #[serde(default)]
+    pub risks: Vec<RiskCallout>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ChangeExplanation {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub base_commit: String,
+    pub head_commit: String,
+    pub execution_process_id: Option<Uuid>,
+    /// Set once the summarization run has finished and its output parsed.
+    #[ts(type = "ChangeExplanationContent | null")]
+    pub explanation: Option<Json<ChangeExplanationContent>>,
+    /// Why no explanation could be produced, e.g. the agent's output wasn't
+    /// valid JSON.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateChangeExplanation {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub base_commit: String,
+    pub head_commit: String,
+    pub execution_process_id: Uuid,
+}
+
+impl ChangeExplanation {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ChangeExplanation,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      base_commit,
+                      head_commit,
+                      execution_process_id as "execution_process_id: Uuid",
+                      explanation as "explanation: Json<ChangeExplanationContent>",
+                      error,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM change_explanations
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_commits(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        base_commit: &str,
+        head_commit: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ChangeExplanation,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      base_commit,
+                      head_commit,
+                      execution_process_id as "execution_process_id: Uuid",
+                      explanation as "explanation: Json<ChangeExplanationContent>",
+                      error,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM change_explanations
+               WHERE repo_id = $1 AND base_commit = $2 AND head_commit = $3"#,
+            repo_id,
+            base_commit,
+            head_commit
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// How many explanations were requested for a workspace since `since`.
+    pub async fn count_for_workspace_since(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM change_explanations
+               WHERE workspace_id = $1 AND created_at >= datetime($2)"#,
+            workspace_id,
+            since
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateChangeExplanation,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ChangeExplanation,
+            r#"INSERT INTO change_explanations
+                   (id, workspace_id, repo_id, base_commit, head_commit, execution_process_id)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         base_commit,
+                         head_commit,
+                         execution_process_id as "execution_process_id: Uuid",
+                         explanation as "explanation: Json<ChangeExplanationContent>",
+                         error,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.repo_id,
+            data.base_commit,
+            data.head_commit,
+            data.execution_process_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the outcome of the summarization run.
+    pub async fn complete(
+        pool: &SqlitePool,
+        id: Uuid,
+        outcome: Result<&ChangeExplanationContent, &str>,
+    ) -> Result<Self, sqlx::Error> {
+        let (explanation, error) = match outcome {
+            Ok(content) => (Some(Json(content)), None),
+            Err(error) => (None, Some(error)),
+        };
+        sqlx::query_as!(
+            ChangeExplanation,
+            r#"UPDATE change_explanations
+               SET explanation = $1, error = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $3
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         base_commit,
+                         head_commit,
+                         execution_process_id as "execution_process_id: Uuid",
+                         explanation as "explanation: Json<ChangeExplanationContent>",
+                         error,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            explanation,
+            error,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Reset a failed explanation for a new summarization run.
+    pub async fn restart(
+        pool: &SqlitePool,
+        id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ChangeExplanation,
+            r#"UPDATE change_explanations
+               SET execution_process_id = $1, explanation = NULL, error = NULL,
+                   created_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec')
+               WHERE id = $2
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         base_commit,
+                         head_commit,
+                         execution_process_id as "execution_process_id: Uuid",
+                         explanation as "explanation: Json<ChangeExplanationContent>",
+                         error,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            execution_process_id,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
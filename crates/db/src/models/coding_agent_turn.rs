@@ -232,6 +232,27 @@ impl CodingAgentTurn {
         Ok(())
     }
 
+    /// All coding agent turns across a workspace's sessions, oldest first.
+    /// Dropped turns (discarded by a restore) are excluded.
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, CodingAgentTurn>(
+            r#"SELECT cat.id, cat.execution_process_id, cat.agent_session_id,
+                      cat.agent_message_id, cat.prompt, cat.summary, cat.seen,
+                      cat.created_at, cat.updated_at
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = ? AND ep.dropped = FALSE
+               ORDER BY cat.created_at ASC"#,
+        )
+        .bind(workspace_id)
+        .fetch_all(pool)
+        .await
+    }
+
     /// Check if a workspace has any unseen coding agent turns
     /// Find all workspaces that have unseen coding agent turns, filtered by archived status
     pub async fn find_workspaces_with_unseen(
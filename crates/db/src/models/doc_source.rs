@@ -0,0 +1,232 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DocSourceKind {
+    Url,
+    LocalFolder,
+    Openapi,
+}
+
+/// A documentation source registered against a project. Its content is
+/// fetched and split into [`DocChunk`]s by the doc index service.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct DocSource {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub kind: DocSourceKind,
+    pub location: String,
+    /// How often the source is re-indexed. `None` means manual refresh only.
+    pub refresh_interval_minutes: Option<i64>,
+    pub last_indexed_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateDocSource {
+    pub name: String,
+    pub kind: DocSourceKind,
+    /// A URL, or an absolute path for local sources.
+    pub location: String,
+    pub refresh_interval_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct DocChunk {
+    pub id: Uuid,
+    pub source_id: Uuid,
+    pub ordinal: i64,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DocSource {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DocSource,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      kind as "kind!: DocSourceKind",
+                      location,
+                      refresh_interval_minutes,
+                      last_indexed_at as "last_indexed_at: DateTime<Utc>",
+                      last_error,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM doc_sources
+               WHERE project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DocSource,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      kind as "kind!: DocSourceKind",
+                      location,
+                      refresh_interval_minutes,
+                      last_indexed_at as "last_indexed_at: DateTime<Utc>",
+                      last_error,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM doc_sources
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Sources with a refresh interval whose last index is older than that
+    /// interval (or that have never been indexed).
+    pub async fn find_due_for_refresh(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DocSource,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      kind as "kind!: DocSourceKind",
+                      location,
+                      refresh_interval_minutes,
+                      last_indexed_at as "last_indexed_at: DateTime<Utc>",
+                      last_error,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM doc_sources
+               WHERE refresh_interval_minutes IS NOT NULL
+                 AND (last_indexed_at IS NULL
+                      OR datetime(last_indexed_at, '+' || refresh_interval_minutes || ' minutes')
+                         <= datetime('now'))"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateDocSource,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            DocSource,
+            r#"INSERT INTO doc_sources (id, project_id, name, kind, location, refresh_interval_minutes)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         kind as "kind!: DocSourceKind",
+                         location,
+                         refresh_interval_minutes,
+                         last_indexed_at as "last_indexed_at: DateTime<Utc>",
+                         last_error,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.kind,
+            data.location,
+            data.refresh_interval_minutes
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_indexed(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE doc_sources
+             SET last_indexed_at = datetime('now', 'subsec'),
+                 last_error = $1,
+                 updated_at = datetime('now', 'subsec')
+             WHERE id = $2",
+            error,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM doc_sources WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl DocChunk {
+    /// Replace every chunk of a source in a single transaction.
+    pub async fn replace_for_source(
+        pool: &SqlitePool,
+        source_id: Uuid,
+        chunks: &[(String, String)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM doc_chunks WHERE source_id = $1", source_id)
+            .execute(&mut *tx)
+            .await?;
+        for (ordinal, (title, content)) in chunks.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let ordinal = ordinal as i64;
+            sqlx::query!(
+                "INSERT INTO doc_chunks (id, source_id, ordinal, title, content)
+                 VALUES ($1, $2, $3, $4, $5)",
+                id,
+                source_id,
+                ordinal,
+                title,
+                content
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DocChunk,
+            r#"SELECT c.id as "id!: Uuid",
+                      c.source_id as "source_id!: Uuid",
+                      c.ordinal,
+                      c.title,
+                      c.content,
+                      c.created_at as "created_at!: DateTime<Utc>"
+               FROM doc_chunks c
+               JOIN doc_sources s ON s.id = c.source_id
+               WHERE s.project_id = $1
+               ORDER BY c.source_id, c.ordinal"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::execution_process::ExecutionProcessRunReason;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single log line to be written to the full-text index.
+#[derive(Debug, Clone)]
+pub struct IndexedLogLine {
+    pub line_number: i64,
+    pub stream: LogStream,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct LogSearchHit {
+    pub execution_process_id: Uuid,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub line_number: i64,
+    pub stream: LogStream,
+    /// Excerpt of the matching line with matches wrapped in `**`.
+    pub snippet: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UnindexedExecution {
+    pub execution_id: Uuid,
+    pub session_id: Uuid,
+}
+
+pub struct ExecutionLogIndex;
+
+impl ExecutionLogIndex {
+    /// Replace everything indexed for an execution process with `lines`.
+    pub async fn replace_for_execution(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+        lines: &[IndexedLogLine],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM execution_log_fts WHERE execution_id = $1",
+            execution_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for line in lines {
+            sqlx::query!(
+                "INSERT INTO execution_log_fts (content, execution_id, line_number, stream)
+                 VALUES ($1, $2, $3, $4)",
+                line.content,
+                execution_id,
+                line.line_number,
+                line.stream
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let line_count = lines.len() as i64;
+        sqlx::query!(
+            "INSERT INTO execution_log_index_state (execution_id, line_count)
+             VALUES ($1, $2)
+             ON CONFLICT(execution_id) DO UPDATE SET
+                line_count = excluded.line_count,
+                indexed_at = datetime('now', 'subsec')",
+            execution_id,
+            line_count
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Finished execution processes whose logs have not been indexed yet,
+    /// newest first.
+    pub async fn find_unindexed(
+        pool: &SqlitePool,
+        limit: i64,
+    ) -> Result<Vec<UnindexedExecution>, sqlx::Error> {
+        sqlx::query_as!(
+            UnindexedExecution,
+            r#"SELECT ep.id as "execution_id!: Uuid", ep.session_id as "session_id!: Uuid"
+               FROM execution_processes ep
+               LEFT JOIN execution_log_index_state s ON s.execution_id = ep.id
+               WHERE s.execution_id IS NULL AND ep.status != 'running'
+               ORDER BY ep.created_at DESC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Run an FTS5 `MATCH` query, best matches first. `fts_query` must
+    /// already be valid FTS5 syntax.
+    pub async fn search(
+        pool: &SqlitePool,
+        fts_query: &str,
+        workspace_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<LogSearchHit>, sqlx::Error> {
+        sqlx::query_as!(
+            LogSearchHit,
+            r#"SELECT ep.id as "execution_process_id!: Uuid",
+                      ep.session_id as "session_id!: Uuid",
+                      s.workspace_id as "workspace_id!: Uuid",
+                      ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                      f.line_number as "line_number!: i64",
+                      f.stream as "stream!: LogStream",
+                      snippet(execution_log_fts, 0, '**', '**', '…', 24) as "snippet!: String",
+                      ep.started_at as "started_at!: DateTime<Utc>"
+               FROM execution_log_fts f
+               JOIN execution_processes ep ON ep.id = f.execution_id
+               JOIN sessions s ON s.id = ep.session_id
+               WHERE execution_log_fts MATCH $1
+                 AND ($2 IS NULL OR s.workspace_id = $2)
+               ORDER BY bm25(execution_log_fts), ep.started_at DESC, f.line_number ASC
+               LIMIT $3"#,
+            fts_query,
+            workspace_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
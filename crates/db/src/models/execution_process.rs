@@ -13,6 +13,7 @@ use ts_rs::TS;
 use uuid::Uuid;
 
 use super::{
+    activity_event::ActivityEvent,
     execution_process_repo_state::{CreateExecutionProcessRepoState, ExecutionProcessRepoState},
     repo::Repo,
     session::Session,
@@ -45,6 +46,11 @@ pub enum ExecutionProcessStatus {
     Completed,
     Failed,
     Killed,
+    /// Killed after exceeding its wall-clock or idle-output timeout.
+    TimedOut,
+    /// Stopped by a server shutdown after its grace period; resume it with a
+    /// follow-up.
+    Interrupted,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -56,6 +62,11 @@ pub enum ExecutionProcessRunReason {
     ArchiveScript,
     CodingAgent,
     DevServer,
+    RepoCommand,
+    CheckCommand,
+    /// Summarizes a commit range for a reviewer in a fresh agent session.
+    /// Only reads the worktree; nothing it leaves behind is committed.
+    ChangeExplanation,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -249,6 +260,19 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Number of running processes per run reason as stored, e.g.
+    /// `codingagent`; reasons with none are left out.
+    pub async fn count_running_by_run_reason(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (String, i64)>(
+            "SELECT run_reason, COUNT(*) FROM execution_processes
+             WHERE status = 'running' GROUP BY run_reason",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find running execution processes
     pub async fn find_running(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -271,6 +295,28 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Find execution processes that are no longer running, oldest first
+    pub async fn find_finished(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT
+                    ep.id as "id!: Uuid",
+                    ep.session_id as "session_id!: Uuid",
+                    ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                    ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                    ep.status as "status!: ExecutionProcessStatus",
+                    ep.exit_code,
+                    ep.dropped as "dropped!: bool",
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.completed_at as "completed_at?: DateTime<Utc>",
+                    ep.created_at as "created_at!: DateTime<Utc>",
+                    ep.updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes ep WHERE ep.status != 'running' ORDER BY ep.created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Check if there's a running coding agent process for a session
     pub async fn has_running_coding_agent_for_session(
         pool: &SqlitePool,
@@ -410,6 +456,10 @@ impl ExecutionProcess {
 
         ExecutionProcessRepoState::create_many(pool, process_id, repo_states).await?;
 
+        if let Some(executor) = data.executor_action.base_executor() {
+            ActivityEvent::record_coding_agent_started(pool, &executor.to_string()).await?;
+        }
+
         Self::find_by_id(pool, process_id)
             .await?
             .ok_or(sqlx::Error::RowNotFound)
@@ -419,6 +469,8 @@ impl ExecutionProcess {
         if let Ok(exp_process) = Self::find_by_id(pool, id).await
             && exp_process.is_some_and(|ep| {
                 ep.status == ExecutionProcessStatus::Killed
+                    || ep.status == ExecutionProcessStatus::TimedOut
+                    || ep.status == ExecutionProcessStatus::Interrupted
                     || ep.status == ExecutionProcessStatus::Completed
             })
         {
@@ -553,14 +605,12 @@ impl ExecutionProcess {
         })
     }
 
-    /// Fetch the latest CodingAgent executor profile for a session.
-    /// Returns None if no CodingAgent execution process exists for this session.
-    pub async fn latest_executor_profile_for_session(
+    /// Find the latest non-dropped coding agent execution process of a session
+    pub async fn find_latest_coding_agent_for_session(
         pool: &SqlitePool,
         session_id: Uuid,
-    ) -> Result<Option<ExecutorProfileId>, ExecutionProcessError> {
-        // Find the latest CodingAgent execution process for this session
-        let latest_execution_process = sqlx::query_as!(
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT
                     ep.id as "id!: Uuid",
@@ -581,7 +631,17 @@ impl ExecutionProcess {
             ExecutionProcessRunReason::CodingAgent
         )
         .fetch_optional(pool)
-        .await?;
+        .await
+    }
+
+    /// Fetch the latest CodingAgent executor profile for a session.
+    /// Returns None if no CodingAgent execution process exists for this session.
+    pub async fn latest_executor_profile_for_session(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Option<ExecutorProfileId>, ExecutionProcessError> {
+        let latest_execution_process =
+            Self::find_latest_coding_agent_for_session(pool, session_id).await?;
 
         let Some(latest_execution_process) = latest_execution_process else {
             return Ok(None);
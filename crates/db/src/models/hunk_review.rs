@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum HunkReviewAction {
+    Keep,
+    Discard,
+}
+
+/// A reviewer's decision on one hunk of an agent's changes. The hunk's patch
+/// is stored so the decision still makes sense after the file changes again.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct HunkReviewDecision {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub execution_process_id: Option<Uuid>,
+    pub repo_id: Uuid,
+    pub file_path: String,
+    pub hunk_id: String,
+    pub action: HunkReviewAction,
+    pub patch: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateHunkReviewDecision {
+    pub workspace_id: Uuid,
+    pub execution_process_id: Option<Uuid>,
+    pub repo_id: Uuid,
+    pub file_path: String,
+    pub hunk_id: String,
+    pub action: HunkReviewAction,
+    pub patch: String,
+}
+
+impl HunkReviewDecision {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateHunkReviewDecision,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            HunkReviewDecision,
+            r#"INSERT INTO hunk_review_decisions
+                   (id, workspace_id, execution_process_id, repo_id, file_path, hunk_id, action, patch)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id: Uuid", repo_id as "repo_id!: Uuid", file_path, hunk_id, action as "action!: HunkReviewAction", patch, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.execution_process_id,
+            data.repo_id,
+            data.file_path,
+            data.hunk_id,
+            data.action,
+            data.patch
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            HunkReviewDecision,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id: Uuid", repo_id as "repo_id!: Uuid", file_path, hunk_id, action as "action!: HunkReviewAction", patch, created_at as "created_at!: DateTime<Utc>"
+               FROM hunk_review_decisions
+               WHERE workspace_id = $1
+               ORDER BY created_at ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
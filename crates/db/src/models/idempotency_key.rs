@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+/// Completed keys are kept this long, which bounds how late a client may
+/// retry a queued action.
+const RETENTION: &str = "-1 day";
+/// Keys still in flight after this long belong to a request that never
+/// finished, e.g. because the server restarted, and may be claimed again.
+const ABANDONED_AFTER: &str = "-10 minutes";
+
+/// A mutating request sent with an `Idempotency-Key` header and, once it
+/// finished, the response to replay for retries.
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyKey {
+    pub key: String,
+    /// Hash of the method, path and body, so a key reused for a different
+    /// request is rejected instead of replaying an unrelated response.
+    pub request_fingerprint: String,
+    /// `None` while the first request is still being handled.
+    pub status: Option<i64>,
+    pub content_type: Option<String>,
+    pub body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IdempotencyKey {
+    /// Claim `key` for a new request. Returns `None` when the key was free
+    /// and is now held by the caller, otherwise the existing record.
+    pub async fn claim(
+        pool: &SqlitePool,
+        key: &str,
+        request_fingerprint: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM idempotency_keys
+             WHERE created_at < datetime('now', $1)
+                OR (key = $2 AND status IS NULL AND created_at < datetime('now', $3))",
+            RETENTION,
+            key,
+            ABANDONED_AFTER
+        )
+        .execute(pool)
+        .await?;
+
+        let claimed = sqlx::query!(
+            "INSERT INTO idempotency_keys (key, request_fingerprint)
+             VALUES ($1, $2)
+             ON CONFLICT(key) DO NOTHING",
+            key,
+            request_fingerprint
+        )
+        .execute(pool)
+        .await?
+        .rows_affected()
+            == 1;
+        if claimed {
+            return Ok(None);
+        }
+
+        sqlx::query_as!(
+            IdempotencyKey,
+            r#"SELECT key as "key!",
+                      request_fingerprint,
+                      status,
+                      content_type,
+                      body,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM idempotency_keys
+               WHERE key = $1"#,
+            key
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store the response of a claimed key.
+    pub async fn complete(
+        pool: &SqlitePool,
+        key: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        let status = i64::from(status);
+        sqlx::query!(
+            "UPDATE idempotency_keys SET status = $1, content_type = $2, body = $3 WHERE key = $4",
+            status,
+            content_type,
+            body,
+            key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Give up a claimed key so a retry runs the request again.
+    pub async fn release(pool: &SqlitePool, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE key = $1 AND status IS NULL",
+            key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use sqlx::{SqlitePool, Type};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::pull_request::PullRequest;
+use super::{activity_event::ActivityEvent, pull_request::PullRequest};
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS, Type)]
 #[sqlx(type_name = "merge_status", rename_all = "snake_case")]
@@ -95,6 +95,8 @@ impl Merge {
         .execute(pool)
         .await?;
 
+        ActivityEvent::record_workspace_merged(pool, workspace_id, now).await?;
+
         Ok(DirectMerge {
             id,
             workspace_id,
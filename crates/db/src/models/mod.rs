@@ -1,16 +1,51 @@
+pub mod activity_event;
+pub mod approval_notification_rule;
+pub mod audit_log;
+pub mod board;
+pub mod change_explanation;
 pub mod coding_agent_turn;
+pub mod doc_source;
+pub mod execution_log_search;
 pub mod execution_process;
 pub mod execution_process_logs;
 pub mod execution_process_repo_state;
 pub mod file;
+pub mod hunk_review;
+pub mod idempotency_key;
 pub mod merge;
 pub mod project;
+pub mod project_execution_weight;
 pub mod pull_request;
+pub mod remote_execution;
 pub mod repo;
+pub mod repo_check_command;
+pub mod repo_container_image;
+pub mod repo_issue_tracker;
+pub mod repo_protected_path;
+pub mod repo_resource_limit;
+pub mod repo_resource_tag;
+pub mod repo_setup_cache;
+pub mod report_period;
 pub mod requests;
 pub mod scratch;
 pub mod session;
+pub mod session_plan;
+pub mod session_question;
+pub mod session_share_link;
+pub mod session_timeout;
 pub mod tag;
 pub mod task;
+pub mod task_template;
 pub mod workspace;
+pub mod workspace_activity;
+pub mod workspace_branch_freshness;
+pub mod workspace_check_result;
+pub mod workspace_child;
+pub mod workspace_ci_check;
+pub mod workspace_dev_server;
+pub mod workspace_env_var;
+pub mod workspace_handoff;
+pub mod workspace_issue_link;
 pub mod workspace_repo;
+pub mod workspace_repo_command;
+pub mod workspace_secret_finding;
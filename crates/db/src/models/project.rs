@@ -33,6 +33,13 @@ impl Project {
         .await
     }
 
+    pub async fn exists(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM projects WHERE id = ?)")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+    }
+
     pub async fn set_remote_project_id(
         pool: &SqlitePool,
         id: Uuid,
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Weight used when projects without an explicit row compete for capacity.
+pub const DEFAULT_EXECUTION_WEIGHT: u32 = 1;
+
+/// Share of the execution capacity a project receives relative to the other
+/// projects with queued executions.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectExecutionWeight {
+    pub project_id: Uuid,
+    pub weight: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectExecutionWeight {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectExecutionWeight,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      weight as "weight!: u32",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_execution_weights
+               ORDER BY updated_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The project's configured weight, or the default if none is set.
+    pub async fn weight_for(pool: &SqlitePool, project_id: Uuid) -> Result<u32, sqlx::Error> {
+        let weight = sqlx::query_scalar!(
+            r#"SELECT weight as "weight!: u32" FROM project_execution_weights WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(weight.unwrap_or(DEFAULT_EXECUTION_WEIGHT))
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        weight: u32,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectExecutionWeight,
+            r#"INSERT INTO project_execution_weights (project_id, weight)
+               VALUES ($1, $2)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   weight = excluded.weight,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid",
+                         weight as "weight!: u32",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            weight
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM project_execution_weights WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -4,7 +4,10 @@ use chrono::{DateTime, Utc};
 use sqlx::{FromRow, SqlitePool};
 use uuid::Uuid;
 
-use super::merge::{Merge, MergeStatus, PrMerge, PullRequestInfo};
+use super::{
+    activity_event::ActivityEvent,
+    merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+};
 
 #[derive(Debug, Clone, FromRow)]
 pub struct PullRequest {
@@ -123,6 +126,14 @@ impl PullRequest {
         )
         .execute(pool)
         .await?;
+
+        if let Some(merged_at) = merged_at
+            && let Some(workspace_id) = Self::find_by_url(pool, pr_url)
+                .await?
+                .and_then(|pr| pr.workspace_id)
+        {
+            ActivityEvent::record_workspace_merged(pool, workspace_id, merged_at).await?;
+        }
         Ok(())
     }
 
@@ -224,6 +235,19 @@ impl PullRequest {
         Ok(row.count)
     }
 
+    pub async fn count_merged_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(1) AS "count!: i64" FROM pull_requests WHERE workspace_id = ? AND pr_status = 'merged'"#,
+            workspace_id,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(row.count)
+    }
+
     pub async fn get_latest_for_workspaces(
         pool: &SqlitePool,
         archived: bool,
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A workspace started on a paired host from this instance. The remote ids
+/// refer to records on that host, reached through the relay.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RemoteExecution {
+    pub id: Uuid,
+    pub host_id: Uuid,
+    pub remote_workspace_id: Uuid,
+    /// The coding agent run the workspace was started with.
+    pub remote_execution_process_id: Uuid,
+    pub name: Option<String>,
+    pub prompt: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateRemoteExecution {
+    pub host_id: Uuid,
+    pub remote_workspace_id: Uuid,
+    pub remote_execution_process_id: Uuid,
+    pub name: Option<String>,
+    pub prompt: String,
+}
+
+impl RemoteExecution {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RemoteExecution,
+            r#"SELECT id as "id!: Uuid",
+                      host_id as "host_id!: Uuid",
+                      remote_workspace_id as "remote_workspace_id!: Uuid",
+                      remote_execution_process_id as "remote_execution_process_id!: Uuid",
+                      name,
+                      prompt,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM remote_executions
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first.
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RemoteExecution,
+            r#"SELECT id as "id!: Uuid",
+                      host_id as "host_id!: Uuid",
+                      remote_workspace_id as "remote_workspace_id!: Uuid",
+                      remote_execution_process_id as "remote_execution_process_id!: Uuid",
+                      name,
+                      prompt,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM remote_executions
+               ORDER BY created_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateRemoteExecution,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            RemoteExecution,
+            r#"INSERT INTO remote_executions
+                   (id, host_id, remote_workspace_id, remote_execution_process_id, name, prompt)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         host_id as "host_id!: Uuid",
+                         remote_workspace_id as "remote_workspace_id!: Uuid",
+                         remote_execution_process_id as "remote_execution_process_id!: Uuid",
+                         name,
+                         prompt,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.host_id,
+            data.remote_workspace_id,
+            data.remote_execution_process_id,
+            data.name,
+            data.prompt
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM remote_executions WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
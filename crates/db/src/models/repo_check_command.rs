@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A command run in the repo when a coding agent session completes, e.g.
+/// `pnpm lint` or `cargo test`. It passes when it exits with status 0.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoCheckCommand {
+    pub id: Uuid,
+    pub repo_id: Uuid,
+    pub name: String,
+    pub command: String,
+    /// Checks run one after the other in this order.
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct RepoCheckCommandInput {
+    pub name: String,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoCheckCommands {
+    pub checks: Vec<RepoCheckCommandInput>,
+}
+
+impl RepoCheckCommand {
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoCheckCommand,
+            r#"SELECT id as "id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      name,
+                      command,
+                      position as "position!: i64",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM repo_check_commands
+               WHERE repo_id = $1
+               ORDER BY position ASC"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the repo's checks, keeping the given order.
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        checks: &[RepoCheckCommandInput],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM repo_check_commands WHERE repo_id = $1",
+            repo_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (position, check) in checks.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                "INSERT INTO repo_check_commands (id, repo_id, name, command, position)
+                 VALUES ($1, $2, $3, $4, $5)",
+                id,
+                repo_id,
+                check.name,
+                check.command,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_repo_id(pool, repo_id).await
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The Docker image a repo's processes run in under the Docker execution
+/// backend. The image needs the coding agent CLIs the repo's tasks use.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoContainerImage {
+    pub repo_id: Uuid,
+    pub image: String,
+    /// Comma-separated container ports dev servers listen on, e.g.
+    /// `3000, 5173`. Each is published on a free port of 127.0.0.1, so the
+    /// server inside has to listen on all interfaces.
+    pub ports: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoContainerImage {
+    pub image: String,
+    pub ports: Option<String>,
+}
+
+/// Ports in a comma-separated list. Entries that aren't ports are skipped.
+pub fn parse_ports(list: &str) -> Vec<u16> {
+    list.split(',')
+        .filter_map(|port| port.trim().parse().ok())
+        .filter(|port| *port != 0)
+        .collect()
+}
+
+impl RepoContainerImage {
+    pub fn ports(&self) -> Vec<u16> {
+        self.ports.as_deref().map(parse_ports).unwrap_or_default()
+    }
+
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoContainerImage,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      image,
+                      ports,
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_container_images
+               WHERE repo_id = $1"#,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        data: &SetRepoContainerImage,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            RepoContainerImage,
+            r#"INSERT INTO repo_container_images (repo_id, image, ports)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(repo_id) DO UPDATE SET
+                   image = excluded.image,
+                   ports = excluded.ports,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING repo_id as "repo_id!: Uuid",
+                         image,
+                         ports,
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            repo_id,
+            data.image,
+            data.ports
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM repo_container_images WHERE repo_id = $1",
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
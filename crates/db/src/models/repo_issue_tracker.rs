@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "issue_tracker_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum IssueTrackerKind {
+    Github,
+    Linear,
+    Jira,
+}
+
+/// The issue tracker a repo's work is planned in, for importing issues as
+/// workspaces.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoIssueTracker {
+    pub repo_id: Uuid,
+    pub provider: IssueTrackerKind,
+    /// GitHub `owner/name`, Linear team key or Jira project key.
+    pub project: String,
+    /// Jira site, e.g. `https://acme.atlassian.net`.
+    pub base_url: Option<String>,
+    /// Jira account email the token belongs to. Without it the token is sent
+    /// as a bearer token, as Jira Data Center personal access tokens are.
+    pub username: Option<String>,
+    /// Stored encrypted when the config secrets key is available; never
+    /// sent to clients.
+    #[serde(skip)]
+    #[ts(skip)]
+    pub token: Option<String>,
+    pub has_token: bool,
+    /// Close imported issues once all PRs of their workspace are merged.
+    pub close_on_merge: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoIssueTracker {
+    pub provider: IssueTrackerKind,
+    pub project: String,
+    pub base_url: Option<String>,
+    pub username: Option<String>,
+    /// Leave unset to keep the stored token of the same provider.
+    pub token: Option<String>,
+    #[serde(default)]
+    pub close_on_merge: bool,
+}
+
+impl RepoIssueTracker {
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoIssueTracker,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      provider as "provider!: IssueTrackerKind",
+                      project,
+                      base_url,
+                      username,
+                      token,
+                      token IS NOT NULL as "has_token!: bool",
+                      close_on_merge as "close_on_merge!: bool",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_issue_trackers
+               WHERE repo_id = $1"#,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Configure the repo's tracker. `token` is stored as given; without
+    /// one, the stored token is kept unless the provider changes.
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        data: &SetRepoIssueTracker,
+        token: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            RepoIssueTracker,
+            r#"INSERT INTO repo_issue_trackers
+                   (repo_id, provider, project, base_url, username, token, close_on_merge)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               ON CONFLICT(repo_id) DO UPDATE SET
+                   token = CASE
+                       WHEN excluded.token IS NOT NULL THEN excluded.token
+                       WHEN repo_issue_trackers.provider = excluded.provider
+                           THEN repo_issue_trackers.token
+                   END,
+                   provider = excluded.provider,
+                   project = excluded.project,
+                   base_url = excluded.base_url,
+                   username = excluded.username,
+                   close_on_merge = excluded.close_on_merge,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING repo_id as "repo_id!: Uuid",
+                         provider as "provider!: IssueTrackerKind",
+                         project,
+                         base_url,
+                         username,
+                         token,
+                         token IS NOT NULL as "has_token!: bool",
+                         close_on_merge as "close_on_merge!: bool",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            repo_id,
+            data.provider,
+            data.project,
+            data.base_url,
+            data.username,
+            token,
+            data.close_on_merge
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM repo_issue_trackers WHERE repo_id = $1",
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
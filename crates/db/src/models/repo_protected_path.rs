@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "protected_path_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectedPathAction {
+    /// Writes wait for an approval, even in sessions that auto-approve.
+    RequireApproval,
+    /// Writes are refused.
+    Deny,
+}
+
+/// A gitignore-style pattern, relative to the repo root, for files coding
+/// agents may not change freely, e.g. `infra/**` or `*.lock`.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoProtectedPath {
+    pub id: Uuid,
+    pub repo_id: Uuid,
+    pub pattern: String,
+    pub action: ProtectedPathAction,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateRepoProtectedPath {
+    pub pattern: String,
+    pub action: ProtectedPathAction,
+}
+
+impl RepoProtectedPath {
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoProtectedPath,
+            r#"SELECT id as "id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      pattern,
+                      action as "action!: ProtectedPathAction",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM repo_protected_paths
+               WHERE repo_id = $1
+               ORDER BY created_at ASC"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Add a pattern, or change the action of an existing one.
+    pub async fn create(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        data: &CreateRepoProtectedPath,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            RepoProtectedPath,
+            r#"INSERT INTO repo_protected_paths (id, repo_id, pattern, action)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(repo_id, pattern) DO UPDATE SET action = excluded.action
+               RETURNING id as "id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         pattern,
+                         action as "action!: ProtectedPathAction",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            repo_id,
+            data.pattern,
+            data.action
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM repo_protected_paths WHERE id = $1 AND repo_id = $2",
+            id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
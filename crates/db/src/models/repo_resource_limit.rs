@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use utils::resource_limits::ResourceLimits;
+use uuid::Uuid;
+
+/// CPU and memory caps for executor, script and dev server processes started
+/// in a repo. A coding agent spanning several repos gets the strictest caps
+/// of all of them.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoResourceLimits {
+    pub repo_id: Uuid,
+    pub memory_limit_mb: Option<u32>,
+    /// Percent of one CPU; 200 allows two full cores. Needs cgroup v2 on
+    /// Linux and is ignored elsewhere.
+    pub cpu_limit_percent: Option<u32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoResourceLimits {
+    pub memory_limit_mb: Option<u32>,
+    pub cpu_limit_percent: Option<u32>,
+}
+
+impl RepoResourceLimits {
+    pub fn limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            memory_mb: self.memory_limit_mb.map(u64::from),
+            cpu_percent: self.cpu_limit_percent,
+        }
+    }
+
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoResourceLimits,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      memory_limit_mb as "memory_limit_mb: u32",
+                      cpu_limit_percent as "cpu_limit_percent: u32",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_resource_limits
+               WHERE repo_id = $1"#,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The strictest caps across `repo_ids`.
+    pub async fn strictest_for_repos(
+        pool: &SqlitePool,
+        repo_ids: &[Uuid],
+    ) -> Result<ResourceLimits, sqlx::Error> {
+        let mut limits = ResourceLimits::default();
+        for repo_id in repo_ids {
+            if let Some(repo_limits) = Self::find_by_repo_id(pool, *repo_id).await? {
+                limits = limits.strictest(repo_limits.limits());
+            }
+        }
+        Ok(limits)
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        data: &SetRepoResourceLimits,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            RepoResourceLimits,
+            r#"INSERT INTO repo_resource_limits (repo_id, memory_limit_mb, cpu_limit_percent)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(repo_id) DO UPDATE SET
+                   memory_limit_mb = excluded.memory_limit_mb,
+                   cpu_limit_percent = excluded.cpu_limit_percent,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING repo_id as "repo_id!: Uuid",
+                         memory_limit_mb as "memory_limit_mb: u32",
+                         cpu_limit_percent as "cpu_limit_percent: u32",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            repo_id,
+            data.memory_limit_mb,
+            data.cpu_limit_percent
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM repo_resource_limits WHERE repo_id = $1",
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A resource a repo's coding agents use heavily. Executions holding a tag
+/// count against that tag's limit in `execution_tag_limits`.
+#[derive(
+    Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, TS,
+)]
+#[sqlx(type_name = "resource_tag", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceTag {
+    CpuHeavy,
+    Gpu,
+    Network,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoResourceTags {
+    pub repo_id: Uuid,
+    pub tags: Vec<ResourceTag>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoResourceTags {
+    pub tags: Vec<ResourceTag>,
+}
+
+impl RepoResourceTags {
+    pub async fn find_by_repo_id(pool: &SqlitePool, repo_id: Uuid) -> Result<Self, sqlx::Error> {
+        let tags = sqlx::query_scalar!(
+            r#"SELECT tag as "tag!: ResourceTag"
+               FROM repo_resource_tags
+               WHERE repo_id = $1
+               ORDER BY tag"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(Self { repo_id, tags })
+    }
+
+    /// The tags of any of `repo_ids`, each once.
+    pub async fn tags_for_repos(
+        pool: &SqlitePool,
+        repo_ids: &[Uuid],
+    ) -> Result<Vec<ResourceTag>, sqlx::Error> {
+        let mut tags = Vec::new();
+        for repo_id in repo_ids {
+            tags.extend(Self::find_by_repo_id(pool, *repo_id).await?.tags);
+        }
+        tags.sort();
+        tags.dedup();
+        Ok(tags)
+    }
+
+    /// Replace the repo's tags.
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        tags: &[ResourceTag],
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM repo_resource_tags WHERE repo_id = $1", repo_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag in tags {
+            sqlx::query!(
+                "INSERT INTO repo_resource_tags (repo_id, tag) VALUES ($1, $2)
+                 ON CONFLICT DO NOTHING",
+                repo_id,
+                tag
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_repo_id(pool, repo_id).await
+    }
+}
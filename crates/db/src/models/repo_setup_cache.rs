@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Directories warmed up by a repo's setup script that are cached and copied
+/// into new worktrees, so a task doesn't pay the full install cost.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct RepoSetupCache {
+    pub repo_id: Uuid,
+    /// Comma-separated directories relative to the repo root, e.g.
+    /// `node_modules, target`.
+    pub cache_paths: String,
+    /// Comma-separated files whose contents key the cache. Defaults to the
+    /// common lockfiles when empty.
+    pub key_files: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetRepoSetupCache {
+    pub cache_paths: String,
+    pub key_files: Option<String>,
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl RepoSetupCache {
+    pub fn paths(&self) -> Vec<String> {
+        split_list(&self.cache_paths)
+    }
+
+    /// Configured key files, or `None` to use the defaults.
+    pub fn key_files(&self) -> Option<Vec<String>> {
+        self.key_files
+            .as_deref()
+            .map(split_list)
+            .filter(|files| !files.is_empty())
+    }
+
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            RepoSetupCache,
+            r#"SELECT repo_id as "repo_id!: Uuid",
+                      cache_paths,
+                      key_files,
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM repo_setup_caches
+               WHERE repo_id = $1"#,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        data: &SetRepoSetupCache,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            RepoSetupCache,
+            r#"INSERT INTO repo_setup_caches (repo_id, cache_paths, key_files)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(repo_id) DO UPDATE SET
+                   cache_paths = excluded.cache_paths,
+                   key_files = excluded.key_files,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING repo_id as "repo_id!: Uuid",
+                         cache_paths,
+                         key_files,
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            repo_id,
+            data.cache_paths,
+            data.key_files
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, repo_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM repo_setup_caches WHERE repo_id = $1", repo_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, Hash, TS)]
+#[sqlx(type_name = "report_period_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriodKind {
+    /// Monday to Sunday, UTC.
+    Week,
+    /// Calendar month, UTC.
+    Month,
+}
+
+/// Running totals for one report period, updated as activity events are
+/// folded in.
+#[derive(Debug, Clone, FromRow)]
+pub struct ReportPeriod {
+    pub period: ReportPeriodKind,
+    pub period_start: NaiveDate,
+    pub tasks_completed: i64,
+    pub approvals_approved: i64,
+    pub approvals_denied: i64,
+    pub merge_durations: Json<Vec<i64>>,
+    pub executor_counts: Json<BTreeMap<String, i64>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl ReportPeriod {
+    pub fn empty(period: ReportPeriodKind, period_start: NaiveDate) -> Self {
+        Self {
+            period,
+            period_start,
+            tasks_completed: 0,
+            approvals_approved: 0,
+            approvals_denied: 0,
+            merge_durations: Json(Vec::new()),
+            executor_counts: Json(BTreeMap::new()),
+            delivered_at: None,
+        }
+    }
+
+    /// The most recent periods of a kind, newest first.
+    pub async fn find_recent(
+        pool: &SqlitePool,
+        period: ReportPeriodKind,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReportPeriod,
+            r#"SELECT period as "period!: ReportPeriodKind",
+                      period_start as "period_start!: NaiveDate",
+                      tasks_completed,
+                      approvals_approved,
+                      approvals_denied,
+                      merge_durations as "merge_durations!: Json<Vec<i64>>",
+                      executor_counts as "executor_counts!: Json<BTreeMap<String, i64>>",
+                      delivered_at as "delivered_at: DateTime<Utc>"
+               FROM report_periods
+               WHERE period = $1
+               ORDER BY period_start DESC
+               LIMIT $2"#,
+            period,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find(
+        pool: &SqlitePool,
+        period: ReportPeriodKind,
+        period_start: NaiveDate,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReportPeriod,
+            r#"SELECT period as "period!: ReportPeriodKind",
+                      period_start as "period_start!: NaiveDate",
+                      tasks_completed,
+                      approvals_approved,
+                      approvals_denied,
+                      merge_durations as "merge_durations!: Json<Vec<i64>>",
+                      executor_counts as "executor_counts!: Json<BTreeMap<String, i64>>",
+                      delivered_at as "delivered_at: DateTime<Utc>"
+               FROM report_periods
+               WHERE period = $1 AND period_start = $2"#,
+            period,
+            period_start
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Id of the last activity event folded into the periods.
+    pub async fn cursor(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT last_event_id as "last_event_id!: i64" FROM report_cursor WHERE id = 1"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Write updated periods and advance the cursor from `from_event_id` to
+    /// `to_event_id` in one transaction, so a batch of events is counted
+    /// exactly once. Returns `false` without writing anything if another
+    /// refresh moved the cursor first.
+    pub async fn save_batch(
+        pool: &SqlitePool,
+        periods: &[ReportPeriod],
+        from_event_id: i64,
+        to_event_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let advanced = sqlx::query!(
+            "UPDATE report_cursor SET last_event_id = $1 WHERE id = 1 AND last_event_id = $2",
+            to_event_id,
+            from_event_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if advanced == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        for period in periods {
+            sqlx::query!(
+                "INSERT INTO report_periods
+                     (period, period_start, tasks_completed, approvals_approved,
+                      approvals_denied, merge_durations, executor_counts)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT(period, period_start) DO UPDATE SET
+                     tasks_completed = excluded.tasks_completed,
+                     approvals_approved = excluded.approvals_approved,
+                     approvals_denied = excluded.approvals_denied,
+                     merge_durations = excluded.merge_durations,
+                     executor_counts = excluded.executor_counts",
+                period.period,
+                period.period_start,
+                period.tasks_completed,
+                period.approvals_approved,
+                period.approvals_denied,
+                period.merge_durations,
+                period.executor_counts
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn mark_delivered(
+        pool: &SqlitePool,
+        period: ReportPeriodKind,
+        period_start: NaiveDate,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "INSERT INTO report_periods (period, period_start, delivered_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT(period, period_start) DO UPDATE SET delivered_at = excluded.delivered_at",
+            period,
+            period_start,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
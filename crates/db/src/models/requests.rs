@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use executors::profile::ExecutorConfig;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{execution_process::ExecutionProcess, workspace::Workspace};
+use super::{
+    execution_process::ExecutionProcess, workspace::Workspace,
+    workspace_dev_server::UpdateWorkspaceDevServer,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ContainerQuery {
@@ -20,6 +25,10 @@ pub struct WorkspaceRepoInput {
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateWorkspaceApiRequest {
     pub name: Option<String>,
+    /// Override the repos' dev server script and expected port.
+    #[serde(default)]
+    #[ts(optional)]
+    pub dev_server: Option<UpdateWorkspaceDevServer>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -36,6 +45,18 @@ pub struct CreateAndStartWorkspaceRequest {
     pub executor_config: ExecutorConfig,
     pub prompt: String,
     pub attachment_ids: Option<Vec<Uuid>>,
+    /// Override the repos' dev server script and expected port.
+    #[serde(default)]
+    #[ts(optional)]
+    pub dev_server: Option<UpdateWorkspaceDevServer>,
+    /// Runs in the workspace root before the repos' setup scripts.
+    #[serde(default)]
+    #[ts(optional)]
+    pub setup_script: Option<String>,
+    /// Extra environment variables for every process run in the workspace.
+    #[serde(default)]
+    #[ts(optional)]
+    pub env_vars: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -55,6 +55,12 @@ impl Session {
         .await
     }
 
+    pub async fn find_all_ids(pool: &SqlitePool) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT id AS "id!: Uuid" FROM sessions"#)
+            .fetch_all(pool)
+            .await
+    }
+
     /// Find all sessions for a workspace, ordered by most recently used.
     /// "Most recently used" is defined as the most recent non-dev server execution process.
     /// Sessions with no executions fall back to created_at for ordering.
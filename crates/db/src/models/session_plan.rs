@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPlanStatus {
+    /// The agent is waiting for the plan to be accepted or rejected.
+    Pending,
+    Accepted,
+    Rejected,
+    /// The approval timed out or the run ended before anyone answered.
+    Expired,
+}
+
+/// The latest plan a coding agent proposed in plan mode for a session.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SessionPlan {
+    pub session_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub plan: String,
+    /// Starts at 1 and goes up every time the agent revises the plan.
+    pub revision: i64,
+    pub status: SessionPlanStatus,
+    /// Approval the agent waits on while the plan is pending.
+    pub approval_id: Option<String>,
+    /// Reason given when the plan was rejected.
+    pub feedback: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SessionPlan {
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SessionPlan,
+            r#"SELECT session_id as "session_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      plan,
+                      revision as "revision!: i64",
+                      status as "status!: SessionPlanStatus",
+                      approval_id,
+                      feedback,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM session_plans
+               WHERE session_id = $1"#,
+            session_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record a newly proposed plan, replacing the session's previous one.
+    pub async fn propose(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        execution_process_id: Uuid,
+        plan: &str,
+        approval_id: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            SessionPlan,
+            r#"INSERT INTO session_plans (session_id, execution_process_id, plan, approval_id)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(session_id) DO UPDATE SET
+                   execution_process_id = excluded.execution_process_id,
+                   plan = excluded.plan,
+                   revision = session_plans.revision + 1,
+                   status = 'pending',
+                   approval_id = excluded.approval_id,
+                   feedback = NULL,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING session_id as "session_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         revision as "revision!: i64",
+                         status as "status!: SessionPlanStatus",
+                         approval_id,
+                         feedback,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            session_id,
+            execution_process_id,
+            plan,
+            approval_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Settle the plan waiting on `approval_id`. Returns `None` if no pending
+    /// plan waits on it, e.g. because a revision replaced it.
+    pub async fn resolve(
+        pool: &SqlitePool,
+        approval_id: &str,
+        status: SessionPlanStatus,
+        feedback: Option<&str>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SessionPlan,
+            r#"UPDATE session_plans
+               SET status = $1, feedback = $2, updated_at = datetime('now', 'subsec')
+               WHERE approval_id = $3 AND status = 'pending'
+               RETURNING session_id as "session_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         revision as "revision!: i64",
+                         status as "status!: SessionPlanStatus",
+                         approval_id,
+                         feedback,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            status,
+            feedback,
+            approval_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use utils::approvals::{ApprovalQuestion, QuestionAnswer};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SessionQuestionStatus {
+    Pending,
+    Answered,
+    /// The question timed out or the run ended before it was answered.
+    Unanswered,
+}
+
+/// Questions a coding agent asked in one request, with the answers it got.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SessionQuestion {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub approval_id: String,
+    #[ts(type = "Array<ApprovalQuestion>")]
+    pub questions: Json<Vec<ApprovalQuestion>>,
+    /// As forwarded to the agent, in question order.
+    #[ts(type = "Array<QuestionAnswer> | null")]
+    pub answers: Option<Json<Vec<QuestionAnswer>>>,
+    pub status: SessionQuestionStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl SessionQuestion {
+    pub async fn create(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        execution_process_id: Uuid,
+        approval_id: &str,
+        questions: &[ApprovalQuestion],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let questions = Json(questions);
+        sqlx::query_as!(
+            SessionQuestion,
+            r#"INSERT INTO session_questions
+                   (id, session_id, execution_process_id, approval_id, questions)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         session_id as "session_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         approval_id,
+                         questions as "questions!: Json<Vec<ApprovalQuestion>>",
+                         answers as "answers: Json<Vec<QuestionAnswer>>",
+                         status as "status!: SessionQuestionStatus",
+                         created_at as "created_at!: DateTime<Utc>",
+                         resolved_at as "resolved_at: DateTime<Utc>""#,
+            id,
+            session_id,
+            execution_process_id,
+            approval_id,
+            questions
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Oldest first.
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SessionQuestion,
+            r#"SELECT id as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      approval_id,
+                      questions as "questions!: Json<Vec<ApprovalQuestion>>",
+                      answers as "answers: Json<Vec<QuestionAnswer>>",
+                      status as "status!: SessionQuestionStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      resolved_at as "resolved_at: DateTime<Utc>"
+               FROM session_questions
+               WHERE session_id = $1
+               ORDER BY created_at ASC"#,
+            session_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record the answers to a pending question, or that it went unanswered
+    /// when `answers` is `None`.
+    pub async fn resolve(
+        pool: &SqlitePool,
+        approval_id: &str,
+        answers: Option<&[QuestionAnswer]>,
+    ) -> Result<(), sqlx::Error> {
+        let status = match answers {
+            Some(_) => SessionQuestionStatus::Answered,
+            None => SessionQuestionStatus::Unanswered,
+        };
+        let answers = answers.map(Json);
+        sqlx::query!(
+            "UPDATE session_questions
+             SET status = $1, answers = $2, resolved_at = datetime('now', 'subsec')
+             WHERE approval_id = $3 AND status = 'pending'",
+            status,
+            answers,
+            approval_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
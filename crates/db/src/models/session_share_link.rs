@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A read-only link to one session. The token is never stored, only its
+/// hash.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SessionShareLink {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SessionShareLink {
+    pub async fn create(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            SessionShareLink,
+            r#"INSERT INTO session_share_links (id, session_id, token_hash, expires_at)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         session_id as "session_id!: Uuid",
+                         token_hash,
+                         expires_at as "expires_at!: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            session_id,
+            token_hash,
+            expires_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The link with this token hash, unless it has expired.
+    pub async fn find_active_by_token_hash(
+        pool: &SqlitePool,
+        token_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            SessionShareLink,
+            r#"SELECT id as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      token_hash,
+                      expires_at as "expires_at!: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM session_share_links
+               WHERE token_hash = $1 AND expires_at > $2"#,
+            token_hash,
+            now
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first, including expired links.
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SessionShareLink,
+            r#"SELECT id as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      token_hash,
+                      expires_at as "expires_at!: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM session_share_links
+               WHERE session_id = $1
+               ORDER BY created_at DESC"#,
+            session_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns whether a link was deleted.
+    pub async fn delete(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM session_share_links WHERE id = $1 AND session_id = $2",
+            id,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
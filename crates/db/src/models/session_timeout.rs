@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Limits for a coding agent run, in minutes. `None` inherits the next
+/// configured level; `0` disables the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ExecutionTimeouts {
+    /// Kill the run once it has been running this long.
+    #[serde(default)]
+    pub wall_clock_minutes: Option<u32>,
+    /// Kill the run once it has produced no output for this long. Time spent
+    /// waiting on an approval does not count.
+    #[serde(default)]
+    pub idle_minutes: Option<u32>,
+}
+
+impl ExecutionTimeouts {
+    /// Fill unset limits from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            wall_clock_minutes: self.wall_clock_minutes.or(fallback.wall_clock_minutes),
+            idle_minutes: self.idle_minutes.or(fallback.idle_minutes),
+        }
+    }
+}
+
+/// Timeout overrides for the coding agent runs of one session.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SessionTimeout {
+    pub session_id: Uuid,
+    pub wall_clock_minutes: Option<u32>,
+    pub idle_minutes: Option<u32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SessionTimeout {
+    pub fn timeouts(&self) -> ExecutionTimeouts {
+        ExecutionTimeouts {
+            wall_clock_minutes: self.wall_clock_minutes,
+            idle_minutes: self.idle_minutes,
+        }
+    }
+
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SessionTimeout,
+            r#"SELECT session_id as "session_id!: Uuid",
+                      wall_clock_minutes as "wall_clock_minutes: u32",
+                      idle_minutes as "idle_minutes: u32",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM session_timeouts
+               WHERE session_id = $1"#,
+            session_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        timeouts: ExecutionTimeouts,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            SessionTimeout,
+            r#"INSERT INTO session_timeouts (session_id, wall_clock_minutes, idle_minutes)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(session_id) DO UPDATE SET
+                   wall_clock_minutes = excluded.wall_clock_minutes,
+                   idle_minutes = excluded.idle_minutes,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING session_id as "session_id!: Uuid",
+                         wall_clock_minutes as "wall_clock_minutes: u32",
+                         idle_minutes as "idle_minutes: u32",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            session_id,
+            timeouts.wall_clock_minutes,
+            timeouts.idle_minutes
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, session_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM session_timeouts WHERE session_id = $1",
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorConfig;
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A saved prompt and workspace setup for a recurring chore.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub prompt: String,
+    /// `None` uses the configured default executor.
+    #[ts(type = "ExecutorConfig | null")]
+    pub executor_config: Option<Json<ExecutorConfig>>,
+    /// Target branch for every repo. `None` uses each repo's default.
+    pub base_branch: Option<String>,
+    /// Extra environment variables for every process run in the workspace.
+    #[ts(type = "{ [key in string]?: string }")]
+    pub env_vars: Json<HashMap<String, String>>,
+    /// Runs in the workspace root before the repos' setup scripts.
+    pub setup_script: Option<String>,
+    #[ts(type = "Array<string>")]
+    pub repo_ids: Json<Vec<Uuid>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateTaskTemplate {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_config: Option<ExecutorConfig>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub env_vars: Option<HashMap<String, String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub setup_script: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct UpdateTaskTemplate {
+    #[serde(default)]
+    #[ts(optional)]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub prompt: Option<String>,
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "ExecutorConfig | null")]
+    pub executor_config: Option<Option<ExecutorConfig>>,
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "string | null")]
+    pub base_branch: Option<Option<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub env_vars: Option<HashMap<String, String>>,
+    #[serde(default, with = "double_option")]
+    #[ts(optional, type = "string | null")]
+    pub setup_script: Option<Option<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_ids: Option<Vec<Uuid>>,
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|s| !s.is_empty())
+}
+
+impl TaskTemplate {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      prompt,
+                      executor_config as "executor_config: Json<ExecutorConfig>",
+                      base_branch,
+                      env_vars as "env_vars!: Json<HashMap<String, String>>",
+                      setup_script,
+                      repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_templates
+               ORDER BY name COLLATE NOCASE ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      prompt,
+                      executor_config as "executor_config: Json<ExecutorConfig>",
+                      base_branch,
+                      env_vars as "env_vars!: Json<HashMap<String, String>>",
+                      setup_script,
+                      repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_templates
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let name = data.name.trim();
+        let executor_config = data.executor_config.as_ref().map(Json);
+        let base_branch = non_empty(data.base_branch.as_deref());
+        let env_vars = Json(data.env_vars.clone().unwrap_or_default());
+        let setup_script = non_empty(data.setup_script.as_deref());
+        let repo_ids = Json(data.repo_ids.clone().unwrap_or_default());
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"INSERT INTO task_templates
+                   (id, name, prompt, executor_config, base_branch, env_vars, setup_script, repo_ids)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid",
+                         name,
+                         prompt,
+                         executor_config as "executor_config: Json<ExecutorConfig>",
+                         base_branch,
+                         env_vars as "env_vars!: Json<HashMap<String, String>>",
+                         setup_script,
+                         repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            data.prompt,
+            executor_config,
+            base_branch,
+            env_vars,
+            setup_script,
+            repo_ids
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateTaskTemplate,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data
+            .name
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or(&existing.name);
+        let prompt = data.prompt.as_ref().unwrap_or(&existing.prompt);
+        let executor_config = match &data.executor_config {
+            Some(executor_config) => executor_config.clone().map(Json),
+            None => existing.executor_config,
+        };
+        let base_branch = match &data.base_branch {
+            Some(base_branch) => non_empty(base_branch.as_deref()),
+            None => existing.base_branch.as_deref(),
+        };
+        let env_vars = data.env_vars.clone().map(Json).unwrap_or(existing.env_vars);
+        let setup_script = match &data.setup_script {
+            Some(setup_script) => non_empty(setup_script.as_deref()),
+            None => existing.setup_script.as_deref(),
+        };
+        let repo_ids = data.repo_ids.clone().map(Json).unwrap_or(existing.repo_ids);
+
+        sqlx::query_as!(
+            TaskTemplate,
+            r#"UPDATE task_templates
+               SET name = $1, prompt = $2, executor_config = $3, base_branch = $4, env_vars = $5,
+                   setup_script = $6, repo_ids = $7, updated_at = datetime('now', 'subsec')
+               WHERE id = $8
+               RETURNING id as "id!: Uuid",
+                         name,
+                         prompt,
+                         executor_config as "executor_config: Json<ExecutorConfig>",
+                         base_branch,
+                         env_vars as "env_vars!: Json<HashMap<String, String>>",
+                         setup_script,
+                         repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            name,
+            prompt,
+            executor_config,
+            base_branch,
+            env_vars,
+            setup_script,
+            repo_ids,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_templates WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
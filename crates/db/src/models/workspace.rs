@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use executors::actions::{ExecutorAction, ExecutorActionType};
 use serde::{Deserialize, Serialize};
@@ -12,7 +14,12 @@ const WORKSPACE_NAME_MAX_LEN: usize = 60;
 use super::{
     execution_process::ExecutorActionField,
     session::Session,
+    workspace_branch_freshness::WorkspaceBranchFreshness,
+    workspace_check_result::WorkspaceCheckResult,
+    workspace_child::{ChildWorkspaceProgress, WorkspaceChild},
+    workspace_ci_check::{WorkspaceCiCheck, WorkspaceCiStatus},
     workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
+    workspace_repo_command::{RepoCommandStatus, WorkspaceRepoCommand},
 };
 
 #[derive(Debug, Error)]
@@ -51,6 +58,9 @@ pub struct Workspace {
     pub pinned: bool,
     pub name: Option<String>,
     pub worktree_deleted: bool,
+    /// Port the running dev server is published on: the workspace's
+    /// configured port, or else the one detected in its output.
+    pub dev_server_port: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -60,6 +70,19 @@ pub struct WorkspaceWithStatus {
     pub workspace: Workspace,
     pub is_running: bool,
     pub is_errored: bool,
+    /// Per-repo progress of the latest command run across the workspace's
+    /// repos, if any.
+    pub repo_command: Option<RepoCommandStatus>,
+    /// Progress of the child workspaces split off from this one, if any.
+    pub child_progress: Option<ChildWorkspaceProgress>,
+    /// Drift of the workspace branch from each repo's target branch, as last
+    /// checked in the background. Empty until the first check.
+    pub branch_freshness: Vec<WorkspaceBranchFreshness>,
+    /// Results of the repo checks run when the latest coding agent session
+    /// completed. Empty if the workspace's repos have no checks.
+    pub checks: Vec<WorkspaceCheckResult>,
+    /// CI checks of the branch's latest commit, as reported by CI webhooks.
+    pub ci_status: Option<WorkspaceCiStatus>,
 }
 
 impl std::ops::Deref for WorkspaceWithStatus {
@@ -102,7 +125,8 @@ impl Workspace {
                           archived AS "archived!: bool",
                           pinned AS "pinned!: bool",
                           name,
-                          worktree_deleted AS "worktree_deleted!: bool"
+                          worktree_deleted AS "worktree_deleted!: bool",
+                          dev_server_port AS "dev_server_port: u16"
                    FROM workspaces
                    ORDER BY created_at DESC"#
         )
@@ -179,6 +203,64 @@ impl Workspace {
         Ok(())
     }
 
+    /// Publish the port the workspace's dev server was detected on. Leaves
+    /// `updated_at` alone so the workspace list order doesn't change.
+    pub async fn set_dev_server_port(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        port: u16,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspaces SET dev_server_port = $1 WHERE id = $2",
+            port,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Clear the published port if it is still `port`; another dev server
+    /// of the workspace may have published its own since.
+    pub async fn clear_dev_server_port(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        port: u16,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspaces SET dev_server_port = NULL WHERE id = $1 AND dev_server_port = $2",
+            workspace_id,
+            port
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// No dev servers survive a restart, so ports published before it are
+    /// stale.
+    pub async fn clear_all_dev_server_ports(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspaces SET dev_server_port = NULL WHERE dev_server_port IS NOT NULL"
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The workspace whose dev server is published on `port`.
+    pub async fn find_id_by_dev_server_port(
+        pool: &SqlitePool,
+        port: u16,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT id AS "id!: Uuid" FROM workspaces WHERE dev_server_port = $1 LIMIT 1"#,
+            port
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     /// Update the workspace's updated_at timestamp to prevent cleanup.
     /// Call this when the workspace is accessed (e.g., opened in editor).
     pub async fn touch(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
@@ -204,7 +286,8 @@ impl Workspace {
                        archived          AS "archived!: bool",
                        pinned            AS "pinned!: bool",
                        name,
-                       worktree_deleted  AS "worktree_deleted!: bool"
+                       worktree_deleted  AS "worktree_deleted!: bool",
+                       dev_server_port   AS "dev_server_port: u16"
                FROM    workspaces
                WHERE   id = $1"#,
             id
@@ -226,7 +309,8 @@ impl Workspace {
                        archived          AS "archived!: bool",
                        pinned            AS "pinned!: bool",
                        name,
-                       worktree_deleted  AS "worktree_deleted!: bool"
+                       worktree_deleted  AS "worktree_deleted!: bool",
+                       dev_server_port   AS "dev_server_port: u16"
                FROM    workspaces
                WHERE   rowid = $1"#,
             rowid
@@ -269,7 +353,8 @@ impl Workspace {
                 w.archived as "archived!: bool",
                 w.pinned as "pinned!: bool",
                 w.name,
-                w.worktree_deleted as "worktree_deleted!: bool"
+                w.worktree_deleted as "worktree_deleted!: bool",
+                w.dev_server_port as "dev_server_port: u16"
             FROM workspaces w
             LEFT JOIN sessions s ON w.id = s.workspace_id
             LEFT JOIN execution_processes ep ON s.id = ep.session_id AND ep.completed_at IS NOT NULL
@@ -317,7 +402,7 @@ impl Workspace {
             Workspace,
             r#"INSERT INTO workspaces (id, task_id, container_ref, branch, setup_completed_at, name)
                VALUES ($1, $2, $3, $4, $5, $6)
-               RETURNING id as "id!: Uuid", task_id as "task_id: Uuid", container_ref, branch, setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", archived as "archived!: bool", pinned as "pinned!: bool", name, worktree_deleted as "worktree_deleted!: bool""#,
+               RETURNING id as "id!: Uuid", task_id as "task_id: Uuid", container_ref, branch, setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", archived as "archived!: bool", pinned as "pinned!: bool", name, worktree_deleted as "worktree_deleted!: bool", dev_server_port as "dev_server_port: u16""#,
             id,
             Option::<Uuid>::None,
             Option::<String>::None,
@@ -514,6 +599,7 @@ impl Workspace {
                 w.pinned AS "pinned!: bool",
                 w.name,
                 w.worktree_deleted AS "worktree_deleted!: bool",
+                w.dev_server_port AS "dev_server_port: u16",
 
                 CASE WHEN EXISTS (
                     SELECT 1
@@ -533,7 +619,7 @@ impl Workspace {
                       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
                     ORDER BY ep.created_at DESC
                     LIMIT 1
-                ) IN ('failed','killed') THEN 1 ELSE 0 END AS "is_errored!: i64"
+                ) IN ('failed','killed','timedout') THEN 1 ELSE 0 END AS "is_errored!: i64"
 
             FROM workspaces w
             ORDER BY w.updated_at DESC"#
@@ -556,9 +642,15 @@ impl Workspace {
                     pinned: rec.pinned,
                     name: rec.name,
                     worktree_deleted: rec.worktree_deleted,
+                    dev_server_port: rec.dev_server_port,
                 },
                 is_running: rec.is_running != 0,
                 is_errored: rec.is_errored != 0,
+                repo_command: None,
+                child_progress: None,
+                branch_freshness: Vec::new(),
+                checks: Vec::new(),
+                ci_status: None,
             })
             // Apply archived filter if provided
             .filter(|ws| archived.is_none_or(|a| ws.workspace.archived == a))
@@ -569,7 +661,30 @@ impl Workspace {
             workspaces.truncate(lim as usize);
         }
 
+        let mut repo_commands: HashMap<Uuid, WorkspaceRepoCommand> =
+            WorkspaceRepoCommand::find_all(pool)
+                .await?
+                .into_iter()
+                .map(|command| (command.workspace_id, command))
+                .collect();
+
+        let mut child_progress = WorkspaceChild::progress_by_parent(pool).await?;
+        let mut branch_freshness = WorkspaceBranchFreshness::find_all_by_workspace(pool).await?;
+        let mut checks = WorkspaceCheckResult::find_all_by_workspace(pool).await?;
+        let mut ci_checks = WorkspaceCiCheck::find_all_by_workspace(pool).await?;
+
         for ws in &mut workspaces {
+            if let Some(command) = repo_commands.remove(&ws.workspace.id) {
+                ws.repo_command = Some(command.status(pool).await?);
+            }
+            ws.child_progress = child_progress.remove(&ws.workspace.id);
+            ws.branch_freshness = branch_freshness
+                .remove(&ws.workspace.id)
+                .unwrap_or_default();
+            ws.checks = checks.remove(&ws.workspace.id).unwrap_or_default();
+            ws.ci_status = ci_checks
+                .remove(&ws.workspace.id)
+                .and_then(WorkspaceCiStatus::from_checks);
             if ws.workspace.name.is_none()
                 && let Some(prompt) = Self::get_first_user_message(pool, ws.workspace.id).await?
             {
@@ -608,6 +723,7 @@ impl Workspace {
                 w.pinned AS "pinned!: bool",
                 w.name,
                 w.worktree_deleted AS "worktree_deleted!: bool",
+                w.dev_server_port AS "dev_server_port: u16",
 
                 CASE WHEN EXISTS (
                     SELECT 1
@@ -627,7 +743,7 @@ impl Workspace {
                       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
                     ORDER BY ep.created_at DESC
                     LIMIT 1
-                ) IN ('failed','killed') THEN 1 ELSE 0 END AS "is_errored!: i64"
+                ) IN ('failed','killed','timedout') THEN 1 ELSE 0 END AS "is_errored!: i64"
 
             FROM workspaces w
             WHERE w.id = $1"#,
@@ -653,9 +769,17 @@ impl Workspace {
                 pinned: rec.pinned,
                 name: rec.name,
                 worktree_deleted: rec.worktree_deleted,
+                dev_server_port: rec.dev_server_port,
             },
             is_running: rec.is_running != 0,
             is_errored: rec.is_errored != 0,
+            repo_command: WorkspaceRepoCommand::status_for_workspace(pool, rec.id).await?,
+            child_progress: WorkspaceChild::progress_for_parent(pool, rec.id).await?,
+            branch_freshness: WorkspaceBranchFreshness::find_by_workspace_id(pool, rec.id).await?,
+            checks: WorkspaceCheckResult::find_by_workspace_id(pool, rec.id).await?,
+            ci_status: WorkspaceCiStatus::from_checks(
+                WorkspaceCiCheck::find_by_workspace_id(pool, rec.id).await?,
+            ),
         };
 
         if ws.workspace.name.is_none()
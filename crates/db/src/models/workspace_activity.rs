@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    execution_process::{ExecutionProcessRunReason, ExecutionProcessStatus},
+    merge::MergeStatus,
+    workspace_branch_freshness::AutoRebaseStatus,
+};
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceActivityKind {
+    ProcessStarted,
+    ProcessFinished,
+    ApprovalApproved,
+    ApprovalDenied,
+    /// A process moved a repo's HEAD.
+    Commit,
+    PrOpened,
+    PrMerged,
+    PrClosed,
+    AutoRebase,
+}
+
+/// A notable event in a workspace, gathered from the tables that already
+/// record it. Fields that don't apply to the kind are `None`.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceActivity {
+    /// Stable across requests; part of the paging cursor.
+    pub id: String,
+    pub kind: WorkspaceActivityKind,
+    pub occurred_at: DateTime<Utc>,
+    pub execution_process_id: Option<Uuid>,
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    pub process_status: Option<ExecutionProcessStatus>,
+    pub repo_id: Option<Uuid>,
+    pub before_sha: Option<String>,
+    /// New HEAD for commits, target branch commit for rebases.
+    pub after_sha: Option<String>,
+    pub pr_number: Option<i64>,
+    pub pr_url: Option<String>,
+    pub pr_status: Option<MergeStatus>,
+    pub rebase_status: Option<AutoRebaseStatus>,
+}
+
+/// Position in the feed to continue from: the last event of the previous
+/// page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceActivityCursor {
+    pub occurred_at: DateTime<Utc>,
+    pub id: String,
+}
+
+/// Matches the format the activity query normalizes timestamps to.
+const CURSOR_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+impl WorkspaceActivityCursor {
+    pub fn after(activity: &WorkspaceActivity) -> Self {
+        Self {
+            occurred_at: activity.occurred_at,
+            id: activity.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        format!(
+            "{}|{}",
+            self.occurred_at.format(CURSOR_TIME_FORMAT),
+            self.id
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (occurred_at, id) = cursor.split_once('|')?;
+        let occurred_at =
+            chrono::NaiveDateTime::parse_from_str(occurred_at, CURSOR_TIME_FORMAT).ok()?;
+        Some(Self {
+            occurred_at: occurred_at.and_utc(),
+            id: id.to_string(),
+        })
+    }
+}
+
+impl WorkspaceActivity {
+    /// The workspace's events, newest first, starting after `cursor`.
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        cursor: Option<&WorkspaceActivityCursor>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let cursor_occurred_at =
+            cursor.map(|cursor| cursor.occurred_at.format(CURSOR_TIME_FORMAT).to_string());
+        let cursor_id = cursor.map(|cursor| cursor.id.as_str());
+        // Timestamps are stored in more than one text format, so they're
+        // normalized before sorting. Processes dropped by a reset are left
+        // out, as in the conversation view. Only the latest automatic rebase
+        // per repo is kept.
+        sqlx::query_as!(
+            WorkspaceActivity,
+            r#"WITH processes AS (
+                 SELECT ep.* FROM execution_processes ep
+                 JOIN sessions s ON s.id = ep.session_id
+                 WHERE s.workspace_id = $1 AND ep.dropped = FALSE
+             ),
+             events AS (
+                 SELECT 'process_started:' || hex(id) AS id, 'process_started' AS kind,
+                        strftime('%Y-%m-%d %H:%M:%f', started_at) AS occurred_at,
+                        id AS execution_process_id, run_reason, NULL AS process_status,
+                        NULL AS repo_id, NULL AS before_sha, NULL AS after_sha,
+                        NULL AS pr_number, NULL AS pr_url, NULL AS pr_status, NULL AS rebase_status
+                 FROM processes
+                 UNION ALL
+                 SELECT 'process_finished:' || hex(id), 'process_finished',
+                        strftime('%Y-%m-%d %H:%M:%f', completed_at),
+                        id, run_reason, status, NULL, NULL, NULL, NULL, NULL, NULL, NULL
+                 FROM processes
+                 WHERE completed_at IS NOT NULL AND status != 'running'
+                 UNION ALL
+                 SELECT 'commit:' || hex(rs.id), 'commit',
+                        strftime('%Y-%m-%d %H:%M:%f', COALESCE(p.completed_at, rs.updated_at)),
+                        p.id, p.run_reason, NULL, rs.repo_id, rs.before_head_commit,
+                        rs.after_head_commit, NULL, NULL, NULL, NULL
+                 FROM execution_process_repo_states rs
+                 JOIN processes p ON p.id = rs.execution_process_id
+                 WHERE rs.after_head_commit IS NOT NULL
+                   AND rs.after_head_commit IS NOT rs.before_head_commit
+                 UNION ALL
+                 SELECT 'approval:' || id, kind, strftime('%Y-%m-%d %H:%M:%f', occurred_at),
+                        NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL
+                 FROM activity_events
+                 WHERE workspace_id = $1 AND kind IN ('approval_approved', 'approval_denied')
+                 UNION ALL
+                 SELECT 'pr_opened:' || id, 'pr_opened', strftime('%Y-%m-%d %H:%M:%f', created_at),
+                        NULL, NULL, NULL, repo_id, NULL, NULL, pr_number, pr_url, pr_status, NULL
+                 FROM pull_requests
+                 WHERE workspace_id = $1
+                 UNION ALL
+                 SELECT 'pr_merged:' || id, 'pr_merged', strftime('%Y-%m-%d %H:%M:%f', merged_at),
+                        NULL, NULL, NULL, repo_id, NULL, merge_commit_sha, pr_number, pr_url,
+                        pr_status, NULL
+                 FROM pull_requests
+                 WHERE workspace_id = $1 AND merged_at IS NOT NULL
+                 UNION ALL
+                 SELECT 'pr_closed:' || id, 'pr_closed', strftime('%Y-%m-%d %H:%M:%f', updated_at),
+                        NULL, NULL, NULL, repo_id, NULL, NULL, pr_number, pr_url, pr_status, NULL
+                 FROM pull_requests
+                 WHERE workspace_id = $1 AND pr_status = 'closed'
+                 UNION ALL
+                 SELECT 'auto_rebase:' || hex(repo_id) || ':' || auto_rebase_target_oid, 'auto_rebase',
+                        strftime('%Y-%m-%d %H:%M:%f', auto_rebased_at),
+                        NULL, NULL, NULL, repo_id, NULL, auto_rebase_target_oid, NULL, NULL, NULL,
+                        auto_rebase_status
+                 FROM workspace_branch_freshness
+                 WHERE workspace_id = $1 AND auto_rebased_at IS NOT NULL
+             )
+             SELECT id as "id!: String",
+                    kind as "kind!: WorkspaceActivityKind",
+                    occurred_at as "occurred_at!: DateTime<Utc>",
+                    execution_process_id as "execution_process_id: Uuid",
+                    run_reason as "run_reason: ExecutionProcessRunReason",
+                    process_status as "process_status: ExecutionProcessStatus",
+                    repo_id as "repo_id: Uuid",
+                    before_sha as "before_sha: String",
+                    after_sha as "after_sha: String",
+                    pr_number as "pr_number: i64",
+                    pr_url as "pr_url: String",
+                    pr_status as "pr_status: MergeStatus",
+                    rebase_status as "rebase_status: AutoRebaseStatus"
+             FROM events
+             WHERE occurred_at IS NOT NULL
+               AND ($2 IS NULL OR (occurred_at, id) < ($2, $3))
+             ORDER BY occurred_at DESC, id DESC
+             LIMIT $4"#,
+            workspace_id,
+            cursor_occurred_at,
+            cursor_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = WorkspaceActivityCursor {
+            occurred_at: chrono::NaiveDate::from_ymd_opt(2026, 4, 14)
+                .unwrap()
+                .and_hms_milli_opt(9, 30, 5, 250)
+                .unwrap()
+                .and_utc(),
+            id: "commit:AB12".to_string(),
+        };
+        assert_eq!(cursor.encode(), "2026-04-14 09:30:05.250|commit:AB12");
+        assert_eq!(
+            WorkspaceActivityCursor::decode(&cursor.encode()),
+            Some(cursor)
+        );
+        assert_eq!(WorkspaceActivityCursor::decode("garbage"), None);
+    }
+}
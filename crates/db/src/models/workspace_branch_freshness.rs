@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AutoRebaseStatus {
+    Rebased,
+    /// Aborted because of conflicts; left for a manual rebase.
+    Conflicts,
+}
+
+/// How far a workspace branch has drifted from one repo's target branch, as
+/// last checked by the branch freshness monitor.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceBranchFreshness {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    pub commits_ahead: u32,
+    /// Commits on the target branch the workspace branch doesn't have yet.
+    pub commits_behind: u32,
+    pub checked_at: DateTime<Utc>,
+    /// Outcome of the latest automatic rebase onto the target branch, if any.
+    pub auto_rebase_status: Option<AutoRebaseStatus>,
+    /// Target branch commit the latest automatic rebase was attempted onto.
+    pub auto_rebase_target_oid: Option<String>,
+    pub auto_rebased_at: Option<DateTime<Utc>>,
+}
+
+impl WorkspaceBranchFreshness {
+    /// Current freshness of every workspace, keyed by workspace. Rows for
+    /// repos since removed from the workspace, or checked against a target
+    /// branch that has since changed, are left out.
+    pub async fn find_all_by_workspace(
+        pool: &SqlitePool,
+    ) -> Result<HashMap<Uuid, Vec<Self>>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            WorkspaceBranchFreshness,
+            r#"SELECT f.workspace_id as "workspace_id!: Uuid",
+                      f.repo_id as "repo_id!: Uuid",
+                      f.target_branch,
+                      f.commits_ahead as "commits_ahead!: u32",
+                      f.commits_behind as "commits_behind!: u32",
+                      f.checked_at as "checked_at!: DateTime<Utc>",
+                      f.auto_rebase_status as "auto_rebase_status: AutoRebaseStatus",
+                      f.auto_rebase_target_oid,
+                      f.auto_rebased_at as "auto_rebased_at: DateTime<Utc>"
+               FROM workspace_branch_freshness f
+               JOIN workspace_repos wr
+                 ON wr.workspace_id = f.workspace_id
+                AND wr.repo_id = f.repo_id
+                AND wr.target_branch = f.target_branch
+               ORDER BY f.workspace_id, f.repo_id"#
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut by_workspace: HashMap<Uuid, Vec<Self>> = HashMap::new();
+        for row in rows {
+            by_workspace.entry(row.workspace_id).or_default().push(row);
+        }
+        Ok(by_workspace)
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceBranchFreshness,
+            r#"SELECT f.workspace_id as "workspace_id!: Uuid",
+                      f.repo_id as "repo_id!: Uuid",
+                      f.target_branch,
+                      f.commits_ahead as "commits_ahead!: u32",
+                      f.commits_behind as "commits_behind!: u32",
+                      f.checked_at as "checked_at!: DateTime<Utc>",
+                      f.auto_rebase_status as "auto_rebase_status: AutoRebaseStatus",
+                      f.auto_rebase_target_oid,
+                      f.auto_rebased_at as "auto_rebased_at: DateTime<Utc>"
+               FROM workspace_branch_freshness f
+               JOIN workspace_repos wr
+                 ON wr.workspace_id = f.workspace_id
+                AND wr.repo_id = f.repo_id
+                AND wr.target_branch = f.target_branch
+               WHERE f.workspace_id = $1
+               ORDER BY f.repo_id"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record a check. Returns whether the counts or the target branch
+    /// differ from the previous check.
+    pub async fn record(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        target_branch: &str,
+        commits_ahead: u32,
+        commits_behind: u32,
+    ) -> Result<bool, sqlx::Error> {
+        let previous = sqlx::query!(
+            r#"SELECT target_branch,
+                      commits_ahead as "commits_ahead!: u32",
+                      commits_behind as "commits_behind!: u32"
+               FROM workspace_branch_freshness
+               WHERE workspace_id = $1 AND repo_id = $2"#,
+            workspace_id,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let now = Utc::now();
+        sqlx::query!(
+            "INSERT INTO workspace_branch_freshness
+                 (workspace_id, repo_id, target_branch, commits_ahead, commits_behind, checked_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT(workspace_id, repo_id) DO UPDATE SET
+                 target_branch = excluded.target_branch,
+                 commits_ahead = excluded.commits_ahead,
+                 commits_behind = excluded.commits_behind,
+                 checked_at = excluded.checked_at",
+            workspace_id,
+            repo_id,
+            target_branch,
+            commits_ahead,
+            commits_behind,
+            now
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(!previous.is_some_and(|previous| {
+            previous.target_branch == target_branch
+                && previous.commits_ahead == commits_ahead
+                && previous.commits_behind == commits_behind
+        }))
+    }
+
+    /// Record the outcome of an automatic rebase onto `target_oid`.
+    pub async fn record_auto_rebase(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        status: AutoRebaseStatus,
+        target_oid: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "UPDATE workspace_branch_freshness
+             SET auto_rebase_status = $1, auto_rebase_target_oid = $2, auto_rebased_at = $3
+             WHERE workspace_id = $4 AND repo_id = $5",
+            status,
+            target_oid,
+            now,
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
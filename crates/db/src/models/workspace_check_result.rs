@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::repo_check_command::RepoCheckCommand;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "workspace_check_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceCheckStatus {
+    Pending,
+    Running,
+    Passed,
+    Failed,
+    /// Not run, or not finished, because the run was stopped.
+    Skipped,
+}
+
+/// The outcome of one repo check in the latest check run of a workspace.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceCheckResult {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub name: String,
+    pub command: String,
+    pub position: i64,
+    pub status: WorkspaceCheckStatus,
+    /// The process the check ran as; its logs hold the check's output.
+    pub execution_process_id: Option<Uuid>,
+    pub exit_code: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl WorkspaceCheckResult {
+    /// Results of the latest check run of every workspace, keyed by
+    /// workspace.
+    pub async fn find_all_by_workspace(
+        pool: &SqlitePool,
+    ) -> Result<HashMap<Uuid, Vec<Self>>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            WorkspaceCheckResult,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      name,
+                      command,
+                      position as "position!: i64",
+                      status as "status!: WorkspaceCheckStatus",
+                      execution_process_id as "execution_process_id: Uuid",
+                      exit_code,
+                      started_at as "started_at: DateTime<Utc>",
+                      completed_at as "completed_at: DateTime<Utc>"
+               FROM workspace_check_results
+               ORDER BY workspace_id, position"#
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut by_workspace: HashMap<Uuid, Vec<Self>> = HashMap::new();
+        for row in rows {
+            by_workspace.entry(row.workspace_id).or_default().push(row);
+        }
+        Ok(by_workspace)
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceCheckResult,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      name,
+                      command,
+                      position as "position!: i64",
+                      status as "status!: WorkspaceCheckStatus",
+                      execution_process_id as "execution_process_id: Uuid",
+                      exit_code,
+                      started_at as "started_at: DateTime<Utc>",
+                      completed_at as "completed_at: DateTime<Utc>"
+               FROM workspace_check_results
+               WHERE workspace_id = $1
+               ORDER BY position"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Start a check run of `checks`, in order, replacing the previous run.
+    pub async fn start(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        checks: &[RepoCheckCommand],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM workspace_check_results WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (position, check) in checks.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                "INSERT INTO workspace_check_results
+                     (id, workspace_id, repo_id, name, command, position)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                id,
+                workspace_id,
+                check.repo_id,
+                check.name,
+                check.command,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_workspace_id(pool, workspace_id).await
+    }
+
+    /// Mark the next pending check of the workspace as running as
+    /// `execution_process_id`. Checks run in order, so the process started is
+    /// always the next pending one.
+    pub async fn claim_next(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            WorkspaceCheckResult,
+            r#"UPDATE workspace_check_results
+               SET status = 'running', execution_process_id = $1, started_at = $2
+               WHERE id = (
+                   SELECT id FROM workspace_check_results
+                   WHERE workspace_id = $3 AND status = 'pending'
+                   ORDER BY position
+                   LIMIT 1
+               )
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         name,
+                         command,
+                         position as "position!: i64",
+                         status as "status!: WorkspaceCheckStatus",
+                         execution_process_id as "execution_process_id: Uuid",
+                         exit_code,
+                         started_at as "started_at: DateTime<Utc>",
+                         completed_at as "completed_at: DateTime<Utc>""#,
+            execution_process_id,
+            now,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record the outcome of the check that ran as `execution_process_id`.
+    pub async fn complete(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        passed: bool,
+        exit_code: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let status = if passed {
+            WorkspaceCheckStatus::Passed
+        } else {
+            WorkspaceCheckStatus::Failed
+        };
+        let now = Utc::now();
+        sqlx::query!(
+            "UPDATE workspace_check_results
+             SET status = $1, exit_code = $2, completed_at = $3
+             WHERE execution_process_id = $4 AND status = 'running'",
+            status,
+            exit_code,
+            now,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the checks of the workspace that haven't finished as skipped.
+    pub async fn skip_unfinished(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_check_results SET status = 'skipped'
+             WHERE workspace_id = $1 AND status IN ('pending', 'running')",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Link from a child workspace to the parent it was split off from. The
+/// child's repos target the parent's branch.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceChild {
+    pub child_workspace_id: Uuid,
+    pub parent_workspace_id: Uuid,
+    /// The part of the prompt specific to this child.
+    pub prompt_fragment: String,
+    /// Children are merged into the parent in ascending position.
+    pub position: i64,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Progress of a parent workspace's children, by the state of each child's
+/// latest coding agent run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ChildWorkspaceProgress {
+    pub total: u32,
+    pub running: u32,
+    pub completed: u32,
+    /// Failed, killed, timed out or interrupted.
+    pub failed: u32,
+    /// Merged into the parent's branch.
+    pub merged: u32,
+}
+
+struct ChildProgressRow {
+    parent_workspace_id: Uuid,
+    total: i64,
+    running: i64,
+    completed: i64,
+    failed: i64,
+    merged: i64,
+}
+
+impl From<ChildProgressRow> for ChildWorkspaceProgress {
+    fn from(row: ChildProgressRow) -> Self {
+        Self {
+            total: row.total as u32,
+            running: row.running as u32,
+            completed: row.completed as u32,
+            failed: row.failed as u32,
+            merged: row.merged as u32,
+        }
+    }
+}
+
+impl WorkspaceChild {
+    /// The parent's children in merge order.
+    pub async fn find_by_parent(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceChild,
+            r#"SELECT child_workspace_id as "child_workspace_id!: Uuid",
+                      parent_workspace_id as "parent_workspace_id!: Uuid",
+                      prompt_fragment,
+                      position as "position!: i64",
+                      merged_at as "merged_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_children
+               WHERE parent_workspace_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            parent_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_child(
+        pool: &SqlitePool,
+        child_workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceChild,
+            r#"SELECT child_workspace_id as "child_workspace_id!: Uuid",
+                      parent_workspace_id as "parent_workspace_id!: Uuid",
+                      prompt_fragment,
+                      position as "position!: i64",
+                      merged_at as "merged_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_children
+               WHERE child_workspace_id = $1"#,
+            child_workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Link a child to its parent after the parent's existing children.
+    pub async fn create(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+        child_workspace_id: Uuid,
+        prompt_fragment: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceChild,
+            r#"INSERT INTO workspace_children
+                   (child_workspace_id, parent_workspace_id, prompt_fragment, position)
+               VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0)
+                                    FROM workspace_children WHERE parent_workspace_id = $2))
+               RETURNING child_workspace_id as "child_workspace_id!: Uuid",
+                         parent_workspace_id as "parent_workspace_id!: Uuid",
+                         prompt_fragment,
+                         position as "position!: i64",
+                         merged_at as "merged_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            child_workspace_id,
+            parent_workspace_id,
+            prompt_fragment
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_merged(
+        pool: &SqlitePool,
+        child_workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_children SET merged_at = datetime('now', 'subsec')
+             WHERE child_workspace_id = $1",
+            child_workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Progress of every parent workspace's children, keyed by parent.
+    pub async fn progress_by_parent(
+        pool: &SqlitePool,
+    ) -> Result<HashMap<Uuid, ChildWorkspaceProgress>, sqlx::Error> {
+        let rows = Self::progress_rows(pool, None).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.parent_workspace_id, row.into()))
+            .collect())
+    }
+
+    /// Progress of the workspace's children, if it has any.
+    pub async fn progress_for_parent(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+    ) -> Result<Option<ChildWorkspaceProgress>, sqlx::Error> {
+        let rows = Self::progress_rows(pool, Some(parent_workspace_id)).await?;
+        Ok(rows.into_iter().next().map(Into::into))
+    }
+
+    /// Children per parent with the status of their latest coding agent run,
+    /// for one parent or, without `parent_workspace_id`, all of them.
+    async fn progress_rows(
+        pool: &SqlitePool,
+        parent_workspace_id: Option<Uuid>,
+    ) -> Result<Vec<ChildProgressRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ChildProgressRow,
+            r#"SELECT parent_workspace_id as "parent_workspace_id!: Uuid",
+                      COUNT(*) as "total!: i64",
+                      SUM(CASE WHEN status = 'running' THEN 1 ELSE 0 END) as "running!: i64",
+                      SUM(CASE WHEN status = 'completed' THEN 1 ELSE 0 END) as "completed!: i64",
+                      SUM(CASE WHEN status IN ('failed', 'killed', 'timedout', 'interrupted')
+                               THEN 1 ELSE 0 END) as "failed!: i64",
+                      SUM(CASE WHEN merged_at IS NOT NULL THEN 1 ELSE 0 END) as "merged!: i64"
+               FROM (SELECT wc.parent_workspace_id, wc.merged_at,
+                            (SELECT ep.status
+                             FROM sessions s
+                             JOIN execution_processes ep ON ep.session_id = s.id
+                             WHERE s.workspace_id = wc.child_workspace_id
+                               AND ep.run_reason = 'codingagent'
+                             ORDER BY ep.created_at DESC
+                             LIMIT 1) AS status
+                     FROM workspace_children wc
+                     WHERE $1 IS NULL OR wc.parent_workspace_id = $1)
+               GROUP BY parent_workspace_id"#,
+            parent_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "ci_state", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CiState {
+    Pending,
+    Success,
+    Failure,
+}
+
+/// The latest state of one CI check (a workflow, a check run, a commit
+/// status context) on a workspace branch, as reported by a CI webhook.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceCiCheck {
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub state: CiState,
+    /// Commit the check ran on.
+    pub head_sha: Option<String>,
+    /// Page with the check's details on the CI provider.
+    pub url: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The CI checks of a workspace branch's latest reported commit.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceCiStatus {
+    /// Failure if any check failed, pending if any is still running.
+    pub state: CiState,
+    pub checks: Vec<WorkspaceCiCheck>,
+}
+
+impl WorkspaceCiStatus {
+    pub fn from_checks(checks: Vec<WorkspaceCiCheck>) -> Option<Self> {
+        if checks.is_empty() {
+            return None;
+        }
+        let state = if checks.iter().any(|check| check.state == CiState::Failure) {
+            CiState::Failure
+        } else if checks.iter().any(|check| check.state == CiState::Pending) {
+            CiState::Pending
+        } else {
+            CiState::Success
+        };
+        Some(Self { state, checks })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordCiCheck {
+    pub name: String,
+    pub state: CiState,
+    pub head_sha: Option<String>,
+    pub url: Option<String>,
+}
+
+impl WorkspaceCiCheck {
+    /// Checks of every workspace, keyed by workspace.
+    pub async fn find_all_by_workspace(
+        pool: &SqlitePool,
+    ) -> Result<HashMap<Uuid, Vec<Self>>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            WorkspaceCiCheck,
+            r#"SELECT workspace_id AS "workspace_id!: Uuid",
+                      name,
+                      state AS "state!: CiState",
+                      head_sha,
+                      url,
+                      updated_at AS "updated_at!: DateTime<Utc>"
+               FROM workspace_ci_checks
+               ORDER BY workspace_id, name"#
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut by_workspace: HashMap<Uuid, Vec<Self>> = HashMap::new();
+        for row in rows {
+            by_workspace.entry(row.workspace_id).or_default().push(row);
+        }
+        Ok(by_workspace)
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceCiCheck,
+            r#"SELECT workspace_id AS "workspace_id!: Uuid",
+                      name,
+                      state AS "state!: CiState",
+                      head_sha,
+                      url,
+                      updated_at AS "updated_at!: DateTime<Utc>"
+               FROM workspace_ci_checks
+               WHERE workspace_id = $1
+               ORDER BY name"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Workspaces on `branch` with a repo named `repo_name`, or any repo when
+    /// it's `None`.
+    pub async fn workspace_ids_for_branch(
+        pool: &SqlitePool,
+        branch: &str,
+        repo_name: Option<&str>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT w.id AS "id!: Uuid"
+               FROM workspaces w
+               JOIN workspace_repos wr ON wr.workspace_id = w.id
+               JOIN repos r ON r.id = wr.repo_id
+               WHERE w.branch = $1
+                 AND ($2 IS NULL OR r.name = $2 OR r.display_name = $2)"#,
+            branch,
+            repo_name
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record a check's state. A check on a new head commit drops the checks
+    /// of the commits before it.
+    pub async fn record(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        check: &RecordCiCheck,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        if let Some(head_sha) = &check.head_sha {
+            sqlx::query!(
+                "DELETE FROM workspace_ci_checks
+                 WHERE workspace_id = $1 AND head_sha IS NOT NULL AND head_sha <> $2",
+                workspace_id,
+                head_sha
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let updated_at = Utc::now();
+        sqlx::query!(
+            "INSERT INTO workspace_ci_checks (workspace_id, name, state, head_sha, url, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT(workspace_id, name) DO UPDATE SET
+                 state = excluded.state,
+                 head_sha = excluded.head_sha,
+                 url = excluded.url,
+                 updated_at = excluded.updated_at",
+            workspace_id,
+            check.name,
+            check.state,
+            check.head_sha,
+            check.url,
+            updated_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+}
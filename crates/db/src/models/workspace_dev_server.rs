@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Which scripts the preview proxy injects into proxied pages.
+#[derive(Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "preview_script_injection", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewScriptInjection {
+    /// Pages are served unmodified.
+    None,
+    /// Only the scripts bundled with Vibe Kanban, without the Eruda console.
+    Local,
+    /// The bundled scripts plus Eruda.
+    #[default]
+    Full,
+}
+
+/// Dev server settings for a single workspace, overriding the dev server
+/// scripts configured on its repos.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceDevServer {
+    pub workspace_id: Uuid,
+    /// Runs instead of the repos' dev server scripts.
+    pub script: Option<String>,
+    /// Port the dev server is expected to listen on, exported as `PORT`.
+    /// Published as the workspace's `dev_server_port` while it runs instead
+    /// of a port detected in its output.
+    pub port: Option<u16>,
+    /// Overrides the configured preview script injection for this
+    /// workspace's dev server port.
+    pub script_injection: Option<PreviewScriptInjection>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct UpdateWorkspaceDevServer {
+    pub script: Option<String>,
+    pub port: Option<u16>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub script_injection: Option<PreviewScriptInjection>,
+}
+
+impl UpdateWorkspaceDevServer {
+    /// Whether the update leaves nothing to override.
+    pub fn is_empty(&self) -> bool {
+        self.script.as_deref().is_none_or(|s| s.trim().is_empty())
+            && self.port.is_none()
+            && self.script_injection.is_none()
+    }
+}
+
+impl WorkspaceDevServer {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceDevServer,
+            r#"SELECT workspace_id as "workspace_id!: Uuid",
+                      script,
+                      port as "port: u16",
+                      script_injection as "script_injection: PreviewScriptInjection",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspace_dev_servers
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Replace the workspace's override, or remove it if `data` is empty.
+    pub async fn set(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        data: &UpdateWorkspaceDevServer,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        if data.is_empty() {
+            Self::delete(pool, workspace_id).await?;
+            return Ok(None);
+        }
+
+        let script = data
+            .script
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        sqlx::query_as!(
+            WorkspaceDevServer,
+            r#"INSERT INTO workspace_dev_servers (workspace_id, script, port, script_injection)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(workspace_id) DO UPDATE SET
+                   script = excluded.script,
+                   port = excluded.port,
+                   script_injection = excluded.script_injection,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING workspace_id as "workspace_id!: Uuid",
+                         script,
+                         port as "port: u16",
+                         script_injection as "script_injection: PreviewScriptInjection",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            workspace_id,
+            script,
+            data.port,
+            data.script_injection
+        )
+        .fetch_one(pool)
+        .await
+        .map(Some)
+    }
+
+    pub async fn delete(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM workspace_dev_servers WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use sqlx::{SqlitePool, types::Json};
+use uuid::Uuid;
+
+/// Extra environment variables injected into every process run in a
+/// workspace, e.g. those of the template it was started from.
+pub struct WorkspaceEnvVars;
+
+impl WorkspaceEnvVars {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        let vars: Option<Json<HashMap<String, String>>> =
+            sqlx::query_scalar("SELECT vars FROM workspace_env_vars WHERE workspace_id = ?")
+                .bind(workspace_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(vars.map(|Json(vars)| vars).unwrap_or_default())
+    }
+
+    /// Replace the workspace's variables; an empty map removes them.
+    pub async fn set(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        vars: &HashMap<String, String>,
+    ) -> Result<(), sqlx::Error> {
+        if vars.is_empty() {
+            sqlx::query("DELETE FROM workspace_env_vars WHERE workspace_id = ?")
+                .bind(workspace_id)
+                .execute(pool)
+                .await?;
+            return Ok(());
+        }
+
+        sqlx::query(
+            "INSERT INTO workspace_env_vars (workspace_id, vars)
+             VALUES (?, ?)
+             ON CONFLICT(workspace_id) DO UPDATE SET
+                 vars = excluded.vars,
+                 updated_at = datetime('now', 'subsec')",
+        )
+        .bind(workspace_id)
+        .bind(Json(vars))
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// `Outgoing` workspaces were handed off to another instance and are
+/// read-only here; `Incoming` ones were claimed from another instance.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceHandoffDirection {
+    Outgoing,
+    Incoming,
+}
+
+/// Local record of a workspace handoff published to the remote backend.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceHandoffLink {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub remote_handoff_id: Uuid,
+    pub direction: WorkspaceHandoffDirection,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WorkspaceHandoffLink {
+    pub async fn find_by_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        direction: WorkspaceHandoffDirection,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceHandoffLink,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      remote_handoff_id as "remote_handoff_id!: Uuid",
+                      direction as "direction!: WorkspaceHandoffDirection",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_handoffs
+               WHERE workspace_id = $1 AND direction = $2"#,
+            workspace_id,
+            direction
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Whether the workspace was handed off and must not be changed here.
+    pub async fn is_handed_off(pool: &SqlitePool, workspace_id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM workspace_handoffs
+                   WHERE workspace_id = $1 AND direction = 'outgoing'
+               ) as "handed_off!: bool""#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        remote_handoff_id: Uuid,
+        direction: WorkspaceHandoffDirection,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceHandoffLink,
+            r#"INSERT INTO workspace_handoffs (id, workspace_id, remote_handoff_id, direction)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         remote_handoff_id as "remote_handoff_id!: Uuid",
+                         direction as "direction!: WorkspaceHandoffDirection",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            remote_handoff_id,
+            direction
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM workspace_handoffs WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
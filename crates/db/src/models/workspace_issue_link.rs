@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::repo_issue_tracker::IssueTrackerKind;
+
+/// An issue imported from a repo's issue tracker as a workspace.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceIssueLink {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub provider: IssueTrackerKind,
+    /// Number or key shown in the tracker, e.g. `42` or `ENG-42`.
+    pub issue_key: String,
+    pub title: String,
+    pub url: String,
+    /// When the issue was closed after the workspace's PRs were merged.
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateWorkspaceIssueLink {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub provider: IssueTrackerKind,
+    pub issue_key: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl WorkspaceIssueLink {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateWorkspaceIssueLink,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceIssueLink,
+            r#"INSERT INTO workspace_issue_links
+                   (id, workspace_id, repo_id, provider, issue_key, title, url)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         provider as "provider!: IssueTrackerKind",
+                         issue_key,
+                         title,
+                         url,
+                         closed_at as "closed_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.repo_id,
+            data.provider,
+            data.issue_key,
+            data.title,
+            data.url
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceIssueLink,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      provider as "provider!: IssueTrackerKind",
+                      issue_key,
+                      title,
+                      url,
+                      closed_at as "closed_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_issue_links
+               WHERE workspace_id = $1
+               ORDER BY created_at"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_closed(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "UPDATE workspace_issue_links SET closed_at = $1 WHERE id = $2",
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
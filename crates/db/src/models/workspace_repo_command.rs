@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{execution_process::ExecutionProcessStatus, repo::Repo, workspace_repo::WorkspaceRepo};
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "repo_command_mode", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RepoCommandMode {
+    /// One repo after the other, stopping at the first failure.
+    Sequential,
+    /// All repos at once.
+    Parallel,
+}
+
+/// The latest command run across the repos of a workspace.
+#[derive(Debug, Clone, FromRow)]
+pub struct WorkspaceRepoCommand {
+    pub workspace_id: Uuid,
+    pub mode: RepoCommandMode,
+    pub script: String,
+    pub repo_ids: Json<Vec<Uuid>>,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoCommandStepStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Killed,
+    /// Not run because an earlier repo failed in sequential mode.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RepoCommandRepoStatus {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub status: RepoCommandStepStatus,
+    pub execution_process_id: Option<Uuid>,
+    pub exit_code: Option<i64>,
+}
+
+/// Per-repo progress of the latest repo command, pushed with the workspace
+/// in the workspace event stream.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RepoCommandStatus {
+    pub mode: RepoCommandMode,
+    pub script: String,
+    pub started_at: DateTime<Utc>,
+    pub repos: Vec<RepoCommandRepoStatus>,
+}
+
+struct RepoCommandProcess {
+    id: Uuid,
+    status: ExecutionProcessStatus,
+    exit_code: Option<i64>,
+    working_dir: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl WorkspaceRepoCommand {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceRepoCommand,
+            r#"SELECT workspace_id as "workspace_id!: Uuid",
+                      mode as "mode!: RepoCommandMode",
+                      script,
+                      repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                      started_at as "started_at!: DateTime<Utc>"
+               FROM workspace_repo_commands"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceRepoCommand,
+            r#"SELECT workspace_id as "workspace_id!: Uuid",
+                      mode as "mode!: RepoCommandMode",
+                      script,
+                      repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                      started_at as "started_at!: DateTime<Utc>"
+               FROM workspace_repo_commands
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record a new run, replacing the workspace's previous one.
+    pub async fn start(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        mode: RepoCommandMode,
+        script: &str,
+        repo_ids: &[Uuid],
+    ) -> Result<Self, sqlx::Error> {
+        let repo_ids = Json(repo_ids);
+        let started_at = Utc::now();
+        sqlx::query_as!(
+            WorkspaceRepoCommand,
+            r#"INSERT INTO workspace_repo_commands (workspace_id, mode, script, repo_ids, started_at)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(workspace_id) DO UPDATE SET
+                   mode = excluded.mode,
+                   script = excluded.script,
+                   repo_ids = excluded.repo_ids,
+                   started_at = excluded.started_at
+               RETURNING workspace_id as "workspace_id!: Uuid",
+                         mode as "mode!: RepoCommandMode",
+                         script,
+                         repo_ids as "repo_ids!: Json<Vec<Uuid>>",
+                         started_at as "started_at!: DateTime<Utc>""#,
+            workspace_id,
+            mode,
+            script,
+            repo_ids,
+            started_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Status of each targeted repo, matched to the run's processes by the
+    /// repo directory they ran in.
+    pub async fn status(&self, pool: &SqlitePool) -> Result<RepoCommandStatus, sqlx::Error> {
+        let processes = sqlx::query_as!(
+            RepoCommandProcess,
+            r#"SELECT ep.id as "id!: Uuid",
+                      ep.status as "status!: ExecutionProcessStatus",
+                      ep.exit_code,
+                      json_extract(ep.executor_action, '$.typ.working_dir') as "working_dir?: String",
+                      ep.created_at as "created_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = $1
+                 AND ep.run_reason = 'repocommand'
+                 AND ep.dropped = FALSE
+               ORDER BY ep.created_at ASC"#,
+            self.workspace_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut latest: HashMap<String, RepoCommandProcess> = HashMap::new();
+        for process in processes {
+            if process.created_at < self.started_at {
+                continue;
+            }
+            if let Some(dir) = process.working_dir.clone() {
+                latest.insert(dir, process);
+            }
+        }
+
+        let repos: HashMap<Uuid, Repo> =
+            WorkspaceRepo::find_repos_for_workspace(pool, self.workspace_id)
+                .await?
+                .into_iter()
+                .map(|repo| (repo.id, repo))
+                .collect();
+
+        let mut stopped = false;
+        let mut statuses = Vec::new();
+        // Repos removed from the workspace since the run started are left out.
+        for repo in self.repo_ids.iter().filter_map(|id| repos.get(id)) {
+            let (status, execution_process_id, exit_code) = match latest.get(&repo.name) {
+                Some(process) => {
+                    let status = match process.status {
+                        ExecutionProcessStatus::Running => RepoCommandStepStatus::Running,
+                        ExecutionProcessStatus::Completed => RepoCommandStepStatus::Completed,
+                        ExecutionProcessStatus::Failed => RepoCommandStepStatus::Failed,
+                        ExecutionProcessStatus::Killed
+                        | ExecutionProcessStatus::TimedOut
+                        | ExecutionProcessStatus::Interrupted => RepoCommandStepStatus::Killed,
+                    };
+                    (status, Some(process.id), process.exit_code)
+                }
+                None if stopped => (RepoCommandStepStatus::Skipped, None, None),
+                None => (RepoCommandStepStatus::Pending, None, None),
+            };
+            if self.mode == RepoCommandMode::Sequential
+                && matches!(
+                    status,
+                    RepoCommandStepStatus::Failed | RepoCommandStepStatus::Killed
+                )
+            {
+                stopped = true;
+            }
+            statuses.push(RepoCommandRepoStatus {
+                repo_id: repo.id,
+                repo_name: repo.name.clone(),
+                status,
+                execution_process_id,
+                exit_code,
+            });
+        }
+
+        Ok(RepoCommandStatus {
+            mode: self.mode,
+            script: self.script.clone(),
+            started_at: self.started_at,
+            repos: statuses,
+        })
+    }
+
+    pub async fn status_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<RepoCommandStatus>, sqlx::Error> {
+        match Self::find_by_workspace_id(pool, workspace_id).await? {
+            Some(command) => command.status(pool).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
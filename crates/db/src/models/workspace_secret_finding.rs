@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A possible credential in the changes a workspace branch adds to a repo.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceSecretFinding {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub file_path: String,
+    pub line: Option<u32>,
+    /// The scanner rule that matched, e.g. `aws-access-key-id`.
+    pub rule_id: String,
+    /// The start of the matched text, with the rest masked.
+    pub redacted: String,
+    /// When someone chose to push with this finding anyway.
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateWorkspaceSecretFinding {
+    pub file_path: String,
+    pub line: Option<u32>,
+    pub rule_id: String,
+    pub redacted: String,
+}
+
+impl WorkspaceSecretFinding {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceSecretFinding,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      file_path,
+                      line as "line: u32",
+                      rule_id,
+                      redacted,
+                      confirmed_at as "confirmed_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_secret_findings
+               WHERE workspace_id = $1
+               ORDER BY file_path ASC, line ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the repo's findings with those of a new scan. Findings that
+    /// were confirmed before stay confirmed, so a push confirmed once isn't
+    /// blocked again by the same credential.
+    pub async fn replace_for_repo(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        findings: &[CreateWorkspaceSecretFinding],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let confirmed: HashSet<(String, String, String)> = sqlx::query!(
+            "SELECT file_path, rule_id, redacted FROM workspace_secret_findings
+             WHERE workspace_id = $1 AND repo_id = $2 AND confirmed_at IS NOT NULL",
+            workspace_id,
+            repo_id
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| (row.file_path, row.rule_id, row.redacted))
+        .collect();
+
+        sqlx::query!(
+            "DELETE FROM workspace_secret_findings WHERE workspace_id = $1 AND repo_id = $2",
+            workspace_id,
+            repo_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let mut created = Vec::with_capacity(findings.len());
+        for finding in findings {
+            let was_confirmed = confirmed.contains(&(
+                finding.file_path.clone(),
+                finding.rule_id.clone(),
+                finding.redacted.clone(),
+            ));
+            let id = Uuid::new_v4();
+            let row = sqlx::query_as!(
+                WorkspaceSecretFinding,
+                r#"INSERT INTO workspace_secret_findings
+                       (id, workspace_id, repo_id, file_path, line, rule_id, redacted, confirmed_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7,
+                       CASE WHEN $8 THEN datetime('now', 'subsec') END)
+                   RETURNING id as "id!: Uuid",
+                             workspace_id as "workspace_id!: Uuid",
+                             repo_id as "repo_id!: Uuid",
+                             file_path,
+                             line as "line: u32",
+                             rule_id,
+                             redacted,
+                             confirmed_at as "confirmed_at: DateTime<Utc>",
+                             created_at as "created_at!: DateTime<Utc>""#,
+                id,
+                workspace_id,
+                repo_id,
+                finding.file_path,
+                finding.line,
+                finding.rule_id,
+                finding.redacted,
+                was_confirmed
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            created.push(row);
+        }
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn confirm_for_repo(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_secret_findings SET confirmed_at = datetime('now', 'subsec')
+             WHERE workspace_id = $1 AND repo_id = $2 AND confirmed_at IS NULL",
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
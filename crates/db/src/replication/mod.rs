@@ -0,0 +1,724 @@
+//! Warm standby replication of the SQLite database.
+//!
+//! When `VK_DB_REPLICA` names a directory or an `s3://bucket/prefix` target,
+//! the database runs in WAL mode and a [`Replicator`] ships every committed
+//! WAL frame to the target, in the spirit of Litestream. Automatic
+//! checkpoints are disabled while the replicator is running and syncing. The replica is a series of generations, each a full
+//! snapshot followed by numbered, timestamped WAL segments:
+//!
+//! ```text
+//! generations/<generation>/snapshot.db
+//! generations/<generation>/generation.json
+//! generations/<generation>/wal/<index>-<unix millis>.frames
+//! ```
+//!
+//! The replicator is then the only checkpointer, and it checkpoints while
+//! holding the write lock after shipping everything, so no frame can be
+//! folded into the database without having been copied first. After
+//! repeated failed syncs automatic checkpoints come back on so the WAL
+//! cannot grow without bound. If continuity is lost (the process restarted,
+//! the WAL was reset by such a checkpoint) it starts a new generation. [`restore`] rebuilds the database as of any point in time
+//! covered by a generation; `vk-db-replica` exposes it on the command line.
+
+mod store;
+mod wal;
+
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{ConnectOptions, SqliteConnection, sqlite::SqliteConnectOptions};
+use thiserror::Error;
+use tokio::{fs, task::JoinHandle};
+
+use self::{
+    store::ReplicaStore,
+    wal::{WAL_HEADER_SIZE, WalHeader},
+};
+
+pub const REPLICA_ENV: &str = "VK_DB_REPLICA";
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SYNC_BACKOFF: Duration = Duration::from_secs(60);
+/// WAL size at which the replicator checkpoints.
+const CHECKPOINT_THRESHOLD: u64 = 4 * 1024 * 1024;
+/// Age at which a new generation, and so a new snapshot, is started.
+const GENERATION_MAX_AGE: chrono::Duration = chrono::Duration::hours(24);
+/// Generations older than this are deleted, except the current one.
+const GENERATION_RETENTION: chrono::Duration = chrono::Duration::days(7);
+
+const GENERATIONS_PREFIX: &str = "generations/";
+
+/// SQLite's default `wal_autocheckpoint`, used whenever the replicator is
+/// not keeping up.
+const DEFAULT_AUTOCHECKPOINT: u32 = 1000;
+/// Consecutive failed syncs after which automatic checkpoints are turned
+/// back on.
+const MAX_FAILED_SYNCS: u32 = 5;
+
+/// Whether a replicator is running and syncing, and so the only checkpointer.
+static CHECKPOINTS_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Apply the current `wal_autocheckpoint` to a pool connection: off while a
+/// replicator is shipping the WAL, SQLite's default otherwise.
+pub(crate) async fn apply_autocheckpoint(
+    connection: &mut SqliteConnection,
+) -> Result<(), sqlx::Error> {
+    let pages = if CHECKPOINTS_PAUSED.load(Ordering::Relaxed) {
+        0
+    } else {
+        DEFAULT_AUTOCHECKPOINT
+    };
+    // PRAGMA values cannot be bound as parameters, and the statement's result
+    // column has no declared type, so this can't be a checked query.
+    sqlx::query(&format!("PRAGMA wal_autocheckpoint = {pages}"))
+        .execute(connection)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ReplicationError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid replica target: {0}")]
+    InvalidTarget(String),
+    #[error("Replica store error: {0}")]
+    Store(String),
+    #[error("This build does not support S3 replicas; enable the `s3-replica` feature")]
+    S3Unsupported,
+    #[error("No replica generation covers {0}")]
+    NoGeneration(String),
+    #[error("Replica generation {0} is corrupt: {1}")]
+    Corrupt(String, String),
+}
+
+/// Where the replica is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicaTarget {
+    Directory(PathBuf),
+    S3 { bucket: String, prefix: String },
+}
+
+impl FromStr for ReplicaTarget {
+    type Err = ReplicationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(rest) = value.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return Err(ReplicationError::InvalidTarget(format!(
+                    "'{value}' has no bucket"
+                )));
+            }
+            return Ok(Self::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+            });
+        }
+        if value.is_empty() {
+            return Err(ReplicationError::InvalidTarget("empty path".to_string()));
+        }
+        Ok(Self::Directory(utils::path::expand_tilde(value)))
+    }
+}
+
+impl ReplicaTarget {
+    /// The target configured by `VK_DB_REPLICA`, if any. An invalid value
+    /// is logged once and treated as unset.
+    pub fn configured() -> Option<&'static ReplicaTarget> {
+        static TARGET: OnceLock<Option<ReplicaTarget>> = OnceLock::new();
+        TARGET
+            .get_or_init(|| {
+                let value = std::env::var(REPLICA_ENV).ok()?;
+                match value.parse() {
+                    Ok(target) => Some(target),
+                    Err(e) => {
+                        tracing::error!("Ignoring {REPLICA_ENV}: {e}");
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+}
+
+/// Written last when a generation starts, so a generation without it is
+/// incomplete and ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationInfo {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub page_size: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationSummary {
+    pub info: GenerationInfo,
+    pub segments: usize,
+    /// The latest point in time the generation can be restored to.
+    pub latest: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    pub generation: String,
+    pub segments_applied: usize,
+    pub restored_to: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    key: String,
+    index: u64,
+    shipped_at: DateTime<Utc>,
+}
+
+fn generation_prefix(generation: &str) -> String {
+    format!("{GENERATIONS_PREFIX}{generation}/")
+}
+
+fn segment_key(generation: &str, index: u64, shipped_at: DateTime<Utc>) -> String {
+    format!(
+        "{}wal/{index:010}-{}.frames",
+        generation_prefix(generation),
+        shipped_at.timestamp_millis()
+    )
+}
+
+fn parse_segment(key: &str) -> Option<Segment> {
+    let name = key.rsplit('/').next()?.strip_suffix(".frames")?;
+    let (index, millis) = name.split_once('-')?;
+    Some(Segment {
+        key: key.to_string(),
+        index: index.parse().ok()?,
+        shipped_at: DateTime::from_timestamp_millis(millis.parse().ok()?)?,
+    })
+}
+
+/// Where shipping resumes in the live WAL.
+#[derive(Debug, Clone)]
+struct Position {
+    generation: GenerationInfo,
+    /// Salts of the WAL being shipped, `None` while it is still empty.
+    salt: Option<[u32; 2]>,
+    offset: u64,
+    checksum: [u32; 2],
+    next_index: u64,
+    /// Set after a full checkpoint, when SQLite may restart the WAL with new
+    /// salts without breaking continuity.
+    restart_expected: bool,
+}
+
+/// Ships the WAL of the live database to a [`ReplicaTarget`].
+pub struct Replicator {
+    database_path: PathBuf,
+    store: ReplicaStore,
+    /// Holds the write lock while shipping before a checkpoint or snapshot.
+    lock: SqliteConnection,
+    checkpointer: SqliteConnection,
+    position: Option<Position>,
+}
+
+impl Replicator {
+    pub async fn new(
+        target: &ReplicaTarget,
+        options: &SqliteConnectOptions,
+    ) -> Result<Self, ReplicationError> {
+        Ok(Self {
+            database_path: options.get_filename().to_path_buf(),
+            store: ReplicaStore::open(target)?,
+            lock: options.connect().await?,
+            checkpointer: options.connect().await?,
+            position: None,
+        })
+    }
+
+    /// Replicate to the target configured by `VK_DB_REPLICA`, if any, until
+    /// the process exits.
+    pub fn spawn_configured() -> Option<JoinHandle<()>> {
+        let target = ReplicaTarget::configured()?;
+        Some(tokio::spawn(async move {
+            let options = match crate::connect_options() {
+                Ok(options) => options,
+                Err(e) => {
+                    tracing::error!("Database replication disabled: {e}");
+                    return;
+                }
+            };
+            let mut replicator = match Replicator::new(target, &options).await {
+                Ok(replicator) => replicator,
+                Err(e) => {
+                    tracing::error!("Database replication disabled: {e}");
+                    return;
+                }
+            };
+            tracing::info!("Replicating database to {target:?}");
+            CHECKPOINTS_PAUSED.store(true, Ordering::Relaxed);
+
+            let mut failures = 0u32;
+            loop {
+                let delay = (SYNC_INTERVAL * 2u32.pow(failures.min(6))).min(MAX_SYNC_BACKOFF);
+                tokio::time::sleep(delay).await;
+                match replicator.sync().await {
+                    Ok(()) => {
+                        if failures >= MAX_FAILED_SYNCS {
+                            tracing::info!("Database replication recovered");
+                            CHECKPOINTS_PAUSED.store(true, Ordering::Relaxed);
+                        }
+                        failures = 0;
+                    }
+                    Err(e) => {
+                        if failures == 0 {
+                            tracing::warn!("Database replication failed, retrying: {e}");
+                        }
+                        failures += 1;
+                        if failures == MAX_FAILED_SYNCS {
+                            tracing::error!(
+                                "Database replication failed {failures} times in a row, \
+                                 re-enabling automatic checkpoints: {e}"
+                            );
+                            CHECKPOINTS_PAUSED.store(false, Ordering::Relaxed);
+                        }
+                    }
+                }
+            }
+        }))
+    }
+
+    fn wal_path(&self) -> PathBuf {
+        let mut path = self.database_path.clone().into_os_string();
+        path.push("-wal");
+        path.into()
+    }
+
+    async fn read_wal(&self) -> Result<Vec<u8>, ReplicationError> {
+        match fs::read(self.wal_path()).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Ship new frames, starting a generation or checkpointing as needed.
+    pub async fn sync(&mut self) -> Result<(), ReplicationError> {
+        let due_for_generation = self.position.as_ref().is_none_or(|position| {
+            Utc::now() - position.generation.created_at > GENERATION_MAX_AGE
+        });
+        if due_for_generation {
+            return self.start_generation().await;
+        }
+
+        if !self.ship_frames().await? {
+            tracing::warn!("Lost track of the database WAL, starting a new replica generation");
+            self.position = None;
+            return self.start_generation().await;
+        }
+
+        if self
+            .position
+            .as_ref()
+            .is_some_and(|position| position.offset >= CHECKPOINT_THRESHOLD)
+        {
+            self.checkpoint().await?;
+        }
+        Ok(())
+    }
+
+    /// Copy committed frames written since the last sync. Returns `false`
+    /// when the WAL no longer continues from the shipped position.
+    async fn ship_frames(&mut self) -> Result<bool, ReplicationError> {
+        let wal = self.read_wal().await?;
+        let Some(position) = self.position.as_mut() else {
+            return Ok(false);
+        };
+        let Some(header) = WalHeader::parse(&wal) else {
+            return Ok(position.salt.is_none());
+        };
+        if header.page_size != position.generation.page_size {
+            return Ok(false);
+        }
+
+        let (offset, checksum) = if position.salt == Some(header.salt) {
+            (position.offset, position.checksum)
+        } else if position.salt.is_none() || position.restart_expected {
+            (WAL_HEADER_SIZE as u64, header.checksum)
+        } else {
+            return Ok(false);
+        };
+
+        let committed = wal::committed_frames(&wal, &header, offset, checksum);
+        if !committed.frames.is_empty() {
+            let key = segment_key(&position.generation.id, position.next_index, Utc::now());
+            self.store.put(&key, committed.frames).await?;
+            position.next_index += 1;
+        }
+        if position.salt != Some(header.salt) {
+            position.salt = Some(header.salt);
+            position.restart_expected = false;
+        }
+        position.offset = committed.end_offset;
+        position.checksum = committed.checksum;
+        Ok(true)
+    }
+
+    /// Run a passive checkpoint. Returns whether every frame in the WAL is
+    /// now in the database file.
+    async fn full_checkpoint(&mut self) -> Result<bool, ReplicationError> {
+        // PRAGMA result columns are untyped, which the query macros reject,
+        // and wal_checkpoint has no table-valued form to cast through.
+        let (busy, log, checkpointed): (i64, i64, i64) =
+            sqlx::query_as("PRAGMA wal_checkpoint(PASSIVE)")
+                .fetch_one(&mut self.checkpointer)
+                .await?;
+        Ok(busy == 0 && log == checkpointed)
+    }
+
+    async fn begin_write_lock(&mut self) -> Result<(), ReplicationError> {
+        sqlx::query!("BEGIN IMMEDIATE")
+            .execute(&mut self.lock)
+            .await?;
+        Ok(())
+    }
+
+    async fn release_write_lock(&mut self) {
+        if let Err(e) = sqlx::query!("ROLLBACK").execute(&mut self.lock).await {
+            tracing::error!("Failed to release the replication write lock: {e}");
+        }
+    }
+
+    /// Ship the rest of the WAL and checkpoint it with writers held off, so
+    /// nothing is checkpointed unshipped. Readers on older snapshots can
+    /// keep the checkpoint from completing; it is retried on the next sync.
+    async fn checkpoint(&mut self) -> Result<(), ReplicationError> {
+        self.begin_write_lock().await?;
+        let result = match self.ship_frames().await {
+            Ok(true) => self.full_checkpoint().await.map(Some),
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        self.release_write_lock().await;
+
+        match result? {
+            Some(true) => {
+                if let Some(position) = self.position.as_mut() {
+                    position.restart_expected = true;
+                }
+            }
+            Some(false) => {}
+            None => self.position = None,
+        }
+        Ok(())
+    }
+
+    /// Snapshot the database and start shipping its WAL into a new
+    /// generation.
+    async fn start_generation(&mut self) -> Result<(), ReplicationError> {
+        self.begin_write_lock().await?;
+        let snapshot = self.take_snapshot().await;
+        self.release_write_lock().await;
+        let Some((snapshot, position)) = snapshot? else {
+            tracing::debug!("Database replica snapshot deferred until readers finish");
+            return Ok(());
+        };
+
+        let prefix = generation_prefix(&position.generation.id);
+        self.store
+            .put(&format!("{prefix}snapshot.db"), snapshot)
+            .await?;
+        self.store
+            .put(
+                &format!("{prefix}generation.json"),
+                serde_json::to_vec_pretty(&position.generation)?,
+            )
+            .await?;
+        tracing::info!(
+            "Started database replica generation {}",
+            position.generation.id
+        );
+        self.position = Some(position);
+
+        if let Err(e) = self.prune_generations().await {
+            tracing::warn!("Failed to prune old database replica generations: {e}");
+        }
+        Ok(())
+    }
+
+    /// With the write lock held: checkpoint everything, then copy the
+    /// database file, which now reflects the whole WAL.
+    async fn take_snapshot(&mut self) -> Result<Option<(Vec<u8>, Position)>, ReplicationError> {
+        if !self.full_checkpoint().await? {
+            return Ok(None);
+        }
+        // Untyped PRAGMA result; see `full_checkpoint`.
+        let (page_size,): (i64,) = sqlx::query_as("PRAGMA page_size")
+            .fetch_one(&mut self.checkpointer)
+            .await?;
+        let wal = self.read_wal().await?;
+        let snapshot = fs::read(&self.database_path).await?;
+
+        let created_at = Utc::now();
+        let generation = GenerationInfo {
+            id: created_at.format("%Y%m%dT%H%M%S%.3fZ").to_string(),
+            created_at,
+            page_size: page_size as u32,
+        };
+        let position = match WalHeader::parse(&wal) {
+            Some(header) => {
+                let end =
+                    wal::committed_frames(&wal, &header, WAL_HEADER_SIZE as u64, header.checksum);
+                Position {
+                    generation,
+                    salt: Some(header.salt),
+                    offset: end.end_offset,
+                    checksum: end.checksum,
+                    next_index: 0,
+                    restart_expected: true,
+                }
+            }
+            None => Position {
+                generation,
+                salt: None,
+                offset: WAL_HEADER_SIZE as u64,
+                checksum: [0, 0],
+                next_index: 0,
+                restart_expected: true,
+            },
+        };
+        Ok(Some((snapshot, position)))
+    }
+
+    async fn prune_generations(&self) -> Result<(), ReplicationError> {
+        let Some(current) = self.position.as_ref() else {
+            return Ok(());
+        };
+        let cutoff = Utc::now() - GENERATION_RETENTION;
+        for summary in list_generations_in(&self.store).await? {
+            if summary.info.id != current.generation.id && summary.latest < cutoff {
+                self.store
+                    .delete_prefix(&generation_prefix(&summary.info.id))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn segments(
+    store: &ReplicaStore,
+    generation: &str,
+) -> Result<Vec<Segment>, ReplicationError> {
+    let prefix = format!("{}wal/", generation_prefix(generation));
+    let mut segments: Vec<Segment> = store
+        .list(&prefix)
+        .await?
+        .iter()
+        .filter_map(|key| parse_segment(key))
+        .collect();
+    segments.sort_by_key(|segment| segment.index);
+    Ok(segments)
+}
+
+async fn list_generations_in(
+    store: &ReplicaStore,
+) -> Result<Vec<GenerationSummary>, ReplicationError> {
+    let mut summaries = Vec::new();
+    for key in store.list(GENERATIONS_PREFIX).await? {
+        if !key.ends_with("/generation.json") {
+            continue;
+        }
+        let info: GenerationInfo = serde_json::from_slice(&store.get(&key).await?)?;
+        let segments = segments(store, &info.id).await?;
+        let latest = segments
+            .last()
+            .map_or(info.created_at, |segment| segment.shipped_at);
+        summaries.push(GenerationSummary {
+            info,
+            segments: segments.len(),
+            latest,
+        });
+    }
+    summaries.sort_by_key(|summary| summary.info.created_at);
+    Ok(summaries)
+}
+
+/// Every complete generation in the replica, oldest first.
+pub async fn list_generations(
+    target: &ReplicaTarget,
+) -> Result<Vec<GenerationSummary>, ReplicationError> {
+    list_generations_in(&ReplicaStore::open(target)?).await
+}
+
+/// Rebuild the database as it was at `at`, or as late as possible, into
+/// `output`. Any WAL or shared-memory file beside `output` is removed so
+/// SQLite does not replay it over the restored database.
+pub async fn restore(
+    target: &ReplicaTarget,
+    at: Option<DateTime<Utc>>,
+    output: &Path,
+) -> Result<RestoreReport, ReplicationError> {
+    let store = ReplicaStore::open(target)?;
+    let generation = list_generations_in(&store)
+        .await?
+        .into_iter()
+        .rev()
+        .find(|summary| at.is_none_or(|at| summary.info.created_at <= at))
+        .ok_or_else(|| {
+            ReplicationError::NoGeneration(
+                at.map_or_else(|| "any time".to_string(), |at| at.to_rfc3339()),
+            )
+        })?
+        .info;
+
+    let prefix = generation_prefix(&generation.id);
+    let corrupt = |reason: String| ReplicationError::Corrupt(generation.id.clone(), reason);
+    let mut database = store.get(&format!("{prefix}snapshot.db")).await?;
+    let mut report = RestoreReport {
+        generation: generation.id.clone(),
+        segments_applied: 0,
+        restored_to: generation.created_at,
+    };
+
+    for (expected_index, segment) in (0u64..).zip(segments(&store, &generation.id).await?) {
+        if at.is_some_and(|at| segment.shipped_at > at) {
+            break;
+        }
+        if segment.index != expected_index {
+            return Err(corrupt(format!("WAL segment {expected_index} is missing")));
+        }
+        let frames = store.get(&segment.key).await?;
+        let frame_size = wal::FRAME_HEADER_SIZE + generation.page_size as usize;
+        if frames.len() % frame_size != 0 {
+            return Err(corrupt(format!(
+                "WAL segment {} is truncated",
+                segment.index
+            )));
+        }
+        wal::apply_frames(&mut database, &frames, generation.page_size)
+            .map_err(|reason| corrupt(format!("WAL segment {}: {reason}", segment.index)))?;
+        report.segments_applied += 1;
+        report.restored_to = segment.shipped_at;
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = output.as_os_str().to_owned();
+        sidecar.push(suffix);
+        if let Err(e) = fs::remove_file(PathBuf::from(sidecar)).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            return Err(e.into());
+        }
+    }
+    let mut partial = output.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    fs::write(&partial, database).await?;
+    fs::rename(&partial, output).await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqliteJournalMode;
+
+    use super::*;
+
+    async fn note_count(path: &Path) -> i64 {
+        let mut connection = SqliteConnectOptions::new()
+            .filename(path)
+            .connect()
+            .await
+            .unwrap();
+        sqlx::query_scalar("SELECT COUNT(*) FROM notes")
+            .fetch_one(&mut connection)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_replica_targets() {
+        assert_eq!(
+            "s3://backups/vibe-kanban/"
+                .parse::<ReplicaTarget>()
+                .unwrap(),
+            ReplicaTarget::S3 {
+                bucket: "backups".to_string(),
+                prefix: "vibe-kanban".to_string(),
+            }
+        );
+        assert_eq!(
+            "/var/backups/vk".parse::<ReplicaTarget>().unwrap(),
+            ReplicaTarget::Directory(PathBuf::from("/var/backups/vk"))
+        );
+        assert!("s3://".parse::<ReplicaTarget>().is_err());
+    }
+
+    #[test]
+    fn segment_keys_round_trip() {
+        let shipped_at = DateTime::from_timestamp_millis(1_760_000_000_123).unwrap();
+        let key = segment_key("20251009T085320.123Z", 42, shipped_at);
+        let segment = parse_segment(&key).unwrap();
+        assert_eq!(segment.index, 42);
+        assert_eq!(segment.shipped_at, shipped_at);
+    }
+
+    #[tokio::test]
+    async fn replicates_and_restores_a_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = ReplicaTarget::Directory(dir.path().join("replica"));
+        let options = SqliteConnectOptions::new()
+            .filename(dir.path().join("db.sqlite"))
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .pragma("wal_autocheckpoint", "0");
+        let mut writer = options.connect().await.unwrap();
+        sqlx::query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL)")
+            .execute(&mut writer)
+            .await
+            .unwrap();
+        let insert =
+            |body: &'static str| sqlx::query("INSERT INTO notes (body) VALUES (?)").bind(body);
+
+        let mut replicator = Replicator::new(&target, &options).await.unwrap();
+        replicator.sync().await.unwrap();
+        insert("first").execute(&mut writer).await.unwrap();
+        insert("second").execute(&mut writer).await.unwrap();
+        replicator.sync().await.unwrap();
+        let before_third = Utc::now();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        insert("third").execute(&mut writer).await.unwrap();
+        replicator.sync().await.unwrap();
+
+        let generations = list_generations(&target).await.unwrap();
+        assert_eq!(generations.len(), 1);
+        assert_eq!(generations[0].segments, 2);
+
+        let latest = dir.path().join("restored/latest.sqlite");
+        let report = restore(&target, None, &latest).await.unwrap();
+        assert_eq!(report.segments_applied, 2);
+        assert_eq!(note_count(&latest).await, 3);
+
+        let earlier = dir.path().join("restored/earlier.sqlite");
+        let report = restore(&target, Some(before_third), &earlier)
+            .await
+            .unwrap();
+        assert_eq!(report.segments_applied, 1);
+        assert_eq!(note_count(&earlier).await, 2);
+    }
+}
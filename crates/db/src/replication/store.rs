@@ -0,0 +1,261 @@
+//! Where replicas are written: a local directory or, with the `s3-replica`
+//! feature, an S3-compatible bucket.
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+use super::{ReplicaTarget, ReplicationError};
+
+pub(crate) enum ReplicaStore {
+    Directory(PathBuf),
+    #[cfg(feature = "s3-replica")]
+    S3 {
+        client: aws_sdk_s3::Client,
+        bucket: String,
+        prefix: String,
+    },
+}
+
+impl ReplicaStore {
+    pub fn open(target: &ReplicaTarget) -> Result<Self, ReplicationError> {
+        match target {
+            ReplicaTarget::Directory(path) => Ok(Self::Directory(path.clone())),
+            #[cfg(feature = "s3-replica")]
+            ReplicaTarget::S3 { bucket, prefix } => Ok(Self::S3 {
+                client: s3::client()?,
+                bucket: bucket.clone(),
+                prefix: prefix.clone(),
+            }),
+            #[cfg(not(feature = "s3-replica"))]
+            ReplicaTarget::S3 { .. } => Err(ReplicationError::S3Unsupported),
+        }
+    }
+
+    /// Write `data` under `key`, replacing any previous object.
+    pub async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), ReplicationError> {
+        match self {
+            Self::Directory(root) => {
+                let path = root.join(key);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                // Readers never see a partially written object.
+                let partial = path.with_extension("partial");
+                fs::write(&partial, data).await?;
+                fs::rename(&partial, &path).await?;
+                Ok(())
+            }
+            #[cfg(feature = "s3-replica")]
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => s3::put(client, bucket, &s3::key(prefix, key), data).await,
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Vec<u8>, ReplicationError> {
+        match self {
+            Self::Directory(root) => Ok(fs::read(root.join(key)).await?),
+            #[cfg(feature = "s3-replica")]
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => s3::get(client, bucket, &s3::key(prefix, key)).await,
+        }
+    }
+
+    /// Keys below `prefix`, relative to the store root and sorted.
+    pub async fn list(&self, prefix: &str) -> Result<Vec<String>, ReplicationError> {
+        let mut keys = match self {
+            Self::Directory(root) => list_directory(root, prefix).await?,
+            #[cfg(feature = "s3-replica")]
+            Self::S3 {
+                client,
+                bucket,
+                prefix: root,
+            } => s3::list(client, bucket, root, prefix).await?,
+        };
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Remove every key below `prefix`.
+    pub async fn delete_prefix(&self, prefix: &str) -> Result<(), ReplicationError> {
+        match self {
+            Self::Directory(root) => match fs::remove_dir_all(root.join(prefix)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            },
+            #[cfg(feature = "s3-replica")]
+            Self::S3 {
+                client,
+                bucket,
+                prefix: root,
+            } => {
+                for key in s3::list(client, bucket, root, prefix).await? {
+                    s3::delete(client, bucket, &s3::key(root, &key)).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn list_directory(root: &Path, prefix: &str) -> Result<Vec<String>, ReplicationError> {
+    let mut keys = Vec::new();
+    let mut pending = vec![root.join(prefix)];
+    while let Some(dir) = pending.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_none_or(|ext| ext != "partial")
+                && let Ok(relative) = path.strip_prefix(root)
+            {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+#[cfg(feature = "s3-replica")]
+mod s3 {
+    use aws_credential_types::Credentials;
+    use aws_sdk_s3::{
+        Client,
+        config::{Builder as S3ConfigBuilder, Region},
+        primitives::ByteStream,
+    };
+
+    use super::ReplicationError;
+
+    fn store_error(e: impl std::fmt::Display) -> ReplicationError {
+        ReplicationError::Store(e.to_string())
+    }
+
+    /// A client configured from the standard `AWS_*` variables.
+    /// `AWS_ENDPOINT_URL` selects an S3-compatible service such as R2 or MinIO.
+    pub(super) fn client() -> Result<Client, ReplicationError> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| ReplicationError::Store(format!("{name} is not set")))
+        };
+        let credentials = Credentials::new(
+            var("AWS_ACCESS_KEY_ID")?,
+            var("AWS_SECRET_ACCESS_KEY")?,
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+            None,
+            "vk-db-replica",
+        );
+        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+
+        let mut config = S3ConfigBuilder::new()
+            .region(Region::new(region))
+            .credentials_provider(credentials);
+        if let Ok(endpoint) = std::env::var("AWS_ENDPOINT_URL") {
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+        Ok(Client::from_conf(config.build()))
+    }
+
+    pub(super) fn key(prefix: &str, key: &str) -> String {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}/{key}")
+        }
+    }
+
+    pub(super) async fn put(
+        client: &Client,
+        bucket: &str,
+        key: &str,
+        data: Vec<u8>,
+    ) -> Result<(), ReplicationError> {
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    pub(super) async fn get(
+        client: &Client,
+        bucket: &str,
+        key: &str,
+    ) -> Result<Vec<u8>, ReplicationError> {
+        let object = client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(store_error)?;
+        let body = object.body.collect().await.map_err(store_error)?;
+        Ok(body.into_bytes().to_vec())
+    }
+
+    pub(super) async fn list(
+        client: &Client,
+        bucket: &str,
+        root: &str,
+        prefix: &str,
+    ) -> Result<Vec<String>, ReplicationError> {
+        let root_prefix = key(root, "");
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let page = client
+                .list_objects_v2()
+                .bucket(bucket)
+                .prefix(key(root, prefix))
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(store_error)?;
+            keys.extend(
+                page.contents()
+                    .iter()
+                    .filter_map(|object| object.key())
+                    .filter_map(|key| key.strip_prefix(root_prefix.as_str()))
+                    .map(str::to_string),
+            );
+            match page.next_continuation_token() {
+                Some(token) => continuation_token = Some(token.to_string()),
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    pub(super) async fn delete(
+        client: &Client,
+        bucket: &str,
+        key: &str,
+    ) -> Result<(), ReplicationError> {
+        client
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+//! Reading and replaying SQLite WAL frames.
+//!
+//! See <https://www.sqlite.org/fileformat2.html#walformat>. A WAL file is a
+//! 32-byte header followed by frames, each a 24-byte header and one page.
+//! Frames are only trusted while their salts match the header and the running
+//! checksum holds, which is also how a torn or restarted WAL is detected.
+
+pub(crate) const WAL_HEADER_SIZE: usize = 32;
+pub(crate) const FRAME_HEADER_SIZE: usize = 24;
+
+const MAGIC_LITTLE_ENDIAN: u32 = 0x377f_0682;
+const MAGIC_BIG_ENDIAN: u32 = 0x377f_0683;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WalHeader {
+    pub page_size: u32,
+    pub salt: [u32; 2],
+    pub checksum: [u32; 2],
+    big_endian_checksums: bool,
+}
+
+impl WalHeader {
+    /// Parse and verify a WAL header. `None` if it is missing or invalid.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let header = bytes.get(..WAL_HEADER_SIZE)?;
+        let big_endian_checksums = match read_u32(header, 0) {
+            MAGIC_LITTLE_ENDIAN => false,
+            MAGIC_BIG_ENDIAN => true,
+            _ => return None,
+        };
+        let checksum = [read_u32(header, 24), read_u32(header, 28)];
+        if wal_checksum(big_endian_checksums, &header[..24], [0, 0]) != checksum {
+            return None;
+        }
+        // A page size of 1 encodes 65536.
+        let page_size = match read_u32(header, 8) {
+            1 => 65536,
+            size => size,
+        };
+        Some(Self {
+            page_size,
+            salt: [read_u32(header, 16), read_u32(header, 20)],
+            checksum,
+            big_endian_checksums,
+        })
+    }
+
+    pub fn frame_size(&self) -> usize {
+        FRAME_HEADER_SIZE + self.page_size as usize
+    }
+}
+
+/// The committed frames found after a known position in a WAL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CommittedFrames {
+    /// Byte offset just past the last commit frame.
+    pub end_offset: u64,
+    /// Running checksum at `end_offset`.
+    pub checksum: [u32; 2],
+    /// The raw frames, headers included, up to `end_offset`.
+    pub frames: Vec<u8>,
+}
+
+/// Scan `wal` from `offset`, where the running checksum is `checksum`, and
+/// return every frame up to the last complete transaction. Frames of an
+/// unfinished transaction, a torn write or an older WAL generation stop the
+/// scan without error.
+pub(crate) fn committed_frames(
+    wal: &[u8],
+    header: &WalHeader,
+    offset: u64,
+    checksum: [u32; 2],
+) -> CommittedFrames {
+    let frame_size = header.frame_size();
+    let start = offset as usize;
+    let mut position = start;
+    let mut running = checksum;
+    let mut committed = CommittedFrames {
+        end_offset: offset,
+        checksum,
+        frames: Vec::new(),
+    };
+
+    while let Some(frame) = wal.get(position..position + frame_size) {
+        if read_u32(frame, 0) == 0 || [read_u32(frame, 8), read_u32(frame, 12)] != header.salt {
+            break;
+        }
+        running = wal_checksum(header.big_endian_checksums, &frame[..8], running);
+        running = wal_checksum(
+            header.big_endian_checksums,
+            &frame[FRAME_HEADER_SIZE..],
+            running,
+        );
+        if running != [read_u32(frame, 16), read_u32(frame, 20)] {
+            break;
+        }
+        position += frame_size;
+        if read_u32(frame, 4) != 0 {
+            committed.end_offset = position as u64;
+            committed.checksum = running;
+        }
+    }
+
+    committed.frames = wal[start..committed.end_offset as usize].to_vec();
+    committed
+}
+
+/// Apply raw frames, as returned by [`committed_frames`], to a database image
+/// the way a checkpoint would. Trailing frames without a commit are ignored.
+/// Fails on a frame for page 0, which SQLite never writes.
+pub(crate) fn apply_frames(
+    database: &mut Vec<u8>,
+    frames: &[u8],
+    page_size: u32,
+) -> Result<(), String> {
+    let page_size = page_size as usize;
+    let mut pending: Vec<(u32, &[u8])> = Vec::new();
+
+    for frame in frames.chunks_exact(FRAME_HEADER_SIZE + page_size) {
+        let page_number = read_u32(frame, 0);
+        if page_number == 0 {
+            return Err("frame for page 0".to_string());
+        }
+        pending.push((page_number, &frame[FRAME_HEADER_SIZE..]));
+        let commit_size = read_u32(frame, 4) as usize;
+        if commit_size == 0 {
+            continue;
+        }
+
+        database.resize(database.len().max(commit_size * page_size), 0);
+        for (page_number, page) in pending.drain(..) {
+            let start = (page_number as usize - 1) * page_size;
+            if start + page_size <= database.len() {
+                database[start..start + page_size].copy_from_slice(page);
+            }
+        }
+        database.truncate(commit_size * page_size);
+    }
+    Ok(())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn wal_checksum(big_endian: bool, data: &[u8], [mut s0, mut s1]: [u32; 2]) -> [u32; 2] {
+    let word = |bytes: &[u8]| {
+        let bytes: [u8; 4] = bytes.try_into().unwrap();
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    for pair in data.chunks_exact(8) {
+        s0 = s0.wrapping_add(word(&pair[..4])).wrapping_add(s1);
+        s1 = s1.wrapping_add(word(&pair[4..])).wrapping_add(s0);
+    }
+    [s0, s1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: u32 = 512;
+
+    /// Builds WAL files the way SQLite writes them.
+    struct WalWriter {
+        bytes: Vec<u8>,
+        salt: [u32; 2],
+        checksum: [u32; 2],
+    }
+
+    impl WalWriter {
+        fn new(salt: [u32; 2]) -> Self {
+            let mut bytes = Vec::new();
+            for value in [
+                MAGIC_LITTLE_ENDIAN,
+                3_007_000,
+                PAGE_SIZE,
+                0,
+                salt[0],
+                salt[1],
+            ] {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+            let checksum = wal_checksum(false, &bytes, [0, 0]);
+            bytes.extend_from_slice(&checksum[0].to_be_bytes());
+            bytes.extend_from_slice(&checksum[1].to_be_bytes());
+            Self {
+                bytes,
+                salt,
+                checksum,
+            }
+        }
+
+        fn frame(&mut self, page_number: u32, fill: u8, commit_size: u32) {
+            let mut header = Vec::new();
+            for value in [page_number, commit_size, self.salt[0], self.salt[1]] {
+                header.extend_from_slice(&value.to_be_bytes());
+            }
+            let page = vec![fill; PAGE_SIZE as usize];
+            self.checksum = wal_checksum(false, &header[..8], self.checksum);
+            self.checksum = wal_checksum(false, &page, self.checksum);
+            header.extend_from_slice(&self.checksum[0].to_be_bytes());
+            header.extend_from_slice(&self.checksum[1].to_be_bytes());
+            self.bytes.extend_from_slice(&header);
+            self.bytes.extend_from_slice(&page);
+        }
+    }
+
+    #[test]
+    fn stops_at_the_last_commit() {
+        let mut wal = WalWriter::new([7, 9]);
+        wal.frame(1, 0xaa, 0);
+        wal.frame(2, 0xbb, 2);
+        wal.frame(3, 0xcc, 0);
+        let header = WalHeader::parse(&wal.bytes).unwrap();
+
+        let committed =
+            committed_frames(&wal.bytes, &header, WAL_HEADER_SIZE as u64, header.checksum);
+        assert_eq!(committed.frames.len(), 2 * header.frame_size());
+
+        // Resuming from the returned position picks up the next transaction.
+        wal.frame(3, 0xcc, 3);
+        let rest = committed_frames(
+            &wal.bytes,
+            &header,
+            committed.end_offset,
+            committed.checksum,
+        );
+        assert_eq!(rest.frames.len(), 2 * header.frame_size());
+    }
+
+    #[test]
+    fn ignores_frames_from_another_salt_or_torn_pages() {
+        let mut old = WalWriter::new([1, 1]);
+        old.frame(1, 0x11, 1);
+        old.frame(2, 0x22, 2);
+        let mut restarted = WalWriter::new([2, 2]);
+        restarted.frame(1, 0x33, 2);
+        // The restarted WAL overwrites the old one from the start.
+        let mut bytes = restarted.bytes.clone();
+        bytes.extend_from_slice(&old.bytes[bytes.len()..]);
+
+        let header = WalHeader::parse(&bytes).unwrap();
+        let committed = committed_frames(&bytes, &header, WAL_HEADER_SIZE as u64, header.checksum);
+        assert_eq!(committed.frames.len(), header.frame_size());
+
+        let last = bytes.len() - 1;
+        bytes[WAL_HEADER_SIZE + FRAME_HEADER_SIZE] ^= 0xff;
+        bytes[last] ^= 0xff;
+        let torn = committed_frames(&bytes, &header, WAL_HEADER_SIZE as u64, header.checksum);
+        assert!(torn.frames.is_empty());
+    }
+
+    #[test]
+    fn applying_frames_matches_a_checkpoint() {
+        let mut wal = WalWriter::new([3, 4]);
+        wal.frame(2, 0xbb, 0);
+        wal.frame(3, 0xcc, 3);
+        wal.frame(1, 0xdd, 2);
+        wal.frame(3, 0xee, 0);
+        let header = WalHeader::parse(&wal.bytes).unwrap();
+        let committed =
+            committed_frames(&wal.bytes, &header, WAL_HEADER_SIZE as u64, header.checksum);
+
+        let mut database = vec![0xaa; PAGE_SIZE as usize];
+        apply_frames(&mut database, &committed.frames, PAGE_SIZE).unwrap();
+
+        let page_size = PAGE_SIZE as usize;
+        assert_eq!(database.len(), 2 * page_size);
+        assert!(database[..page_size].iter().all(|b| *b == 0xdd));
+        assert!(database[page_size..].iter().all(|b| *b == 0xbb));
+    }
+
+    #[test]
+    fn rejects_frames_for_page_zero() {
+        let mut wal = WalWriter::new([5, 6]);
+        wal.frame(0, 0xaa, 1);
+        let frames = &wal.bytes[WAL_HEADER_SIZE..];
+
+        let mut database = vec![0xbb; PAGE_SIZE as usize];
+        assert!(apply_frames(&mut database, frames, PAGE_SIZE).is_err());
+        assert!(database.iter().all(|b| *b == 0xbb));
+    }
+}
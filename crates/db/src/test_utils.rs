@@ -0,0 +1,47 @@
+//! Helpers for tests that need a real, migrated database.
+
+use std::path::PathBuf;
+
+use sqlx::{
+    SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use tempfile::TempDir;
+
+use crate::DBService;
+
+const DB_FILE_NAME: &str = "db.sqlite";
+
+/// A fully migrated database in a temporary directory that is removed when
+/// this is dropped.
+pub struct TestDb {
+    pub dir: TempDir,
+    pub pool: SqlitePool,
+}
+
+impl TestDb {
+    pub async fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = SqlitePoolOptions::new()
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(dir.path().join(DB_FILE_NAME))
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        Self { dir, pool }
+    }
+
+    /// Path of the database file.
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().join(DB_FILE_NAME)
+    }
+
+    pub fn service(&self) -> DBService {
+        DBService {
+            pool: self.pool.clone(),
+        }
+    }
+}
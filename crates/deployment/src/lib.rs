@@ -14,16 +14,19 @@ use relay_hosts::RelayHosts;
 use remote_info::RemoteInfo;
 use serde_json::Value;
 use services::services::{
-    analytics::AnalyticsService,
+    analytics::{AnalyticsCategory, AnalyticsService},
     approvals::Approvals,
     auth::AuthContext,
     config::{Config, ConfigError},
     container::{ContainerError, ContainerService},
+    dev_server::DevServerMonitor,
     events::{EventError, EventService},
+    execution_scheduler::ExecutionScheduler,
     file::{FileError, FileService},
     file_search::FileSearchCache,
     filesystem::{FilesystemError, FilesystemService},
     filesystem_watcher::FilesystemWatcherError,
+    provider_credentials::ProviderCredentials,
     queued_message::QueuedMessageService,
     remote_client::RemoteClient,
     repo::RepoService,
@@ -106,6 +109,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn queued_message_service(&self) -> &QueuedMessageService;
 
+    fn execution_scheduler(&self) -> &ExecutionScheduler;
+
+    fn provider_credentials(&self) -> &ProviderCredentials;
+
     fn auth_context(&self) -> &AuthContext;
 
     fn relay_control(&self) -> &Arc<RelayControl>;
@@ -118,6 +125,8 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn preview_proxy(&self) -> &PreviewProxyService;
 
+    fn dev_servers(&self) -> &DevServerMonitor;
+
     fn relay_hosts(&self) -> Result<&Arc<RelayHosts>, RelayHostsNotConfigured> {
         Err(RelayHostsNotConfigured)
     }
@@ -138,20 +147,26 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         Ok(())
     }
 
+    /// Tracks a usage event. Whether it is sent depends on the user's
+    /// consent, which the analytics service checks.
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
-        let analytics_enabled = self.config().read().await.analytics_enabled;
-        // Track events unless user has explicitly opted out
-        if analytics_enabled && let Some(analytics) = self.analytics() {
-            analytics.track_event(self.user_id(), event_name, Some(properties.clone()));
+        if let Some(analytics) = self.analytics() {
+            analytics.track_event(
+                AnalyticsCategory::Usage,
+                self.user_id(),
+                event_name,
+                Some(properties),
+            );
         }
     }
 
+    /// SSE event stream, resuming after `since_seq` when given.
     async fn stream_events(
         &self,
+        since_seq: Option<u64>,
     ) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
         self.events()
-            .msg_store()
-            .history_plus_stream()
+            .resume_stream(since_seq)
             .map_ok(|m| m.to_sse_event())
             .boxed()
     }
@@ -25,6 +25,8 @@ pub enum ScriptContext {
     ArchiveScript,
     DevServer,
     ToolInstallScript,
+    RepoCommand,
+    CheckCommand,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -52,13 +54,13 @@ impl Executable for ScriptRequest {
             None => current_dir.to_path_buf(),
         };
 
-        let (shell_cmd, shell_arg) = get_shell_command();
+        // The user's shell may not exist in a sandbox image; `sh` always does.
+        let (shell_cmd, shell_arg) = match env.sandbox {
+            Some(_) => ("sh".to_string(), "-c"),
+            None => get_shell_command(),
+        };
         let mut command = Command::new(shell_cmd);
         command
-            .kill_on_drop(true)
-            .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
             .arg(shell_arg)
             .arg(&self.script)
             .current_dir(&effective_dir);
@@ -66,6 +68,12 @@ impl Executable for ScriptRequest {
         // Apply environment variables
         env.apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+
         let child = command.group_spawn_no_window()?;
 
         Ok(child.into())
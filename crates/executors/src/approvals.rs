@@ -4,7 +4,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio_util::sync::CancellationToken;
-use workspace_utils::approvals::{ApprovalStatus, QuestionStatus};
+use workspace_utils::approvals::{
+    ApprovalQuestion, ApprovalStatus, ApprovalToolInput, QuestionStatus,
+};
 
 /// Errors emitted by executor approval services.
 #[derive(Debug, Error)]
@@ -25,17 +27,57 @@ impl ExecutorApprovalError {
     }
 }
 
+/// A backend's say over a tool call, ahead of the session's own approval
+/// setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolCallPolicy {
+    /// Follow the session's approval setting.
+    Default,
+    /// Ask for approval even if the session auto-approves.
+    RequireApproval,
+    /// Refuse the call.
+    Deny { reason: String },
+}
+
 /// Abstraction for executor approval backends.
 #[async_trait]
 pub trait ExecutorApprovalService: Send + Sync {
     /// Creates a tool approval request. Returns the approval_id immediately.
-    async fn create_tool_approval(&self, tool_name: &str) -> Result<String, ExecutorApprovalError>;
+    /// `tool_input` describes what the call will do, where the executor knows.
+    async fn create_tool_approval(
+        &self,
+        tool_name: &str,
+        tool_input: Option<ApprovalToolInput>,
+    ) -> Result<String, ExecutorApprovalError>;
+
+    /// Whether the call must be approved or refused whatever the session's
+    /// approval setting. Executors that auto-approve ask this first.
+    async fn tool_call_policy(
+        &self,
+        tool_name: &str,
+        tool_input: &ApprovalToolInput,
+    ) -> ToolCallPolicy {
+        let _ = (tool_name, tool_input);
+        ToolCallPolicy::Default
+    }
+
+    /// Creates an approval request for a plan the agent wants to carry out.
+    /// Backends that track plans record `plan` as the session's latest one.
+    async fn create_plan_approval(
+        &self,
+        tool_name: &str,
+        plan: &str,
+    ) -> Result<String, ExecutorApprovalError> {
+        let _ = plan;
+        self.create_tool_approval(tool_name, None).await
+    }
 
     /// Creates a question approval request. Returns the approval_id immediately.
+    /// Answers are checked against `questions`.
     async fn create_question_approval(
         &self,
         tool_name: &str,
-        question_count: usize,
+        questions: Vec<ApprovalQuestion>,
     ) -> Result<String, ExecutorApprovalError>;
 
     /// Waits for a tool approval to be resolved. Blocks until approved/denied/timed out.
@@ -61,6 +103,7 @@ impl ExecutorApprovalService for NoopExecutorApprovalService {
     async fn create_tool_approval(
         &self,
         _tool_name: &str,
+        _tool_input: Option<ApprovalToolInput>,
     ) -> Result<String, ExecutorApprovalError> {
         Ok("noop".to_string())
     }
@@ -68,7 +111,7 @@ impl ExecutorApprovalService for NoopExecutorApprovalService {
     async fn create_question_approval(
         &self,
         _tool_name: &str,
-        _question_count: usize,
+        _questions: Vec<ApprovalQuestion>,
     ) -> Result<String, ExecutorApprovalError> {
         Ok("noop".to_string())
     }
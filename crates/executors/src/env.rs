@@ -2,6 +2,7 @@ use std::{collections::HashMap, path::PathBuf};
 
 use git::GitService;
 use tokio::process::Command;
+use workspace_utils::{docker::DockerSandbox, resource_limits::ResourceLimits};
 
 use crate::command::CmdOverrides;
 
@@ -81,6 +82,10 @@ pub struct ExecutionEnv {
     pub repo_context: RepoContext,
     pub commit_reminder: bool,
     pub commit_reminder_prompt: String,
+    /// Caps applied to the spawned process tree.
+    pub resource_limits: ResourceLimits,
+    /// Run the process in this Docker container instead of on the host.
+    pub sandbox: Option<DockerSandbox>,
 }
 
 impl ExecutionEnv {
@@ -94,6 +99,8 @@ impl ExecutionEnv {
             repo_context,
             commit_reminder,
             commit_reminder_prompt,
+            resource_limits: ResourceLimits::default(),
+            sandbox: None,
         }
     }
 
@@ -123,11 +130,17 @@ impl ExecutionEnv {
         }
     }
 
-    /// Apply all environment variables to a Command
+    /// Apply all environment variables and resource limits to a Command.
+    /// With a sandbox the command is replaced by the `docker run` that runs
+    /// it, so stdio has to be configured afterwards.
     pub fn apply_to_command(&self, command: &mut Command) {
         for (key, value) in &self.vars {
             command.env(key, value);
         }
+        match &self.sandbox {
+            Some(sandbox) => *command = sandbox.wrap(command, &self.resource_limits),
+            None => self.resource_limits.apply_to_command(command),
+        }
     }
 
     pub fn contains_key(&self, key: &str) -> bool {
@@ -5,7 +5,7 @@ use async_trait::async_trait;
 use tokio::sync::{Mutex, mpsc};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
-use workspace_utils::approvals::ApprovalStatus;
+use workspace_utils::approvals::{ApprovalStatus, ApprovalToolInput};
 
 use crate::{
     approvals::{ExecutorApprovalError, ExecutorApprovalService},
@@ -105,7 +105,11 @@ impl acp::Client for AcpClient {
             .ok_or(ExecutorApprovalError::ServiceUnavailable)
             .map_err(|_| acp::Error::invalid_request())?;
 
-        let approval_id = match approval_service.create_tool_approval(tool_name).await {
+        let tool_input = approval_tool_input(&args.tool_call.fields);
+        let approval_id = match approval_service
+            .create_tool_approval(tool_name, Some(tool_input))
+            .await
+        {
             Ok(id) => id,
             Err(err) => return self.handle_approval_error(err, &tool_call_id),
         };
@@ -279,3 +283,151 @@ impl AcpClient {
         }
     }
 }
+
+/// Pull the command, target file and proposed change out of an ACP tool call,
+/// falling back to its raw input as JSON.
+fn approval_tool_input(fields: &acp::ToolCallUpdateFields) -> ApprovalToolInput {
+    let raw_str = |key: &str| {
+        fields
+            .raw_input
+            .as_ref()
+            .and_then(|input| input.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+
+    let diff = fields.content.as_ref().and_then(|content| {
+        let diffs: Vec<String> = content
+            .iter()
+            .filter_map(|c| match c {
+                acp::ToolCallContent::Diff(diff) => {
+                    let removed = diff
+                        .old_text
+                        .as_deref()
+                        .unwrap_or("")
+                        .lines()
+                        .map(|line| format!("-{line}"));
+                    let added = diff.new_text.lines().map(|line| format!("+{line}"));
+                    Some(removed.chain(added).collect::<Vec<_>>().join("\n"))
+                }
+                _ => None,
+            })
+            .collect();
+        (!diffs.is_empty()).then(|| diffs.join("\n"))
+    });
+
+    let mut tool_input = ApprovalToolInput {
+        command: raw_str("command"),
+        file_path: fields
+            .locations
+            .as_ref()
+            .and_then(|locations| locations.first())
+            .map(|l| l.path.to_string_lossy().to_string())
+            .or_else(|| raw_str("file_path"))
+            .or_else(|| raw_str("path")),
+        diff,
+        ..Default::default()
+    };
+    if tool_input.command.is_none() && tool_input.file_path.is_none() && tool_input.diff.is_none() {
+        tool_input.raw_input = fields
+            .raw_input
+            .as_ref()
+            .filter(|input| !input.is_null())
+            .map(|input| input.to_string());
+    }
+    tool_input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(update: serde_json::Value) -> acp::ToolCallUpdateFields {
+        serde_json::from_value(update).unwrap()
+    }
+
+    #[test]
+    fn execute_tool_has_command() {
+        let input = approval_tool_input(&fields(serde_json::json!({
+            "kind": "execute",
+            "rawInput": { "command": "cargo test", "description": "Run tests" },
+        })));
+        assert_eq!(input.command.as_deref(), Some("cargo test"));
+        assert_eq!(input.file_path, None);
+        assert_eq!(input.diff, None);
+        assert_eq!(input.raw_input, None);
+    }
+
+    #[test]
+    fn edit_tool_has_location_and_diff() {
+        let input = approval_tool_input(&fields(serde_json::json!({
+            "kind": "edit",
+            "locations": [{ "path": "/repo/src/lib.rs" }],
+            "content": [
+                {
+                    "type": "diff",
+                    "path": "/repo/src/lib.rs",
+                    "oldText": "let a = 1;",
+                    "newText": "let a = 2;\nlet b = 3;",
+                },
+                { "type": "content", "content": { "type": "text", "text": "note" } },
+                {
+                    "type": "diff",
+                    "path": "/repo/src/new.rs",
+                    "newText": "fn new() {}",
+                },
+            ],
+        })));
+        assert_eq!(input.file_path.as_deref(), Some("/repo/src/lib.rs"));
+        assert_eq!(
+            input.diff.as_deref(),
+            Some("-let a = 1;\n+let a = 2;\n+let b = 3;\n+fn new() {}")
+        );
+        assert_eq!(input.raw_input, None);
+    }
+
+    #[test]
+    fn read_tool_has_location_only() {
+        let input = approval_tool_input(&fields(serde_json::json!({
+            "kind": "read",
+            "locations": [{ "path": "/repo/README.md", "line": 10 }],
+        })));
+        assert_eq!(input.file_path.as_deref(), Some("/repo/README.md"));
+        assert_eq!(input.command, None);
+        assert_eq!(input.diff, None);
+        assert_eq!(input.raw_input, None);
+    }
+
+    #[test]
+    fn delete_tool_falls_back_to_raw_input_path() {
+        let input = approval_tool_input(&fields(serde_json::json!({
+            "kind": "delete",
+            "rawInput": { "path": "/repo/old.rs" },
+        })));
+        assert_eq!(input.file_path.as_deref(), Some("/repo/old.rs"));
+
+        let input = approval_tool_input(&fields(serde_json::json!({
+            "kind": "edit",
+            "rawInput": { "file_path": "/repo/src/main.rs" },
+        })));
+        assert_eq!(input.file_path.as_deref(), Some("/repo/src/main.rs"));
+    }
+
+    #[test]
+    fn other_tools_pass_their_raw_input_as_json() {
+        let fetch = approval_tool_input(&fields(serde_json::json!({
+            "kind": "fetch",
+            "rawInput": { "url": "https://example.com" },
+        })));
+        assert_eq!(
+            fetch.raw_input.as_deref(),
+            Some(r#"{"url":"https://example.com"}"#)
+        );
+
+        let think = approval_tool_input(&fields(serde_json::json!({
+            "kind": "think",
+            "rawInput": null,
+        })));
+        assert_eq!(think, ApprovalToolInput::default());
+    }
+}
@@ -58,10 +58,6 @@ impl StandardCodingAgentExecutor for Amp {
 
         let mut command = Command::new(executable_path);
         command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .args(&args);
@@ -70,6 +66,12 @@ impl StandardCodingAgentExecutor for Amp {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
         let mut child = command.group_spawn_no_window()?;
 
         // Feed the prompt in, then close the pipe so amp sees EOF
@@ -101,10 +103,6 @@ impl StandardCodingAgentExecutor for Amp {
 
         let mut command = Command::new(continue_program);
         command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .args(&continue_args);
@@ -113,6 +111,12 @@ impl StandardCodingAgentExecutor for Amp {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
         let mut child = command.group_spawn_no_window()?;
 
         // Feed the prompt in, then close the pipe so amp sees EOF
@@ -23,13 +23,17 @@ use workspace_utils::{
     approvals::{ApprovalStatus, QuestionStatus},
     command_ext::GroupSpawnNoWindowExt,
     diff::create_unified_diff,
+    json_stream::{JsonLineDecoder, scan_string_field},
     log_msg::LogMsg,
     msg_store::MsgStore,
     path::make_path_relative,
 };
 
 use self::{
-    client::{AUTO_APPROVE_CALLBACK_ID, ClaudeAgentClient, STOP_GIT_CHECK_CALLBACK_ID},
+    client::{
+        AUTO_APPROVE_CALLBACK_ID, ClaudeAgentClient, PROTECTED_PATH_CALLBACK_ID,
+        STOP_GIT_CHECK_CALLBACK_ID,
+    },
     protocol::ProtocolPeer,
     types::{ControlRequestType, ControlResponseType, PermissionMode},
 };
@@ -58,6 +62,12 @@ use crate::{
 
 const SUPPRESSED_STDERR_PATTERNS: &[&str] = &["[WARN] Fast mode requires the native binary"];
 
+/// Command output shorter than this is shown only once the tool result completes.
+const PARTIAL_OUTPUT_PREVIEW_MIN_BYTES: usize = 16 * 1024;
+
+/// Tools that change files at their `file_path` or `notebook_path`.
+const FILE_WRITE_TOOLS_MATCHER: &str = "^(Edit|MultiEdit|Write|NotebookEdit)$";
+
 fn base_command(claude_code_router: bool) -> &'static str {
     if claude_code_router {
         "npx -y @musistudio/claude-code-router@1.0.66 code"
@@ -205,6 +215,8 @@ impl ClaudeCode {
         }
     }
 
+    /// Writes to protected paths are checked even without approvals; with
+    /// them, every write already goes through the approval service.
     pub fn get_hooks(&self, commit_reminder: bool) -> Option<serde_json::Value> {
         let mut hooks = serde_json::Map::new();
 
@@ -229,6 +241,10 @@ impl ClaudeCode {
                     {
                         "matcher": "^(?!(ExitPlanMode|AskUserQuestion)$).*",
                         "hookCallbackIds": [AUTO_APPROVE_CALLBACK_ID],
+                    },
+                    {
+                        "matcher": FILE_WRITE_TOOLS_MATCHER,
+                        "hookCallbackIds": [PROTECTED_PATH_CALLBACK_ID],
                     }
                 ]),
             );
@@ -249,6 +265,10 @@ impl ClaudeCode {
                     {
                         "matcher": "^AskUserQuestion$",
                         "hookCallbackIds": ["tool_approval"],
+                    },
+                    {
+                        "matcher": FILE_WRITE_TOOLS_MATCHER,
+                        "hookCallbackIds": [PROTECTED_PATH_CALLBACK_ID],
                     }
                 ]),
             );
@@ -623,10 +643,6 @@ impl ClaudeCode {
 
         let mut command = Command::new(program_path);
         command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .args(&args);
@@ -635,6 +651,12 @@ impl ClaudeCode {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
         // Remove ANTHROPIC_API_KEY if disable_api_key is enabled
         if self.disable_api_key.unwrap_or(false) {
             command.env_remove("ANTHROPIC_API_KEY");
@@ -762,7 +784,9 @@ impl ClaudeLogProcessor {
         let current_dir_clone = current_dir.to_owned();
         tokio::spawn(async move {
             let mut stream = msg_store.history_plus_stream();
-            let mut buffer = String::new();
+            let mut decoder = JsonLineDecoder::new();
+            // Pending line length at which the next partial output preview is due
+            let mut next_preview_at = PARTIAL_OUTPUT_PREVIEW_MIN_BYTES;
             let worktree_path = current_dir_clone.to_string_lossy().to_string();
             let mut session_id_extracted = false;
             let mut processor = Self::new_with_strategy(strategy);
@@ -775,20 +799,19 @@ impl ClaudeLogProcessor {
                     LogMsg::JsonPatch(_)
                     | LogMsg::SessionId(_)
                     | LogMsg::MessageId(_)
+                    | LogMsg::Span(_)
                     | LogMsg::Stderr(_)
                     | LogMsg::Ready => continue,
                     LogMsg::Finished => break,
                 };
 
-                buffer.push_str(&chunk);
+                let lines = decoder.push(&chunk);
+                if !lines.is_empty() {
+                    next_preview_at = PARTIAL_OUTPUT_PREVIEW_MIN_BYTES;
+                }
 
                 // Process complete JSON lines
-                for line in buffer
-                    .split_inclusive('\n')
-                    .filter(|l| l.ends_with('\n'))
-                    .map(str::to_owned)
-                    .collect::<Vec<_>>()
-                {
+                for line in lines {
                     let trimmed = line.trim();
                     if trimmed.is_empty() {
                         continue;
@@ -861,16 +884,24 @@ impl ClaudeLogProcessor {
                     }
                 }
 
-                // Keep the partial line in the buffer
-                buffer = buffer.rsplit('\n').next().unwrap_or("").to_owned();
+                // Preview large command output while its line is still arriving.
+                // Previews grow geometrically so rescanning the line stays linear.
+                let pending_len = decoder.pending().len();
+                if pending_len >= next_preview_at {
+                    next_preview_at = pending_len + pending_len / 2;
+                    if let Some(patch) = processor.partial_command_output_patch(decoder.pending()) {
+                        msg_store.push_patch(patch);
+                    }
+                }
             }
 
             // Handle any remaining content in buffer
-            if !buffer.trim().is_empty() {
+            let remaining = decoder.finish();
+            if !remaining.trim().is_empty() {
                 let entry = NormalizedEntry {
                     timestamp: None,
                     entry_type: NormalizedEntryType::SystemMessage,
-                    content: buffer.trim().to_string(),
+                    content: remaining.trim().to_string(),
                     metadata: None,
                 };
 
@@ -881,6 +912,35 @@ impl ClaudeLogProcessor {
         })
     }
 
+    /// Replace a Bash tool entry with the output received so far, read from
+    /// the incomplete `tool_result` line that carries it.
+    fn partial_command_output_patch(&self, line: &str) -> Option<json_patch::Patch> {
+        let tool_use_id = scan_string_field(line, "tool_use_id", 0)?;
+        if !tool_use_id.complete {
+            return None;
+        }
+        let info = self.tool_map.get(&tool_use_id.value)?;
+        if !matches!(info.tool_data, ClaudeToolData::Bash { .. }) {
+            return None;
+        }
+        let output = scan_string_field(line, "content", tool_use_id.end)?;
+
+        let entry = Self::tool_use_entry(
+            info.tool_name.clone(),
+            ActionType::CommandRun {
+                command: info.content.clone(),
+                result: Some(crate::logs::CommandRunResult {
+                    exit_status: None,
+                    output: Some(output.value),
+                }),
+                category: CommandCategory::from_command(&info.content),
+            },
+            ToolStatus::Created,
+            info.content.clone(),
+        );
+        Some(ConversationPatch::replace(info.entry_index, entry))
+    }
+
     /// Extract session ID from Claude JSON
     fn extract_session_id(claude_json: &ClaudeJson) -> Option<String> {
         match claude_json {
@@ -3136,6 +3196,34 @@ mod tests {
         assert_eq!(entries[0].content, "Task: `Add header to README`");
     }
 
+    #[test]
+    fn test_partial_bash_output_preview() {
+        let mut processor = ClaudeLogProcessor::new();
+        let bash_json = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cat log.txt"}}]}}"#;
+        let parsed: ClaudeJson = serde_json::from_str(bash_json).unwrap();
+        normalize_helper(&mut processor, &parsed, "/tmp/work");
+
+        let partial = r#"{"type":"user","message":{"role":"user","content":[{"tool_use_id":"t1","type":"tool_result","content":"first\nsec"#;
+        let patch = processor.partial_command_output_patch(partial).unwrap();
+        let entries = patches_to_entries(&[patch]);
+        let NormalizedEntryType::ToolUse {
+            action_type: ActionType::CommandRun { result, .. },
+            status,
+            ..
+        } = &entries[0].entry_type
+        else {
+            panic!("expected a command run entry");
+        };
+        assert!(matches!(status, ToolStatus::Created));
+        let result = result.as_ref().unwrap();
+        assert_eq!(result.output.as_deref(), Some("first\nsec"));
+        assert!(result.exit_status.is_none());
+
+        // Results for unknown tools are left alone
+        let unknown = partial.replace("\"t1\"", "\"t2\"");
+        assert!(processor.partial_command_output_patch(&unknown).is_none());
+    }
+
     #[test]
     fn test_task_description_or_prompt_backticks() {
         // When description present, use it
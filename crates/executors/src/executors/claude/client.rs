@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
 use tokio_util::sync::CancellationToken;
-use workspace_utils::approvals::{ApprovalStatus, QuestionStatus};
+use workspace_utils::approvals::{
+    ApprovalQuestion, ApprovalQuestionOption, ApprovalStatus, ApprovalToolInput, QuestionStatus,
+};
 
 use super::types::PermissionMode;
 use crate::{
-    approvals::{ExecutorApprovalError, ExecutorApprovalService},
+    approvals::{ExecutorApprovalError, ExecutorApprovalService, ToolCallPolicy},
     env::RepoContext,
     executors::{
         ExecutorError,
@@ -24,6 +26,7 @@ const EXIT_PLAN_MODE_NAME: &str = "ExitPlanMode";
 const ASK_USER_QUESTION_NAME: &str = "AskUserQuestion";
 pub const AUTO_APPROVE_CALLBACK_ID: &str = "AUTO_APPROVE_CALLBACK_ID";
 pub const STOP_GIT_CHECK_CALLBACK_ID: &str = "STOP_GIT_CHECK_CALLBACK_ID";
+pub const PROTECTED_PATH_CALLBACK_ID: &str = "PROTECTED_PATH_CALLBACK_ID";
 // Prefix for denial messages from the user, mirrors claude code CLI behavior
 const TOOL_DENY_PREFIX: &str = "The user doesn't want to proceed with this tool use. The tool use was rejected (eg. if it was a file edit, the new_string was NOT written to the file). To tell you how to proceed, the user said: ";
 
@@ -68,7 +71,24 @@ impl ClaudeAgentClient {
             .as_ref()
             .ok_or(ExecutorApprovalError::ServiceUnavailable)?;
 
-        let approval_id = match approval_service.create_tool_approval(&tool_name).await {
+        let plan = if tool_name == EXIT_PLAN_MODE_NAME {
+            tool_input.get("plan").and_then(|plan| plan.as_str())
+        } else {
+            None
+        };
+        let approval = match plan {
+            Some(plan) => {
+                approval_service
+                    .create_plan_approval(&tool_name, plan)
+                    .await
+            }
+            None => {
+                approval_service
+                    .create_tool_approval(&tool_name, Some(approval_tool_input(&tool_input)))
+                    .await
+            }
+        };
+        let approval_id = match approval {
             Ok(id) => id,
             Err(err) => {
                 self.handle_approval_error(&tool_name, &tool_use_id, &err)
@@ -153,14 +173,8 @@ impl ClaudeAgentClient {
             .as_ref()
             .ok_or(ExecutorApprovalError::ServiceUnavailable)?;
 
-        let question_count = tool_input
-            .get("questions")
-            .and_then(|q| q.as_array())
-            .map(|a| a.len())
-            .unwrap_or(1);
-
         let approval_id = match approval_service
-            .create_question_approval(&tool_name, question_count)
+            .create_question_approval(&tool_name, approval_questions(&tool_input))
             .await
         {
             Ok(id) => id,
@@ -346,6 +360,10 @@ impl ClaudeAgentClient {
             });
         }
 
+        if callback_id == PROTECTED_PATH_CALLBACK_ID {
+            return Ok(self.protected_path_decision(&input).await);
+        }
+
         if self.auto_approve {
             Ok(serde_json::json!({
                 "hookSpecificOutput": {
@@ -379,7 +397,172 @@ impl ClaudeAgentClient {
         }
     }
 
+    /// PreToolUse decision for a file write in a session that doesn't ask
+    /// for approvals. No decision leaves the call to the permission mode.
+    async fn protected_path_decision(&self, input: &serde_json::Value) -> serde_json::Value {
+        let Some(approvals) = self.approvals.as_ref() else {
+            return serde_json::json!({});
+        };
+        let tool_name = input
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let tool_input = input
+            .get("tool_input")
+            .map(approval_tool_input)
+            .unwrap_or_default();
+        let (decision, reason) = match approvals.tool_call_policy(tool_name, &tool_input).await {
+            ToolCallPolicy::Default => return serde_json::json!({}),
+            ToolCallPolicy::RequireApproval => (
+                "ask",
+                "Protected path, forwarding to canusetool service".to_string(),
+            ),
+            ToolCallPolicy::Deny { reason } => ("deny", reason),
+        };
+        serde_json::json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": decision,
+                "permissionDecisionReason": reason
+            }
+        })
+    }
+
     pub async fn log_message(&self, line: &str) -> Result<(), ExecutorError> {
         self.log_writer.log_raw(line).await
     }
 }
+
+/// The questions of an `AskUserQuestion` call.
+fn approval_questions(input: &serde_json::Value) -> Vec<ApprovalQuestion> {
+    let str_field = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    input
+        .get("questions")
+        .and_then(|q| q.as_array())
+        .into_iter()
+        .flatten()
+        .map(|question| {
+            let options = question
+                .get("options")
+                .and_then(|o| o.as_array())
+                .into_iter()
+                .flatten()
+                .map(|option| {
+                    ApprovalQuestionOption::new(
+                        str_field(option, "label"),
+                        str_field(option, "description"),
+                    )
+                })
+                .collect();
+            ApprovalQuestion::new(
+                str_field(question, "question"),
+                Some(str_field(question, "header")),
+                options,
+                question
+                    .get("multiSelect")
+                    .and_then(|m| m.as_bool())
+                    .unwrap_or(false),
+            )
+        })
+        .collect()
+}
+
+/// Pull the command, path and proposed change out of a Claude tool input.
+/// Inputs of other tools are passed on as JSON.
+fn approval_tool_input(input: &serde_json::Value) -> ApprovalToolInput {
+    let str_field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+    let edits: Vec<(&str, &str)> = match input.get("edits").and_then(|v| v.as_array()) {
+        Some(edits) => edits
+            .iter()
+            .filter_map(|edit| {
+                Some((
+                    edit.get("old_string")?.as_str()?,
+                    edit.get("new_string")?.as_str()?,
+                ))
+            })
+            .collect(),
+        None => input
+            .get("old_string")
+            .and_then(|v| v.as_str())
+            .zip(input.get("new_string").and_then(|v| v.as_str()))
+            .into_iter()
+            .collect(),
+    };
+    let diff = if !edits.is_empty() {
+        Some(
+            edits
+                .iter()
+                .map(|(old, new)| {
+                    let removed = old.lines().map(|line| format!("-{line}"));
+                    let added = new.lines().map(|line| format!("+{line}"));
+                    removed.chain(added).collect::<Vec<_>>().join("\n")
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    } else {
+        str_field("content")
+            .or_else(|| str_field("new_source"))
+            .map(|content| {
+                content
+                    .lines()
+                    .map(|line| format!("+{line}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+    };
+
+    let mut tool_input = ApprovalToolInput {
+        command: str_field("command"),
+        file_path: str_field("file_path").or_else(|| str_field("notebook_path")),
+        diff,
+        ..Default::default()
+    };
+    if tool_input.command.is_none() && tool_input.file_path.is_none() && tool_input.diff.is_none() {
+        tool_input.raw_input = serde_json::to_string(input).ok();
+    }
+    tool_input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approval_input_for_edit_has_path_and_diff() {
+        let input = approval_tool_input(&serde_json::json!({
+            "file_path": "src/lib.rs",
+            "old_string": "let a = 1;",
+            "new_string": "let a = 2;\nlet b = 3;",
+        }));
+        assert_eq!(input.file_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(
+            input.diff.as_deref(),
+            Some("-let a = 1;\n+let a = 2;\n+let b = 3;")
+        );
+        assert_eq!(input.raw_input, None);
+    }
+
+    #[test]
+    fn approval_input_for_bash_and_other_tools() {
+        let bash = approval_tool_input(&serde_json::json!({
+            "command": "rm -rf target",
+            "description": "Clean build output",
+        }));
+        assert_eq!(bash.command.as_deref(), Some("rm -rf target"));
+        assert_eq!(bash.diff, None);
+
+        let fetch = approval_tool_input(&serde_json::json!({ "url": "https://example.com" }));
+        assert_eq!(
+            fetch.raw_input.as_deref(),
+            Some(r#"{"url":"https://example.com"}"#)
+        );
+    }
+}
@@ -221,18 +221,18 @@ impl ClaudeCode {
         let (program_path, args) = command_parts.into_resolved().await?;
 
         let mut command = Command::new(program_path);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .current_dir(current_dir)
-            .args(&args);
+        command.current_dir(current_dir).args(&args);
 
         ExecutionEnv::new(RepoContext::default(), false, String::new())
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+
         if self.disable_api_key.unwrap_or(false) {
             command.env_remove("ANTHROPIC_API_KEY");
         }
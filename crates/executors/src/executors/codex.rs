@@ -636,10 +636,6 @@ impl Codex {
 
         let mut process = Command::new(program_path);
         process
-            .kill_on_drop(true)
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .env("NODE_NO_WARNINGS", "1")
@@ -651,6 +647,12 @@ impl Codex {
             .with_profile(&self.cmd)
             .apply_to_command(&mut process);
 
+        process
+            .kill_on_drop(true)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+
         let mut child = process.group_spawn_no_window()?;
 
         let child_stdout = child.inner().stdout.take().ok_or_else(|| {
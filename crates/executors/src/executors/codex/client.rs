@@ -32,7 +32,9 @@ use tokio::{
     sync::Mutex,
 };
 use tokio_util::sync::CancellationToken;
-use workspace_utils::approvals::{ApprovalStatus, QuestionStatus};
+use workspace_utils::approvals::{
+    ApprovalQuestion, ApprovalQuestionOption, ApprovalStatus, QuestionStatus,
+};
 
 use super::jsonrpc::{JsonRpcCallbacks, JsonRpcPeer};
 use crate::{
@@ -43,6 +45,7 @@ use crate::{
 
 struct PendingPlan {
     item_id: String,
+    text: String,
 }
 
 pub struct AppServerClient {
@@ -377,9 +380,31 @@ impl AppServerClient {
             }
             ServerRequest::ToolRequestUserInput { request_id, params } => {
                 let call_id = params.item_id.clone();
-                let question_count = params.questions.len();
+                let questions = params
+                    .questions
+                    .iter()
+                    .map(|question| {
+                        let options = question
+                            .options
+                            .iter()
+                            .flatten()
+                            .map(|option| {
+                                ApprovalQuestionOption::new(
+                                    option.label.clone(),
+                                    option.description.clone(),
+                                )
+                            })
+                            .collect();
+                        ApprovalQuestion::new(
+                            question.question.clone(),
+                            Some(question.header.clone()),
+                            options,
+                            false,
+                        )
+                    })
+                    .collect();
                 let status = self
-                    .request_question_answer(question_count, &call_id)
+                    .request_question_answer(questions, &call_id)
                     .await
                     .inspect_err(|err| {
                         if !matches!(
@@ -467,7 +492,7 @@ impl AppServerClient {
             .ok_or(ExecutorApprovalError::ServiceUnavailable)?;
 
         let approval_id = approval_service
-            .create_tool_approval(tool_name)
+            .create_tool_approval(tool_name, None)
             .or_else(|err| async {
                 self.handle_approval_error(display_tool_name, tool_call_id)
                     .await;
@@ -514,7 +539,7 @@ impl AppServerClient {
 
     async fn request_question_answer(
         &self,
-        question_count: usize,
+        questions: Vec<ApprovalQuestion>,
         tool_call_id: &str,
     ) -> Result<QuestionStatus, ExecutorError> {
         let approval_service = self
@@ -523,7 +548,7 @@ impl AppServerClient {
             .ok_or(ExecutorApprovalError::ServiceUnavailable)?;
 
         let approval_id = approval_service
-            .create_question_approval("question", question_count)
+            .create_question_approval("question", questions)
             .or_else(|err| async {
                 self.handle_question_error(tool_call_id).await;
                 Err(err)
@@ -569,7 +594,7 @@ impl AppServerClient {
             .ok_or(ExecutorApprovalError::ServiceUnavailable)?;
 
         let approval_id = approval_service
-            .create_tool_approval("plan")
+            .create_plan_approval("plan", &plan.text)
             .or_else(|err| async {
                 self.handle_approval_error("codex.plan", &plan.item_id)
                     .await;
@@ -864,9 +889,9 @@ impl JsonRpcCallbacks for AppServerClient {
             && let Some(ref params) = notification.params
             && let Ok(completed) =
                 serde_json::from_value::<ItemCompletedNotification>(params.clone())
-            && let ThreadItem::Plan { id, .. } = completed.item
+            && let ThreadItem::Plan { id, text } = completed.item
         {
-            *self.pending_plan.lock().await = Some(PendingPlan { item_id: id });
+            *self.pending_plan.lock().await = Some(PendingPlan { item_id: id, text });
         }
 
         // V2 turn completion detection
@@ -199,10 +199,6 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
         let mut command = Command::new(executable_path);
         command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .args(&args);
@@ -211,6 +207,12 @@ impl StandardCodingAgentExecutor for CursorAgent {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
         let mut child = command.group_spawn_no_window()?;
 
         if let Some(mut stdin) = child.inner().stdin.take() {
@@ -240,10 +242,6 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
         let mut command = Command::new(executable_path);
         command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .args(&args);
@@ -252,6 +250,12 @@ impl StandardCodingAgentExecutor for CursorAgent {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
         let mut child = command.group_spawn_no_window()?;
 
         if let Some(mut stdin) = child.inner().stdin.take() {
@@ -118,10 +118,6 @@ async fn spawn_droid(
 
     let mut command = Command::new(program_path);
     command
-        .kill_on_drop(true)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
         .current_dir(current_dir)
         .env("NPM_CONFIG_LOGLEVEL", "error")
         .args(args);
@@ -130,6 +126,12 @@ async fn spawn_droid(
         .with_profile(cmd_overrides)
         .apply_to_command(&mut command);
 
+    command
+        .kill_on_drop(true)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
     let mut child = command.group_spawn_no_window()?;
 
     if let Some(mut stdin) = child.inner().stdin.take() {
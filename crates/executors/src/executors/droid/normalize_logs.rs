@@ -4,11 +4,14 @@ use std::{
     sync::Arc,
 };
 
-use futures::{StreamExt, future::ready};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use workspace_utils::{
-    diff::normalize_unified_diff, msg_store::MsgStore, path::make_path_relative,
+    diff::normalize_unified_diff,
+    json_stream::{JsonLineEvent, json_line_events, scan_string_field},
+    msg_store::MsgStore,
+    path::make_path_relative,
 };
 
 use crate::logs::{
@@ -22,6 +25,9 @@ use crate::logs::{
     },
 };
 
+/// Assistant messages shorter than this are shown only once they complete.
+const PARTIAL_MESSAGE_PREVIEW_MIN_BYTES: usize = 4 * 1024;
+
 pub fn normalize_logs(
     msg_store: Arc<MsgStore>,
     worktree_path: &Path,
@@ -37,11 +43,40 @@ pub fn normalize_logs(
 
         let worktree_path_str = worktree_path.to_string_lossy();
 
-        let mut lines_stream = msg_store
-            .stdout_lines_stream()
-            .filter_map(|res| ready(res.ok()));
+        // Entry showing an assistant message whose line is still arriving
+        let mut assistant_preview: Option<usize> = None;
+
+        let mut lines_stream = json_line_events(
+            msg_store.stdout_chunked_stream(),
+            PARTIAL_MESSAGE_PREVIEW_MIN_BYTES,
+        );
 
-        while let Some(line) = lines_stream.next().await {
+        while let Some(event) = lines_stream.next().await {
+            let line = match event {
+                JsonLineEvent::Line(line) => line,
+                JsonLineEvent::Partial(partial) => {
+                    if !sent_completion && let Some(text) = partial_assistant_text(&partial) {
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::AssistantMessage,
+                            content: text,
+                            metadata: None,
+                        };
+                        match assistant_preview {
+                            Some(index) => replace_normalized_entry(&msg_store, index, entry),
+                            None => {
+                                assistant_preview = Some(add_normalized_entry(
+                                    &msg_store,
+                                    &entry_index_provider,
+                                    entry,
+                                ));
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+            let preview_index = assistant_preview.take();
             let trimmed = line.trim();
             let droid_json = match serde_json::from_str::<DroidJson>(trimmed) {
                 Ok(droid_json) => droid_json,
@@ -116,7 +151,14 @@ pub fn normalize_logs(
                         metadata: None,
                     };
 
-                    add_normalized_entry(&msg_store, &entry_index_provider, entry);
+                    match preview_index {
+                        Some(index) if role == "assistant" => {
+                            replace_normalized_entry(&msg_store, index, entry)
+                        }
+                        _ => {
+                            add_normalized_entry(&msg_store, &entry_index_provider, entry);
+                        }
+                    }
                 }
 
                 DroidJson::ToolCall {
@@ -708,6 +750,18 @@ fn normalize_stderr_logs(
     })
 }
 
+/// Text received so far of an assistant message whose line is still arriving.
+fn partial_assistant_text(line: &str) -> Option<String> {
+    let message_type = scan_string_field(line, "type", 0)?;
+    let role = scan_string_field(line, "role", 0)?;
+    if !(message_type.complete && message_type.value == "message")
+        || !(role.complete && role.value == "assistant")
+    {
+        return None;
+    }
+    scan_string_field(line, "text", 0).map(|text| text.value)
+}
+
 /// Extract path from ApplyPatch input format
 fn extract_path_from_patch(input: &str) -> String {
     for line in input.lines() {
@@ -114,10 +114,6 @@ impl Opencode {
 
         let mut command = Command::new(program_path);
         command
-            .kill_on_drop(true)
-            .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
             .current_dir(current_dir)
             .env("NPM_CONFIG_LOGLEVEL", "error")
             .env("NODE_NO_WARNINGS", "1")
@@ -130,6 +126,12 @@ impl Opencode {
             .with_profile(&self.cmd)
             .apply_to_command(&mut command);
 
+        command
+            .kill_on_drop(true)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+
         let child = command.group_spawn_no_window()?;
 
         Ok((child, server_password))
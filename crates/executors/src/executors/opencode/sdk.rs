@@ -18,11 +18,13 @@ use tokio::{
     sync::{Mutex as AsyncMutex, mpsc, oneshot},
 };
 use tokio_util::sync::CancellationToken;
-use workspace_utils::approvals::{ApprovalStatus, QuestionAnswer, QuestionStatus};
+use workspace_utils::approvals::{
+    ApprovalQuestion, ApprovalQuestionOption, ApprovalStatus, QuestionAnswer, QuestionStatus,
+};
 
 use super::{
     slash_commands,
-    types::{OpencodeExecutorEvent, ProviderInfo, ProviderListResponse},
+    types::{OpencodeExecutorEvent, ProviderInfo, ProviderListResponse, QuestionInfo},
 };
 use crate::{
     approvals::{ExecutorApprovalError, ExecutorApprovalService},
@@ -1372,7 +1374,7 @@ async fn process_event_stream(
                     .and_then(Value::as_array)
                     .cloned()
                     .unwrap_or_default();
-                let question_count = questions.len().max(1);
+                let approval_questions = approval_questions(&questions);
 
                 let approvals = ctx.approvals.clone();
                 let client = ctx.client.clone();
@@ -1382,8 +1384,11 @@ async fn process_event_stream(
                 let cancel = ctx.cancel.clone();
                 let done_tx = ctx.pending_approvals.push().await;
                 tokio::spawn(async move {
-                    let status = match create_question_approval(approvals.clone(), question_count)
-                        .await
+                    let status = match create_question_approval(
+                        approvals.clone(),
+                        approval_questions,
+                    )
+                    .await
                     {
                         Ok(created) => {
                             let _ = log_writer
@@ -1720,7 +1725,7 @@ async fn create_permission_approval(
         return Ok(None);
     };
 
-    match approvals.create_tool_approval(tool_name).await {
+    match approvals.create_tool_approval(tool_name, None).await {
         Ok(approval_id) => Ok(Some(ApprovalCreated { approval_id })),
         Err(
             ExecutorApprovalError::ServiceUnavailable | ExecutorApprovalError::SessionNotRegistered,
@@ -1743,14 +1748,14 @@ async fn wait_permission_approval(
 
 async fn create_question_approval(
     approvals: Option<Arc<dyn ExecutorApprovalService>>,
-    question_count: usize,
+    questions: Vec<ApprovalQuestion>,
 ) -> Result<ApprovalCreated, ExecutorApprovalError> {
     let Some(approvals) = approvals else {
         return Err(ExecutorApprovalError::ServiceUnavailable);
     };
 
     let approval_id = approvals
-        .create_question_approval("question", question_count)
+        .create_question_approval("question", questions)
         .await?;
     Ok(ApprovalCreated { approval_id })
 }
@@ -1767,6 +1772,26 @@ async fn wait_question_approval(
     approvals.wait_question_answer(approval_id, cancel).await
 }
 
+fn approval_questions(questions: &[Value]) -> Vec<ApprovalQuestion> {
+    questions
+        .iter()
+        .filter_map(|question| serde_json::from_value::<QuestionInfo>(question.clone()).ok())
+        .map(|info| {
+            let options = info
+                .options
+                .into_iter()
+                .map(|option| ApprovalQuestionOption::new(option.label, option.description))
+                .collect();
+            ApprovalQuestion::new(
+                info.question,
+                Some(info.header),
+                options,
+                info.multiple.unwrap_or(false),
+            )
+        })
+        .collect()
+}
+
 fn answers_to_opencode_format(questions: &[Value], answers: &[QuestionAnswer]) -> Vec<Vec<String>> {
     questions
         .iter()
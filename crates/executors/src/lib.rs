@@ -7,5 +7,6 @@ pub mod executors;
 pub mod logs;
 pub mod mcp_config;
 pub mod model_selector;
+pub mod one_shot;
 pub mod profile;
 pub mod stdout_dup;
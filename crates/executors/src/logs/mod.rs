@@ -5,6 +5,7 @@ use workspace_utils::approvals::{ApprovalStatus, QuestionStatus};
 use crate::logs::utils::shell_command_parsing::CommandCategory;
 
 pub mod plain_text_processor;
+pub mod spans;
 pub mod stderr_processor;
 pub mod utils;
 
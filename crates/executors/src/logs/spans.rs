@@ -0,0 +1,242 @@
+//! Derive structured [`LogSpan`]s from the normalized conversation patches an
+//! executor emits, so stored logs describe tool calls without re-parsing the
+//! agent's raw output.
+
+use std::collections::HashSet;
+
+use json_patch::Patch;
+use workspace_utils::log_msg::{LogSpan, LogSpanStatus};
+
+use crate::logs::{
+    ActionType, CommandExitStatus, NormalizedEntry, NormalizedEntryType, ToolStatus,
+    utils::patch::extract_normalized_entry_from_patch,
+};
+
+/// Tracks which tool calls were already reported so each one produces a
+/// single `ToolStart` and a single `ToolEnd`, however many times its entry is
+/// replaced.
+#[derive(Debug, Default)]
+pub struct LogSpanTracker {
+    started: HashSet<usize>,
+    ended: HashSet<usize>,
+    tokens: Option<(u32, u32)>,
+}
+
+impl LogSpanTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spans opened or closed by a normalized log patch, in order.
+    pub fn push_patch(&mut self, patch: &Patch) -> Vec<LogSpan> {
+        match extract_normalized_entry_from_patch(patch) {
+            Some((index, entry)) => self.push_entry(index, &entry),
+            None => Vec::new(),
+        }
+    }
+
+    fn push_entry(&mut self, index: usize, entry: &NormalizedEntry) -> Vec<LogSpan> {
+        let mut spans = Vec::new();
+        match &entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type,
+                status,
+            } => {
+                let id = format!("entry-{index}");
+                if self.started.insert(index) {
+                    spans.push(LogSpan::ToolStart {
+                        id: id.clone(),
+                        tool_name: tool_name.clone(),
+                        action: action_name(action_type),
+                        command: match action_type {
+                            ActionType::CommandRun { command, .. } => Some(command.clone()),
+                            _ => None,
+                        },
+                        files: files_touched(action_type),
+                        timestamp: entry.timestamp.clone(),
+                    });
+                }
+                if let Some(status) = end_status(status)
+                    && self.ended.insert(index)
+                {
+                    spans.push(LogSpan::ToolEnd {
+                        id,
+                        status,
+                        exit_code: exit_code(action_type),
+                        timestamp: entry.timestamp.clone(),
+                    });
+                }
+            }
+            NormalizedEntryType::TokenUsageInfo(usage) => {
+                let tokens = (usage.total_tokens, usage.model_context_window);
+                if self.tokens != Some(tokens) {
+                    self.tokens = Some(tokens);
+                    spans.push(LogSpan::Tokens {
+                        total_tokens: usage.total_tokens,
+                        model_context_window: usage.model_context_window,
+                    });
+                }
+            }
+            _ => {}
+        }
+        spans
+    }
+}
+
+/// Replay a process's normalized log patches into spans.
+pub fn spans_from_patches(patches: &[Patch]) -> Vec<LogSpan> {
+    let mut tracker = LogSpanTracker::new();
+    patches
+        .iter()
+        .flat_map(|patch| tracker.push_patch(patch))
+        .collect()
+}
+
+fn action_name(action_type: &ActionType) -> String {
+    serde_json::to_value(action_type)
+        .ok()
+        .and_then(|value| value.get("action")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "other".to_string())
+}
+
+fn files_touched(action_type: &ActionType) -> Vec<String> {
+    match action_type {
+        ActionType::FileRead { path } | ActionType::FileEdit { path, .. } if !path.is_empty() => {
+            vec![path.clone()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn exit_code(action_type: &ActionType) -> Option<i32> {
+    match action_type {
+        ActionType::CommandRun {
+            result: Some(result),
+            ..
+        } => match result.exit_status {
+            Some(CommandExitStatus::ExitCode { code }) => Some(code),
+            Some(CommandExitStatus::Success { success }) => Some(if success { 0 } else { 1 }),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+fn end_status(status: &ToolStatus) -> Option<LogSpanStatus> {
+    match status {
+        ToolStatus::Success => Some(LogSpanStatus::Success),
+        ToolStatus::Failed => Some(LogSpanStatus::Failed),
+        ToolStatus::Denied { .. } => Some(LogSpanStatus::Denied),
+        ToolStatus::TimedOut => Some(LogSpanStatus::TimedOut),
+        ToolStatus::Created | ToolStatus::PendingApproval { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::{
+        CommandRunResult, TokenUsageInfo,
+        utils::{ConversationPatch, shell_command_parsing::CommandCategory},
+    };
+
+    fn command(status: ToolStatus, exit: Option<i32>) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: "cargo test".to_string(),
+                    result: exit.map(|code| CommandRunResult {
+                        exit_status: Some(CommandExitStatus::ExitCode { code }),
+                        output: None,
+                    }),
+                    category: CommandCategory::from_command("cargo test"),
+                },
+                status,
+            },
+            content: "cargo test".to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn tool_call_opens_and_closes_one_span() {
+        let spans = spans_from_patches(&[
+            ConversationPatch::add_normalized_entry(3, command(ToolStatus::Created, None)),
+            ConversationPatch::replace(3, command(ToolStatus::Created, None)),
+            ConversationPatch::replace(3, command(ToolStatus::Failed, Some(101))),
+            ConversationPatch::replace(3, command(ToolStatus::Failed, Some(101))),
+        ]);
+
+        assert_eq!(spans.len(), 2);
+        assert!(matches!(
+            &spans[0],
+            LogSpan::ToolStart { id, action, command: Some(cmd), .. }
+                if id == "entry-3" && action == "command_run" && cmd == "cargo test"
+        ));
+        assert_eq!(
+            spans[1],
+            LogSpan::ToolEnd {
+                id: "entry-3".to_string(),
+                status: LogSpanStatus::Failed,
+                exit_code: Some(101),
+                timestamp: None,
+            }
+        );
+    }
+
+    #[test]
+    fn file_edits_and_token_usage_are_recorded() {
+        let edit = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Edit".to_string(),
+                action_type: ActionType::FileEdit {
+                    path: "src/lib.rs".to_string(),
+                    changes: Vec::new(),
+                },
+                status: ToolStatus::Success,
+            },
+            content: String::new(),
+            metadata: None,
+        };
+        let usage = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::TokenUsageInfo(TokenUsageInfo {
+                total_tokens: 1200,
+                model_context_window: 200_000,
+            }),
+            content: String::new(),
+            metadata: None,
+        };
+
+        let spans = spans_from_patches(&[
+            ConversationPatch::add_normalized_entry(0, edit),
+            ConversationPatch::add_normalized_entry(1, usage.clone()),
+            ConversationPatch::replace(1, usage),
+        ]);
+
+        assert!(matches!(
+            &spans[0],
+            LogSpan::ToolStart { files, .. } if files == &vec!["src/lib.rs".to_string()]
+        ));
+        assert!(matches!(
+            &spans[1],
+            LogSpan::ToolEnd {
+                status: LogSpanStatus::Success,
+                exit_code: None,
+                ..
+            }
+        ));
+        assert_eq!(
+            spans[2],
+            LogSpan::Tokens {
+                total_tokens: 1200,
+                model_context_window: 200_000,
+            }
+        );
+        assert_eq!(spans.len(), 3);
+    }
+}
@@ -0,0 +1,130 @@
+//! Run a coding agent once for a short helper prompt (e.g. a commit message)
+//! and return its final reply, without creating a session or an execution
+//! process. The agent runs in an empty scratch directory, so everything it
+//! needs has to be in the prompt and it has nothing to modify.
+
+use std::{path::Path, sync::Arc, time::Duration};
+
+use futures::{FutureExt, TryStreamExt, stream::select};
+use thiserror::Error;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore, process::kill_process_group};
+
+use crate::{
+    approvals::NoopExecutorApprovalService,
+    env::{ExecutionEnv, RepoContext},
+    executors::{ExecutorError, StandardCodingAgentExecutor},
+    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
+    profile::{ExecutorConfig, ExecutorConfigs},
+};
+
+/// How long log normalization may take to catch up once the agent exited.
+const NORMALIZE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum OneShotError {
+    #[error(transparent)]
+    Executor(#[from] ExecutorError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The agent did not reply within {0} seconds")]
+    TimedOut(u64),
+    #[error("The agent exited without replying")]
+    NoReply,
+}
+
+/// Send `prompt` to the configured agent and return its last assistant
+/// message. The agent is killed if it hasn't finished within `timeout`.
+pub async fn run_one_shot(
+    executor_config: &ExecutorConfig,
+    prompt: &str,
+    timeout: Duration,
+) -> Result<String, OneShotError> {
+    let profile_id = executor_config.profile_id();
+    let mut agent = ExecutorConfigs::get_cached()
+        .get_coding_agent(&profile_id)
+        .ok_or_else(|| ExecutorError::UnknownExecutorType(profile_id.to_string()))?;
+    if executor_config.has_overrides() {
+        agent.apply_overrides(executor_config);
+    }
+    agent.use_approvals(Arc::new(NoopExecutorApprovalService));
+
+    let scratch_dir = std::env::temp_dir().join(format!("vk-one-shot-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&scratch_dir).await?;
+    let result = run_in_dir(&agent, &scratch_dir, prompt, timeout).await;
+    if let Err(e) = tokio::fs::remove_dir_all(&scratch_dir).await {
+        tracing::debug!("Failed to remove {}: {}", scratch_dir.display(), e);
+    }
+    result
+}
+
+async fn run_in_dir(
+    agent: &impl StandardCodingAgentExecutor,
+    dir: &Path,
+    prompt: &str,
+    timeout: Duration,
+) -> Result<String, OneShotError> {
+    let env = ExecutionEnv::new(
+        RepoContext::new(dir.to_path_buf(), Vec::new()),
+        false,
+        String::new(),
+    );
+    let mut spawned = agent.spawn(dir, prompt, &env).await?;
+
+    let store = Arc::new(MsgStore::new());
+    let stdout = spawned.child.inner().stdout.take();
+    let stderr = spawned.child.inner().stderr.take();
+    let forwarder = match (stdout, stderr) {
+        (Some(stdout), Some(stderr)) => {
+            let stdout = ReaderStream::new(stdout)
+                .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
+            let stderr = ReaderStream::new(stderr)
+                .map_ok(|chunk| LogMsg::Stderr(String::from_utf8_lossy(&chunk).into_owned()));
+            Some(store.clone().spawn_forwarder(select(stdout, stderr)))
+        }
+        _ => None,
+    };
+    let normalizers = agent.normalize_logs(store.clone(), dir);
+
+    // Some agents stay alive after replying and signal completion instead
+    let exit_signal = spawned
+        .exit_signal
+        .take()
+        .map(|rx| rx.boxed())
+        .unwrap_or_else(|| std::future::pending().boxed());
+    let finished = tokio::time::timeout(timeout, async {
+        tokio::select! {
+            _ = exit_signal => {}
+            _ = spawned.child.wait() => {}
+        }
+    })
+    .await
+    .is_ok();
+    if let Err(e) = kill_process_group(&mut spawned.child).await {
+        tracing::debug!("Failed to kill one-shot agent: {}", e);
+    }
+
+    if let Some(forwarder) = forwarder {
+        let _ = tokio::time::timeout(NORMALIZE_GRACE_PERIOD, forwarder).await;
+    }
+    store.push_finished();
+    for normalizer in normalizers {
+        let _ = tokio::time::timeout(NORMALIZE_GRACE_PERIOD, normalizer).await;
+    }
+
+    if !finished {
+        return Err(OneShotError::TimedOut(timeout.as_secs()));
+    }
+    last_assistant_message(&store).ok_or(OneShotError::NoReply)
+}
+
+fn last_assistant_message(store: &MsgStore) -> Option<String> {
+    store.get_history().iter().rev().find_map(|msg| match msg {
+        LogMsg::JsonPatch(patch) => extract_normalized_entry_from_patch(patch)
+            .filter(|(_, entry)| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+            .map(|(_, entry)| entry.content.trim().to_string())
+            .filter(|content| !content.is_empty()),
+        _ => None,
+    })
+}
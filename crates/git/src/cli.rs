@@ -171,6 +171,63 @@ impl GitCli {
         base_commit: &Commit,
         opts: StatusDiffOptions,
     ) -> Result<Vec<StatusDiffEntry>, GitCliError> {
+        let (_tmp_dir, envs) = self.stage_worktree_in_temp_index(worktree_path)?;
+        // git diff --cached
+        let mut args: Vec<OsString> = vec![
+            "-c".into(),
+            "core.quotepath=false".into(),
+            "diff".into(),
+            "--cached".into(),
+            "-M".into(),
+            "--name-status".into(),
+            OsString::from(base_commit.to_string()),
+        ];
+        args = Self::apply_pathspec_filter(args, opts.path_filter.as_ref());
+        let out = self.git_with_env(worktree_path, args, &envs)?;
+        Ok(Self::parse_name_status(&out))
+    }
+
+    /// Unified diff of everything `git add -A` would commit, untracked files
+    /// included, without touching the real index.
+    pub fn uncommitted_patch(&self, worktree_path: &Path) -> Result<String, GitCliError> {
+        let (_tmp_dir, envs) = self.stage_worktree_in_temp_index(worktree_path)?;
+        let args = Self::apply_default_excludes(vec![
+            "-c",
+            "core.quotepath=false",
+            "diff",
+            "--cached",
+            "-M",
+            "--no-color",
+            "--no-ext-diff",
+            "HEAD",
+        ]);
+        self.git_with_env(worktree_path, args, &envs)
+    }
+
+    /// Unified diff of the commits on HEAD since it forked from `base`.
+    pub fn branch_patch(&self, worktree_path: &Path, base: &str) -> Result<String, GitCliError> {
+        let range = format!("{base}...HEAD");
+        self.git(
+            worktree_path,
+            [
+                "-c",
+                "core.quotepath=false",
+                "diff",
+                "-M",
+                "--no-color",
+                "--no-ext-diff",
+                range.as_str(),
+            ],
+        )
+    }
+
+    /// Copy HEAD into a temporary index and stage the worktree's changes and
+    /// untracked files in it. Returns the temp dir, which must be kept alive
+    /// while the index is used, and the env pointing git at the index.
+    fn stage_worktree_in_temp_index(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<(tempfile::TempDir, Vec<(OsString, OsString)>), GitCliError> {
         // Create a temp index file
         let tmp_dir = tempfile::TempDir::new()
             .map_err(|e| GitCliError::CommandFailed(format!("temp dir create failed: {e}")))?;
@@ -212,19 +269,7 @@ impl GitCli {
             ];
             self.git_with_stdin(worktree_path, args, Some(&envs), &input)?;
         }
-        // git diff --cached
-        let mut args: Vec<OsString> = vec![
-            "-c".into(),
-            "core.quotepath=false".into(),
-            "diff".into(),
-            "--cached".into(),
-            "-M".into(),
-            "--name-status".into(),
-            OsString::from(base_commit.to_string()),
-        ];
-        args = Self::apply_pathspec_filter(args, opts.path_filter.as_ref());
-        let out = self.git_with_env(worktree_path, args, &envs)?;
-        Ok(Self::parse_name_status(&out))
+        Ok((tmp_dir, envs))
     }
 
     /// Return `git status --porcelain` parsed into a structured summary
@@ -296,6 +341,28 @@ impl GitCli {
         Ok(())
     }
 
+    /// Apply `patch` to the index only; the working tree is left as it is.
+    pub fn apply_to_index(&self, worktree_path: &Path, patch: &str) -> Result<(), GitCliError> {
+        self.git_with_stdin(
+            worktree_path,
+            ["apply", "--cached", "-"],
+            None,
+            patch.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Whether the index already has every change in `patch`.
+    pub fn index_contains_patch(&self, worktree_path: &Path, patch: &str) -> bool {
+        self.git_with_stdin(
+            worktree_path,
+            ["apply", "--cached", "--reverse", "--check", "-"],
+            None,
+            patch.as_bytes(),
+        )
+        .is_ok()
+    }
+
     pub fn list_worktrees(&self, repo_path: &Path) -> Result<Vec<WorktreeEntry>, GitCliError> {
         let out = self.git(repo_path, ["worktree", "list", "--porcelain"])?;
         let mut entries = Vec::new();
@@ -729,6 +796,21 @@ impl GitCli {
         }
     }
 
+    /// `git --version` output, e.g. `git version 2.45.1`.
+    pub fn version(&self) -> Result<String, GitCliError> {
+        use utils::command_ext::NoWindowExt;
+        let git = resolve_executable_path_blocking("git").ok_or(GitCliError::NotAvailable)?;
+        let out = Command::new(&git)
+            .arg("--version")
+            .no_window()
+            .output()
+            .map_err(|_| GitCliError::NotAvailable)?;
+        if !out.status.success() {
+            return Err(GitCliError::NotAvailable);
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+
     /// Ensure `git` is available on PATH
     fn ensure_available(&self) -> Result<(), GitCliError> {
         use utils::command_ext::NoWindowExt;
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    ffi::OsStr,
     path::{Path, PathBuf},
 };
 
@@ -12,6 +13,7 @@ use utils::diff::{Diff, DiffChangeKind};
 
 mod cli;
 mod validation;
+pub mod vcs;
 
 use cli::{ChangeType, StatusDiffEntry, StatusDiffOptions};
 pub use cli::{GitCli, GitCliError, StatusEntry, WorktreeStatus};
@@ -52,6 +54,8 @@ pub enum GitServiceError {
     WorktreeDirty(String, String),
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("VCS command failed: {0}")]
+    VcsCommand(String),
 }
 
 /// Service for managing Git operations in task execution workflows
@@ -87,6 +91,29 @@ pub struct GitRemote {
     pub url: String,
 }
 
+/// A commit folded into the single commit produced by squashing a branch.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SquashedCommit {
+    pub sha: String,
+    pub summary: String,
+    pub author: String,
+}
+
+/// What squashing a branch onto its fork point produces, or produced.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SquashPlan {
+    /// Fork point from the target branch; parent of the squashed commit.
+    pub base_commit: String,
+    /// Branch head the plan was made from.
+    pub head_commit: String,
+    /// Commits being squashed, oldest first.
+    pub commits: Vec<SquashedCommit>,
+    /// Message of the squashed commit, co-author trailers included.
+    pub message: String,
+    /// Set once the squash has been applied.
+    pub squashed_commit: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct HeadInfo {
     pub branch: String,
@@ -671,6 +698,196 @@ impl GitService {
             }
         }
     }
+
+    /// Squash-merge a child workspace's branch into its parent's branch,
+    /// which must be checked out in `parent_worktree_path`. Unlike
+    /// [`Self::merge_changes`] the parent may have moved on since the child
+    /// branched off, e.g. because sibling children were merged first: the
+    /// merge is three-way, and fails with `MergeConflicts` without changing
+    /// anything if the two overlap. Returns the parent's new head, which is
+    /// unchanged if the child adds nothing.
+    pub fn merge_child_into_parent(
+        &self,
+        parent_worktree_path: &Path,
+        parent_branch: &str,
+        child_branch: &str,
+        commit_message: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(parent_worktree_path)?;
+        let head = repo.head()?;
+        if !head.is_branch() || head.shorthand() != Some(parent_branch) {
+            return Err(GitServiceError::InvalidRepository(format!(
+                "Branch '{parent_branch}' is not checked out in {}",
+                parent_worktree_path.display()
+            )));
+        }
+        self.check_worktree_clean(&repo)?;
+
+        let parent_commit = head.peel_to_commit()?;
+        let child_commit = Self::find_branch(&repo, child_branch)?
+            .get()
+            .peel_to_commit()?;
+
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true);
+        let mut index = repo.merge_commits(&parent_commit, &child_commit, Some(&merge_opts))?;
+        if index.has_conflicts() {
+            let conflicted_files: Vec<String> = index
+                .conflicts()?
+                .filter_map(|conflict| {
+                    let conflict = conflict.ok()?;
+                    let entry = conflict.our.or(conflict.their).or(conflict.ancestor)?;
+                    Some(String::from_utf8_lossy(&entry.path).into_owned())
+                })
+                .collect();
+            return Err(GitServiceError::MergeConflicts {
+                message: format!(
+                    "Merging '{child_branch}' into '{parent_branch}' conflicts in: {}",
+                    conflicted_files.join(", ")
+                ),
+                conflicted_files,
+            });
+        }
+
+        let tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+        if tree.id() == parent_commit.tree_id() {
+            return Ok(parent_commit.id().to_string());
+        }
+        let signature = self.signature_with_fallback(&repo)?;
+        let squash_commit_id = repo.commit(
+            None,
+            &signature,
+            &signature,
+            commit_message,
+            &tree,
+            &[&parent_commit],
+        )?;
+
+        // Fast-forward through the CLI so the checked-out files follow the
+        // branch, refusing rather than overwriting untracked files.
+        let squash_commit = squash_commit_id.to_string();
+        GitCli::new().git(
+            parent_worktree_path,
+            ["merge", "--ff-only", squash_commit.as_str()],
+        )?;
+        Ok(squash_commit)
+    }
+    /// Plan squashing the commits `branch` has on top of its fork point
+    /// from `target_branch` into one. Without `message` the squashed commit
+    /// is titled after the oldest commit and lists every commit's summary.
+    /// `Co-authored-by` trailers from the squashed commits are always kept.
+    pub fn plan_squash(
+        &self,
+        worktree_path: &Path,
+        target_branch: &str,
+        branch: &str,
+        message: Option<&str>,
+    ) -> Result<SquashPlan, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let head = Self::find_branch(&repo, branch)?.get().peel_to_commit()?;
+        let base = self.get_fork_point(worktree_path, target_branch, branch)?;
+        let base_oid = git2::Oid::from_str(&base)?;
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(head.id())?;
+        revwalk.hide(base_oid)?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+        let mut commits = Vec::new();
+        let mut messages = Vec::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let author = commit.author();
+            commits.push(SquashedCommit {
+                sha: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: format!(
+                    "{} <{}>",
+                    author.name().unwrap_or_default(),
+                    author.email().unwrap_or_default()
+                ),
+            });
+            messages.push(commit.message().unwrap_or_default().to_string());
+        }
+
+        let message = match message.map(str::trim).filter(|m| !m.is_empty()) {
+            Some(message) => message.to_string(),
+            None => default_squash_message(&commits),
+        };
+        Ok(SquashPlan {
+            base_commit: base,
+            head_commit: head.id().to_string(),
+            commits,
+            message: with_co_author_trailers(&message, &messages),
+            squashed_commit: None,
+        })
+    }
+
+    /// Replace the commits in `plan` with a single commit holding the same
+    /// tree. Fails without changing anything if the branch moved since the
+    /// plan was made or the worktree has uncommitted changes; the checked
+    /// out files are already identical, so nothing is touched on disk.
+    pub fn apply_squash(
+        &self,
+        worktree_path: &Path,
+        branch: &str,
+        plan: &SquashPlan,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let head = repo.head()?;
+        if !head.is_branch() || head.shorthand() != Some(branch) {
+            return Err(GitServiceError::InvalidRepository(format!(
+                "Branch '{branch}' is not checked out in {}",
+                worktree_path.display()
+            )));
+        }
+        if GitCli::new()
+            .is_rebase_in_progress(worktree_path)
+            .unwrap_or(false)
+        {
+            return Err(GitServiceError::RebaseInProgress);
+        }
+        self.check_worktree_clean(&repo)?;
+
+        let head_commit = head.peel_to_commit()?;
+        let expected_head = git2::Oid::from_str(&plan.head_commit)?;
+        if head_commit.id() != expected_head {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Branch '{branch}' moved since the squash was planned"
+            )));
+        }
+        let base_commit = repo.find_commit(git2::Oid::from_str(&plan.base_commit)?)?;
+        // Keep the original author of the work; the committer is whoever squashes
+        let oldest = plan
+            .commits
+            .first()
+            .map(|c| git2::Oid::from_str(&c.sha))
+            .transpose()?
+            .map(|oid| repo.find_commit(oid))
+            .transpose()?;
+        let committer = self.signature_with_fallback(&repo)?;
+        let author = oldest
+            .as_ref()
+            .map(|c| c.author().to_owned())
+            .unwrap_or_else(|| committer.to_owned());
+        let squashed = repo.commit(
+            None,
+            &author,
+            &committer,
+            &plan.message,
+            &head_commit.tree()?,
+            &[&base_commit],
+        )?;
+        repo.reference_matching(
+            &format!("refs/heads/{branch}"),
+            squashed,
+            true,
+            expected_head,
+            "squash branch commits",
+        )?;
+        Ok(squashed.to_string())
+    }
+
     fn get_branch_status_inner(
         &self,
         repo: &Repository,
@@ -746,6 +963,23 @@ impl GitService {
         self.get_branch_status_inner(&repo, &branch_ref, &base_branch_ref)
     }
 
+    /// Fetch a remote-tracking branch (e.g. `origin/main`) from its remote
+    /// so ahead/behind counts against it are current. Local branches are left
+    /// alone. Returns whether a fetch happened.
+    pub fn fetch_remote_tracking_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<bool, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_ref = Self::find_branch(&repo, branch_name)?.into_reference();
+        if !branch_ref.is_remote() {
+            return Ok(false);
+        }
+        self.fetch_branch_from_remote(&repo, &branch_ref)?;
+        Ok(true)
+    }
+
     pub fn is_worktree_clean(&self, worktree_path: &Path) -> Result<bool, GitServiceError> {
         let repo = self.open_repo(worktree_path)?;
         match self.check_worktree_clean(&repo) {
@@ -755,6 +989,46 @@ impl GitService {
         }
     }
 
+    /// Fail with `WorktreeDirty` if tracked files have uncommitted changes.
+    pub fn ensure_worktree_clean(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        self.check_worktree_clean(&repo)
+    }
+
+    /// Stash uncommitted changes to tracked files under `message`. Returns
+    /// whether anything was stashed.
+    pub fn stash_changes(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+    ) -> Result<bool, GitServiceError> {
+        if self.is_worktree_clean(worktree_path)? {
+            return Ok(false);
+        }
+        let git = GitCli::new();
+        git.git(worktree_path, ["stash", "push", "--message", message])?;
+        Ok(true)
+    }
+
+    /// Pop the most recent stash created by [`Self::stash_changes`] with
+    /// `message`. Returns whether one was found. On conflicts the stash is
+    /// kept so nothing is lost.
+    pub fn pop_stash(&self, worktree_path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        let git = GitCli::new();
+        let stashes = git.git(worktree_path, ["stash", "list", "--format=%gd %gs"])?;
+        // Subjects look like "On <branch>: <message>".
+        let Some(stash_ref) = stashes.lines().find_map(|line| {
+            let (stash_ref, subject) = line.split_once(' ')?;
+            subject
+                .ends_with(&format!(": {message}"))
+                .then(|| stash_ref.to_string())
+        }) else {
+            return Ok(false);
+        };
+        git.git(worktree_path, ["stash", "pop", stash_ref.as_str()])?;
+        Ok(true)
+    }
+
     /// Check if the worktree is clean (no uncommitted changes to tracked files)
     fn check_worktree_clean(&self, repo: &Repository) -> Result<(), GitServiceError> {
         let mut status_options = git2::StatusOptions::new();
@@ -808,6 +1082,13 @@ impl GitService {
         head.target().map(Commit::new)
     }
 
+    /// Resolve a revision (`HEAD~2`, a branch, an abbreviated SHA) to a full commit SHA.
+    pub fn resolve_commit(&self, repo_path: &Path, rev: &str) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+
     /// Returns true if HEAD's first parent is `expected_parent_oid` (i.e., HEAD is a simple commit on top of it).
     pub fn is_head_child_of(&self, repo_path: &Path, expected_parent_oid: git2::Oid) -> bool {
         let check = || -> Option<bool> {
@@ -878,6 +1159,37 @@ impl GitService {
             .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))
     }
 
+    /// Unified diff of all uncommitted changes in the worktree against HEAD,
+    /// untracked files included. Empty when there is nothing to commit.
+    pub fn get_uncommitted_patch(&self, worktree_path: &Path) -> Result<String, GitServiceError> {
+        let cli = GitCli::new();
+        cli.uncommitted_patch(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git diff failed: {e}")))
+    }
+
+    /// Stage the changes in `patch` without touching the worktree. Changes
+    /// the index already has, e.g. because they were committed, are left
+    /// as they are.
+    pub fn stage_patch(&self, worktree_path: &Path, patch: &str) -> Result<(), GitServiceError> {
+        let cli = GitCli::new();
+        if cli.index_contains_patch(worktree_path, patch) {
+            return Ok(());
+        }
+        cli.apply_to_index(worktree_path, patch)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git apply failed: {e}")))
+    }
+
+    /// Unified diff of what the worktree's branch adds on top of `base`.
+    pub fn get_branch_patch(
+        &self,
+        worktree_path: &Path,
+        base: &str,
+    ) -> Result<String, GitServiceError> {
+        let cli = GitCli::new();
+        cli.branch_patch(worktree_path, base)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git diff failed: {e}")))
+    }
+
     /// Return (uncommitted_tracked_changes, untracked_files) counts in worktree
     pub fn get_worktree_change_counts(
         &self,
@@ -970,6 +1282,54 @@ impl GitService {
         Ok(())
     }
 
+    /// Register a worktree for an existing branch without checking out any
+    /// files, so they can be filled in from another checkout.
+    pub fn add_worktree_without_checkout(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> Result<(), GitServiceError> {
+        let cli = GitCli::new();
+        cli.git(
+            repo_path,
+            [
+                OsStr::new("worktree"),
+                OsStr::new("add"),
+                OsStr::new("--no-checkout"),
+                worktree_path.as_os_str(),
+                OsStr::new(branch),
+            ],
+        )
+        .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Bring a worktree whose files were copied from another checkout in line
+    /// with its HEAD. Tracked files that differ are rewritten and untracked
+    /// files are removed; ignored files such as `node_modules` are kept.
+    pub fn sync_copied_worktree_to_head(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        let cli = GitCli::new();
+        cli.git(worktree_path, ["reset", "-q"])
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git reset failed: {e}")))?;
+        // Records stat info for files whose content already matches, so the
+        // hard reset below leaves them (and their shared extents) alone. Exits
+        // non-zero while some files differ, which is expected here.
+        let _ = cli.git(worktree_path, ["update-index", "-q", "--refresh"]);
+        cli.git(worktree_path, ["reset", "--hard", "-q"])
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git reset --hard failed: {e}"))
+            })?;
+        cli.git(worktree_path, ["clean", "-fdq"]).map_err(|e| {
+            GitServiceError::InvalidRepository(format!("git clean -fd failed: {e}"))
+        })?;
+        let _ = cli.git(worktree_path, ["sparse-checkout", "reapply"]);
+        Ok(())
+    }
+
     /// Remove a worktree
     pub fn remove_worktree(
         &self,
@@ -1693,6 +2053,35 @@ impl GitService {
 }
 
 /// Compute addition/deletion counts between two text snapshots using libgit2.
+fn default_squash_message(commits: &[SquashedCommit]) -> String {
+    let Some(first) = commits.first() else {
+        return String::new();
+    };
+    if commits.len() == 1 {
+        return first.summary.clone();
+    }
+    let list: Vec<String> = commits.iter().map(|c| format!("- {}", c.summary)).collect();
+    format!("{}\n\n{}", first.summary, list.join("\n"))
+}
+
+/// Append the `Co-authored-by` trailers found in `messages` that `message`
+/// doesn't already carry.
+fn with_co_author_trailers(message: &str, messages: &[String]) -> String {
+    let mut trailers: Vec<&str> = Vec::new();
+    for line in messages.iter().flat_map(|m| m.lines()).map(str::trim) {
+        let is_trailer = line
+            .get(..15)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("co-authored-by:"));
+        if is_trailer && !trailers.contains(&line) && !message.lines().any(|l| l.trim() == line) {
+            trailers.push(line);
+        }
+    }
+    if trailers.is_empty() {
+        return message.to_string();
+    }
+    format!("{}\n\n{}", message.trim_end(), trailers.join("\n"))
+}
+
 pub fn compute_line_change_counts(old: &str, new: &str) -> (usize, usize) {
     fn ensure_newline(s: &str) -> std::borrow::Cow<'_, str> {
         if s.ends_with('\n') {
@@ -0,0 +1,645 @@
+//! Version control abstraction over the operations workspaces need.
+//!
+//! Git (via [`GitService`]) is the default and only fully supported backend.
+//! Jujutsu and Mercurial backends drive the `jj` and `hg` CLIs and are
+//! experimental: they are only picked when `VK_EXPERIMENTAL_VCS` is set, so
+//! colocated jj repositories keep using git unless the user opts in. Features
+//! outside this trait (rebase, merge, PRs) remain git-only.
+use std::{
+    ffi::OsStr,
+    fs,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+use utils::{
+    command_ext::NoWindowExt,
+    diff::{Diff, DiffChangeKind},
+    shell::resolve_executable_path_blocking,
+};
+
+use crate::{GitService, GitServiceError, MAX_INLINE_DIFF_BYTES, compute_line_change_counts};
+
+/// Name of the file inside a jj workspace's `.jj` directory that records the
+/// bookmark the workspace commits to.
+const JJ_BOOKMARK_FILE: &str = "vk-bookmark";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcsKind {
+    Git,
+    Jujutsu,
+    Mercurial,
+}
+
+impl VcsKind {
+    /// Detect the VCS managing `path`, which may be a repository or one of
+    /// its worktrees. Falls back to git when experimental backends are off or
+    /// nothing else matches.
+    pub fn detect(path: &Path) -> Self {
+        if !experimental_vcs_enabled() {
+            return VcsKind::Git;
+        }
+        for dir in path.ancestors() {
+            if dir.join(".jj").is_dir() {
+                return VcsKind::Jujutsu;
+            }
+            if dir.join(".hg").is_dir() {
+                return VcsKind::Mercurial;
+            }
+            if dir.join(".git").exists() {
+                return VcsKind::Git;
+            }
+        }
+        VcsKind::Git
+    }
+}
+
+fn experimental_vcs_enabled() -> bool {
+    std::env::var("VK_EXPERIMENTAL_VCS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Returns the backend for the repository or worktree at `path`.
+pub fn vcs_for(path: &Path) -> Box<dyn Vcs> {
+    match VcsKind::detect(path) {
+        VcsKind::Git => Box::new(GitService::new()),
+        VcsKind::Jujutsu => Box::new(JjVcs),
+        VcsKind::Mercurial => Box::new(HgVcs),
+    }
+}
+
+/// The VCS operations workspace flows rely on. "Branch" means a git branch,
+/// a jj bookmark or an hg bookmark; "worktree" means a git worktree, a jj
+/// workspace or an hg share.
+pub trait Vcs: Send + Sync {
+    fn kind(&self) -> VcsKind;
+
+    /// Create `new_branch` pointing at the tip of `base_branch`.
+    fn create_branch(
+        &self,
+        repo_path: &Path,
+        new_branch: &str,
+        base_branch: &str,
+    ) -> Result<(), GitServiceError>;
+
+    /// Check out `branch` into a new working copy at `worktree_path`.
+    fn add_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> Result<(), GitServiceError>;
+
+    /// Unregister the working copy at `worktree_path`. Deleting the directory
+    /// is left to the caller.
+    fn remove_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError>;
+
+    /// Commit all changes in the working copy. Returns `false` when there was
+    /// nothing to commit.
+    fn commit(&self, worktree_path: &Path, message: &str) -> Result<bool, GitServiceError>;
+
+    /// The commit `branch` forked from `target_branch` at, which its
+    /// changes are diffed against.
+    fn base_commit(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError>;
+
+    /// Changes in the working copy relative to `base`, a commit id.
+    fn diff(&self, worktree_path: &Path, base: &str) -> Result<Vec<Diff>, GitServiceError>;
+
+    fn push(&self, worktree_path: &Path, branch: &str, force: bool) -> Result<(), GitServiceError>;
+}
+
+impl Vcs for GitService {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Git
+    }
+
+    fn create_branch(
+        &self,
+        repo_path: &Path,
+        new_branch: &str,
+        base_branch: &str,
+    ) -> Result<(), GitServiceError> {
+        GitService::create_branch(self, repo_path, new_branch, base_branch)
+    }
+
+    fn add_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> Result<(), GitServiceError> {
+        GitService::add_worktree(self, repo_path, worktree_path, branch, false)
+    }
+
+    fn remove_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitService::remove_worktree(self, repo_path, worktree_path, true)
+    }
+
+    fn commit(&self, worktree_path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        GitService::commit(self, worktree_path, message)
+    }
+
+    fn base_commit(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        Ok(self
+            .get_base_commit(repo_path, branch, target_branch)?
+            .to_string())
+    }
+
+    fn diff(&self, worktree_path: &Path, base: &str) -> Result<Vec<Diff>, GitServiceError> {
+        let oid = git2::Oid::from_str(&self.resolve_commit(worktree_path, base)?)?;
+        self.get_diffs(worktree_path, &crate::Commit::new(oid), None)
+    }
+
+    fn push(&self, worktree_path: &Path, branch: &str, force: bool) -> Result<(), GitServiceError> {
+        self.push_to_remote(worktree_path, branch, force)
+    }
+}
+
+/// Experimental Jujutsu backend. Worktrees are jj workspaces; each records the
+/// bookmark it works on so commits can advance it.
+pub struct JjVcs;
+
+impl JjVcs {
+    fn bookmark(worktree_path: &Path) -> Result<String, GitServiceError> {
+        let path = worktree_path.join(".jj").join(JJ_BOOKMARK_FILE);
+        let bookmark = fs::read_to_string(&path)?;
+        Ok(bookmark.trim().to_string())
+    }
+}
+
+impl Vcs for JjVcs {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Jujutsu
+    }
+
+    fn create_branch(
+        &self,
+        repo_path: &Path,
+        new_branch: &str,
+        base_branch: &str,
+    ) -> Result<(), GitServiceError> {
+        run(
+            "jj",
+            repo_path,
+            ["bookmark", "create", new_branch, "-r", base_branch],
+        )?;
+        Ok(())
+    }
+
+    fn add_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> Result<(), GitServiceError> {
+        let name = workspace_name(worktree_path)?;
+        run(
+            "jj",
+            repo_path,
+            [
+                OsStr::new("workspace"),
+                OsStr::new("add"),
+                OsStr::new("--name"),
+                OsStr::new(&name),
+                OsStr::new("-r"),
+                OsStr::new(branch),
+                worktree_path.as_os_str(),
+            ],
+        )?;
+        fs::write(worktree_path.join(".jj").join(JJ_BOOKMARK_FILE), branch)?;
+        Ok(())
+    }
+
+    fn remove_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        let name = workspace_name(worktree_path)?;
+        run("jj", repo_path, ["workspace", "forget", name.as_str()])?;
+        Ok(())
+    }
+
+    fn commit(&self, worktree_path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        // `jj diff` snapshots the working copy first, so the check is current.
+        let changes = run("jj", worktree_path, ["diff", "--summary", "-r", "@"])?;
+        if changes.trim().is_empty() {
+            return Ok(false);
+        }
+        run("jj", worktree_path, ["commit", "-m", message])?;
+        let bookmark = Self::bookmark(worktree_path)?;
+        run(
+            "jj",
+            worktree_path,
+            ["bookmark", "set", bookmark.as_str(), "-r", "@-"],
+        )?;
+        Ok(true)
+    }
+
+    fn base_commit(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        let revset = format!(
+            "fork_point({} | {})",
+            revset_symbol(branch),
+            revset_symbol(target_branch)
+        );
+        let commit = run(
+            "jj",
+            repo_path,
+            [
+                "log",
+                "--no-graph",
+                "-r",
+                revset.as_str(),
+                "-T",
+                "commit_id",
+            ],
+        )?;
+        non_empty_commit(commit, branch, target_branch)
+    }
+
+    fn diff(&self, worktree_path: &Path, base: &str) -> Result<Vec<Diff>, GitServiceError> {
+        let summary = run("jj", worktree_path, ["diff", "--summary", "--from", base])?;
+        summary
+            .lines()
+            .filter_map(parse_jj_summary_line)
+            .map(|(change, old_path, new_path)| {
+                let old_content = old_path
+                    .as_deref()
+                    .map(|path| {
+                        run_bytes(
+                            "jj",
+                            worktree_path,
+                            ["file", "show", "-r", base, "--", path],
+                        )
+                    })
+                    .transpose()?;
+                let new_content = new_path
+                    .as_deref()
+                    .and_then(|path| fs::read(worktree_path.join(path)).ok());
+                Ok(content_diff(
+                    change,
+                    old_path,
+                    new_path,
+                    old_content,
+                    new_content,
+                ))
+            })
+            .collect()
+    }
+
+    fn push(
+        &self,
+        worktree_path: &Path,
+        branch: &str,
+        _force: bool,
+    ) -> Result<(), GitServiceError> {
+        // jj always pushes with a lease on the last fetched remote position,
+        // so there is no separate force mode.
+        run(
+            "jj",
+            worktree_path,
+            ["git", "push", "--bookmark", branch, "--allow-new"],
+        )?;
+        Ok(())
+    }
+}
+
+/// Experimental Mercurial backend. Worktrees are shares with bookmarks shared
+/// back to the source repository, and branches are bookmarks.
+pub struct HgVcs;
+
+impl Vcs for HgVcs {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Mercurial
+    }
+
+    fn create_branch(
+        &self,
+        repo_path: &Path,
+        new_branch: &str,
+        base_branch: &str,
+    ) -> Result<(), GitServiceError> {
+        run("hg", repo_path, ["bookmark", "-r", base_branch, new_branch])?;
+        Ok(())
+    }
+
+    fn add_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> Result<(), GitServiceError> {
+        run(
+            "hg",
+            repo_path,
+            [
+                OsStr::new("--config"),
+                OsStr::new("extensions.share="),
+                OsStr::new("share"),
+                OsStr::new("-B"),
+                OsStr::new("-U"),
+                repo_path.as_os_str(),
+                worktree_path.as_os_str(),
+            ],
+        )?;
+        // Updating to a bookmark activates it, so commits advance it.
+        run("hg", worktree_path, ["update", branch])?;
+        Ok(())
+    }
+
+    fn remove_worktree(
+        &self,
+        _repo_path: &Path,
+        _worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        // Shares aren't registered with their source; removing the directory
+        // is all there is to it.
+        Ok(())
+    }
+
+    fn commit(&self, worktree_path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        let status = run("hg", worktree_path, ["status"])?;
+        if status.trim().is_empty() {
+            return Ok(false);
+        }
+        let username = run("hg", worktree_path, ["config", "ui.username"]).unwrap_or_default();
+        let mut args = vec!["commit", "-A", "-m", message];
+        if username.trim().is_empty() {
+            args.extend(["-u", "Vibe Kanban <noreply@vibekanban.com>"]);
+        }
+        run("hg", worktree_path, args)?;
+        Ok(true)
+    }
+
+    fn base_commit(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        let revset = format!(
+            "ancestor({}, {})",
+            revset_symbol(branch),
+            revset_symbol(target_branch)
+        );
+        let commit = run(
+            "hg",
+            repo_path,
+            ["log", "-r", revset.as_str(), "-T", "{node}"],
+        )?;
+        non_empty_commit(commit, branch, target_branch)
+    }
+
+    fn diff(&self, worktree_path: &Path, base: &str) -> Result<Vec<Diff>, GitServiceError> {
+        let status = run("hg", worktree_path, ["status", "--rev", base])?;
+        status
+            .lines()
+            .filter_map(|line| {
+                let (code, path) = line.split_once(' ')?;
+                let change = match code {
+                    "A" | "?" => DiffChangeKind::Added,
+                    "R" | "!" => DiffChangeKind::Deleted,
+                    "M" => DiffChangeKind::Modified,
+                    _ => return None,
+                };
+                Some((change, path.to_string()))
+            })
+            .map(|(change, path)| {
+                let (old_path, new_path) = match change {
+                    DiffChangeKind::Added => (None, Some(path)),
+                    DiffChangeKind::Deleted => (Some(path), None),
+                    _ => (Some(path.clone()), Some(path)),
+                };
+                let old_content = old_path
+                    .as_deref()
+                    .map(|path| run_bytes("hg", worktree_path, ["cat", "-r", base, "--", path]))
+                    .transpose()?;
+                let new_content = new_path
+                    .as_deref()
+                    .and_then(|path| fs::read(worktree_path.join(path)).ok());
+                Ok(content_diff(
+                    change,
+                    old_path,
+                    new_path,
+                    old_content,
+                    new_content,
+                ))
+            })
+            .collect()
+    }
+
+    fn push(&self, worktree_path: &Path, branch: &str, force: bool) -> Result<(), GitServiceError> {
+        let mut args = vec!["push", "-B", branch];
+        if force {
+            args.push("--force");
+        }
+        let out = output("hg", worktree_path, args)?;
+        // hg exits with 1 when there was nothing to push.
+        match out.status.code() {
+            Some(0 | 1) => Ok(()),
+            _ => Err(GitServiceError::VcsCommand(format!(
+                "hg push failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ))),
+        }
+    }
+}
+
+/// Workspace names must be unique per repository; the worktree directory's
+/// parent is the workspace directory, which is unique per workspace.
+fn workspace_name(worktree_path: &Path) -> Result<String, GitServiceError> {
+    worktree_path
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| {
+            GitServiceError::InvalidRepository(format!(
+                "Cannot derive a workspace name from {}",
+                worktree_path.display()
+            ))
+        })
+}
+
+/// Quote a bookmark name for a jj or hg revset, so names like `feat/x-1`
+/// aren't read as operators.
+fn revset_symbol(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn non_empty_commit(
+    output: String,
+    branch: &str,
+    target_branch: &str,
+) -> Result<String, GitServiceError> {
+    let commit = output.trim();
+    if commit.is_empty() {
+        return Err(GitServiceError::VcsCommand(format!(
+            "{branch} and {target_branch} have no common ancestor"
+        )));
+    }
+    Ok(commit.to_string())
+}
+
+/// Parse a line of `jj diff --summary`, e.g. `M src/lib.rs` or
+/// `R src/{old.rs => new.rs}`.
+fn parse_jj_summary_line(line: &str) -> Option<(DiffChangeKind, Option<String>, Option<String>)> {
+    let (code, path) = line.split_once(' ')?;
+    match code {
+        "A" => Some((DiffChangeKind::Added, None, Some(path.to_string()))),
+        "D" => Some((DiffChangeKind::Deleted, Some(path.to_string()), None)),
+        "M" => Some((
+            DiffChangeKind::Modified,
+            Some(path.to_string()),
+            Some(path.to_string()),
+        )),
+        "R" | "C" => {
+            let (prefix, rest) = path.split_once('{')?;
+            let (renamed, suffix) = rest.split_once('}')?;
+            let (old, new) = renamed.split_once(" => ")?;
+            let kind = if code == "R" {
+                DiffChangeKind::Renamed
+            } else {
+                DiffChangeKind::Copied
+            };
+            // `a/{b => }/c` collapses the doubled separator.
+            let join = |part: &str| format!("{prefix}{part}{suffix}").replace("//", "/");
+            Some((kind, Some(join(old)), Some(join(new))))
+        }
+        _ => None,
+    }
+}
+
+/// Build a [`Diff`] from raw file contents, omitting binary and oversized
+/// files the same way git diffs do.
+fn content_diff(
+    change: DiffChangeKind,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    old_content: Option<Vec<u8>>,
+    new_content: Option<Vec<u8>>,
+) -> Diff {
+    let content_omitted = [&old_content, &new_content]
+        .into_iter()
+        .flatten()
+        .any(|bytes| bytes.len() > MAX_INLINE_DIFF_BYTES);
+    let to_text = |bytes: Option<Vec<u8>>| {
+        bytes
+            .filter(|_| !content_omitted)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    };
+    let old_content = to_text(old_content);
+    let new_content = to_text(new_content);
+
+    let (additions, deletions) = match (&old_content, &new_content) {
+        (Some(old), Some(new)) => {
+            let (adds, dels) = compute_line_change_counts(old, new);
+            (Some(adds), Some(dels))
+        }
+        (Some(old), None) => (Some(0), Some(old.lines().count())),
+        (None, Some(new)) => (Some(new.lines().count()), Some(0)),
+        (None, None) => (None, None),
+    };
+
+    Diff {
+        change,
+        old_path,
+        new_path,
+        old_content,
+        new_content,
+        content_omitted,
+        additions,
+        deletions,
+        repo_id: None,
+    }
+}
+
+fn output<I, S>(program: &str, dir: &Path, args: I) -> Result<Output, GitServiceError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let executable = resolve_executable_path_blocking(program)
+        .ok_or_else(|| GitServiceError::VcsCommand(format!("{program} executable not found")))?;
+    Ok(Command::new(executable)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .no_window()
+        .output()?)
+}
+
+fn run_bytes<I, S>(program: &str, dir: &Path, args: I) -> Result<Vec<u8>, GitServiceError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let out = output(program, dir, args)?;
+    if !out.status.success() {
+        return Err(GitServiceError::VcsCommand(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    Ok(out.stdout)
+}
+
+fn run<I, S>(program: &str, dir: &Path, args: I) -> Result<String, GitServiceError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let stdout = run_bytes(program, dir, args)?;
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jj_summary_lines() {
+        assert!(matches!(
+            parse_jj_summary_line("M src/lib.rs"),
+            Some((DiffChangeKind::Modified, Some(old), Some(new))) if old == "src/lib.rs" && new == old
+        ));
+        assert!(matches!(
+            parse_jj_summary_line("R src/{old.rs => new.rs}"),
+            Some((DiffChangeKind::Renamed, Some(old), Some(new)))
+                if old == "src/old.rs" && new == "src/new.rs"
+        ));
+        assert!(matches!(
+            parse_jj_summary_line("R {a => b/c}/d.rs"),
+            Some((DiffChangeKind::Renamed, Some(old), Some(new)))
+                if old == "a/d.rs" && new == "b/c/d.rs"
+        ));
+        assert!(parse_jj_summary_line("Working copy changes:").is_none());
+    }
+
+    #[test]
+    fn quotes_revset_symbols() {
+        assert_eq!(revset_symbol("vk/ab12-fix"), r#""vk/ab12-fix""#);
+        assert_eq!(revset_symbol(r#"odd"na\me"#), r#""odd\"na\\me""#);
+    }
+}
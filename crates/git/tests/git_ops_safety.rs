@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use git::{GitCli, GitCliError, GitService};
+use git::{GitCli, GitCliError, GitService, GitServiceError};
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
 use tempfile::TempDir;
 // Avoid direct git CLI usage in tests; exercise GitService instead.
@@ -404,6 +404,66 @@ fn push_and_fetch_roundtrip_updates_tracking_branch() {
     );
 }
 
+#[test]
+fn fetching_remote_tracking_target_updates_behind_count() {
+    let temp_dir = TempDir::new().unwrap();
+    let remote_path = temp_dir.path().join("remote.git");
+    Repository::init_bare(&remote_path).expect("init bare remote");
+    let remote_url = remote_path.to_str().expect("remote path str");
+
+    let seed_path = temp_dir.path().join("seed");
+    let service = GitService::new();
+    service
+        .initialize_repo_with_main_branch(&seed_path)
+        .expect("init seed repo");
+    let seed_repo = Repository::open(&seed_path).expect("open seed repo");
+    configure_user(&seed_repo);
+    seed_repo.remote("origin", remote_url).expect("add remote");
+    push_ref(&seed_repo, "refs/heads/main", "refs/heads/main");
+    Repository::open_bare(&remote_path)
+        .expect("open bare remote")
+        .set_head("refs/heads/main")
+        .expect("set remote HEAD");
+
+    let local_path = temp_dir.path().join("local");
+    let local_repo = Repository::clone(remote_url, &local_path).expect("clone local");
+    configure_user(&local_repo);
+    checkout_branch(&local_repo, "main");
+    create_branch_from_head(&local_repo, "feature");
+    checkout_branch(&local_repo, "feature");
+    write_file(&local_path, "feature.txt", "feature\n");
+    commit_all(&local_repo, "feature commit");
+
+    // Someone else lands two commits on main
+    write_file(&seed_path, "a.txt", "a\n");
+    commit_all(&seed_repo, "main commit a");
+    write_file(&seed_path, "b.txt", "b\n");
+    commit_all(&seed_repo, "main commit b");
+    push_ref(&seed_repo, "refs/heads/main", "refs/heads/main");
+
+    let status = service
+        .get_branch_status(&local_path, "feature", "origin/main")
+        .expect("status before fetch");
+    assert_eq!(status, (1, 0));
+
+    assert!(
+        service
+            .fetch_remote_tracking_branch(&local_path, "origin/main")
+            .expect("fetch origin/main")
+    );
+    let status = service
+        .get_branch_status(&local_path, "feature", "origin/main")
+        .expect("status after fetch");
+    assert_eq!(status, (1, 2));
+
+    // Local branches aren't fetched
+    assert!(
+        !service
+            .fetch_remote_tracking_branch(&local_path, "main")
+            .expect("local branch")
+    );
+}
+
 #[test]
 fn rebase_preserves_untracked_files() {
     let td = TempDir::new().unwrap();
@@ -1027,6 +1087,84 @@ fn worktree_diff_ignores_commits_where_base_branch_is_ahead() {
     }));
 }
 
+#[test]
+fn uncommitted_patch_includes_untracked_files_and_leaves_index_alone() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+
+    assert!(s.get_uncommitted_patch(&worktree_path).unwrap().is_empty());
+
+    write_file(&worktree_path, "feat.txt", "feat change\nmore\n");
+    write_file(&worktree_path, "new.txt", "brand new\n");
+    let patch = s.get_uncommitted_patch(&worktree_path).unwrap();
+    assert!(patch.contains("diff --git a/feat.txt b/feat.txt"));
+    assert!(patch.contains("+more"));
+    assert!(patch.contains("diff --git a/new.txt b/new.txt"));
+    assert!(patch.contains("+brand new"));
+
+    // The real index is untouched: new.txt is still untracked
+    let (_, untracked) = s.get_worktree_change_counts(&worktree_path).unwrap();
+    assert_eq!(untracked, 1);
+}
+
+#[test]
+fn squash_folds_branch_commits_and_keeps_co_authors() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    write_file(&worktree_path, "feat.txt", "feat change 2\n");
+    commit_all(
+        &wt_repo,
+        "wip: second\n\nCo-authored-by: Pair <pair@example.com>",
+    );
+    write_file(&worktree_path, "other.txt", "other\n");
+    commit_all(&wt_repo, "wip: third");
+
+    let plan = s
+        .plan_squash(&worktree_path, "old-base", "feature", None)
+        .unwrap();
+    assert_eq!(plan.commits.len(), 3);
+    assert_eq!(plan.commits[0].summary, "feature commit");
+    assert!(
+        plan.message
+            .starts_with("feature commit\n\n- feature commit")
+    );
+    assert!(
+        plan.message
+            .ends_with("\n\nCo-authored-by: Pair <pair@example.com>")
+    );
+
+    // A dry run changes nothing
+    assert_eq!(
+        s.get_branch_status(&repo_path, "feature", "old-base")
+            .unwrap(),
+        (3, 0)
+    );
+
+    let squashed = s.apply_squash(&worktree_path, "feature", &plan).unwrap();
+    assert_eq!(
+        s.get_branch_status(&repo_path, "feature", "old-base")
+            .unwrap(),
+        (1, 0)
+    );
+    let head = wt_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.id().to_string(), squashed);
+    assert_eq!(head.message().unwrap(), plan.message);
+    assert!(s.is_worktree_clean(&worktree_path).unwrap());
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("feat.txt")).unwrap(),
+        "feat change 2\n"
+    );
+
+    // The plan is stale once the branch has moved
+    assert!(matches!(
+        s.apply_squash(&worktree_path, "feature", &plan),
+        Err(GitServiceError::BranchesDiverged(_))
+    ));
+}
+
 // Helper: initialize a repo with main, configure user via service
 fn init_repo_only_service(root: &TempDir) -> PathBuf {
     let repo_path = root.path().join("repo_svc");
@@ -1347,3 +1485,52 @@ fn merge_base_ahead_of_task_should_error() {
         "Merge should error when base branch is ahead of task branch"
     );
 }
+
+fn copy_tree_without_git(source: &Path, target: &Path) {
+    for entry in fs::read_dir(source).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let dest = target.join(entry.file_name());
+        if entry.path().is_dir() {
+            fs::create_dir_all(&dest).unwrap();
+            copy_tree_without_git(&entry.path(), &dest);
+        } else {
+            fs::copy(entry.path(), dest).unwrap();
+        }
+    }
+}
+
+#[test]
+fn copied_worktree_matches_branch_and_keeps_ignored_files() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _) = setup_repo_with_worktree(&td);
+    write_file(&repo_path, ".gitignore", "node_modules/\n");
+    let repo = Repository::open(&repo_path).unwrap();
+    commit_all(&repo, "ignore node_modules");
+    create_branch_from_head(&repo, "copied");
+
+    // Template checkout state: a stale tracked file, an untracked file and an
+    // ignored dependency directory.
+    write_file(&repo_path, "base.txt", "local edit\n");
+    write_file(&repo_path, "scratch.txt", "untracked\n");
+    write_file(&repo_path, "node_modules/dep/index.js", "dep\n");
+
+    let worktree_path = td.path().join("wt-copied");
+    let svc = GitService::new();
+    svc.add_worktree_without_checkout(&repo_path, &worktree_path, "copied")
+        .expect("add worktree without checkout");
+    copy_tree_without_git(&repo_path, &worktree_path);
+
+    svc.sync_copied_worktree_to_head(&worktree_path)
+        .expect("sync copied worktree");
+
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("base.txt")).unwrap(),
+        "from old-base\n"
+    );
+    assert!(!worktree_path.join("scratch.txt").exists());
+    assert!(worktree_path.join("node_modules/dep/index.js").exists());
+    assert!(svc.is_worktree_clean(&worktree_path).unwrap());
+}
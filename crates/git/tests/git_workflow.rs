@@ -7,7 +7,7 @@ use std::{
 use git::{GitCli, GitService};
 use git2::{Repository, build::CheckoutBuilder};
 use tempfile::TempDir;
-use utils::diff::DiffChangeKind;
+use utils::diff::{DiffChangeKind, diff_hunks, hunk_patch};
 
 fn add_path(repo_path: &Path, path: &str) {
     let git = GitCli::new();
@@ -206,6 +206,30 @@ fn commit_and_is_worktree_clean() {
     assert!(s.is_worktree_clean(&repo_path).unwrap());
 }
 
+#[test]
+fn stash_changes_and_pop_by_message() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "foo.txt", "hello\n");
+    let s = GitService::new();
+    s.commit(&repo_path, "add foo").unwrap();
+
+    assert!(!s.stash_changes(&repo_path, "before run").unwrap());
+
+    write_file(&repo_path, "foo.txt", "manual edit\n");
+    assert!(s.ensure_worktree_clean(&repo_path).is_err());
+    assert!(s.stash_changes(&repo_path, "before run").unwrap());
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+
+    assert!(!s.pop_stash(&repo_path, "other run").unwrap());
+    assert!(s.pop_stash(&repo_path, "before run").unwrap());
+    assert_eq!(
+        fs::read_to_string(repo_path.join("foo.txt")).unwrap(),
+        "manual edit\n"
+    );
+    assert!(!s.pop_stash(&repo_path, "before run").unwrap());
+}
+
 #[test]
 fn commit_in_detached_head_succeeds_via_service() {
     let td = TempDir::new().unwrap();
@@ -429,3 +453,115 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn merge_children_into_parent_after_parent_moves() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "shared.txt", "base\n");
+    s.commit(&repo_path, "base").unwrap();
+
+    create_branch(&repo_path, "parent");
+    for (branch, file, content) in [
+        ("child-a", "a.txt", "a\n"),
+        ("child-b", "b.txt", "b\n"),
+        ("child-c", "shared.txt", "from c\n"),
+        ("child-d", "shared.txt", "from d\n"),
+    ] {
+        checkout_branch(&repo_path, "parent");
+        create_branch(&repo_path, branch);
+        checkout_branch(&repo_path, branch);
+        write_file(&repo_path, file, content);
+        s.commit(&repo_path, branch).unwrap();
+    }
+    checkout_branch(&repo_path, "parent");
+
+    s.merge_child_into_parent(&repo_path, "parent", "child-a", "merge a")
+        .unwrap();
+    // The parent has moved past child-b's base; the merge is still clean.
+    s.merge_child_into_parent(&repo_path, "parent", "child-b", "merge b")
+        .unwrap();
+    s.merge_child_into_parent(&repo_path, "parent", "child-c", "merge c")
+        .unwrap();
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt")).unwrap(), "a\n");
+    assert_eq!(fs::read_to_string(repo_path.join("b.txt")).unwrap(), "b\n");
+
+    let head = s.get_branch_oid(&repo_path, "parent").unwrap();
+    let err = s
+        .merge_child_into_parent(&repo_path, "parent", "child-d", "merge d")
+        .unwrap_err();
+    match err {
+        git::GitServiceError::MergeConflicts {
+            conflicted_files, ..
+        } => assert_eq!(conflicted_files, vec!["shared.txt".to_string()]),
+        other => panic!("expected merge conflicts, got {other}"),
+    }
+    assert_eq!(s.get_branch_oid(&repo_path, "parent").unwrap(), head);
+    assert_eq!(
+        fs::read_to_string(repo_path.join("shared.txt")).unwrap(),
+        "from c\n"
+    );
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+
+    // Already merged children add nothing.
+    let again = s
+        .merge_child_into_parent(&repo_path, "parent", "child-a", "merge a again")
+        .unwrap();
+    assert_eq!(again, head);
+}
+
+#[test]
+fn stage_patch_stages_a_single_hunk_in_the_index() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let old: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+    write_file(&repo_path, "notes.txt", &old);
+    let _ = s.commit(&repo_path, "seed").unwrap();
+
+    let new = old
+        .replace("line 2\n", "line two\n")
+        .replace("line 18\n", "line 18\nadded\n");
+    write_file(&repo_path, "notes.txt", &new);
+    let hunks = diff_hunks(&old, &new);
+    assert_eq!(hunks.len(), 2);
+
+    let first = hunk_patch("notes.txt", &hunks[0], false);
+    s.stage_patch(&repo_path, &first).unwrap();
+    let git = GitCli::new();
+    let staged = git.git(&repo_path, ["diff", "--cached"]).unwrap();
+    assert!(staged.contains("+line two"));
+    assert!(!staged.contains("+added"));
+    // The worktree keeps both changes.
+    assert_eq!(
+        fs::read_to_string(repo_path.join("notes.txt")).unwrap(),
+        new
+    );
+
+    // Staging the same hunk again is a no-op, and the later hunk still
+    // applies after the index moved on.
+    s.stage_patch(&repo_path, &first).unwrap();
+    s.stage_patch(&repo_path, &hunk_patch("notes.txt", &hunks[1], false))
+        .unwrap();
+    assert_eq!(git.git(&repo_path, ["diff"]).unwrap(), "");
+}
+
+#[test]
+fn stage_patch_adds_a_new_file_to_the_index() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "seed.txt", "seed\n");
+    let _ = s.commit(&repo_path, "seed").unwrap();
+
+    write_file(&repo_path, "new.txt", "one\ntwo\n");
+    let hunks = diff_hunks("", "one\ntwo\n");
+    s.stage_patch(&repo_path, &hunk_patch("new.txt", &hunks[0], true))
+        .unwrap();
+
+    let staged = GitCli::new()
+        .git(&repo_path, ["diff", "--cached", "--name-status"])
+        .unwrap();
+    assert_eq!(staged.trim(), "A\tnew.txt");
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -17,10 +17,18 @@ use db::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
         execution_process_repo_state::ExecutionProcessRepoState,
+        project_execution_weight::{DEFAULT_EXECUTION_WEIGHT, ProjectExecutionWeight},
         repo::Repo,
+        repo_container_image::RepoContainerImage,
+        repo_resource_limit::RepoResourceLimits,
+        repo_resource_tag::RepoResourceTags,
+        repo_setup_cache::RepoSetupCache,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
         session::{Session, SessionError},
+        task::Task,
         workspace::Workspace,
+        workspace_dev_server::WorkspaceDevServer,
+        workspace_env_var::WorkspaceEnvVars,
         workspace_repo::WorkspaceRepo,
     },
 };
@@ -37,25 +45,40 @@ use executors::{
     logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
 };
 use futures::{FutureExt, TryStreamExt, stream::select};
-use git::GitService;
+use git::{
+    GitService,
+    vcs::{VcsKind, vcs_for},
+};
 use serde_json::json;
 use services::services::{
-    analytics::AnalyticsContext,
+    analytics::{AnalyticsCategory, AnalyticsContext},
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::{Config, DEFAULT_COMMIT_REMINDER_PROMPT},
+    config::{Config, DEFAULT_COMMIT_REMINDER_PROMPT, DirtyWorktreePolicy, ExecutionBackend},
     container::{ContainerError, ContainerRef, ContainerService},
+    dev_server::{PortChange, track_listening_port},
     diff_stream::{self, DiffStreamHandle},
+    execution_scheduler::{ExecutionPermit, ExecutionScheduler},
+    execution_timeout::{self, TimeoutKind, TimeoutLimits, TimeoutWatch},
     file::FileService,
     notification::NotificationService,
+    provider_credentials::ProviderCredentials,
     queued_message::QueuedMessageService,
     remote_client::RemoteClient,
     remote_sync,
+    setup_cache::SetupCacheService,
+};
+use tokio::{
+    sync::{RwLock, broadcast::error::RecvError},
+    task::JoinHandle,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
+    docker::{DockerSandbox, control_container, published_port, remove_container},
     log_msg::LogMsg,
     msg_store::MsgStore,
+    process::{ProcessSignal, signal_process_group},
+    process_tree::{ProcessTreeNode, process_tree},
+    resource_limits::{ExecutionCgroup, ResourceLimits},
     text::{git_branch_id, short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
@@ -64,6 +87,7 @@ use workspace_manager::{RepoWorkspaceInput, WorkspaceError, WorkspaceManager};
 use crate::{command, copy};
 
 const WORKSPACE_TOUCH_DEBOUNCE: Duration = Duration::from_mins(2);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 pub struct LocalContainerService {
@@ -76,6 +100,14 @@ pub struct LocalContainerService {
     /// When stopping execution, we await these to ensure logs are fully persisted.
     db_stream_handles: Arc<RwLock<HashMap<Uuid, JoinHandle<()>>>>,
     exit_monitor_handles: Arc<RwLock<HashMap<Uuid, JoinHandle<()>>>>,
+    /// Scheduler slots held by running coding agent executions.
+    execution_permits: Arc<RwLock<HashMap<Uuid, ExecutionPermit>>>,
+    /// Cgroups enforcing repo resource limits, removed once the process exits.
+    execution_cgroups: Arc<RwLock<HashMap<Uuid, ExecutionCgroup>>>,
+    /// Executions stopped with SIGSTOP, which the idle timeout skips.
+    paused_executions: Arc<RwLock<HashSet<Uuid>>>,
+    /// Docker containers of executions run by the Docker backend.
+    execution_containers: Arc<RwLock<HashMap<Uuid, String>>>,
     workspace_touch_times: Arc<RwLock<HashMap<Uuid, Instant>>>,
     config: Arc<RwLock<Config>>,
     git: GitService,
@@ -83,6 +115,8 @@ pub struct LocalContainerService {
     analytics: Option<AnalyticsContext>,
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
+    execution_scheduler: ExecutionScheduler,
+    provider_credentials: ProviderCredentials,
     notification_service: NotificationService,
     remote_client: Option<RemoteClient>,
 }
@@ -99,12 +133,18 @@ impl LocalContainerService {
         analytics: Option<AnalyticsContext>,
         approvals: Approvals,
         queued_message_service: QueuedMessageService,
+        execution_scheduler: ExecutionScheduler,
+        provider_credentials: ProviderCredentials,
         remote_client: Option<RemoteClient>,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let cancellation_tokens = Arc::new(RwLock::new(HashMap::new()));
         let db_stream_handles = Arc::new(RwLock::new(HashMap::new()));
         let exit_monitor_handles = Arc::new(RwLock::new(HashMap::new()));
+        let execution_permits = Arc::new(RwLock::new(HashMap::new()));
+        let execution_cgroups = Arc::new(RwLock::new(HashMap::new()));
+        let paused_executions = Arc::new(RwLock::new(HashSet::new()));
+        let execution_containers = Arc::new(RwLock::new(HashMap::new()));
         let workspace_touch_times = Arc::new(RwLock::new(HashMap::new()));
         let notification_service = NotificationService::new(config.clone());
 
@@ -116,6 +156,10 @@ impl LocalContainerService {
             msg_stores,
             db_stream_handles,
             exit_monitor_handles,
+            execution_permits,
+            execution_cgroups,
+            paused_executions,
+            execution_containers,
             workspace_touch_times,
             config,
             git,
@@ -123,6 +167,8 @@ impl LocalContainerService {
             analytics,
             approvals,
             queued_message_service,
+            execution_scheduler,
+            provider_credentials,
             notification_service,
             remote_client,
         };
@@ -142,6 +188,7 @@ impl LocalContainerService {
                 ContainerError::Other(anyhow!("No repositories provided"))
             }
             WorkspaceError::Repo(err) => ContainerError::Other(anyhow!(err)),
+            WorkspaceError::DbWorkspace(err) => ContainerError::Workspace(err),
             WorkspaceError::WorkspaceNotFound => {
                 ContainerError::Other(anyhow!("Workspace not found"))
             }
@@ -206,6 +253,21 @@ impl LocalContainerService {
     async fn remove_child_from_store(&self, id: &Uuid) {
         let mut map = self.child_store.write().await;
         map.remove(id);
+        self.execution_cgroups.write().await.remove(id);
+        self.paused_executions.write().await.remove(id);
+        self.remove_execution_container(id).await;
+    }
+
+    /// Remove the Docker container of an execution, which outlives the
+    /// `docker run` client when that is killed.
+    async fn remove_execution_container(&self, id: &Uuid) {
+        let Some(name) = self.execution_containers.write().await.remove(id) else {
+            return;
+        };
+        if let Err(e) = remove_container(&name).await {
+            // Containers that exited on their own are already gone.
+            tracing::debug!("Failed to remove container {}: {}", name, e);
+        }
     }
 
     async fn add_cancellation_token(&self, id: Uuid, token: CancellationToken) {
@@ -238,6 +300,45 @@ impl LocalContainerService {
         map.remove(id)
     }
 
+    /// Wait for a scheduler slot for a coding agent execution in `workspace`.
+    /// The workspace's task project decides which fair-share bucket it joins
+    /// and its repos' resource tags which tag limits it counts against.
+    async fn acquire_execution_slot(
+        &self,
+        workspace: &Workspace,
+        repos: &[Repo],
+    ) -> Result<ExecutionPermit, ContainerError> {
+        let project_id = match workspace.task_id {
+            Some(task_id) => Task::find_by_id(&self.db.pool, task_id)
+                .await?
+                .map(|task| task.project_id),
+            None => None,
+        };
+        let weight = match project_id {
+            Some(project_id) => {
+                ProjectExecutionWeight::weight_for(&self.db.pool, project_id).await?
+            }
+            None => DEFAULT_EXECUTION_WEIGHT,
+        };
+        let repo_ids: Vec<Uuid> = repos.iter().map(|repo| repo.id).collect();
+        let tags = RepoResourceTags::tags_for_repos(&self.db.pool, &repo_ids).await?;
+        let (capacity, tag_limits) = {
+            let config = self.config.read().await;
+            (
+                config.max_concurrent_executions,
+                config.execution_tag_limits.clone(),
+            )
+        };
+        Ok(self
+            .execution_scheduler
+            .acquire(project_id, weight, tags, capacity, tag_limits)
+            .await)
+    }
+
+    async fn release_execution_slot(&self, id: &Uuid) {
+        self.execution_permits.write().await.remove(id);
+    }
+
     async fn cleanup_workspace(&self, workspace: &Workspace) {
         let Some(container_ref) = &workspace.container_ref else {
             return;
@@ -303,6 +404,7 @@ impl LocalContainerService {
                 .workspace_manager
                 .cleanup_orphan_workspaces()
                 .await;
+            container.workspace_manager.log_drift_scan().await;
 
             let mut cleanup_interval =
                 tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
@@ -447,6 +549,65 @@ impl LocalContainerService {
         Ok(false)
     }
 
+    /// Stop a coding agent that outlived the shutdown grace period and commit
+    /// its uncommitted work so a follow-up can pick up from there.
+    async fn interrupt_execution(&self, process: &ExecutionProcess) {
+        tracing::info!(
+            "Interrupting coding agent execution {} for shutdown",
+            process.id
+        );
+        if let Err(e) = self
+            .stop_execution(process, ExecutionProcessStatus::Interrupted)
+            .await
+        {
+            // Still waiting for a scheduler slot, so there is no child to stop
+            tracing::debug!("Failed to stop execution {}: {}", process.id, e);
+            if let Err(e) = ExecutionProcess::update_completion(
+                &self.db.pool,
+                process.id,
+                ExecutionProcessStatus::Interrupted,
+                None,
+            )
+            .await
+            {
+                tracing::error!(
+                    "Failed to mark execution process {} as interrupted: {}",
+                    process.id,
+                    e
+                );
+                return;
+            }
+        }
+
+        let ctx = match ExecutionProcess::load_context(&self.db.pool, process.id).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                tracing::error!("Failed to load context for execution {}: {}", process.id, e);
+                return;
+            }
+        };
+        let Some(workspace_root) = ctx.workspace.container_ref.as_ref().map(PathBuf::from) else {
+            return;
+        };
+        match self.check_repos_for_changes(&workspace_root, &ctx.repos) {
+            Ok(repos_with_changes) if !repos_with_changes.is_empty() => {
+                let message = format!(
+                    "WIP: interrupted by server shutdown\n\nUncommitted changes from execution {}",
+                    process.id
+                );
+                if self.commit_repos(repos_with_changes, &message) {
+                    self.update_after_head_commits(process.id).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                "Failed to check for changes of interrupted execution {}: {}",
+                process.id,
+                e
+            ),
+        }
+    }
+
     /// Commit changes to each repo. Logs failures but continues with other repos.
     fn commit_repos(&self, repos_with_changes: Vec<(Repo, PathBuf)>, message: &str) -> bool {
         let mut any_committed = false;
@@ -458,7 +619,7 @@ impl LocalContainerService {
                 &worktree_path
             );
 
-            match self.git().commit(&worktree_path, message) {
+            match vcs_for(&worktree_path).commit(&worktree_path, message) {
                 Ok(true) => {
                     any_committed = true;
                     tracing::info!("Committed changes in repo '{}'", repo.name);
@@ -486,7 +647,6 @@ impl LocalContainerService {
         let child_store = self.child_store.clone();
         let msg_stores = self.msg_stores.clone();
         let db = self.db.clone();
-        let config = self.config.clone();
         let container = self.clone();
         let analytics = self.analytics.clone();
 
@@ -526,6 +686,9 @@ impl LocalContainerService {
                 }
             }
 
+            // Free the slot before follow-ups below queue for their own.
+            container.release_execution_slot(&exec_id).await;
+
             let (exit_code, status) = match status_result {
                 Ok(exit_status) => {
                     let code = exit_status.code().unwrap_or(-1) as i64;
@@ -567,7 +730,30 @@ impl LocalContainerService {
 
                 let mut already_finalized = false;
 
-                if success || cleanup_done {
+                if success
+                    && matches!(
+                        ctx.execution_process.run_reason,
+                        ExecutionProcessRunReason::SetupScript
+                    )
+                {
+                    container.save_setup_cache(&ctx).await;
+                }
+
+                if ctx.execution_process.run_reason == ExecutionProcessRunReason::CheckCommand {
+                    container.record_check_result(&ctx).await;
+                    // Checks leave the worktree uncommitted, and a failed check
+                    // doesn't stop the ones after it
+                    if matches!(
+                        ctx.execution_process.status,
+                        ExecutionProcessStatus::Completed | ExecutionProcessStatus::Failed
+                    ) && let Err(e) = container.try_start_next_action(&ctx).await
+                    {
+                        tracing::error!("Failed to start next check after completion: {}", e);
+                    }
+                } else if (success || cleanup_done)
+                    && ctx.execution_process.run_reason
+                        != ExecutionProcessRunReason::ChangeExplanation
+                {
                     // Commit changes (if any) and get feedback about whether changes were made
                     let changes_committed = match container.try_commit_changes(&ctx).await {
                         Ok(committed) => committed,
@@ -619,10 +805,12 @@ impl LocalContainerService {
                     let mut started_queued_follow_up = false;
 
                     // Only execute queued messages if the execution succeeded
-                    // If it failed or was killed, just clear the queue and finalize
+                    // If it failed, was killed or timed out, just clear the queue and finalize
                     let should_execute_queued = !matches!(
                         ctx.execution_process.status,
-                        ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+                        ExecutionProcessStatus::Failed
+                            | ExecutionProcessStatus::Killed
+                            | ExecutionProcessStatus::TimedOut
                     );
 
                     if let Some(queued_msg) =
@@ -660,9 +848,12 @@ impl LocalContainerService {
                                 started_queued_follow_up = true;
                             }
                         } else {
-                            // Execution failed or was killed - discard the queued message and finalize
+                            // Execution failed or was killed - discard the queued messages and finalize
+                            container
+                                .queued_message_service
+                                .cancel_queued(ctx.session.id);
                             tracing::info!(
-                                "Discarding queued message for session {} due to execution status {:?}",
+                                "Discarding queued messages for session {} due to execution status {:?}",
                                 ctx.session.id,
                                 ctx.execution_process.status
                             );
@@ -738,20 +929,53 @@ impl LocalContainerService {
                     }
                 }
 
-                // Fire analytics event when CodingAgent execution has finished
-                if config.read().await.analytics_enabled
-                    && matches!(
-                        &ctx.execution_process.run_reason,
-                        ExecutionProcessRunReason::CodingAgent
-                    )
-                    && let Some(analytics) = &analytics
+                // Fire analytics events when CodingAgent execution has finished
+                if matches!(
+                    &ctx.execution_process.run_reason,
+                    ExecutionProcessRunReason::CodingAgent
+                ) && let Some(analytics) = &analytics
                 {
-                    analytics.analytics_service.track_event(&analytics.user_id, "task_attempt_finished", Some(json!({
-                        "workspace_id": ctx.workspace.id.to_string(),
-                        "session_id": ctx.session.id.to_string(),
-                        "execution_success": matches!(ctx.execution_process.status, ExecutionProcessStatus::Completed),
-                        "exit_code": ctx.execution_process.exit_code,
-                    })));
+                    let process = &ctx.execution_process;
+                    let service = &analytics.analytics_service;
+                    service.track_event(
+                        AnalyticsCategory::Usage,
+                        &analytics.user_id,
+                        "task_attempt_finished",
+                        Some(json!({
+                            "workspace_id": ctx.workspace.id.to_string(),
+                            "session_id": ctx.session.id.to_string(),
+                            "execution_success": matches!(process.status, ExecutionProcessStatus::Completed),
+                            "exit_code": process.exit_code,
+                        })),
+                    );
+                    if let Some(completed_at) = process.completed_at {
+                        service.track_event(
+                            AnalyticsCategory::Performance,
+                            &analytics.user_id,
+                            "coding_agent_run_timed",
+                            Some(json!({
+                                "session_id": ctx.session.id.to_string(),
+                                "executor": ctx.session.executor,
+                                "duration_ms": (completed_at - process.started_at).num_milliseconds(),
+                            })),
+                        );
+                    }
+                    if matches!(
+                        process.status,
+                        ExecutionProcessStatus::Failed | ExecutionProcessStatus::TimedOut
+                    ) {
+                        service.track_event(
+                            AnalyticsCategory::Errors,
+                            &analytics.user_id,
+                            "coding_agent_run_failed",
+                            Some(json!({
+                                "session_id": ctx.session.id.to_string(),
+                                "executor": ctx.session.executor,
+                                "status": process.status,
+                                "exit_code": process.exit_code,
+                            })),
+                        );
+                    }
                 }
 
                 // Sync workspace to remote after CodingAgent execution
@@ -809,6 +1033,8 @@ impl LocalContainerService {
                 let _ = child.start_kill();
             }
             child_store.write().await.remove(&exec_id);
+            container.execution_cgroups.write().await.remove(&exec_id);
+            container.remove_execution_container(&exec_id).await;
         })
     }
 
@@ -849,6 +1075,303 @@ impl LocalContainerService {
         rx
     }
 
+    /// Kill the execution once it exceeds `limits`. Any log output counts as
+    /// activity, as does waiting on an approval.
+    async fn spawn_timeout_watchdog(&self, exec_id: Uuid, limits: TimeoutLimits) {
+        if limits.is_unlimited() {
+            return;
+        }
+        let Some(store) = self.msg_stores.read().await.get(&exec_id).cloned() else {
+            return;
+        };
+        let container = self.clone();
+
+        tokio::spawn(async move {
+            let mut receiver = store.get_receiver();
+            let mut watch = TimeoutWatch::new(limits, Instant::now());
+            while let Some(deadline) = watch.next_deadline() {
+                tokio::select! {
+                    msg = receiver.recv() => match msg {
+                        Ok(LogMsg::Finished) | Err(RecvError::Closed) => return,
+                        Ok(_) | Err(RecvError::Lagged(_)) => watch.record_activity(Instant::now()),
+                    },
+                    _ = tokio::time::sleep_until(deadline.into()) => {
+                        let now = Instant::now();
+                        if !container
+                            .approvals
+                            .get_pending_execution_process_ids(&[exec_id])
+                            .is_empty()
+                            || container.paused_executions.read().await.contains(&exec_id)
+                        {
+                            watch.record_activity(now);
+                        }
+                        if let Some(kind) = watch.expired(now) {
+                            container.time_out_execution(exec_id, &watch.limits(), kind).await;
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Publish the port a dev server listens on to its workspace record,
+    /// and clear it again once the dev server exits. The port is the one
+    /// configured for the workspace, exported to the script as `PORT`, or
+    /// else the one the dev server reports.
+    ///
+    /// A dev server in a Docker container (`container`) listens inside it,
+    /// on a port that is published on a different host port.
+    async fn spawn_dev_server_port_scanner(
+        &self,
+        workspace_id: Uuid,
+        exec_id: Uuid,
+        container: Option<String>,
+    ) {
+        let Some(store) = self.msg_stores.read().await.get(&exec_id).cloned() else {
+            return;
+        };
+        let pool = self.db.pool.clone();
+        let configured_port =
+            match WorkspaceDevServer::find_by_workspace_id(&pool, workspace_id).await {
+                Ok(dev_server) => dev_server.and_then(|d| d.port),
+                Err(e) => {
+                    tracing::error!("Failed to load dev server settings: {}", e);
+                    None
+                }
+            };
+
+        tokio::spawn(async move {
+            let resolve = |port: u16| {
+                let container = container.clone();
+                async move {
+                    let Some(name) = container else {
+                        return Some(port);
+                    };
+                    match published_port(&name, port).await {
+                        Ok(host_port) => host_port,
+                        Err(e) => {
+                            tracing::warn!(
+                                "Dev server {} port {} is not published: {}",
+                                exec_id,
+                                port,
+                                e
+                            );
+                            None
+                        }
+                    }
+                }
+            };
+            let publish = |change: PortChange| {
+                let pool = pool.clone();
+                async move {
+                    let result = match change {
+                        PortChange::Allocated(port) => {
+                            tracing::info!(
+                                "Dev server {} of workspace {} listens on port {}",
+                                exec_id,
+                                workspace_id,
+                                port
+                            );
+                            Workspace::set_dev_server_port(&pool, workspace_id, port).await
+                        }
+                        PortChange::Released(port) => {
+                            Workspace::clear_dev_server_port(&pool, workspace_id, port).await
+                        }
+                    };
+                    if let Err(e) = result {
+                        tracing::error!("Failed to publish dev server port: {}", e);
+                    }
+                }
+            };
+            track_listening_port(store, configured_port, resolve, publish).await;
+        });
+    }
+
+    async fn time_out_execution(&self, exec_id: Uuid, limits: &TimeoutLimits, kind: TimeoutKind) {
+        let process = match ExecutionProcess::find_by_id(&self.db.pool, exec_id).await {
+            Ok(Some(process)) if process.status == ExecutionProcessStatus::Running => process,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::error!("Failed to load timed out execution {}: {}", exec_id, e);
+                return;
+            }
+        };
+
+        let message = limits.message(kind);
+        tracing::info!("Execution process {}: {}", exec_id, message);
+        if let Some(store) = self.msg_stores.read().await.get(&exec_id) {
+            store.push(LogMsg::Stderr(format!("\n{message}\n")));
+        }
+        if let Err(e) = self
+            .stop_execution(&process, ExecutionProcessStatus::TimedOut)
+            .await
+        {
+            tracing::error!("Failed to stop timed out execution {}: {}", exec_id, e);
+        }
+    }
+
+    /// The repos an action runs for: a script's own repo, or every repo of
+    /// the workspace for anything running across it.
+    fn repos_for_action<'a>(repos: &'a [Repo], executor_action: &ExecutorAction) -> Vec<&'a Repo> {
+        let script_repo = match executor_action.typ() {
+            ExecutorActionType::ScriptRequest(request) => {
+                request.working_dir.as_deref().and_then(|dir| {
+                    let first = Path::new(dir).components().next()?;
+                    repos
+                        .iter()
+                        .find(|repo| first.as_os_str() == repo.name.as_str())
+                })
+            }
+            _ => None,
+        };
+        match script_repo {
+            Some(repo) => vec![repo],
+            None => repos.iter().collect(),
+        }
+    }
+
+    /// A script run in one repo gets that repo's resource limits; anything
+    /// running across the workspace gets the strictest of all its repos.
+    async fn resource_limits_for(
+        &self,
+        repos: &[Repo],
+        executor_action: &ExecutorAction,
+    ) -> Result<ResourceLimits, ContainerError> {
+        let repo_ids: Vec<Uuid> = Self::repos_for_action(repos, executor_action)
+            .iter()
+            .map(|repo| repo.id)
+            .collect();
+        Ok(RepoResourceLimits::strictest_for_repos(&self.db.pool, &repo_ids).await?)
+    }
+
+    /// The container an execution runs in under the Docker backend. Every
+    /// repo it runs for needs an image; the first repo's is used and the
+    /// ports of all of them are published. `dev_server_port`, the port a dev
+    /// server is configured to listen on, is published on the same host port
+    /// so the preview reaches it where it expects.
+    async fn sandbox_for(
+        &self,
+        repos: &[Repo],
+        executor_action: &ExecutorAction,
+        workspace_dir: &Path,
+        exec_id: Uuid,
+        dev_server_port: Option<u16>,
+    ) -> Result<DockerSandbox, ContainerError> {
+        if cfg!(windows) {
+            return Err(ContainerError::Other(anyhow!(
+                "The Docker execution backend is not supported on Windows"
+            )));
+        }
+        let action_repos = Self::repos_for_action(repos, executor_action);
+        let mut image = None;
+        let mut ports = Vec::new();
+        // Worktrees keep their git metadata in the original repo.
+        let mut mounts = vec![workspace_dir.to_path_buf()];
+        for repo in action_repos {
+            let config = RepoContainerImage::find_by_repo_id(&self.db.pool, repo.id)
+                .await?
+                .ok_or_else(|| {
+                    ContainerError::Other(anyhow!(
+                        "No Docker image is configured for repo {}",
+                        repo.display_name
+                    ))
+                })?;
+            for port in config.ports() {
+                if !ports.contains(&port) && dev_server_port != Some(port) {
+                    ports.push(port);
+                }
+            }
+            image.get_or_insert(config.image);
+            mounts.push(repo.path.join(".git"));
+        }
+        let image = image.ok_or_else(|| {
+            ContainerError::Other(anyhow!("No repos to pick a Docker image from"))
+        })?;
+        Ok(DockerSandbox {
+            image,
+            name: DockerSandbox::container_name(exec_id),
+            mounts,
+            home_mounts: DockerSandbox::agent_config_dirs(),
+            ports,
+            fixed_ports: dev_server_port.into_iter().collect(),
+        })
+    }
+
+    /// Seed new worktrees with the cached output of their repo's setup
+    /// script, so it only has to catch up instead of starting cold.
+    async fn restore_setup_caches(&self, workspace_dir: &Path, repos: &[Repo]) {
+        let cache = SetupCacheService::new();
+        for repo in repos {
+            let Some(script) = repo.setup_script.as_deref() else {
+                continue;
+            };
+            let config = match RepoSetupCache::find_by_repo_id(&self.db.pool, repo.id).await {
+                Ok(Some(config)) => config,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to load setup cache for repo '{}': {}", repo.name, e);
+                    continue;
+                }
+            };
+            match cache
+                .restore(repo.id, &workspace_dir.join(&repo.name), script, &config)
+                .await
+            {
+                Ok(true) => tracing::info!("Restored setup cache for repo '{}'", repo.name),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to restore setup cache for repo '{}': {}",
+                        repo.name,
+                        e
+                    )
+                }
+            }
+        }
+    }
+
+    /// Cache what a finished repo setup script left behind. Runs before the
+    /// next action starts so nothing else is writing to the directories.
+    async fn save_setup_cache(&self, ctx: &ExecutionContext) {
+        let Ok(ExecutorActionType::ScriptRequest(request)) = ctx
+            .execution_process
+            .executor_action()
+            .map(|action| action.typ())
+        else {
+            return;
+        };
+        let (Some(repo_name), Some(container_ref)) = (
+            request.working_dir.as_deref(),
+            ctx.workspace.container_ref.as_deref(),
+        ) else {
+            return;
+        };
+        let repos =
+            match WorkspaceRepo::find_repos_for_workspace(&self.db.pool, ctx.workspace.id).await {
+                Ok(repos) => repos,
+                Err(e) => {
+                    tracing::warn!("Failed to load repos for setup cache: {}", e);
+                    return;
+                }
+            };
+        let Some(repo) = repos.iter().find(|repo| repo.name == repo_name) else {
+            return;
+        };
+        let Ok(Some(config)) = RepoSetupCache::find_by_repo_id(&self.db.pool, repo.id).await else {
+            return;
+        };
+        let worktree = PathBuf::from(container_ref).join(&repo.name);
+        match SetupCacheService::new()
+            .save(repo.id, &worktree, &request.script, &config)
+            .await
+        {
+            Ok(true) => tracing::info!("Saved setup cache for repo '{}'", repo.name),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to save setup cache for repo '{}': {}", repo.name, e),
+        }
+    }
+
     fn dir_name_from_workspace(workspace_id: &Uuid, task_title: &str) -> String {
         let task_title_id = git_branch_id(task_title);
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
@@ -1071,6 +1594,18 @@ impl LocalContainerService {
             .await?;
         }
 
+        // Queued messages can't ask for confirmation, so `confirm` blocks
+        // them like `block` does.
+        if let Some(workspace_root) = ctx.workspace.container_ref.as_deref() {
+            self.guard_dirty_worktree(
+                ctx.workspace.id,
+                Path::new(workspace_root),
+                ctx.session.id,
+                false,
+            )
+            .await?;
+        }
+
         // Get latest agent turn for session continuity (from coding agent turns)
         let latest_session_info =
             CodingAgentTurn::find_latest_session_info(&self.db.pool, ctx.session.id).await?;
@@ -1214,6 +1749,9 @@ impl ContainerService for LocalContainerService {
         self.copy_files_and_images(&created_workspace.workspace_dir, workspace)
             .await?;
 
+        self.restore_setup_caches(&created_workspace.workspace_dir, &repositories)
+            .await;
+
         Self::create_workspace_config_files(&created_workspace.workspace_dir, &repositories)
             .await?;
 
@@ -1358,10 +1896,44 @@ impl ContainerService for LocalContainerService {
             commit_reminder_prompt,
         );
 
+        env.resource_limits = self.resource_limits_for(&repos, executor_action).await?;
+        if self.config.read().await.execution_backend == ExecutionBackend::Docker {
+            let dev_server_port = match execution_process.run_reason {
+                ExecutionProcessRunReason::DevServer => {
+                    WorkspaceDevServer::find_by_workspace_id(&self.db.pool, workspace.id)
+                        .await?
+                        .and_then(|dev_server| dev_server.port)
+                }
+                _ => None,
+            };
+            env.sandbox = Some(
+                self.sandbox_for(
+                    &repos,
+                    executor_action,
+                    &current_dir,
+                    execution_process.id,
+                    dev_server_port,
+                )
+                .await?,
+            );
+        }
+
+        // Stored provider credentials, overridable per workspace
+        env.merge(&self.provider_credentials.env_vars().await);
+
+        // Workspace variables first so they cannot shadow the VK_* context
+        env.merge(&WorkspaceEnvVars::find_by_workspace_id(&self.db.pool, workspace.id).await?);
+
         // Always inject workspace/session context
         env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
         env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
 
+        // Coding agents wait for capacity when concurrent executions are capped
+        let permit = match executor_action.base_executor() {
+            Some(_) => Some(self.acquire_execution_slot(workspace, &repos).await?),
+            None => None,
+        };
+
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
             Duration::from_secs(30),
@@ -1377,8 +1949,36 @@ impl ContainerService for LocalContainerService {
         self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
             .await;
 
+        // Docker enforces the limits of sandboxed executions.
+        if env.sandbox.is_none()
+            && !env.resource_limits.is_unlimited()
+            && let Some(pid) = spawned.child.id()
+            && let Some(cgroup) = ExecutionCgroup::attach(
+                &format!("vk-exec-{}", execution_process.id),
+                &env.resource_limits,
+                pid,
+            )
+        {
+            self.execution_cgroups
+                .write()
+                .await
+                .insert(execution_process.id, cgroup);
+        }
+
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
+        if let Some(sandbox) = &env.sandbox {
+            self.execution_containers
+                .write()
+                .await
+                .insert(execution_process.id, sandbox.name.clone());
+        }
+        if let Some(permit) = permit {
+            self.execution_permits
+                .write()
+                .await
+                .insert(execution_process.id, permit);
+        }
 
         // Store cancellation token for graceful shutdown
         if let Some(cancel) = spawned.cancel {
@@ -1390,6 +1990,27 @@ impl ContainerService for LocalContainerService {
         let hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
         self.add_exit_monitor_handle(execution_process.id, hn).await;
 
+        if execution_process.run_reason == ExecutionProcessRunReason::DevServer {
+            self.spawn_dev_server_port_scanner(
+                workspace.id,
+                execution_process.id,
+                env.sandbox.as_ref().map(|sandbox| sandbox.name.clone()),
+            )
+            .await;
+        }
+
+        if let Some(executor) = executor_action.base_executor() {
+            let timeouts = execution_timeout::effective_timeouts_for_session(
+                &self.db.pool,
+                &self.config.read().await.executor_timeouts,
+                execution_process.session_id,
+                Some(executor),
+            )
+            .await?;
+            self.spawn_timeout_watchdog(execution_process.id, timeouts.into())
+                .await;
+        }
+
         Ok(())
     }
 
@@ -1446,6 +2067,7 @@ impl ContainerService for LocalContainerService {
             }
         }
         self.remove_child_from_store(&execution_process.id).await;
+        self.release_execution_slot(&execution_process.id).await;
 
         // Mark the process finished in the MsgStore and wait for DB persistence
         let db_stream_handle = self.take_db_stream_handle(&execution_process.id).await;
@@ -1500,6 +2122,32 @@ impl ContainerService for LocalContainerService {
                 continue;
             };
 
+            if VcsKind::detect(&worktree_path) != VcsKind::Git {
+                let base_commit =
+                    match vcs_for(&repo.path).base_commit(&repo.path, branch, target_branch) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            tracing::warn!(
+                                "Skipping diff stream for repo {}: failed to get base commit: {}",
+                                repo.name,
+                                e
+                            );
+                            continue;
+                        }
+                    };
+                let stream = diff_stream::create_vcs(diff_stream::VcsDiffStreamArgs {
+                    repo_id: repo.id,
+                    worktree_path: worktree_path.clone(),
+                    base_commit,
+                    stats_only,
+                    path_prefix: Some(repo.name.clone()),
+                })
+                .await
+                .map_err(|e| ContainerError::Other(anyhow!("{e}")))?;
+                streams.push(Box::pin(stream));
+                continue;
+            }
+
             let base_commit = match self
                 .git()
                 .get_base_commit(&repo.path, branch, target_branch)
@@ -1542,6 +2190,62 @@ impl ContainerService for LocalContainerService {
         Ok(Box::pin(futures::stream::select_all(streams)))
     }
 
+    async fn signal_execution(
+        &self,
+        execution_process: &ExecutionProcess,
+        signal: ProcessSignal,
+    ) -> Result<(), ContainerError> {
+        let child = self
+            .get_child_from_store(&execution_process.id)
+            .await
+            .ok_or_else(|| {
+                ContainerError::Other(anyhow!("Child process not found for execution"))
+            })?;
+        let container = self
+            .execution_containers
+            .read()
+            .await
+            .get(&execution_process.id)
+            .cloned();
+        match container {
+            // Signals to the `docker run` client don't reach the container.
+            Some(name) => {
+                let command: &[&str] = match signal {
+                    ProcessSignal::Pause => &["pause"],
+                    ProcessSignal::Resume => &["unpause"],
+                    ProcessSignal::Interrupt => &["kill", "--signal", "INT"],
+                };
+                control_container(command, &name).await?;
+            }
+            None => signal_process_group(&*child.read().await, signal)?,
+        }
+
+        let mut paused = self.paused_executions.write().await;
+        match signal {
+            ProcessSignal::Pause => {
+                paused.insert(execution_process.id);
+            }
+            ProcessSignal::Resume => {
+                paused.remove(&execution_process.id);
+            }
+            ProcessSignal::Interrupt => {}
+        }
+        Ok(())
+    }
+
+    async fn process_tree(
+        &self,
+        execution_process: &ExecutionProcess,
+    ) -> Result<Option<ProcessTreeNode>, ContainerError> {
+        let Some(child) = self.get_child_from_store(&execution_process.id).await else {
+            return Ok(None);
+        };
+        let Some(pid) = child.read().await.id() else {
+            return Ok(None);
+        };
+        Ok(process_tree(pid).await?)
+    }
+
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
         if !matches!(
             ctx.execution_process.run_reason,
@@ -1622,6 +2326,45 @@ impl ContainerService for LocalContainerService {
 
         Ok(())
     }
+
+    async fn drain_running_processes(&self, grace_period: Duration) -> Result<(), ContainerError> {
+        self.execution_scheduler.start_draining();
+
+        let deadline = tokio::time::Instant::now() + grace_period;
+        let mut logged = false;
+        let agents = loop {
+            let agents: Vec<ExecutionProcess> = ExecutionProcess::find_running(&self.db.pool)
+                .await?
+                .into_iter()
+                .filter(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+                .collect();
+            if agents.is_empty() || tokio::time::Instant::now() >= deadline {
+                break agents;
+            }
+            if !logged {
+                tracing::info!(
+                    "Waiting up to {}s for {} coding agent(s) to finish",
+                    grace_period.as_secs(),
+                    agents.len()
+                );
+                logged = true;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        };
+
+        for process in &agents {
+            self.interrupt_execution(process).await;
+        }
+        self.kill_all_running_processes().await
+    }
+
+    fn is_draining(&self) -> bool {
+        self.execution_scheduler.is_draining()
+    }
+
+    async fn dirty_worktree_policy(&self) -> DirtyWorktreePolicy {
+        self.config.read().await.dirty_worktree_policy
+    }
 }
 fn success_exit_status() -> std::process::ExitStatus {
     #[cfg(unix)]
@@ -1635,3 +2378,260 @@ fn success_exit_status() -> std::process::ExitStatus {
         ExitStatusExt::from_raw(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::{
+            execution_process::CreateExecutionProcess, session::CreateSession,
+            workspace::CreateWorkspace, workspace_repo::CreateWorkspaceRepo,
+        },
+        test_utils::TestDb,
+    };
+    use executors::actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage};
+    use utils::command_ext::GroupSpawnNoWindowExt;
+
+    use super::*;
+
+    struct Fixture {
+        _db: TestDb,
+        container: LocalContainerService,
+        workspace: Workspace,
+        session: Session,
+        repo_path: PathBuf,
+    }
+
+    /// A workspace with a single git repo and a session, served by a container
+    /// that skips the orphan cleanup `new` would spawn.
+    async fn fixture() -> Fixture {
+        let test_db = TestDb::new().await;
+        let pool = test_db.pool.clone();
+
+        let workspace_root = test_db.dir.path().join("workspace");
+        let repo_path = workspace_root.join("app");
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+
+        let repo = Repo::find_or_create(&pool, &repo_path, "app")
+            .await
+            .unwrap();
+        let workspace = Workspace::create(
+            &pool,
+            &CreateWorkspace {
+                branch: "vk/drain".to_string(),
+                name: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        Workspace::update_container_ref(&pool, workspace.id, &workspace_root.to_string_lossy())
+            .await
+            .unwrap();
+        let workspace = Workspace::find_by_id(&pool, workspace.id)
+            .await
+            .unwrap()
+            .unwrap();
+        WorkspaceRepo::create_many(
+            &pool,
+            workspace.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: "main".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+        let session = Session::create(
+            &pool,
+            &CreateSession {
+                executor: None,
+                name: None,
+            },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await
+        .unwrap();
+
+        let db = test_db.service();
+        let config = Arc::new(RwLock::new(Config::default()));
+        let container = LocalContainerService {
+            workspace_manager: WorkspaceManager::new(db.clone()),
+            db,
+            child_store: Default::default(),
+            cancellation_tokens: Default::default(),
+            msg_stores: Default::default(),
+            db_stream_handles: Default::default(),
+            exit_monitor_handles: Default::default(),
+            execution_permits: Default::default(),
+            execution_cgroups: Default::default(),
+            paused_executions: Default::default(),
+            execution_containers: Default::default(),
+            workspace_touch_times: Default::default(),
+            notification_service: NotificationService::new(config.clone()),
+            config,
+            git,
+            file_service: FileService::new(pool).unwrap(),
+            analytics: None,
+            approvals: Approvals::new(),
+            queued_message_service: QueuedMessageService::new(Arc::new(MsgStore::new())),
+            execution_scheduler: ExecutionScheduler::new(),
+            provider_credentials: ProviderCredentials::new(
+                test_db.dir.path().join("credentials.json"),
+            ),
+            remote_client: None,
+        };
+
+        Fixture {
+            _db: test_db,
+            container,
+            workspace,
+            session,
+            repo_path,
+        }
+    }
+
+    fn script_action(script: &str) -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: script.to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+                working_dir: None,
+            }),
+            None,
+        )
+    }
+
+    async fn running_coding_agent(fixture: &Fixture) -> ExecutionProcess {
+        ExecutionProcess::create(
+            &fixture.container.db.pool,
+            &CreateExecutionProcess {
+                session_id: fixture.session.id,
+                executor_action: script_action("true"),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+            },
+            Uuid::new_v4(),
+            &[],
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn status_of(fixture: &Fixture, id: Uuid) -> ExecutionProcessStatus {
+        ExecutionProcess::find_by_id(&fixture.container.db.pool, id)
+            .await
+            .unwrap()
+            .unwrap()
+            .status
+    }
+
+    #[tokio::test]
+    async fn grace_period_timeout_interrupts_the_agent_and_commits_its_work() {
+        let fixture = fixture().await;
+        let process = running_coding_agent(&fixture).await;
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .group_spawn_no_window()
+            .unwrap();
+        fixture
+            .container
+            .add_child_to_store(process.id, child)
+            .await;
+        std::fs::write(fixture.repo_path.join("notes.txt"), "half done\n").unwrap();
+        let before = fixture
+            .container
+            .git
+            .get_head_info(&fixture.repo_path)
+            .unwrap();
+
+        let grace_period = Duration::from_millis(100);
+        let started = tokio::time::Instant::now();
+        fixture
+            .container
+            .drain_running_processes(grace_period)
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() >= grace_period);
+        assert_eq!(
+            status_of(&fixture, process.id).await,
+            ExecutionProcessStatus::Interrupted
+        );
+        assert!(
+            fixture
+                .container
+                .get_child_from_store(&process.id)
+                .await
+                .is_none()
+        );
+        let after = fixture
+            .container
+            .git
+            .get_head_info(&fixture.repo_path)
+            .unwrap();
+        assert_ne!(after.oid, before.oid);
+        let status = fixture
+            .container
+            .git
+            .get_worktree_status(&fixture.repo_path)
+            .unwrap();
+        assert!(status.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn queued_agent_without_a_child_is_marked_interrupted() {
+        let fixture = fixture().await;
+        let process = running_coding_agent(&fixture).await;
+        let before = fixture
+            .container
+            .git
+            .get_head_info(&fixture.repo_path)
+            .unwrap();
+
+        fixture
+            .container
+            .drain_running_processes(Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            status_of(&fixture, process.id).await,
+            ExecutionProcessStatus::Interrupted
+        );
+        // Nothing to commit in a clean worktree
+        let after = fixture
+            .container
+            .git
+            .get_head_info(&fixture.repo_path)
+            .unwrap();
+        assert_eq!(after.oid, before.oid);
+    }
+
+    #[tokio::test]
+    async fn draining_refuses_new_executions() {
+        let fixture = fixture().await;
+        fixture.container.execution_scheduler.start_draining();
+
+        let result = fixture
+            .container
+            .start_execution(
+                &fixture.workspace,
+                &fixture.session,
+                &script_action("echo never"),
+                &ExecutionProcessRunReason::SetupScript,
+            )
+            .await;
+
+        assert!(matches!(result, Err(ContainerError::ShuttingDown)));
+        let processes = ExecutionProcess::find_by_session_id(
+            &fixture.container.db.pool,
+            fixture.session.id,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(processes.is_empty());
+    }
+}
@@ -6,12 +6,12 @@ use std::{
 use api_types::LoginStatus;
 use async_trait::async_trait;
 use client_info::ClientInfo;
-use db::DBService;
+use db::{DBService, replication::Replicator};
 use deployment::{Deployment, DeploymentError, RelayHostsNotConfigured, RemoteClientNotConfigured};
 use executors::profile::ExecutorConfigs;
 use git::GitService;
 use preview_proxy::PreviewProxyService;
-use relay_control::{RelayControl, signing::RelaySigningService};
+use relay_control::{RelayControl, SessionKeySealer, signing::RelaySigningService};
 use relay_hosts::RelayHosts;
 use relay_webrtc::WebRtcHost;
 use remote_info::RemoteInfo;
@@ -19,24 +19,40 @@ use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     approvals::Approvals,
     auth::AuthContext,
-    config::{Config, load_config_from_file, save_config_to_file},
+    branch_freshness::BranchFreshnessService,
+    config::{
+        Config, WorktreeStrategy, load_config_from_file, save_config_to_file,
+        secrets::{self, SecretsCipher},
+    },
     container::ContainerService,
+    db_maintenance::DbMaintenanceService,
+    dev_server::DevServerMonitor,
+    doc_index::DocIndexService,
     events::EventService,
+    execution_scheduler::ExecutionScheduler,
     file::FileService,
     file_search::FileSearchCache,
     filesystem::FilesystemService,
+    log_retention::LogRetentionService,
+    log_search::LogSearchService,
     oauth_credentials::OAuthCredentials,
     pr_monitor::PrMonitorService,
+    provider_credentials::ProviderCredentials,
     queued_message::QueuedMessageService,
     remote_client::{RemoteClient, RemoteClientError},
     repo::RepoService,
+    reports::ReportService,
 };
 use tokio::sync::{Notify, RwLock};
 use tokio_util::sync::CancellationToken;
-use trusted_key_auth::runtime::TrustedKeyAuthRuntime;
+use trusted_key_auth::{runtime::TrustedKeyAuthRuntime, trusted_keys::parse_public_key_base64};
 use utils::{
-    assets::{config_path, credentials_path, server_signing_key_path, trusted_keys_path},
+    assets::{
+        config_path, credentials_path, provider_credentials_path, relay_signing_sessions_path,
+        server_signing_key_path, trusted_keys_path,
+    },
     msg_store::MsgStore,
+    sentry as sentry_utils,
 };
 use uuid::Uuid;
 use workspace_manager::WorkspaceManager;
@@ -64,6 +80,8 @@ pub struct LocalDeployment {
     file_search_cache: Arc<FileSearchCache>,
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
+    execution_scheduler: ExecutionScheduler,
+    provider_credentials: ProviderCredentials,
     remote_client: Result<RemoteClient, RemoteClientNotConfigured>,
     auth_context: AuthContext,
     oauth_handoffs: Arc<RwLock<HashMap<Uuid, PendingHandoff>>>,
@@ -73,6 +91,7 @@ pub struct LocalDeployment {
     client_info: ClientInfo,
     remote_info: RemoteInfo,
     preview_proxy: PreviewProxyService,
+    dev_servers: DevServerMonitor,
     relay_hosts: Option<Arc<RelayHosts>>,
     shutdown: CancellationToken,
     webrtc_host: OnceLock<Arc<WebRtcHost>>,
@@ -87,6 +106,20 @@ struct PendingHandoff {
     app_verifier: String,
 }
 
+/// Seals the persisted keys of encrypted relay sessions with the config
+/// secrets key.
+struct ConfigSecretsSealer(&'static SecretsCipher);
+
+impl SessionKeySealer for ConfigSecretsSealer {
+    fn seal(&self, plaintext: &str) -> anyhow::Result<String> {
+        Ok(self.0.encrypt(plaintext)?)
+    }
+
+    fn open(&self, sealed: &str) -> anyhow::Result<String> {
+        Ok(self.0.decrypt(sealed)?)
+    }
+}
+
 #[async_trait]
 impl Deployment for LocalDeployment {
     async fn new(shutdown: CancellationToken) -> Result<Self, DeploymentError> {
@@ -123,10 +156,18 @@ impl Deployment for LocalDeployment {
             let path = utils::path::expand_tilde(workspace_dir);
             WorktreeManager::set_workspace_dir_override(path);
         }
+        WorktreeManager::set_reflink_worktrees(
+            raw_config.worktree_strategy == WorktreeStrategy::Reflink,
+        );
+        sentry_utils::set_error_reporting(
+            raw_config.analytics.errors,
+            raw_config.error_reports_metadata_only,
+        );
 
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
-        let analytics = AnalyticsConfig::new().map(AnalyticsService::new);
+        let analytics = AnalyticsConfig::new()
+            .map(|analytics_config| AnalyticsService::new(analytics_config, config.clone()));
         let git = GitService::new();
         let repo = RepoService::new();
         let msg_stores = Arc::new(RwLock::new(HashMap::new()));
@@ -158,7 +199,13 @@ impl Deployment for LocalDeployment {
         }
 
         let approvals = Approvals::new();
-        let queued_message_service = QueuedMessageService::new();
+        let queued_message_service = QueuedMessageService::new(events_msg_store.clone());
+        let execution_scheduler = ExecutionScheduler::new();
+
+        let provider_credentials = ProviderCredentials::new(provider_credentials_path());
+        if let Err(e) = provider_credentials.load().await {
+            tracing::warn!(?e, "failed to load provider credentials");
+        }
 
         let oauth_credentials = Arc::new(OAuthCredentials::new(credentials_path()));
         if let Err(e) = oauth_credentials.load().await {
@@ -205,11 +252,29 @@ impl Deployment for LocalDeployment {
 
         let oauth_handoffs = Arc::new(RwLock::new(HashMap::new()));
         let trusted_key_auth = TrustedKeyAuthRuntime::new(trusted_keys_path());
+        let trusted_relay_keys = match trusted_key_auth.list_trusted_clients().await {
+            Ok(clients) => clients
+                .iter()
+                .filter_map(|client| parse_public_key_base64(&client.public_key_b64).ok())
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to load trusted relay clients: {}", e);
+                Vec::new()
+            }
+        };
         let relay_signing = RelaySigningService::load_or_generate(&server_signing_key_path())
-            .expect("Failed to load or generate server signing key");
+            .expect("Failed to load or generate server signing key")
+            .with_session_persistence(
+                relay_signing_sessions_path(),
+                &trusted_relay_keys,
+                secrets::cipher().map(|cipher| {
+                    Arc::new(ConfigSecretsSealer(cipher)) as Arc<dyn SessionKeySealer>
+                }),
+            );
         let relay_control = Arc::new(RelayControl::new());
         let client_info = ClientInfo::new();
         let preview_proxy = PreviewProxyService::new();
+        let dev_servers = DevServerMonitor::new(preview_proxy.dev_server_ports().clone());
 
         let ssh_config = embedded_ssh::config::build_config(relay_signing.signing_key());
 
@@ -230,11 +295,14 @@ impl Deployment for LocalDeployment {
             analytics_ctx,
             approvals.clone(),
             queued_message_service.clone(),
+            execution_scheduler.clone(),
+            provider_credentials.clone(),
             remote_client.clone().ok(),
         )
         .await;
 
         let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
+        events.spawn_compaction_loop();
 
         let file_search_cache = Arc::new(FileSearchCache::new());
 
@@ -262,6 +330,30 @@ impl Deployment for LocalDeployment {
             let rc = remote_client.clone().ok();
             PrMonitorService::spawn(db, analytics, container, rc, pr_sync_notify.clone()).await;
         }
+        DocIndexService::spawn_refresh_loop(db.clone());
+        BranchFreshnessService::spawn_monitor_loop(
+            db.clone(),
+            git.clone(),
+            events.msg_store().clone(),
+            config.clone(),
+        );
+        LogSearchService::spawn_backfill_loop(db.clone());
+        {
+            let container = container.clone();
+            let db = db.clone();
+            let config = config.clone();
+            tokio::spawn(async move {
+                // Retention compresses logs the backfill may still be
+                // rewriting, so it only starts once the backfill is done.
+                if let Err(e) = container.backfill_log_spans().await {
+                    tracing::error!("Failed to backfill log spans: {}", e);
+                }
+                LogRetentionService::spawn_compaction_loop(db, config);
+            });
+        }
+        DbMaintenanceService::spawn_maintenance_loop(db.clone());
+        ReportService::spawn_delivery_loop(db.clone(), config.clone());
+        Replicator::spawn_configured();
 
         let deployment = Self {
             config,
@@ -278,6 +370,8 @@ impl Deployment for LocalDeployment {
             file_search_cache,
             approvals,
             queued_message_service,
+            execution_scheduler,
+            provider_credentials,
             remote_client,
             auth_context,
             oauth_handoffs,
@@ -287,6 +381,7 @@ impl Deployment for LocalDeployment {
             client_info,
             remote_info,
             preview_proxy,
+            dev_servers,
             relay_hosts,
             shutdown,
             webrtc_host: OnceLock::new(),
@@ -350,6 +445,14 @@ impl Deployment for LocalDeployment {
         &self.queued_message_service
     }
 
+    fn execution_scheduler(&self) -> &ExecutionScheduler {
+        &self.execution_scheduler
+    }
+
+    fn provider_credentials(&self) -> &ProviderCredentials {
+        &self.provider_credentials
+    }
+
     fn auth_context(&self) -> &AuthContext {
         &self.auth_context
     }
@@ -374,6 +477,10 @@ impl Deployment for LocalDeployment {
         &self.preview_proxy
     }
 
+    fn dev_servers(&self) -> &DevServerMonitor {
+        &self.dev_servers
+    }
+
     fn relay_hosts(&self) -> Result<&Arc<RelayHosts>, RelayHostsNotConfigured> {
         self.relay_hosts.as_ref().ok_or(RelayHostsNotConfigured)
     }
@@ -6,10 +6,15 @@ use std::{
     thread,
 };
 
-use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use portable_pty::{ChildKiller, CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use services::services::config::TerminalConfig;
 use thiserror::Error;
 use tokio::sync::mpsc;
-use utils::shell::get_interactive_shell;
+use utils::{
+    path::{expand_env_vars, expand_tilde},
+    shell::{get_interactive_shell, resolve_executable_path},
+    terminal_recordings::TerminalRecorder,
+};
 use uuid::Uuid;
 
 #[derive(Debug, Error)]
@@ -26,13 +31,52 @@ pub enum PtyError {
     SessionClosed,
 }
 
+type SharedRecorder = Arc<Mutex<TerminalRecorder>>;
+
 struct PtySession {
     writer: Box<dyn Write + Send>,
     master: Box<dyn portable_pty::MasterPty + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    recorder: Option<SharedRecorder>,
     _output_handle: thread::JoinHandle<()>,
     closed: bool,
 }
 
+/// ConPTY rejects a zero-sized console, which the client can briefly report
+/// while its terminal is hidden.
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// A failed write leaves a truncated recording but never disturbs the
+/// terminal itself.
+fn record(
+    recorder: &SharedRecorder,
+    write: impl FnOnce(&mut TerminalRecorder) -> std::io::Result<()>,
+) {
+    if let Ok(mut recorder) = recorder.lock()
+        && let Err(e) = write(&mut recorder)
+    {
+        tracing::warn!("Failed to write terminal recording: {}", e);
+    }
+}
+
+/// Length of `buf` without a trailing, incomplete UTF-8 sequence, so a
+/// character split across reads is sent whole with the next chunk.
+fn complete_utf8_len(buf: &[u8]) -> usize {
+    match std::str::from_utf8(buf) {
+        Ok(_) => buf.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        // Not UTF-8 at all; pass it through as is.
+        Err(_) => buf.len(),
+    }
+}
+
 #[derive(Clone)]
 pub struct PtyService {
     sessions: Arc<Mutex<HashMap<Uuid, PtySession>>>,
@@ -48,63 +92,109 @@ impl PtyService {
     pub async fn create_session(
         &self,
         working_dir: PathBuf,
+        terminal: &TerminalConfig,
+        recorder: Option<TerminalRecorder>,
         cols: u16,
         rows: u16,
     ) -> Result<(Uuid, mpsc::UnboundedReceiver<Vec<u8>>), PtyError> {
         let session_id = Uuid::new_v4();
         let (output_tx, output_rx) = mpsc::unbounded_channel();
-        let shell = get_interactive_shell().await;
+        let configured_shell = terminal
+            .shell
+            .as_deref()
+            .map(str::trim)
+            .filter(|shell| !shell.is_empty());
+        let shell = match configured_shell {
+            Some(shell) => resolve_executable_path(&expand_tilde(shell).to_string_lossy())
+                .await
+                .ok_or_else(|| PtyError::CreateFailed(format!("Shell not found: {shell}")))?,
+            None => get_interactive_shell().await,
+        };
+        let customized = configured_shell.is_some() || terminal.args.is_some();
+        let args = terminal.args.clone();
+        let env: Vec<(String, String)> = terminal
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), expand_env_vars(value)))
+            .collect();
+        let recorder = recorder.map(|recorder| Arc::new(Mutex::new(recorder)));
+        let output_recorder = recorder.clone();
 
         let result = tokio::task::spawn_blocking(move || {
             let pty_system = NativePtySystem::default();
 
             let pty_pair = pty_system
-                .openpty(PtySize {
-                    rows,
-                    cols,
-                    pixel_width: 0,
-                    pixel_height: 0,
-                })
+                .openpty(pty_size(cols, rows))
                 .map_err(|e| PtyError::CreateFailed(e.to_string()))?;
 
             let mut cmd = CommandBuilder::new(&shell);
-            cmd.cwd(&working_dir);
+            // cmd.exe refuses to start in a `\\?\` verbatim path.
+            cmd.cwd(dunce::simplified(&working_dir));
 
             // Configure shell-specific options
-            let shell_name = shell.file_name().and_then(|n| n.to_str()).unwrap_or("");
-
-            if shell_name == "powershell.exe" || shell_name == "pwsh.exe" {
-                // PowerShell: use -NoLogo for cleaner startup
-                cmd.arg("-NoLogo");
+            let shell_name = shell
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_ascii_lowercase();
+
+            if let Some(args) = &args {
+                cmd.args(args);
+            } else if shell_name == "powershell.exe" || shell_name == "pwsh.exe" {
+                // PowerShell: use -NoLogo for cleaner startup, and write UTF-8
+                // instead of the OEM code page so non-ASCII output survives.
+                cmd.args([
+                    "-NoLogo",
+                    "-NoExit",
+                    "-Command",
+                    "[Console]::InputEncoding = [Console]::OutputEncoding = \
+                     [System.Text.UTF8Encoding]::new($false)",
+                ]);
             } else if shell_name == "cmd.exe" {
-                // cmd.exe: no special args needed
-            } else {
+                // cmd.exe: switch the console to the UTF-8 code page
+                cmd.args(["/K", "chcp 65001 >NUL"]);
+            }
+
+            if !matches!(
+                shell_name.as_str(),
+                "powershell.exe" | "pwsh.exe" | "cmd.exe"
+            ) {
                 // Unix shells
                 cmd.env("VIBE_KANBAN_TERMINAL", "1");
 
-                if shell_name == "bash" {
-                    cmd.env("PROMPT_COMMAND", r#"PS1='$ '; unset PROMPT_COMMAND"#);
-                } else if shell_name == "zsh" {
-                    // PROMPT is set after spawning
-                } else {
-                    cmd.env("PS1", "$ ");
+                // A configured shell keeps its own prompt
+                if !customized {
+                    if shell_name == "bash" {
+                        cmd.env("PROMPT_COMMAND", r#"PS1='$ '; unset PROMPT_COMMAND"#);
+                    } else if shell_name == "zsh" {
+                        // PROMPT is set after spawning
+                    } else {
+                        cmd.env("PS1", "$ ");
+                    }
                 }
             }
 
             cmd.env("TERM", "xterm-256color");
             cmd.env("COLORTERM", "truecolor");
+            for (key, value) in &env {
+                cmd.env(key, value);
+            }
 
             let child = pty_pair
                 .slave
                 .spawn_command(cmd)
                 .map_err(|e| PtyError::CreateFailed(e.to_string()))?;
+            let killer = child.clone_killer();
+            // Our copy of the slave would keep the PTY open after the shell
+            // exits.
+            drop(pty_pair.slave);
 
             let mut writer = pty_pair
                 .master
                 .take_writer()
                 .map_err(|e| PtyError::CreateFailed(e.to_string()))?;
 
-            if shell_name == "zsh" {
+            if shell_name == "zsh" && !customized {
                 let _ = writer.write_all(b" PROMPT='$ '; RPROMPT=''\n");
                 let _ = writer.flush();
                 let _ = writer.write_all(b"\x0c");
@@ -118,30 +208,50 @@ impl PtyService {
 
             let output_handle = thread::spawn(move || {
                 let mut buf = [0u8; 4096];
+                let mut pending = Vec::new();
                 loop {
                     match reader.read(&mut buf) {
                         Ok(0) => break,
                         Ok(n) => {
-                            if output_tx.send(buf[..n].to_vec()).is_err() {
+                            pending.extend_from_slice(&buf[..n]);
+                            let complete = complete_utf8_len(&pending);
+                            if complete == 0 {
+                                continue;
+                            }
+                            let rest = pending.split_off(complete);
+                            if let Some(recorder) = &output_recorder {
+                                record(recorder, |recorder| recorder.output(&pending));
+                            }
+                            if output_tx
+                                .send(std::mem::replace(&mut pending, rest))
+                                .is_err()
+                            {
                                 break;
                             }
                         }
                         Err(_) => break,
                     }
                 }
-                drop(child);
+                if !pending.is_empty() {
+                    if let Some(recorder) = &output_recorder {
+                        record(recorder, |recorder| recorder.output(&pending));
+                    }
+                    let _ = output_tx.send(pending);
+                }
             });
 
-            Ok::<_, PtyError>((pty_pair.master, writer, output_handle))
+            Ok::<_, PtyError>((pty_pair.master, writer, child, killer, output_handle))
         })
         .await
         .map_err(|e| PtyError::CreateFailed(e.to_string()))??;
 
-        let (master, writer, output_handle) = result;
+        let (master, writer, mut child, killer, output_handle) = result;
 
         let session = PtySession {
             writer,
             master,
+            killer,
+            recorder,
             _output_handle: output_handle,
             closed: false,
         };
@@ -151,6 +261,17 @@ impl PtyService {
             .map_err(|e| PtyError::CreateFailed(e.to_string()))?
             .insert(session_id, session);
 
+        // A ConPTY's output pipe stays open after the shell exits until the
+        // pseudo console is closed, so the session is dropped once the shell is
+        // gone to end the output stream.
+        let sessions = self.sessions.clone();
+        thread::spawn(move || {
+            let _ = child.wait();
+            if let Ok(mut sessions) = sessions.lock() {
+                sessions.remove(&session_id);
+            }
+        });
+
         Ok((session_id, output_rx))
     }
 
@@ -195,13 +316,11 @@ impl PtyService {
 
         session
             .master
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
+            .resize(pty_size(cols, rows))
             .map_err(|e| PtyError::ResizeFailed(e.to_string()))?;
+        if let Some(recorder) = &session.recorder {
+            record(recorder, |recorder| recorder.resize(cols, rows));
+        }
 
         Ok(())
     }
@@ -214,6 +333,8 @@ impl PtyService {
             .remove(&session_id)
         {
             session.closed = true;
+            // Shells don't exit when their terminal goes away on Windows.
+            let _ = session.killer.kill();
         }
         Ok(())
     }
@@ -224,3 +345,135 @@ impl Default for PtyService {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn holds_back_split_utf8_sequences() {
+        let text = "ok é".as_bytes();
+        assert_eq!(complete_utf8_len(text), text.len());
+        assert_eq!(complete_utf8_len(&text[..text.len() - 1]), 3);
+        assert_eq!(complete_utf8_len(&[0xff, b'a']), 2);
+        assert_eq!(complete_utf8_len(&[]), 0);
+    }
+
+    #[test]
+    fn pty_size_is_never_zero() {
+        let size = pty_size(0, 0);
+        assert_eq!((size.cols, size.rows), (1, 1));
+    }
+
+    /// Reads output until `marker` appears `count` times.
+    async fn read_until(
+        output_rx: &mut mpsc::UnboundedReceiver<Vec<u8>>,
+        marker: &str,
+        count: usize,
+    ) {
+        let mut output = String::new();
+        tokio::time::timeout(Duration::from_secs(20), async {
+            while output.matches(marker).count() < count {
+                let chunk = output_rx.recv().await.expect("output ended early");
+                output.push_str(std::str::from_utf8(&chunk).expect("chunk split a character"));
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{marker:?} not in output: {output:?}"));
+    }
+
+    /// Runs an echo through the platform's shell and checks that the output
+    /// ends once the shell exits.
+    async fn assert_shell_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let service = PtyService::new();
+        let (session_id, mut output_rx) = service
+            .create_session(
+                dir.path().to_path_buf(),
+                &TerminalConfig::default(),
+                None,
+                80,
+                24,
+            )
+            .await
+            .unwrap();
+
+        service
+            .write(session_id, "echo vk-pty-é\r".as_bytes())
+            .await
+            .unwrap();
+        // Once for the echoed input, once for the command's output.
+        read_until(&mut output_rx, "vk-pty-é", 2).await;
+
+        service.write(session_id, b"exit\r").await.unwrap();
+        tokio::time::timeout(Duration::from_secs(20), async {
+            while output_rx.recv().await.is_some() {}
+        })
+        .await
+        .expect("output did not end after the shell exited");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn configured_shell_gets_env() {
+        let dir = TempDir::new().unwrap();
+        let service = PtyService::new();
+        let terminal = TerminalConfig {
+            shell: Some("sh".to_string()),
+            env: HashMap::from([("VK_TERMINAL_TEST".to_string(), "from-config".to_string())]),
+            ..Default::default()
+        };
+        let (session_id, mut output_rx) = service
+            .create_session(dir.path().to_path_buf(), &terminal, None, 80, 24)
+            .await
+            .unwrap();
+
+        service
+            .write(session_id, b"echo \"[$VK_TERMINAL_TEST]\"\r")
+            .await
+            .unwrap();
+        read_until(&mut output_rx, "[from-config]", 1).await;
+        service.close_session(session_id).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_shell_session_round_trip() {
+        assert_shell_round_trip().await;
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn conpty_session_round_trip() {
+        assert_shell_round_trip().await;
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn conpty_session_resizes_and_closes() {
+        let dir = TempDir::new().unwrap();
+        let service = PtyService::new();
+        let (session_id, _output_rx) = service
+            .create_session(
+                dir.path().to_path_buf(),
+                &TerminalConfig::default(),
+                None,
+                80,
+                24,
+            )
+            .await
+            .unwrap();
+
+        service.resize(session_id, 120, 40).await.unwrap();
+        service.resize(session_id, 0, 0).await.unwrap();
+        service.close_session(session_id).await.unwrap();
+        assert!(matches!(
+            service.resize(session_id, 80, 24).await,
+            Err(PtyError::SessionNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use rmcp::{
+    ErrorData, handler::server::wrapper::Parameters, model::CallToolResult, schemars, tool,
+    tool_router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::McpServer;
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+struct SearchDocsRequest {
+    #[schemars(description = "Search terms to look up in the project documentation")]
+    query: String,
+    #[schemars(
+        description = "The project whose documentation to search. Optional inside a workspace, which searches its own project."
+    )]
+    project_id: Option<Uuid>,
+    #[schemars(description = "Maximum number of results to return (default: 5, max: 50)")]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+struct DocSearchHit {
+    #[schemars(description = "The name of the documentation source")]
+    source_name: String,
+    #[schemars(description = "The section title within the source")]
+    title: String,
+    #[schemars(description = "The matching section content")]
+    content: String,
+    #[schemars(description = "Relevance score (higher is better)")]
+    score: f64,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+struct SearchDocsResponse {
+    results: Vec<DocSearchHit>,
+    count: usize,
+}
+
+#[tool_router(router = docs_tools_router, vis = "pub")]
+impl McpServer {
+    #[tool(
+        description = "Search the documentation registered for a project (project docs, API references, OpenAPI specs). Use this to consult project docs without web access."
+    )]
+    async fn search_docs(
+        &self,
+        Parameters(SearchDocsRequest {
+            query,
+            project_id,
+            limit,
+        }): Parameters<SearchDocsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        // The context's project_id is the remote project, so a workspace
+        // searches through its local task's project instead.
+        let path = match (project_id, self.context.as_ref()) {
+            (Some(project_id), _) => format!("/api/projects/{}/docs/search", project_id),
+            (None, Some(ctx)) => format!("/api/workspaces/{}/docs/search", ctx.workspace_id),
+            (None, None) => {
+                return Self::err(
+                    "project_id is required when not running inside a workspace",
+                    None,
+                );
+            }
+        };
+
+        let url = self.url(&path);
+        let mut params = vec![("q", query)];
+        if let Some(limit) = limit {
+            params.push(("limit", limit.to_string()));
+        }
+        let results: Vec<DocSearchHit> =
+            match self.send_json(self.client.get(&url).query(&params)).await {
+                Ok(r) => r,
+                Err(e) => return Ok(Self::tool_error(e)),
+            };
+
+        McpServer::success(&SearchDocsResponse {
+            count: results.len(),
+            results,
+        })
+    }
+}
@@ -37,6 +37,7 @@ impl ToolError {
 }
 
 mod context;
+mod docs;
 mod issue_assignees;
 mod issue_relationships;
 mod issue_tags;
@@ -54,6 +55,7 @@ impl McpServer {
             + Self::workspaces_tools_router()
             + Self::organizations_tools_router()
             + Self::repos_tools_router()
+            + Self::docs_tools_router()
             + Self::remote_projects_tools_router()
             + Self::remote_issues_tools_router()
             + Self::issue_assignees_tools_router()
@@ -66,7 +68,8 @@ impl McpServer {
     pub fn orchestrator_mode_router() -> rmcp::handler::server::tool::ToolRouter<Self> {
         let mut router = Self::context_tools_router()
             + Self::workspaces_tools_router()
-            + Self::session_tools_router();
+            + Self::session_tools_router()
+            + Self::docs_tools_router();
         router.remove_route("list_workspaces");
         router.remove_route("delete_workspace");
         router
@@ -379,6 +382,8 @@ impl McpServer {
             ExecutionProcessStatus::Completed => "completed",
             ExecutionProcessStatus::Failed => "failed",
             ExecutionProcessStatus::Killed => "killed",
+            ExecutionProcessStatus::TimedOut => "timedout",
+            ExecutionProcessStatus::Interrupted => "interrupted",
         }
     }
 }
@@ -420,6 +425,7 @@ mod tests {
             "get_execution".to_string(),
             "list_sessions".to_string(),
             "run_session_prompt".to_string(),
+            "search_docs".to_string(),
             "update_session".to_string(),
             "update_workspace".to_string(),
         ]);
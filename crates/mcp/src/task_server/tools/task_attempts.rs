@@ -190,6 +190,9 @@ impl McpServer {
             },
             prompt: workspace_prompt,
             attachment_ids: None,
+            dev_server: None,
+            setup_script: None,
+            env_vars: None,
         };
 
         let create_and_start_url = self.url("/api/workspaces/start");
@@ -0,0 +1,292 @@
+//! Rewrites loopback absolute URLs in HTML bodies to the proxy origin.
+//!
+//! Dev servers often render links such as `http://localhost:4000/login`.
+//! Followed inside the preview iframe, these leave the proxy origin, so the
+//! injected scripts and relay routing are lost. Only `<a href>`,
+//! `<form action>` and `<meta http-equiv="refresh" content>` are rewritten.
+//! Links built at runtime by scripts are not touched.
+//!
+//! The rewriter is fed the body chunk by chunk and holds back at most one
+//! unfinished tag between chunks.
+
+use std::ops::Range;
+
+use uuid::Uuid;
+
+use crate::{proxied_loopback_url, rewrite_refresh_header_value};
+
+/// A tag longer than this is passed through unchanged rather than buffered
+/// until its end arrives.
+const MAX_PENDING_TAG_BYTES: usize = 16 * 1024;
+
+pub(crate) struct LoopbackUrlRewriter {
+    target_port: u16,
+    proxy_port: u16,
+    relay_host_id: Option<Uuid>,
+    pending: Vec<u8>,
+}
+
+struct Attribute<'a> {
+    name: &'a str,
+    value: &'a str,
+    /// Byte range of the value within the tag, excluding quotes.
+    value_range: Range<usize>,
+    quote: Option<char>,
+}
+
+impl LoopbackUrlRewriter {
+    pub(crate) fn new(target_port: u16, proxy_port: u16, relay_host_id: Option<Uuid>) -> Self {
+        Self {
+            target_port,
+            proxy_port,
+            relay_host_id,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Rewrite the next chunk of the body. A tag cut off at the end of the
+    /// chunk is held back until the next call.
+    pub(crate) fn write(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let mut buf = std::mem::take(&mut self.pending);
+        buf.extend_from_slice(chunk);
+
+        let mut out = Vec::with_capacity(buf.len());
+        let mut pos = 0;
+        while let Some(offset) = buf[pos..].iter().position(|&b| b == b'<') {
+            let tag_start = pos + offset;
+            out.extend_from_slice(&buf[pos..tag_start]);
+
+            let name_end = buf[tag_start + 1..]
+                .iter()
+                .position(|b| !b.is_ascii_alphanumeric())
+                .map(|len| tag_start + 1 + len);
+            let Some(name_end) = name_end else {
+                self.hold_back(&buf[tag_start..], &mut out);
+                return out;
+            };
+
+            let name = &buf[tag_start + 1..name_end];
+            let rewritable = [b"a".as_slice(), b"form", b"meta"]
+                .iter()
+                .any(|candidate| name.eq_ignore_ascii_case(candidate));
+            if !rewritable {
+                out.push(b'<');
+                pos = tag_start + 1;
+                continue;
+            }
+
+            let Some(tag_end) = find_tag_end(&buf, name_end) else {
+                self.hold_back(&buf[tag_start..], &mut out);
+                return out;
+            };
+            let tag = &buf[tag_start..=tag_end];
+            match std::str::from_utf8(tag)
+                .ok()
+                .and_then(|tag| self.rewrite_tag(tag, name_end - tag_start))
+            {
+                Some(rewritten) => out.extend_from_slice(rewritten.as_bytes()),
+                None => out.extend_from_slice(tag),
+            }
+            pos = tag_end + 1;
+        }
+        out.extend_from_slice(&buf[pos..]);
+        out
+    }
+
+    /// Flush whatever was held back. Call once the body has ended.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.pending
+    }
+
+    fn hold_back(&mut self, rest: &[u8], out: &mut Vec<u8>) {
+        if rest.len() > MAX_PENDING_TAG_BYTES {
+            out.extend_from_slice(rest);
+        } else {
+            self.pending = rest.to_vec();
+        }
+    }
+
+    /// Rewrite the URL attribute of a complete `a`, `form` or `meta` tag.
+    /// Returns `None` when nothing in the tag needs to change.
+    fn rewrite_tag(&self, tag: &str, name_end: usize) -> Option<String> {
+        let attributes = parse_attributes(tag, name_end);
+        let find = |name: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+        };
+
+        let tag_name = &tag[1..name_end];
+        let (attribute, rewritten) = if tag_name.eq_ignore_ascii_case("meta") {
+            let is_refresh = find("http-equiv")
+                .is_some_and(|attribute| attribute.value.trim().eq_ignore_ascii_case("refresh"));
+            if !is_refresh {
+                return None;
+            }
+            let content = find("content")?;
+            let rewritten = rewrite_refresh_header_value(
+                content.value,
+                self.target_port,
+                self.proxy_port,
+                self.relay_host_id,
+            )?;
+            (content, rewritten)
+        } else {
+            let url_attribute = if tag_name.eq_ignore_ascii_case("form") {
+                "action"
+            } else {
+                "href"
+            };
+            let attribute = find(url_attribute)?;
+            let rewritten = proxied_loopback_url(
+                attribute.value.trim(),
+                self.target_port,
+                self.proxy_port,
+                self.relay_host_id,
+            )?;
+            (attribute, rewritten)
+        };
+
+        let quote = attribute.quote.unwrap_or('"');
+        let escaped = match quote {
+            '\'' => rewritten.replace('\'', "&#39;"),
+            _ => rewritten.replace('"', "&quot;"),
+        };
+        // Unquoted values are quoted, since the rewritten URL may need it.
+        let wrap = if attribute.quote.is_none() {
+            quote.to_string()
+        } else {
+            String::new()
+        };
+        Some(format!(
+            "{}{wrap}{escaped}{wrap}{}",
+            &tag[..attribute.value_range.start],
+            &tag[attribute.value_range.end..]
+        ))
+    }
+}
+
+/// Index of the `>` closing a tag, ignoring any inside quoted values.
+fn find_tag_end(buf: &[u8], from: usize) -> Option<usize> {
+    let mut quote = None;
+    for (index, &byte) in buf.iter().enumerate().skip(from) {
+        match (quote, byte) {
+            (None, b'>') => return Some(index),
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (Some(open), _) if byte == open => quote = None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_attributes(tag: &str, name_end: usize) -> Vec<Attribute<'_>> {
+    let bytes = tag.as_bytes();
+    let end = tag.len() - 1;
+    let is_name_end = |b: u8| b.is_ascii_whitespace() || matches!(b, b'=' | b'>' | b'/');
+    let skip_whitespace = |mut pos: usize| {
+        while pos < end && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos
+    };
+
+    let mut attributes = Vec::new();
+    let mut pos = name_end;
+    while pos < end {
+        if bytes[pos].is_ascii_whitespace() || bytes[pos] == b'/' {
+            pos += 1;
+            continue;
+        }
+
+        let name_start = pos;
+        while pos < end && !is_name_end(bytes[pos]) {
+            pos += 1;
+        }
+        let name = &tag[name_start..pos];
+        if name.is_empty() {
+            pos += 1;
+            continue;
+        }
+
+        pos = skip_whitespace(pos);
+        if pos >= end || bytes[pos] != b'=' {
+            continue;
+        }
+        pos = skip_whitespace(pos + 1);
+
+        let (value_range, quote) = match bytes.get(pos) {
+            Some(&quote @ (b'"' | b'\'')) if pos < end => {
+                let value_start = pos + 1;
+                let value_end = tag[value_start..end]
+                    .find(quote as char)
+                    .map_or(end, |len| value_start + len);
+                pos = (value_end + 1).min(end);
+                (value_start..value_end, Some(quote as char))
+            }
+            _ => {
+                let value_start = pos;
+                while pos < end && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                (value_start..pos, None)
+            }
+        };
+        attributes.push(Attribute {
+            name,
+            value: &tag[value_range.clone()],
+            value_range,
+            quote,
+        });
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(chunks: &[&str]) -> String {
+        let mut rewriter = LoopbackUrlRewriter::new(4000, 3009, None);
+        let mut out = Vec::new();
+        for chunk in chunks {
+            out.extend(rewriter.write(chunk.as_bytes()));
+        }
+        out.extend(rewriter.finish());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn rewrites_loopback_links_forms_and_meta_refresh() {
+        let html = r#"<a class="nav" HREF="http://localhost:4000/login?next=/">Log in</a>
+<form method=post action=http://127.0.0.1:4000/session></form>
+<meta http-equiv="refresh" content="0; url='http://localhost:4000/home'">"#;
+        assert_eq!(
+            rewrite(&[html]),
+            r#"<a class="nav" HREF="http://4000.localhost:3009/login?next=/">Log in</a>
+<form method=post action="http://4000.localhost:3009/session"></form>
+<meta http-equiv="refresh" content="0; url=http://4000.localhost:3009/home">"#
+        );
+    }
+
+    #[test]
+    fn leaves_other_urls_and_tags_untouched() {
+        let html = r#"<a href="/docs">Docs</a><a href="https://example.com/">Out</a>
+<a href="http://localhost:5173/">Other port</a><img src="http://localhost:4000/logo.png">
+<script>if (a<b && c>d) {}</script><a data-x="1 > 0" title='x'>No href</a>"#;
+        assert_eq!(rewrite(&[html]), html);
+    }
+
+    #[test]
+    fn rewrites_tags_split_across_chunks() {
+        assert_eq!(
+            rewrite(&[
+                "<p>Hi</p><",
+                "a hr",
+                "ef=\"http://localhost:4000/a\" title=\"x > y\"",
+                ">x</a>"
+            ]),
+            r#"<p>Hi</p><a href="http://4000.localhost:3009/a" title="x > y">x</a>"#
+        );
+    }
+}
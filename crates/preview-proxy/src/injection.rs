@@ -0,0 +1,116 @@
+//! Scripts injected into proxied HTML pages.
+//!
+//! Pages only reference the scripts; the proxy serves them itself under
+//! [`SCRIPT_PATH_PREFIX`], so a preview never loads code from a CDN. The
+//! bundled scripts are compiled in. Eruda is fetched by the server once, the
+//! first time a page asks for it, and only with [`ScriptInjection::Full`].
+
+/// Which scripts are injected into proxied HTML pages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptInjection {
+    /// Pages are served exactly as the dev server sent them.
+    None,
+    /// Only the scripts compiled into the binary: the React DevTools hook,
+    /// console/network capture and click-to-component.
+    Local,
+    /// The bundled scripts plus the Eruda console.
+    #[default]
+    Full,
+}
+
+/// Path on the proxy origin the injected scripts are served from.
+pub(crate) const SCRIPT_PATH_PREFIX: &str = "/__vibe_kanban/preview/";
+
+pub(crate) const ERUDA_SCRIPT: &str = "eruda.js";
+pub(crate) const ERUDA_SOURCE_URL: &str = "https://cdn.jsdelivr.net/npm/eruda@3.4.3/eruda.js";
+
+/// DevTools script injected before </body> in HTML responses.
+/// Captures console, network, errors and sends via postMessage.
+const DEVTOOLS_SCRIPT: &str = include_str!("devtools_script.js");
+
+/// Bippy bundle script injected after <head> to install React DevTools hook
+/// before React initializes. Provides fiber inspection utilities.
+const BIPPY_BUNDLE: &str = include_str!("bippy_bundle.js");
+
+/// Click-to-component detection script injected before </body>.
+/// Enables inspect mode for detecting React component hierarchy.
+const CLICK_TO_COMPONENT_SCRIPT: &str = include_str!("click_to_component_script.js");
+
+/// Eruda DevTools initialization script. Initializes Eruda with dark theme
+/// and listens for toggle commands from parent window.
+const ERUDA_INIT: &str = include_str!("eruda_init.js");
+
+/// A script compiled into the binary, by the name it is served under.
+pub(crate) fn bundled_script(name: &str) -> Option<&'static str> {
+    match name {
+        "bippy.js" => Some(BIPPY_BUNDLE),
+        "devtools.js" => Some(DEVTOOLS_SCRIPT),
+        "click-to-component.js" => Some(CLICK_TO_COMPONENT_SCRIPT),
+        "eruda-init.js" => Some(ERUDA_INIT),
+        _ => None,
+    }
+}
+
+fn script_tags(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("<script src=\"{SCRIPT_PATH_PREFIX}{name}\"></script>"))
+        .collect()
+}
+
+/// Tags inserted right after `<head>`. The React DevTools hook has to be in
+/// place before React initializes.
+pub(crate) fn head_tags(policy: ScriptInjection) -> String {
+    match policy {
+        ScriptInjection::None => String::new(),
+        ScriptInjection::Local | ScriptInjection::Full => script_tags(&["bippy.js"]),
+    }
+}
+
+/// Tags inserted before `</body>`. With `nav_script_disabled` the
+/// console/network capture script is left out.
+pub(crate) fn body_tags(policy: ScriptInjection, nav_script_disabled: bool) -> String {
+    let mut names = Vec::new();
+    if policy == ScriptInjection::Full {
+        names.extend([ERUDA_SCRIPT, "eruda-init.js"]);
+    }
+    if policy != ScriptInjection::None {
+        if !nav_script_disabled {
+            names.push("devtools.js");
+        }
+        names.push("click-to-component.js");
+    }
+    script_tags(&names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_policy_skips_eruda() {
+        let tags = body_tags(ScriptInjection::Local, false);
+        assert!(!tags.contains("eruda"));
+        assert!(tags.contains("/__vibe_kanban/preview/devtools.js"));
+        assert!(body_tags(ScriptInjection::Full, false).contains("eruda.js"));
+    }
+
+    #[test]
+    fn none_policy_injects_nothing() {
+        assert!(head_tags(ScriptInjection::None).is_empty());
+        assert!(body_tags(ScriptInjection::None, false).is_empty());
+    }
+
+    #[test]
+    fn every_injected_bundled_script_is_served() {
+        let tags = head_tags(ScriptInjection::Full) + &body_tags(ScriptInjection::Full, false);
+        for name in tags
+            .split(SCRIPT_PATH_PREFIX)
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .filter(|name| *name != ERUDA_SCRIPT)
+        {
+            assert!(bundled_script(name).is_some(), "{name} is not served");
+        }
+    }
+}
@@ -7,29 +7,47 @@
 //! Host header subdomain. A request to `{port}.localhost:{proxy_port}/path`
 //! is forwarded to `localhost:{port}/path`.
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::{FromRequestParts, Request, ws::WebSocketUpgrade},
     http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use hyper_util::rt::TokioIo;
 use reqwest::Client;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    sync::OnceCell,
+};
+use utils::http_headers::is_hop_by_hop_header;
 use uuid::Uuid;
 use ws_bridge::{UpstreamWsConnectError, WsBridgeError, bridge_axum_ws, connect_upstream_ws};
 
-use crate::proxy_common::{
-    build_local_upstream_url, extract_ws_protocols, normalized_proxy_path,
-    should_forward_request_header,
+use crate::{
+    html_rewrite::LoopbackUrlRewriter,
+    injection::{ERUDA_SCRIPT, ERUDA_SOURCE_URL, SCRIPT_PATH_PREFIX},
+    proxy_common::{
+        build_local_upstream_url, extract_ws_protocols, normalized_proxy_path,
+        should_forward_request_header,
+    },
 };
+pub use crate::{injection::ScriptInjection, registry::DevServerPorts};
 
 pub mod api;
+mod html_rewrite;
+mod injection;
 mod proxy_common;
+mod registry;
 
 #[derive(Clone)]
 pub struct PreviewProxyService {
     http_client: Client,
+    /// Eruda, downloaded on first use so pages load it from the proxy.
+    eruda_script: Arc<OnceCell<Bytes>>,
+    dev_server_ports: DevServerPorts,
 }
 
 impl Default for PreviewProxyService {
@@ -44,12 +62,35 @@ impl PreviewProxyService {
             .redirect(reqwest::redirect::Policy::none())
             .build()
             .expect("failed to build preview proxy HTTP client");
-        Self { http_client }
+        Self {
+            http_client,
+            eruda_script: Arc::new(OnceCell::new()),
+            dev_server_ports: DevServerPorts::default(),
+        }
+    }
+
+    pub fn dev_server_ports(&self) -> &DevServerPorts {
+        &self.dev_server_ports
     }
 
     pub(crate) fn http_client(&self) -> &Client {
         &self.http_client
     }
+
+    async fn eruda_script(&self) -> Result<Bytes, reqwest::Error> {
+        self.eruda_script
+            .get_or_try_init(|| async {
+                self.http_client
+                    .get(ERUDA_SOURCE_URL)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await
+            })
+            .await
+            .cloned()
+    }
 }
 
 fn env_flag_enabled(name: &str) -> bool {
@@ -85,22 +126,6 @@ const STRIP_RESPONSE_HEADERS: &[&str] = &[
     "content-encoding",
 ];
 
-/// DevTools script injected before </body> in HTML responses.
-/// Captures console, network, errors and sends via postMessage.
-const DEVTOOLS_SCRIPT: &str = include_str!("devtools_script.js");
-
-/// Bippy bundle script injected after <head> to install React DevTools hook
-/// before React initializes. Provides fiber inspection utilities.
-const BIPPY_BUNDLE: &str = include_str!("bippy_bundle.js");
-
-/// Click-to-component detection script injected before </body>.
-/// Enables inspect mode for detecting React component hierarchy.
-const CLICK_TO_COMPONENT_SCRIPT: &str = include_str!("click_to_component_script.js");
-
-/// Eruda DevTools initialization script. Initializes Eruda with dark theme
-/// and listens for toggle commands from parent window.
-const ERUDA_INIT: &str = include_str!("eruda_init.js");
-
 /// Collect response headers to forward to the iframe response.
 /// Keeps duplicate headers (e.g. `Set-Cookie`) by preserving each entry.
 fn collect_response_headers(
@@ -255,25 +280,34 @@ fn rewrite_redirect_like_header_value(
         return Some(normalized_value);
     }
 
-    let mut parsed = if normalized_value.starts_with("//") {
-        reqwest::Url::parse(&format!("http:{normalized_value}")).ok()?
+    if let Some(rewritten) =
+        proxied_loopback_url(&normalized_value, target_port, proxy_port, relay_host_id)
+    {
+        return Some(rewritten);
+    }
+
+    if normalized_value == original_value {
+        return None;
+    }
+    Some(normalized_value)
+}
+
+/// Point an absolute (or scheme-relative) URL on the target's loopback port at
+/// the proxy origin. Returns `None` for any other URL.
+fn proxied_loopback_url(
+    value: &str,
+    target_port: u16,
+    proxy_port: u16,
+    relay_host_id: Option<Uuid>,
+) -> Option<String> {
+    let mut parsed = if value.starts_with("//") {
+        reqwest::Url::parse(&format!("http:{value}")).ok()?
     } else {
-        reqwest::Url::parse(&normalized_value).ok()?
+        reqwest::Url::parse(value).ok()?
     };
     let host = parsed.host_str()?.to_ascii_lowercase();
-    if !is_loopback_redirect_host(&host) {
-        if normalized_value == original_value {
-            return None;
-        }
-        return Some(normalized_value);
-    }
-
-    let parsed_port = parsed.port_or_known_default()?;
-    if parsed_port != target_port {
-        if normalized_value == original_value {
-            return None;
-        }
-        return Some(normalized_value);
+    if !is_loopback_redirect_host(&host) || parsed.port_or_known_default()? != target_port {
+        return None;
     }
 
     parsed.set_scheme("http").ok()?;
@@ -381,10 +415,19 @@ fn extract_target_from_host(headers: &HeaderMap) -> Option<PreviewTarget> {
     })
 }
 
+/// The port of a preview served by this machine, from the Host subdomain.
+/// `None` for relayed previews, whose port belongs to another host.
+pub fn local_target_port(headers: &HeaderMap) -> Option<u16> {
+    extract_target_from_host(headers)
+        .filter(|target| target.relay_host_id.is_none())
+        .map(|target| target.port)
+}
+
 pub async fn proxy_subdomain_request(
     service: &PreviewProxyService,
     backend_addr: SocketAddr,
     proxy_port: u16,
+    injection: ScriptInjection,
     request: Request,
 ) -> Response {
     let target = match extract_target_from_host(request.headers()) {
@@ -394,9 +437,54 @@ pub async fn proxy_subdomain_request(
         }
     };
 
+    if let Some(name) = request.uri().path().strip_prefix(SCRIPT_PATH_PREFIX) {
+        return serve_injected_script(service, name).await;
+    }
+
     let path = normalized_proxy_path(request.uri().path()).to_string();
 
-    proxy_impl(service, backend_addr, proxy_port, target, path, request).await
+    proxy_impl(
+        service,
+        backend_addr,
+        proxy_port,
+        target,
+        injection,
+        path,
+        request,
+    )
+    .await
+}
+
+/// Serve one of the scripts referenced by injected tags. These are never
+/// forwarded to the dev server.
+async fn serve_injected_script(service: &PreviewProxyService, name: &str) -> Response {
+    let script = if name == ERUDA_SCRIPT {
+        match service.eruda_script().await {
+            Ok(script) => script,
+            Err(error) => {
+                tracing::warn!("Failed to fetch Eruda: {}", error);
+                return (StatusCode::BAD_GATEWAY, "Eruda is unavailable").into_response();
+            }
+        }
+    } else {
+        match injection::bundled_script(name) {
+            Some(script) => Bytes::from_static(script.as_bytes()),
+            None => return StatusCode::NOT_FOUND.into_response(),
+        }
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/javascript; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(script))
+        .unwrap_or_else(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to build response",
+            )
+                .into_response()
+        })
 }
 
 async fn proxy_impl(
@@ -404,6 +492,7 @@ async fn proxy_impl(
     backend_addr: SocketAddr,
     proxy_port: u16,
     target: PreviewTarget,
+    injection: ScriptInjection,
     path_str: String,
     request: Request,
 ) -> Response {
@@ -415,6 +504,15 @@ async fn proxy_impl(
     let query_string = parts.uri.query().map(|q| q.to_string());
     let ws_protocols: Option<String> = extract_ws_protocols(&parts.headers);
 
+    if wants_ws_passthrough(&parts.headers) {
+        tracing::debug!(
+            "WebSocket passthrough for path: {} -> localhost:{}",
+            path_str,
+            target.port
+        );
+        return handle_ws_passthrough(backend_addr, target, &path_str, &mut parts).await;
+    }
+
     if let Ok(ws) = WebSocketUpgrade::from_request_parts(&mut parts, &()).await {
         tracing::debug!(
             "WebSocket upgrade request for path: {} -> localhost:{}",
@@ -449,7 +547,37 @@ async fn proxy_impl(
     }
 
     let request = Request::from_parts(parts, body);
-    http_proxy_handler(service, backend_addr, proxy_port, target, path_str, request).await
+    http_proxy_handler(
+        service,
+        backend_addr,
+        proxy_port,
+        target,
+        injection,
+        path_str,
+        request,
+    )
+    .await
+}
+
+/// Read an HTML response body. With `VK_PREVIEW_REWRITE_ABSOLUTE_URLS` set,
+/// loopback links to the target are rewritten to the proxy origin as the body
+/// streams in.
+async fn read_html_body(
+    mut response: reqwest::Response,
+    target: PreviewTarget,
+    proxy_port: u16,
+) -> Result<Vec<u8>, reqwest::Error> {
+    if !env_flag_enabled("VK_PREVIEW_REWRITE_ABSOLUTE_URLS") {
+        return Ok(response.bytes().await?.to_vec());
+    }
+
+    let mut rewriter = LoopbackUrlRewriter::new(target.port, proxy_port, target.relay_host_id);
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend(rewriter.write(&chunk));
+    }
+    body.extend(rewriter.finish());
+    Ok(body)
 }
 
 async fn http_proxy_handler(
@@ -457,6 +585,7 @@ async fn http_proxy_handler(
     backend_addr: SocketAddr,
     proxy_port: u16,
     target: PreviewTarget,
+    injection: ScriptInjection,
     path_str: String,
     request: Request,
 ) -> Response {
@@ -589,32 +718,20 @@ async fn http_proxy_handler(
     }
 
     if is_html {
-        match response.bytes().await {
+        match read_html_body(response, target, proxy_port).await {
             Ok(body_bytes) => {
                 let mut html = String::from_utf8_lossy(&body_bytes).to_string();
 
-                // Inject bippy bundle after <head> (must load before React)
+                // Inject the React DevTools hook after <head> (must load before React)
                 if let Some(pos) = html.to_lowercase().find("<head>") {
                     let head_end = pos + "<head>".len();
-                    let bippy_tag = format!("<script>{}</script>", BIPPY_BUNDLE);
-                    html.insert_str(head_end, &bippy_tag);
+                    html.insert_str(head_end, &injection::head_tags(injection));
                 }
 
-                // Inject Eruda CDN, init, devtools and click-to-component scripts before </body>
+                // Inject Eruda, devtools and click-to-component scripts before </body>
                 if let Some(pos) = html.to_lowercase().rfind("</body>") {
                     let nav_script_disabled = env_flag_enabled("VK_PREVIEW_DISABLE_NAV_SCRIPT");
-                    let scripts = if nav_script_disabled {
-                        format!(
-                            "<script src=\"https://cdn.jsdelivr.net/npm/eruda@3.4.3/eruda.js\"></script><script>{}</script><script>{}</script>",
-                            ERUDA_INIT, CLICK_TO_COMPONENT_SCRIPT
-                        )
-                    } else {
-                        format!(
-                            "<script src=\"https://cdn.jsdelivr.net/npm/eruda@3.4.3/eruda.js\"></script><script>{}</script><script>{}</script><script>{}</script>",
-                            ERUDA_INIT, DEVTOOLS_SCRIPT, CLICK_TO_COMPONENT_SCRIPT
-                        )
-                    };
-                    html.insert_str(pos, &scripts);
+                    html.insert_str(pos, &injection::body_tags(injection, nav_script_disabled));
                 }
 
                 let mut builder = Response::builder().status(status);
@@ -728,28 +845,233 @@ async fn handle_ws_proxy(
     query_string: Option<String>,
     ws_protocols: Option<String>,
 ) -> Result<(), PreviewWsBridgeError> {
-    let normalized_path = normalized_proxy_path(&path);
-    let query = query_string.as_deref().unwrap_or_default();
-    let ws_url = if let Some(host_id) = target.relay_host_id {
+    let ws_url = upstream_target_url(
+        backend_addr,
+        target,
+        &path,
+        query_string.as_deref().unwrap_or_default(),
+        "ws",
+    );
+    tracing::debug!("Connecting to dev server WebSocket: {}", ws_url);
+
+    let (dev_server_ws, _selected_protocol) =
+        connect_upstream_ws(ws_url, ws_protocols.as_deref()).await?;
+    tracing::debug!("Connected to dev server WebSocket");
+
+    bridge_axum_ws(client_socket, dev_server_ws).await?;
+    Ok(())
+}
+
+fn upstream_target_url(
+    backend_addr: SocketAddr,
+    target: PreviewTarget,
+    path: &str,
+    query: &str,
+    scheme: &str,
+) -> String {
+    let normalized_path = normalized_proxy_path(path);
+    if let Some(host_id) = target.relay_host_id {
         relay_preview_target_url(
             backend_addr,
             host_id,
             target.port,
             normalized_path,
             query,
-            "ws",
+            scheme,
         )
     } else {
-        build_local_upstream_url("ws", target.port, normalized_path, query)
+        build_local_upstream_url(scheme, target.port, normalized_path, query)
+    }
+}
+
+fn offers_permessage_deflate(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::SEC_WEBSOCKET_EXTENSIONS)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|extension| {
+            extension
+                .split(';')
+                .next()
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case("permessage-deflate"))
+        })
+}
+
+/// Whether a request is a WebSocket upgrade that should go through
+/// [`passthrough_ws_upgrade`] rather than be bridged message by message.
+///
+/// Neither axum nor tungstenite implement permessage-deflate, so a bridged
+/// socket always runs uncompressed. Only upgrades offering compression are
+/// worth passing through.
+pub fn wants_ws_passthrough(headers: &HeaderMap) -> bool {
+    let is_ws_upgrade = headers
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    is_ws_upgrade && offers_permessage_deflate(headers)
+}
+
+/// Proxy a WebSocket upgrade to a local dev server, or to this server's host
+/// proxy route for relay targets, which passes it on through the relay the
+/// same way.
+async fn handle_ws_passthrough(
+    backend_addr: SocketAddr,
+    target: PreviewTarget,
+    path: &str,
+    parts: &mut axum::http::request::Parts,
+) -> Response {
+    let target_url = upstream_target_url(
+        backend_addr,
+        target,
+        path,
+        parts.uri.query().unwrap_or_default(),
+        "http",
+    );
+    let Some((authority, path_and_query)) = target_url
+        .strip_prefix("http://")
+        .and_then(|rest| rest.split_once('/'))
+    else {
+        return (StatusCode::BAD_GATEWAY, "Invalid upstream URL").into_response();
     };
-    tracing::debug!("Connecting to dev server WebSocket: {}", ws_url);
 
-    let (dev_server_ws, _selected_protocol) =
-        connect_upstream_ws(ws_url, ws_protocols.as_deref()).await?;
-    tracing::debug!("Connected to dev server WebSocket");
+    let upstream = match TcpStream::connect(authority).await {
+        Ok(upstream) => upstream,
+        Err(error) => {
+            tracing::debug!(
+                "Failed to proxy WebSocket upgrade to {}: {}",
+                target_url,
+                error
+            );
+            return (
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server unreachable: {}", error),
+            )
+                .into_response();
+        }
+    };
+    passthrough_ws_upgrade(parts, upstream, authority, &format!("/{path_and_query}")).await
+}
 
-    bridge_axum_ws(client_socket, dev_server_ws).await?;
-    Ok(())
+/// Proxy a WebSocket upgrade as raw bytes over `upstream`, instead of
+/// bridging messages.
+///
+/// The handshake is forwarded so the browser and dev server negotiate
+/// compression with each other, and the frames pass through untouched.
+/// `upstream` is any byte stream that ends at an HTTP server, e.g. a TCP
+/// connection to the dev server or a tunnel through the relay.
+pub async fn passthrough_ws_upgrade<U>(
+    parts: &mut axum::http::request::Parts,
+    upstream: U,
+    host: &str,
+    path_and_query: &str,
+) -> Response
+where
+    U: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let Some(client_upgrade) = parts.extensions.remove::<hyper::upgrade::OnUpgrade>() else {
+        return (StatusCode::BAD_REQUEST, "Connection cannot be upgraded").into_response();
+    };
+
+    let mut request = axum::http::Request::get(path_and_query)
+        .header(header::HOST, host)
+        .header(header::CONNECTION, "Upgrade")
+        .header(header::UPGRADE, "websocket");
+    for (name, value) in &parts.headers {
+        let handshake_header = name == header::SEC_WEBSOCKET_KEY
+            || name == header::SEC_WEBSOCKET_VERSION
+            || name == header::SEC_WEBSOCKET_EXTENSIONS;
+        if handshake_header || should_forward_request_header(name.as_str()) {
+            request = request.header(name, value);
+        }
+    }
+    let Ok(request) = request.body(Body::empty()) else {
+        return (StatusCode::BAD_REQUEST, "Invalid WebSocket upgrade request").into_response();
+    };
+
+    let response = match send_upgrade_request(upstream, request).await {
+        Ok(response) => response,
+        Err(error) => {
+            tracing::debug!(
+                "Failed to proxy WebSocket upgrade to {}: {}",
+                path_and_query,
+                error
+            );
+            return (
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server unreachable: {}", error),
+            )
+                .into_response();
+        }
+    };
+
+    let status = response.status();
+    let mut builder = Response::builder().status(status);
+    for (name, value) in response.headers() {
+        if status == StatusCode::SWITCHING_PROTOCOLS || !is_hop_by_hop_header(name.as_str()) {
+            builder = builder.header(name, value);
+        }
+    }
+
+    if status != StatusCode::SWITCHING_PROTOCOLS {
+        // The dev server refused the upgrade; let the browser see why.
+        return builder
+            .body(Body::new(response.into_body()))
+            .unwrap_or_else(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to build response",
+                )
+                    .into_response()
+            });
+    }
+
+    tokio::spawn(async move {
+        let (client, upstream) = match tokio::try_join!(
+            async { client_upgrade.await.map_err(|e| e.to_string()) },
+            async {
+                hyper::upgrade::on(response)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+        ) {
+            Ok(upgraded) => upgraded,
+            Err(error) => {
+                tracing::debug!("WebSocket passthrough upgrade failed: {}", error);
+                return;
+            }
+        };
+        let mut client = TokioIo::new(client);
+        let mut upstream = TokioIo::new(upstream);
+        if let Err(error) = tokio::io::copy_bidirectional(&mut client, &mut upstream).await {
+            tracing::debug!("WebSocket passthrough closed: {}", error);
+        }
+    });
+
+    builder.body(Body::empty()).unwrap_or_else(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to build response",
+        )
+            .into_response()
+    })
+}
+
+async fn send_upgrade_request<U>(
+    upstream: U,
+    request: axum::http::Request<Body>,
+) -> Result<axum::http::Response<hyper::body::Incoming>, hyper::Error>
+where
+    U: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) =
+        hyper::client::conn::http1::handshake(TokioIo::new(upstream)).await?;
+    tokio::spawn(async move {
+        if let Err(error) = connection.with_upgrades().await {
+            tracing::debug!("WebSocket passthrough connection failed: {}", error);
+        }
+    });
+    sender.send_request(request).await
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -834,6 +1156,7 @@ mod tests {
     use axum::http::header::{
         CACHE_CONTROL, CONTENT_LENGTH, CONTENT_SECURITY_POLICY, LOCATION, SET_COOKIE,
     };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use uuid::Uuid;
 
     use super::*;
@@ -1271,4 +1594,117 @@ mod tests {
         let result = detect_rsc_redirect_in_body(body);
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn offers_permessage_deflate_matches_extension_name_only() {
+        let mut headers = HeaderMap::new();
+        assert!(!offers_permessage_deflate(&headers));
+
+        headers.insert(
+            header::SEC_WEBSOCKET_EXTENSIONS,
+            HeaderValue::from_static("x-webkit-deflate-frame"),
+        );
+        assert!(!offers_permessage_deflate(&headers));
+
+        headers.insert(
+            header::SEC_WEBSOCKET_EXTENSIONS,
+            HeaderValue::from_static("foo, Permessage-Deflate; client_max_window_bits"),
+        );
+        assert!(offers_permessage_deflate(&headers));
+    }
+
+    async fn read_head(stream: &mut (impl AsyncRead + Unpin)) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap().to_ascii_lowercase()
+    }
+
+    /// Accepts one WebSocket handshake with compression, reports the request
+    /// head, then echoes whatever arrives.
+    async fn fake_dev_server(
+        mut stream: impl AsyncRead + AsyncWrite + Unpin,
+        head_tx: tokio::sync::oneshot::Sender<String>,
+    ) {
+        let head = read_head(&mut stream).await;
+        stream
+            .write_all(
+                b"HTTP/1.1 101 Switching Protocols\r\n\
+                  upgrade: websocket\r\n\
+                  connection: Upgrade\r\n\
+                  sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+                  sec-websocket-extensions: permessage-deflate\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let _ = head_tx.send(head);
+        let mut buf = [0u8; 64];
+        loop {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => stream.write_all(&buf[..n]).await.unwrap(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn passthrough_forwards_the_handshake_and_raw_frames_over_any_stream() {
+        // An in-memory stream stands in for a tunnel through the relay.
+        let (upstream, dev_server) = tokio::io::duplex(1024);
+        let (head_tx, head_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(fake_dev_server(dev_server, head_tx));
+
+        let upstream = Arc::new(std::sync::Mutex::new(Some(upstream)));
+        let app = axum::Router::new().fallback(move |request: Request| {
+            let upstream = upstream.lock().unwrap().take();
+            async move {
+                let (mut parts, _) = request.into_parts();
+                assert!(wants_ws_passthrough(&parts.headers));
+                let upstream = upstream.expect("one upgrade per test");
+                passthrough_ws_upgrade(&mut parts, upstream, "localhost:5173", "/hmr?token=1").await
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(
+                b"GET /hmr?token=1 HTTP/1.1\r\n\
+                  host: 5173.localhost:8080\r\n\
+                  connection: Upgrade\r\n\
+                  upgrade: websocket\r\n\
+                  sec-websocket-version: 13\r\n\
+                  sec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  sec-websocket-extensions: permessage-deflate; client_max_window_bits\r\n\r\n",
+            )
+            .await
+            .unwrap();
+
+        let response = read_head(&mut client).await;
+        assert!(response.starts_with("http/1.1 101"), "{response}");
+        assert!(response.contains("sec-websocket-extensions: permessage-deflate\r\n"));
+        assert!(response.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo=\r\n"));
+
+        let request = head_rx.await.unwrap();
+        assert!(
+            request.starts_with("get /hmr?token=1 http/1.1\r\n"),
+            "{request}"
+        );
+        assert!(request.contains("host: localhost:5173\r\n"));
+        assert!(
+            request.contains(
+                "sec-websocket-extensions: permessage-deflate; client_max_window_bits\r\n"
+            )
+        );
+
+        // A compressed text frame (RSV1 set) passes through untouched.
+        let frame = b"\xc1\x07\xf2\x48\xcd\xc9\xc9\x07\x00";
+        client.write_all(frame).await.unwrap();
+        let mut echoed = [0u8; 9];
+        client.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, frame);
+    }
 }
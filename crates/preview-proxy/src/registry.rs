@@ -0,0 +1,60 @@
+//! Ports of running dev servers, registered once they answer so previews can
+//! be opened by workspace instead of by guessing the port.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+pub struct DevServerPorts {
+    ports: Arc<RwLock<HashMap<Uuid, u16>>>,
+}
+
+impl DevServerPorts {
+    /// Point `workspace_id`'s preview at `port`, replacing any earlier port.
+    pub fn register(&self, workspace_id: Uuid, port: u16) {
+        self.ports.write().unwrap().insert(workspace_id, port);
+    }
+
+    pub fn unregister(&self, workspace_id: Uuid) {
+        self.ports.write().unwrap().remove(&workspace_id);
+    }
+
+    pub fn port(&self, workspace_id: Uuid) -> Option<u16> {
+        self.ports.read().unwrap().get(&workspace_id).copied()
+    }
+
+    /// The workspace whose dev server is registered on `port`.
+    pub fn workspace(&self, port: u16) -> Option<Uuid> {
+        self.ports
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|(workspace_id, registered)| (*registered == port).then_some(*workspace_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_is_shared_between_clones() {
+        let ports = DevServerPorts::default();
+        let clone = ports.clone();
+        let workspace_id = Uuid::new_v4();
+
+        clone.register(workspace_id, 5173);
+        assert_eq!(ports.port(workspace_id), Some(5173));
+        assert_eq!(ports.workspace(5173), Some(workspace_id));
+
+        clone.register(workspace_id, 3000);
+        assert_eq!(ports.workspace(5173), None);
+
+        clone.unregister(workspace_id);
+        assert_eq!(ports.port(workspace_id), None);
+    }
+}
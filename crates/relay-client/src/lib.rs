@@ -150,6 +150,8 @@ impl RelayApiClient {
             timestamp,
             nonce,
             signature_b64,
+            encryption_public_key_b64: None,
+            encryption_key_signature_b64: None,
         };
 
         self.post_session_api(
@@ -217,6 +219,8 @@ impl RelayApiClient {
                     client_device: "desktop".to_string(),
                     public_key_b64: client_public_key_b64,
                     client_proof_b64,
+                    encryption_public_key_b64: None,
+                    encryption_key_signature_b64: None,
                 },
             )
             .await?;
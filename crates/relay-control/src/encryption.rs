@@ -0,0 +1,135 @@
+//! End-to-end encryption of relayed request bodies and WebSocket frames.
+//!
+//! A client opts in by sending an X25519 public key, signed with its Ed25519
+//! key, when it finishes pairing or refreshes its signing session. The server
+//! answers with an ephemeral X25519 key signed with the server key, so the
+//! relay in between can neither read the traffic nor substitute either key.
+//! Both sides derive a ChaCha20-Poly1305 key for the signing session from the
+//! X25519 shared secret with HKDF-SHA256.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use chacha20poly1305::{
+    AeadCore, ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use sha2::Sha256;
+use uuid::Uuid;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+/// Set to `1` on requests and responses whose body is encrypted.
+pub const ENCRYPTED_BODY_HEADER: &str = "x-vk-enc";
+
+const NONCE_LEN: usize = 12;
+const HKDF_SALT: &[u8] = b"vk-relay-e2e-v1";
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("invalid X25519 public key")]
+    InvalidPublicKey,
+    #[error("failed to encrypt relay payload")]
+    Encrypt,
+    #[error("failed to decrypt relay payload")]
+    Decrypt,
+}
+
+/// ChaCha20-Poly1305 key of one signing session. Ciphertexts are the random
+/// 96-bit nonce followed by the sealed payload.
+#[derive(Clone)]
+pub struct SessionCipher {
+    key: Key,
+}
+
+impl SessionCipher {
+    pub fn from_key_bytes(key: [u8; 32]) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub(crate) fn key_bytes(&self) -> [u8; 32] {
+        self.key.into()
+    }
+
+    /// Derive the session key from an X25519 shared secret.
+    pub fn derive(shared_secret: &[u8; 32], signing_session_id: Uuid) -> Self {
+        let hkdf = Hkdf::<Sha256>::new(Some(HKDF_SALT), shared_secret);
+        let mut key = [0u8; 32];
+        hkdf.expand(signing_session_id.as_bytes(), &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self::from_key_bytes(key)
+    }
+
+    pub fn encrypt(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let cipher = ChaCha20Poly1305::new(&self.key);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| EncryptionError::Encrypt)?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + sealed.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    pub fn decrypt(&self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if ciphertext.len() < NONCE_LEN {
+            return Err(EncryptionError::Decrypt);
+        }
+        let (nonce, sealed) = ciphertext.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.key)
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad })
+            .map_err(|_| EncryptionError::Decrypt)
+    }
+}
+
+/// Message the client signs with its Ed25519 key to vouch for its X25519 key.
+pub fn client_encryption_key_message(client_id: Uuid, public_key_b64: &str) -> String {
+    format!("v1|relay-e2e|client|{client_id}|{public_key_b64}")
+}
+
+/// Message the server signs with its Ed25519 key to vouch for its X25519 key.
+pub fn server_encryption_key_message(signing_session_id: Uuid, public_key_b64: &str) -> String {
+    format!("v1|relay-e2e|server|{signing_session_id}|{public_key_b64}")
+}
+
+/// Associated data binding a request or response body to its exchange.
+pub fn body_aad(signing_session_id: Uuid, request_nonce: Uuid, direction: &str) -> Vec<u8> {
+    format!("v1|{signing_session_id}|{request_nonce}|{direction}").into_bytes()
+}
+
+pub(crate) struct ServerKeyAgreement {
+    pub public_key_b64: String,
+    pub cipher: SessionCipher,
+}
+
+/// Answer a client's X25519 key with an ephemeral server key.
+pub(crate) fn agree_server_key(
+    client_public_key_b64: &str,
+    signing_session_id: Uuid,
+) -> Result<ServerKeyAgreement, EncryptionError> {
+    let client_public_key: [u8; 32] = BASE64_STANDARD
+        .decode(client_public_key_b64)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EncryptionError::InvalidPublicKey)?;
+
+    let secret = EphemeralSecret::random_from_rng(OsRng);
+    let public_key = PublicKey::from(&secret);
+    let shared_secret = secret.diffie_hellman(&PublicKey::from(client_public_key));
+    // Low-order client keys would yield a predictable secret.
+    if !shared_secret.was_contributory() {
+        return Err(EncryptionError::InvalidPublicKey);
+    }
+
+    Ok(ServerKeyAgreement {
+        public_key_b64: BASE64_STANDARD.encode(public_key.as_bytes()),
+        cipher: SessionCipher::derive(shared_secret.as_bytes(), signing_session_id),
+    })
+}
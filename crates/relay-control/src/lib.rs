@@ -1,5 +1,10 @@
+pub mod encryption;
+pub mod quality;
+mod session_store;
 pub mod signing;
 
+use quality::RelayQualityTracker;
+pub use session_store::SessionKeySealer;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
@@ -10,6 +15,7 @@ use tokio_util::sync::CancellationToken;
 pub struct RelayControl {
     /// Token used to cancel the current relay connection
     shutdown: RwLock<Option<CancellationToken>>,
+    quality: RelayQualityTracker,
 }
 
 impl Default for RelayControl {
@@ -22,6 +28,7 @@ impl RelayControl {
     pub fn new() -> Self {
         Self {
             shutdown: RwLock::new(None),
+            quality: RelayQualityTracker::default(),
         }
     }
 
@@ -37,6 +44,21 @@ impl RelayControl {
         token
     }
 
+    /// Whether a relay session has been started and not stopped. The session
+    /// may still be reconnecting.
+    pub async fn is_active(&self) -> bool {
+        self.shutdown
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|token| !token.is_cancelled())
+    }
+
+    /// Connection quality of the current relay session.
+    pub fn quality(&self) -> &RelayQualityTracker {
+        &self.quality
+    }
+
     /// Cancel the current relay session if one is running.
     pub async fn stop(&self) {
         let mut guard = self.shutdown.write().await;
@@ -0,0 +1,195 @@
+//! Connection quality tracking for the relay tunnel.
+//!
+//! The relay client reports keepalive probe results and (dis)connects here;
+//! the status endpoint turns them into a [`RelayConnectionStatus`] so the UI
+//! can tell network lag apart from a slow agent.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use relay_types::{RelayConnectionQuality, RelayConnectionStatus, RelayTunnelTransport};
+
+/// Number of recent probes latency and loss are computed over.
+const PROBE_WINDOW: usize = 20;
+const RECONNECT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+const DEGRADED_LATENCY_MS: u32 = 250;
+const POOR_LATENCY_MS: u32 = 600;
+const DEGRADED_PROBE_LOSS: f32 = 0.05;
+const POOR_PROBE_LOSS: f32 = 0.2;
+const DEGRADED_RECONNECTS_PER_HOUR: u32 = 2;
+const POOR_RECONNECTS_PER_HOUR: u32 = 6;
+
+#[derive(Default)]
+pub struct RelayQualityTracker {
+    state: Mutex<QualityState>,
+}
+
+#[derive(Default)]
+struct QualityState {
+    transport: Option<RelayTunnelTransport>,
+    connected_since: Option<DateTime<Utc>>,
+    /// Round trip of each recent probe, `None` for probes that got no answer.
+    probes: VecDeque<Option<Duration>>,
+    keepalive_interval: Duration,
+    disconnects: VecDeque<Instant>,
+    last_error: Option<String>,
+}
+
+impl RelayQualityTracker {
+    pub fn connected(&self, transport: RelayTunnelTransport) {
+        let mut state = self.lock();
+        state.transport = Some(transport);
+        state.connected_since = Some(Utc::now());
+        state.probes.clear();
+    }
+
+    /// Record that the control channel dropped or could not be established.
+    pub fn disconnected(&self, error: Option<String>) {
+        let mut state = self.lock();
+        if state.connected_since.take().is_some() {
+            state.disconnects.push_back(Instant::now());
+        }
+        state.transport = None;
+        if error.is_some() {
+            state.last_error = error;
+        }
+    }
+
+    pub fn probe_completed(&self, rtt: Option<Duration>) {
+        let mut state = self.lock();
+        if state.probes.len() == PROBE_WINDOW {
+            state.probes.pop_front();
+        }
+        state.probes.push_back(rtt);
+    }
+
+    pub fn keepalive_interval_changed(&self, interval: Duration) {
+        self.lock().keepalive_interval = interval;
+    }
+
+    /// Summarize the connection. `active` is whether a relay session has been
+    /// started, see [`crate::RelayControl::is_active`].
+    pub fn status(&self, active: bool) -> RelayConnectionStatus {
+        let mut state = self.lock();
+        while state
+            .disconnects
+            .front()
+            .is_some_and(|at| at.elapsed() > RECONNECT_WINDOW)
+        {
+            state.disconnects.pop_front();
+        }
+
+        let connected = active && state.connected_since.is_some();
+        let answered: Vec<u32> = state.probes.iter().flatten().map(millis).collect();
+        let probe_loss = if state.probes.is_empty() {
+            0.0
+        } else {
+            (state.probes.len() - answered.len()) as f32 / state.probes.len() as f32
+        };
+        let avg_latency_ms =
+            (!answered.is_empty()).then(|| answered.iter().sum::<u32>() / answered.len() as u32);
+        let jitter_ms = (answered.len() > 1).then(|| {
+            let total: u32 = answered.windows(2).map(|w| w[0].abs_diff(w[1])).sum();
+            total / (answered.len() - 1) as u32
+        });
+        let reconnects_last_hour = state.disconnects.len() as u32;
+
+        RelayConnectionStatus {
+            active,
+            connected,
+            quality: rate(connected, avg_latency_ms, probe_loss, reconnects_last_hour),
+            transport: state.transport.filter(|_| connected),
+            connected_since: state.connected_since.filter(|_| connected),
+            latency_ms: state.probes.iter().rev().flatten().next().map(millis),
+            avg_latency_ms,
+            jitter_ms,
+            probe_loss,
+            keepalive_interval_secs: state.keepalive_interval.as_secs() as u32,
+            reconnects_last_hour,
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QualityState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn millis(duration: &Duration) -> u32 {
+    duration.as_millis().min(u32::MAX as u128) as u32
+}
+
+fn rate(
+    connected: bool,
+    avg_latency_ms: Option<u32>,
+    probe_loss: f32,
+    reconnects_last_hour: u32,
+) -> RelayConnectionQuality {
+    if !connected {
+        return RelayConnectionQuality::Disconnected;
+    }
+    let latency = avg_latency_ms.unwrap_or(0);
+    if latency >= POOR_LATENCY_MS
+        || probe_loss >= POOR_PROBE_LOSS
+        || reconnects_last_hour >= POOR_RECONNECTS_PER_HOUR
+    {
+        RelayConnectionQuality::Poor
+    } else if latency >= DEGRADED_LATENCY_MS
+        || probe_loss >= DEGRADED_PROBE_LOSS
+        || reconnects_last_hour >= DEGRADED_RECONNECTS_PER_HOUR
+    {
+        RelayConnectionQuality::Degraded
+    } else {
+        RelayConnectionQuality::Good
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_latency_jitter_and_loss() {
+        let tracker = RelayQualityTracker::default();
+        tracker.connected(RelayTunnelTransport::WebSocket);
+        for rtt in [Some(40), Some(60), None, Some(50)] {
+            tracker.probe_completed(rtt.map(Duration::from_millis));
+        }
+
+        let status = tracker.status(true);
+        assert!(status.connected);
+        assert_eq!(status.latency_ms, Some(50));
+        assert_eq!(status.avg_latency_ms, Some(50));
+        assert_eq!(status.jitter_ms, Some(15));
+        assert_eq!(status.probe_loss, 0.25);
+        assert_eq!(status.quality, RelayConnectionQuality::Poor);
+    }
+
+    #[test]
+    fn counts_reconnects_and_reports_disconnected() {
+        let tracker = RelayQualityTracker::default();
+        for _ in 0..DEGRADED_RECONNECTS_PER_HOUR {
+            tracker.connected(RelayTunnelTransport::LongPoll);
+            tracker.disconnected(Some("connection reset".to_string()));
+        }
+        let status = tracker.status(true);
+        assert_eq!(status.quality, RelayConnectionQuality::Disconnected);
+        assert_eq!(status.transport, None);
+        assert_eq!(status.last_error.as_deref(), Some("connection reset"));
+
+        tracker.connected(RelayTunnelTransport::LongPoll);
+        tracker.probe_completed(Some(Duration::from_millis(20)));
+        let status = tracker.status(true);
+        assert_eq!(status.reconnects_last_hour, DEGRADED_RECONNECTS_PER_HOUR);
+        assert_eq!(status.quality, RelayConnectionQuality::Degraded);
+        assert_eq!(
+            tracker.status(false).quality,
+            RelayConnectionQuality::Disconnected
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! On-disk snapshot of server-side signing sessions, so paired browsers
+//! keep their sessions across a server restart instead of re-running the
+//! refresh flow.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Encrypts the end-to-end session keys written to the sessions file, e.g.
+/// with the config secrets key kept in the OS keychain.
+pub trait SessionKeySealer: Send + Sync {
+    fn seal(&self, plaintext: &str) -> anyhow::Result<String>;
+    fn open(&self, sealed: &str) -> anyhow::Result<String>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PersistedSession {
+    pub id: Uuid,
+    pub peer_public_key_b64: String,
+    /// Unix seconds.
+    pub created_at: i64,
+    /// Unix seconds.
+    pub last_used_at: i64,
+    /// End-to-end session key, if the client negotiated encryption, sealed
+    /// with the store's [`SessionKeySealer`]. Files written before keys were
+    /// sealed hold them in plaintext under the old name; those fail to open.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        alias = "encryption_key_b64"
+    )]
+    pub sealed_encryption_key: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionsFile {
+    sessions: Vec<PersistedSession>,
+}
+
+pub(crate) struct SessionStore {
+    path: PathBuf,
+    /// Serializes writers so an older snapshot never replaces a newer one.
+    write_lock: tokio::sync::Mutex<()>,
+    key_sealer: Option<Arc<dyn SessionKeySealer>>,
+}
+
+impl SessionStore {
+    pub fn new(path: PathBuf, key_sealer: Option<Arc<dyn SessionKeySealer>>) -> Self {
+        Self {
+            path,
+            write_lock: tokio::sync::Mutex::new(()),
+            key_sealer,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Seal a session key for the sessions file. `None` without a sealer or
+    /// if sealing fails, in which case the session isn't persisted.
+    pub fn seal_key(&self, key: &[u8; 32]) -> Option<String> {
+        self.key_sealer
+            .as_ref()?
+            .seal(&BASE64_STANDARD.encode(key))
+            .inspect_err(|error| tracing::warn!(?error, "failed to seal relay session key"))
+            .ok()
+    }
+
+    pub fn open_key(&self, sealed: &str) -> Option<[u8; 32]> {
+        let key_b64 = self
+            .key_sealer
+            .as_ref()?
+            .open(sealed)
+            .inspect_err(|error| tracing::warn!(?error, "failed to open relay session key"))
+            .ok()?;
+        BASE64_STANDARD.decode(key_b64).ok()?.try_into().ok()
+    }
+
+    pub fn load(&self) -> io::Result<Vec<PersistedSession>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        if contents.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let file: SessionsFile = serde_json::from_str(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(file.sessions)
+    }
+
+    /// Write the sessions `snapshot` resolves to, atomically. The snapshot
+    /// is only awaited under the write lock so concurrent saves land in order.
+    pub async fn save(
+        &self,
+        snapshot: impl Future<Output = Vec<PersistedSession>>,
+    ) -> io::Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let file = SessionsFile {
+            sessions: snapshot.await,
+        };
+        let serialized = serde_json::to_vec_pretty(&file)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serialized).await?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600)).await?;
+        }
+
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     fs, io,
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
@@ -13,6 +13,11 @@ use sha2::{Digest, Sha256};
 use tokio::sync::{RwLock, RwLockMappedWriteGuard, RwLockWriteGuard};
 use uuid::Uuid;
 
+use crate::{
+    encryption::{EncryptionError, SessionCipher, agree_server_key, server_encryption_key_message},
+    session_store::{PersistedSession, SessionKeySealer, SessionStore},
+};
+
 // ---------------------------------------------------------------------------
 // Request signing — used by local proxy and tunnel to sign outbound requests
 // ---------------------------------------------------------------------------
@@ -97,6 +102,20 @@ struct RelaySigningSession {
     created_at: Instant,
     last_used_at: Instant,
     seen_nonces: HashMap<Uuid, Instant>,
+    /// Set on sessions restored from disk, whose seen nonces were lost:
+    /// requests signed before the restore could be replays.
+    not_before: Option<i64>,
+    /// Set when the client negotiated end-to-end encryption.
+    cipher: Option<SessionCipher>,
+}
+
+/// Server half of an encrypted session's key agreement, for the client.
+#[derive(Debug, Clone)]
+pub struct EncryptedSessionKey {
+    pub signing_session_id: Uuid,
+    pub encryption_public_key_b64: String,
+    /// Server signature over the encryption key.
+    pub encryption_key_signature_b64: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -124,11 +143,15 @@ const RELAY_SIGNATURE_MAX_TIMESTAMP_DRIFT_SECS: i64 = 30;
 const RELAY_SIGNING_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
 const RELAY_SIGNING_SESSION_IDLE_TTL: Duration = Duration::from_secs(15 * 60);
 const RELAY_NONCE_TTL: Duration = Duration::from_secs(2 * 60);
+/// How stale the persisted `last_used_at` of sessions may get.
+const RELAY_SESSION_PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Clone)]
 pub struct RelaySigningService {
     sessions: Arc<RwLock<HashMap<Uuid, RelaySigningSession>>>,
     server_signing_key: Arc<SigningKey>,
+    session_store: Option<Arc<SessionStore>>,
+    last_persisted_at: Arc<std::sync::Mutex<Instant>>,
 }
 
 impl RelaySigningService {
@@ -136,9 +159,42 @@ impl RelaySigningService {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             server_signing_key: Arc::new(server_signing_key),
+            session_store: None,
+            last_persisted_at: Arc::new(std::sync::Mutex::new(Instant::now())),
         }
     }
 
+    /// Keep signing sessions in `sessions_path` and restore the ones that
+    /// have not expired and whose peer is still one of `trusted_keys`, so a
+    /// client unpaired while the server was down can't resume. Keys of
+    /// end-to-end encrypted sessions are sealed with `key_sealer`; without
+    /// one, those sessions aren't persisted and their clients refresh after a
+    /// restart. Call once at startup, before any session exists.
+    pub fn with_session_persistence(
+        mut self,
+        sessions_path: PathBuf,
+        trusted_keys: &[VerifyingKey],
+        key_sealer: Option<Arc<dyn SessionKeySealer>>,
+    ) -> Self {
+        let store = SessionStore::new(sessions_path, key_sealer);
+        match store.load() {
+            Ok(persisted) => {
+                let restored = restore_sessions(persisted, trusted_keys, &store);
+                tracing::info!(count = restored.len(), "restored relay signing sessions");
+                self.sessions = Arc::new(RwLock::new(restored));
+            }
+            Err(error) => {
+                tracing::warn!(
+                    ?error,
+                    path = %store.path().display(),
+                    "failed to load relay signing sessions; starting without them"
+                );
+            }
+        }
+        self.session_store = Some(Arc::new(store));
+        self
+    }
+
     pub fn load_or_generate(key_path: &Path) -> io::Result<Self> {
         let key = if let Ok(bytes) = fs::read(key_path) {
             let arr: [u8; 32] = bytes.try_into().map_err(|_| {
@@ -212,6 +268,39 @@ impl RelaySigningService {
     /// On the server this is called via `create_session`; on the client
     /// it is called after receiving a session ID from the server.
     pub async fn register_session(&self, signing_session_id: Uuid, peer_public_key: VerifyingKey) {
+        self.insert_session(signing_session_id, peer_public_key, None)
+            .await;
+    }
+
+    /// Create a session whose traffic is encrypted end to end, answering the
+    /// client's X25519 key. The client's signature over that key must have
+    /// been checked already.
+    pub async fn create_encrypted_session(
+        &self,
+        peer_public_key: VerifyingKey,
+        client_encryption_key_b64: &str,
+    ) -> Result<EncryptedSessionKey, EncryptionError> {
+        let signing_session_id = Uuid::new_v4();
+        let agreement = agree_server_key(client_encryption_key_b64, signing_session_id)?;
+        let signature = self.sign_bytes(
+            server_encryption_key_message(signing_session_id, &agreement.public_key_b64).as_bytes(),
+        );
+        self.insert_session(signing_session_id, peer_public_key, Some(agreement.cipher))
+            .await;
+
+        Ok(EncryptedSessionKey {
+            signing_session_id,
+            encryption_public_key_b64: agreement.public_key_b64,
+            encryption_key_signature_b64: BASE64_STANDARD.encode(signature.to_bytes()),
+        })
+    }
+
+    async fn insert_session(
+        &self,
+        signing_session_id: Uuid,
+        peer_public_key: VerifyingKey,
+        cipher: Option<SessionCipher>,
+    ) {
         let now = Instant::now();
         self.sessions.write().await.insert(
             signing_session_id,
@@ -220,8 +309,26 @@ impl RelaySigningService {
                 created_at: now,
                 last_used_at: now,
                 seen_nonces: HashMap::new(),
+                not_before: None,
+                cipher,
             },
         );
+        self.persist_sessions().await;
+    }
+
+    /// The end-to-end cipher of a valid signing session, if it negotiated one.
+    pub async fn session_cipher(&self, signing_session_id: Uuid) -> Option<SessionCipher> {
+        let sessions = self.sessions.read().await;
+        let now = Instant::now();
+        sessions.get(&signing_session_id).and_then(|session| {
+            if now.duration_since(session.created_at) <= RELAY_SIGNING_SESSION_TTL
+                && now.duration_since(session.last_used_at) <= RELAY_SIGNING_SESSION_IDLE_TTL
+            {
+                session.cipher.clone()
+            } else {
+                None
+            }
+        })
     }
 
     /// Verify an HTTP request signature against a signing session.
@@ -235,29 +342,37 @@ impl RelaySigningService {
         validate_timestamp(request_signature.timestamp)?;
 
         let signature = parse_signature_b64(&request_signature.signature_b64)?;
-        let mut session = self
-            .get_valid_session(request_signature.signing_session_id)
-            .await?;
-
-        session
-            .seen_nonces
-            .retain(|_, seen_at| Instant::now().duration_since(*seen_at) <= RELAY_NONCE_TTL);
-        if session.seen_nonces.contains_key(&request_signature.nonce) {
-            return Err(RelaySignatureValidationError::ReplayNonce);
-        }
-
-        let message =
-            build_request_signing_message(request_signature, method, path_and_query, body);
-        session
-            .peer_public_key
-            .verify(message.as_bytes(), &signature)
-            .map_err(|_| RelaySignatureValidationError::InvalidSignature)?;
+        {
+            let mut session = self
+                .get_valid_session(request_signature.signing_session_id)
+                .await?;
+            session
+                .seen_nonces
+                .retain(|_, seen_at| Instant::now().duration_since(*seen_at) <= RELAY_NONCE_TTL);
+            if session.seen_nonces.contains_key(&request_signature.nonce)
+                || session
+                    .not_before
+                    .is_some_and(|not_before| request_signature.timestamp < not_before)
+            {
+                return Err(RelaySignatureValidationError::ReplayNonce);
+            }
 
-        session
-            .seen_nonces
-            .insert(request_signature.nonce, Instant::now());
-        session.last_used_at = Instant::now();
+            let message =
+                build_request_signing_message(request_signature, method, path_and_query, body);
+            session
+                .peer_public_key
+                .verify(message.as_bytes(), &signature)
+                .map_err(|_| RelaySignatureValidationError::InvalidSignature)?;
+
+            session
+                .seen_nonces
+                .insert(request_signature.nonce, Instant::now());
+            session.last_used_at = Instant::now();
+        }
 
+        if self.persist_due() {
+            self.persist_sessions().await;
+        }
         Ok(())
     }
 
@@ -288,6 +403,70 @@ impl RelaySigningService {
         })
     }
 
+    /// Drop every signing session established with `peer_public_key`, e.g.
+    /// after the client rotated away from it. Returns how many were dropped.
+    pub async fn revoke_sessions_for_key(&self, peer_public_key: &VerifyingKey) -> usize {
+        let mut sessions = self.sessions.write().await;
+        let previous_len = sessions.len();
+        sessions.retain(|_, session| session.peer_public_key != *peer_public_key);
+        let revoked = previous_len - sessions.len();
+        drop(sessions);
+
+        if revoked > 0 {
+            self.persist_sessions().await;
+        }
+        revoked
+    }
+
+    fn persist_due(&self) -> bool {
+        self.session_store.is_some()
+            && self.last_persisted_at.lock().unwrap().elapsed() >= RELAY_SESSION_PERSIST_INTERVAL
+    }
+
+    /// Write live sessions to the session store, if persistence is enabled.
+    async fn persist_sessions(&self) {
+        let Some(store) = &self.session_store else {
+            return;
+        };
+        *self.last_persisted_at.lock().unwrap() = Instant::now();
+
+        let snapshot = async {
+            let now = Instant::now();
+            let now_unix = unix_now();
+            self.sessions
+                .read()
+                .await
+                .iter()
+                .filter(|(_, session)| {
+                    now.duration_since(session.created_at) <= RELAY_SIGNING_SESSION_TTL
+                        && now.duration_since(session.last_used_at)
+                            <= RELAY_SIGNING_SESSION_IDLE_TTL
+                })
+                .filter_map(|(id, session)| {
+                    let sealed_encryption_key = match &session.cipher {
+                        Some(cipher) => Some(store.seal_key(&cipher.key_bytes())?),
+                        None => None,
+                    };
+                    Some(PersistedSession {
+                        id: *id,
+                        peer_public_key_b64: BASE64_STANDARD
+                            .encode(session.peer_public_key.as_bytes()),
+                        created_at: now_unix - session.created_at.elapsed().as_secs() as i64,
+                        last_used_at: now_unix - session.last_used_at.elapsed().as_secs() as i64,
+                        sealed_encryption_key,
+                    })
+                })
+                .collect()
+        };
+        if let Err(error) = store.save(snapshot).await {
+            tracing::warn!(
+                ?error,
+                path = %store.path().display(),
+                "failed to persist relay signing sessions"
+            );
+        }
+    }
+
     async fn get_valid_session(
         &self,
         signing_session_id: Uuid,
@@ -304,6 +483,64 @@ impl RelaySigningService {
     }
 }
 
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Rebuild unexpired sessions of trusted peers from their persisted form.
+fn restore_sessions(
+    persisted: Vec<PersistedSession>,
+    trusted_keys: &[VerifyingKey],
+    store: &SessionStore,
+) -> HashMap<Uuid, RelaySigningSession> {
+    let now = Instant::now();
+    let now_unix = unix_now();
+    let instant_at = |unix: i64| {
+        let age = Duration::from_secs(now_unix.saturating_sub(unix).max(0) as u64);
+        (age, now.checked_sub(age).unwrap_or(now))
+    };
+
+    persisted
+        .into_iter()
+        .filter_map(|session| {
+            let (age, created_at) = instant_at(session.created_at);
+            let (idle, last_used_at) = instant_at(session.last_used_at);
+            if age > RELAY_SIGNING_SESSION_TTL || idle > RELAY_SIGNING_SESSION_IDLE_TTL {
+                return None;
+            }
+            let key_bytes: [u8; 32] = BASE64_STANDARD
+                .decode(&session.peer_public_key_b64)
+                .ok()?
+                .try_into()
+                .ok()?;
+            let peer_public_key = VerifyingKey::from_bytes(&key_bytes).ok()?;
+            if !trusted_keys.contains(&peer_public_key) {
+                return None;
+            }
+            // A session whose key can't be restored must not silently fall
+            // back to plaintext.
+            let cipher = match &session.sealed_encryption_key {
+                Some(sealed) => Some(SessionCipher::from_key_bytes(store.open_key(sealed)?)),
+                None => None,
+            };
+            Some((
+                session.id,
+                RelaySigningSession {
+                    peer_public_key,
+                    created_at,
+                    last_used_at,
+                    seen_nonces: HashMap::new(),
+                    not_before: Some(now_unix),
+                    cipher,
+                },
+            ))
+        })
+        .collect()
+}
+
 fn validate_timestamp(timestamp: i64) -> Result<(), RelaySignatureValidationError> {
     let now_secs = i64::try_from(
         SystemTime::now()
@@ -326,3 +563,199 @@ fn parse_signature_b64(signature_b64: &str) -> Result<Signature, RelaySignatureV
         .map_err(|_| RelaySignatureValidationError::InvalidSignature)?;
     Signature::from_slice(&sig_bytes).map_err(|_| RelaySignatureValidationError::InvalidSignature)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METHOD: &str = "GET";
+    const PATH: &str = "/api/info";
+
+    fn client_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn temp_sessions_path() -> PathBuf {
+        std::env::temp_dir().join(format!("vk-relay-sessions-{}.json", Uuid::new_v4()))
+    }
+
+    /// Seals with a fixed session key, standing in for the config secrets
+    /// key.
+    struct TestSealer;
+
+    impl SessionKeySealer for TestSealer {
+        fn seal(&self, plaintext: &str) -> anyhow::Result<String> {
+            let sealed =
+                SessionCipher::from_key_bytes([9; 32]).encrypt(b"", plaintext.as_bytes())?;
+            Ok(format!("sealed:{}", BASE64_STANDARD.encode(sealed)))
+        }
+
+        fn open(&self, sealed: &str) -> anyhow::Result<String> {
+            let sealed = sealed
+                .strip_prefix("sealed:")
+                .ok_or_else(|| anyhow::anyhow!("not sealed"))?;
+            let plaintext = SessionCipher::from_key_bytes([9; 32])
+                .decrypt(b"", &BASE64_STANDARD.decode(sealed)?)?;
+            Ok(String::from_utf8(plaintext)?)
+        }
+    }
+
+    /// A server started with the sessions file at `path`.
+    fn start_server(path: &Path, trusted_keys: &[VerifyingKey]) -> RelaySigningService {
+        start_server_with_sealer(path, trusted_keys, Some(Arc::new(TestSealer)))
+    }
+
+    fn start_server_with_sealer(
+        path: &Path,
+        trusted_keys: &[VerifyingKey],
+        key_sealer: Option<Arc<dyn SessionKeySealer>>,
+    ) -> RelaySigningService {
+        RelaySigningService::new(SigningKey::from_bytes(&[1; 32])).with_session_persistence(
+            path.to_path_buf(),
+            trusted_keys,
+            key_sealer,
+        )
+    }
+
+    fn client_encryption_key_b64() -> String {
+        let secret = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
+        BASE64_STANDARD.encode(x25519_dalek::PublicKey::from(&secret).as_bytes())
+    }
+
+    async fn create_encrypted_session(
+        server: &RelaySigningService,
+        key: VerifyingKey,
+    ) -> (Uuid, SessionCipher) {
+        let session = server
+            .create_encrypted_session(key, &client_encryption_key_b64())
+            .await
+            .unwrap();
+        let cipher = server
+            .session_cipher(session.signing_session_id)
+            .await
+            .unwrap();
+        (session.signing_session_id, cipher)
+    }
+
+    async fn verify(
+        server: &RelaySigningService,
+        signing_session_id: Uuid,
+    ) -> Result<(), RelaySignatureValidationError> {
+        let signature =
+            build_request_signature(&client_key(), signing_session_id, METHOD, PATH, b"");
+        server.verify_request(&signature, METHOD, PATH, b"").await
+    }
+
+    #[tokio::test]
+    async fn sessions_of_trusted_clients_survive_a_restart() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let session_id = start_server(&path, &[key]).create_session(key).await;
+
+        let restarted = start_server(&path, &[key]);
+        assert_eq!(verify(&restarted, session_id).await, Ok(()));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn sessions_of_unpaired_clients_are_rejected_after_a_restart() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let session_id = start_server(&path, &[key]).create_session(key).await;
+
+        // The client was unpaired, so its key is no longer trusted.
+        let restarted = start_server(&path, &[]);
+        assert_eq!(
+            verify(&restarted, session_id).await,
+            Err(RelaySignatureValidationError::MissingSigningSession)
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn encrypted_sessions_survive_a_restart_with_their_key_sealed() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let (session_id, cipher) =
+            create_encrypted_session(&start_server(&path, &[key]), key).await;
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(&BASE64_STANDARD.encode(cipher.key_bytes())));
+        assert!(contents.contains("sealed:"));
+
+        let restarted = start_server(&path, &[key]);
+        assert_eq!(verify(&restarted, session_id).await, Ok(()));
+        let restored = restarted.session_cipher(session_id).await.unwrap();
+        let sealed = cipher.encrypt(b"aad", b"body").unwrap();
+        assert_eq!(restored.decrypt(b"aad", &sealed).unwrap(), b"body");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn encrypted_sessions_are_not_persisted_without_a_sealer() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let server = start_server_with_sealer(&path, &[key], None);
+        let plain_session_id = server.create_session(key).await;
+        let (encrypted_session_id, _) = create_encrypted_session(&server, key).await;
+
+        let restarted = start_server_with_sealer(&path, &[key], None);
+        assert_eq!(verify(&restarted, plain_session_id).await, Ok(()));
+        assert_eq!(
+            verify(&restarted, encrypted_session_id).await,
+            Err(RelaySignatureValidationError::MissingSigningSession)
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn plaintext_keys_from_older_files_are_discarded() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let session_id = Uuid::new_v4();
+        let now = unix_now();
+        fs::write(
+            &path,
+            serde_json::json!({
+                "sessions": [{
+                    "id": session_id,
+                    "peer_public_key_b64": BASE64_STANDARD.encode(key.as_bytes()),
+                    "created_at": now,
+                    "last_used_at": now,
+                    "encryption_key_b64": BASE64_STANDARD.encode([3u8; 32]),
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let restarted = start_server(&path, &[key]);
+        assert_eq!(
+            verify(&restarted, session_id).await,
+            Err(RelaySignatureValidationError::MissingSigningSession)
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn revoked_sessions_stay_revoked_after_a_restart() {
+        let path = temp_sessions_path();
+        let key = client_key().verifying_key();
+        let server = start_server(&path, &[key]);
+        let session_id = server.create_session(key).await;
+        assert_eq!(server.revoke_sessions_for_key(&key).await, 1);
+
+        let restarted = start_server(&path, &[key]);
+        assert_eq!(
+            verify(&restarted, session_id).await,
+            Err(RelaySignatureValidationError::MissingSigningSession)
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -14,7 +14,10 @@ use relay_ws::SignedTungsteniteSocket;
 use remote_info::RemoteInfo;
 use serde::{Deserialize, Serialize};
 use services::services::remote_client::{RemoteClient, RemoteClientError};
-use tokio::sync::RwLock;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::RwLock,
+};
 use tokio_util::sync::CancellationToken;
 use trusted_key_auth::trusted_keys::parse_public_key_base64;
 use utils::{assets::relay_host_credentials_path, response::ApiResponse};
@@ -270,15 +273,19 @@ impl ProxiedWsConnection {
         Ok(())
     }
 
-    pub async fn bridge_tcp(self, mut tcp_stream: tokio::net::TcpStream) -> Result<(), io::Error> {
+    /// Carry raw bytes between `stream` and the upstream socket.
+    pub async fn bridge_io<T>(self, mut stream: T) -> Result<(), io::Error>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
         match self.upstream {
             UpstreamWs::Relay(socket) => {
                 let mut ws_io = tungstenite_ws_stream_io(socket);
-                tokio::io::copy_bidirectional(&mut tcp_stream, &mut ws_io).await?;
+                tokio::io::copy_bidirectional(&mut stream, &mut ws_io).await?;
             }
-            UpstreamWs::WebRtc(stream) => {
-                let mut ws_io = tungstenite_ws_stream_io(stream);
-                tokio::io::copy_bidirectional(&mut tcp_stream, &mut ws_io).await?;
+            UpstreamWs::WebRtc(stream_ws) => {
+                let mut ws_io = tungstenite_ws_stream_io(stream_ws);
+                tokio::io::copy_bidirectional(&mut stream, &mut ws_io).await?;
             }
         }
 
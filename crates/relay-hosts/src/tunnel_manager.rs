@@ -101,7 +101,7 @@ async fn run_tunnel_listener(
                         tokio::spawn(async move {
                             match relay_host.proxy_ws("/api/ssh-session", None).await {
                                 Ok(upstream_ws) => {
-                                    if let Err(error) = upstream_ws.bridge_tcp(tcp_stream).await {
+                                    if let Err(error) = upstream_ws.bridge_io(tcp_stream).await {
                                         tracing::debug!(?error, "SSH tunnel bridge ended");
                                     }
                                 }
@@ -1,4 +1,9 @@
-use std::{convert::Infallible, net::SocketAddr};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Context as _;
 use axum::body::Body;
@@ -9,25 +14,137 @@ use hyper::{
     server::conn::http1 as server_http1, service::service_fn, upgrade,
 };
 use hyper_util::rt::TokioIo;
-use tokio::net::TcpStream;
-use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::client::IntoClientRequest};
 use tokio_util::sync::CancellationToken;
-use tokio_yamux::Session;
+use tokio_yamux::{Control, Session};
 use ws_bridge::tungstenite_ws_stream_io;
 
-use crate::{tls::ws_connector, yamux_config};
+use crate::{long_poll::connect_long_poll, tls::ws_connector, yamux_config};
 
 pub struct RelayClientConfig {
     pub ws_url: String,
+    /// URL that opens an HTTP long-poll session, used when WebSocket
+    /// handshakes keep failing.
+    pub long_poll_url: String,
     pub bearer_token: String,
     pub local_addr: SocketAddr,
     pub shutdown: CancellationToken,
+    pub observer: Arc<dyn ConnectionObserver>,
+}
+
+/// Receives control channel health events, e.g. to surface connection quality.
+pub trait ConnectionObserver: Send + Sync {
+    fn connected(&self, transport: RelayTransport);
+    /// Round trip of a keepalive probe, `None` when it got no answer.
+    fn probe_completed(&self, rtt: Option<Duration>);
+    fn keepalive_interval_changed(&self, interval: Duration);
+}
+
+/// Byte transport carrying the yamux control channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayTransport {
+    WebSocket,
+    LongPoll,
+}
+
+/// Consecutive WebSocket handshake failures before falling back to long-polling.
+const WS_FAILURES_BEFORE_FALLBACK: u32 = 3;
+/// How long to stay on long-polling before trying WebSocket again.
+const WS_RETRY_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+const PROBE_MIN_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_MAX_INTERVAL: Duration = Duration::from_secs(60);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Unanswered probes in a row after which the control channel is considered dead.
+const MAX_LOST_PROBES: u32 = 3;
+
+/// Picks the transport for each connection attempt.
+///
+/// Starts on WebSocket and switches to long-polling after repeated handshake
+/// failures. WebSocket gets one more attempt every [`WS_RETRY_INTERVAL`]; a
+/// single failure then goes straight back to long-polling.
+#[derive(Debug, Default)]
+pub struct TransportNegotiator {
+    ws_failures: u32,
+    fallback_since: Option<Instant>,
+}
+
+impl TransportNegotiator {
+    pub fn transport(&mut self) -> RelayTransport {
+        match self.fallback_since {
+            Some(since) if since.elapsed() < WS_RETRY_INTERVAL => RelayTransport::LongPoll,
+            Some(_) => {
+                self.fallback_since = None;
+                RelayTransport::WebSocket
+            }
+            None => RelayTransport::WebSocket,
+        }
+    }
+
+    fn record_ws_handshake_failure(&mut self) {
+        self.ws_failures += 1;
+        if self.ws_failures >= WS_FAILURES_BEFORE_FALLBACK {
+            tracing::info!(
+                failures = self.ws_failures,
+                "Relay WebSocket handshake keeps failing; falling back to HTTP long-polling"
+            );
+            self.ws_failures = WS_FAILURES_BEFORE_FALLBACK - 1;
+            self.fallback_since = Some(Instant::now());
+        }
+    }
+
+    fn record_ws_connected(&mut self) {
+        self.ws_failures = 0;
+    }
 }
 
 /// Connects the relay client control channel and starts handling inbound streams.
 ///
-/// Returns when shutdown is requested or when the control channel disconnects/errors.
-pub async fn start_relay_client(config: RelayClientConfig) -> anyhow::Result<()> {
+/// The transport is chosen by `negotiator`, which is updated with the outcome
+/// of the handshake. Returns when shutdown is requested or when the control
+/// channel disconnects/errors.
+pub async fn start_relay_client(
+    config: RelayClientConfig,
+    negotiator: &mut TransportNegotiator,
+) -> anyhow::Result<()> {
+    match negotiator.transport() {
+        RelayTransport::WebSocket => {
+            let ws_stream = match connect_ws(&config).await {
+                Ok(ws_stream) => ws_stream,
+                Err(error) => {
+                    negotiator.record_ws_handshake_failure();
+                    return Err(error);
+                }
+            };
+            negotiator.record_ws_connected();
+            tracing::debug!("Relay control channel connected");
+            run_client_session(
+                tungstenite_ws_stream_io(ws_stream),
+                RelayTransport::WebSocket,
+                config,
+            )
+            .await
+        }
+        RelayTransport::LongPoll => {
+            let io = connect_long_poll(
+                &config.long_poll_url,
+                &config.bearer_token,
+                config.shutdown.clone(),
+            )
+            .await?;
+            tracing::debug!("Relay control channel connected over HTTP long-polling");
+            run_client_session(io, RelayTransport::LongPoll, config).await
+        }
+    }
+}
+
+async fn connect_ws(
+    config: &RelayClientConfig,
+) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
     let mut request = config
         .ws_url
         .clone()
@@ -44,15 +161,26 @@ pub async fn start_relay_client(config: RelayClientConfig) -> anyhow::Result<()>
         tokio_tungstenite::connect_async_tls_with_config(request, None, false, ws_connector())
             .await
             .context("Failed to connect relay control channel")?;
+    Ok(ws_stream)
+}
 
-    let ws_io = tungstenite_ws_stream_io(ws_stream);
-    let mut session = Session::new_client(ws_io, yamux_config());
+async fn run_client_session<S>(
+    io: S,
+    transport: RelayTransport,
+    config: RelayClientConfig,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut session = Session::new_client(io, yamux_config());
     let mut control = session.control();
 
-    tracing::debug!("Relay control channel connected");
-
     let shutdown = config.shutdown;
     let local_addr = config.local_addr;
+    config.observer.connected(transport);
+
+    let keepalive = keepalive(session.control(), config.observer);
+    tokio::pin!(keepalive);
 
     loop {
         tokio::select! {
@@ -60,6 +188,10 @@ pub async fn start_relay_client(config: RelayClientConfig) -> anyhow::Result<()>
                 control.close().await;
                 return Ok(());
             }
+            error = &mut keepalive => {
+                control.close().await;
+                return Err(error);
+            }
             inbound = session.next() => {
                 let stream = inbound
                     .ok_or_else(|| anyhow::anyhow!("Relay control channel closed"))?
@@ -75,6 +207,74 @@ pub async fn start_relay_client(config: RelayClientConfig) -> anyhow::Result<()>
     }
 }
 
+/// Probes the control channel until it stops answering.
+///
+/// The probe interval doubles after every answered probe, up to
+/// [`PROBE_MAX_INTERVAL`], and drops back to [`PROBE_MIN_INTERVAL`] after a
+/// lost one so a flaky link is detected quickly.
+async fn keepalive(mut control: Control, observer: Arc<dyn ConnectionObserver>) -> anyhow::Error {
+    let mut interval = PROBE_MIN_INTERVAL;
+    let mut lost = 0;
+    observer.keepalive_interval_changed(interval);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let next_interval = match probe(&mut control).await {
+            Ok(Some(rtt)) => {
+                lost = 0;
+                observer.probe_completed(Some(rtt));
+                interval.saturating_mul(2).min(PROBE_MAX_INTERVAL)
+            }
+            Ok(None) => {
+                tracing::debug!("Relay server does not answer probes; keepalive disabled");
+                return std::future::pending().await;
+            }
+            Err(error) => {
+                lost += 1;
+                observer.probe_completed(None);
+                tracing::debug!(?error, lost, "Relay keepalive probe lost");
+                if lost >= MAX_LOST_PROBES {
+                    return error.context("Relay control channel stopped answering probes");
+                }
+                PROBE_MIN_INTERVAL
+            }
+        };
+        if next_interval != interval {
+            interval = next_interval;
+            observer.keepalive_interval_changed(interval);
+        }
+    }
+}
+
+/// Sends one probe over a fresh yamux stream, which the relay server echoes.
+///
+/// Returns `None` when the server closes the stream without echoing, which
+/// relay servers predating probes do.
+async fn probe(control: &mut Control) -> anyhow::Result<Option<Duration>> {
+    let started = Instant::now();
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+
+    let exchange = async {
+        let mut stream = control.open_stream().await?;
+        stream.write_all(&nonce.to_be_bytes()).await?;
+        let mut echo = [0u8; 8];
+        match stream.read_exact(&mut echo).await {
+            Ok(_) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error.into()),
+        }
+        anyhow::ensure!(echo == nonce.to_be_bytes(), "Relay probe echo mismatch");
+        Ok(Some(started.elapsed()))
+    };
+
+    tokio::time::timeout(PROBE_TIMEOUT, exchange)
+        .await
+        .context("Relay probe timed out")?
+}
+
 async fn handle_inbound_stream(
     stream: tokio_yamux::StreamHandle,
     local_addr: SocketAddr,
@@ -167,3 +367,38 @@ fn simple_response(status: StatusCode, body: &'static str) -> Response<Body> {
         .body(Body::from(body))
         .unwrap_or_else(|_| Response::new(Body::from(body)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiator_falls_back_after_repeated_ws_failures() {
+        let mut negotiator = TransportNegotiator::default();
+        for _ in 0..WS_FAILURES_BEFORE_FALLBACK - 1 {
+            assert_eq!(negotiator.transport(), RelayTransport::WebSocket);
+            negotiator.record_ws_handshake_failure();
+        }
+        assert_eq!(negotiator.transport(), RelayTransport::WebSocket);
+        negotiator.record_ws_handshake_failure();
+        assert_eq!(negotiator.transport(), RelayTransport::LongPoll);
+    }
+
+    #[test]
+    fn negotiator_resets_on_ws_success_and_retries_ws_later() {
+        let mut negotiator = TransportNegotiator::default();
+        negotiator.record_ws_handshake_failure();
+        negotiator.record_ws_connected();
+        negotiator.record_ws_handshake_failure();
+        assert_eq!(negotiator.transport(), RelayTransport::WebSocket);
+
+        let mut negotiator = TransportNegotiator::default();
+        for _ in 0..WS_FAILURES_BEFORE_FALLBACK {
+            negotiator.record_ws_handshake_failure();
+        }
+        negotiator.fallback_since = Some(Instant::now() - WS_RETRY_INTERVAL);
+        assert_eq!(negotiator.transport(), RelayTransport::WebSocket);
+        negotiator.record_ws_handshake_failure();
+        assert_eq!(negotiator.transport(), RelayTransport::LongPoll);
+    }
+}
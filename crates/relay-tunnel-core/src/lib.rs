@@ -3,6 +3,7 @@ use std::time::Duration;
 use tokio_yamux::Config as YamuxConfig;
 
 pub mod client;
+pub mod long_poll;
 pub mod server;
 pub mod tls;
 
@@ -0,0 +1,578 @@
+//! HTTP long-polling transport for the relay control channel.
+//!
+//! Used when WebSocket upgrades never make it to the relay (corporate proxies
+//! and some firewalls strip the `Upgrade` header). The yamux byte stream is
+//! carried by plain HTTP/1.1 requests instead:
+//!
+//! - `POST {base}?{query}` opens a session and returns its ID as plain text.
+//! - `GET {base}/{id}` waits up to [`POLL_WAIT`] for server → client bytes.
+//!   It answers `200` with data, `204` when nothing arrived and `410` once
+//!   the session is closed.
+//! - `POST {base}/{id}` delivers client → server bytes.
+//! - `DELETE {base}/{id}` closes the session.
+//!
+//! Each direction uses its own keep-alive connection and issues requests one
+//! at a time, so bytes arrive in order without extra framing.
+
+use std::{
+    io,
+    sync::{Arc, Mutex as StdMutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use axum::body::{Body, Bytes};
+use http::{
+    Method, Request, StatusCode, Uri,
+    header::{AUTHORIZATION, HOST},
+};
+use hyper::client::conn::http1::{self as client_http1, SendRequest};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::ServerName;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadHalf, WriteHalf},
+    net::TcpStream,
+    sync::Mutex,
+};
+use tokio_rustls::TlsConnector;
+use tokio_util::sync::CancellationToken;
+
+use crate::tls::client_config;
+
+/// How long the server holds a `GET` poll open before answering `204`.
+pub const POLL_WAIT: Duration = Duration::from_secs(25);
+/// Sessions with no requests for this long are closed by the server.
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Upper bound on the bytes moved by a single request in either direction.
+const MAX_CHUNK_BYTES: usize = 256 * 1024;
+const DUPLEX_BUFFER_BYTES: usize = 2 * MAX_CHUNK_BYTES;
+/// Allowance on top of [`POLL_WAIT`] before the client gives up on a request.
+const REQUEST_GRACE: Duration = Duration::from_secs(15);
+
+/// Server side of one long-poll session.
+///
+/// [`LongPollSession::new`] also returns the byte stream the yamux session
+/// should run over; requests feed and drain it through `push` and `poll`.
+pub struct LongPollSession {
+    upstream: Mutex<WriteHalf<DuplexStream>>,
+    downstream: Mutex<ReadHalf<DuplexStream>>,
+    last_seen: StdMutex<Instant>,
+    closed: CancellationToken,
+}
+
+impl LongPollSession {
+    pub fn new() -> (Arc<Self>, DuplexStream) {
+        let (control_io, session_io) = tokio::io::duplex(DUPLEX_BUFFER_BYTES);
+        let (downstream, upstream) = tokio::io::split(session_io);
+        let session = Arc::new(Self {
+            upstream: Mutex::new(upstream),
+            downstream: Mutex::new(downstream),
+            last_seen: StdMutex::new(Instant::now()),
+            closed: CancellationToken::new(),
+        });
+        (session, control_io)
+    }
+
+    /// Delivers bytes sent by the client to the control channel.
+    pub async fn push(&self, data: &[u8]) -> io::Result<()> {
+        self.touch();
+        if self.closed.is_cancelled() {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        self.upstream.lock().await.write_all(data).await
+    }
+
+    /// Waits up to `wait` for bytes destined for the client.
+    ///
+    /// Returns an empty chunk when nothing arrived in time and `None` once the
+    /// session is closed.
+    pub async fn poll(&self, wait: Duration) -> io::Result<Option<Bytes>> {
+        self.touch();
+        let mut downstream = self.downstream.lock().await;
+        let mut buf = vec![0u8; MAX_CHUNK_BYTES];
+        let result = tokio::select! {
+            _ = self.closed.cancelled() => Ok(None),
+            read = tokio::time::timeout(wait, downstream.read(&mut buf)) => match read {
+                Err(_) => Ok(Some(Bytes::new())),
+                Ok(Ok(0)) => Ok(None),
+                Ok(Ok(n)) => {
+                    buf.truncate(n);
+                    Ok(Some(Bytes::from(buf)))
+                }
+                Ok(Err(error)) => Err(error),
+            },
+        };
+        self.touch();
+        result
+    }
+
+    /// Closes the session. The control channel sees end-of-stream and any
+    /// pending poll returns `None`.
+    pub async fn close(&self) {
+        self.closed.cancel();
+        let _ = self.upstream.lock().await.shutdown().await;
+    }
+
+    /// Closes the session once no request has touched it for `idle_timeout`.
+    /// Returns early if the session is closed some other way.
+    pub async fn close_when_idle(&self, idle_timeout: Duration) {
+        loop {
+            let idle = self.idle_for();
+            if idle >= idle_timeout {
+                break;
+            }
+            tokio::select! {
+                _ = self.closed.cancelled() => return,
+                _ = tokio::time::sleep(idle_timeout - idle) => {}
+            }
+        }
+        tracing::debug!("Closing idle relay long-poll session");
+        self.close().await;
+    }
+
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+}
+
+/// Opens a long-poll session at `open_url` and returns the byte stream to run
+/// the client yamux session over.
+///
+/// The request pumps stop when `shutdown` fires, when the returned stream is
+/// dropped or when either direction fails; the stream then reaches EOF.
+pub(crate) async fn connect_long_poll(
+    open_url: &str,
+    bearer_token: &str,
+    shutdown: CancellationToken,
+) -> anyhow::Result<DuplexStream> {
+    let endpoint = Arc::new(Endpoint::parse(open_url, bearer_token)?);
+    let mut upload_channel = HttpChannel::new(endpoint.clone());
+
+    let (status, body) = upload_channel
+        .send(Method::POST, &endpoint.open_path, Bytes::new())
+        .await
+        .context("Failed to open relay long-poll session")?;
+    if !status.is_success() {
+        anyhow::bail!("Relay long-poll session rejected with status {status}");
+    }
+    let session_id = std::str::from_utf8(&body)
+        .context("Relay long-poll session ID is not UTF-8")?
+        .trim()
+        .to_string();
+    let session_path = format!("{}/{session_id}", endpoint.base_path);
+
+    let (client_io, bridge_io) = tokio::io::duplex(DUPLEX_BUFFER_BYTES);
+    let (reader, writer) = tokio::io::split(bridge_io);
+    let download_channel = HttpChannel::new(endpoint.clone());
+
+    tokio::spawn(async move {
+        let result = tokio::select! {
+            result = upload(upload_channel, &session_path, reader) => result,
+            result = download(download_channel, &session_path, writer) => result,
+            _ = shutdown.cancelled() => Ok(()),
+        };
+        if let Err(error) = result {
+            tracing::debug!(?error, "Relay long-poll transport stopped");
+        }
+
+        let mut close_channel = HttpChannel::new(endpoint);
+        if let Err(error) = close_channel
+            .send(Method::DELETE, &session_path, Bytes::new())
+            .await
+        {
+            tracing::debug!(?error, "Failed to close relay long-poll session");
+        }
+    });
+
+    Ok(client_io)
+}
+
+async fn upload(
+    mut channel: HttpChannel,
+    session_path: &str,
+    mut reader: ReadHalf<DuplexStream>,
+) -> anyhow::Result<()> {
+    let mut buf = vec![0u8; MAX_CHUNK_BYTES];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        let (status, _) = channel
+            .send(
+                Method::POST,
+                session_path,
+                Bytes::copy_from_slice(&buf[..n]),
+            )
+            .await?;
+        if !status.is_success() {
+            anyhow::bail!("Relay long-poll upload failed with status {status}");
+        }
+    }
+}
+
+async fn download(
+    mut channel: HttpChannel,
+    session_path: &str,
+    mut writer: WriteHalf<DuplexStream>,
+) -> anyhow::Result<()> {
+    loop {
+        let (status, body) = channel
+            .send(Method::GET, session_path, Bytes::new())
+            .await?;
+        match status {
+            StatusCode::OK => writer.write_all(&body).await?,
+            StatusCode::NO_CONTENT => {}
+            StatusCode::GONE => return Ok(()),
+            status => anyhow::bail!("Relay long-poll download failed with status {status}"),
+        }
+    }
+}
+
+/// Connection details shared by every request of one session.
+struct Endpoint {
+    tls: bool,
+    host: String,
+    port: u16,
+    authority: String,
+    /// Path used for session requests, e.g. `/v1/relay/poll`.
+    base_path: String,
+    /// Path and query used to open the session.
+    open_path: String,
+    authorization: String,
+}
+
+impl Endpoint {
+    fn parse(url: &str, bearer_token: &str) -> anyhow::Result<Self> {
+        let uri: Uri = url.parse().context("Invalid relay long-poll URL")?;
+        let tls = match uri.scheme_str() {
+            Some("https") => true,
+            Some("http") => false,
+            _ => anyhow::bail!("unsupported URL scheme: {url}"),
+        };
+        let authority = uri
+            .authority()
+            .context("Relay long-poll URL has no host")?
+            .clone();
+        let port = authority.port_u16().unwrap_or(if tls { 443 } else { 80 });
+        let open_path = uri
+            .path_and_query()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| "/".to_string());
+
+        Ok(Self {
+            tls,
+            host: authority.host().to_string(),
+            port,
+            authority: authority.to_string(),
+            base_path: uri.path().trim_end_matches('/').to_string(),
+            open_path,
+            authorization: format!("Bearer {bearer_token}"),
+        })
+    }
+
+    async fn connect(&self) -> anyhow::Result<SendRequest<Body>> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .context("Failed to connect to relay")?;
+        tcp.set_nodelay(true)?;
+
+        if !self.tls {
+            return handshake(tcp).await;
+        }
+
+        let server_name =
+            ServerName::try_from(self.host.clone()).context("Invalid relay TLS server name")?;
+        let tls_stream = TlsConnector::from(client_config())
+            .connect(server_name, tcp)
+            .await
+            .context("Relay TLS handshake failed")?;
+        handshake(tls_stream).await
+    }
+}
+
+async fn handshake<S>(io: S) -> anyhow::Result<SendRequest<Body>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (sender, connection) = client_http1::Builder::new()
+        .handshake(TokioIo::new(io))
+        .await
+        .context("Relay HTTP handshake failed")?;
+
+    tokio::spawn(async move {
+        if let Err(error) = connection.await {
+            tracing::debug!(?error, "Relay long-poll connection closed");
+        }
+    });
+
+    Ok(sender)
+}
+
+/// A lazily (re)connected HTTP/1.1 connection that sends one request at a time.
+struct HttpChannel {
+    endpoint: Arc<Endpoint>,
+    sender: Option<SendRequest<Body>>,
+}
+
+impl HttpChannel {
+    fn new(endpoint: Arc<Endpoint>) -> Self {
+        Self {
+            endpoint,
+            sender: None,
+        }
+    }
+
+    async fn send(
+        &mut self,
+        method: Method,
+        path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<(StatusCode, Bytes)> {
+        let result = tokio::time::timeout(
+            POLL_WAIT + REQUEST_GRACE,
+            self.send_inner(method, path, body),
+        )
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Relay long-poll request timed out")));
+        if result.is_err() {
+            self.sender = None;
+        }
+        result
+    }
+
+    async fn send_inner(
+        &mut self,
+        method: Method,
+        path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<(StatusCode, Bytes)> {
+        let sender = match self.sender.take() {
+            Some(sender) if !sender.is_closed() => sender,
+            _ => self.endpoint.connect().await?,
+        };
+        let sender = self.sender.insert(sender);
+
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(HOST, &self.endpoint.authority)
+            .header(AUTHORIZATION, &self.endpoint.authorization)
+            .body(Body::from(body))
+            .context("Failed to build relay long-poll request")?;
+
+        sender.ready().await?;
+        let response = sender.send_request(request).await?;
+        let status = response.status();
+        let body = axum::body::to_bytes(Body::new(response.into_body()), MAX_CHUNK_BYTES)
+            .await
+            .context("Failed to read relay long-poll response")?;
+        Ok((status, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        extract::State,
+        http::HeaderMap,
+        response::{IntoResponse, Response},
+        routing::post,
+    };
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const SHORT_WAIT: Duration = Duration::from_millis(50);
+
+    async fn poll_until(session: &LongPollSession, len: usize) -> Vec<u8> {
+        let mut received = Vec::new();
+        while received.len() < len {
+            let chunk = session.poll(Duration::from_secs(5)).await.unwrap().unwrap();
+            received.extend_from_slice(&chunk);
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn carries_bytes_in_order_both_ways() {
+        let (session, mut control) = LongPollSession::new();
+
+        session.push(b"one ").await.unwrap();
+        session.push(b"two").await.unwrap();
+        let mut buf = [0u8; 7];
+        control.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"one two");
+
+        control.write_all(b"three ").await.unwrap();
+        control.write_all(b"four").await.unwrap();
+        assert_eq!(poll_until(&session, 10).await, b"three four");
+    }
+
+    #[tokio::test]
+    async fn poll_answers_empty_when_nothing_arrives_in_time() {
+        let (session, _control) = LongPollSession::new();
+        let started = Instant::now();
+        assert_eq!(session.poll(SHORT_WAIT).await.unwrap(), Some(Bytes::new()));
+        assert!(started.elapsed() >= SHORT_WAIT);
+    }
+
+    #[tokio::test]
+    async fn close_ends_both_sides() {
+        let (session, mut control) = LongPollSession::new();
+        let pending = tokio::spawn({
+            let session = session.clone();
+            async move { session.poll(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(SHORT_WAIT).await;
+
+        session.close().await;
+        assert_eq!(pending.await.unwrap().unwrap(), None);
+        assert_eq!(session.poll(SHORT_WAIT).await.unwrap(), None);
+        assert_eq!(
+            session.push(b"late").await.unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+        let mut buf = [0u8; 1];
+        assert_eq!(control.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn idle_sessions_are_closed_and_active_ones_kept() {
+        let idle_timeout = Duration::from_millis(200);
+        let (session, _control) = LongPollSession::new();
+        let closer = tokio::spawn({
+            let session = session.clone();
+            async move { session.close_when_idle(idle_timeout).await }
+        });
+
+        // Every request restarts the idle timer.
+        for _ in 0..4 {
+            tokio::time::sleep(idle_timeout / 2).await;
+            session.push(b"keepalive").await.unwrap();
+        }
+        assert!(!closer.is_finished());
+
+        tokio::time::timeout(idle_timeout * 3, closer)
+            .await
+            .expect("idle session was not closed")
+            .unwrap();
+        assert_eq!(session.poll(SHORT_WAIT).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn idle_watch_stops_when_closed_otherwise() {
+        let (session, _control) = LongPollSession::new();
+        let closer = tokio::spawn({
+            let session = session.clone();
+            async move { session.close_when_idle(Duration::from_secs(60)).await }
+        });
+        session.close().await;
+        tokio::time::timeout(Duration::from_secs(1), closer)
+            .await
+            .expect("idle watch outlived the session")
+            .unwrap();
+    }
+
+    #[derive(Clone)]
+    struct Relay {
+        session: Arc<LongPollSession>,
+        deleted: CancellationToken,
+    }
+
+    fn authorized(headers: &HeaderMap) -> bool {
+        headers
+            .get(AUTHORIZATION)
+            .is_some_and(|value| value == "Bearer secret")
+    }
+
+    /// A relay serving a single long-poll session `s1` under `/poll`.
+    async fn serve_relay() -> (String, Relay, DuplexStream) {
+        let (session, control) = LongPollSession::new();
+        let relay = Relay {
+            session,
+            deleted: CancellationToken::new(),
+        };
+
+        async fn open(headers: HeaderMap) -> Response {
+            if !authorized(&headers) {
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+            "s1\n".into_response()
+        }
+        async fn poll(State(relay): State<Relay>) -> Response {
+            match relay.session.poll(POLL_WAIT).await {
+                Ok(Some(data)) if data.is_empty() => StatusCode::NO_CONTENT.into_response(),
+                Ok(Some(data)) => data.into_response(),
+                _ => StatusCode::GONE.into_response(),
+            }
+        }
+        async fn push(State(relay): State<Relay>, body: Bytes) -> StatusCode {
+            match relay.session.push(&body).await {
+                Ok(()) => StatusCode::NO_CONTENT,
+                Err(_) => StatusCode::GONE,
+            }
+        }
+        async fn close(State(relay): State<Relay>) -> StatusCode {
+            relay.session.close().await;
+            relay.deleted.cancel();
+            StatusCode::NO_CONTENT
+        }
+
+        let app = Router::new()
+            .route("/poll", post(open))
+            .route(
+                "/poll/s1",
+                axum::routing::get(poll).post(push).delete(close),
+            )
+            .with_state(relay.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/poll?host=test", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, relay, control)
+    }
+
+    #[tokio::test]
+    async fn client_session_lifecycle() {
+        let (url, relay, mut control) = serve_relay().await;
+        let shutdown = CancellationToken::new();
+        let mut client = connect_long_poll(&url, "secret", shutdown.clone())
+            .await
+            .unwrap();
+
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        control.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        control.write_all(b"world").await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"world");
+
+        // Shutting down closes the session on the relay.
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), relay.deleted.cancelled())
+            .await
+            .expect("session was not closed on the relay");
+        assert_eq!(control.read(&mut buf).await.unwrap(), 0);
+        assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn client_rejected_without_valid_token() {
+        let (url, _relay, _control) = serve_relay().await;
+        let error = connect_long_poll(&url, "wrong", CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("401"), "{error}");
+    }
+}
@@ -9,8 +9,11 @@ use axum::{
 use futures_util::StreamExt;
 use hyper::{client::conn::http1 as client_http1, upgrade};
 use hyper_util::rt::TokioIo;
-use tokio::sync::Mutex;
-use tokio_yamux::{Control, Session};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::Mutex,
+};
+use tokio_yamux::{Control, Session, StreamHandle};
 use ws_bridge::axum_ws_stream_io;
 
 use crate::yamux_config;
@@ -26,16 +29,27 @@ where
     F: FnOnce(SharedControl) -> Fut,
     Fut: Future<Output = ()>,
 {
-    let ws_io = axum_ws_stream_io(socket);
-    let mut session = Session::new_server(ws_io, yamux_config());
+    run_control_channel_over_io(axum_ws_stream_io(socket), on_connected).await
+}
+
+/// Runs the server-side control channel over any byte stream, such as the
+/// one backing a [`crate::long_poll::LongPollSession`].
+pub async fn run_control_channel_over_io<S, F, Fut>(io: S, on_connected: F) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    F: FnOnce(SharedControl) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut session = Session::new_server(io, yamux_config());
     let control = Arc::new(Mutex::new(session.control()));
 
     on_connected(control).await;
 
     while let Some(stream_result) = session.next().await {
         match stream_result {
-            Ok(_stream) => {
-                // The client side does not currently open server-initiated streams.
+            Ok(stream) => {
+                // Streams opened by the client are keepalive probes.
+                tokio::spawn(echo_probe(stream));
             }
             Err(error) => {
                 return Err(anyhow::anyhow!("relay session error: {error}"));
@@ -46,6 +60,14 @@ where
     Ok(())
 }
 
+/// Echoes a client keepalive probe so it can measure the round trip.
+async fn echo_probe(mut stream: StreamHandle) {
+    let mut nonce = [0u8; 8];
+    if stream.read_exact(&mut nonce).await.is_ok() && stream.write_all(&nonce).await.is_ok() {
+        let _ = stream.shutdown().await;
+    }
+}
+
 /// Proxies one HTTP request over a new yamux stream using the shared control.
 pub async fn proxy_request_over_control(
     control: &Mutex<Control>,
@@ -63,6 +85,20 @@ pub async fn proxy_request_over_control(
         }
     };
 
+    proxy_request_over_stream(stream, request, strip_prefix).await
+}
+
+/// Proxies one HTTP request over an already-open byte stream, such as a
+/// yamux stream or a TCP connection to another relay instance. Upgrades
+/// (WebSockets) are spliced through once both sides have switched protocols.
+pub async fn proxy_request_over_stream<S>(
+    stream: S,
+    request: Request,
+    strip_prefix: &str,
+) -> Response
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     let (mut parts, body) = request.into_parts();
     let path = normalized_relay_path(&parts.uri, strip_prefix);
     parts.uri = match Uri::builder().path_and_query(path).build() {
@@ -1,3 +1,6 @@
+use std::sync::Arc;
+
+use rustls::ClientConfig;
 use tokio_tungstenite::Connector;
 
 /// Build TLS connector for the relay WebSocket client.
@@ -9,18 +12,38 @@ use tokio_tungstenite::Connector;
 pub fn ws_connector() -> Option<Connector> {
     #[cfg(debug_assertions)]
     {
-        use std::sync::Arc;
+        Some(Connector::Rustls(client_config()))
+    }
 
-        let config = rustls::ClientConfig::builder()
+    #[cfg(not(debug_assertions))]
+    {
+        None
+    }
+}
+
+/// Build the rustls client configuration used by the HTTP long-poll transport.
+///
+/// Mirrors [`ws_connector`]: debug builds accept all certificates, release
+/// builds validate against the bundled webpki roots.
+pub(crate) fn client_config() -> Arc<ClientConfig> {
+    #[cfg(debug_assertions)]
+    {
+        let config = ClientConfig::builder()
             .dangerous()
             .with_custom_certificate_verifier(Arc::new(AcceptAllCerts))
             .with_no_client_auth();
-        Some(Connector::Rustls(Arc::new(config)))
+        Arc::new(config)
     }
 
     #[cfg(not(debug_assertions))]
     {
-        None
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Arc::new(config)
     }
 }
 
@@ -7,18 +7,21 @@ use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberI
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let config = RelayServerConfig::from_env()?;
+
     // Initialise tracing
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
-        .with(fmt::layer())
+        .with(config.json_logs.then(|| fmt::layer().json()))
+        .with((!config.json_logs).then(fmt::layer))
         .init();
 
     // Force rustls crypto provider (same as remote)
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
-    let config = RelayServerConfig::from_env()?;
     tracing::info!(
         listen_addr = %config.listen_addr,
+        instance_addr = ?config.instance_addr,
         "Starting relay server"
     );
 
@@ -27,6 +30,7 @@ async fn main() -> anyhow::Result<()> {
 
     let jwt = Arc::new(JwtService::new(config.jwt_secret.clone()));
     let state = RelayAppState::new(pool, config.clone(), jwt);
+    state.host_router.spawn_heartbeat();
 
     let router = routes::build_router(state);
 
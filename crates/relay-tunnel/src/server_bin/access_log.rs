@@ -0,0 +1,80 @@
+//! Structured access logging and request metrics middleware.
+//!
+//! Successful requests are sampled according to
+//! `RELAY_ACCESS_LOG_SAMPLE_RATE`; errors and auth failures are always
+//! logged. With `RELAY_LOG_FORMAT=json` each entry is a single JSON object.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use super::state::RelayAppState;
+
+/// Logs one in every `every` successful requests.
+pub struct AccessLogSampler {
+    every: u64,
+    counter: AtomicU64,
+}
+
+impl AccessLogSampler {
+    /// `rate` is the fraction of successful requests to log, in `[0, 1]`.
+    pub fn new(rate: f64) -> Self {
+        let every = if rate <= 0.0 {
+            0
+        } else {
+            (1.0 / rate.min(1.0)).round() as u64
+        };
+        Self {
+            every,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn should_log(&self, is_error: bool) -> bool {
+        if is_error {
+            return true;
+        }
+        self.every != 0 && self.counter.fetch_add(1, Ordering::Relaxed) % self.every == 0
+    }
+}
+
+pub async fn access_log(
+    State(state): State<RelayAppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    let status = response.status();
+    state
+        .metrics
+        .record_http_request(method.as_str(), &route, status);
+
+    let is_error = status.is_client_error() || status.is_server_error();
+    if state.access_log_sampler.should_log(is_error) {
+        tracing::info!(
+            target: "relay_access",
+            method = %method,
+            route = %route,
+            status = status.as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "request"
+        );
+    }
+
+    response
+}
@@ -8,6 +8,16 @@ pub struct RelayServerConfig {
     pub database_url: String,
     pub listen_addr: String,
     pub jwt_secret: SecretString,
+    /// Bearer token required to scrape `/metrics`. The endpoint refuses every
+    /// request when unset.
+    pub metrics_token: Option<SecretString>,
+    /// Fraction of successful requests written to the access log.
+    pub access_log_sample_rate: f64,
+    /// Emit logs as JSON objects instead of human-readable lines.
+    pub json_logs: bool,
+    /// `host:port` other relay instances use to reach this one. Enables
+    /// cross-instance routing when set.
+    pub instance_addr: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -32,10 +42,34 @@ impl RelayServerConfig {
         validate_jwt_secret(&jwt_secret_str)?;
         let jwt_secret = SecretString::new(jwt_secret_str.into());
 
+        let metrics_token = env::var("RELAY_METRICS_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .map(|token| SecretString::new(token.into()));
+
+        let access_log_sample_rate = match env::var("RELAY_ACCESS_LOG_SAMPLE_RATE") {
+            Ok(value) => value
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or(ConfigError::InvalidVar("RELAY_ACCESS_LOG_SAMPLE_RATE"))?,
+            Err(_) => 1.0,
+        };
+
+        let json_logs = env::var("RELAY_LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json"));
+
+        let instance_addr = env::var("RELAY_INSTANCE_ADDR")
+            .ok()
+            .filter(|addr| !addr.is_empty());
+
         Ok(Self {
             database_url,
             listen_addr,
             jwt_secret,
+            metrics_token,
+            access_log_sample_rate,
+            json_logs,
+            instance_addr,
         })
     }
 }
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct HostRouteRepository<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> HostRouteRepository<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record that this instance now holds the host's control channel.
+    pub async fn upsert(
+        &self,
+        host_id: Uuid,
+        instance_id: Uuid,
+        instance_addr: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO relay_host_routes (host_id, instance_id, instance_addr, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (host_id) DO UPDATE
+                SET instance_id = EXCLUDED.instance_id,
+                    instance_addr = EXCLUDED.instance_addr,
+                    updated_at = NOW()
+            "#,
+            host_id,
+            instance_id,
+            instance_addr
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove the route only if it still points at this instance, so a
+    /// reconnect that already moved the host elsewhere is kept.
+    pub async fn delete_if_owned(
+        &self,
+        host_id: Uuid,
+        instance_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM relay_host_routes WHERE host_id = $1 AND instance_id = $2",
+            host_id,
+            instance_id
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Refresh every route held by this instance.
+    pub async fn touch_instance(&self, instance_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE relay_host_routes SET updated_at = NOW() WHERE instance_id = $1",
+            instance_id
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Address of the instance holding the host's control channel, ignoring
+    /// routes not refreshed within `max_age`.
+    pub async fn find_fresh(
+        &self,
+        host_id: Uuid,
+        max_age: Duration,
+    ) -> Result<Option<(Uuid, String)>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT instance_id, instance_addr
+            FROM relay_host_routes
+            WHERE host_id = $1
+              AND updated_at > NOW() - make_interval(secs => $2)
+            "#,
+            host_id,
+            max_age.as_secs_f64()
+        )
+        .fetch_optional(self.pool)
+        .await?;
+        Ok(row.map(|r| (r.instance_id, r.instance_addr)))
+    }
+}
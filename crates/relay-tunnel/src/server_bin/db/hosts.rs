@@ -1,3 +1,4 @@
+use relay_types::RelayHostRole;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -50,34 +51,47 @@ impl<'a> HostRepository<'a> {
         Ok(row.id)
     }
 
-    pub async fn assert_host_access(
+    /// The user's role on the host, or `None` if the host does not exist or
+    /// the user cannot reach it.
+    pub async fn access_role(
         &self,
         host_id: Uuid,
         user_id: Uuid,
-    ) -> Result<(), IdentityError> {
+    ) -> Result<Option<RelayHostRole>, sqlx::Error> {
         let row = sqlx::query!(
             r#"
-            SELECT EXISTS (
-                SELECT 1
-                FROM hosts h
-                LEFT JOIN organization_member_metadata om
-                    ON om.organization_id = h.shared_with_organization_id
-                    AND om.user_id = $2
-                WHERE h.id = $1
-                  AND (h.owner_user_id = $2 OR om.user_id IS NOT NULL)
-            ) AS "allowed!"
+            SELECT
+                h.owner_user_id = $2 AS "is_owner!",
+                om.user_id IS NOT NULL AS "is_organization_member!",
+                s.role AS "share_role?: RelayHostRole"
+            FROM hosts h
+            LEFT JOIN organization_member_metadata om
+                ON om.organization_id = h.shared_with_organization_id
+                AND om.user_id = $2
+            LEFT JOIN relay_host_shares s
+                ON s.host_id = h.id
+                AND s.user_id = $2
+            WHERE h.id = $1
             "#,
             host_id,
             user_id
         )
-        .fetch_one(self.pool)
+        .fetch_optional(self.pool)
         .await?;
 
-        if row.allowed {
-            Ok(())
-        } else {
-            Err(IdentityError::PermissionDenied)
-        }
+        Ok(row.and_then(|row| {
+            RelayHostRole::effective(row.is_owner, row.is_organization_member, row.share_role)
+        }))
+    }
+
+    pub async fn assert_host_access(
+        &self,
+        host_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<RelayHostRole, IdentityError> {
+        self.access_role(host_id, user_id)
+            .await?
+            .ok_or(IdentityError::PermissionDenied)
     }
 
     pub async fn is_host_online(&self, host_id: Uuid) -> Result<bool, sqlx::Error> {
@@ -1,4 +1,5 @@
 pub mod auth_sessions;
+pub mod host_routes;
 pub mod hosts;
 pub mod identity_errors;
 pub mod relay_browser_sessions;
@@ -0,0 +1,146 @@
+//! Cross-instance routing for relay servers behind a load balancer.
+//!
+//! A host's control channel lives on exactly one relay instance, but browser
+//! requests can land on any of them. Each instance records the hosts it holds
+//! in `relay_host_routes` together with an address its peers can reach it on.
+//! An instance without the host's channel looks the route up and forwards the
+//! request there. Routing is disabled when `RELAY_INSTANCE_ADDR` is unset.
+
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::db::host_routes::HostRouteRepository;
+
+/// Marks a request forwarded by another instance, so it is never forwarded
+/// again. Clients setting it only opt out of forwarding.
+pub const FORWARDED_HEADER: HeaderName = HeaderName::from_static("x-vk-relay-forwarded");
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+/// Routes not refreshed for this long belong to an instance that has died.
+const ROUTE_MAX_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct HostRouter {
+    pool: PgPool,
+    instance_id: Uuid,
+    instance_addr: Option<String>,
+}
+
+impl HostRouter {
+    pub fn new(pool: PgPool, instance_addr: Option<String>) -> Self {
+        Self {
+            pool,
+            instance_id: Uuid::new_v4(),
+            instance_addr,
+        }
+    }
+
+    pub fn forwarded_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.instance_id.to_string())
+            .expect("a UUID is a valid header value")
+    }
+
+    /// Advertise that this instance holds the host's control channel.
+    pub async fn claim(&self, host_id: Uuid) {
+        let Some(addr) = &self.instance_addr else {
+            return;
+        };
+        if let Err(error) = HostRouteRepository::new(&self.pool)
+            .upsert(host_id, self.instance_id, addr)
+            .await
+        {
+            tracing::warn!(?error, %host_id, "failed to record relay host route");
+        }
+    }
+
+    pub async fn release(&self, host_id: Uuid) {
+        if self.instance_addr.is_none() {
+            return;
+        }
+        if let Err(error) = HostRouteRepository::new(&self.pool)
+            .delete_if_owned(host_id, self.instance_id)
+            .await
+        {
+            tracing::warn!(?error, %host_id, "failed to remove relay host route");
+        }
+    }
+
+    /// Address of the peer instance holding the host's control channel.
+    pub async fn peer_for(&self, host_id: Uuid) -> Option<String> {
+        self.instance_addr.as_ref()?;
+        match HostRouteRepository::new(&self.pool)
+            .find_fresh(host_id, ROUTE_MAX_AGE)
+            .await
+        {
+            Ok(route) => peer_from_route(route, self.instance_id),
+            Err(error) => {
+                tracing::warn!(?error, %host_id, "failed to look up relay host route");
+                None
+            }
+        }
+    }
+
+    /// Keep this instance's routes fresh for as long as it runs.
+    pub fn spawn_heartbeat(&self) {
+        if self.instance_addr.is_none() {
+            return;
+        }
+        let router = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(error) = HostRouteRepository::new(&router.pool)
+                    .touch_instance(router.instance_id)
+                    .await
+                {
+                    tracing::warn!(?error, "failed to refresh relay host routes");
+                }
+            }
+        });
+    }
+}
+
+/// A fresh route only names a peer if it points at another instance; a route
+/// back at this one means the channel was lost here and not yet reclaimed.
+fn peer_from_route(route: Option<(Uuid, String)>, instance_id: Uuid) -> Option<String> {
+    route
+        .filter(|(owner, _)| *owner != instance_id)
+        .map(|(_, addr)| addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_to_the_instance_holding_the_host() {
+        let own = Uuid::new_v4();
+        let peer = Uuid::new_v4();
+        assert_eq!(
+            peer_from_route(Some((peer, "10.0.0.2:8080".to_string())), own),
+            Some("10.0.0.2:8080".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_or_own_routes_are_not_forwarded() {
+        let own = Uuid::new_v4();
+        assert_eq!(peer_from_route(None, own), None);
+        assert_eq!(
+            peer_from_route(Some((own, "10.0.0.1:8080".to_string())), own),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn routing_is_disabled_without_an_instance_address() {
+        // Never connects: the lookup is skipped before touching the pool.
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let router = HostRouter::new(pool, None);
+        assert_eq!(router.peer_for(Uuid::new_v4()).await, None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Operational metrics for the relay server, rendered in the Prometheus text
+//! exposition format.
+//!
+//! Route labels use the matched route template (`/v1/relay/h/{host_id}/...`)
+//! rather than the raw path, so label cardinality stays bounded.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+};
+
+use axum::http::StatusCode;
+
+#[derive(Default)]
+pub struct RelayMetrics {
+    control_connections_total: AtomicU64,
+    control_connections_active: Arc<AtomicI64>,
+    auth_failures_total: AtomicU64,
+    yamux_streams_total: AtomicU64,
+    yamux_streams_active: Arc<AtomicI64>,
+    yamux_stream_errors_total: AtomicU64,
+    forwarded_requests_total: AtomicU64,
+    proxied_requests: Mutex<BTreeMap<&'static str, u64>>,
+    http_requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+}
+
+/// Decrements a gauge when dropped.
+pub struct GaugeGuard(Arc<AtomicI64>);
+
+impl GaugeGuard {
+    fn new(gauge: &Arc<AtomicI64>) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+impl RelayMetrics {
+    /// Track a local server control channel for as long as the guard lives.
+    pub fn control_connected(&self) -> GaugeGuard {
+        self.control_connections_total
+            .fetch_add(1, Ordering::Relaxed);
+        GaugeGuard::new(&self.control_connections_active)
+    }
+
+    /// Track a yamux stream opened for a proxied request for as long as the
+    /// guard lives.
+    pub fn yamux_stream_opened(&self) -> GaugeGuard {
+        self.yamux_streams_total.fetch_add(1, Ordering::Relaxed);
+        GaugeGuard::new(&self.yamux_streams_active)
+    }
+
+    pub fn record_proxied_request(&self, status: StatusCode) {
+        if status == StatusCode::BAD_GATEWAY {
+            self.yamux_stream_errors_total
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        let mut proxied = self.proxied_requests.lock().unwrap();
+        *proxied.entry(status_class(status)).or_default() += 1;
+    }
+
+    pub fn record_forwarded_request(&self) {
+        self.forwarded_requests_total
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_http_request(&self, method: &str, route: &str, status: StatusCode) {
+        if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            self.auth_failures_total.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut requests = self.http_requests.lock().unwrap();
+        *requests
+            .entry((method.to_string(), route.to_string(), status.as_u16()))
+            .or_default() += 1;
+    }
+
+    pub fn render(&self, active_hosts: usize) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            let _ = writeln!(out, "{name} {value}");
+        };
+
+        metric(
+            "relay_active_hosts",
+            "gauge",
+            "Hosts with a registered relay connection.",
+            active_hosts.to_string(),
+        );
+        metric(
+            "relay_control_connections_active",
+            "gauge",
+            "Open control channel WebSockets from local servers.",
+            self.control_connections_active
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "relay_control_connections_total",
+            "counter",
+            "Control channel WebSockets accepted since start.",
+            self.control_connections_total
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "relay_auth_failures_total",
+            "counter",
+            "Requests rejected with 401 or 403.",
+            self.auth_failures_total.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "relay_yamux_streams_active",
+            "gauge",
+            "Yamux streams currently carrying a proxied response.",
+            self.yamux_streams_active
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "relay_yamux_streams_total",
+            "counter",
+            "Yamux streams opened for proxied requests.",
+            self.yamux_streams_total.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "relay_yamux_stream_errors_total",
+            "counter",
+            "Proxied requests that failed at the tunnel (502).",
+            self.yamux_stream_errors_total
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "relay_forwarded_requests_total",
+            "counter",
+            "Requests forwarded to the relay instance holding the host's control channel.",
+            self.forwarded_requests_total
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP relay_proxied_requests_total Requests proxied to hosts, by response status class."
+        );
+        let _ = writeln!(out, "# TYPE relay_proxied_requests_total counter");
+        for (class, count) in self.proxied_requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "relay_proxied_requests_total{{status_class=\"{class}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP relay_http_requests_total HTTP requests handled, by method, route and status."
+        );
+        let _ = writeln!(out, "# TYPE relay_http_requests_total counter");
+        for ((method, route, status), count) in self.http_requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "relay_http_requests_total{{method=\"{method}\",route=\"{}\",status=\"{status}\"}} {count}",
+                route.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+
+        out
+    }
+}
@@ -1,6 +1,9 @@
+pub mod access_log;
 pub mod auth;
 pub mod config;
 pub mod db;
+pub mod host_routing;
+pub mod metrics;
 pub mod relay_registry;
 pub mod routes;
 pub mod state;
@@ -3,10 +3,12 @@
 //! Each connected local server gets an `ActiveRelay` entry. The remote
 //! relay proxy looks up relays by host ID and opens yamux streams over
 //! the existing control connection. One-time auth codes are DB-backed.
+//! Control channels carried over HTTP long-polling also register their
+//! session here so follow-up poll requests can find it.
 
 use std::{collections::HashMap, sync::Arc};
 
-use relay_tunnel_core::server::SharedControl;
+use relay_tunnel_core::{long_poll::LongPollSession, server::SharedControl};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
@@ -53,8 +55,94 @@ impl RelayRegistry {
         }
     }
 
+    /// Number of hosts with an active relay.
+    pub async fn active_count(&self) -> usize {
+        self.inner.lock().await.len()
+    }
+
     /// Look up the active relay for a host.
     pub async fn get(&self, host_id: &Uuid) -> Option<Arc<ActiveRelay>> {
         self.inner.lock().await.get(host_id).cloned()
     }
 }
+
+/// Long-poll sessions backing relay control channels, indexed by session ID.
+///
+/// Sessions live in the memory of the instance that opened them, so the relay
+/// has to run as a single instance or behind a load balancer with sticky
+/// routing for `/relay/poll/*`. A request reaching another instance gets
+/// `404`, which makes the local server drop the session and reconnect.
+#[derive(Default, Clone)]
+pub struct LongPollSessions {
+    inner: Arc<Mutex<HashMap<Uuid, LongPollEntry>>>,
+}
+
+struct LongPollEntry {
+    user_id: Uuid,
+    session: Arc<LongPollSession>,
+}
+
+impl LongPollSessions {
+    pub async fn insert(&self, session_id: Uuid, user_id: Uuid, session: Arc<LongPollSession>) {
+        self.inner
+            .lock()
+            .await
+            .insert(session_id, LongPollEntry { user_id, session });
+    }
+
+    /// Look up a session, only returning it to the user that opened it.
+    pub async fn get(&self, session_id: &Uuid, user_id: Uuid) -> Option<Arc<LongPollSession>> {
+        self.inner
+            .lock()
+            .await
+            .get(session_id)
+            .filter(|entry| entry.user_id == user_id)
+            .map(|entry| entry.session.clone())
+    }
+
+    /// Remove a session, only at the request of the user that opened it.
+    pub async fn take(&self, session_id: &Uuid, user_id: Uuid) -> Option<Arc<LongPollSession>> {
+        let mut sessions = self.inner.lock().await;
+        if !sessions
+            .get(session_id)
+            .is_some_and(|entry| entry.user_id == user_id)
+        {
+            return None;
+        }
+        sessions.remove(session_id).map(|entry| entry.session)
+    }
+
+    pub async fn remove(&self, session_id: &Uuid) -> Option<Arc<LongPollSession>> {
+        self.inner
+            .lock()
+            .await
+            .remove(session_id)
+            .map(|entry| entry.session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn long_poll_sessions_are_only_visible_to_their_user() {
+        let sessions = LongPollSessions::default();
+        let (session, _io) = LongPollSession::new();
+        let (session_id, owner, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sessions.insert(session_id, owner, session.clone()).await;
+
+        assert!(sessions.get(&session_id, other).await.is_none());
+        assert!(sessions.take(&session_id, other).await.is_none());
+        assert!(
+            sessions
+                .get(&session_id, owner)
+                .await
+                .is_some_and(|found| Arc::ptr_eq(&found, &session))
+        );
+
+        assert!(sessions.take(&session_id, owner).await.is_some());
+        assert!(sessions.get(&session_id, owner).await.is_none());
+        assert!(sessions.remove(&session_id).await.is_none());
+    }
+}
@@ -1,20 +1,25 @@
-//! WebSocket control channel handler for local server connections.
+//! Control channel handlers for local server connections.
 
 use std::sync::Arc;
 
 use axum::{
     Extension,
-    extract::{Query, State, ws::WebSocketUpgrade},
+    extract::{
+        Query, State,
+        ws::{WebSocket, WebSocketUpgrade},
+    },
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use relay_tunnel_core::server::run_control_channel;
+use relay_tunnel_core::server::{run_control_channel, run_control_channel_over_io};
 use serde::Deserialize;
+use tokio::io::DuplexStream;
 use uuid::Uuid;
 
 use super::super::{
     auth::RequestContext,
     db::hosts::HostRepository,
+    host_routing::HostRouter,
     relay_registry::{ActiveRelay, RelayRegistry},
     state::RelayAppState,
 };
@@ -27,6 +32,12 @@ pub struct ConnectQuery {
     pub agent_version: Option<String>,
 }
 
+/// Byte stream a control channel runs over.
+pub(super) enum ControlTransport {
+    WebSocket(WebSocket),
+    LongPoll(DuplexStream),
+}
+
 /// Local server connects here to establish a relay control channel.
 /// The host record is upserted from the authenticated user + machine_id query param.
 pub async fn relay_connect(
@@ -35,6 +46,22 @@ pub async fn relay_connect(
     Query(query): Query<ConnectQuery>,
     ws: WebSocketUpgrade,
 ) -> Response {
+    let host_id = match register_host(&state, &ctx, &query).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    ws.on_upgrade(move |socket| async move {
+        run_host_control_channel(state, host_id, ControlTransport::WebSocket(socket)).await;
+    })
+}
+
+/// Upsert the connecting host and mark it online.
+pub(super) async fn register_host(
+    state: &RelayAppState,
+    ctx: &RequestContext,
+    query: &ConnectQuery,
+) -> Result<Uuid, Response> {
     let repo = HostRepository::new(&state.pool);
 
     let host_id = match repo
@@ -49,7 +76,7 @@ pub async fn relay_connect(
         Ok(id) => id,
         Err(error) => {
             tracing::error!(?error, "failed to upsert host for relay connect");
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
@@ -60,44 +87,68 @@ pub async fn relay_connect(
         tracing::warn!(?error, "failed to mark host online");
     }
 
-    let registry = state.relay_registry.clone();
-    let pool = state.pool.clone();
+    Ok(host_id)
+}
 
-    ws.on_upgrade(move |socket| async move {
-        handle_control_channel(socket, pool, registry, host_id).await;
-    })
+/// Run a host's control channel until it disconnects, then release the host.
+pub(super) async fn run_host_control_channel(
+    state: RelayAppState,
+    host_id: Uuid,
+    transport: ControlTransport,
+) {
+    let _connection = state.metrics.control_connected();
+    handle_control_channel(
+        transport,
+        state.pool,
+        state.relay_registry,
+        state.host_router,
+        host_id,
+    )
+    .await;
 }
 
 async fn handle_control_channel(
-    socket: axum::extract::ws::WebSocket,
+    transport: ControlTransport,
     pool: sqlx::PgPool,
     registry: RelayRegistry,
+    host_router: HostRouter,
     host_id: Uuid,
 ) {
     let registry_for_connect = registry.clone();
+    let host_router_for_connect = host_router.clone();
     let connected_relay = Arc::new(tokio::sync::Mutex::new(None::<Arc<ActiveRelay>>));
     let connected_relay_for_connect = connected_relay.clone();
-    let run_result = run_control_channel(socket, move |control| {
+    let on_connected = move |control| {
         let registry_for_connect = registry_for_connect.clone();
         let connected_relay_for_connect = connected_relay_for_connect.clone();
+        let host_router_for_connect = host_router_for_connect.clone();
         async move {
             let relay = Arc::new(ActiveRelay::new(control));
             registry_for_connect.insert(host_id, relay.clone()).await;
+            host_router_for_connect.claim(host_id).await;
             *connected_relay_for_connect.lock().await = Some(relay);
             tracing::debug!(%host_id, "Relay control channel connected");
         }
-    })
-    .await;
+    };
+    let run_result = match transport {
+        ControlTransport::WebSocket(socket) => run_control_channel(socket, on_connected).await,
+        ControlTransport::LongPoll(io) => run_control_channel_over_io(io, on_connected).await,
+    };
 
     if let Err(error) = run_result {
         tracing::warn!(?error, %host_id, "relay session error");
     }
 
-    let should_mark_offline = if let Some(relay) = connected_relay.lock().await.clone() {
+    let mut should_mark_offline = if let Some(relay) = connected_relay.lock().await.clone() {
         registry.remove_if_same(&host_id, &relay).await
     } else {
         registry.get(&host_id).await.is_none()
     };
+    if should_mark_offline {
+        host_router.release(host_id).await;
+        // The host may already have reconnected through another instance.
+        should_mark_offline = host_router.peer_for(host_id).await.is_none();
+    }
 
     let repo = HostRepository::new(&pool);
     if should_mark_offline {
@@ -0,0 +1,116 @@
+//! HTTP long-poll control channel for local servers whose WebSocket
+//! handshakes never reach the relay.
+//!
+//! Sessions are held in memory by the instance that opened them (see
+//! `LongPollSessions`), so `/relay/poll/*` needs sticky routing when the
+//! relay runs as several instances.
+
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use relay_tunnel_core::long_poll::{LongPollSession, POLL_WAIT, SESSION_IDLE_TIMEOUT};
+use uuid::Uuid;
+
+use super::{
+    super::{auth::RequestContext, state::RelayAppState},
+    connect::{ConnectQuery, ControlTransport, register_host, run_host_control_channel},
+};
+
+/// Open a long-poll session and start its control channel.
+/// Responds with the session ID as plain text.
+pub async fn open_session(
+    State(state): State<RelayAppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(query): Query<ConnectQuery>,
+) -> Response {
+    let host_id = match register_host(&state, &ctx, &query).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let session_id = Uuid::new_v4();
+    let (session, io) = LongPollSession::new();
+    state
+        .long_poll_sessions
+        .insert(session_id, ctx.user.id, session.clone())
+        .await;
+
+    tokio::spawn(async move {
+        session.close_when_idle(SESSION_IDLE_TIMEOUT).await;
+    });
+
+    let sessions = state.long_poll_sessions.clone();
+    tokio::spawn(async move {
+        run_host_control_channel(state, host_id, ControlTransport::LongPoll(io)).await;
+        if let Some(session) = sessions.remove(&session_id).await {
+            session.close().await;
+        }
+    });
+
+    tracing::debug!(%host_id, %session_id, "Relay long-poll session opened");
+    session_id.to_string().into_response()
+}
+
+/// Wait for bytes destined for the local server.
+pub async fn poll_session(
+    State(state): State<RelayAppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(session_id): Path<Uuid>,
+) -> Response {
+    let Some(session) = state.long_poll_sessions.get(&session_id, ctx.user.id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match session.poll(POLL_WAIT).await {
+        Ok(Some(data)) if data.is_empty() => StatusCode::NO_CONTENT.into_response(),
+        Ok(Some(data)) => data.into_response(),
+        Ok(None) => StatusCode::GONE.into_response(),
+        Err(error) => {
+            tracing::debug!(?error, %session_id, "relay long-poll read failed");
+            StatusCode::GONE.into_response()
+        }
+    }
+}
+
+/// Deliver bytes sent by the local server.
+pub async fn push_session(
+    State(state): State<RelayAppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(session_id): Path<Uuid>,
+    body: Bytes,
+) -> Response {
+    let Some(session) = state.long_poll_sessions.get(&session_id, ctx.user.id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match session.push(&body).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => {
+            tracing::debug!(?error, %session_id, "relay long-poll write failed");
+            StatusCode::GONE.into_response()
+        }
+    }
+}
+
+/// Close a session at the local server's request. Later requests for it get
+/// `404`.
+pub async fn close_session(
+    State(state): State<RelayAppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(session_id): Path<Uuid>,
+) -> Response {
+    let Some(session) = state
+        .long_poll_sessions
+        .take(&session_id, ctx.user.id)
+        .await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    session.close().await;
+    StatusCode::NO_CONTENT.into_response()
+}
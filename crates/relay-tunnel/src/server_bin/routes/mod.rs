@@ -1,25 +1,37 @@
 mod auth_code;
 pub mod connect;
+pub mod long_poll;
 pub mod path_routes;
 
 use axum::{
     Router,
-    http::{HeaderName, StatusCode},
+    extract::State,
+    http::{HeaderMap, HeaderName, StatusCode, header},
     middleware,
     response::IntoResponse,
     routing::{any, get, post},
 };
+use axum_extra::headers::{Authorization, HeaderMapExt, authorization::Bearer};
+use secrecy::{ExposeSecret, SecretString};
 use serde::Serialize;
+use subtle::ConstantTimeEq;
 use tower_http::{
     cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders},
     trace::TraceLayer,
 };
 
-use super::{auth, state::RelayAppState};
+use super::{access_log, auth, state::RelayAppState};
 
 pub fn build_router(state: RelayAppState) -> Router {
     let protected = Router::new()
         .route("/relay/connect", get(connect::relay_connect))
+        .route("/relay/poll", post(long_poll::open_session))
+        .route(
+            "/relay/poll/{session_id}",
+            get(long_poll::poll_session)
+                .post(long_poll::push_session)
+                .delete(long_poll::close_session),
+        )
         .route(
             "/relay/create/{host_id}",
             post(auth_code::create_relay_session),
@@ -45,7 +57,9 @@ pub fn build_router(state: RelayAppState) -> Router {
             any(path_routes::relay_path_proxy_with_tail),
         );
 
-    let public = Router::new().route("/health", get(health));
+    let public = Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics));
 
     Router::<RelayAppState>::new()
         .nest("/v1", protected)
@@ -64,6 +78,10 @@ pub fn build_router(state: RelayAppState) -> Router {
                 .allow_credentials(true),
         )
         .layer(TraceLayer::new_for_http())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            access_log::access_log,
+        ))
         .with_state(state)
 }
 
@@ -75,3 +93,64 @@ struct HealthResponse {
 async fn health() -> impl IntoResponse {
     (StatusCode::OK, axum::Json(HealthResponse { status: "ok" }))
 }
+
+async fn metrics(State(state): State<RelayAppState>, headers: HeaderMap) -> impl IntoResponse {
+    if !metrics_authorized(state.config.metrics_token.as_ref(), &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let active_hosts = state.relay_registry.active_count().await;
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(active_hosts),
+    )
+        .into_response()
+}
+
+/// Whether the request carries the configured metrics bearer token. Without a
+/// configured token nothing is authorized.
+fn metrics_authorized(expected: Option<&SecretString>, headers: &HeaderMap) -> bool {
+    let Some(expected) = expected else {
+        return false;
+    };
+    headers
+        .typed_get::<Authorization<Bearer>>()
+        .is_some_and(|Authorization(bearer)| {
+            bool::from(
+                bearer
+                    .token()
+                    .as_bytes()
+                    .ct_eq(expected.expose_secret().as_bytes()),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn metrics_require_the_configured_token() {
+        let token = SecretString::new("scrape-me".into());
+        assert!(metrics_authorized(Some(&token), &bearer("scrape-me")));
+        assert!(!metrics_authorized(Some(&token), &bearer("scrape-you")));
+        assert!(!metrics_authorized(Some(&token), &HeaderMap::new()));
+    }
+
+    #[test]
+    fn metrics_are_denied_without_a_configured_token() {
+        assert!(!metrics_authorized(None, &bearer("anything")));
+        assert!(!metrics_authorized(None, &HeaderMap::new()));
+    }
+}
@@ -1,11 +1,15 @@
 //! Relay path handlers: auth code exchange and proxy.
 
 use axum::{
+    body::Body,
     extract::{Path, Request, State},
-    http::StatusCode,
+    http::{Method, StatusCode},
     response::{IntoResponse, Response},
 };
-use relay_tunnel_core::server::proxy_request_over_control;
+use futures::StreamExt;
+use relay_tunnel_core::server::{proxy_request_over_control, proxy_request_over_stream};
+use relay_types::RelayHostRole;
+use tokio::net::TcpStream;
 use uuid::Uuid;
 
 use super::super::{
@@ -14,11 +18,29 @@ use super::super::{
         hosts::HostRepository, identity_errors::IdentityError,
         relay_browser_sessions::RelayBrowserSessionRepository,
     },
+    host_routing::FORWARDED_HEADER,
     state::RelayAppState,
 };
 
 const RELAY_PROXY_PREFIX: &str = "/relay/h";
 
+/// Local server API routes a viewer may read: those showing workspaces,
+/// their runs and the repos they belong to. Everything else under `/api`,
+/// including terminals, previews and configuration, needs an operator.
+const VIEWER_API_PREFIXES: &[&str] = &[
+    "/api/info",
+    "/api/health",
+    "/api/workspaces",
+    "/api/sessions",
+    "/api/execution-processes",
+    "/api/approvals",
+    "/api/events",
+    "/api/boards",
+    "/api/tags",
+    "/api/repos",
+    "/api/search",
+];
+
 /// Handle `ANY /relay/h/{host_id}/s/{browser_session_id}`.
 pub(super) async fn relay_path_proxy(
     State(state): State<RelayAppState>,
@@ -26,7 +48,7 @@ pub(super) async fn relay_path_proxy(
     request: Request,
 ) -> Response {
     if let Err(response) =
-        validate_browser_session_for_host(&state, browser_session_id, host_id).await
+        validate_browser_session_for_host(&state, browser_session_id, host_id, &request).await
     {
         return response;
     }
@@ -41,7 +63,7 @@ pub(super) async fn relay_path_proxy_with_tail(
     request: Request,
 ) -> Response {
     if let Err(response) =
-        validate_browser_session_for_host(&state, browser_session_id, host_id).await
+        validate_browser_session_for_host(&state, browser_session_id, host_id, &request).await
     {
         return response;
     }
@@ -53,6 +75,7 @@ async fn validate_browser_session_for_host(
     state: &RelayAppState,
     relay_browser_session_id: Uuid,
     expected_host_id: Uuid,
+    request: &Request,
 ) -> Result<(), Response> {
     let relay_browser_session_repo = RelayBrowserSessionRepository::new(&state.pool);
     let relay_browser_session = match relay_browser_session_repo
@@ -102,19 +125,32 @@ async fn validate_browser_session_for_host(
     }
 
     let host_repo = HostRepository::new(&state.pool);
-    if let Err(error) = host_repo
+    let role = match host_repo
         .assert_host_access(expected_host_id, ctx.user.id)
         .await
     {
-        return Err(match error {
-            IdentityError::PermissionDenied | IdentityError::NotFound => {
-                (StatusCode::FORBIDDEN, "Host access denied").into_response()
-            }
-            IdentityError::Database(db_error) => {
-                tracing::warn!(?db_error, "failed to validate host access");
-                StatusCode::INTERNAL_SERVER_ERROR.into_response()
-            }
-        });
+        Ok(role) => role,
+        Err(error) => {
+            return Err(match error {
+                IdentityError::PermissionDenied | IdentityError::NotFound => {
+                    (StatusCode::FORBIDDEN, "Host access denied").into_response()
+                }
+                IdentityError::Database(db_error) => {
+                    tracing::warn!(?db_error, "failed to validate host access");
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            });
+        }
+    };
+
+    let prefix = format!("{RELAY_PROXY_PREFIX}/{expected_host_id}/s/{relay_browser_session_id}");
+    let permitted = request
+        .uri()
+        .path()
+        .strip_prefix(&prefix)
+        .is_some_and(|local_path| role_permits_request(role, request.method(), local_path));
+    if !permitted {
+        return Err((StatusCode::FORBIDDEN, "Host is shared read-only").into_response());
     }
 
     if let Err(error) = relay_browser_session_repo
@@ -131,6 +167,30 @@ async fn validate_browser_session_for_host(
     Ok(())
 }
 
+/// Viewers may only read the frontend and the API routes in
+/// [`VIEWER_API_PREFIXES`].
+fn role_permits_request(role: RelayHostRole, method: &Method, local_path: &str) -> bool {
+    if role.can_write() {
+        return true;
+    }
+    if !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+    let local_path = if local_path.is_empty() {
+        "/"
+    } else {
+        local_path
+    };
+    if local_path != "/api" && !local_path.starts_with("/api/") {
+        return true;
+    }
+    VIEWER_API_PREFIXES.iter().any(|prefix| {
+        local_path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
 async fn do_relay_proxy_for_host(
     state: &RelayAppState,
     host_id: Uuid,
@@ -139,9 +199,97 @@ async fn do_relay_proxy_for_host(
 ) -> Response {
     let relay = match state.relay_registry.get(&host_id).await {
         Some(relay) => relay,
-        None => return (StatusCode::NOT_FOUND, "No active relay").into_response(),
+        None => return forward_to_peer(state, host_id, request).await,
     };
 
     let strip_prefix = format!("{RELAY_PROXY_PREFIX}/{host_id}/s/{browser_session_id}");
-    proxy_request_over_control(relay.control.as_ref(), request, &strip_prefix).await
+    let stream_guard = state.metrics.yamux_stream_opened();
+    let response = proxy_request_over_control(relay.control.as_ref(), request, &strip_prefix).await;
+    state.metrics.record_proxied_request(response.status());
+
+    // Keep the stream counted as active until the response body (which may be
+    // a long-lived SSE stream) has been fully sent or dropped.
+    let (parts, body) = response.into_parts();
+    let body = body.into_data_stream().map(move |chunk| {
+        let _ = &stream_guard;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
+/// Forward a request for a host whose control channel is held by another
+/// relay instance. The peer validates the browser session again.
+async fn forward_to_peer(state: &RelayAppState, host_id: Uuid, mut request: Request) -> Response {
+    let no_relay = || (StatusCode::NOT_FOUND, "No active relay").into_response();
+    if request.headers().contains_key(FORWARDED_HEADER) {
+        return no_relay();
+    }
+    let Some(peer_addr) = state.host_router.peer_for(host_id).await else {
+        return no_relay();
+    };
+
+    let stream = match TcpStream::connect(&peer_addr).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            tracing::warn!(?error, %host_id, %peer_addr, "failed to connect to relay peer");
+            return (StatusCode::BAD_GATEWAY, "Relay peer unreachable").into_response();
+        }
+    };
+    request
+        .headers_mut()
+        .insert(FORWARDED_HEADER, state.host_router.forwarded_header_value());
+    state.metrics.record_forwarded_request();
+    proxy_request_over_stream(stream, request, "").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewers_may_only_read_allow_listed_routes() {
+        let viewer = RelayHostRole::Viewer;
+        for path in [
+            "",
+            "/",
+            "/assets/index.js",
+            "/workspaces/123",
+            "/api/info",
+            "/api/workspaces",
+            "/api/workspaces/123/git/diff/ws",
+            "/api/execution-processes/123/normalized-logs/ws",
+            "/api/events/ws",
+        ] {
+            assert!(role_permits_request(viewer, &Method::GET, path), "{path}");
+        }
+        for path in [
+            "/api",
+            "/api/terminal/ws",
+            "/api/ssh-session",
+            "/api/preview/3000/",
+            "/api/config/info",
+            "/api/filesystem/directory",
+            "/api/workspaces-export",
+            "/api/unknown",
+        ] {
+            assert!(!role_permits_request(viewer, &Method::GET, path), "{path}");
+        }
+        assert!(!role_permits_request(
+            viewer,
+            &Method::POST,
+            "/api/workspaces"
+        ));
+        assert!(!role_permits_request(viewer, &Method::DELETE, "/"));
+    }
+
+    #[test]
+    fn operators_may_do_anything() {
+        for role in [RelayHostRole::Operator, RelayHostRole::Admin] {
+            assert!(role_permits_request(
+                role,
+                &Method::POST,
+                "/api/terminal/ws"
+            ));
+        }
+    }
 }
@@ -2,7 +2,14 @@ use std::sync::Arc;
 
 use sqlx::PgPool;
 
-use super::{auth::JwtService, config::RelayServerConfig, relay_registry::RelayRegistry};
+use super::{
+    access_log::AccessLogSampler,
+    auth::JwtService,
+    config::RelayServerConfig,
+    host_routing::HostRouter,
+    metrics::RelayMetrics,
+    relay_registry::{LongPollSessions, RelayRegistry},
+};
 
 #[derive(Clone)]
 pub struct RelayAppState {
@@ -10,15 +17,25 @@ pub struct RelayAppState {
     pub config: RelayServerConfig,
     pub jwt: Arc<JwtService>,
     pub relay_registry: RelayRegistry,
+    pub long_poll_sessions: LongPollSessions,
+    pub host_router: HostRouter,
+    pub metrics: Arc<RelayMetrics>,
+    pub access_log_sampler: Arc<AccessLogSampler>,
 }
 
 impl RelayAppState {
     pub fn new(pool: PgPool, config: RelayServerConfig, jwt: Arc<JwtService>) -> Self {
+        let access_log_sampler = Arc::new(AccessLogSampler::new(config.access_log_sample_rate));
+        let host_router = HostRouter::new(pool.clone(), config.instance_addr.clone());
         Self {
             pool,
             config,
             jwt,
             relay_registry: RelayRegistry::default(),
+            long_poll_sessions: LongPollSessions::default(),
+            host_router,
+            metrics: Arc::new(RelayMetrics::default()),
+            access_log_sampler,
         }
     }
 }
@@ -22,6 +22,63 @@ pub struct ListRelayHostsResponse {
     pub hosts: Vec<RelayHost>,
 }
 
+/// Access a user has to a relay host. Owners act as admins and members of
+/// the host's shared organization as operators.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type, TS,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "relay_host_role", rename_all = "lowercase")]
+pub enum RelayHostRole {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl RelayHostRole {
+    /// Combine every way a user can reach a host into the strongest role.
+    pub fn effective(
+        is_owner: bool,
+        is_organization_member: bool,
+        share_role: Option<RelayHostRole>,
+    ) -> Option<Self> {
+        if is_owner {
+            return Some(Self::Admin);
+        }
+        let organization_role = is_organization_member.then_some(Self::Operator);
+        organization_role.max(share_role)
+    }
+
+    /// Viewers may only read through the relay.
+    pub fn can_write(self) -> bool {
+        self >= Self::Operator
+    }
+
+    pub fn can_manage_shares(self) -> bool {
+        self == Self::Admin
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
+pub struct RelayHostShare {
+    pub host_id: Uuid,
+    pub user_id: Uuid,
+    pub role: RelayHostRole,
+    pub granted_by_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ListRelayHostSharesResponse {
+    pub shares: Vec<RelayHostShare>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct UpsertRelayHostShareRequest {
+    pub role: RelayHostRole,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct CreateRemoteSessionResponse {
     pub session_id: Uuid,
@@ -61,6 +118,14 @@ pub struct FinishSpake2EnrollmentRequest {
     pub client_device: String,
     pub public_key_b64: String,
     pub client_proof_b64: String,
+    /// X25519 key requesting end-to-end encryption for the new session.
+    #[serde(default)]
+    #[ts(optional)]
+    pub encryption_public_key_b64: Option<String>,
+    /// Client signature over `encryption_public_key_b64`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub encryption_key_signature_b64: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -68,6 +133,10 @@ pub struct FinishSpake2EnrollmentResponse {
     pub signing_session_id: Uuid,
     pub server_public_key_b64: String,
     pub server_proof_b64: String,
+    /// Server X25519 key, set when end-to-end encryption was negotiated.
+    pub encryption_public_key_b64: Option<String>,
+    /// Server signature over `encryption_public_key_b64`.
+    pub encryption_key_signature_b64: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -76,11 +145,41 @@ pub struct RefreshRelaySigningSessionRequest {
     pub timestamp: i64,
     pub nonce: String,
     pub signature_b64: String,
+    /// X25519 key requesting end-to-end encryption for the new session.
+    #[serde(default)]
+    #[ts(optional)]
+    pub encryption_public_key_b64: Option<String>,
+    /// Client signature over `encryption_public_key_b64`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub encryption_key_signature_b64: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct RefreshRelaySigningSessionResponse {
     pub signing_session_id: Uuid,
+    /// Server X25519 key, set when end-to-end encryption was negotiated.
+    pub encryption_public_key_b64: Option<String>,
+    /// Server signature over `encryption_public_key_b64`.
+    pub encryption_key_signature_b64: Option<String>,
+}
+
+/// Replace a paired client's key. `signature_b64` is by the current key and
+/// `new_key_signature_b64` by the new one, both over the rotation message.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RotateRelayClientKeyRequest {
+    pub client_id: Uuid,
+    pub timestamp: i64,
+    pub nonce: String,
+    pub new_public_key_b64: String,
+    pub signature_b64: String,
+    pub new_key_signature_b64: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RotateRelayClientKeyResponse {
+    /// Signing session for the new key; sessions of the old key are revoked.
+    pub signing_session_id: Uuid,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -130,3 +229,45 @@ pub struct ListRelayPairedClientsResponse {
 pub struct RemoveRelayPairedClientResponse {
     pub removed: bool,
 }
+
+/// Byte transport carrying the host's relay control channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayTunnelTransport {
+    #[serde(rename = "websocket")]
+    WebSocket,
+    LongPoll,
+}
+
+/// Coarse rating of the relay connection, for warning users that lag comes
+/// from the network rather than the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayConnectionQuality {
+    Good,
+    Degraded,
+    Poor,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RelayConnectionStatus {
+    /// Whether the relay is enabled and a session has been started.
+    pub active: bool,
+    pub connected: bool,
+    pub quality: RelayConnectionQuality,
+    pub transport: Option<RelayTunnelTransport>,
+    pub connected_since: Option<DateTime<Utc>>,
+    /// Round trip time of the most recent successful probe.
+    pub latency_ms: Option<u32>,
+    /// Mean round trip time over the recent probe window.
+    pub avg_latency_ms: Option<u32>,
+    /// Mean absolute difference between consecutive probe round trips.
+    pub jitter_ms: Option<u32>,
+    /// Share of recent probes that got no answer, between 0 and 1.
+    pub probe_loss: f32,
+    /// Current interval between keepalive probes.
+    pub keepalive_interval_secs: u32,
+    pub reconnects_last_hour: u32,
+    pub last_error: Option<String>,
+}
@@ -5,10 +5,15 @@
 //!
 //! Each frame is bound to the signing session, request nonce, a monotonic
 //! sequence number, the message type, and a SHA-256 hash of the payload.
+//! When the session negotiated end-to-end encryption the payload is
+//! encrypted first, so the signature covers the ciphertext.
 
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
-use relay_control::signing::{RelaySigningService, RequestSignature};
+use relay_control::{
+    encryption::SessionCipher,
+    signing::{RelaySigningService, RequestSignature},
+};
 use relay_protocol::{RelayWsFrame, RelayWsMessageType};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -21,14 +26,20 @@ pub(crate) struct WsFrameSigner {
     request_signature: RequestSignature,
     outbound_seq: u64,
     signing: RelaySigningService,
+    cipher: Option<SessionCipher>,
 }
 
 impl WsFrameSigner {
-    pub(crate) fn new(request_signature: &RequestSignature, signing: RelaySigningService) -> Self {
+    pub(crate) fn new(
+        request_signature: &RequestSignature,
+        signing: RelaySigningService,
+        cipher: Option<SessionCipher>,
+    ) -> Self {
         Self {
             request_signature: request_signature.clone(),
             outbound_seq: 0,
             signing,
+            cipher,
         }
     }
 
@@ -39,11 +50,18 @@ impl WsFrameSigner {
     /// then wraps everything into a versioned envelope.
     pub(crate) fn encode(&mut self, frame: RelayWsFrame) -> anyhow::Result<Vec<u8>> {
         self.outbound_seq = self.outbound_seq.saturating_add(1);
+        let payload = match &self.cipher {
+            Some(cipher) => cipher.encrypt(
+                &ws_frame_aad(&self.request_signature, self.outbound_seq, frame.msg_type),
+                &frame.payload,
+            )?,
+            None => frame.payload,
+        };
         let signing_input = ws_signing_input(
             &self.request_signature,
             self.outbound_seq,
             frame.msg_type,
-            &frame.payload,
+            &payload,
         );
         let signature = self.signing.sign_bytes(signing_input.as_bytes());
         let signature_b64 = BASE64_STANDARD.encode(signature.to_bytes());
@@ -51,8 +69,9 @@ impl WsFrameSigner {
             version: ENVELOPE_VERSION,
             seq: self.outbound_seq,
             msg_type: frame.msg_type,
-            payload_b64: BASE64_STANDARD.encode(frame.payload),
+            payload_b64: BASE64_STANDARD.encode(payload),
             signature_b64,
+            encrypted: self.cipher.is_some(),
         };
         serde_json::to_vec(&envelope).map_err(anyhow::Error::from)
     }
@@ -66,14 +85,20 @@ pub(crate) struct WsFrameVerifier {
     request_signature: RequestSignature,
     inbound_seq: u64,
     peer_verify_key: VerifyingKey,
+    cipher: Option<SessionCipher>,
 }
 
 impl WsFrameVerifier {
-    pub(crate) fn new(request_signature: &RequestSignature, peer_verify_key: VerifyingKey) -> Self {
+    pub(crate) fn new(
+        request_signature: &RequestSignature,
+        peer_verify_key: VerifyingKey,
+        cipher: Option<SessionCipher>,
+    ) -> Self {
         Self {
             request_signature: request_signature.clone(),
             inbound_seq: 0,
             peer_verify_key,
+            cipher,
         }
     }
 
@@ -117,6 +142,19 @@ impl WsFrameVerifier {
             .verify(signing_input.as_bytes(), &signature)
             .context("invalid relay WS frame signature")?;
 
+        // Both sides of an encrypted session must encrypt every frame.
+        let payload = match (&self.cipher, envelope.encrypted) {
+            (Some(cipher), true) => cipher
+                .decrypt(
+                    &ws_frame_aad(&self.request_signature, envelope.seq, envelope.msg_type),
+                    &payload,
+                )
+                .context("invalid relay WS frame ciphertext")?,
+            (None, false) => payload,
+            (Some(_), false) => anyhow::bail!("unencrypted relay WS frame on encrypted session"),
+            (None, true) => anyhow::bail!("encrypted relay WS frame on unencrypted session"),
+        };
+
         self.inbound_seq = envelope.seq;
         Ok(RelayWsFrame {
             msg_type: envelope.msg_type,
@@ -138,6 +176,18 @@ struct SignedWsEnvelope {
     msg_type: RelayWsMessageType,
     payload_b64: String,
     signature_b64: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    encrypted: bool,
+}
+
+fn ws_frame_aad(sig: &RequestSignature, seq: u64, msg_type: RelayWsMessageType) -> Vec<u8> {
+    format!(
+        "v1|{}|{}|{seq}|{}",
+        sig.signing_session_id,
+        sig.nonce,
+        msg_type.as_str()
+    )
+    .into_bytes()
 }
 
 fn ws_signing_input(
@@ -181,8 +231,8 @@ mod tests {
         let signing = RelaySigningService::new(signing_key);
         let sig = test_signature();
 
-        let mut signer = WsFrameSigner::new(&sig, signing);
-        let mut verifier = WsFrameVerifier::new(&sig, verify_key);
+        let mut signer = WsFrameSigner::new(&sig, signing, None);
+        let mut verifier = WsFrameVerifier::new(&sig, verify_key, None);
 
         let frame = RelayWsFrame {
             msg_type: RelayWsMessageType::Text,
@@ -202,8 +252,8 @@ mod tests {
         let signing = RelaySigningService::new(signing_key);
         let sig = test_signature();
 
-        let mut signer = WsFrameSigner::new(&sig, signing);
-        let mut verifier = WsFrameVerifier::new(&sig, verify_key);
+        let mut signer = WsFrameSigner::new(&sig, signing, None);
+        let mut verifier = WsFrameVerifier::new(&sig, verify_key, None);
 
         let frame1 = RelayWsFrame {
             msg_type: RelayWsMessageType::Binary,
@@ -223,6 +273,51 @@ mod tests {
         verifier.decode(&encoded2).expect("decode second");
     }
 
+    #[test]
+    fn roundtrip_encrypted_frames() {
+        let signing_key = SigningKey::generate(&mut rand::thread_rng());
+        let verify_key = signing_key.verifying_key();
+        let signing = RelaySigningService::new(signing_key);
+        let sig = test_signature();
+        let cipher = SessionCipher::from_key_bytes([7; 32]);
+
+        let mut signer = WsFrameSigner::new(&sig, signing, Some(cipher.clone()));
+        let mut verifier = WsFrameVerifier::new(&sig, verify_key, Some(cipher));
+
+        let frame = RelayWsFrame {
+            msg_type: RelayWsMessageType::Text,
+            payload: b"secret".to_vec(),
+        };
+        let encoded = signer.encode(frame).expect("encode");
+        let json_str = String::from_utf8(encoded.clone()).unwrap();
+        assert!(!json_str.contains(&BASE64_STANDARD.encode(b"secret")));
+
+        let decoded = verifier.decode(&encoded).expect("decode");
+        assert_eq!(decoded.payload, b"secret");
+    }
+
+    #[test]
+    fn decode_rejects_plaintext_on_encrypted_session() {
+        let signing_key = SigningKey::generate(&mut rand::thread_rng());
+        let verify_key = signing_key.verifying_key();
+        let signing = RelaySigningService::new(signing_key);
+        let sig = test_signature();
+
+        let mut signer = WsFrameSigner::new(&sig, signing, None);
+        let mut verifier = WsFrameVerifier::new(
+            &sig,
+            verify_key,
+            Some(SessionCipher::from_key_bytes([7; 32])),
+        );
+
+        let frame = RelayWsFrame {
+            msg_type: RelayWsMessageType::Text,
+            payload: b"hello".to_vec(),
+        };
+        let encoded = signer.encode(frame).expect("encode");
+        assert!(verifier.decode(&encoded).is_err());
+    }
+
     #[test]
     fn decode_rejects_tampered_payload() {
         let signing_key = SigningKey::generate(&mut rand::thread_rng());
@@ -230,8 +325,8 @@ mod tests {
         let signing = RelaySigningService::new(signing_key);
         let sig = test_signature();
 
-        let mut signer = WsFrameSigner::new(&sig, signing);
-        let mut verifier = WsFrameVerifier::new(&sig, verify_key);
+        let mut signer = WsFrameSigner::new(&sig, signing, None);
+        let mut verifier = WsFrameVerifier::new(&sig, verify_key, None);
 
         let frame = RelayWsFrame {
             msg_type: RelayWsMessageType::Text,
@@ -45,10 +45,13 @@ impl<S, M> SignedWebSocket<S, M> {
                     request_signature.signing_session_id
                 )
             })?;
+        let cipher = signing
+            .session_cipher(request_signature.signing_session_id)
+            .await;
         Ok(Self {
             ws,
-            signer: WsFrameSigner::new(request_signature, signing.clone()),
-            verifier: WsFrameVerifier::new(request_signature, peer_verify_key),
+            signer: WsFrameSigner::new(request_signature, signing.clone(), cipher.clone()),
+            verifier: WsFrameVerifier::new(request_signature, peer_verify_key, cipher),
             _message: PhantomData,
         })
     }
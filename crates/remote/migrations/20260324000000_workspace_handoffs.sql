@@ -0,0 +1,16 @@
+-- Workspaces handed off from one local instance to another owned by the same user.
+-- The payload carries the pushed branches, transcript and queued messages; a
+-- handoff can be claimed exactly once.
+CREATE TABLE workspace_handoffs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source_local_workspace_id UUID NOT NULL,
+    payload JSONB NOT NULL,
+    claimed_at TIMESTAMPTZ,
+    claimed_local_workspace_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_workspace_handoffs_owner_unclaimed
+    ON workspace_handoffs(owner_user_id, created_at DESC)
+    WHERE claimed_at IS NULL;
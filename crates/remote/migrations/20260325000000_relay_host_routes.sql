@@ -0,0 +1,11 @@
+-- Which relay server instance holds each host's control channel, so that an
+-- instance without the channel can forward browser traffic to the one that
+-- has it. Rows are refreshed by a heartbeat and ignored once stale.
+CREATE TABLE relay_host_routes (
+    host_id UUID PRIMARY KEY REFERENCES hosts(id) ON DELETE CASCADE,
+    instance_id UUID NOT NULL,
+    instance_addr TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_relay_host_routes_instance_id ON relay_host_routes(instance_id);
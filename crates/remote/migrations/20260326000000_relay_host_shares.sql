@@ -0,0 +1,16 @@
+-- Per-user access to a relay host beyond its owner and shared organization.
+-- Viewers may only read through the relay, operators have full relay access,
+-- and admins can additionally manage the host's shares.
+CREATE TYPE relay_host_role AS ENUM ('viewer', 'operator', 'admin');
+
+CREATE TABLE relay_host_shares (
+    host_id UUID NOT NULL REFERENCES hosts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role relay_host_role NOT NULL,
+    granted_by_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (host_id, user_id)
+);
+
+CREATE INDEX idx_relay_host_shares_user_id ON relay_host_shares(user_id);
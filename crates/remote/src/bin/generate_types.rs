@@ -14,7 +14,10 @@ use api_types::{
     UpdateNotificationRequest, UpdateProjectRequest, UpdateProjectStatusRequest, UpdateTagRequest,
     User, UserData, Workspace,
 };
-use relay_types::{CreateRemoteSessionResponse, ListRelayHostsResponse, RelayHost};
+use relay_types::{
+    CreateRemoteSessionResponse, ListRelayHostSharesResponse, ListRelayHostsResponse, RelayHost,
+    RelayHostRole, RelayHostShare, UpsertRelayHostShareRequest,
+};
 use remote::{
     routes::{
         all_mutation_definitions,
@@ -106,6 +109,10 @@ fn export_shapes() -> String {
         User::decl(),
         RelayHost::decl(),
         ListRelayHostsResponse::decl(),
+        RelayHostRole::decl(),
+        RelayHostShare::decl(),
+        ListRelayHostSharesResponse::decl(),
+        UpsertRelayHostShareRequest::decl(),
         CreateRemoteSessionResponse::decl(),
         MemberRole::decl(),
         OrganizationMember::decl(),
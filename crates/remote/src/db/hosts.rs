@@ -1,4 +1,4 @@
-use relay_types::RelayHost;
+use relay_types::{RelayHost, RelayHostRole, RelayHostShare};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -30,13 +30,18 @@ impl<'a> HostRepository<'a> {
                 h.updated_at,
                 CASE
                     WHEN h.owner_user_id = $1 THEN 'owner'
-                    ELSE 'member'
+                    WHEN s.role = 'admin' THEN 'admin'
+                    WHEN om.user_id IS NOT NULL THEN 'member'
+                    ELSE s.role::TEXT
                 END AS "access_role!"
             FROM hosts h
             LEFT JOIN organization_member_metadata om
                 ON om.organization_id = h.shared_with_organization_id
                 AND om.user_id = $1
-            WHERE h.owner_user_id = $1 OR om.user_id IS NOT NULL
+            LEFT JOIN relay_host_shares s
+                ON s.host_id = h.id
+                AND s.user_id = $1
+            WHERE h.owner_user_id = $1 OR om.user_id IS NOT NULL OR s.user_id IS NOT NULL
             ORDER BY h.updated_at DESC
             "#,
             user_id
@@ -44,4 +49,108 @@ impl<'a> HostRepository<'a> {
         .fetch_all(self.pool)
         .await
     }
+
+    /// The user's role on the host, or `None` if the host does not exist or
+    /// the user cannot reach it.
+    pub async fn access_role(
+        &self,
+        host_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<RelayHostRole>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                h.owner_user_id = $2 AS "is_owner!",
+                om.user_id IS NOT NULL AS "is_organization_member!",
+                s.role AS "share_role?: RelayHostRole"
+            FROM hosts h
+            LEFT JOIN organization_member_metadata om
+                ON om.organization_id = h.shared_with_organization_id
+                AND om.user_id = $2
+            LEFT JOIN relay_host_shares s
+                ON s.host_id = h.id
+                AND s.user_id = $2
+            WHERE h.id = $1
+            "#,
+            host_id,
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.and_then(|row| {
+            RelayHostRole::effective(row.is_owner, row.is_organization_member, row.share_role)
+        }))
+    }
+
+    pub async fn owner_user_id(&self, host_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT owner_user_id FROM hosts WHERE id = $1"#, host_id)
+            .fetch_optional(self.pool)
+            .await
+    }
+
+    pub async fn list_shares(&self, host_id: Uuid) -> Result<Vec<RelayHostShare>, sqlx::Error> {
+        sqlx::query_as!(
+            RelayHostShare,
+            r#"
+            SELECT
+                host_id,
+                user_id,
+                role AS "role!: RelayHostRole",
+                granted_by_user_id,
+                created_at,
+                updated_at
+            FROM relay_host_shares
+            WHERE host_id = $1
+            ORDER BY created_at
+            "#,
+            host_id
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    pub async fn upsert_share(
+        &self,
+        host_id: Uuid,
+        user_id: Uuid,
+        role: RelayHostRole,
+        granted_by_user_id: Uuid,
+    ) -> Result<RelayHostShare, sqlx::Error> {
+        sqlx::query_as!(
+            RelayHostShare,
+            r#"
+            INSERT INTO relay_host_shares (host_id, user_id, role, granted_by_user_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (host_id, user_id) DO UPDATE
+                SET role = EXCLUDED.role,
+                    granted_by_user_id = EXCLUDED.granted_by_user_id,
+                    updated_at = NOW()
+            RETURNING
+                host_id,
+                user_id,
+                role AS "role!: RelayHostRole",
+                granted_by_user_id,
+                created_at,
+                updated_at
+            "#,
+            host_id,
+            user_id,
+            role as RelayHostRole,
+            granted_by_user_id
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    pub async fn delete_share(&self, host_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM relay_host_shares WHERE host_id = $1 AND user_id = $2"#,
+            host_id,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 }
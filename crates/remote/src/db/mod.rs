@@ -30,6 +30,7 @@ pub mod reviews;
 pub mod tags;
 pub mod types;
 pub mod users;
+pub mod workspace_handoffs;
 pub mod workspaces;
 
 use sqlx::{
@@ -75,6 +75,30 @@ where
     Ok(exists)
 }
 
+/// Whether two users belong to at least one common organization.
+pub(crate) async fn share_organization(
+    pool: &PgPool,
+    user_id: Uuid,
+    other_user_id: Uuid,
+) -> Result<bool, IdentityError> {
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM organization_member_metadata a
+            JOIN organization_member_metadata b ON b.organization_id = a.organization_id
+            WHERE a.user_id = $1 AND b.user_id = $2
+        ) AS "exists!"
+        "#,
+        user_id,
+        other_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
 pub(crate) async fn assert_membership(
     pool: &PgPool,
     organization_id: Uuid,
@@ -0,0 +1,140 @@
+use api_types::{WorkspaceHandoff, WorkspaceHandoffPayload};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, types::Json};
+use uuid::Uuid;
+
+#[derive(Debug)]
+struct WorkspaceHandoffRow {
+    id: Uuid,
+    owner_user_id: Uuid,
+    source_local_workspace_id: Uuid,
+    payload: Json<WorkspaceHandoffPayload>,
+    claimed_at: Option<DateTime<Utc>>,
+    claimed_local_workspace_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<WorkspaceHandoffRow> for WorkspaceHandoff {
+    fn from(row: WorkspaceHandoffRow) -> Self {
+        Self {
+            id: row.id,
+            owner_user_id: row.owner_user_id,
+            source_local_workspace_id: row.source_local_workspace_id,
+            payload: row.payload.0,
+            claimed_at: row.claimed_at,
+            claimed_local_workspace_id: row.claimed_local_workspace_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+pub struct WorkspaceHandoffRepository;
+
+impl WorkspaceHandoffRepository {
+    pub async fn create(
+        pool: &PgPool,
+        owner_user_id: Uuid,
+        source_local_workspace_id: Uuid,
+        payload: &WorkspaceHandoffPayload,
+    ) -> Result<WorkspaceHandoff, sqlx::Error> {
+        let row = sqlx::query_as!(
+            WorkspaceHandoffRow,
+            r#"
+            INSERT INTO workspace_handoffs (owner_user_id, source_local_workspace_id, payload)
+            VALUES ($1, $2, $3)
+            RETURNING
+                id                          AS "id!: Uuid",
+                owner_user_id               AS "owner_user_id!: Uuid",
+                source_local_workspace_id   AS "source_local_workspace_id!: Uuid",
+                payload                     AS "payload!: Json<WorkspaceHandoffPayload>",
+                claimed_at                  AS "claimed_at?: DateTime<Utc>",
+                claimed_local_workspace_id  AS "claimed_local_workspace_id?: Uuid",
+                created_at                  AS "created_at!: DateTime<Utc>"
+            "#,
+            owner_user_id,
+            source_local_workspace_id,
+            Json(payload) as Json<&WorkspaceHandoffPayload>
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    pub async fn list_unclaimed(
+        pool: &PgPool,
+        owner_user_id: Uuid,
+    ) -> Result<Vec<WorkspaceHandoff>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            WorkspaceHandoffRow,
+            r#"
+            SELECT
+                id                          AS "id!: Uuid",
+                owner_user_id               AS "owner_user_id!: Uuid",
+                source_local_workspace_id   AS "source_local_workspace_id!: Uuid",
+                payload                     AS "payload!: Json<WorkspaceHandoffPayload>",
+                claimed_at                  AS "claimed_at?: DateTime<Utc>",
+                claimed_local_workspace_id  AS "claimed_local_workspace_id?: Uuid",
+                created_at                  AS "created_at!: DateTime<Utc>"
+            FROM workspace_handoffs
+            WHERE owner_user_id = $1 AND claimed_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+            owner_user_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Marks the handoff as claimed. Returns `None` if it does not exist, is
+    /// owned by someone else, or was already claimed, so two instances racing
+    /// for the same handoff cannot both import it.
+    pub async fn claim(
+        pool: &PgPool,
+        id: Uuid,
+        owner_user_id: Uuid,
+        local_workspace_id: Uuid,
+    ) -> Result<Option<WorkspaceHandoff>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            WorkspaceHandoffRow,
+            r#"
+            UPDATE workspace_handoffs
+            SET claimed_at = NOW(), claimed_local_workspace_id = $3
+            WHERE id = $1 AND owner_user_id = $2 AND claimed_at IS NULL
+            RETURNING
+                id                          AS "id!: Uuid",
+                owner_user_id               AS "owner_user_id!: Uuid",
+                source_local_workspace_id   AS "source_local_workspace_id!: Uuid",
+                payload                     AS "payload!: Json<WorkspaceHandoffPayload>",
+                claimed_at                  AS "claimed_at?: DateTime<Utc>",
+                claimed_local_workspace_id  AS "claimed_local_workspace_id?: Uuid",
+                created_at                  AS "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            owner_user_id,
+            local_workspace_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Deletes an unclaimed handoff. Returns whether a row was removed.
+    pub async fn delete_unclaimed(
+        pool: &PgPool,
+        id: Uuid,
+        owner_user_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM workspace_handoffs
+            WHERE id = $1 AND owner_user_id = $2 AND claimed_at IS NULL
+            "#,
+            id,
+            owner_user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
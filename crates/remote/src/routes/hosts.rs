@@ -1,16 +1,30 @@
 use axum::{
     Json, Router,
-    extract::{Extension, State},
+    extract::{Extension, Path, State},
     http::StatusCode,
-    routing::get,
+    routing::{get, put},
 };
-use relay_types::ListRelayHostsResponse;
+use relay_types::{
+    ListRelayHostSharesResponse, ListRelayHostsResponse, RelayHostShare,
+    UpsertRelayHostShareRequest,
+};
+use uuid::Uuid;
 
-use super::error::ErrorResponse;
-use crate::{AppState, auth::RequestContext, db::hosts::HostRepository};
+use super::error::{ErrorResponse, db_error};
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::{hosts::HostRepository, organization_members::share_organization},
+};
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/hosts", get(list_hosts))
+    Router::new()
+        .route("/hosts", get(list_hosts))
+        .route("/hosts/{host_id}/shares", get(list_host_shares))
+        .route(
+            "/hosts/{host_id}/shares/{user_id}",
+            put(upsert_host_share).delete(delete_host_share),
+        )
 }
 
 async fn list_hosts(
@@ -28,3 +42,118 @@ async fn list_hosts(
 
     Ok(Json(ListRelayHostsResponse { hosts }))
 }
+
+async fn list_host_shares(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(host_id): Path<Uuid>,
+) -> Result<Json<ListRelayHostSharesResponse>, ErrorResponse> {
+    let repo = HostRepository::new(state.pool());
+    ensure_can_manage_shares(&repo, host_id, ctx.user.id).await?;
+
+    let shares = repo.list_shares(host_id).await.map_err(|error| {
+        tracing::warn!(?error, "failed to list relay host shares");
+        ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to list host shares",
+        )
+    })?;
+
+    Ok(Json(ListRelayHostSharesResponse { shares }))
+}
+
+async fn upsert_host_share(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((host_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpsertRelayHostShareRequest>,
+) -> Result<Json<RelayHostShare>, ErrorResponse> {
+    let repo = HostRepository::new(state.pool());
+    ensure_can_manage_shares(&repo, host_id, ctx.user.id).await?;
+
+    let owner_user_id = repo
+        .owner_user_id(host_id)
+        .await
+        .map_err(|error| db_error(error, "Failed to share host"))?;
+    if owner_user_id == Some(user_id) {
+        return Err(ErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            "The host owner already has full access",
+        ));
+    }
+
+    // Hosts can only be shared with teammates.
+    let is_teammate = share_organization(state.pool(), ctx.user.id, user_id)
+        .await
+        .map_err(|error| {
+            tracing::warn!(?error, "failed to check organization membership");
+            ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to share host")
+        })?;
+    if !is_teammate {
+        return Err(ErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            "Hosts can only be shared with members of your organizations",
+        ));
+    }
+
+    let share = repo
+        .upsert_share(host_id, user_id, payload.role, ctx.user.id)
+        .await
+        .map_err(|error| {
+            tracing::warn!(?error, "failed to share relay host");
+            db_error(error, "Failed to share host")
+        })?;
+
+    Ok(Json(share))
+}
+
+async fn delete_host_share(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((host_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ErrorResponse> {
+    let repo = HostRepository::new(state.pool());
+    // Anyone may give up a share they were granted.
+    if user_id != ctx.user.id {
+        ensure_can_manage_shares(&repo, host_id, ctx.user.id).await?;
+    }
+
+    let deleted = repo.delete_share(host_id, user_id).await.map_err(|error| {
+        tracing::warn!(?error, "failed to remove relay host share");
+        ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to remove host share",
+        )
+    })?;
+
+    if !deleted {
+        return Err(ErrorResponse::new(
+            StatusCode::NOT_FOUND,
+            "Host share not found",
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn ensure_can_manage_shares(
+    repo: &HostRepository<'_>,
+    host_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), ErrorResponse> {
+    let role = repo.access_role(host_id, user_id).await.map_err(|error| {
+        tracing::warn!(?error, "failed to check relay host access");
+        ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to check host access",
+        )
+    })?;
+
+    match role {
+        Some(role) if role.can_manage_shares() => Ok(()),
+        Some(_) => Err(ErrorResponse::new(
+            StatusCode::FORBIDDEN,
+            "Only host admins can manage sharing",
+        )),
+        None => Err(ErrorResponse::new(StatusCode::NOT_FOUND, "Host not found")),
+    }
+}
@@ -50,6 +50,7 @@ mod pull_requests;
 mod review;
 pub mod tags;
 mod tokens;
+mod workspace_handoffs;
 mod workspaces;
 
 pub fn router(state: AppState) -> Router {
@@ -132,6 +133,7 @@ pub fn router(state: AppState) -> Router {
         .merge(pull_requests::router())
         .merge(notifications::router())
         .merge(workspaces::router())
+        .merge(workspace_handoffs::router())
         .merge(billing::protected_router())
         .merge(export::router())
         .layer(middleware::from_fn_with_state(
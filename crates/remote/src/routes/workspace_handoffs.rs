@@ -0,0 +1,140 @@
+use api_types::{
+    ClaimWorkspaceHandoffRequest, CreateWorkspaceHandoffRequest, ListWorkspaceHandoffsResponse,
+    WorkspaceHandoff,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::error::{ErrorResponse, db_error};
+use crate::{AppState, auth::RequestContext, db::workspace_handoffs::WorkspaceHandoffRepository};
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/workspace-handoffs",
+            get(list_handoffs).post(create_handoff),
+        )
+        .route("/workspace-handoffs/{handoff_id}", delete(cancel_handoff))
+        .route(
+            "/workspace-handoffs/{handoff_id}/claim",
+            post(claim_handoff),
+        )
+}
+
+#[instrument(
+    name = "workspace_handoffs.create_handoff",
+    skip(state, ctx, payload),
+    fields(source_local_workspace_id = %payload.source_local_workspace_id, user_id = %ctx.user.id)
+)]
+async fn create_handoff(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<CreateWorkspaceHandoffRequest>,
+) -> Result<Json<WorkspaceHandoff>, ErrorResponse> {
+    let handoff = WorkspaceHandoffRepository::create(
+        state.pool(),
+        ctx.user.id,
+        payload.source_local_workspace_id,
+        &payload.payload,
+    )
+    .await
+    .map_err(|error| {
+        tracing::error!(?error, "failed to create workspace handoff");
+        db_error(error, "failed to create workspace handoff")
+    })?;
+
+    Ok(Json(handoff))
+}
+
+#[instrument(
+    name = "workspace_handoffs.list_handoffs",
+    skip(state, ctx),
+    fields(user_id = %ctx.user.id)
+)]
+async fn list_handoffs(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+) -> Result<Json<ListWorkspaceHandoffsResponse>, ErrorResponse> {
+    let handoffs = WorkspaceHandoffRepository::list_unclaimed(state.pool(), ctx.user.id)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to list workspace handoffs");
+            ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to list workspace handoffs",
+            )
+        })?;
+
+    Ok(Json(ListWorkspaceHandoffsResponse { handoffs }))
+}
+
+#[instrument(
+    name = "workspace_handoffs.claim_handoff",
+    skip(state, ctx, payload),
+    fields(handoff_id = %handoff_id, user_id = %ctx.user.id)
+)]
+async fn claim_handoff(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(handoff_id): Path<Uuid>,
+    Json(payload): Json<ClaimWorkspaceHandoffRequest>,
+) -> Result<Json<WorkspaceHandoff>, ErrorResponse> {
+    let handoff = WorkspaceHandoffRepository::claim(
+        state.pool(),
+        handoff_id,
+        ctx.user.id,
+        payload.local_workspace_id,
+    )
+    .await
+    .map_err(|error| {
+        tracing::error!(?error, "failed to claim workspace handoff");
+        ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to claim workspace handoff",
+        )
+    })?
+    .ok_or_else(|| {
+        ErrorResponse::new(
+            StatusCode::CONFLICT,
+            "workspace handoff not found or already claimed",
+        )
+    })?;
+
+    Ok(Json(handoff))
+}
+
+#[instrument(
+    name = "workspace_handoffs.cancel_handoff",
+    skip(state, ctx),
+    fields(handoff_id = %handoff_id, user_id = %ctx.user.id)
+)]
+async fn cancel_handoff(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(handoff_id): Path<Uuid>,
+) -> Result<StatusCode, ErrorResponse> {
+    let deleted =
+        WorkspaceHandoffRepository::delete_unclaimed(state.pool(), handoff_id, ctx.user.id)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to cancel workspace handoff");
+                ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to cancel workspace handoff",
+                )
+            })?;
+
+    if !deleted {
+        return Err(ErrorResponse::new(
+            StatusCode::CONFLICT,
+            "workspace handoff not found or already claimed",
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -14,14 +14,62 @@ fn generate_types_content() -> String {
         db::models::repo::Repo::decl(),
         db::models::project::Project::decl(),
         db::models::repo::UpdateRepo::decl(),
+        db::models::repo_resource_limit::RepoResourceLimits::decl(),
+        db::models::repo_resource_limit::SetRepoResourceLimits::decl(),
+        db::models::remote_execution::RemoteExecution::decl(),
+        db::models::repo_container_image::RepoContainerImage::decl(),
+        db::models::repo_container_image::SetRepoContainerImage::decl(),
+        db::models::repo_protected_path::ProtectedPathAction::decl(),
+        db::models::repo_protected_path::RepoProtectedPath::decl(),
+        db::models::repo_protected_path::CreateRepoProtectedPath::decl(),
+        db::models::repo_check_command::RepoCheckCommand::decl(),
+        db::models::repo_check_command::RepoCheckCommandInput::decl(),
+        db::models::repo_check_command::SetRepoCheckCommands::decl(),
+        db::models::repo_issue_tracker::IssueTrackerKind::decl(),
+        db::models::repo_issue_tracker::RepoIssueTracker::decl(),
+        db::models::repo_issue_tracker::SetRepoIssueTracker::decl(),
+        db::models::repo_resource_tag::ResourceTag::decl(),
+        db::models::repo_resource_tag::RepoResourceTags::decl(),
+        db::models::repo_resource_tag::SetRepoResourceTags::decl(),
+        db::models::repo_setup_cache::RepoSetupCache::decl(),
+        db::models::repo_setup_cache::SetRepoSetupCache::decl(),
         db::models::repo::SearchResult::decl(),
         db::models::repo::SearchMatchType::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
         db::models::workspace_repo::CreateWorkspaceRepo::decl(),
         db::models::workspace_repo::RepoWithTargetBranch::decl(),
+        db::models::doc_source::DocSource::decl(),
+        db::models::doc_source::DocSourceKind::decl(),
+        db::models::doc_source::CreateDocSource::decl(),
+        db::models::doc_source::DocChunk::decl(),
+        services::services::doc_index::DocSearchHit::decl(),
+        services::services::package_manager::PackageManager::decl(),
+        services::services::package_manager::ScriptSuggestions::decl(),
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
+        db::models::task_template::TaskTemplate::decl(),
+        db::models::task_template::CreateTaskTemplate::decl(),
+        db::models::task_template::UpdateTaskTemplate::decl(),
+        db::models::approval_notification_rule::ToolCategory::decl(),
+        db::models::approval_notification_rule::NotificationChannel::decl(),
+        db::models::approval_notification_rule::ApprovalNotificationRule::decl(),
+        db::models::approval_notification_rule::CreateApprovalNotificationRule::decl(),
+        db::models::approval_notification_rule::UpdateApprovalNotificationRule::decl(),
+        db::models::audit_log::AuditAction::decl(),
+        db::models::audit_log::AuditActorKind::decl(),
+        db::models::audit_log::AuditLogEntry::decl(),
+        db::models::audit_log::AuditLogQuery::decl(),
+        db::models::board::Board::decl(),
+        db::models::board::BoardColumn::decl(),
+        db::models::board::BoardCard::decl(),
+        db::models::board::BoardState::decl(),
+        db::models::board::CreateBoard::decl(),
+        db::models::board::UpdateBoard::decl(),
+        db::models::board::CreateBoardColumn::decl(),
+        db::models::board::UpdateBoardColumn::decl(),
+        db::models::board::ReorderBoardColumns::decl(),
+        db::models::board::MoveBoardCard::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::DraftWorkspaceData::decl(),
         db::models::scratch::DraftWorkspaceAttachment::decl(),
@@ -46,7 +94,24 @@ fn generate_types_content() -> String {
         db::models::workspace::Workspace::decl(),
         db::models::workspace::WorkspaceWithStatus::decl(),
         db::models::session::Session::decl(),
+        db::models::session_timeout::ExecutionTimeouts::decl(),
+        db::models::session_timeout::SessionTimeout::decl(),
+        server::routes::sessions::timeouts::SessionTimeoutsResponse::decl(),
+        db::models::session_plan::SessionPlanStatus::decl(),
+        db::models::session_plan::SessionPlan::decl(),
+        server::routes::sessions::plan::RejectPlanRequest::decl(),
+        db::models::session_question::SessionQuestionStatus::decl(),
+        db::models::session_question::SessionQuestion::decl(),
+        db::models::session_share_link::SessionShareLink::decl(),
+        server::routes::sessions::share::CreateShareLinkRequest::decl(),
+        server::routes::sessions::share::CreatedShareLink::decl(),
+        server::routes::sessions::resume::InterruptedRun::decl(),
+        server::routes::sessions::resume::ResumeSessionRequest::decl(),
+        server::routes::sessions::queue::UpdateQueuedMessageRequest::decl(),
+        server::routes::sessions::queue::ReorderQueuedMessagesRequest::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
+        db::models::execution_log_search::LogStream::decl(),
+        db::models::execution_log_search::LogSearchHit::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
@@ -55,14 +120,27 @@ fn generate_types_content() -> String {
         db::models::merge::PrMerge::decl(),
         db::models::merge::MergeStatus::decl(),
         db::models::merge::PullRequestInfo::decl(),
+        utils::approvals::ApprovalToolInput::decl(),
+        utils::approvals::ApprovalQuestion::decl(),
+        utils::approvals::ApprovalQuestionOption::decl(),
         services::services::approvals::ApprovalInfo::decl(),
         utils::approvals::ApprovalStatus::decl(),
         utils::approvals::QuestionAnswer::decl(),
         utils::approvals::QuestionStatus::decl(),
         utils::approvals::ApprovalOutcome::decl(),
         utils::approvals::ApprovalResponse::decl(),
+        server::routes::approvals::BatchApprovalItem::decl(),
+        server::routes::approvals::BatchApprovalRequest::decl(),
+        server::routes::approvals::BatchApprovalResult::decl(),
+        server::routes::approvals::DelegateApprovalRequest::decl(),
+        server::routes::approvals::ApprovalDelegation::decl(),
+        server::routes::approvals::DelegatedApprovalResponse::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
+        utils::diff::DiffHunk::decl(),
+        utils::log_msg::LogSpan::decl(),
+        utils::log_msg::LogSpanStatus::decl(),
+        utils::response::ApiErrorCode::decl(),
         utils::response::ApiResponse::<()>::decl(),
         api_types::LoginStatus::decl(),
         api_types::ProfileResponse::decl(),
@@ -102,6 +180,8 @@ fn generate_types_content() -> String {
         server::routes::config::CheckEditorAvailabilityResponse::decl(),
         server::routes::config::CheckAgentAvailabilityQuery::decl(),
         server::routes::config::AgentPresetOptionsQuery::decl(),
+        server::routes::config_profiles::ConfigProfiles::decl(),
+        server::routes::config_profiles::ConfigProfileRequest::decl(),
         server::routes::oauth::CurrentUserResponse::decl(),
         relay_types::StartSpake2EnrollmentRequest::decl(),
         relay_types::FinishSpake2EnrollmentRequest::decl(),
@@ -112,8 +192,17 @@ fn generate_types_content() -> String {
         relay_types::RemoveRelayPairedClientResponse::decl(),
         relay_types::RefreshRelaySigningSessionRequest::decl(),
         relay_types::RefreshRelaySigningSessionResponse::decl(),
+        relay_types::RotateRelayClientKeyRequest::decl(),
+        relay_types::RotateRelayClientKeyResponse::decl(),
+        relay_types::RelayTunnelTransport::decl(),
+        relay_types::RelayConnectionQuality::decl(),
+        relay_types::RelayConnectionStatus::decl(),
         server::routes::sessions::CreateFollowUpAttempt::decl(),
         server::routes::sessions::ResetProcessRequest::decl(),
+        server::routes::prompt_snippets::RenderPromptSnippetRequest::decl(),
+        server::routes::provider_credentials::SetProviderCredentialRequest::decl(),
+        server::routes::notification_rules::TestApprovalRouteRequest::decl(),
+        services::services::notification_routing::ApprovalRoute::decl(),
         server::routes::workspaces::git::ChangeTargetBranchRequest::decl(),
         server::routes::workspaces::git::ChangeTargetBranchResponse::decl(),
         server::routes::workspaces::repos::AddWorkspaceRepoRequest::decl(),
@@ -122,12 +211,80 @@ fn generate_types_content() -> String {
         server::routes::workspaces::git::PushWorkspaceRequest::decl(),
         server::routes::workspaces::git::RenameBranchRequest::decl(),
         server::routes::workspaces::git::RenameBranchResponse::decl(),
+        server::routes::hooks::WebhookTaskRequest::decl(),
+        services::services::ci_webhook::GenericCiEvent::decl(),
+        services::services::issue_tracker::TrackerIssue::decl(),
+        utils::alloc_stats::AllocStats::decl(),
+        services::services::shared_watcher::WatcherHubStats::decl(),
+        services::services::events::EntityChecksum::decl(),
+        services::services::events::EventsCheckpoint::decl(),
+        server::routes::workspaces::drift::RepairWorkspaceDriftRequest::decl(),
+        server::routes::workspaces::drift::RepairWorkspaceDriftResponse::decl(),
+        server::routes::workspaces::drift::WorkspaceRepairFailure::decl(),
+        server::routes::workspaces::bulk::BulkWorkspaceAction::decl(),
+        server::routes::workspaces::bulk::BulkWorkspaceRequest::decl(),
+        server::routes::workspaces::bulk::BulkItemStatus::decl(),
+        server::routes::workspaces::bulk::BulkWorkspaceItemResult::decl(),
+        server::routes::workspaces::bulk::BulkWorkspaceResponse::decl(),
+        server::routes::workspaces::hunks::HunkDecision::decl(),
+        server::routes::workspaces::hunks::ReviewHunksRequest::decl(),
+        server::routes::workspaces::hunks::ReviewHunksResponse::decl(),
+        db::models::hunk_review::HunkReviewAction::decl(),
+        db::models::hunk_review::HunkReviewDecision::decl(),
+        workspace_manager::WorkspaceDriftKind::decl(),
+        workspace_manager::WorkspaceDrift::decl(),
+        workspace_manager::WorkspaceDriftReport::decl(),
         server::routes::sessions::review::StartReviewRequest::decl(),
         server::routes::sessions::review::ReviewError::decl(),
+        server::routes::sessions::explain::ExplainChangeRequest::decl(),
+        db::models::change_explanation::RiskSeverity::decl(),
+        db::models::change_explanation::FileRationale::decl(),
+        db::models::change_explanation::RiskCallout::decl(),
+        db::models::change_explanation::ChangeExplanationContent::decl(),
+        db::models::change_explanation::ChangeExplanation::decl(),
+        api_types::WorkspaceHandoffRepo::decl(),
+        api_types::WorkspaceHandoffTurn::decl(),
+        api_types::WorkspaceHandoffQueuedMessage::decl(),
+        api_types::WorkspaceHandoffPayload::decl(),
+        api_types::WorkspaceHandoff::decl(),
+        api_types::ListWorkspaceHandoffsResponse::decl(),
+        db::models::workspace_handoff::WorkspaceHandoffDirection::decl(),
+        db::models::workspace_handoff::WorkspaceHandoffLink::decl(),
+        db::models::workspace_dev_server::PreviewScriptInjection::decl(),
+        db::models::workspace_dev_server::WorkspaceDevServer::decl(),
+        db::models::workspace_dev_server::UpdateWorkspaceDevServer::decl(),
+        db::models::workspace_repo_command::RepoCommandMode::decl(),
+        db::models::workspace_repo_command::RepoCommandStepStatus::decl(),
+        db::models::workspace_repo_command::RepoCommandRepoStatus::decl(),
+        db::models::workspace_repo_command::RepoCommandStatus::decl(),
+        db::models::workspace_secret_finding::WorkspaceSecretFinding::decl(),
+        db::models::workspace_child::WorkspaceChild::decl(),
+        db::models::workspace_child::ChildWorkspaceProgress::decl(),
+        db::models::workspace_branch_freshness::AutoRebaseStatus::decl(),
+        db::models::workspace_branch_freshness::WorkspaceBranchFreshness::decl(),
+        db::models::workspace_check_result::WorkspaceCheckStatus::decl(),
+        db::models::workspace_check_result::WorkspaceCheckResult::decl(),
+        db::models::workspace_ci_check::CiState::decl(),
+        db::models::workspace_ci_check::WorkspaceCiCheck::decl(),
+        db::models::workspace_ci_check::WorkspaceCiStatus::decl(),
+        db::models::workspace_issue_link::WorkspaceIssueLink::decl(),
+        db::models::workspace_activity::WorkspaceActivityKind::decl(),
+        db::models::workspace_activity::WorkspaceActivity::decl(),
+        server::routes::workspaces::activity::WorkspaceActivityQuery::decl(),
+        server::routes::workspaces::activity::WorkspaceActivityPage::decl(),
+        server::routes::workspaces::children::ChildWorkspaceSpec::decl(),
+        server::routes::workspaces::children::SpawnChildWorkspacesRequest::decl(),
+        server::routes::workspaces::children::ChildWorkspace::decl(),
+        server::routes::workspaces::children::ChildWorkspacesResponse::decl(),
+        server::routes::workspaces::children::MergeChildWorkspacesRequest::decl(),
+        db::models::project_execution_weight::ProjectExecutionWeight::decl(),
+        server::routes::execution_queue::SetProjectExecutionWeightRequest::decl(),
         server::routes::workspaces::integration::OpenEditorRequest::decl(),
+        server::routes::workspaces::integration::OpenComponentSourceRequest::decl(),
         server::routes::workspaces::integration::OpenEditorResponse::decl(),
         desktop_bridge::service::OpenRemoteEditorResponse::decl(),
         server::routes::host_relay::OpenRemoteWorkspaceInEditorRequest::decl(),
+        server::routes::host_relay::DispatchRemoteExecutionRequest::decl(),
         relay_types::PairRelayHostRequest::decl(),
         relay_types::PairRelayHostResponse::decl(),
         relay_types::RelayPairedHost::decl(),
@@ -138,10 +295,24 @@ fn generate_types_content() -> String {
         server::routes::workspaces::pr::CreatePrApiRequest::decl(),
         server::routes::attachments::AttachmentResponse::decl(),
         server::routes::attachments::AttachmentMetadata::decl(),
+        services::services::chunked_upload::InitChunkedUpload::decl(),
+        services::services::chunked_upload::ChunkedUploadStatus::decl(),
+        services::services::content_search::ContentSearchQuery::decl(),
+        services::services::content_search::ContentMatch::decl(),
+        services::services::content_search::ContentSearchEvent::decl(),
+        services::services::worktree_files::WorktreeFileVersion::decl(),
+        services::services::worktree_files::WorktreeFile::decl(),
+        server::routes::workspaces::files::WriteWorkspaceFileRequest::decl(),
+        server::routes::workspaces::files::WriteWorkspaceFileError::decl(),
+        services::services::dev_server::DevServerState::decl(),
+        services::services::dev_server::DevServerStatus::decl(),
         db::models::requests::WorkspaceRepoInput::decl(),
         server::routes::workspaces::integration::RunAgentSetupRequest::decl(),
         server::routes::workspaces::integration::RunAgentSetupResponse::decl(),
         server::routes::workspaces::gh_cli_setup::GhCliSetupError::decl(),
+        server::routes::workspaces::commit_message::GenerateCommitMessageRequest::decl(),
+        server::routes::workspaces::commit_message::GenerateCommitMessageResponse::decl(),
+        server::routes::workspaces::squash::SquashWorkspaceRequest::decl(),
         server::routes::workspaces::git::RebaseWorkspaceRequest::decl(),
         server::routes::workspaces::git::ContinueRebaseRequest::decl(),
         server::routes::workspaces::git::AbortConflictsRequest::decl(),
@@ -149,6 +320,7 @@ fn generate_types_content() -> String {
         server::routes::workspaces::git::PushError::decl(),
         server::routes::workspaces::pr::PrError::decl(),
         server::routes::workspaces::execution::RunScriptError::decl(),
+        server::routes::workspaces::execution::RunRepoCommandRequest::decl(),
         server::routes::workspaces::attachments::AssociateWorkspaceAttachmentsRequest::decl(),
         server::routes::workspaces::attachments::ImportIssueAttachmentsRequest::decl(),
         server::routes::workspaces::attachments::ImportIssueAttachmentsResponse::decl(),
@@ -159,11 +331,13 @@ fn generate_types_content() -> String {
         server::routes::workspaces::pr::GetPrCommentsQuery::decl(),
         db::models::requests::CreateAndStartWorkspaceRequest::decl(),
         db::models::requests::CreateAndStartWorkspaceResponse::decl(),
+        server::routes::workspaces::create::CreateWorkspaceFromTemplateRequest::decl(),
         git_host::UnifiedPrComment::decl(),
         git_host::ProviderKind::decl(),
         git_host::PullRequestDetail::decl(),
         git::GitRemote::decl(),
         server::routes::repo::ListPrsError::decl(),
+        server::routes::repo::ImportIssuesRequest::decl(),
         server::routes::remote::pull_requests::LinkPrToIssueRequest::decl(),
         server::routes::workspaces::pr::CreateWorkspaceFromPrBody::decl(),
         server::routes::workspaces::pr::CreateWorkspaceFromPrResponse::decl(),
@@ -177,6 +351,9 @@ fn generate_types_content() -> String {
         server::routes::workspaces::workspace_summary::DiffStats::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
+        services::services::filesystem::TreeGitStatus::decl(),
+        services::services::filesystem::TreeEntry::decl(),
+        services::services::filesystem::DirectoryTreePage::decl(),
         services::services::file_search::SearchMode::decl(),
         services::services::config::Config::decl(),
         services::services::config::NotificationConfig::decl(),
@@ -189,10 +366,54 @@ fn generate_types_content() -> String {
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
         services::services::config::SendMessageShortcut::decl(),
+        services::services::config::LogRetentionConfig::decl(),
+        services::services::config::DirtyWorktreePolicy::decl(),
+        services::services::config::PromptSnippet::decl(),
+        services::services::config::ReportDeliveryConfig::decl(),
+        services::services::config::ExecutorTimeoutConfig::decl(),
+        services::services::config::WorktreeStrategy::decl(),
+        services::services::config::RouteRateLimitConfig::decl(),
+        services::services::config::NotificationEventToggles::decl(),
+        services::services::config::QuietHours::decl(),
+        services::services::config::NotificationDeliveryConfig::decl(),
+        services::services::config::AnalyticsConsent::decl(),
+        services::services::config::TerminalCwd::decl(),
+        services::services::config::TerminalConfig::decl(),
+        services::services::config::ExecutionBackend::decl(),
+        services::services::config::ExecutionTagLimits::decl(),
+        utils::terminal_recordings::TerminalRecordingInfo::decl(),
+        utils::process_tree::ProcessTreeNode::decl(),
+        utils::process::ProcessSignal::decl(),
+        server::routes::execution_processes::SignalExecutionProcessRequest::decl(),
+        services::services::analytics::AnalyticsCategory::decl(),
+        services::services::analytics::RecordedAnalyticsEvent::decl(),
+        services::services::log_retention::LogCompactionReport::decl(),
+        services::services::db_maintenance::DbVacuumKind::decl(),
+        services::services::db_maintenance::DbMaintenanceReport::decl(),
+        services::services::provider_credentials::ApiProvider::decl(),
+        services::services::provider_credentials::ProviderCredentialKind::decl(),
+        services::services::provider_credentials::ProviderCredentialInfo::decl(),
+        services::services::provider_credentials::ProviderCredentialValidation::decl(),
+        db::models::report_period::ReportPeriodKind::decl(),
+        services::services::reports::ProductivityReport::decl(),
+        services::services::health::HealthStatus::decl(),
+        services::services::health::HealthCheck::decl(),
+        services::services::health::HealthReport::decl(),
+        services::services::session_timeline::SessionTimeline::decl(),
+        services::services::session_timeline::TimelineProcess::decl(),
+        services::services::session_timeline::TimelineCommit::decl(),
+        services::services::session_timeline::TimelineEvent::decl(),
+        services::services::session_timeline::TimelineEventKind::decl(),
+        services::services::session_timeline::TimelineApproval::decl(),
         git::GitBranch::decl(),
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
+        services::services::execution_scheduler::ExecutionQueueStatus::decl(),
+        services::services::execution_scheduler::ProjectQueueStatus::decl(),
+        services::services::execution_scheduler::TagQueueStatus::decl(),
         git::ConflictOp::decl(),
+        git::SquashedCommit::decl(),
+        git::SquashPlan::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
@@ -9,7 +9,7 @@ use db::models::{
     session::SessionError, workspace::WorkspaceError,
 };
 use deployment::{DeploymentError, RelayHostsNotConfigured, RemoteClientNotConfigured};
-use executors::{command::CommandBuildError, executors::ExecutorError};
+use executors::{command::CommandBuildError, executors::ExecutorError, one_shot::OneShotError};
 use git::GitServiceError;
 use git_host::GitHostError;
 use local_deployment::pty::PtyError;
@@ -20,13 +20,21 @@ use relay_webrtc::WebRtcError;
 use services::services::{
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
+    db_maintenance::DbMaintenanceError,
+    doc_index::DocIndexError,
     file::FileError,
+    issue_tracker::IssueTrackerError,
+    log_retention::LogRetentionError,
+    log_search::LogSearchError,
+    provider_credentials::ProviderCredentialsError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    setup_cache::SetupCacheError,
+    worktree_files::WorktreeFileError,
 };
 use thiserror::Error;
 use trusted_key_auth::error::TrustedKeyAuthError;
-use utils::response::ApiResponse;
+use utils::response::{ApiErrorCode, ApiResponse};
 use workspace_manager::WorkspaceError as WorkspaceManagerError;
 use worktree_manager::WorktreeError;
 
@@ -73,6 +81,8 @@ pub enum ApiError {
     Unauthorized,
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
     #[error("Conflict: {0}")]
     Conflict(String),
     #[error("Forbidden: {0}")]
@@ -114,6 +124,7 @@ impl From<WorkspaceManagerError> for ApiError {
         match err {
             WorkspaceManagerError::Database(err) => ApiError::Database(err),
             WorkspaceManagerError::Repo(err) => ApiError::Repo(err),
+            WorkspaceManagerError::DbWorkspace(err) => ApiError::Workspace(err),
             WorkspaceManagerError::Worktree(err) => ApiError::Worktree(err),
             WorkspaceManagerError::GitService(err) => ApiError::GitService(err),
             WorkspaceManagerError::Io(err) => ApiError::Io(err),
@@ -160,9 +171,22 @@ impl From<ContainerError> for ApiError {
     }
 }
 
+impl From<OneShotError> for ApiError {
+    fn from(err: OneShotError) -> Self {
+        match err {
+            OneShotError::Executor(e) => ApiError::Executor(e),
+            OneShotError::Io(e) => ApiError::Io(e),
+            OneShotError::TimedOut(_) | OneShotError::NoReply => {
+                ApiError::BadGateway(err.to_string())
+            }
+        }
+    }
+}
+
 struct ErrorInfo {
     status: StatusCode,
     error_type: &'static str,
+    code: ApiErrorCode,
     message: Option<String>,
 }
 
@@ -171,41 +195,56 @@ impl ErrorInfo {
         Self {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             error_type,
+            code: ApiErrorCode::Internal,
             message: Some("An internal error occurred. Please try again.".into()),
         }
     }
 
     fn not_found(error_type: &'static str, msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::NOT_FOUND,
-            error_type,
-            message: Some(msg.into()),
-        }
+        Self::with_status(StatusCode::NOT_FOUND, error_type, msg)
     }
 
     fn bad_request(error_type: &'static str, msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::BAD_REQUEST,
-            error_type,
-            message: Some(msg.into()),
-        }
+        Self::with_status(StatusCode::BAD_REQUEST, error_type, msg)
     }
 
     fn conflict(error_type: &'static str, msg: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::CONFLICT,
-            error_type,
-            message: Some(msg.into()),
-        }
+        Self::with_status(StatusCode::CONFLICT, error_type, msg)
     }
 
     fn with_status(status: StatusCode, error_type: &'static str, msg: impl Into<String>) -> Self {
         Self {
             status,
             error_type,
+            code: status_code(status),
             message: Some(msg.into()),
         }
     }
+
+    /// Replace the code derived from the status with a more specific one.
+    fn code(mut self, code: ApiErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+/// Generic code for errors without a more specific one.
+fn status_code(status: StatusCode) -> ApiErrorCode {
+    match status {
+        StatusCode::BAD_REQUEST => ApiErrorCode::BadRequest,
+        StatusCode::UNAUTHORIZED => ApiErrorCode::Unauthorized,
+        StatusCode::FORBIDDEN => ApiErrorCode::Forbidden,
+        StatusCode::NOT_FOUND => ApiErrorCode::NotFound,
+        StatusCode::CONFLICT => ApiErrorCode::Conflict,
+        StatusCode::GONE => ApiErrorCode::Gone,
+        StatusCode::PAYLOAD_TOO_LARGE => ApiErrorCode::PayloadTooLarge,
+        StatusCode::TOO_MANY_REQUESTS => ApiErrorCode::RateLimited,
+        StatusCode::BAD_GATEWAY => ApiErrorCode::BadGateway,
+        StatusCode::SERVICE_UNAVAILABLE => ApiErrorCode::ShuttingDown,
+        StatusCode::GATEWAY_TIMEOUT => ApiErrorCode::Timeout,
+        status if status.is_client_error() => ApiErrorCode::BadRequest,
+        _ => ApiErrorCode::Internal,
+    }
 }
 
 fn remote_client_error(err: &RemoteClientError) -> ErrorInfo {
@@ -215,7 +254,8 @@ fn remote_client_error(err: &RemoteClientError) -> ErrorInfo {
             StatusCode::UNAUTHORIZED,
             "RemoteClientError",
             "Unauthorized. Please sign in again.",
-        ),
+        )
+        .code(ApiErrorCode::RemoteAuthRequired),
         RemoteClientError::Timeout => ErrorInfo::with_status(
             StatusCode::GATEWAY_TIMEOUT,
             "RemoteClientError",
@@ -245,12 +285,13 @@ fn remote_client_error(err: &RemoteClientError) -> ErrorInfo {
             StatusCode::BAD_GATEWAY,
             "RemoteClientError",
             "Remote service returned an invalid access token. Please sign in again.",
+        )
+        .code(ApiErrorCode::RemoteAuthRequired),
+        RemoteClientError::Storage(_) => ErrorInfo::with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "RemoteClientError",
+            "Failed to persist credentials locally. Please retry.",
         ),
-        RemoteClientError::Storage(_) => ErrorInfo {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            error_type: "RemoteClientError",
-            message: Some("Failed to persist credentials locally. Please retry.".into()),
-        },
         RemoteClientError::Api(code) => {
             let (status, msg) = match code {
                 HandoffErrorCode::NotFound => (
@@ -293,7 +334,8 @@ fn remote_client_error(err: &RemoteClientError) -> ErrorInfo {
                             StatusCode::UNAUTHORIZED,
                             "RemoteClientError",
                             "Unauthorized. Please sign in again.",
-                        );
+                        )
+                        .code(ApiErrorCode::RemoteAuthRequired);
                     }
                     return ErrorInfo::bad_request(
                         "RemoteClientError",
@@ -301,7 +343,12 @@ fn remote_client_error(err: &RemoteClientError) -> ErrorInfo {
                     );
                 }
             };
-            ErrorInfo::with_status(status, "RemoteClientError", msg)
+            let info = ErrorInfo::with_status(status, "RemoteClientError", msg);
+            if status == StatusCode::UNAUTHORIZED {
+                info.code(ApiErrorCode::RemoteAuthRequired)
+            } else {
+                info
+            }
         }
         RemoteClientError::Serde(_) => ErrorInfo::bad_request(
             "RemoteClientError",
@@ -319,6 +366,7 @@ impl IntoResponse for ApiError {
             ApiError::Repo(RepoError::Database(_)) => ErrorInfo::internal("RepoError"),
             ApiError::Repo(RepoError::NotFound) => {
                 ErrorInfo::not_found("RepoError", "Repository not found.")
+                    .code(ApiErrorCode::RepoNotFound)
             }
 
             ApiError::Workspace(WorkspaceError::Database(_)) => {
@@ -326,20 +374,24 @@ impl IntoResponse for ApiError {
             }
             ApiError::Workspace(WorkspaceError::WorkspaceNotFound) => {
                 ErrorInfo::not_found("WorkspaceError", "Workspace not found.")
+                    .code(ApiErrorCode::WorkspaceNotFound)
             }
             ApiError::Workspace(WorkspaceError::ValidationError(msg)) => {
                 ErrorInfo::bad_request("WorkspaceError", msg.clone())
             }
             ApiError::Workspace(WorkspaceError::BranchNotFound(branch)) => {
                 ErrorInfo::not_found("WorkspaceError", format!("Branch '{}' not found.", branch))
+                    .code(ApiErrorCode::BranchNotFound)
             }
 
             ApiError::Session(SessionError::Database(_)) => ErrorInfo::internal("SessionError"),
             ApiError::Session(SessionError::NotFound) => {
                 ErrorInfo::not_found("SessionError", "Session not found.")
+                    .code(ApiErrorCode::SessionNotFound)
             }
             ApiError::Session(SessionError::WorkspaceNotFound) => {
                 ErrorInfo::not_found("SessionError", "Workspace not found.")
+                    .code(ApiErrorCode::WorkspaceNotFound)
             }
             ApiError::Session(SessionError::ExecutorMismatch { expected, actual }) => {
                 ErrorInfo::conflict(
@@ -349,6 +401,7 @@ impl IntoResponse for ApiError {
                         expected, actual
                     ),
                 )
+                .code(ApiErrorCode::ExecutorMismatch)
             }
 
             ApiError::ScratchError(ScratchError::Database(_)) => {
@@ -369,30 +422,35 @@ impl IntoResponse for ApiError {
 
             ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound) => {
                 ErrorInfo::not_found("ExecutionProcessError", "Execution process not found.")
+                    .code(ApiErrorCode::ExecutionProcessNotFound)
             }
             ApiError::ExecutionProcess(_) => ErrorInfo::internal("ExecutionProcessError"),
 
             ApiError::GitService(GitServiceError::MergeConflicts { message, .. }) => {
                 ErrorInfo::conflict("GitServiceError", message.clone())
+                    .code(ApiErrorCode::MergeConflict)
             }
             ApiError::GitService(GitServiceError::RebaseInProgress) => ErrorInfo::conflict(
                 "GitServiceError",
                 "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.",
-            ),
+            )
+            .code(ApiErrorCode::RebaseInProgress),
             ApiError::GitService(GitServiceError::BranchNotFound(branch)) => ErrorInfo::not_found(
                 "GitServiceError",
                 format!(
                     "Branch '{}' not found. Try changing the target branch.",
                     branch
                 ),
-            ),
+            )
+            .code(ApiErrorCode::BranchNotFound),
             ApiError::GitService(GitServiceError::BranchesDiverged(msg)) => ErrorInfo::conflict(
                 "GitServiceError",
                 format!(
                     "{} Rebase onto the target branch first, then retry the merge.",
                     msg
                 ),
-            ),
+            )
+            .code(ApiErrorCode::BranchesDiverged),
             ApiError::GitService(GitServiceError::WorktreeDirty(branch, files)) => {
                 ErrorInfo::conflict(
                     "GitServiceError",
@@ -401,6 +459,7 @@ impl IntoResponse for ApiError {
                         branch, files
                     ),
                 )
+                .code(ApiErrorCode::WorktreeDirty)
             }
             ApiError::GitService(GitServiceError::GitCLI(git::GitCliError::AuthFailed(msg))) => {
                 ErrorInfo::with_status(
@@ -411,6 +470,7 @@ impl IntoResponse for ApiError {
                         msg
                     ),
                 )
+                .code(ApiErrorCode::GitAuthFailed)
             }
             ApiError::GitService(e) => ErrorInfo::with_status(
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -427,19 +487,36 @@ impl IntoResponse for ApiError {
                     *size as f64 / 1_048_576.0,
                     *max as f64 / 1_048_576.0
                 ),
-            ),
+            )
+            .code(ApiErrorCode::FileTooLarge),
             ApiError::File(FileError::NotFound) => {
                 ErrorInfo::not_found("FileNotFound", "File not found.")
             }
-            ApiError::File(_) => ErrorInfo {
-                status: StatusCode::INTERNAL_SERVER_ERROR,
-                error_type: "FileError",
-                message: Some("Failed to process file. Please try again.".into()),
-            },
+            ApiError::File(FileError::Image(e)) => {
+                ErrorInfo::bad_request("ImageProcessingError", e.to_string())
+            }
+            ApiError::File(FileError::UploadNotFound) => ErrorInfo::not_found(
+                "UploadNotFound",
+                "Upload not found or expired. Start the upload again.",
+            )
+            .code(ApiErrorCode::UploadNotFound),
+            ApiError::File(e @ (FileError::InvalidUpload(_) | FileError::HashMismatch { .. })) => {
+                ErrorInfo::bad_request("InvalidUpload", e.to_string())
+                    .code(ApiErrorCode::InvalidUpload)
+            }
+            ApiError::File(_) => ErrorInfo::with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "FileError",
+                "Failed to process file. Please try again.",
+            ),
 
             ApiError::EditorOpen(EditorOpenError::LaunchFailed { .. }) => {
                 ErrorInfo::internal("EditorLaunchError")
             }
+            ApiError::EditorOpen(EditorOpenError::ExecutableNotFound { .. }) => {
+                ErrorInfo::bad_request("EditorOpenError", format!("{}", self))
+                    .code(ApiErrorCode::EditorNotFound)
+            }
             ApiError::EditorOpen(_) => {
                 ErrorInfo::bad_request("EditorOpenError", format!("{}", self))
             }
@@ -460,6 +537,7 @@ impl IntoResponse for ApiError {
                 "Unauthorized. Please sign in again.",
             ),
             ApiError::BadRequest(msg) => ErrorInfo::bad_request("BadRequest", msg.clone()),
+            ApiError::NotFound(msg) => ErrorInfo::not_found("NotFound", msg.clone()),
             ApiError::Conflict(msg) => ErrorInfo::conflict("ConflictError", msg.clone()),
             ApiError::Forbidden(msg) => {
                 ErrorInfo::with_status(StatusCode::FORBIDDEN, "ForbiddenError", msg.clone())
@@ -483,7 +561,27 @@ impl IntoResponse for ApiError {
             ),
 
             ApiError::Deployment(_) => ErrorInfo::internal("DeploymentError"),
+            ApiError::Container(ContainerError::ShuttingDown) => ErrorInfo::with_status(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "ContainerError",
+                "The server is shutting down and not starting new executions.",
+            ),
             ApiError::Container(_) => ErrorInfo::internal("ContainerError"),
+            ApiError::Executor(err @ ExecutorError::ExecutableNotFound { .. }) => {
+                ErrorInfo::with_status(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "ExecutorError",
+                    format!("{}. Install the coding agent and try again.", err),
+                )
+                .code(ApiErrorCode::ExecutorNotInstalled)
+            }
+            // Not 401: that would read as the user's own session expiring.
+            ApiError::Executor(err @ ExecutorError::AuthRequired(_)) => ErrorInfo::with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "ExecutorError",
+                err.to_string(),
+            )
+            .code(ApiErrorCode::ExecutorAuthRequired),
             ApiError::Executor(_) => ErrorInfo::internal("ExecutorError"),
             ApiError::CommandBuilder(_) => ErrorInfo::internal("CommandBuildError"),
             ApiError::Database(_) => ErrorInfo::internal("DatabaseError"),
@@ -524,7 +622,7 @@ impl IntoResponse for ApiError {
         let message = info
             .message
             .unwrap_or_else(|| format!("{}: {}", info.error_type, self));
-        let response = ApiResponse::<()>::error(&message);
+        let response = ApiResponse::<()>::error_with_code(&message, info.code);
         (info.status, Json(response)).into_response()
     }
 }
@@ -569,6 +667,106 @@ impl From<RepoServiceError> for ApiError {
     }
 }
 
+impl From<LogSearchError> for ApiError {
+    fn from(err: LogSearchError) -> Self {
+        match err {
+            LogSearchError::Database(db_err) => ApiError::Database(db_err),
+            LogSearchError::Io(io_err) => ApiError::Io(io_err),
+            LogSearchError::EmptyQuery => ApiError::BadRequest(
+                "Query parameter 'q' is required and cannot be empty".to_string(),
+            ),
+        }
+    }
+}
+
+impl From<LogRetentionError> for ApiError {
+    fn from(err: LogRetentionError) -> Self {
+        match err {
+            LogRetentionError::Database(db_err) => ApiError::Database(db_err),
+            LogRetentionError::Io(io_err) => ApiError::Io(io_err),
+        }
+    }
+}
+
+impl From<DbMaintenanceError> for ApiError {
+    fn from(err: DbMaintenanceError) -> Self {
+        match err {
+            DbMaintenanceError::Database(db_err) => ApiError::Database(db_err),
+            DbMaintenanceError::Io(io_err) => ApiError::Io(io_err),
+        }
+    }
+}
+
+impl From<ProviderCredentialsError> for ApiError {
+    fn from(err: ProviderCredentialsError) -> Self {
+        match err {
+            ProviderCredentialsError::Io(io_err) => ApiError::Io(io_err),
+            ProviderCredentialsError::Json(_) | ProviderCredentialsError::Secrets(_) => {
+                ApiError::Io(std::io::Error::other(err))
+            }
+            ProviderCredentialsError::UnsupportedKind { .. }
+            | ProviderCredentialsError::Empty
+            | ProviderCredentialsError::NotConfigured(_) => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
+
+impl From<SetupCacheError> for ApiError {
+    fn from(err: SetupCacheError) -> Self {
+        match err {
+            SetupCacheError::Io(io_err) => ApiError::Io(io_err),
+            SetupCacheError::Join(join_err) => ApiError::Io(std::io::Error::other(join_err)),
+        }
+    }
+}
+
+impl From<WorktreeFileError> for ApiError {
+    fn from(err: WorktreeFileError) -> Self {
+        match err {
+            WorktreeFileError::NotFound => ApiError::File(FileError::NotFound),
+            WorktreeFileError::TooLarge(size, max) => {
+                ApiError::File(FileError::TooLarge(size, max))
+            }
+            WorktreeFileError::Io(io_err) => ApiError::Io(io_err),
+            WorktreeFileError::InvalidPath(_) | WorktreeFileError::NotText => {
+                ApiError::BadRequest(err.to_string())
+            }
+            WorktreeFileError::Conflict(_) => ApiError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<DocIndexError> for ApiError {
+    fn from(err: DocIndexError) -> Self {
+        match err {
+            DocIndexError::Database(db_err) => ApiError::Database(db_err),
+            DocIndexError::Io(io_err) => ApiError::Io(io_err),
+            DocIndexError::Http(http_err) => {
+                ApiError::BadGateway(format!("Failed to fetch documentation: {}", http_err))
+            }
+            DocIndexError::InvalidOpenApi(msg) => {
+                ApiError::BadRequest(format!("Invalid OpenAPI document: {}", msg))
+            }
+            DocIndexError::RepoNotFound => ApiError::Repo(RepoError::NotFound),
+        }
+    }
+}
+
+impl From<IssueTrackerError> for ApiError {
+    fn from(err: IssueTrackerError) -> Self {
+        match err {
+            IssueTrackerError::Http(_)
+            | IssueTrackerError::Api { .. }
+            | IssueTrackerError::UnexpectedResponse(_) => ApiError::BadGateway(err.to_string()),
+            IssueTrackerError::Secrets(_) => ApiError::Io(std::io::Error::other(err)),
+            IssueTrackerError::MissingBaseUrl
+            | IssueTrackerError::IssueNotFound(_)
+            | IssueTrackerError::InvalidKey(_)
+            | IssueTrackerError::NoCloseTransition(_) => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
+
 impl From<RelayHostLookupError> for ApiError {
     fn from(err: RelayHostLookupError) -> Self {
         ApiError::BadRequest(err.to_string())
@@ -618,3 +816,59 @@ impl From<RelayPairingClientError> for ApiError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+
+    async fn error_code(error: ApiError) -> (StatusCode, Option<ApiErrorCode>) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: ApiResponse<()> = serde_json::from_slice(&body).unwrap();
+        (status, body.error_code())
+    }
+
+    #[tokio::test]
+    async fn specific_errors_carry_their_code() {
+        assert_eq!(
+            error_code(ApiError::GitService(GitServiceError::WorktreeDirty(
+                "main".to_string(),
+                "a.rs".to_string(),
+            )))
+            .await,
+            (StatusCode::CONFLICT, Some(ApiErrorCode::WorktreeDirty))
+        );
+        assert_eq!(
+            error_code(ApiError::Executor(ExecutorError::ExecutableNotFound {
+                program: "claude".to_string(),
+            }))
+            .await,
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Some(ApiErrorCode::ExecutorNotInstalled)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn other_errors_fall_back_to_a_status_code() {
+        assert_eq!(
+            error_code(ApiError::TooManyRequests("slow down".to_string())).await,
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                Some(ApiErrorCode::RateLimited)
+            )
+        );
+        assert_eq!(
+            error_code(ApiError::Conflict("busy".to_string())).await,
+            (StatusCode::CONFLICT, Some(ApiErrorCode::Conflict))
+        );
+        assert_eq!(
+            error_code(ApiError::NotFound("gone".to_string())).await,
+            (StatusCode::NOT_FOUND, Some(ApiErrorCode::NotFound))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::{self, Error as AnyhowError};
 use axum::Router;
 use deployment::{Deployment, DeploymentError};
@@ -11,12 +13,19 @@ use thiserror::Error;
 use tokio_util::sync::CancellationToken;
 use tower_http::validate_request::ValidateRequestHeaderLayer;
 use tracing_subscriber::{EnvFilter, prelude::*};
+#[cfg(feature = "profiling")]
+use utils::alloc_stats::CountingAllocator;
 use utils::{
-    assets::asset_dir,
+    assets::{asset_dir, base_asset_dir},
+    config_profile,
     port_file::write_port_file_with_proxy,
     sentry::{self as sentry_utils, SentrySource, sentry_layer},
 };
 
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
 #[derive(Debug, Error)]
 pub enum VibeKanbanError {
     #[error(transparent)]
@@ -49,6 +58,14 @@ async fn main() -> Result<(), VibeKanbanError> {
         .with(sentry_layer())
         .init();
 
+    // Pick the config profile before anything reads the asset directory
+    let profile = config_profile::select(
+        &base_asset_dir(),
+        config_profile::profile_from_args(std::env::args().skip(1)),
+    )
+    .map_err(anyhow::Error::from)?;
+    tracing::info!("Using config profile '{profile}'");
+
     // Create asset directory if it doesn't exist
     if !asset_dir().exists() {
         std::fs::create_dir_all(asset_dir())?;
@@ -234,9 +251,17 @@ pub async fn shutdown_signal() {
 }
 
 pub async fn perform_cleanup_actions(deployment: &DeploymentImpl) {
+    let grace_period = Duration::from_secs(
+        deployment
+            .config()
+            .read()
+            .await
+            .shutdown_grace_period_secs
+            .into(),
+    );
     deployment
         .container()
-        .kill_all_running_processes()
+        .drain_running_processes(grace_period)
         .await
-        .expect("Failed to cleanly kill running execution processes");
+        .expect("Failed to cleanly stop running execution processes");
 }
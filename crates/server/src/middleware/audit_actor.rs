@@ -0,0 +1,66 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use db::models::audit_log::{AuditActor, AuditActorKind};
+use deployment::Deployment;
+use trusted_key_auth::trusted_keys::parse_public_key_base64;
+
+use crate::{
+    DeploymentImpl, middleware::RelayRequestSignatureContext,
+    relay_pairing::server::is_relay_request,
+};
+
+/// Who made the request, for the audit log. Requests signed over the relay
+/// are attributed to the paired client whose key signed them; unsigned relay
+/// requests, e.g. to the relay auth routes, only as coming over the relay.
+pub struct RequestActor(pub AuditActor);
+
+impl<S> FromRequestParts<S> for RequestActor
+where
+    S: Send + Sync,
+    DeploymentImpl: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ctx = parts
+            .extensions
+            .get::<RelayRequestSignatureContext>()
+            .cloned();
+        if ctx.is_none() && !is_relay_request(&parts.headers) {
+            return Ok(Self(AuditActor::local()));
+        }
+
+        let mut actor = AuditActor {
+            kind: AuditActorKind::Relay,
+            client_id: None,
+            name: None,
+        };
+        let Some(ctx) = ctx else {
+            return Ok(Self(actor));
+        };
+        let deployment = DeploymentImpl::from_ref(state);
+        let Some(peer_key) = deployment
+            .relay_signing()
+            .get_session_peer_key(ctx.signing_session_id)
+            .await
+        else {
+            return Ok(Self(actor));
+        };
+        match deployment.trusted_key_auth().list_trusted_clients().await {
+            Ok(clients) => {
+                if let Some(client) = clients.into_iter().find(|client| {
+                    parse_public_key_base64(&client.public_key_b64).is_ok_and(|key| key == peer_key)
+                }) {
+                    actor.client_id = Some(client.client_id);
+                    actor.name = Some(client.client_name);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to list trusted relay clients: {}", e),
+        }
+        Ok(Self(actor))
+    }
+}
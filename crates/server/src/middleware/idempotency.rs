@@ -0,0 +1,206 @@
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderValue, Method, StatusCode, header::CONTENT_TYPE},
+    middleware::Next,
+    response::Response,
+};
+use db::models::idempotency_key::IdempotencyKey;
+use deployment::Deployment;
+use sha2::{Digest, Sha256};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Header a client sets on a mutating request it may retry, e.g. an action
+/// queued by the remote frontend while the relay was down.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set on responses replayed from an earlier request with the same key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LEN: usize = 255;
+/// Requests are buffered to fingerprint them; matches the relay body cap.
+const MAX_REQUEST_BYTES: usize = 50 * 1024 * 1024;
+/// Larger (or streamed) responses are passed through without being stored.
+const MAX_STORED_RESPONSE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Runs a mutating request at most once per `Idempotency-Key`.
+///
+/// The first request claims the key and its response is stored; retries with
+/// the same key get that response back instead of running the handler again.
+/// Reusing a key for a different request is rejected, as is a retry while the
+/// first request is still running. Server errors release the key so the
+/// action can be retried.
+pub async fn idempotency(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !is_mutating(request.method()) {
+        return Ok(next.run(request).await);
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Idempotency-Key must be 1-{MAX_KEY_LEN} visible ASCII characters"
+            ))
+        })?
+        .to_string();
+
+    let path_and_query = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|original| original.0.clone())
+        .unwrap_or_else(|| request.uri().clone())
+        .path_and_query()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let (parts, body) = request.into_parts();
+    let body_bytes = to_bytes(body, MAX_REQUEST_BYTES)
+        .await
+        .map_err(|_| ApiError::PayloadTooLarge)?;
+    let fingerprint = request_fingerprint(&parts.method, &path_and_query, &body_bytes);
+
+    let pool = &deployment.db().pool;
+    if let Some(existing) = IdempotencyKey::claim(pool, &key, &fingerprint).await? {
+        return replay(existing, &fingerprint);
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(body_bytes)))
+        .await;
+
+    let storable = !response.status().is_server_error()
+        && response
+            .body()
+            .size_hint()
+            .upper()
+            .is_some_and(|upper| upper <= MAX_STORED_RESPONSE_BYTES);
+    if !storable {
+        IdempotencyKey::release(pool, &key).await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body_bytes = to_bytes(body, MAX_STORED_RESPONSE_BYTES as usize)
+        .await
+        .map_err(|_| ApiError::PayloadTooLarge)?;
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    IdempotencyKey::complete(pool, &key, parts.status.as_u16(), content_type, &body_bytes).await?;
+
+    Ok(Response::from_parts(parts, Body::from(body_bytes)))
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+fn request_fingerprint(method: &Method, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b"\n");
+    hasher.update(path_and_query);
+    hasher.update(b"\n");
+    hasher.update(body);
+
+    let mut output = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        use std::fmt::Write;
+        let _ = write!(output, "{byte:02x}");
+    }
+    output
+}
+
+fn replay(existing: IdempotencyKey, fingerprint: &str) -> Result<Response, ApiError> {
+    if existing.request_fingerprint != fingerprint {
+        return Err(ApiError::BadRequest(
+            "Idempotency-Key was already used for a different request".to_string(),
+        ));
+    }
+    let Some(status) = existing
+        .status
+        .and_then(|status| u16::try_from(status).ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+    else {
+        return Err(ApiError::Conflict(
+            "A request with this Idempotency-Key is still in progress".to_string(),
+        ));
+    };
+
+    let mut response = Response::new(Body::from(existing.body.unwrap_or_default()));
+    *response.status_mut() = status;
+    if let Some(content_type) = existing
+        .content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_covers_method_path_and_body() {
+        let base = request_fingerprint(&Method::POST, "/api/sessions/1/follow-up", b"{}");
+        assert_eq!(
+            base,
+            request_fingerprint(&Method::POST, "/api/sessions/1/follow-up", b"{}")
+        );
+        assert_ne!(
+            base,
+            request_fingerprint(&Method::PUT, "/api/sessions/1/follow-up", b"{}")
+        );
+        assert_ne!(
+            base,
+            request_fingerprint(&Method::POST, "/api/sessions/2/follow-up", b"{}")
+        );
+        assert_ne!(
+            base,
+            request_fingerprint(&Method::POST, "/api/sessions/1/follow-up", b"{\"a\":1}")
+        );
+    }
+
+    #[test]
+    fn replays_stored_response_and_rejects_mismatches() {
+        let fingerprint = request_fingerprint(&Method::POST, "/api/tasks", b"{}");
+        let record = |status: Option<i64>| IdempotencyKey {
+            key: "retry-1".to_string(),
+            request_fingerprint: fingerprint.clone(),
+            status,
+            content_type: Some("application/json".to_string()),
+            body: Some(b"{\"success\":true}".to_vec()),
+            created_at: chrono::Utc::now(),
+        };
+
+        let response = replay(record(Some(201)), &fingerprint).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        assert!(matches!(
+            replay(record(None), &fingerprint),
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(matches!(
+            replay(record(Some(200)), "other"),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -1,10 +1,16 @@
+pub mod audit_actor;
 pub mod error_logging;
+pub mod idempotency;
 pub mod model_loaders;
 pub mod origin;
+pub mod rate_limit;
 pub mod relay_request_signature;
 pub mod signed_ws;
 
+pub use audit_actor::*;
 pub use error_logging::*;
+pub use idempotency::*;
 pub use model_loaders::*;
 pub use origin::*;
+pub use rate_limit::*;
 pub use relay_request_signature::*;
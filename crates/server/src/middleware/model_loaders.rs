@@ -1,11 +1,13 @@
 use axum::{
     extract::{Path, Request, State},
-    http::StatusCode,
+    http::{Method, StatusCode},
     middleware::Next,
     response::Response,
 };
 use db::models::{
-    execution_process::ExecutionProcess, session::Session, tag::Tag, workspace::Workspace,
+    approval_notification_rule::ApprovalNotificationRule, board::Board,
+    execution_process::ExecutionProcess, session::Session, tag::Tag, task_template::TaskTemplate,
+    workspace::Workspace, workspace_handoff::WorkspaceHandoffLink,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -31,6 +33,8 @@ pub async fn load_workspace_middleware(
         }
     };
 
+    ensure_not_handed_off(&deployment, &request, workspace.id).await?;
+
     // Insert the workspace into extensions
     request.extensions_mut().insert(workspace);
 
@@ -38,6 +42,38 @@ pub async fn load_workspace_middleware(
     Ok(next.run(request).await)
 }
 
+/// A workspace handed off to another instance is read-only here until the
+/// handoff is cancelled. Reads, deletes and marking as seen are still allowed.
+async fn ensure_not_handed_off(
+    deployment: &DeploymentImpl,
+    request: &Request,
+    workspace_id: Uuid,
+) -> Result<(), StatusCode> {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::DELETE
+    ) || request.uri().path().ends_with("/seen")
+    {
+        return Ok(());
+    }
+
+    match WorkspaceHandoffLink::is_handed_off(&deployment.db().pool, workspace_id).await {
+        Ok(false) => Ok(()),
+        Ok(true) => {
+            tracing::debug!("Rejecting change to handed-off workspace {}", workspace_id);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to check handoff state of workspace {}: {}",
+                workspace_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn load_execution_process_middleware(
     State(deployment): State<DeploymentImpl>,
     Path(process_id): Path<Uuid>,
@@ -93,6 +129,76 @@ pub async fn load_tag_middleware(
     Ok(next.run(request).await)
 }
 
+pub async fn load_task_template_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let template = match TaskTemplate::find_by_id(&deployment.db().pool, template_id).await {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            tracing::warn!("Task template {} not found", template_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task template {}: {}", template_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(template);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_approval_notification_rule_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(rule_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let rule = match ApprovalNotificationRule::find_by_id(&deployment.db().pool, rule_id).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => {
+            tracing::warn!("Approval notification rule {} not found", rule_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch approval notification rule {}: {}",
+                rule_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(rule);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_board_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(board_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let board = match Board::find_by_id(&deployment.db().pool, board_id).await {
+        Ok(Some(board)) => board,
+        Ok(None) => {
+            tracing::warn!("Board {} not found", board_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch board {}: {}", board_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(board);
+    Ok(next.run(request).await)
+}
+
 pub async fn load_session_middleware(
     State(deployment): State<DeploymentImpl>,
     Path(session_id): Path<Uuid>,
@@ -111,6 +217,8 @@ pub async fn load_session_middleware(
         }
     };
 
+    ensure_not_handed_off(&deployment, &request, session.workspace_id).await?;
+
     request.extensions_mut().insert(session);
     Ok(next.run(request).await)
 }
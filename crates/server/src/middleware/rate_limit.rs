@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, Method, header::AUTHORIZATION},
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::config::RouteRateLimitConfig;
+use sha2::{Digest, Sha256};
+
+use crate::{DeploymentImpl, error::ApiError, middleware::RelayRequestSignatureContext};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Groups of sensitive routes that share a per-client request budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitedRoute {
+    TaskCreation,
+    ProcessControl,
+    FileWrites,
+}
+
+impl RateLimitedRoute {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::TaskCreation => "task-creation",
+            Self::ProcessControl => "process-control",
+            Self::FileWrites => "file-writes",
+        }
+    }
+
+    fn limit(self, config: &RouteRateLimitConfig) -> u32 {
+        match self {
+            Self::TaskCreation => config.task_creation_per_minute,
+            Self::ProcessControl => config.process_control_per_minute,
+            Self::FileWrites => config.file_writes_per_minute,
+        }
+    }
+}
+
+/// Limits mutating requests to a route group per client, so a runaway client
+/// reaching the host through the relay cannot flood it with agent runs,
+/// process kills or file writes.
+///
+/// Layer with `from_fn_with_state((deployment, route), rate_limit)`. Clients
+/// are told apart by relay signing session, then API token; other local
+/// requests share one budget.
+pub async fn rate_limit(
+    State((deployment, route)): State<(DeploymentImpl, RateLimitedRoute)>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return Ok(next.run(request).await);
+    }
+
+    let config = deployment.config().read().await.route_rate_limits.clone();
+    let limit = route.limit(&config);
+    if !config.enabled || limit == 0 {
+        return Ok(next.run(request).await);
+    }
+
+    let bucket = format!(
+        "route-rate-limit:{}:{}",
+        route.as_str(),
+        client_identity(
+            request.extensions().get::<RelayRequestSignatureContext>(),
+            request.headers(),
+        )
+    );
+    deployment
+        .trusted_key_auth()
+        .enforce_rate_limit(&bucket, limit as usize, WINDOW)
+        .await?;
+
+    Ok(next.run(request).await)
+}
+
+fn client_identity(
+    signature: Option<&RelayRequestSignatureContext>,
+    headers: &HeaderMap,
+) -> String {
+    if let Some(signature) = signature {
+        return format!("relay:{}", signature.signing_session_id);
+    }
+
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty());
+    let Some(token) = token else {
+        return "local".to_string();
+    };
+
+    // Bucket names are kept in memory; avoid holding raw tokens there.
+    let mut output = String::from("token:");
+    for byte in Sha256::digest(token.as_bytes()).iter().take(16) {
+        use std::fmt::Write;
+        let _ = write!(output, "{byte:02x}");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn identifies_clients_by_signing_session_then_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_identity(None, &headers), "local");
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        let token_identity = client_identity(None, &headers);
+        assert!(token_identity.starts_with("token:"));
+        assert!(!token_identity.contains("secret"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer other"));
+        assert_ne!(client_identity(None, &headers), token_identity);
+
+        let signature = RelayRequestSignatureContext {
+            signing_session_id: Uuid::nil(),
+            timestamp: 0,
+            nonce: Uuid::nil(),
+            signature_b64: String::new(),
+        };
+        assert_eq!(
+            client_identity(Some(&signature), &headers),
+            format!("relay:{}", Uuid::nil())
+        );
+    }
+
+    #[test]
+    fn route_groups_use_their_configured_limit() {
+        let config = RouteRateLimitConfig::default();
+        assert_eq!(
+            RateLimitedRoute::TaskCreation.limit(&config),
+            config.task_creation_per_minute
+        );
+        assert_eq!(
+            RateLimitedRoute::ProcessControl.limit(&config),
+            config.process_control_per_minute
+        );
+        assert_eq!(
+            RateLimitedRoute::FileWrites.limit(&config),
+            config.file_writes_per_minute
+        );
+    }
+}
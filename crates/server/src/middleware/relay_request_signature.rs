@@ -3,17 +3,20 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use axum::{
     body::{Body, to_bytes},
     extract::{OriginalUri, Request, State},
-    http::HeaderValue,
+    http::{HeaderValue, header::CONTENT_LENGTH},
     middleware::Next,
     response::Response,
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use deployment::Deployment;
 use relay_client::RELAY_HEADER;
-use relay_control::signing::{
-    NONCE_HEADER, REQUEST_SIGNATURE_HEADER, RESPONSE_NONCE_HEADER, RESPONSE_SIGNATURE_HEADER,
-    RESPONSE_TIMESTAMP_HEADER, RequestSignature, SIGNING_SESSION_HEADER, TIMESTAMP_HEADER,
-    build_response_signing_message,
+use relay_control::{
+    encryption::{ENCRYPTED_BODY_HEADER, body_aad},
+    signing::{
+        NONCE_HEADER, REQUEST_SIGNATURE_HEADER, RESPONSE_NONCE_HEADER, RESPONSE_SIGNATURE_HEADER,
+        RESPONSE_TIMESTAMP_HEADER, RequestSignature, SIGNING_SESSION_HEADER, TIMESTAMP_HEADER,
+        build_response_signing_message,
+    },
 };
 use url::form_urlencoded;
 use uuid::Uuid;
@@ -38,7 +41,7 @@ pub async fn require_relay_request_signature(
 
     let (request_signature, path_and_query) = extract_request_signature(&request)?;
 
-    let (parts, body) = request.into_parts();
+    let (mut parts, body) = request.into_parts();
     let body_bytes = to_bytes(body, RELAY_SIGNED_BODY_MAX_BYTES)
         .await
         .map_err(|_| ApiError::PayloadTooLarge)?;
@@ -62,6 +65,39 @@ pub async fn require_relay_request_signature(
         return Err(ApiError::Unauthorized);
     }
 
+    // Sessions negotiated with end-to-end encryption must send encrypted
+    // bodies; the signature above covers the ciphertext.
+    let body_bytes = match deployment
+        .relay_signing()
+        .session_cipher(request_signature.signing_session_id)
+        .await
+    {
+        Some(cipher) if parts.headers.contains_key(ENCRYPTED_BODY_HEADER) => {
+            let plaintext = cipher
+                .decrypt(
+                    &body_aad(
+                        request_signature.signing_session_id,
+                        request_signature.nonce,
+                        "request",
+                    ),
+                    &body_bytes,
+                )
+                .map_err(|_| ApiError::Unauthorized)?;
+            parts.headers.remove(ENCRYPTED_BODY_HEADER);
+            parts.headers.remove(CONTENT_LENGTH);
+            plaintext.into()
+        }
+        Some(_) if !body_bytes.is_empty() => {
+            tracing::warn!(
+                signing_session_id = %request_signature.signing_session_id,
+                path = %path_and_query,
+                "Rejecting unencrypted relay request on encrypted session"
+            );
+            return Err(ApiError::Unauthorized);
+        }
+        _ => body_bytes,
+    };
+
     let mut request = Request::from_parts(parts, Body::from(body_bytes));
     request.extensions_mut().insert(request_signature);
 
@@ -84,6 +120,28 @@ pub async fn sign_relay_response(
     let body_bytes = to_bytes(body, RELAY_SIGNED_BODY_MAX_BYTES)
         .await
         .map_err(|_| ApiError::PayloadTooLarge)?;
+    let cipher = deployment
+        .relay_signing()
+        .session_cipher(request_signature.signing_session_id)
+        .await;
+    let body_bytes = match cipher {
+        Some(cipher) if !body_bytes.is_empty() => {
+            let ciphertext = cipher
+                .encrypt(
+                    &body_aad(
+                        request_signature.signing_session_id,
+                        request_signature.nonce,
+                        "response",
+                    ),
+                    &body_bytes,
+                )
+                .map_err(|_| ApiError::Unauthorized)?;
+            parts.headers.remove(CONTENT_LENGTH);
+            insert_header(&mut parts, ENCRYPTED_BODY_HEADER, "1");
+            ciphertext.into()
+        }
+        _ => body_bytes,
+    };
     let response_timestamp = unix_timestamp_now().map_err(|_| ApiError::Unauthorized)?;
     let response_nonce = Uuid::new_v4();
     let status = parts.status.as_u16();
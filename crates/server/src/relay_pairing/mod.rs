@@ -13,7 +13,6 @@ pub fn build_relay_pairing_server(deployment: &DeploymentImpl) -> RelayPairingSe
         deployment.relay_signing().clone(),
         RelayPairingEvents::new(
             deployment.user_id().to_string(),
-            deployment.config().clone(),
             deployment.analytics().clone(),
         ),
     )
@@ -1,18 +1,23 @@
-use std::{sync::Arc, time::Duration};
+use std::time::Duration;
 
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use ed25519_dalek::VerifyingKey;
 use http::HeaderMap;
-use relay_control::signing::RelaySigningService;
+use relay_control::{
+    encryption::client_encryption_key_message,
+    signing::{EncryptedSessionKey, RelaySigningService},
+};
 use relay_types::{
     FinishSpake2EnrollmentRequest, FinishSpake2EnrollmentResponse,
     RefreshRelaySigningSessionRequest, RefreshRelaySigningSessionResponse, RelayPairedClient,
-    StartSpake2EnrollmentRequest, StartSpake2EnrollmentResponse,
+    RotateRelayClientKeyRequest, RotateRelayClientKeyResponse, StartSpake2EnrollmentRequest,
+    StartSpake2EnrollmentResponse,
 };
-use services::services::{analytics::AnalyticsService, config::Config};
-use tokio::sync::RwLock;
+use services::services::analytics::{AnalyticsCategory, AnalyticsService};
 use trusted_key_auth::{
     key_confirmation::{build_server_proof, verify_client_proof},
     refresh::{build_refresh_message, validate_refresh_timestamp, verify_refresh_signature},
+    rotation::{build_key_rotation_message, validate_key_rotation_timestamp, verify_key_rotation},
     runtime::TrustedKeyAuthRuntime,
     spake2::{generate_one_time_code, start_spake2_enrollment},
     trusted_keys::{TrustedRelayClient, parse_public_key_base64},
@@ -25,6 +30,7 @@ pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
 pub const GENERATE_CODE_GLOBAL_LIMIT: usize = 5;
 pub const SPAKE2_START_GLOBAL_LIMIT: usize = 30;
 pub const SIGNING_SESSION_REFRESH_GLOBAL_LIMIT: usize = 30;
+pub const KEY_ROTATION_GLOBAL_LIMIT: usize = 10;
 
 #[derive(Clone)]
 pub struct RelayPairingServer {
@@ -36,21 +42,12 @@ pub struct RelayPairingServer {
 #[derive(Clone)]
 pub struct RelayPairingEvents {
     user_id: String,
-    config: Arc<RwLock<Config>>,
     analytics: Option<AnalyticsService>,
 }
 
 impl RelayPairingEvents {
-    pub fn new(
-        user_id: String,
-        config: Arc<RwLock<Config>>,
-        analytics: Option<AnalyticsService>,
-    ) -> Self {
-        Self {
-            user_id,
-            config,
-            analytics,
-        }
+    pub fn new(user_id: String, analytics: Option<AnalyticsService>) -> Self {
+        Self { user_id, analytics }
     }
 
     pub async fn track_host_paired(
@@ -60,9 +57,9 @@ impl RelayPairingEvents {
         client_os: &str,
         client_device: &str,
     ) {
-        let analytics_enabled = self.config.read().await.analytics_enabled;
-        if analytics_enabled && let Some(analytics) = &self.analytics {
+        if let Some(analytics) = &self.analytics {
             analytics.track_event(
+                AnalyticsCategory::Usage,
                 &self.user_id,
                 "relay_host_paired",
                 Some(serde_json::json!({
@@ -154,11 +151,26 @@ impl RelayPairingServer {
             .collect())
     }
 
+    /// Unpair a client and end its signing sessions, so it can't keep
+    /// using the relay with a session it already holds.
     pub async fn remove_paired_client(&self, client_id: Uuid) -> Result<bool, ApiError> {
-        self.trusted_key_auth
+        let client = self.trusted_key_auth.find_trusted_client(client_id).await?;
+        let removed = self
+            .trusted_key_auth
             .remove_trusted_client(client_id)
-            .await
-            .map_err(ApiError::from)
+            .await?;
+
+        if removed
+            && let Some(public_key) =
+                client.and_then(|client| parse_public_key_base64(&client.public_key_b64).ok())
+        {
+            let revoked_sessions = self
+                .relay_signing
+                .revoke_sessions_for_key(&public_key)
+                .await;
+            tracing::info!(%client_id, revoked_sessions, "removed relay paired client");
+        }
+        Ok(removed)
     }
 
     pub async fn finish_spake2_enrollment(
@@ -198,7 +210,14 @@ impl RelayPairingServer {
             })
             .await?;
 
-        let signing_session_id = self.relay_signing.create_session(client_public_key).await;
+        let (signing_session_id, encryption) = self
+            .create_signing_session(
+                payload.client_id,
+                client_public_key,
+                payload.encryption_public_key_b64.as_deref(),
+                payload.encryption_key_signature_b64.as_deref(),
+            )
+            .await?;
 
         let server_proof_b64 = build_server_proof(
             &shared_key,
@@ -229,6 +248,10 @@ impl RelayPairingServer {
             signing_session_id,
             server_public_key_b64,
             server_proof_b64,
+            encryption_public_key_b64: encryption
+                .as_ref()
+                .map(|key| key.encryption_public_key_b64.clone()),
+            encryption_key_signature_b64: encryption.map(|key| key.encryption_key_signature_b64),
         })
     }
 
@@ -263,9 +286,124 @@ impl RelayPairingServer {
             build_refresh_message(payload.timestamp, &payload.nonce, payload.client_id);
         verify_refresh_signature(&client_public_key, &refresh_message, &payload.signature_b64)?;
 
-        let signing_session_id = self.relay_signing.create_session(client_public_key).await;
+        let (signing_session_id, encryption) = self
+            .create_signing_session(
+                payload.client_id,
+                client_public_key,
+                payload.encryption_public_key_b64.as_deref(),
+                payload.encryption_key_signature_b64.as_deref(),
+            )
+            .await?;
+
+        Ok(RefreshRelaySigningSessionResponse {
+            signing_session_id,
+            encryption_public_key_b64: encryption
+                .as_ref()
+                .map(|key| key.encryption_public_key_b64.clone()),
+            encryption_key_signature_b64: encryption.map(|key| key.encryption_key_signature_b64),
+        })
+    }
+
+    /// Create a signing session, negotiating end-to-end encryption when the
+    /// client offered an encryption key signed by its identity key.
+    async fn create_signing_session(
+        &self,
+        client_id: Uuid,
+        client_public_key: VerifyingKey,
+        encryption_public_key_b64: Option<&str>,
+        encryption_key_signature_b64: Option<&str>,
+    ) -> Result<(Uuid, Option<EncryptedSessionKey>), ApiError> {
+        let Some(encryption_public_key_b64) = encryption_public_key_b64 else {
+            let signing_session_id = self.relay_signing.create_session(client_public_key).await;
+            return Ok((signing_session_id, None));
+        };
+        let encryption_key_signature_b64 = encryption_key_signature_b64.ok_or_else(|| {
+            ApiError::BadRequest("Missing encryption_key_signature_b64".to_string())
+        })?;
+
+        verify_refresh_signature(
+            &client_public_key,
+            &client_encryption_key_message(client_id, encryption_public_key_b64),
+            encryption_key_signature_b64,
+        )?;
+
+        let encryption = self
+            .relay_signing
+            .create_encrypted_session(client_public_key, encryption_public_key_b64)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        Ok((encryption.signing_session_id, Some(encryption)))
+    }
+
+    /// Replace a paired client's key without re-pairing. The old key's
+    /// signing sessions are revoked and a session for the new key returned.
+    pub async fn rotate_client_key(
+        &self,
+        payload: RotateRelayClientKeyRequest,
+    ) -> Result<RotateRelayClientKeyResponse, ApiError> {
+        self.trusted_key_auth
+            .enforce_rate_limit(
+                "relay-auth:key-rotation:global",
+                KEY_ROTATION_GLOBAL_LIMIT,
+                RATE_LIMIT_WINDOW,
+            )
+            .await
+            .map_err(ApiError::from)?;
+
+        let trusted_client = self
+            .trusted_key_auth
+            .find_trusted_client(payload.client_id)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        let current_public_key = parse_public_key_base64(&trusted_client.public_key_b64)
+            .map_err(|_| ApiError::Unauthorized)?;
+        let new_public_key = parse_public_key_base64(&payload.new_public_key_b64)
+            .map_err(|_| ApiError::BadRequest("Invalid new_public_key_b64".to_string()))?;
+
+        validate_key_rotation_timestamp(payload.timestamp)?;
+        self.trusted_key_auth
+            .claim_refresh_nonce(&payload.nonce)
+            .await?;
+
+        let rotation_message = build_key_rotation_message(
+            payload.timestamp,
+            &payload.nonce,
+            payload.client_id,
+            &payload.new_public_key_b64,
+        );
+        verify_key_rotation(
+            &current_public_key,
+            &new_public_key,
+            &rotation_message,
+            &payload.signature_b64,
+            &payload.new_key_signature_b64,
+        )?;
+
+        // A concurrent rotation or removal wins; the caller must re-pair.
+        self.trusted_key_auth
+            .rotate_trusted_client_key(
+                payload.client_id,
+                &trusted_client.public_key_b64,
+                &payload.new_public_key_b64,
+            )
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        let revoked_sessions = self
+            .relay_signing
+            .revoke_sessions_for_key(&current_public_key)
+            .await;
+        let signing_session_id = self.relay_signing.create_session(new_public_key).await;
+
+        tracing::info!(
+            client_id = %payload.client_id,
+            revoked_sessions,
+            signing_session_id = %signing_session_id,
+            "rotated relay client key"
+        );
 
-        Ok(RefreshRelaySigningSessionResponse { signing_session_id })
+        Ok(RotateRelayClientKeyResponse { signing_session_id })
     }
 }
 
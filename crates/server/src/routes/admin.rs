@@ -0,0 +1,178 @@
+//! Live performance debugging endpoints.
+//!
+//! Disabled unless the server is started with `VK_ENABLE_PROFILER=1`, and
+//! never reachable through the relay. The CPU profiler and allocation
+//! counters are only compiled into builds with the `profiling` feature.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::Query,
+    http::{HeaderMap, StatusCode, header},
+    response::{Json as ResponseJson, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use services::services::shared_watcher::{self, WatcherHubStats};
+use utils::{alloc_stats::AllocStats, response::ApiResponse};
+
+use crate::{DeploymentImpl, error::ApiError, relay_pairing::server::is_relay_request};
+
+const DEFAULT_PROFILE_SECONDS: u64 = 30;
+const MAX_PROFILE_SECONDS: u64 = 300;
+
+static PROFILE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFormat {
+    /// SVG flamegraph, viewable directly in a browser.
+    #[default]
+    Flamegraph,
+    /// Protobuf profile for `go tool pprof` or speedscope.
+    Pprof,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    pub seconds: Option<u64>,
+    #[serde(default)]
+    pub format: ProfileFormat,
+}
+
+fn profiler_enabled() -> bool {
+    std::env::var("VK_ENABLE_PROFILER").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+fn ensure_local_admin(headers: &HeaderMap) -> Result<(), ApiError> {
+    if !profiler_enabled() {
+        return Err(ApiError::Forbidden(
+            "Profiling is disabled. Restart the server with VK_ENABLE_PROFILER=1.".to_string(),
+        ));
+    }
+    if is_relay_request(headers) {
+        return Err(ApiError::Forbidden(
+            "Profiling is only available from the local machine".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Releases the single-profile slot when dropped.
+struct ProfileSlot;
+
+impl ProfileSlot {
+    fn acquire() -> Option<Self> {
+        PROFILE_IN_PROGRESS
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ProfileSlot {
+    fn drop(&mut self) {
+        PROFILE_IN_PROGRESS.store(false, Ordering::Release);
+    }
+}
+
+pub async fn profile(
+    headers: HeaderMap,
+    Query(query): Query<ProfileQuery>,
+) -> Result<Response, ApiError> {
+    ensure_local_admin(&headers)?;
+    let seconds = query
+        .seconds
+        .unwrap_or(DEFAULT_PROFILE_SECONDS)
+        .clamp(1, MAX_PROFILE_SECONDS);
+    let slot = ProfileSlot::acquire()
+        .ok_or_else(|| ApiError::Conflict("A profile is already being collected".to_string()))?;
+
+    tracing::info!("Collecting {}s CPU profile", seconds);
+    let format = query.format;
+    let body = tokio::task::spawn_blocking(move || {
+        let _slot = slot;
+        collect_profile(Duration::from_secs(seconds), format)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let (content_type, filename) = match format {
+        ProfileFormat::Flamegraph => ("image/svg+xml", "profile.svg"),
+        ProfileFormat::Pprof => ("application/octet-stream", "profile.pb"),
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}\"", filename),
+        )
+        .body(Body::from(body))
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+}
+
+#[cfg(all(unix, feature = "profiling"))]
+fn collect_profile(duration: Duration, format: ProfileFormat) -> Result<Vec<u8>, ApiError> {
+    use pprof::protos::Message;
+
+    const SAMPLE_FREQUENCY_HZ: i32 = 99;
+
+    let to_api_error = |e: pprof::Error| ApiError::Io(std::io::Error::other(e));
+
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(SAMPLE_FREQUENCY_HZ)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(to_api_error)?;
+    std::thread::sleep(duration);
+    let report = guard.report().build().map_err(to_api_error)?;
+
+    let mut body = Vec::new();
+    match format {
+        ProfileFormat::Flamegraph => report.flamegraph(&mut body).map_err(to_api_error)?,
+        ProfileFormat::Pprof => report
+            .pprof()
+            .map_err(to_api_error)?
+            .encode(&mut body)
+            .map_err(|e| ApiError::Io(std::io::Error::other(e)))?,
+    }
+    Ok(body)
+}
+
+#[cfg(not(all(unix, feature = "profiling")))]
+fn collect_profile(_duration: Duration, _format: ProfileFormat) -> Result<Vec<u8>, ApiError> {
+    Err(ApiError::BadRequest(
+        "CPU profiling needs a Unix build with the `profiling` feature".to_string(),
+    ))
+}
+
+pub async fn alloc_stats(
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<AllocStats>>, ApiError> {
+    ensure_local_admin(&headers)?;
+    Ok(ResponseJson(ApiResponse::success(
+        utils::alloc_stats::snapshot(),
+    )))
+}
+
+pub async fn watcher_stats(
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<WatcherHubStats>>, ApiError> {
+    ensure_local_admin(&headers)?;
+    Ok(ResponseJson(ApiResponse::success(
+        shared_watcher::hub().stats(),
+    )))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/admin/profile", get(profile))
+        .route("/admin/alloc-stats", get(alloc_stats))
+        .route("/admin/watcher-stats", get(watcher_stats))
+}
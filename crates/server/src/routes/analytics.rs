@@ -0,0 +1,24 @@
+use axum::{Router, extract::State, response::Json, routing::get};
+use deployment::Deployment;
+use services::services::analytics::RecordedAnalyticsEvent;
+use utils::response::ApiResponse;
+
+use crate::DeploymentImpl;
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/analytics/recent", get(get_recent_analytics_events))
+}
+
+/// The last events this host tracked, newest first, with the exact payloads
+/// sent. Events outside the user's consent are listed but marked unsent.
+/// Empty when this build has no analytics backend.
+async fn get_recent_analytics_events(
+    State(deployment): State<DeploymentImpl>,
+) -> Json<ApiResponse<Vec<RecordedAnalyticsEvent>>> {
+    let events = deployment
+        .analytics()
+        .as_ref()
+        .map(|analytics| analytics.recent_events())
+        .unwrap_or_default();
+    Json(ApiResponse::success(events))
+}
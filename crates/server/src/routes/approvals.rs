@@ -5,19 +5,84 @@ use axum::{
     response::{IntoResponse, Json as ResponseJson},
     routing::{get, post},
 };
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL_SAFE_NO_PAD};
+use db::models::{
+    activity_event::ActivityEvent,
+    audit_log::{AuditActor, AuditActorKind},
+};
 use deployment::Deployment;
+use ed25519_dalek::{Signature, Verifier};
 use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use services::services::approvals::{ApprovalError, ApprovalInfo, ToolContext};
+use ts_rs::TS;
 use utils::{
     approvals::{ApprovalOutcome, ApprovalResponse},
     log_msg::LogMsg,
     response::ApiResponse,
 };
+use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
-    middleware::signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
+    error::ApiError,
+    middleware::{
+        RequestActor,
+        signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
+    },
 };
 
+/// One response in a batch. `execution_process_id` and `status` are the
+/// same as for a single response.
+#[derive(Debug, Deserialize, TS)]
+pub struct BatchApprovalItem {
+    pub approval_id: String,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub response: ApprovalResponse,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum BatchApprovalRequest {
+    /// Respond to each listed approval.
+    Responses { responses: Vec<BatchApprovalItem> },
+    /// Approve every pending tool approval of the execution process.
+    ApproveAllPending { execution_process_id: Uuid },
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DelegateApprovalRequest {
+    /// Trusted relay client to hand the approval to.
+    pub client_id: Uuid,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ApprovalDelegation {
+    pub approval: ApprovalInfo,
+    pub client_name: String,
+    /// Opens the approval on the delegated device.
+    pub deeplink_path: String,
+    /// Host signature to send back with the response. Valid only for the
+    /// delegated device and until the approval times out.
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DelegatedApprovalResponse {
+    pub signature: String,
+    pub status: ApprovalOutcome,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BatchApprovalResult {
+    pub approval_id: String,
+    /// Set if the response was accepted.
+    pub outcome: Option<ApprovalOutcome>,
+    pub error: Option<String>,
+}
+
 async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
     axum::extract::Path(id): axum::extract::Path<String>,
@@ -27,6 +92,8 @@ async fn respond_to_approval(
 
     match service.respond(&id, request).await {
         Ok((outcome, context)) => {
+            record_approval_activity(&deployment, &outcome, &context).await;
+
             deployment
                 .track_if_analytics_allowed(
                     "approval_responded",
@@ -41,6 +108,10 @@ async fn respond_to_approval(
 
             Ok(ResponseJson(ApiResponse::success(outcome)))
         }
+        Err(e @ (ApprovalError::InvalidStatus | ApprovalError::InvalidAnswer(_))) => {
+            tracing::warn!("Rejected approval response: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             tracing::error!("Failed to respond to approval: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -48,6 +119,256 @@ async fn respond_to_approval(
     }
 }
 
+/// Respond to several approvals at once. Each approval succeeds or fails on
+/// its own, e.g. one that timed out in the meantime does not stop the rest.
+async fn respond_to_approvals_batch(
+    State(deployment): State<DeploymentImpl>,
+    ResponseJson(request): ResponseJson<BatchApprovalRequest>,
+) -> ResponseJson<ApiResponse<Vec<BatchApprovalResult>>> {
+    let service = deployment.approvals();
+    let results = match request {
+        BatchApprovalRequest::Responses { responses } => {
+            let mut results = Vec::with_capacity(responses.len());
+            for item in responses {
+                let result = service.respond(&item.approval_id, item.response).await;
+                results.push((item.approval_id, result));
+            }
+            results
+        }
+        BatchApprovalRequest::ApproveAllPending {
+            execution_process_id,
+        } => service.approve_all_pending(execution_process_id).await,
+    };
+
+    let mut batch_results = Vec::with_capacity(results.len());
+    for (approval_id, result) in results {
+        batch_results.push(match result {
+            Ok((outcome, context)) => {
+                record_approval_activity(&deployment, &outcome, &context).await;
+                BatchApprovalResult {
+                    approval_id,
+                    outcome: Some(outcome),
+                    error: None,
+                }
+            }
+            Err(e) => BatchApprovalResult {
+                approval_id,
+                outcome: None,
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "approvals_batch_responded",
+            serde_json::json!({
+                "count": batch_results.len(),
+                "failed": batch_results.iter().filter(|r| r.error.is_some()).count(),
+            }),
+        )
+        .await;
+
+    ResponseJson(ApiResponse::success(batch_results))
+}
+
+/// Hand a pending approval to a trusted relay client, e.g. a phone, and
+/// return a signed deep link that opens it there.
+async fn delegate_approval(
+    State(deployment): State<DeploymentImpl>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    ResponseJson(request): ResponseJson<DelegateApprovalRequest>,
+) -> Result<ResponseJson<ApiResponse<ApprovalDelegation>>, ApiError> {
+    let client = deployment
+        .trusted_key_auth()
+        .find_trusted_client(request.client_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!("Relay client {} is not paired", request.client_id))
+        })?;
+    let approval = deployment
+        .approvals()
+        .delegate(&id, client.client_id)
+        .map_err(approval_error)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "approval_delegated",
+            serde_json::json!({
+                "approval_id": &id,
+                "tool_name": &approval.tool_name,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(build_delegation(
+        &deployment,
+        approval,
+        client.client_id,
+        client.client_name,
+    ))))
+}
+
+/// Pending approvals delegated to the relay client making the request.
+async fn get_delegated_approvals(
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalDelegation>>>, ApiError> {
+    let client_id = relay_client_id(&actor)?;
+    let client_name = actor.name.unwrap_or_default();
+    let delegations = deployment
+        .approvals()
+        .delegated_to(client_id)
+        .into_iter()
+        .map(|approval| build_delegation(&deployment, approval, client_id, client_name.clone()))
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(delegations)))
+}
+
+/// Answer a delegated approval from the device it was delegated to. The
+/// request must be relay-signed by that device and carry the deep link's
+/// signature.
+async fn respond_to_delegated_approval(
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    ResponseJson(request): ResponseJson<DelegatedApprovalResponse>,
+) -> Result<ResponseJson<ApiResponse<ApprovalOutcome>>, ApiError> {
+    let client_id = relay_client_id(&actor)?;
+    let service = deployment.approvals();
+    let approval = service
+        .pending_info(&id)
+        .ok_or_else(|| ApiError::Conflict("The approval is no longer pending".to_string()))?;
+    if approval.delegated_to != Some(client_id) {
+        return Err(ApiError::Forbidden(
+            "The approval is not delegated to this device".to_string(),
+        ));
+    }
+    verify_delegation_signature(&deployment, &approval, client_id, &request.signature)?;
+
+    let (outcome, context) = service
+        .respond(
+            &id,
+            ApprovalResponse {
+                execution_process_id: approval.execution_process_id,
+                status: request.status,
+            },
+        )
+        .await
+        .map_err(approval_error)?;
+    record_approval_activity(&deployment, &outcome, &context).await;
+
+    deployment
+        .track_if_analytics_allowed(
+            "approval_responded",
+            serde_json::json!({
+                "approval_id": &id,
+                "status": format!("{:?}", outcome),
+                "tool_name": context.tool_name,
+                "execution_process_id": context.execution_process_id.to_string(),
+                "delegated": true,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(outcome)))
+}
+
+/// Signed over the approval, the device and the approval's timeout, so a
+/// link is only good for that device and only while the approval is pending.
+fn delegation_message(approval: &ApprovalInfo, client_id: Uuid) -> Vec<u8> {
+    format!(
+        "vk-approval-delegation:v1|{}|{}|{}",
+        approval.approval_id,
+        client_id,
+        approval.timeout_at.timestamp()
+    )
+    .into_bytes()
+}
+
+fn build_delegation(
+    deployment: &DeploymentImpl,
+    approval: ApprovalInfo,
+    client_id: Uuid,
+    client_name: String,
+) -> ApprovalDelegation {
+    let signature = BASE64_URL_SAFE_NO_PAD.encode(
+        deployment
+            .relay_signing()
+            .sign_bytes(&delegation_message(&approval, client_id))
+            .to_bytes(),
+    );
+    let deeplink_path = format!(
+        "/approvals/{}/delegated?client_id={}&signature={}",
+        approval.approval_id, client_id, signature
+    );
+    ApprovalDelegation {
+        approval,
+        client_name,
+        deeplink_path,
+        signature,
+    }
+}
+
+fn verify_delegation_signature(
+    deployment: &DeploymentImpl,
+    approval: &ApprovalInfo,
+    client_id: Uuid,
+    signature: &str,
+) -> Result<(), ApiError> {
+    let invalid = || ApiError::Forbidden("Invalid delegation signature".to_string());
+    let bytes = BASE64_URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| invalid())?;
+    let signature = Signature::from_slice(&bytes).map_err(|_| invalid())?;
+    deployment
+        .relay_signing()
+        .server_public_key()
+        .verify(&delegation_message(approval, client_id), &signature)
+        .map_err(|_| invalid())
+}
+
+fn relay_client_id(actor: &AuditActor) -> Result<Uuid, ApiError> {
+    match (actor.kind, actor.client_id) {
+        (AuditActorKind::Relay, Some(client_id)) => Ok(client_id),
+        _ => Err(ApiError::Forbidden(
+            "Only a paired relay client can use delegated approvals".to_string(),
+        )),
+    }
+}
+
+fn approval_error(error: ApprovalError) -> ApiError {
+    match error {
+        ApprovalError::NotFound => ApiError::BadRequest("Approval not found".to_string()),
+        ApprovalError::AlreadyCompleted => {
+            ApiError::Conflict("The approval has already been answered".to_string())
+        }
+        error => ApiError::BadRequest(error.to_string()),
+    }
+}
+
+async fn record_approval_activity(
+    deployment: &DeploymentImpl,
+    outcome: &ApprovalOutcome,
+    context: &ToolContext,
+) {
+    let approved = match outcome {
+        ApprovalOutcome::Approved => Some(true),
+        ApprovalOutcome::Denied { .. } => Some(false),
+        _ => None,
+    };
+    if let Some(approved) = approved
+        && let Err(e) = ActivityEvent::record_approval(
+            &deployment.db().pool,
+            approved,
+            context.execution_process_id,
+        )
+        .await
+    {
+        tracing::warn!("Failed to record approval activity: {}", e);
+    }
+}
+
 async fn stream_approvals_ws(
     ws: SignedWsUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -109,5 +430,12 @@ async fn handle_approvals_ws(
 pub(super) fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/approvals/{id}/respond", post(respond_to_approval))
+        .route("/approvals/respond-batch", post(respond_to_approvals_batch))
+        .route("/approvals/{id}/delegate", post(delegate_approval))
+        .route(
+            "/approvals/{id}/respond-delegated",
+            post(respond_to_delegated_approval),
+        )
+        .route("/approvals/delegated", get(get_delegated_approvals))
         .route("/approvals/stream/ws", get(stream_approvals_ws))
 }
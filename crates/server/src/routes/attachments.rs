@@ -1,16 +1,20 @@
 use axum::{
-    Router,
-    body::Body,
+    Json, Router,
+    body::{Body, Bytes},
     extract::{DefaultBodyLimit, Multipart, Path, State},
     http::{StatusCode, header},
     response::{Json as ResponseJson, Response},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use chrono::{DateTime, Utc};
 use db::models::file::{File, WorkspaceAttachment};
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
-use services::services::file::FileError;
+use services::services::{
+    chunked_upload::{CHUNK_SIZE, ChunkedUploadStatus, InitChunkedUpload},
+    file::FileError,
+    image_processing::ImageLimits,
+};
 use tokio::fs::File as TokioFile;
 use tokio_util::io::ReaderStream;
 use ts_rs::TS;
@@ -84,7 +88,8 @@ pub async fn upload_file(
     State(deployment): State<DeploymentImpl>,
     multipart: Multipart,
 ) -> Result<ResponseJson<ApiResponse<AttachmentResponse>>, ApiError> {
-    let file_response = process_file_upload(&deployment, multipart, None).await?;
+    let file_response =
+        process_file_upload(&deployment, multipart, None, ImageLimits::default()).await?;
     Ok(ResponseJson(ApiResponse::success(file_response)))
 }
 
@@ -92,9 +97,8 @@ pub(crate) async fn process_file_upload(
     deployment: &DeploymentImpl,
     mut multipart: Multipart,
     link_workspace_id: Option<Uuid>,
+    image_limits: ImageLimits,
 ) -> Result<AttachmentResponse, ApiError> {
-    let file_service = deployment.file();
-
     while let Some(field) = multipart.next_field().await? {
         if field.name() == Some("image") {
             let filename = field
@@ -103,36 +107,113 @@ pub(crate) async fn process_file_upload(
                 .unwrap_or_else(|| "file.bin".to_string());
 
             let data = field.bytes().await?;
-            let file = file_service.store_file(&data, &filename).await?;
-
-            if let Some(workspace_id) = link_workspace_id {
-                WorkspaceAttachment::associate_many_dedup(
-                    &deployment.db().pool,
-                    workspace_id,
-                    std::slice::from_ref(&file.id),
-                )
-                .await?;
-            }
-
-            deployment
-                .track_if_analytics_allowed(
-                    "file_uploaded",
-                    serde_json::json!({
-                        "file_id": file.id.to_string(),
-                        "size_bytes": file.size_bytes,
-                        "mime_type": file.mime_type,
-                        "workspace_id": link_workspace_id.map(|id| id.to_string()),
-                    }),
-                )
-                .await;
-
-            return Ok(AttachmentResponse::from_file(file));
+            return store_upload(
+                deployment,
+                &data,
+                &filename,
+                link_workspace_id,
+                image_limits,
+            )
+            .await;
         }
     }
 
     Err(ApiError::File(FileError::NotFound))
 }
 
+/// Store uploaded content and link it to the workspace, if any.
+pub(crate) async fn store_upload(
+    deployment: &DeploymentImpl,
+    data: &[u8],
+    filename: &str,
+    link_workspace_id: Option<Uuid>,
+    image_limits: ImageLimits,
+) -> Result<AttachmentResponse, ApiError> {
+    let file = deployment
+        .file()
+        .store_file(data, filename, image_limits)
+        .await?;
+
+    if let Some(workspace_id) = link_workspace_id {
+        WorkspaceAttachment::associate_many_dedup(
+            &deployment.db().pool,
+            workspace_id,
+            std::slice::from_ref(&file.id),
+        )
+        .await?;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "file_uploaded",
+            serde_json::json!({
+                "file_id": file.id.to_string(),
+                "size_bytes": file.size_bytes,
+                "mime_type": file.mime_type,
+                "workspace_id": link_workspace_id.map(|id| id.to_string()),
+            }),
+        )
+        .await;
+
+    Ok(AttachmentResponse::from_file(file))
+}
+
+/// Start a chunked upload. Chunks are then sent with
+/// `PUT /attachments/uploads/{upload_id}/chunks/{index}`.
+pub async fn init_chunked_upload(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<InitChunkedUpload>,
+) -> Result<ResponseJson<ApiResponse<ChunkedUploadStatus>>, ApiError> {
+    let status = deployment.file().chunked_uploads().init(&payload).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+/// Which chunks are still missing, to resume an interrupted upload.
+pub async fn get_chunked_upload(
+    Path(upload_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ChunkedUploadStatus>>, ApiError> {
+    let status = deployment
+        .file()
+        .chunked_uploads()
+        .status(upload_id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn upload_chunk(
+    Path((upload_id, index)): Path<(Uuid, u32)>,
+    State(deployment): State<DeploymentImpl>,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<ChunkedUploadStatus>>, ApiError> {
+    let status = deployment
+        .file()
+        .chunked_uploads()
+        .write_chunk(upload_id, index, &body)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn complete_chunked_upload(
+    Path(upload_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttachmentResponse>>, ApiError> {
+    let upload = deployment
+        .file()
+        .chunked_uploads()
+        .complete(upload_id)
+        .await?;
+    let response = store_upload(
+        &deployment,
+        &upload.data,
+        &upload.filename,
+        None,
+        ImageLimits::default(),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
 pub async fn serve_file(
     Path(file_id): Path<Uuid>,
     State(deployment): State<DeploymentImpl>,
@@ -188,6 +269,16 @@ pub fn routes() -> Router<DeploymentImpl> {
             "/upload",
             post(upload_file).layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
         )
+        .route("/uploads", post(init_chunked_upload))
+        .route("/uploads/{upload_id}", get(get_chunked_upload))
+        .route(
+            "/uploads/{upload_id}/chunks/{index}",
+            put(upload_chunk).layer(DefaultBodyLimit::max(CHUNK_SIZE as usize)),
+        )
+        .route(
+            "/uploads/{upload_id}/complete",
+            post(complete_chunked_upload),
+        )
         .route("/{id}/file", get(serve_file))
         .route("/{id}", delete(delete_file))
 }
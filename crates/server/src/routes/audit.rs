@@ -0,0 +1,31 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::audit_log::{AuditLogEntry, AuditLogQuery};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+/// Audit log entries matching the filters, newest first.
+pub async fn get_audit_log(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AuditLogEntry>>>, ApiError> {
+    let limit = query
+        .limit
+        .map_or(DEFAULT_AUDIT_LIMIT, i64::from)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    let entries = AuditLogEntry::find(&deployment.db().pool, &query, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/audit", get(get_audit_log))
+}
@@ -0,0 +1,232 @@
+//! Kanban boards. Every change responds with the board's full state and sends
+//! the same state over the event stream, so all clients agree on the order of
+//! columns and cards.
+
+use std::collections::HashSet;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use db::models::{
+    board::{
+        Board, BoardCard, BoardColumn, BoardState, CreateBoard, CreateBoardColumn, MoveBoardCard,
+        ReorderBoardColumns, UpdateBoard, UpdateBoardColumn,
+    },
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_board_middleware};
+
+pub async fn get_boards(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Board>>>, ApiError> {
+    let boards = Board::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(boards)))
+}
+
+pub async fn create_board(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateBoard>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    validate_name("Board", &payload.name)?;
+    for column in payload.columns.iter().flatten() {
+        validate_name("Column", column)?;
+    }
+
+    let board = Board::create(&deployment.db().pool, &payload).await?;
+    deployment
+        .track_if_analytics_allowed(
+            "board_created",
+            serde_json::json!({
+                "columns": payload.columns.as_ref().map_or(0, Vec::len),
+            }),
+        )
+        .await;
+    publish(&deployment, board.id).await
+}
+
+pub async fn get_board(
+    Extension(board): Extension<Board>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(
+        load_state(&deployment, board.id).await?,
+    )))
+}
+
+pub async fn update_board(
+    Extension(board): Extension<Board>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateBoard>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    validate_name("Board", &payload.name)?;
+    Board::update(&deployment.db().pool, board.id, &payload.name).await?;
+    publish(&deployment, board.id).await
+}
+
+pub async fn delete_board(
+    Extension(board): Extension<Board>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = Board::delete(&deployment.db().pool, board.id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    deployment.events().push_board_removed(board.id);
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn create_column(
+    Extension(board): Extension<Board>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateBoardColumn>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    validate_name("Column", &payload.name)?;
+    BoardColumn::create(&deployment.db().pool, board.id, &payload).await?;
+    publish(&deployment, board.id).await
+}
+
+/// Drag-reorder of columns. The request lists every column of the board in
+/// its new order, so a client working from a stale board is rejected instead
+/// of silently dropping a column someone else added.
+pub async fn reorder_columns(
+    Extension(board): Extension<Board>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderBoardColumns>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let existing: HashSet<Uuid> = BoardColumn::find_by_board_id(pool, board.id)
+        .await?
+        .into_iter()
+        .map(|column| column.id)
+        .collect();
+    let requested: HashSet<Uuid> = payload.column_ids.iter().copied().collect();
+    if requested.len() != payload.column_ids.len() || requested != existing {
+        return Err(ApiError::Conflict(
+            "Column order must list every column of the board exactly once".to_string(),
+        ));
+    }
+
+    BoardColumn::reorder(pool, board.id, &payload.column_ids).await?;
+    publish(&deployment, board.id).await
+}
+
+pub async fn update_column(
+    State(deployment): State<DeploymentImpl>,
+    Path((board_id, column_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateBoardColumn>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    validate_name("Column", &payload.name)?;
+    let column = find_column(&deployment, board_id, column_id).await?;
+    BoardColumn::update(&deployment.db().pool, column.id, &payload.name).await?;
+    publish(&deployment, board_id).await
+}
+
+/// Delete a column. Its cards are taken off the board.
+pub async fn delete_column(
+    State(deployment): State<DeploymentImpl>,
+    Path((board_id, column_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    let column = find_column(&deployment, board_id, column_id).await?;
+    BoardColumn::delete(&deployment.db().pool, &column).await?;
+    publish(&deployment, board_id).await
+}
+
+/// Put a workspace on the board, or drag it to another column or position.
+pub async fn move_card(
+    State(deployment): State<DeploymentImpl>,
+    Path((board_id, workspace_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<MoveBoardCard>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    let pool = &deployment.db().pool;
+    find_column(&deployment, board_id, payload.column_id).await?;
+    if Workspace::find_by_id(pool, workspace_id).await?.is_none() {
+        return Err(ApiError::BadRequest(format!(
+            "Workspace {workspace_id} not found"
+        )));
+    }
+
+    BoardCard::move_to(pool, board_id, workspace_id, &payload).await?;
+    publish(&deployment, board_id).await
+}
+
+pub async fn remove_card(
+    State(deployment): State<DeploymentImpl>,
+    Path((board_id, workspace_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    let removed = BoardCard::remove(&deployment.db().pool, board_id, workspace_id).await?;
+    if removed == 0 {
+        return Err(ApiError::BadRequest(
+            "Workspace is not on this board".to_string(),
+        ));
+    }
+    publish(&deployment, board_id).await
+}
+
+async fn load_state(deployment: &DeploymentImpl, board_id: Uuid) -> Result<BoardState, ApiError> {
+    BoardState::load(&deployment.db().pool, board_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest(format!("Board {board_id} not found")))
+}
+
+/// Send the board's state to event stream clients and return it.
+async fn publish(
+    deployment: &DeploymentImpl,
+    board_id: Uuid,
+) -> Result<ResponseJson<ApiResponse<BoardState>>, ApiError> {
+    let state = load_state(deployment, board_id).await?;
+    deployment.events().push_board_state(&state);
+    Ok(ResponseJson(ApiResponse::success(state)))
+}
+
+async fn find_column(
+    deployment: &DeploymentImpl,
+    board_id: Uuid,
+    column_id: Uuid,
+) -> Result<BoardColumn, ApiError> {
+    BoardColumn::find_by_id(&deployment.db().pool, column_id)
+        .await?
+        .filter(|column| column.board_id == board_id)
+        .ok_or_else(|| ApiError::BadRequest(format!("Column {column_id} not found on board")))
+}
+
+fn validate_name(kind: &str, name: &str) -> Result<(), ApiError> {
+    if name.trim().is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "{kind} name must not be empty"
+        )));
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let board_router = Router::new()
+        .route("/", get(get_board).put(update_board).delete(delete_board))
+        .route("/columns", post(create_column))
+        .route("/columns/order", put(reorder_columns))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_board_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_boards).post(create_board))
+        .nest("/{board_id}", board_router)
+        .route(
+            "/{board_id}/columns/{column_id}",
+            put(update_column).delete(delete_column),
+        )
+        .route(
+            "/{board_id}/cards/{workspace_id}",
+            put(move_card).delete(remove_card),
+        );
+
+    Router::new().nest("/boards", inner)
+}
@@ -9,6 +9,7 @@ use axum::{
     response::{IntoResponse, Json as ResponseJson, Response},
     routing::{get, put},
 };
+use db::models::audit_log::{AuditAction, CreateAuditLogEntry};
 use deployment::{Deployment, DeploymentError};
 use executors::{
     executors::{
@@ -20,8 +21,9 @@ use executors::{
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use services::services::{
+    audit,
     config::{
-        Config, ConfigError, SoundFile,
+        Config, ConfigError, SoundFile, WorktreeStrategy,
         editor::{EditorConfig, EditorType},
         save_config_to_file,
     },
@@ -30,13 +32,18 @@ use services::services::{
 };
 use tokio::fs;
 use ts_rs::TS;
-use utils::{assets::config_path, log_msg::LogMsg, response::ApiResponse};
+use utils::{assets::config_path, log_msg::LogMsg, response::ApiResponse, sentry as sentry_utils};
 use uuid::Uuid;
+use worktree_manager::WorktreeManager;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
-    middleware::signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
+    middleware::{
+        RequestActor,
+        signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
+    },
+    routes::prompt_snippets::validate_prompt_snippets,
     runtime::relay_registration,
 };
 
@@ -59,6 +66,25 @@ pub fn router() -> Router<DeploymentImpl> {
         )
 }
 
+/// Sent in place of the webhook token in config responses. A config sent
+/// back with it keeps the stored token.
+const REDACTED_WEBHOOK_TOKEN: &str = "********";
+
+/// The config as returned to clients, without the webhook secret.
+fn redact_config(mut config: Config) -> Config {
+    if config.webhook_token.is_some() {
+        config.webhook_token = Some(REDACTED_WEBHOOK_TOKEN.to_string());
+    }
+    config
+}
+
+/// Keep the stored webhook token when a client saves a config it read back.
+fn restore_webhook_token(new: &mut Config, old: &Config) {
+    if new.webhook_token.as_deref() == Some(REDACTED_WEBHOOK_TOKEN) {
+        new.webhook_token = old.webhook_token.clone();
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct Environment {
     pub os_type: String,
@@ -154,7 +180,7 @@ async fn get_user_system_info(
 
     let user_system_info = UserSystemInfo {
         version: env!("CARGO_PKG_VERSION").to_string(),
-        config,
+        config: redact_config(config),
         machine_id: deployment.user_id().to_string(),
         login_status,
         remote_auth_degraded: deployment.auth_context().remote_auth_degraded_slug().await,
@@ -179,7 +205,7 @@ async fn get_user_system_info(
 
 async fn update_config(
     State(deployment): State<DeploymentImpl>,
-    Json(new_config): Json<Config>,
+    Json(mut new_config): Json<Config>,
 ) -> ResponseJson<ApiResponse<Config>> {
     let config_path = config_path();
 
@@ -190,8 +216,13 @@ async fn update_config(
         ));
     }
 
+    if let Err(message) = validate_prompt_snippets(&new_config.prompt_snippets) {
+        return ResponseJson(ApiResponse::error(&message));
+    }
+
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
+    restore_webhook_token(&mut new_config, &old_config);
 
     match save_config_to_file(&new_config, &config_path).await {
         Ok(_) => {
@@ -202,7 +233,7 @@ async fn update_config(
             // Track config events when fields transition from false → true and run side effects
             handle_config_events(&deployment, &old_config, &new_config).await;
 
-            ResponseJson(ApiResponse::success(new_config))
+            ResponseJson(ApiResponse::success(redact_config(new_config)))
         }
         Err(e) => ResponseJson(ApiResponse::error(&format!("Failed to save config: {}", e))),
     }
@@ -225,7 +256,7 @@ async fn track_config_events(deployment: &DeploymentImpl, old: &Config, new: &Co
             }),
         ),
         (
-            !old.analytics_enabled && new.analytics_enabled,
+            !old.analytics.usage && new.analytics.usage,
             "analytics_session_start",
             serde_json::json!({}),
         ),
@@ -243,6 +274,12 @@ async fn track_config_events(deployment: &DeploymentImpl, old: &Config, new: &Co
 async fn handle_config_events(deployment: &DeploymentImpl, old: &Config, new: &Config) {
     track_config_events(deployment, old, new).await;
 
+    sentry_utils::set_error_reporting(new.analytics.errors, new.error_reports_metadata_only);
+
+    if old.worktree_strategy != new.worktree_strategy {
+        WorktreeManager::set_reflink_worktrees(new.worktree_strategy == WorktreeStrategy::Reflink);
+    }
+
     let old_host_nickname = relay_registration::clean_host_nickname(old, deployment.user_id());
     let new_host_nickname = relay_registration::clean_host_nickname(new, deployment.user_id());
 
@@ -479,7 +516,8 @@ async fn get_profiles(
 }
 
 async fn update_profiles(
-    State(_deployment): State<DeploymentImpl>,
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
     body: String,
 ) -> ResponseJson<ApiResponse<String>> {
     // Try to parse as ExecutorProfileConfigs format
@@ -491,6 +529,20 @@ async fn update_profiles(
                     tracing::info!("Executor profiles saved successfully");
                     // Reload the cached profiles
                     ExecutorConfigs::reload();
+                    // Profiles hold each executor's permission settings.
+                    audit::record(
+                        &deployment.db().pool,
+                        CreateAuditLogEntry {
+                            actor,
+                            action: AuditAction::ApprovalPolicyChanged,
+                            target_type: "executor_profiles",
+                            target_id: None,
+                            details: serde_json::json!({
+                                "executors": executor_profiles.executors.keys().collect::<Vec<_>>(),
+                            }),
+                        },
+                    )
+                    .await;
                     ResponseJson(ApiResponse::success(
                         "Executor profiles updated successfully".to_string(),
                     ))
@@ -674,3 +726,46 @@ async fn handle_executor_discovered_options_ws(
         .await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_token_is_redacted_and_kept_on_save() {
+        let stored = Config {
+            webhook_token: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+
+        let mut returned = redact_config(stored.clone());
+        assert_eq!(
+            returned.webhook_token.as_deref(),
+            Some(REDACTED_WEBHOOK_TOKEN)
+        );
+        assert!(!serde_json::to_string(&returned).unwrap().contains("s3cret"));
+
+        restore_webhook_token(&mut returned, &stored);
+        assert_eq!(returned.webhook_token.as_deref(), Some("s3cret"));
+    }
+
+    #[test]
+    fn webhook_token_can_be_replaced_or_cleared() {
+        let stored = Config {
+            webhook_token: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+
+        let mut replaced = Config {
+            webhook_token: Some("rotated".to_string()),
+            ..Default::default()
+        };
+        restore_webhook_token(&mut replaced, &stored);
+        assert_eq!(replaced.webhook_token.as_deref(), Some("rotated"));
+
+        let mut cleared = Config::default();
+        restore_webhook_token(&mut cleared, &stored);
+        assert_eq!(cleared.webhook_token, None);
+        assert_eq!(redact_config(cleared).webhook_token, None);
+    }
+}
@@ -0,0 +1,107 @@
+//! Named config profiles. Each keeps its own config, credentials and
+//! database, so switching takes effect when the server next starts.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::{
+    assets::base_asset_dir,
+    config_profile::{self, ConfigProfileError},
+    response::ApiResponse,
+};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct ConfigProfiles {
+    /// The profile this server runs with.
+    pub active: String,
+    /// The profile the next start uses unless `--config-profile` or
+    /// `VK_CONFIG_PROFILE` names another.
+    pub startup: String,
+    pub profiles: Vec<String>,
+    /// Whether a restart is needed to run with the startup profile.
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ConfigProfileRequest {
+    pub name: String,
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route(
+            "/config/profiles",
+            get(get_config_profiles).post(create_config_profile),
+        )
+        .route("/config/profiles/startup", put(set_startup_config_profile))
+}
+
+async fn get_config_profiles(
+    State(_deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ConfigProfiles>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(config_profiles()?)))
+}
+
+/// Create an empty profile. It starts with the default config once selected.
+async fn create_config_profile(
+    State(_deployment): State<DeploymentImpl>,
+    Json(payload): Json<ConfigProfileRequest>,
+) -> Result<ResponseJson<ApiResponse<ConfigProfiles>>, ApiError> {
+    config_profile::create(&base_asset_dir(), payload.name.trim()).map_err(config_profile_error)?;
+    Ok(ResponseJson(ApiResponse::success(config_profiles()?)))
+}
+
+/// Switch to another profile. The server keeps running with the active one
+/// until restarted, since its database and connections are already open.
+async fn set_startup_config_profile(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ConfigProfileRequest>,
+) -> Result<ResponseJson<ApiResponse<ConfigProfiles>>, ApiError> {
+    let name = payload.name.trim();
+    config_profile::set_startup_profile(&base_asset_dir(), name).map_err(config_profile_error)?;
+    tracing::info!("Config profile '{name}' will be used from the next start");
+
+    deployment
+        .track_if_analytics_allowed(
+            "config_profile_switched",
+            serde_json::json!({ "is_default": name == config_profile::DEFAULT_PROFILE }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(config_profiles()?)))
+}
+
+fn config_profiles() -> Result<ConfigProfiles, ApiError> {
+    let base = base_asset_dir();
+    let active = config_profile::active(&base).to_string();
+    let startup = config_profile::startup_profile(&base)
+        .unwrap_or_else(|| config_profile::DEFAULT_PROFILE.to_string());
+    let mut profiles = config_profile::list(&base).map_err(config_profile_error)?;
+    // Selected with a flag or the environment without having been created.
+    if !profiles.contains(&active) {
+        profiles.push(active.clone());
+    }
+    Ok(ConfigProfiles {
+        restart_required: active != startup,
+        active,
+        startup,
+        profiles,
+    })
+}
+
+fn config_profile_error(error: ConfigProfileError) -> ApiError {
+    match error {
+        ConfigProfileError::AlreadyExists(_) => ApiError::Conflict(error.to_string()),
+        ConfigProfileError::Io(e) => ApiError::Io(e),
+        ConfigProfileError::InvalidName(_)
+        | ConfigProfileError::NotFound(_)
+        | ConfigProfileError::AlreadySelected => ApiError::BadRequest(error.to_string()),
+    }
+}
@@ -0,0 +1,195 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{delete, get, post},
+};
+use db::models::{
+    doc_source::{CreateDocSource, DocSource, DocSourceKind},
+    project::Project,
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use serde_json::json;
+use services::services::doc_index::{DocIndexService, DocSearchHit};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_SEARCH_LIMIT: usize = 5;
+const MAX_SEARCH_LIMIT: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct DocSearchQuery {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+pub async fn list_doc_sources(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<DocSource>>>, ApiError> {
+    let sources = DocSource::find_by_project_id(&deployment.db().pool, project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(sources)))
+}
+
+pub async fn create_doc_source(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<CreateDocSource>,
+) -> Result<ResponseJson<ApiResponse<DocSource>>, ApiError> {
+    if payload.name.trim().is_empty() || payload.location.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Documentation source name and location are required".to_string(),
+        ));
+    }
+    if payload.refresh_interval_minutes.is_some_and(|m| m <= 0) {
+        return Err(ApiError::BadRequest(
+            "Refresh interval must be a positive number of minutes".to_string(),
+        ));
+    }
+    let is_local = match payload.kind {
+        DocSourceKind::LocalFolder => true,
+        DocSourceKind::Openapi => !is_remote(&payload.location),
+        DocSourceKind::Url => false,
+    };
+    if is_local && !std::path::Path::new(&payload.location).is_absolute() {
+        return Err(ApiError::BadRequest(
+            "Local documentation sources must use an absolute path".to_string(),
+        ));
+    }
+
+    if !Project::exists(&deployment.db().pool, project_id).await? {
+        return Err(ApiError::NotFound("Project not found".to_string()));
+    }
+
+    let source = DocSource::create(&deployment.db().pool, project_id, &payload).await?;
+
+    // Index in the background so registration returns immediately; failures
+    // are recorded on the source.
+    let service = DocIndexService::new(deployment.db().clone());
+    let to_index = source.clone();
+    tokio::spawn(async move {
+        if let Err(e) = service.index_source(&to_index).await {
+            tracing::warn!(
+                "Initial indexing of doc source {} failed: {}",
+                to_index.id,
+                e
+            );
+        }
+    });
+
+    deployment
+        .track_if_analytics_allowed(
+            "doc_source_created",
+            json!({
+                "project_id": project_id.to_string(),
+                "kind": source.kind,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(source)))
+}
+
+pub async fn delete_doc_source(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, source_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let source = find_source(&deployment, project_id, source_id).await?;
+    DocSource::delete(&deployment.db().pool, source.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn refresh_doc_source(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, source_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<DocSource>>, ApiError> {
+    let source = find_source(&deployment, project_id, source_id).await?;
+    DocIndexService::new(deployment.db().clone())
+        .index_source(&source)
+        .await?;
+    let refreshed = find_source(&deployment, project_id, source_id).await?;
+    Ok(ResponseJson(ApiResponse::success(refreshed)))
+}
+
+pub async fn search_docs(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<DocSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<DocSearchHit>>>, ApiError> {
+    search_project_docs(&deployment, project_id, &query).await
+}
+
+/// Search the docs of the project a workspace's task belongs to.
+pub async fn search_workspace_docs(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DocSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<DocSearchHit>>>, ApiError> {
+    let task = match workspace.task_id {
+        Some(task_id) => Task::find_by_id(&deployment.db().pool, task_id).await?,
+        None => None,
+    };
+    let Some(task) = task else {
+        return Err(ApiError::NotFound(
+            "Workspace is not linked to a project".to_string(),
+        ));
+    };
+    search_project_docs(&deployment, task.project_id, &query).await
+}
+
+async fn search_project_docs(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    query: &DocSearchQuery,
+) -> Result<ResponseJson<ApiResponse<Vec<DocSearchHit>>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Query parameter 'q' is required and cannot be empty".to_string(),
+        ));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let hits = DocIndexService::new(deployment.db().clone())
+        .search(project_id, &query.q, limit)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(hits)))
+}
+
+async fn find_source(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    source_id: Uuid,
+) -> Result<DocSource, ApiError> {
+    DocSource::find_by_id(&deployment.db().pool, source_id)
+        .await?
+        .filter(|source| source.project_id == project_id)
+        .ok_or_else(|| ApiError::NotFound("Documentation source not found".to_string()))
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route(
+            "/projects/{project_id}/docs",
+            get(list_doc_sources).post(create_doc_source),
+        )
+        .route("/projects/{project_id}/docs/search", get(search_docs))
+        .route(
+            "/projects/{project_id}/docs/{source_id}",
+            delete(delete_doc_source),
+        )
+        .route(
+            "/projects/{project_id}/docs/{source_id}/refresh",
+            post(refresh_doc_source),
+        )
+}
@@ -1,28 +1,115 @@
 use axum::{
     BoxError, Router,
-    extract::State,
+    extract::{Query, State, ws::Message},
+    http::HeaderMap,
     response::{
-        Sse,
+        IntoResponse, Json as ResponseJson, Sse,
         sse::{Event, KeepAlive},
     },
     routing::get,
 };
 use deployment::Deployment;
-use futures_util::TryStreamExt;
+use futures_util::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use services::services::events::EventsCheckpoint;
+use utils::response::ApiResponse;
 
-use crate::DeploymentImpl;
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
+};
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    /// `seq` of the last message received; only later messages are replayed.
+    /// Takes precedence over `Last-Event-ID`.
+    since_seq: Option<u64>,
+}
 
 async fn events(
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
 ) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, axum::http::StatusCode>
 {
-    // Ask the container service for a combined "history + live" stream
-    let stream = deployment.stream_events().await;
+    // Event ids are sequence numbers, so an EventSource reconnecting with
+    // `Last-Event-ID` only gets what it missed.
+    let since_seq = query.since_seq.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    });
+    let stream = deployment.stream_events(since_seq).await;
     Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
 }
 
+/// The event stream over WebSocket, for proxies that buffer SSE. Each
+/// message carries its sequence number as `seq`; gaps arrive as `resync`.
+async fn events_ws(
+    ws: SignedWsUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<EventsQuery>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_events_ws(socket, deployment, query.since_seq).await {
+            tracing::warn!("events WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_events_ws(
+    mut socket: MaybeSignedWebSocket,
+    deployment: DeploymentImpl,
+    since_seq: Option<u64>,
+) -> anyhow::Result<()> {
+    let mut stream = deployment.events().resume_stream(since_seq);
+
+    loop {
+        tokio::select! {
+            item = stream.next() => {
+                match item {
+                    Some(Ok(msg)) => {
+                        if socket.send(msg.to_ws_message()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("events stream error: {}", e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            inbound = socket.recv() => {
+                match inbound {
+                    Ok(Some(Message::Close(_))) => break,
+                    Ok(Some(_)) => {}
+                    Ok(None) => break,
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+    let _ = socket.close().await;
+    Ok(())
+}
+
+/// Lets clients of the event stream detect missed patches and refetch only
+/// the collections that drifted.
+async fn checkpoint(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<EventsCheckpoint>>, ApiError> {
+    let checkpoint = deployment.events().checkpoint().await?;
+    Ok(ResponseJson(ApiResponse::success(checkpoint)))
+}
+
 pub(super) fn router(_: &DeploymentImpl) -> Router<DeploymentImpl> {
-    let events_router = Router::new().route("/", get(events));
+    let events_router = Router::new()
+        .route("/", get(events))
+        .route("/ws", get(events_ws))
+        .route("/checkpoint", get(checkpoint));
 
     Router::new().nest("/events", events_router)
 }
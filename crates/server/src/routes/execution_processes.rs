@@ -1,27 +1,38 @@
 use anyhow;
 use axum::{
-    Extension, Router,
+    BoxError, Extension, Json, Router,
     extract::{Path, Query, State, ws::Message},
+    http::HeaderMap,
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
+    response::{
+        IntoResponse, Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
     routing::{get, post},
 };
 use db::models::{
+    audit_log::{AuditAction, CreateAuditLogEntry},
     execution_process::{ExecutionProcess, ExecutionProcessStatus},
     execution_process_repo_state::ExecutionProcessRepoState,
 };
 use deployment::Deployment;
-use futures_util::{StreamExt, TryStreamExt};
+use futures_util::{Stream, StreamExt, TryStreamExt, future};
 use serde::Deserialize;
-use services::services::container::ContainerService;
-use utils::{log_msg::LogMsg, response::ApiResponse};
+use services::services::{audit, container::ContainerService};
+use ts_rs::TS;
+use utils::{
+    log_msg::{LogMsg, LogResume, LogSpan},
+    process::ProcessSignal,
+    process_tree::ProcessTreeNode,
+    response::ApiResponse,
+};
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
     middleware::{
-        load_execution_process_middleware,
+        RateLimitedRoute, RequestActor, load_execution_process_middleware, rate_limit,
         signed_ws::{MaybeSignedWebSocket, SignedWsUpgrade},
     },
 };
@@ -200,15 +211,117 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+pub(crate) struct LogStreamQuery {
+    /// The `id` of the last event received; entries before it are skipped.
+    /// Takes precedence over `Last-Event-ID`.
+    pub since_entry: Option<usize>,
+}
+
+/// Tail one process's normalized log entries as server-sent events.
+///
+/// Each event's `id` is the highest conversation entry index sent so far, so
+/// a client that reconnects with `since_entry` (or an EventSource sending
+/// `Last-Event-ID`) picks up where it left off instead of replaying the whole
+/// history, whether the process is still running or has finished since.
+pub(crate) async fn stream_logs_sse(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogStreamQuery>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, BoxError>>> {
+    let since = query.since_entry.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    });
+
+    let stream = match deployment
+        .container()
+        .stream_normalized_logs(&execution_process.id)
+        .await
+    {
+        Some(stream) => stream,
+        None => futures_util::stream::once(async { Ok(LogMsg::Finished) }).boxed(),
+    };
+
+    let mut resume = LogResume::new(since);
+    let events = stream.filter_map(move |msg| {
+        let event = match msg {
+            Ok(LogMsg::JsonPatch(patch)) => resume
+                .resume(patch)
+                .map(|patch| Ok(with_id(LogMsg::JsonPatch(patch).to_sse_event(), &resume))),
+            Ok(msg) => Some(Ok(with_id(msg.to_sse_event(), &resume))),
+            Err(e) => Some(Err(BoxError::from(e))),
+        };
+        future::ready(event)
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn with_id(event: Event, resume: &LogResume) -> Event {
+    match resume.last_event_id() {
+        Some(id) => event.id(id),
+        None => event,
+    }
+}
+
 async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     deployment
         .container()
         .stop_execution(&execution_process, ExecutionProcessStatus::Killed)
         .await?;
 
+    audit::record(
+        &deployment.db().pool,
+        CreateAuditLogEntry {
+            actor,
+            action: AuditAction::ProcessKilled,
+            target_type: "execution_process",
+            target_id: Some(execution_process.id.to_string()),
+            details: serde_json::json!({
+                "session_id": execution_process.session_id,
+                "run_reason": execution_process.run_reason,
+            }),
+        },
+    )
+    .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SignalExecutionProcessRequest {
+    pub signal: ProcessSignal,
+}
+
+/// Pause, resume or interrupt a running execution without ending its
+/// session. Paused executions don't count towards the idle timeout.
+async fn signal_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SignalExecutionProcessRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !cfg!(unix) {
+        return Err(ApiError::BadRequest(
+            "Process signals are only supported on Unix".to_string(),
+        ));
+    }
+    if execution_process.status != ExecutionProcessStatus::Running {
+        return Err(ApiError::BadRequest(
+            "Execution process is not running".to_string(),
+        ));
+    }
+    deployment
+        .container()
+        .signal_execution(&execution_process, payload.signal)
+        .await?;
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
@@ -283,13 +396,61 @@ async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// Structured tool spans of a process's log: tool start/end, exit codes,
+/// files touched and token usage.
+async fn get_execution_process_spans(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<LogSpan>>>, ApiError> {
+    let spans = deployment
+        .container()
+        .log_spans(&execution_process.id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(spans)))
+}
+
+/// The processes a running execution has started, with their CPU and memory
+/// use, to tell a busy agent from a hung one before killing it.
+async fn get_execution_process_tree(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProcessTreeNode>>, ApiError> {
+    if execution_process.status != ExecutionProcessStatus::Running {
+        return Err(ApiError::BadRequest(
+            "Execution process is not running".to_string(),
+        ));
+    }
+    let tree = deployment
+        .container()
+        .process_tree(&execution_process)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Execution process has exited".to_string()))?;
+    Ok(ResponseJson(ApiResponse::success(tree)))
+}
+
 pub(super) fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
-        .route("/stop", post(stop_execution_process))
+        .route(
+            "/stop",
+            post(stop_execution_process).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::ProcessControl),
+                rate_limit,
+            )),
+        )
+        .route(
+            "/signal",
+            post(signal_execution_process).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::ProcessControl),
+                rate_limit,
+            )),
+        )
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/spans", get(get_execution_process_spans))
+        .route("/ps", get(get_execution_process_tree))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/logs/stream", get(stream_logs_sse))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
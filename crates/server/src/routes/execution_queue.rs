@@ -0,0 +1,83 @@
+//! Fair-share execution queue: status and per-project weights. Only applies
+//! when `max_concurrent_executions` or `execution_tag_limits` is set in the
+//! config.
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use db::models::{
+    project::Project,
+    project_execution_weight::{DEFAULT_EXECUTION_WEIGHT, ProjectExecutionWeight},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::execution_scheduler::ExecutionQueueStatus;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const MAX_EXECUTION_WEIGHT: u32 = 100;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetProjectExecutionWeightRequest {
+    pub weight: u32,
+}
+
+pub async fn get_execution_queue(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<ExecutionQueueStatus>> {
+    ResponseJson(ApiResponse::success(
+        deployment.execution_scheduler().status(),
+    ))
+}
+
+pub async fn list_execution_weights(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectExecutionWeight>>>, ApiError> {
+    let weights = ProjectExecutionWeight::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(weights)))
+}
+
+/// Set a project's share of the execution capacity. Applies to executions
+/// queued after the change.
+pub async fn set_execution_weight(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<SetProjectExecutionWeightRequest>,
+) -> Result<ResponseJson<ApiResponse<ProjectExecutionWeight>>, ApiError> {
+    if !(1..=MAX_EXECUTION_WEIGHT).contains(&payload.weight) {
+        return Err(ApiError::BadRequest(format!(
+            "Execution weight must be between 1 and {MAX_EXECUTION_WEIGHT}"
+        )));
+    }
+    let pool = &deployment.db().pool;
+    if !Project::exists(pool, project_id).await? {
+        return Err(ApiError::BadRequest("Project not found".to_string()));
+    }
+    let weight = ProjectExecutionWeight::set(pool, project_id, payload.weight).await?;
+    Ok(ResponseJson(ApiResponse::success(weight)))
+}
+
+/// Reset a project to the default weight.
+pub async fn delete_execution_weight(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<u32>>, ApiError> {
+    ProjectExecutionWeight::delete(&deployment.db().pool, project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(DEFAULT_EXECUTION_WEIGHT)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/execution-queue", get(get_execution_queue))
+        .route("/execution-queue/weights", get(list_execution_weights))
+        .route(
+            "/execution-queue/weights/{project_id}",
+            put(set_execution_weight).delete(delete_execution_weight),
+        )
+}
@@ -28,6 +28,9 @@ pub async fn list_directory(
         Err(FilesystemError::PathIsNotDirectory) => {
             Ok(ResponseJson(ApiResponse::error("Path is not a directory")))
         }
+        Err(e @ FilesystemError::InvalidPath(_)) => {
+            Ok(ResponseJson(ApiResponse::error(&e.to_string())))
+        }
         Err(FilesystemError::Io(e)) => {
             tracing::error!("Failed to read directory: {}", e);
             Ok(ResponseJson(ApiResponse::error(&format!(
@@ -61,6 +64,9 @@ pub async fn list_git_repos(
         Err(FilesystemError::PathIsNotDirectory) => {
             Ok(ResponseJson(ApiResponse::error("Path is not a directory")))
         }
+        Err(e @ FilesystemError::InvalidPath(_)) => {
+            Ok(ResponseJson(ApiResponse::error(&e.to_string())))
+        }
         Err(FilesystemError::Io(e)) => {
             tracing::error!("Failed to read directory: {}", e);
             Ok(ResponseJson(ApiResponse::error(&format!(
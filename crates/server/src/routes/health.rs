@@ -1,6 +1,36 @@
-use axum::response::Json;
-use utils::response::ApiResponse;
+use axum::{extract::State, http::StatusCode, response::Json};
+use deployment::Deployment;
+use services::services::health::{
+    HealthReport, HealthStatus, RelayProbe, liveness_report, run_health_checks,
+};
+use utils::{assets::asset_dir, response::ApiResponse};
+
+use crate::DeploymentImpl;
 
 pub(super) async fn health_check() -> Json<ApiResponse<String>> {
     Json(ApiResponse::success("OK".to_string()))
 }
+
+/// Liveness probe: answers as long as the server can serve requests.
+pub(super) async fn health_live() -> Json<ApiResponse<HealthReport>> {
+    Json(ApiResponse::success(liveness_report()))
+}
+
+/// Run the dependency checks. Also serves readiness probes. Responds 503 when any check failed so uptime
+/// monitors can alert on the status code alone.
+pub(super) async fn health_checks(
+    State(deployment): State<DeploymentImpl>,
+) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    let relay = RelayProbe {
+        enabled: deployment.config().read().await.relay_enabled,
+        api_base: deployment.remote_info().get_relay_api_base(),
+        tunnel_active: deployment.relay_control().is_active().await,
+    };
+    let report = run_health_checks(deployment.db(), asset_dir(), relay).await;
+    let status = if report.status == HealthStatus::Failed {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(ApiResponse::success(report)))
+}
@@ -0,0 +1,277 @@
+//! Inbound webhooks that let external systems (issue trackers, CI) spawn
+//! agent tasks and report CI results. Requests authenticate with the
+//! `webhook_token` from config, sent as `Authorization: Bearer <token>`. CI
+//! webhooks from GitHub, which can't send that header, are signed with the
+//! token as the webhook secret instead.
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, header},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::post,
+};
+use db::models::{
+    repo::Repo,
+    requests::{
+        CreateAndStartWorkspaceRequest, CreateAndStartWorkspaceResponse, WorkspaceRepoInput,
+    },
+    workspace::Workspace,
+    workspace_ci_check::WorkspaceCiCheck,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorConfig;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use services::services::ci_webhook;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RateLimitedRoute, rate_limit},
+    routes::workspaces::create,
+};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct WebhookTaskRequest {
+    /// Repository ID or repository name.
+    pub repo: String,
+    /// Target branch for the workspace. Defaults to the repo's default target
+    /// branch, then its current branch.
+    pub branch: Option<String>,
+    pub prompt: String,
+    /// Executor to run. Defaults to the executor profile from config.
+    pub executor: Option<ExecutorConfig>,
+    /// Workspace name, also used for the git branch label.
+    pub name: Option<String>,
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.as_bytes().ct_eq(provided.as_bytes()).into()
+}
+
+/// Check a GitHub `X-Hub-Signature-256` header, `sha256=<hex HMAC>`, of
+/// `body` signed with `secret`.
+fn github_signature_matches(secret: &str, signature: &str, body: &[u8]) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+    else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.finalize().into_bytes()[..].ct_eq(&signature).into()
+}
+
+async fn webhook_token(deployment: &DeploymentImpl) -> Result<String, ApiError> {
+    let token = deployment.config().read().await.webhook_token.clone();
+    token.filter(|token| !token.is_empty()).ok_or_else(|| {
+        ApiError::Forbidden(
+            "Webhooks are disabled. Set a webhook token in settings to enable them.".to_string(),
+        )
+    })
+}
+
+async fn authorize(deployment: &DeploymentImpl, headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = webhook_token(deployment).await?;
+    match bearer_token(headers) {
+        Some(provided) if token_matches(&expected, provided) => Ok(()),
+        _ => Err(ApiError::Unauthorized),
+    }
+}
+
+async fn resolve_repo(deployment: &DeploymentImpl, repo: &str) -> Result<Repo, ApiError> {
+    let pool = &deployment.db().pool;
+    let found = match Uuid::parse_str(repo) {
+        Ok(id) => Repo::find_by_id(pool, id).await?,
+        Err(_) => Repo::list_all(pool)
+            .await?
+            .into_iter()
+            .find(|r| r.name == repo || r.display_name == repo),
+    };
+    found.ok_or_else(|| ApiError::BadRequest(format!("Repository '{}' not found", repo)))
+}
+
+pub async fn create_task_from_webhook(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+    Json(payload): Json<WebhookTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<CreateAndStartWorkspaceResponse>>, ApiError> {
+    authorize(&deployment, &headers).await?;
+
+    let repo = resolve_repo(&deployment, &payload.repo).await?;
+    let target_branch = match payload.branch.filter(|b| !b.trim().is_empty()) {
+        Some(branch) => branch,
+        None => match repo.default_target_branch.clone() {
+            Some(branch) => branch,
+            None => deployment.git().get_current_branch(&repo.path)?,
+        },
+    };
+    let executor_config = match payload.executor {
+        Some(executor_config) => executor_config,
+        None => {
+            let profile = deployment.config().read().await.executor_profile.clone();
+            ExecutorConfig {
+                variant: profile.variant,
+                ..ExecutorConfig::new(profile.executor)
+            }
+        }
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "webhook_task_received",
+            serde_json::json!({
+                "repo_id": repo.id.to_string(),
+                "executor": &executor_config.executor,
+            }),
+        )
+        .await;
+
+    create::create_and_start_workspace(
+        State(deployment),
+        Json(CreateAndStartWorkspaceRequest {
+            name: payload.name,
+            repos: vec![WorkspaceRepoInput {
+                repo_id: repo.id,
+                target_branch,
+            }],
+            linked_issue: None,
+            executor_config,
+            prompt: payload.prompt,
+            attachment_ids: None,
+            dev_server: None,
+            setup_script: None,
+            env_vars: None,
+        }),
+    )
+    .await
+}
+
+/// Record a CI check state reported for a branch on the workspaces of that
+/// branch. Returns the ids of the workspaces updated.
+pub async fn receive_ci_webhook(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<Vec<Uuid>>>, ApiError> {
+    let signature = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|value| value.to_str().ok());
+    match signature {
+        Some(signature) => {
+            let secret = webhook_token(&deployment).await?;
+            if !github_signature_matches(&secret, signature, &body) {
+                return Err(ApiError::Unauthorized);
+            }
+        }
+        None => authorize(&deployment, &headers).await?,
+    }
+
+    let payload: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid JSON payload: {e}")))?;
+    let github_event = headers
+        .get("X-GitHub-Event")
+        .and_then(|value| value.to_str().ok());
+    let Some(event) = ci_webhook::parse_ci_event(github_event, &payload)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid CI event: {e}")))?
+    else {
+        return Ok(ResponseJson(ApiResponse::success(Vec::new())));
+    };
+
+    let pool = &deployment.db().pool;
+    let mut updated = Vec::new();
+    for branch in &event.branches {
+        for workspace_id in
+            WorkspaceCiCheck::workspace_ids_for_branch(pool, branch, event.repo.as_deref()).await?
+        {
+            WorkspaceCiCheck::record(pool, workspace_id, &event.check).await?;
+            // Pushes the new status to the board
+            Workspace::touch(pool, workspace_id).await?;
+            updated.push(workspace_id);
+        }
+    }
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
+        .route(
+            "/hooks/task",
+            post(create_task_from_webhook).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::TaskCreation),
+                rate_limit,
+            )),
+        )
+        .route(
+            "/hooks/ci",
+            post(receive_ci_webhook).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::TaskCreation),
+                rate_limit,
+            )),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::{bearer_token, github_signature_matches, token_matches};
+
+    #[test]
+    fn extracts_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret-token"),
+        );
+        assert_eq!(bearer_token(&headers), Some("secret-token"));
+    }
+
+    #[test]
+    fn token_comparison_requires_exact_match() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secret", "secret2"));
+        assert!(!token_matches("secret", "Secret"));
+        assert!(!token_matches("secret", ""));
+    }
+
+    #[test]
+    fn verifies_github_signatures() {
+        let body = br#"{"zen":"Keep it logically awesome."}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(github_signature_matches("secret", &signature, body));
+        assert!(!github_signature_matches("other", &signature, body));
+        assert!(!github_signature_matches("secret", &signature, b"{}"));
+        assert!(!github_signature_matches("secret", "sha256=zz", body));
+    }
+}
@@ -4,19 +4,25 @@ use crate::{DeploymentImpl, middleware};
 
 mod open_remote_editor;
 mod proxy;
+mod remote_executions;
 
 pub use open_remote_editor::OpenRemoteWorkspaceInEditorRequest;
+pub use remote_executions::DispatchRemoteExecutionRequest;
 
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
-    Router::new().merge(proxy::router()).merge(
-        open_remote_editor::router()
-            .layer(axum::middleware::from_fn_with_state(
-                deployment.clone(),
-                middleware::sign_relay_response,
-            ))
-            .layer(axum::middleware::from_fn_with_state(
-                deployment.clone(),
-                middleware::require_relay_request_signature,
-            )),
-    )
+    Router::new()
+        .merge(proxy::router())
+        .merge(remote_executions::logs_router())
+        .merge(
+            open_remote_editor::router()
+                .merge(remote_executions::router())
+                .layer(axum::middleware::from_fn_with_state(
+                    deployment.clone(),
+                    middleware::sign_relay_response,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    deployment.clone(),
+                    middleware::require_relay_request_signature,
+                )),
+        )
 }
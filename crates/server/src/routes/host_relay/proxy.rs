@@ -1,10 +1,7 @@
 use axum::{
     Router,
     body::{Body, to_bytes},
-    extract::{
-        Path, Request, State,
-        ws::{WebSocketUpgrade, rejection::WebSocketUpgradeRejection},
-    },
+    extract::{FromRequestParts, Path, Request, State, ws::WebSocketUpgrade},
     http::{StatusCode, Uri},
     response::{IntoResponse, Response},
     routing::any,
@@ -16,7 +13,8 @@ use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError};
 
-type MaybeWsUpgrade = Result<WebSocketUpgrade, WebSocketUpgradeRejection>;
+/// Buffer between a passed-through WebSocket and the relay tunnel carrying it.
+const PREVIEW_TUNNEL_BUFFER_BYTES: usize = 64 * 1024;
 
 pub(super) fn router() -> Router<DeploymentImpl> {
     Router::new().route("/host/{host_id}/{*tail}", any(proxy_host_request))
@@ -25,13 +23,33 @@ pub(super) fn router() -> Router<DeploymentImpl> {
 async fn proxy_host_request(
     State(deployment): State<DeploymentImpl>,
     Path((host_id, tail)): Path<(Uuid, String)>,
-    ws_upgrade: MaybeWsUpgrade,
     mut request: Request,
 ) -> Result<Response, ApiError> {
     let query = request.uri().query().map(str::to_owned);
     let upstream_uri = upstream_api_uri(&tail, query.as_deref())?;
     *request.uri_mut() = upstream_uri;
 
+    if preview_proxy::wants_ws_passthrough(request.headers())
+        && let Some((target_port, path)) = preview_target(&tail)
+    {
+        let mut path_and_query = format!("/{path}");
+        if let Some(query) = &query {
+            path_and_query.push('?');
+            path_and_query.push_str(query);
+        }
+        return forward_preview_ws_passthrough(
+            &deployment,
+            host_id,
+            target_port,
+            &path_and_query,
+            request,
+        )
+        .await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let ws_upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &()).await;
+    let request = Request::from_parts(parts, body);
     match ws_upgrade {
         Ok(ws_upgrade) => forward_ws(&deployment, host_id, request, ws_upgrade).await,
         Err(_) => forward_http(&deployment, host_id, request).await,
@@ -103,6 +121,46 @@ async fn forward_ws(
         .into_response())
 }
 
+/// Pass a WebSocket upgrade for one of the host's previews through a raw
+/// tunnel to its dev server. Bridging messages would drop permessage-deflate,
+/// and HMR payloads crossing the relay are large enough for that to matter.
+async fn forward_preview_ws_passthrough(
+    deployment: &DeploymentImpl,
+    host_id: Uuid,
+    target_port: u16,
+    path_and_query: &str,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let relay_hosts = deployment.relay_hosts()?;
+    let relay_host = relay_hosts.host(host_id).await?;
+    let connection = relay_host
+        .proxy_ws(&format!("/api/preview-tunnel/{target_port}"), None)
+        .await?;
+
+    let (tunnel, relay_end) = tokio::io::duplex(PREVIEW_TUNNEL_BUFFER_BYTES);
+    tokio::spawn(async move {
+        if let Err(error) = connection.bridge_io(relay_end).await {
+            tracing::debug!(?error, "Preview tunnel closed with error");
+        }
+    });
+
+    let (mut parts, _) = request.into_parts();
+    Ok(preview_proxy::passthrough_ws_upgrade(
+        &mut parts,
+        tunnel,
+        &format!("localhost:{target_port}"),
+        path_and_query,
+    )
+    .await)
+}
+
+/// The dev server port and path of a `preview/{port}/{path}` route.
+fn preview_target(tail: &str) -> Option<(u16, &str)> {
+    let rest = tail.strip_prefix("preview/")?;
+    let (port, path) = rest.split_once('/').unwrap_or((rest, ""));
+    Some((port.parse().ok()?, path))
+}
+
 #[allow(clippy::result_large_err)]
 fn upstream_api_uri(tail: &str, query: Option<&str>) -> Result<Uri, ApiError> {
     let mut uri = String::from("/api/");
@@ -135,3 +193,20 @@ fn relay_http_response(response: ProxiedResponse) -> Response {
                 .into_response()
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_target_splits_port_and_path() {
+        assert_eq!(preview_target("preview/5173"), Some((5173, "")));
+        assert_eq!(preview_target("preview/5173/"), Some((5173, "")));
+        assert_eq!(
+            preview_target("preview/5173/@vite/client"),
+            Some((5173, "@vite/client"))
+        );
+        assert_eq!(preview_target("preview/vite/hmr"), None);
+        assert_eq!(preview_target("sessions/abc/stream"), None);
+    }
+}
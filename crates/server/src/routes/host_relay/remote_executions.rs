@@ -0,0 +1,346 @@
+//! Start workspaces on a paired host and follow them from here, so a laptop
+//! can orchestrate agents that run on a more powerful machine. Requests reach
+//! the host through the relay with the trusted keys set up at pairing.
+
+use axum::{
+    Json, Router,
+    extract::{Path, State, ws::WebSocketUpgrade},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use db::models::{
+    remote_execution::{CreateRemoteExecution, RemoteExecution},
+    repo::Repo,
+    requests::{
+        CreateAndStartWorkspaceRequest, CreateAndStartWorkspaceResponse, WorkspaceRepoInput,
+    },
+};
+use deployment::Deployment;
+use futures_util::StreamExt;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use relay_hosts::RelayHost;
+use serde::{Deserialize, de::DeserializeOwned};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub(super) fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route(
+            "/remote-executions",
+            get(list_remote_executions).post(dispatch_remote_execution),
+        )
+        .route(
+            "/remote-executions/{id}",
+            axum::routing::delete(forget_remote_execution),
+        )
+        .route("/remote-executions/{id}/stop", post(stop_remote_execution))
+}
+
+/// The log stream is bridged like the generic host proxy, so it sits outside
+/// the relay-signed routes.
+pub(super) fn logs_router() -> Router<DeploymentImpl> {
+    Router::new().route(
+        "/remote-executions/{id}/logs/ws",
+        get(stream_remote_execution_logs),
+    )
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DispatchRemoteExecutionRequest {
+    pub host_id: Uuid,
+    /// Repos are given by their ids on this instance and matched to the
+    /// host's repos by name.
+    pub workspace: CreateAndStartWorkspaceRequest,
+}
+
+async fn list_remote_executions(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Json<ApiResponse<Vec<RemoteExecution>>>, ApiError> {
+    let executions = RemoteExecution::list(&deployment.db().pool).await?;
+    Ok(Json(ApiResponse::success(executions)))
+}
+
+async fn dispatch_remote_execution(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<DispatchRemoteExecutionRequest>,
+) -> Result<Json<ApiResponse<RemoteExecution>>, ApiError> {
+    let DispatchRemoteExecutionRequest {
+        host_id,
+        mut workspace,
+    } = payload;
+    if workspace
+        .attachment_ids
+        .as_ref()
+        .is_some_and(|ids| !ids.is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "Attachments can't be sent to another host".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    let relay_host = deployment.relay_hosts()?.host(host_id).await?;
+    let host_repos: Vec<Repo> = proxy_json(&relay_host, Method::GET, "/api/repos", None).await?;
+
+    workspace.repos = host_repo_inputs(pool, workspace.repos, &host_repos).await?;
+
+    let body = serde_json::to_vec(&workspace)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid workspace request: {e}")))?;
+    let started: CreateAndStartWorkspaceResponse = proxy_json(
+        &relay_host,
+        Method::POST,
+        "/api/workspaces/start",
+        Some(&body),
+    )
+    .await?;
+
+    let execution = RemoteExecution::create(
+        pool,
+        &CreateRemoteExecution {
+            host_id,
+            remote_workspace_id: started.workspace.id,
+            remote_execution_process_id: started.execution_process.id,
+            name: workspace.name,
+            prompt: workspace.prompt,
+        },
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(execution)))
+}
+
+/// Swap the ids of this instance's repos for those of the host's repos with
+/// the same name.
+async fn host_repo_inputs(
+    pool: &SqlitePool,
+    inputs: Vec<WorkspaceRepoInput>,
+    host_repos: &[Repo],
+) -> Result<Vec<WorkspaceRepoInput>, ApiError> {
+    let mut repos = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let repo = Repo::find_by_id(pool, input.repo_id)
+            .await?
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!("Repository {} not found", input.repo_id))
+            })?;
+        let host_repo = host_repos
+            .iter()
+            .find(|host_repo| host_repo.name == repo.name)
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "Repository '{}' is not registered on the host",
+                    repo.name
+                ))
+            })?;
+        repos.push(WorkspaceRepoInput {
+            repo_id: host_repo.id,
+            target_branch: input.target_branch,
+        });
+    }
+    Ok(repos)
+}
+
+async fn stop_remote_execution(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let execution = find_remote_execution(&deployment, id).await?;
+    let relay_host = deployment.relay_hosts()?.host(execution.host_id).await?;
+    let path = format!(
+        "/api/execution-processes/{}/stop",
+        execution.remote_execution_process_id
+    );
+    let () = proxy_json(&relay_host, Method::POST, &path, None).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// Drop the local record. The workspace on the host is left alone.
+async fn forget_remote_execution(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    if RemoteExecution::delete(&deployment.db().pool, id).await? == 0 {
+        return Err(ApiError::BadRequest(format!(
+            "Remote execution {id} not found"
+        )));
+    }
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// The normalized logs of the agent run on the host, as the host streams
+/// them for its own execution processes.
+async fn stream_remote_execution_logs(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<Uuid>,
+    ws_upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let execution = find_remote_execution(&deployment, id).await?;
+    let relay_host = deployment.relay_hosts()?.host(execution.host_id).await?;
+    let path = format!(
+        "/api/execution-processes/{}/normalized-logs/ws",
+        execution.remote_execution_process_id
+    );
+    let connection = relay_host.proxy_ws(&path, None).await?;
+    Ok(ws_upgrade
+        .on_upgrade(|socket| async move {
+            if let Err(error) = connection.bridge(socket).await {
+                tracing::debug!(?error, "Remote execution log bridge closed with error");
+            }
+        })
+        .into_response())
+}
+
+async fn find_remote_execution(
+    deployment: &DeploymentImpl,
+    id: Uuid,
+) -> Result<RemoteExecution, ApiError> {
+    RemoteExecution::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest(format!("Remote execution {id} not found")))
+}
+
+/// Call the host's API and unwrap its `ApiResponse`, surfacing the host's
+/// error message when it refuses.
+async fn proxy_json<T: DeserializeOwned>(
+    relay_host: &RelayHost,
+    method: Method,
+    path: &str,
+    body: Option<&[u8]>,
+) -> Result<T, ApiError> {
+    let mut headers = HeaderMap::new();
+    if body.is_some() {
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+    }
+    let mut response = relay_host
+        .proxy_http(&method, path, &headers, body.unwrap_or_default())
+        .await?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.body.next().await {
+        let chunk =
+            chunk.map_err(|e| ApiError::BadGateway(format!("Failed to read response: {e}")))?;
+        bytes.extend_from_slice(&chunk);
+    }
+
+    decode_host_response(response.status, &bytes, &method, path)
+}
+
+fn decode_host_response<T: DeserializeOwned>(
+    status: StatusCode,
+    bytes: &[u8],
+    method: &Method,
+    path: &str,
+) -> Result<T, ApiError> {
+    let payload: ApiResponse<T, serde_json::Value> =
+        serde_json::from_slice(bytes).map_err(|e| {
+            ApiError::BadGateway(format!(
+                "Host returned an unexpected response ({status}) for {path}: {e}"
+            ))
+        })?;
+    if status != StatusCode::OK || !payload.is_success() {
+        return Err(ApiError::BadGateway(format!(
+            "Host rejected {method} {path}: {}",
+            payload.message().unwrap_or("no details")
+        )));
+    }
+    // `()` responses carry `null` data.
+    match payload.into_data() {
+        Some(data) => Ok(data),
+        None => serde_json::from_value(serde_json::Value::Null)
+            .map_err(|_| ApiError::BadGateway(format!("Host response for {path} had no data"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path as FsPath;
+
+    use db::test_utils::TestDb;
+
+    use super::*;
+
+    fn decode<T: DeserializeOwned>(status: StatusCode, body: &str) -> Result<T, ApiError> {
+        decode_host_response(status, body.as_bytes(), &Method::POST, "/api/test")
+    }
+
+    #[test]
+    fn host_errors_surface_as_bad_gateway() {
+        let data: Vec<u32> = decode(
+            StatusCode::OK,
+            r#"{"success":true,"data":[1,2],"error_data":null,"message":null}"#,
+        )
+        .unwrap();
+        assert_eq!(data, vec![1, 2]);
+        let () = decode(
+            StatusCode::OK,
+            r#"{"success":true,"data":null,"error_data":null,"message":null}"#,
+        )
+        .unwrap();
+
+        let refused = decode::<()>(
+            StatusCode::BAD_REQUEST,
+            r#"{"success":false,"data":null,"error_data":null,"message":"Branch not found"}"#,
+        );
+        assert!(
+            matches!(refused, Err(ApiError::BadGateway(msg)) if msg == "Host rejected POST /api/test: Branch not found")
+        );
+        let failed = decode::<()>(
+            StatusCode::OK,
+            r#"{"success":false,"data":null,"error_data":null,"message":null}"#,
+        );
+        assert!(matches!(failed, Err(ApiError::BadGateway(msg)) if msg.ends_with("no details")));
+        let garbled = decode::<()>(StatusCode::BAD_GATEWAY, "<html>relay down</html>");
+        assert!(
+            matches!(garbled, Err(ApiError::BadGateway(msg)) if msg.starts_with("Host returned an unexpected response (502 Bad Gateway)"))
+        );
+        let missing = decode::<Vec<u32>>(
+            StatusCode::OK,
+            r#"{"success":true,"data":null,"error_data":null,"message":null}"#,
+        );
+        assert!(matches!(missing, Err(ApiError::BadGateway(msg)) if msg.ends_with("had no data")));
+    }
+
+    #[tokio::test]
+    async fn repos_are_matched_to_the_host_by_name() {
+        let db = TestDb::new().await;
+        let pool = &db.pool;
+        let local = Repo::find_or_create(pool, FsPath::new("/local/app"), "app")
+            .await
+            .unwrap();
+        let unshared = Repo::find_or_create(pool, FsPath::new("/local/tools"), "tools")
+            .await
+            .unwrap();
+        let host_repos = vec![
+            Repo::find_or_create(pool, FsPath::new("/host/docs"), "docs")
+                .await
+                .unwrap(),
+            Repo::find_or_create(pool, FsPath::new("/host/app"), "app")
+                .await
+                .unwrap(),
+        ];
+        let input = |repo_id| WorkspaceRepoInput {
+            repo_id,
+            target_branch: "main".to_string(),
+        };
+
+        let matched = host_repo_inputs(pool, vec![input(local.id)], &host_repos)
+            .await
+            .unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].repo_id, host_repos[1].id);
+        assert_eq!(matched[0].target_branch, "main");
+
+        let unregistered = host_repo_inputs(pool, vec![input(unshared.id)], &host_repos).await;
+        assert!(
+            matches!(unregistered, Err(ApiError::BadRequest(msg)) if msg == "Repository 'tools' is not registered on the host")
+        );
+        let unknown = host_repo_inputs(pool, vec![input(Uuid::new_v4())], &host_repos).await;
+        assert!(matches!(unknown, Err(ApiError::BadRequest(msg)) if msg.ends_with("not found")));
+    }
+}
@@ -0,0 +1,44 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::execution_log_search::LogSearchHit;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::log_search::LogSearchService;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    pub q: String,
+    pub workspace_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Search stdout/stderr of all execution processes, optionally scoped to a
+/// workspace. Every whitespace-separated term must appear in the line.
+pub async fn search_logs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<LogSearchHit>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let hits = LogSearchService::new(deployment.db().clone())
+        .search(&query.q, query.workspace_id, limit)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(hits)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/logs/search", get(search_logs))
+}
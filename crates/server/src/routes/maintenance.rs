@@ -0,0 +1,52 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::post,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    db_maintenance::{DbMaintenanceReport, DbMaintenanceService},
+    log_retention::{LogCompactionReport, LogRetentionService},
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Apply the configured log retention policy now instead of waiting for the
+/// hourly run.
+pub async fn compact_logs(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<LogCompactionReport>>, ApiError> {
+    let report = LogRetentionService::new(deployment.db().clone(), deployment.config().clone())
+        .compact()
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DbMaintenanceQuery {
+    /// Rewrite the whole database file. Needed once to shrink databases
+    /// created before incremental vacuuming was enabled.
+    #[serde(default)]
+    pub full_vacuum: bool,
+}
+
+/// Checkpoint, vacuum and integrity-check the database now instead of
+/// waiting for the daily run.
+pub async fn maintain_db(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DbMaintenanceQuery>,
+) -> Result<ResponseJson<ApiResponse<DbMaintenanceReport>>, ApiError> {
+    let report = DbMaintenanceService::new(deployment.db().clone())
+        .run(query.full_vacuum)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/maintenance/compact-logs", post(compact_logs))
+        .route("/maintenance/db", post(maintain_db))
+}
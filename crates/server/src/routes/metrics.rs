@@ -0,0 +1,44 @@
+use std::{sync::LazyLock, time::Instant};
+
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use db::models::execution_process::ExecutionProcess;
+use deployment::Deployment;
+use services::services::metrics::ServerMetrics;
+
+use crate::DeploymentImpl;
+
+/// Set when the router is built, at startup.
+pub(super) static STARTED_AT: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Prometheus metrics. Served outside `/api` at the conventional path and
+/// without origin checks, since scrapers send no `Origin` header.
+pub(super) async fn prometheus_metrics(State(deployment): State<DeploymentImpl>) -> Response {
+    let pool = &deployment.db().pool;
+    let running_processes = match ExecutionProcess::count_running_by_run_reason(pool).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::error!("Failed to count running execution processes: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let metrics = ServerMetrics {
+        running_processes,
+        execution_queue: deployment.execution_scheduler().status(),
+        pending_approvals: deployment.approvals().pending_count(),
+        event_stream_subscribers: deployment.events().msg_store().subscriber_count(),
+        relay_enabled: deployment.config().read().await.relay_enabled,
+        relay_active: deployment.relay_control().is_active().await,
+        db_pool_size: pool.size(),
+        db_pool_idle: pool.num_idle(),
+        uptime_seconds: STARTED_AT.elapsed().as_secs(),
+    };
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+        .into_response()
+}
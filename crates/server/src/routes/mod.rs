@@ -1,3 +1,5 @@
+use std::sync::LazyLock;
+
 use axum::{
     Router,
     routing::{IntoMakeService, get},
@@ -6,57 +8,101 @@ use tower_http::{compression::CompressionLayer, validate_request::ValidateReques
 
 use crate::{DeploymentImpl, middleware};
 
+pub mod admin;
+pub mod analytics;
 pub mod approvals;
+pub mod audit;
 pub mod config;
+pub mod config_profiles;
 pub mod containers;
+pub mod docs;
 pub mod filesystem;
 // pub mod github;
 pub mod attachments;
+pub mod boards;
 pub mod events;
 pub mod execution_processes;
+pub mod execution_queue;
 pub mod frontend;
 pub mod health;
+pub mod hooks;
 pub mod host_relay;
+pub mod logs;
+pub mod maintenance;
+pub mod metrics;
+pub mod notification_rules;
 pub mod oauth;
 pub mod organizations;
 pub mod preview;
+pub mod prompt_snippets;
+pub mod provider_credentials;
 pub mod relay_auth;
+pub mod relay_status;
 pub mod releases;
 pub mod remote;
 pub mod repo;
+pub mod reports;
 pub mod scratch;
 pub mod search;
 pub mod sessions;
+pub mod shared_sessions;
 pub mod ssh_session;
 pub mod tags;
+pub mod task_templates;
 pub mod terminal;
 pub mod webrtc;
 pub mod workspaces;
 
 pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
+    LazyLock::force(&metrics::STARTED_AT);
+    let metrics_route = get(metrics::prometheus_metrics).with_state(deployment.clone());
+
     let relay_signed_routes = Router::new()
         .route("/health", get(health::health_check))
+        .route("/health/checks", get(health::health_checks))
+        .route("/health/live", get(health::health_live))
+        .route("/health/ready", get(health::health_checks))
         .merge(config::router())
+        .merge(config_profiles::router())
+        .merge(admin::router())
+        .merge(analytics::router())
         .merge(containers::router(&deployment))
         .merge(workspaces::router(&deployment))
+        .merge(boards::router(&deployment))
         .merge(execution_processes::router(&deployment))
+        .merge(execution_queue::router())
         .merge(tags::router(&deployment))
+        .merge(task_templates::router(&deployment))
+        .merge(prompt_snippets::router())
+        .merge(provider_credentials::router())
         .merge(oauth::router())
         .merge(organizations::router())
         .merge(filesystem::router())
         .merge(repo::router())
+        .merge(docs::router())
+        .merge(logs::router())
+        .merge(maintenance::router())
+        .merge(reports::router())
         .merge(events::router(&deployment))
         .merge(approvals::router())
+        .merge(audit::router())
+        .merge(notification_rules::router(&deployment))
         .merge(scratch::router(&deployment))
         .merge(search::router(&deployment))
         .merge(preview::api_router())
         .merge(releases::router())
+        .merge(relay_status::router())
         .merge(sessions::router(&deployment))
         .merge(terminal::router())
         .route("/ssh-session", get(ssh_session::ssh_session_ws))
         .nest("/remote", remote::router())
         .merge(webrtc::router())
         .nest("/attachments", attachments::routes())
+        // Innermost, so replayed responses are signed like fresh ones.
+        .layer(axum::middleware::from_fn_with_state(
+            deployment.clone(),
+            middleware::idempotency,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             deployment.clone(),
             middleware::sign_relay_response,
@@ -70,6 +116,12 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
     let api_routes = Router::new()
         .merge(relay_auth::router())
         .merge(host_relay::router(&deployment))
+        // Webhooks authenticate with their own token and are called by
+        // external systems that cannot sign relay requests.
+        .merge(hooks::router(&deployment))
+        // Share links carry their own read-only capability token, for
+        // viewers that aren't paired with this host.
+        .merge(shared_sessions::router(&deployment))
         .merge(relay_signed_routes)
         .layer(ValidateRequestHeaderLayer::custom(
             middleware::validate_origin,
@@ -80,6 +132,7 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
     Router::new()
         .route("/", get(frontend::serve_frontend_root))
         .route("/{*path}", get(frontend::serve_frontend))
+        .route("/metrics", metrics_route)
         .nest("/api", api_routes)
         .layer(CompressionLayer::new())
         .into_make_service()
@@ -0,0 +1,201 @@
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use db::models::{
+    approval_notification_rule::{
+        ApprovalNotificationRule, CreateApprovalNotificationRule, NotificationChannel,
+        UpdateApprovalNotificationRule,
+    },
+    audit_log::{AuditAction, AuditActor, CreateAuditLogEntry},
+    repo::Repo,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    audit,
+    container::ContainerService,
+    notification_routing::{self, ApprovalRoute},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RequestActor, load_approval_notification_rule_middleware},
+};
+
+/// A hypothetical approval to route without notifying anyone.
+#[derive(Debug, Deserialize, TS)]
+pub struct TestApprovalRouteRequest {
+    pub tool_name: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_id: Option<Uuid>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub is_question: Option<bool>,
+}
+
+pub async fn get_notification_rules(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalNotificationRule>>>, ApiError> {
+    let rules = ApprovalNotificationRule::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(rules)))
+}
+
+pub async fn create_notification_rule(
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+    Json(payload): Json<CreateApprovalNotificationRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalNotificationRule>>, ApiError> {
+    validate_channels(&payload.channels)?;
+    if let Some(repo_id) = payload.repo_id {
+        validate_repo_id(&deployment, repo_id).await?;
+    }
+
+    let rule = ApprovalNotificationRule::create(&deployment.db().pool, &payload).await?;
+    record_rule_change(&deployment, actor, rule.id, "created").await;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn update_notification_rule(
+    Extension(rule): Extension<ApprovalNotificationRule>,
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+    Json(payload): Json<UpdateApprovalNotificationRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalNotificationRule>>, ApiError> {
+    if let Some(channels) = &payload.channels {
+        validate_channels(channels)?;
+    }
+    if let Some(Some(repo_id)) = payload.repo_id {
+        validate_repo_id(&deployment, repo_id).await?;
+    }
+
+    let rule = ApprovalNotificationRule::update(&deployment.db().pool, rule.id, &payload).await?;
+    record_rule_change(&deployment, actor, rule.id, "updated").await;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn delete_notification_rule(
+    Extension(rule): Extension<ApprovalNotificationRule>,
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = ApprovalNotificationRule::delete(&deployment.db().pool, rule.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        record_rule_change(&deployment, actor, rule.id, "deleted").await;
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Which channels an approval for the given tool and repo would be sent to.
+pub async fn test_notification_route(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<TestApprovalRouteRequest>,
+) -> Result<ResponseJson<ApiResponse<ApprovalRoute>>, ApiError> {
+    let config = deployment
+        .container()
+        .notification_service()
+        .notification_config()
+        .await;
+    let repo_ids: Vec<Uuid> = payload.repo_id.into_iter().collect();
+    let route = notification_routing::route_approval(
+        &deployment.db().pool,
+        &config,
+        &payload.tool_name,
+        payload.is_question.unwrap_or(false),
+        &repo_ids,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(route)))
+}
+
+/// Channel settings are left out of the entry since they hold webhook URLs.
+async fn record_rule_change(
+    deployment: &DeploymentImpl,
+    actor: AuditActor,
+    rule_id: Uuid,
+    change: &str,
+) {
+    audit::record(
+        &deployment.db().pool,
+        CreateAuditLogEntry {
+            actor,
+            action: AuditAction::ApprovalPolicyChanged,
+            target_type: "approval_notification_rule",
+            target_id: Some(rule_id.to_string()),
+            details: serde_json::json!({ "change": change }),
+        },
+    )
+    .await;
+}
+
+fn validate_channels(channels: &[NotificationChannel]) -> Result<(), ApiError> {
+    for channel in channels {
+        let webhook = match channel {
+            NotificationChannel::Desktop | NotificationChannel::Sound => continue,
+            NotificationChannel::Slack {
+                webhook_url,
+                channel,
+            } => {
+                if channel.as_deref().is_some_and(|c| c.trim().is_empty()) {
+                    return Err(ApiError::BadRequest(
+                        "Slack channel must not be empty".to_string(),
+                    ));
+                }
+                webhook_url
+            }
+            NotificationChannel::Webhook { url } => url,
+        };
+        let valid = url::Url::parse(webhook)
+            .is_ok_and(|parsed| matches!(parsed.scheme(), "http" | "https"));
+        if !valid {
+            return Err(ApiError::BadRequest(format!(
+                "Notification webhook URL '{webhook}' must be an http(s) URL"
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn validate_repo_id(deployment: &DeploymentImpl, repo_id: Uuid) -> Result<(), ApiError> {
+    if Repo::find_by_id(&deployment.db().pool, repo_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Repository {repo_id} not found"
+        )));
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let rule_router = Router::new()
+        .route(
+            "/",
+            put(update_notification_rule).delete(delete_notification_rule),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_approval_notification_rule_middleware,
+        ));
+
+    let inner = Router::new()
+        .route(
+            "/",
+            get(get_notification_rules).post(create_notification_rule),
+        )
+        .route("/test", post(test_notification_route))
+        .nest("/{rule_id}", rule_router);
+
+    Router::new().nest("/notification-rules", inner)
+}
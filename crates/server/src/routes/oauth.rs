@@ -14,7 +14,10 @@ use deployment::Deployment;
 use rand::{Rng, distributions::Alphanumeric};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    config::save_config_to_file, oauth_credentials::Credentials, remote_sync,
+    analytics::AnalyticsCategory,
+    config::{AnalyticsConsent, save_config_to_file},
+    oauth_credentials::Credentials,
+    remote_sync,
 };
 use sha2::{Digest, Sha256};
 use ts_rs::TS;
@@ -347,11 +350,11 @@ async fn finalize_login(
         })?;
 
     let config_guard = deployment.config().read().await;
-    if !config_guard.analytics_enabled {
+    if !config_guard.analytics.any() {
         let mut new_config = config_guard.clone();
         drop(config_guard);
 
-        new_config.analytics_enabled = true;
+        new_config.analytics = AnalyticsConsent::all(true);
 
         let config_path = config_path();
         if let Err(e) = save_config_to_file(&new_config, &config_path).await {
@@ -368,6 +371,7 @@ async fn finalize_login(
 
             if let Some(analytics) = deployment.analytics() {
                 analytics.track_event(
+                    AnalyticsCategory::Usage,
                     deployment.user_id(),
                     "analytics_session_start",
                     Some(serde_json::json!({})),
@@ -399,6 +403,7 @@ async fn finalize_login(
 
     if let Some(analytics) = deployment.analytics() {
         analytics.track_event(
+            AnalyticsCategory::Usage,
             deployment.user_id(),
             "$identify",
             Some(serde_json::json!({
@@ -406,6 +411,7 @@ async fn finalize_login(
             })),
         );
         analytics.track_event(
+            AnalyticsCategory::Usage,
             &profile.user_id.to_string(),
             "$merge_dangerously",
             Some(serde_json::json!({
@@ -3,9 +3,15 @@ use axum::{
     extract::{Path, Request, State, ws::rejection::WebSocketUpgradeRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::any,
+    routing::{any, get},
+};
+use db::models::{
+    workspace::Workspace,
+    workspace_dev_server::{PreviewScriptInjection, WorkspaceDevServer},
 };
 use deployment::Deployment;
+use preview_proxy::ScriptInjection;
+use tokio::net::TcpStream;
 use ws_bridge::{bridge_axum_ws, connect_upstream_ws};
 
 use crate::{DeploymentImpl, middleware::signed_ws::SignedWsUpgrade};
@@ -14,6 +20,7 @@ pub(super) fn api_router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/preview/{target_port}", any(proxy_preview_request_no_tail))
         .route("/preview/{target_port}/{*tail}", any(proxy_preview_request))
+        .route("/preview-tunnel/{target_port}", get(preview_tunnel))
 }
 
 pub fn subdomain_router(deployment: DeploymentImpl) -> Router {
@@ -109,6 +116,27 @@ async fn forward_preview_ws(
     .into_response()
 }
 
+/// Raw byte tunnel to a dev server. A paired machine opens this through the
+/// relay to pass a preview's WebSocket upgrade through untouched, so the
+/// browser and dev server can negotiate compression.
+async fn preview_tunnel(Path(target_port): Path<u16>, ws: SignedWsUpgrade) -> Response {
+    let mut dev_server = match TcpStream::connect(("localhost", target_port)).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            tracing::debug!(?error, target_port, "Failed to open preview tunnel");
+            return (StatusCode::BAD_GATEWAY, "Preview tunnel unavailable").into_response();
+        }
+    };
+
+    ws.on_upgrade(move |socket| async move {
+        let mut socket = ws_bridge::axum_ws_stream_io(socket);
+        if let Err(error) = tokio::io::copy_bidirectional(&mut socket, &mut dev_server).await {
+            tracing::debug!(?error, "Preview tunnel closed with error");
+        }
+    })
+    .into_response()
+}
+
 async fn subdomain_proxy_request(
     State(deployment): State<DeploymentImpl>,
     request: Request,
@@ -129,11 +157,59 @@ async fn subdomain_proxy_request(
             .into_response();
     };
 
+    let injection = script_injection(&deployment, &request).await;
+
     preview_proxy::proxy_subdomain_request(
         deployment.preview_proxy(),
         server_addr,
         proxy_port,
+        injection,
         request,
     )
     .await
 }
+
+/// The injection policy of the workspace whose dev server owns the target
+/// port, falling back to the config.
+async fn script_injection(deployment: &DeploymentImpl, request: &Request) -> ScriptInjection {
+    let mut policy = deployment.config().read().await.preview_script_injection;
+    if let Some(port) = preview_proxy::local_target_port(request.headers()) {
+        let pool = &deployment.db().pool;
+        // Healthy dev servers are in the proxy's registry; ones still
+        // starting only have the port published on their workspace.
+        let workspace_id = match deployment
+            .preview_proxy()
+            .dev_server_ports()
+            .workspace(port)
+        {
+            Some(workspace_id) => Ok(Some(workspace_id)),
+            None => Workspace::find_id_by_dev_server_port(pool, port).await,
+        };
+        let dev_server = match workspace_id {
+            Ok(Some(workspace_id)) => {
+                WorkspaceDevServer::find_by_workspace_id(pool, workspace_id).await
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+        match dev_server {
+            Ok(dev_server) => {
+                if let Some(script_injection) = dev_server.and_then(|d| d.script_injection) {
+                    policy = script_injection;
+                }
+            }
+            Err(error) => {
+                tracing::warn!(
+                    ?error,
+                    "Failed to look up preview script injection override"
+                );
+            }
+        }
+    }
+
+    match policy {
+        PreviewScriptInjection::None => ScriptInjection::None,
+        PreviewScriptInjection::Local => ScriptInjection::Local,
+        PreviewScriptInjection::Full => ScriptInjection::Full,
+    }
+}
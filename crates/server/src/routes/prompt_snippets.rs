@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::workspace::Workspace;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{config::PromptSnippet, prompt_snippet};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct PromptSnippetQuery {
+    pub project_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RenderPromptSnippetRequest {
+    pub workspace_id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub variables: Option<HashMap<String, String>>,
+}
+
+/// Snippets available in the given project, or only the global ones.
+pub async fn get_prompt_snippets(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PromptSnippetQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PromptSnippet>>>, ApiError> {
+    let config = deployment.config().read().await;
+    let snippets = prompt_snippet::snippets_for_project(&config.prompt_snippets, query.project_id);
+    Ok(ResponseJson(ApiResponse::success(snippets)))
+}
+
+/// Render a snippet for a workspace without sending it, for previews and for
+/// inserting into a prompt that is still being edited.
+pub async fn render_prompt_snippet(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RenderPromptSnippetRequest>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    let workspace = Workspace::find_by_id(&deployment.db().pool, payload.workspace_id)
+        .await?
+        .ok_or(ApiError::BadRequest(format!(
+            "Workspace {} not found",
+            payload.workspace_id
+        )))?;
+    let mut rendered = render_snippets(
+        &deployment,
+        &workspace,
+        std::slice::from_ref(&payload.name),
+        payload.variables.unwrap_or_default(),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(rendered.remove(0))))
+}
+
+/// Look up the named snippets for the workspace's project and substitute
+/// their variables. Caller-supplied variables win over derived ones.
+pub(crate) async fn render_snippets(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    names: &[String],
+    overrides: HashMap<String, String>,
+) -> Result<Vec<String>, ApiError> {
+    let pool = &deployment.db().pool;
+    let project_id = prompt_snippet::project_id_for_workspace(pool, workspace).await?;
+    let snippets = {
+        let config = deployment.config().read().await;
+        prompt_snippet::snippets_for_project(&config.prompt_snippets, project_id)
+    };
+
+    let mut variables = prompt_snippet::workspace_variables(pool, workspace).await?;
+    variables.extend(overrides);
+
+    names
+        .iter()
+        .map(|name| {
+            snippets
+                .iter()
+                .find(|s| &s.name == name)
+                .map(|s| prompt_snippet::render(&s.content, &variables))
+                .ok_or_else(|| ApiError::BadRequest(format!("Prompt snippet '{name}' not found")))
+        })
+        .collect()
+}
+
+/// Reject snippet lists with blank names or two snippets of the same name in
+/// the same scope, which would make lookups by name ambiguous.
+pub(crate) fn validate_prompt_snippets(snippets: &[PromptSnippet]) -> Result<(), String> {
+    for (i, snippet) in snippets.iter().enumerate() {
+        if snippet.name.trim().is_empty() {
+            return Err("Prompt snippet names must not be empty".to_string());
+        }
+        if snippets[..i]
+            .iter()
+            .any(|s| s.name == snippet.name && s.project_id == snippet.project_id)
+        {
+            return Err(format!("Duplicate prompt snippet '{}'", snippet.name));
+        }
+    }
+    Ok(())
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/prompt-snippets", get(get_prompt_snippets))
+        .route("/prompt-snippets/render", post(render_prompt_snippet))
+}
@@ -0,0 +1,72 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::provider_credentials::{
+    ApiProvider, ProviderCredentialInfo, ProviderCredentialKind, ProviderCredentialValidation,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetProviderCredentialRequest {
+    pub kind: ProviderCredentialKind,
+    pub secret: String,
+}
+
+/// Every supported provider, with its stored credential masked.
+pub async fn list_provider_credentials(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProviderCredentialInfo>>>, ApiError> {
+    let credentials = deployment.provider_credentials().list().await;
+    Ok(ResponseJson(ApiResponse::success(credentials)))
+}
+
+pub async fn set_provider_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(provider): Path<ApiProvider>,
+    Json(payload): Json<SetProviderCredentialRequest>,
+) -> Result<ResponseJson<ApiResponse<ProviderCredentialInfo>>, ApiError> {
+    let info = deployment
+        .provider_credentials()
+        .set(provider, payload.kind, &payload.secret)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(info)))
+}
+
+pub async fn delete_provider_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(provider): Path<ApiProvider>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment.provider_credentials().remove(provider).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Ping the provider with the stored credential. A rejected or unreachable
+/// credential is reported in the result rather than as an error.
+pub async fn validate_provider_credential(
+    State(deployment): State<DeploymentImpl>,
+    Path(provider): Path<ApiProvider>,
+) -> Result<ResponseJson<ApiResponse<ProviderCredentialValidation>>, ApiError> {
+    let validation = deployment.provider_credentials().validate(provider).await?;
+    Ok(ResponseJson(ApiResponse::success(validation)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/provider-credentials", get(list_provider_credentials))
+        .route(
+            "/provider-credentials/{provider}",
+            put(set_provider_credential).delete(delete_provider_credential),
+        )
+        .route(
+            "/provider-credentials/{provider}/validate",
+            post(validate_provider_credential),
+        )
+}
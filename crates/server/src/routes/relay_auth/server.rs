@@ -4,18 +4,23 @@ use axum::{
     http::HeaderMap,
     routing::{delete, get, post},
 };
+use db::models::audit_log::{AuditAction, CreateAuditLogEntry};
+use deployment::Deployment;
 use relay_types::{
     FinishSpake2EnrollmentRequest, FinishSpake2EnrollmentResponse, ListRelayPairedClientsResponse,
     RefreshRelaySigningSessionRequest, RefreshRelaySigningSessionResponse,
-    RemoveRelayPairedClientResponse, StartSpake2EnrollmentRequest, StartSpake2EnrollmentResponse,
+    RemoveRelayPairedClientResponse, RotateRelayClientKeyRequest, RotateRelayClientKeyResponse,
+    StartSpake2EnrollmentRequest, StartSpake2EnrollmentResponse,
 };
 use serde::Serialize;
+use services::services::audit;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
+    middleware::RequestActor,
     relay_pairing::{build_relay_pairing_server, server::is_relay_request},
 };
 
@@ -47,6 +52,10 @@ pub fn router() -> Router<DeploymentImpl> {
             "/relay-auth/server/signing-session/refresh",
             post(refresh_relay_signing_session),
         )
+        .route(
+            "/relay-auth/server/client-key/rotate",
+            post(rotate_relay_client_key),
+        )
 }
 
 async fn generate_enrollment_code(
@@ -93,12 +102,35 @@ async fn list_relay_paired_clients(
 
 async fn remove_relay_paired_client(
     State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
     Path(client_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<RemoveRelayPairedClientResponse>>, ApiError> {
+    let client = deployment
+        .trusted_key_auth()
+        .find_trusted_client(client_id)
+        .await
+        .ok()
+        .flatten();
     let removed = build_relay_pairing_server(&deployment)
         .remove_paired_client(client_id)
         .await?;
 
+    if removed {
+        audit::record(
+            &deployment.db().pool,
+            CreateAuditLogEntry {
+                actor,
+                action: AuditAction::RelayClientRemoved,
+                target_type: "relay_client",
+                target_id: Some(client_id.to_string()),
+                details: serde_json::json!({
+                    "client_name": client.map(|client| client.client_name),
+                }),
+            },
+        )
+        .await;
+    }
+
     Ok(Json(ApiResponse::success(
         RemoveRelayPairedClientResponse { removed },
     )))
@@ -125,3 +157,14 @@ async fn refresh_relay_signing_session(
 
     Ok(Json(ApiResponse::success(response)))
 }
+
+async fn rotate_relay_client_key(
+    State(deployment): State<DeploymentImpl>,
+    ExtractJson(payload): ExtractJson<RotateRelayClientKeyRequest>,
+) -> Result<Json<ApiResponse<RotateRelayClientKeyResponse>>, ApiError> {
+    let response = build_relay_pairing_server(&deployment)
+        .rotate_client_key(payload)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
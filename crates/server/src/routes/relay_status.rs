@@ -0,0 +1,19 @@
+use axum::{Router, extract::State, response::Json, routing::get};
+use deployment::Deployment;
+use relay_types::RelayConnectionStatus;
+use utils::response::ApiResponse;
+
+use crate::DeploymentImpl;
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/relay/status", get(get_relay_status))
+}
+
+/// Quality of this host's relay tunnel: latency, probe loss and reconnects.
+async fn get_relay_status(
+    State(deployment): State<DeploymentImpl>,
+) -> Json<ApiResponse<RelayConnectionStatus>> {
+    let relay_control = deployment.relay_control();
+    let active = relay_control.is_active().await;
+    Json(ApiResponse::success(relay_control.quality().status(active)))
+}
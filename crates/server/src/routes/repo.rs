@@ -1,23 +1,41 @@
 use std::path::PathBuf;
 
 use axum::{
-    Router,
+    Json, Router,
     extract::{Path, Query, State},
     http::StatusCode,
     response::Json as ResponseJson,
-    routing::{get, post},
+    routing::{delete, get, post},
+};
+use db::models::{
+    repo::{Repo, SearchResult, UpdateRepo},
+    repo_check_command::{RepoCheckCommand, RepoCheckCommandInput, SetRepoCheckCommands},
+    repo_container_image::{RepoContainerImage, SetRepoContainerImage, parse_ports},
+    repo_issue_tracker::{IssueTrackerKind, RepoIssueTracker, SetRepoIssueTracker},
+    repo_protected_path::{CreateRepoProtectedPath, RepoProtectedPath},
+    repo_resource_limit::{RepoResourceLimits, SetRepoResourceLimits},
+    repo_resource_tag::{RepoResourceTags, SetRepoResourceTags},
+    repo_setup_cache::{RepoSetupCache, SetRepoSetupCache},
+    requests::{CreateAndStartWorkspaceRequest, WorkspaceRepoInput},
+    workspace_issue_link::{CreateWorkspaceIssueLink, WorkspaceIssueLink},
 };
-use db::models::repo::{Repo, SearchResult, UpdateRepo};
 use deployment::Deployment;
+use executors::profile::ExecutorConfig;
 use git::{GitBranch, GitRemote};
 use git_host::{GitHostError, GitHostProvider, GitHostService, ProviderKind, PullRequestDetail};
 use serde::{Deserialize, Serialize};
-use services::services::file_search::SearchQuery;
+use services::services::{
+    approvals::protected_paths,
+    file_search::SearchQuery,
+    issue_tracker::{self, TrackerIssue},
+    package_manager::{self, ScriptSuggestions},
+    setup_cache::{self, SetupCacheService},
+};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{DeploymentImpl, error::ApiError, routes::workspaces::create};
 
 #[derive(serde::Deserialize)]
 pub struct OpenEditorRequest {
@@ -148,6 +166,21 @@ pub async fn update_repo(
     Ok(ResponseJson(ApiResponse::success(repo)))
 }
 
+/// Suggested setup and dev server scripts based on the repo's package manager.
+/// Suggestions are computed on every call and never written to the repo.
+pub async fn get_script_suggestions(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<ScriptSuggestions>>, ApiError> {
+    let repo = deployment
+        .repo()
+        .get_by_id(&deployment.db().pool, repo_id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(
+        package_manager::suggest_scripts(&repo.path),
+    )))
+}
+
 pub async fn open_repo_in_editor(
     State(deployment): State<DeploymentImpl>,
     Path(repo_id): Path<Uuid>,
@@ -365,6 +398,442 @@ pub async fn delete_repo(
     Ok((StatusCode::OK, ResponseJson(ApiResponse::success(()))))
 }
 
+/// Smallest memory cap accepted; anything lower keeps most tools from starting.
+const MIN_MEMORY_LIMIT_MB: u32 = 64;
+
+pub async fn get_repo_resource_limits(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoResourceLimits>>>, ApiError> {
+    let limits = RepoResourceLimits::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(limits)))
+}
+
+/// Set the repo's caps. Applies to processes started after the change;
+/// clearing both caps removes them.
+pub async fn set_repo_resource_limits(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SetRepoResourceLimits>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoResourceLimits>>>, ApiError> {
+    if payload
+        .memory_limit_mb
+        .is_some_and(|mb| mb < MIN_MEMORY_LIMIT_MB)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Memory limit must be at least {MIN_MEMORY_LIMIT_MB} MB"
+        )));
+    }
+    if payload.cpu_limit_percent == Some(0) {
+        return Err(ApiError::BadRequest(
+            "CPU limit must be at least 1%".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    if payload.memory_limit_mb.is_none() && payload.cpu_limit_percent.is_none() {
+        RepoResourceLimits::delete(pool, repo_id).await?;
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    }
+    let limits = RepoResourceLimits::set(pool, repo_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(limits))))
+}
+
+pub async fn get_repo_container_image(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoContainerImage>>>, ApiError> {
+    let image = RepoContainerImage::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(image)))
+}
+
+/// Set the image the repo's processes run in under the Docker execution
+/// backend. An empty image removes it.
+pub async fn set_repo_container_image(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    Json(payload): Json<SetRepoContainerImage>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoContainerImage>>>, ApiError> {
+    if let Some(ports) = payload.ports.as_deref()
+        && ports.split(',').any(|port| {
+            let port = port.trim();
+            !port.is_empty() && parse_ports(port).is_empty()
+        })
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid ports \"{ports}\", expected e.g. \"3000, 5173\""
+        )));
+    }
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    let image = payload.image.trim();
+    if image.is_empty() {
+        RepoContainerImage::delete(pool, repo_id).await?;
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    }
+    let data = SetRepoContainerImage {
+        image: image.to_string(),
+        ports: payload.ports.filter(|ports| !ports.trim().is_empty()),
+    };
+    let image = RepoContainerImage::set(pool, repo_id, &data).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(image))))
+}
+
+pub async fn get_repo_resource_tags(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RepoResourceTags>>, ApiError> {
+    let tags = RepoResourceTags::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(tags)))
+}
+
+/// Replace the resources the repo's coding agents are scheduled by. Applies
+/// to executions queued after the change.
+pub async fn set_repo_resource_tags(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SetRepoResourceTags>,
+) -> Result<ResponseJson<ApiResponse<RepoResourceTags>>, ApiError> {
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    let tags = RepoResourceTags::set(pool, repo_id, &payload.tags).await?;
+    Ok(ResponseJson(ApiResponse::success(tags)))
+}
+
+pub async fn list_repo_protected_paths(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<RepoProtectedPath>>>, ApiError> {
+    let paths = RepoProtectedPath::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(paths)))
+}
+
+/// Protect files matching a gitignore-style pattern from coding agent
+/// writes. Re-adding a pattern changes its action.
+pub async fn create_repo_protected_path(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<CreateRepoProtectedPath>,
+) -> Result<ResponseJson<ApiResponse<RepoProtectedPath>>, ApiError> {
+    let pattern = payload.pattern.trim();
+    if pattern.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Pattern must not be empty".to_string(),
+        ));
+    }
+    protected_paths::validate_pattern(pattern)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid pattern \"{pattern}\": {e}")))?;
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    let data = CreateRepoProtectedPath {
+        pattern: pattern.to_string(),
+        action: payload.action,
+    };
+    let path = RepoProtectedPath::create(pool, repo_id, &data).await?;
+    Ok(ResponseJson(ApiResponse::success(path)))
+}
+
+pub async fn delete_repo_protected_path(
+    State(deployment): State<DeploymentImpl>,
+    Path((repo_id, path_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if RepoProtectedPath::delete(&deployment.db().pool, repo_id, path_id).await? == 0 {
+        return Err(ApiError::BadRequest("Protected path not found".to_string()));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn list_repo_check_commands(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Vec<RepoCheckCommand>>>, ApiError> {
+    let checks = RepoCheckCommand::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(checks)))
+}
+
+/// Replace the commands run in the repo when a coding agent session
+/// completes. They run in the order given.
+pub async fn set_repo_check_commands(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SetRepoCheckCommands>,
+) -> Result<ResponseJson<ApiResponse<Vec<RepoCheckCommand>>>, ApiError> {
+    let checks = payload
+        .checks
+        .iter()
+        .map(|check| {
+            let name = check.name.trim();
+            let command = check.command.trim();
+            if name.is_empty() || command.is_empty() {
+                return Err(ApiError::BadRequest(
+                    "Checks need a name and a command".to_string(),
+                ));
+            }
+            Ok(RepoCheckCommandInput {
+                name: name.to_string(),
+                command: command.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    let checks = RepoCheckCommand::set(pool, repo_id, &checks).await?;
+    Ok(ResponseJson(ApiResponse::success(checks)))
+}
+
+pub async fn get_repo_setup_cache(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoSetupCache>>>, ApiError> {
+    let cache = RepoSetupCache::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(cache)))
+}
+
+/// Set which directories the repo's setup script output is cached for.
+/// Clearing `cache_paths` turns caching off.
+pub async fn set_repo_setup_cache(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    ResponseJson(payload): ResponseJson<SetRepoSetupCache>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoSetupCache>>>, ApiError> {
+    let listed = |list: &str| {
+        list.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let paths = listed(&payload.cache_paths);
+    let key_files = payload.key_files.as_deref().map(listed).unwrap_or_default();
+    if let Some(invalid) = paths
+        .iter()
+        .chain(&key_files)
+        .find(|path| !setup_cache::is_valid_cache_path(path))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "'{invalid}' must be a path inside the repo"
+        )));
+    }
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    if paths.is_empty() {
+        RepoSetupCache::delete(pool, repo_id).await?;
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    }
+    let cache = RepoSetupCache::set(pool, repo_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(cache))))
+}
+
+/// Drop the repo's cached setup output; the next worktree starts cold and
+/// refills the cache.
+pub async fn clear_repo_setup_cache(
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    SetupCacheService::new().clear(repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_repo_issue_tracker(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Option<RepoIssueTracker>>>, ApiError> {
+    let tracker = RepoIssueTracker::find_by_repo_id(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(tracker)))
+}
+
+/// Configure the issue tracker issues are imported from. Without a `token`,
+/// the stored one is kept.
+pub async fn set_repo_issue_tracker(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    Json(payload): Json<SetRepoIssueTracker>,
+) -> Result<ResponseJson<ApiResponse<RepoIssueTracker>>, ApiError> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let tracker = SetRepoIssueTracker {
+        provider: payload.provider,
+        project: payload.project.trim().to_string(),
+        base_url: trimmed(payload.base_url),
+        username: trimmed(payload.username),
+        token: trimmed(payload.token),
+        close_on_merge: payload.close_on_merge,
+    };
+    if tracker.project.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Issue trackers need a project".to_string(),
+        ));
+    }
+    match (&tracker.provider, &tracker.base_url) {
+        (IssueTrackerKind::Jira, None) => {
+            return Err(ApiError::BadRequest(
+                "Jira trackers need the site URL".to_string(),
+            ));
+        }
+        (IssueTrackerKind::Jira, Some(base_url)) if url::Url::parse(base_url).is_err() => {
+            return Err(ApiError::BadRequest(format!("'{base_url}' is not a URL")));
+        }
+        _ => {}
+    }
+    let token = tracker
+        .token
+        .as_deref()
+        .map(issue_tracker::seal_token)
+        .transpose()
+        .map_err(std::io::Error::other)?;
+
+    let pool = &deployment.db().pool;
+    deployment.repo().get_by_id(pool, repo_id).await?;
+    let tracker = RepoIssueTracker::set(pool, repo_id, &tracker, token.as_deref()).await?;
+    Ok(ResponseJson(ApiResponse::success(tracker)))
+}
+
+pub async fn delete_repo_issue_tracker(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    RepoIssueTracker::delete(&deployment.db().pool, repo_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+async fn issue_tracker_for(
+    deployment: &DeploymentImpl,
+    repo_id: Uuid,
+) -> Result<RepoIssueTracker, ApiError> {
+    RepoIssueTracker::find_by_repo_id(&deployment.db().pool, repo_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("No issue tracker is configured for this repo".to_string())
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepoIssuesQuery {
+    pub search: Option<String>,
+}
+
+/// Open issues of the repo's tracker, for picking which to import.
+pub async fn list_repo_issues(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    Query(query): Query<RepoIssuesQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TrackerIssue>>>, ApiError> {
+    let tracker = issue_tracker_for(&deployment, repo_id).await?;
+    let issues = issue_tracker::provider(&tracker)?
+        .list_open_issues(query.search.as_deref())
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(issues)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ImportIssuesRequest {
+    /// Issue numbers or keys, e.g. `42` or `ENG-42`.
+    pub issue_keys: Vec<String>,
+    /// Executor to run. Defaults to the executor profile from config.
+    pub executor_config: Option<ExecutorConfig>,
+    /// Defaults to the repo's default target branch, then its current branch.
+    pub target_branch: Option<String>,
+}
+
+/// Start a workspace for each issue, prompted with the issue's title and
+/// description, and link it back to the issue.
+pub async fn import_repo_issues(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+    Json(payload): Json<ImportIssuesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceIssueLink>>>, ApiError> {
+    if payload.issue_keys.is_empty() {
+        return Err(ApiError::BadRequest("No issues selected".to_string()));
+    }
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+    let tracker = issue_tracker_for(&deployment, repo_id).await?;
+
+    // Fetch every issue before starting any workspace, so a bad key doesn't
+    // leave a partial import behind
+    let provider = issue_tracker::provider(&tracker)?;
+    let mut issues = Vec::with_capacity(payload.issue_keys.len());
+    for key in &payload.issue_keys {
+        issues.push(provider.get_issue(key.trim()).await?);
+    }
+
+    let target_branch = match payload.target_branch.filter(|b| !b.trim().is_empty()) {
+        Some(branch) => branch,
+        None => match repo.default_target_branch.clone() {
+            Some(branch) => branch,
+            None => deployment.git().get_current_branch(&repo.path)?,
+        },
+    };
+    let executor_config = match payload.executor_config {
+        Some(executor_config) => executor_config,
+        None => {
+            let profile = deployment.config().read().await.executor_profile.clone();
+            ExecutorConfig {
+                variant: profile.variant,
+                ..ExecutorConfig::new(profile.executor)
+            }
+        }
+    };
+
+    let mut links = Vec::with_capacity(issues.len());
+    for issue in issues {
+        let ResponseJson(response) = create::create_and_start_workspace(
+            State(deployment.clone()),
+            Json(CreateAndStartWorkspaceRequest {
+                name: Some(format!("{} {}", issue.key, issue.title)),
+                repos: vec![WorkspaceRepoInput {
+                    repo_id: repo.id,
+                    target_branch: target_branch.clone(),
+                }],
+                linked_issue: None,
+                executor_config: executor_config.clone(),
+                prompt: issue.prompt(),
+                attachment_ids: None,
+                dev_server: None,
+                setup_script: None,
+                env_vars: None,
+            }),
+        )
+        .await?;
+        let Some(created) = response.into_data() else {
+            continue;
+        };
+        let link = WorkspaceIssueLink::create(
+            pool,
+            &CreateWorkspaceIssueLink {
+                workspace_id: created.workspace.id,
+                repo_id: repo.id,
+                provider: tracker.provider,
+                issue_key: issue.key,
+                title: issue.title,
+                url: issue.url,
+            },
+        )
+        .await?;
+        links.push(link);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "issues_imported",
+            serde_json::json!({
+                "repo_id": repo.id.to_string(),
+                "provider": tracker.provider,
+                "count": links.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
+
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/repos", get(get_repos).post(register_repo))
@@ -377,6 +846,50 @@ pub fn router() -> Router<DeploymentImpl> {
         )
         .route("/repos/{repo_id}/branches", get(get_repo_branches))
         .route("/repos/{repo_id}/remotes", get(get_repo_remotes))
+        .route(
+            "/repos/{repo_id}/resource-limits",
+            get(get_repo_resource_limits).put(set_repo_resource_limits),
+        )
+        .route(
+            "/repos/{repo_id}/protected-paths",
+            get(list_repo_protected_paths).post(create_repo_protected_path),
+        )
+        .route(
+            "/repos/{repo_id}/protected-paths/{path_id}",
+            delete(delete_repo_protected_path),
+        )
+        .route(
+            "/repos/{repo_id}/check-commands",
+            get(list_repo_check_commands).put(set_repo_check_commands),
+        )
+        .route(
+            "/repos/{repo_id}/resource-tags",
+            get(get_repo_resource_tags).put(set_repo_resource_tags),
+        )
+        .route(
+            "/repos/{repo_id}/container-image",
+            get(get_repo_container_image).put(set_repo_container_image),
+        )
+        .route(
+            "/repos/{repo_id}/setup-cache",
+            get(get_repo_setup_cache).put(set_repo_setup_cache),
+        )
+        .route(
+            "/repos/{repo_id}/setup-cache/entries",
+            delete(clear_repo_setup_cache),
+        )
+        .route(
+            "/repos/{repo_id}/issue-tracker",
+            get(get_repo_issue_tracker)
+                .put(set_repo_issue_tracker)
+                .delete(delete_repo_issue_tracker),
+        )
+        .route("/repos/{repo_id}/issues", get(list_repo_issues))
+        .route("/repos/{repo_id}/issues/import", post(import_repo_issues))
+        .route(
+            "/repos/{repo_id}/script-suggestions",
+            get(get_script_suggestions),
+        )
         .route("/repos/{repo_id}/prs", get(list_open_prs))
         .route("/repos/pr-info", get(get_pr_info))
         .route("/repos/{repo_id}/search", get(search_repo))
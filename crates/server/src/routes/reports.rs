@@ -0,0 +1,63 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::{Query, State},
+    http,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::get,
+};
+use db::models::report_period::ReportPeriodKind;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::reports;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_REPORT_LIMIT: i64 = 12;
+const MAX_REPORT_LIMIT: i64 = 120;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    pub period: ReportPeriodKind,
+    #[serde(default)]
+    pub format: ReportFormat,
+    pub limit: Option<i64>,
+}
+
+/// Recent weekly or monthly productivity reports, newest first, as JSON or
+/// CSV.
+pub async fn get_reports(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Response, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_REPORT_LIMIT)
+        .clamp(1, MAX_REPORT_LIMIT);
+    let reports = reports::recent_reports(&deployment.db().pool, query.period, limit).await?;
+
+    match query.format {
+        ReportFormat::Json => Ok(ResponseJson(ApiResponse::success(reports)).into_response()),
+        ReportFormat::Csv => Ok(Response::builder()
+            .status(http::StatusCode::OK)
+            .header(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("text/csv; charset=utf-8"),
+            )
+            .body(Body::from(reports::to_csv(&reports)))
+            .unwrap()),
+    }
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/reports", get(get_reports))
+}
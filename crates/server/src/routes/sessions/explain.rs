@@ -0,0 +1,268 @@
+use std::path::Path as StdPath;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use chrono::{Duration, Utc};
+use db::models::{
+    change_explanation::{ChangeExplanation, CreateChangeExplanation},
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_repo_state::ExecutionProcessRepoState,
+    repo::{Repo, RepoError},
+    session::Session,
+    workspace::{Workspace, WorkspaceError},
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType, review::ReviewRequest as ReviewAction},
+    profile::ExecutorConfig,
+};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    change_explanation::{build_explain_prompt, parse_explanation},
+    container::ContainerService,
+};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Explanation runs allowed per workspace within `RATE_LIMIT_WINDOW_MINUTES`.
+/// Cached explanations don't count.
+const RATE_LIMIT_MAX_RUNS: i64 = 10;
+const RATE_LIMIT_WINDOW_MINUTES: i64 = 60;
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ExplainChangeRequest {
+    pub executor_config: ExecutorConfig,
+    pub repo_id: Uuid,
+    /// Explain the commits made by this execution process. Takes precedence
+    /// over `base_commit` and `head_commit`.
+    pub execution_process_id: Option<Uuid>,
+    /// Any revision git understands. Defaults to the fork point from the
+    /// target branch.
+    pub base_commit: Option<String>,
+    /// Any revision git understands. Defaults to the worktree's HEAD.
+    pub head_commit: Option<String>,
+}
+
+/// Fill in the outcome of a finished summarization run. Explanations that
+/// are already complete, or still running, are returned unchanged.
+async fn settle(
+    pool: &SqlitePool,
+    explanation: ChangeExplanation,
+) -> Result<ChangeExplanation, ApiError> {
+    if explanation.explanation.is_some() || explanation.error.is_some() {
+        return Ok(explanation);
+    }
+
+    let process = match explanation.execution_process_id {
+        Some(id) => ExecutionProcess::find_by_id(pool, id).await?,
+        None => None,
+    };
+    let outcome = match process {
+        Some(process) => match process.status {
+            ExecutionProcessStatus::Running => return Ok(explanation),
+            ExecutionProcessStatus::Completed => {
+                let summary = CodingAgentTurn::find_by_execution_process_id(pool, process.id)
+                    .await?
+                    .and_then(|turn| turn.summary);
+                match summary {
+                    Some(summary) => parse_explanation(&summary),
+                    None => Err("The agent finished without replying".to_string()),
+                }
+            }
+            ExecutionProcessStatus::Failed
+            | ExecutionProcessStatus::Killed
+            | ExecutionProcessStatus::TimedOut
+            | ExecutionProcessStatus::Interrupted => {
+                Err("The summarization run did not complete".to_string())
+            }
+        },
+        None => Err("The summarization run was deleted".to_string()),
+    };
+    Ok(ChangeExplanation::complete(
+        pool,
+        explanation.id,
+        outcome.as_ref().map_err(String::as_str),
+    )
+    .await?)
+}
+
+fn resolve(
+    deployment: &DeploymentImpl,
+    worktree_path: &StdPath,
+    rev: &str,
+) -> Result<String, ApiError> {
+    deployment
+        .git()
+        .resolve_commit(worktree_path, rev)
+        .map_err(|_| ApiError::BadRequest(format!("Unknown revision '{rev}'")))
+}
+
+/// Explain a commit range for a reviewer. Returns the cached explanation for
+/// the same repo and commit pair when there is one; otherwise starts a
+/// summarization run in a fresh agent session and returns a pending
+/// explanation to poll with [`get_explanation`]. The run only reads the
+/// worktree: nothing it changes is committed and no follow-up action starts.
+pub async fn explain_change(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ExplainChangeRequest>,
+) -> Result<ResponseJson<ApiResponse<ChangeExplanation>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, session.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Workspace not found".to_string(),
+        )))?;
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = StdPath::new(&container_ref).join(&repo.name);
+
+    let (base_commit, head_commit) = if let Some(process_id) = payload.execution_process_id {
+        let state = ExecutionProcessRepoState::find_by_execution_process_id(pool, process_id)
+            .await?
+            .into_iter()
+            .find(|state| state.repo_id == repo.id);
+        match state.and_then(|s| s.before_head_commit.zip(s.after_head_commit)) {
+            Some(commits) => commits,
+            None => {
+                return Err(ApiError::BadRequest(
+                    "That execution process has no recorded commits for this repo".to_string(),
+                ));
+            }
+        }
+    } else {
+        let base_commit = match payload.base_commit.as_deref() {
+            Some(rev) => resolve(&deployment, &worktree_path, rev)?,
+            None => deployment.git().get_fork_point(
+                &worktree_path,
+                &workspace_repo.target_branch,
+                &workspace.branch,
+            )?,
+        };
+        let head_rev = payload.head_commit.as_deref().unwrap_or("HEAD");
+        (base_commit, resolve(&deployment, &worktree_path, head_rev)?)
+    };
+    if base_commit == head_commit {
+        return Err(ApiError::BadRequest(
+            "There are no commits between the base and head".to_string(),
+        ));
+    }
+
+    let failed = match ChangeExplanation::find_by_commits(pool, repo.id, &base_commit, &head_commit)
+        .await?
+    {
+        Some(cached) => {
+            let cached = settle(pool, cached).await?;
+            if cached.error.is_none() {
+                return Ok(ResponseJson(ApiResponse::success(cached)));
+            }
+            // Failed runs aren't worth caching; the retry reuses the row.
+            Some(cached)
+        }
+        None => None,
+    };
+
+    let window_start = Utc::now() - Duration::minutes(RATE_LIMIT_WINDOW_MINUTES);
+    if ChangeExplanation::count_for_workspace_since(pool, workspace.id, window_start).await?
+        >= RATE_LIMIT_MAX_RUNS
+    {
+        return Err(ApiError::TooManyRequests(format!(
+            "At most {RATE_LIMIT_MAX_RUNS} change explanations per workspace every {RATE_LIMIT_WINDOW_MINUTES} minutes"
+        )));
+    }
+
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Another process is running in this workspace".to_string(),
+        ));
+    }
+
+    // A fresh agent session, so the explanation neither sees nor joins the
+    // user's conversation with the agent.
+    let action = ExecutorAction::new(
+        ExecutorActionType::ReviewRequest(ReviewAction {
+            executor_config: payload.executor_config.clone(),
+            context: None,
+            prompt: build_explain_prompt(&repo.display_name, &base_commit, &head_commit),
+            session_id: None,
+            working_dir: session.agent_working_dir.clone(),
+        }),
+        None,
+    );
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::ChangeExplanation,
+        )
+        .await?;
+
+    let explanation = match failed {
+        Some(failed) => ChangeExplanation::restart(pool, failed.id, execution_process.id).await?,
+        None => {
+            ChangeExplanation::create(
+                pool,
+                &CreateChangeExplanation {
+                    workspace_id: workspace.id,
+                    repo_id: repo.id,
+                    base_commit,
+                    head_commit,
+                    execution_process_id: execution_process.id,
+                },
+            )
+            .await?
+        }
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "change_explanation_started",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "executor": payload.executor_config.executor.to_string(),
+                "from_execution_process": payload.execution_process_id.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(explanation)))
+}
+
+pub async fn get_explanation(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_session_id, explanation_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<ChangeExplanation>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let explanation = ChangeExplanation::find_by_id(pool, explanation_id)
+        .await?
+        .filter(|explanation| explanation.workspace_id == session.workspace_id)
+        .ok_or_else(|| ApiError::NotFound("Change explanation not found".to_string()))?;
+    Ok(ResponseJson(ApiResponse::success(
+        settle(pool, explanation).await?,
+    )))
+}
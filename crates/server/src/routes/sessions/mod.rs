@@ -1,12 +1,22 @@
+pub mod explain;
+pub mod plan;
+pub mod questions;
 pub mod queue;
+pub mod resume;
 pub mod review;
+pub mod share;
+pub mod timeline;
+pub mod timeouts;
+pub mod transcript;
+
+use std::{collections::HashMap, path::Path};
 
 use axum::{
     Extension, Json, Router,
     extract::{Query, State},
     middleware::from_fn_with_state,
     response::Json as ResponseJson,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use db::models::{
     coding_agent_turn::CodingAgentTurn,
@@ -31,8 +41,10 @@ use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_session_middleware,
-    routes::workspaces::execution::RunScriptError,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RateLimitedRoute, load_session_middleware, rate_limit},
+    routes::{prompt_snippets, workspaces::execution::RunScriptError},
 };
 
 #[derive(Debug, Deserialize)]
@@ -112,6 +124,20 @@ pub struct CreateFollowUpAttempt {
     pub retry_process_id: Option<Uuid>,
     pub force_when_dirty: Option<bool>,
     pub perform_git_reset: Option<bool>,
+    /// Start even though the worktree has uncommitted changes. Required when
+    /// the dirty worktree policy is `confirm`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub allow_dirty_worktree: Option<bool>,
+    /// Names of prompt snippets to append to the prompt, in order.
+    #[serde(default)]
+    #[ts(optional)]
+    pub snippets: Option<Vec<String>>,
+    /// Values for snippet variables, e.g. `failing_test`. These override the
+    /// ones derived from the workspace.
+    #[serde(default)]
+    #[ts(optional)]
+    pub snippet_variables: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -137,11 +163,31 @@ pub async fn follow_up(
 
     tracing::info!("{:?}", workspace);
 
-    deployment
+    let container_ref = deployment
         .container()
         .ensure_container_exists(&workspace)
         .await?;
 
+    // Resolve snippets first so an unknown name fails before any reset
+    let prompt = match &payload.snippets {
+        Some(names) if !names.is_empty() => {
+            let snippets = prompt_snippets::render_snippets(
+                &deployment,
+                &workspace,
+                names,
+                payload.snippet_variables.clone().unwrap_or_default(),
+            )
+            .await?;
+            [payload.prompt]
+                .into_iter()
+                .chain(snippets)
+                .filter(|part| !part.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+        _ => payload.prompt,
+    };
+
     let executor_profile_id = payload.executor_config.profile_id();
 
     // Validate executor matches session if session has prior executions
@@ -166,6 +212,18 @@ pub async fn follow_up(
             .await?;
     }
 
+    // Before any retry reset, so manual edits are stashed or refused rather
+    // than reset away
+    deployment
+        .container()
+        .guard_dirty_worktree(
+            workspace.id,
+            Path::new(&container_ref),
+            session.id,
+            payload.allow_dirty_worktree.unwrap_or(false),
+        )
+        .await?;
+
     if let Some(proc_id) = payload.retry_process_id {
         let force_when_dirty = payload.force_when_dirty.unwrap_or(false);
         let perform_git_reset = payload.perform_git_reset.unwrap_or(true);
@@ -177,8 +235,6 @@ pub async fn follow_up(
 
     let latest_session_info = CodingAgentTurn::find_latest_session_info(pool, session.id).await?;
 
-    let prompt = payload.prompt;
-
     let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
     let cleanup_action = deployment.container().cleanup_actions_for_repos(&repos);
 
@@ -314,17 +370,52 @@ pub async fn run_setup_script(
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let session_id_router = Router::new()
         .route("/", get(get_session).put(update_session))
-        .route("/follow-up", post(follow_up))
+        .route(
+            "/follow-up",
+            post(follow_up).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::TaskCreation),
+                rate_limit,
+            )),
+        )
         .route("/reset", post(reset_process))
         .route("/setup", post(run_setup_script))
         .route("/review", post(review::start_review))
+        .route("/explain", post(explain::explain_change))
+        .route("/explain/{explanation_id}", get(explain::get_explanation))
+        .route("/timeline", get(timeline::get_session_timeline))
+        .route("/transcript", get(transcript::get_session_transcript))
+        .route("/plan", get(plan::get_session_plan))
+        .route("/plan/accept", post(plan::accept_session_plan))
+        .route("/plan/reject", post(plan::reject_session_plan))
+        .route("/questions", get(questions::get_session_questions))
+        .route(
+            "/share",
+            get(share::list_share_links).post(share::create_share_link),
+        )
+        .route("/share/{link_id}", delete(share::revoke_share_link))
+        .route(
+            "/resume",
+            get(resume::get_interrupted_run).post(resume::resume_session),
+        )
+        .route(
+            "/timeouts",
+            get(timeouts::get_session_timeouts).put(timeouts::set_session_timeouts),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
         ));
 
     let sessions_router = Router::new()
-        .route("/", get(get_sessions).post(create_session))
+        .route(
+            "/",
+            get(get_sessions)
+                .post(create_session)
+                .layer(from_fn_with_state(
+                    (deployment.clone(), RateLimitedRoute::TaskCreation),
+                    rate_limit,
+                )),
+        )
         .nest("/{session_id}", session_id_router)
         .nest("/{session_id}/queue", queue::router(deployment));
 
@@ -0,0 +1,99 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    session::Session,
+    session_plan::{SessionPlan, SessionPlanStatus},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::approvals::ApprovalError;
+use ts_rs::TS;
+use utils::{
+    approvals::{ApprovalOutcome, ApprovalResponse},
+    response::ApiResponse,
+};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RejectPlanRequest {
+    /// Told to the agent so it can revise the plan.
+    #[serde(default)]
+    #[ts(optional)]
+    pub feedback: Option<String>,
+}
+
+/// The latest plan the session's agent proposed, if any.
+pub async fn get_session_plan(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<SessionPlan>>>, ApiError> {
+    let plan = SessionPlan::find_by_session_id(&deployment.db().pool, session.id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+/// Let the agent go ahead and carry out its pending plan.
+pub async fn accept_session_plan(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SessionPlan>>, ApiError> {
+    let plan = respond_to_plan(&deployment, &session, ApprovalOutcome::Approved).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+/// Stop the agent from carrying out its pending plan. With feedback it
+/// usually comes back with a revised plan.
+pub async fn reject_session_plan(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RejectPlanRequest>,
+) -> Result<ResponseJson<ApiResponse<SessionPlan>>, ApiError> {
+    let outcome = ApprovalOutcome::Denied {
+        reason: payload
+            .feedback
+            .filter(|feedback| !feedback.trim().is_empty()),
+    };
+    let plan = respond_to_plan(&deployment, &session, outcome).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+async fn respond_to_plan(
+    deployment: &DeploymentImpl,
+    session: &Session,
+    outcome: ApprovalOutcome,
+) -> Result<SessionPlan, ApiError> {
+    let pool = &deployment.db().pool;
+    let plan = SessionPlan::find_by_session_id(pool, session.id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Session has no plan".to_string()))?;
+    let approval_id = match (&plan.approval_id, plan.status) {
+        (Some(approval_id), SessionPlanStatus::Pending) => approval_id.clone(),
+        _ => return Err(ApiError::Conflict("Plan is no longer pending".to_string())),
+    };
+
+    let response = ApprovalResponse {
+        execution_process_id: plan.execution_process_id,
+        status: outcome.clone(),
+    };
+    match deployment.approvals().respond(&approval_id, response).await {
+        Ok(_) => {}
+        Err(ApprovalError::NotFound | ApprovalError::AlreadyCompleted) => {
+            // The agent stopped waiting, e.g. because the server restarted.
+            SessionPlan::resolve(pool, &approval_id, SessionPlanStatus::Expired, None).await?;
+            return Err(ApiError::Conflict("Plan is no longer pending".to_string()));
+        }
+        Err(e) => return Err(ApiError::BadRequest(e.to_string())),
+    }
+
+    // The executor settles the plan once it sees the response as well; do it
+    // here so the caller gets the decision back right away.
+    let (status, feedback) = match &outcome {
+        ApprovalOutcome::Denied { reason } => (SessionPlanStatus::Rejected, reason.as_deref()),
+        _ => (SessionPlanStatus::Accepted, None),
+    };
+    match SessionPlan::resolve(pool, &approval_id, status, feedback).await? {
+        Some(plan) => Ok(plan),
+        None => SessionPlan::find_by_session_id(pool, session.id)
+            .await?
+            .ok_or_else(|| ApiError::BadRequest("Session has no plan".to_string())),
+    }
+}
@@ -0,0 +1,16 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{session::Session, session_question::SessionQuestion};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Questions the session's agents asked and how they were answered, oldest
+/// first.
+pub async fn get_session_questions(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<SessionQuestion>>>, ApiError> {
+    let questions = SessionQuestion::find_by_session_id(&deployment.db().pool, session.id).await?;
+    Ok(ResponseJson(ApiResponse::success(questions)))
+}
@@ -1,14 +1,18 @@
 use axum::{
-    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
-    response::Json as ResponseJson, routing::get,
+    Extension, Json, Router,
+    extract::{Path, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, put},
 };
 use db::models::{scratch::DraftFollowUpData, session::Session};
 use deployment::Deployment;
 use executors::profile::ExecutorConfig;
 use serde::Deserialize;
-use services::services::queued_message::QueueStatus;
+use services::services::queued_message::{QueueStatus, QueuedMessage};
 use ts_rs::TS;
 use utils::response::ApiResponse;
+use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_session_middleware};
 
@@ -19,6 +23,22 @@ struct QueueMessageRequest {
     pub executor_config: ExecutorConfig,
 }
 
+/// Request body for editing a queued follow-up message
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateQueuedMessageRequest {
+    pub message: String,
+    /// Defaults to the message's current executor config.
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_config: Option<ExecutorConfig>,
+}
+
+/// Every queued message of the session in its new order.
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderQueuedMessagesRequest {
+    pub message_ids: Vec<Uuid>,
+}
+
 /// Queue a follow-up message to be executed when the current execution finishes
 async fn queue_message(
     Extension(session): Extension<Session>,
@@ -49,7 +69,7 @@ async fn queue_message(
     })))
 }
 
-/// Cancel a queued follow-up message
+/// Cancel all queued follow-up messages
 async fn cancel_queued_message(
     Extension(session): Extension<Session>,
     State(deployment): State<DeploymentImpl>,
@@ -81,6 +101,78 @@ async fn get_queue_status(
     Ok(ResponseJson(ApiResponse::success(status)))
 }
 
+/// Queued follow-up messages in the order they will be sent
+async fn get_queued_messages(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedMessage>>>, ApiError> {
+    let messages = deployment.queued_message_service().list_queued(session.id);
+    Ok(ResponseJson(ApiResponse::success(messages)))
+}
+
+/// Reorder queued follow-up messages. The request lists every queued message,
+/// so a client working from a stale queue is rejected instead of dropping a
+/// message queued or sent in the meantime.
+async fn reorder_queued_messages(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderQueuedMessagesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedMessage>>>, ApiError> {
+    let messages = deployment
+        .queued_message_service()
+        .reorder_queued(session.id, &payload.message_ids)
+        .ok_or_else(|| {
+            ApiError::Conflict(
+                "Message order must list every queued message exactly once".to_string(),
+            )
+        })?;
+    Ok(ResponseJson(ApiResponse::success(messages)))
+}
+
+/// Edit a queued follow-up message before it is sent
+async fn update_queued_message(
+    State(deployment): State<DeploymentImpl>,
+    Path((session_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateQueuedMessageRequest>,
+) -> Result<ResponseJson<ApiResponse<QueuedMessage>>, ApiError> {
+    let queue = deployment.queued_message_service();
+    let current = queue
+        .list_queued(session_id)
+        .into_iter()
+        .find(|queued| queued.id == message_id)
+        .ok_or_else(|| not_queued(message_id))?;
+    let data = DraftFollowUpData {
+        message: payload.message,
+        executor_config: payload
+            .executor_config
+            .unwrap_or(current.data.executor_config),
+    };
+    let updated = queue
+        .update_queued(session_id, message_id, data)
+        .ok_or_else(|| not_queued(message_id))?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+/// Remove a single queued follow-up message
+async fn delete_queued_message(
+    State(deployment): State<DeploymentImpl>,
+    Path((session_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
+    let queue = deployment.queued_message_service();
+    queue
+        .remove_queued(session_id, message_id)
+        .ok_or_else(|| not_queued(message_id))?;
+    Ok(ResponseJson(ApiResponse::success(
+        queue.get_status(session_id),
+    )))
+}
+
+fn not_queued(message_id: Uuid) -> ApiError {
+    ApiError::Conflict(format!(
+        "Message {message_id} is no longer queued. It may already have been sent."
+    ))
+}
+
 pub(super) fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     Router::new()
         .route(
@@ -89,8 +181,14 @@ pub(super) fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .post(queue_message)
                 .delete(cancel_queued_message),
         )
+        .route("/messages", get(get_queued_messages))
+        .route("/messages/order", put(reorder_queued_messages))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
         ))
+        .route(
+            "/messages/{message_id}",
+            put(update_queued_message).delete(delete_queued_message),
+        )
 }
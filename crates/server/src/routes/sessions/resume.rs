@@ -0,0 +1,182 @@
+//! Resuming coding agent runs cut off by a shutdown or crash. The provider
+//! session recorded on the run's turn is continued, e.g. with Claude's
+//! `--resume`, so the agent keeps its context; runs that ended before the
+//! agent reported a session are started again with their original prompt.
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use chrono::{DateTime, Utc};
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    session::Session,
+    workspace::{Workspace, WorkspaceError},
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+        coding_agent_initial::CodingAgentInitialRequest,
+    },
+    profile::ExecutorConfig,
+};
+use serde::{Deserialize, Serialize};
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const RESUME_PROMPT: &str = "Your previous run was interrupted when vibe-kanban restarted. \
+Uncommitted changes may have been saved in a WIP commit. Continue where you left off.";
+
+/// The interrupted run a session can be resumed from.
+#[derive(Debug, Serialize, TS)]
+pub struct InterruptedRun {
+    pub execution_process_id: Uuid,
+    pub interrupted_at: Option<DateTime<Utc>>,
+    /// Whether the agent's own session is continued. Otherwise the run's
+    /// prompt is sent again in a new agent session.
+    pub resumes_agent_session: bool,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ResumeSessionRequest {
+    /// Sent to the agent instead of the default instruction to carry on.
+    pub prompt: Option<String>,
+}
+
+struct Resumable {
+    process: ExecutionProcess,
+    executor_config: ExecutorConfig,
+    working_dir: Option<String>,
+    original_prompt: String,
+    agent_session_id: Option<String>,
+}
+
+/// The session's latest coding agent run, if it was interrupted.
+async fn find_resumable(
+    deployment: &DeploymentImpl,
+    session: &Session,
+) -> Result<Option<Resumable>, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(process) = ExecutionProcess::find_latest_coding_agent_for_session(pool, session.id)
+        .await?
+        .filter(|process| process.status == ExecutionProcessStatus::Interrupted)
+    else {
+        return Ok(None);
+    };
+
+    let action = process
+        .executor_action()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let (executor_config, working_dir, original_prompt) = match action.typ() {
+        ExecutorActionType::CodingAgentInitialRequest(request) => (
+            request.executor_config.clone(),
+            request.working_dir.clone(),
+            request.prompt.clone(),
+        ),
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => (
+            request.executor_config.clone(),
+            request.working_dir.clone(),
+            request.prompt.clone(),
+        ),
+        ExecutorActionType::ReviewRequest(request) => (
+            request.executor_config.clone(),
+            request.working_dir.clone(),
+            request.prompt.clone(),
+        ),
+        ExecutorActionType::ScriptRequest(_) => return Ok(None),
+    };
+    let agent_session_id = CodingAgentTurn::find_latest_session_info(pool, session.id)
+        .await?
+        .map(|info| info.session_id);
+
+    Ok(Some(Resumable {
+        process,
+        executor_config,
+        working_dir,
+        original_prompt,
+        agent_session_id,
+    }))
+}
+
+pub async fn get_interrupted_run(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<InterruptedRun>>>, ApiError> {
+    let run = find_resumable(&deployment, &session)
+        .await?
+        .map(|resumable| InterruptedRun {
+            execution_process_id: resumable.process.id,
+            interrupted_at: resumable.process.completed_at,
+            resumes_agent_session: resumable.agent_session_id.is_some(),
+        });
+    Ok(ResponseJson(ApiResponse::success(run)))
+}
+
+pub async fn resume_session(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ResumeSessionRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(resumable) = find_resumable(&deployment, &session).await? else {
+        return Err(ApiError::Conflict(
+            "The session has no interrupted run to resume".to_string(),
+        ));
+    };
+
+    let workspace = Workspace::find_by_id(pool, session.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Workspace not found".to_string(),
+        )))?;
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    let prompt = payload
+        .prompt
+        .filter(|prompt| !prompt.trim().is_empty())
+        .unwrap_or_else(|| RESUME_PROMPT.to_string());
+    let action_type = match resumable.agent_session_id {
+        Some(agent_session_id) => {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id: agent_session_id,
+                reset_to_message_id: None,
+                executor_config: resumable.executor_config,
+                working_dir: resumable.working_dir,
+            })
+        }
+        None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt: resumable.original_prompt,
+            executor_config: resumable.executor_config,
+            working_dir: resumable.working_dir,
+        }),
+    };
+
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+    let cleanup_action = deployment.container().cleanup_actions_for_repos(&repos);
+    let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+
+    tracing::info!(
+        "Resuming session {} after interrupted execution {}",
+        session.id,
+        resumable.process.id
+    );
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
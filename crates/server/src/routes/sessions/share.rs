@@ -0,0 +1,92 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use chrono::{Duration, Utc};
+use db::models::{session::Session, session_share_link::SessionShareLink};
+use deployment::Deployment;
+use rand::{Rng, distributions::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::shared_sessions::hash_share_token};
+
+const DEFAULT_SHARE_LINK_HOURS: u32 = 24;
+const MAX_SHARE_LINK_HOURS: u32 = 24 * 30;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateShareLinkRequest {
+    /// How long the link works for. Defaults to a day; at most 30 days.
+    #[serde(default)]
+    #[ts(optional)]
+    pub expires_in_hours: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct CreatedShareLink {
+    pub link: SessionShareLink,
+    /// Only returned here; it can't be recovered later.
+    pub token: String,
+    /// API path of the shared session, to open through the relay.
+    pub path: String,
+}
+
+fn generate_share_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect()
+}
+
+/// Create a read-only link to the session's transcript and live logs, for
+/// someone who isn't paired with this host.
+pub async fn create_share_link(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateShareLinkRequest>,
+) -> Result<ResponseJson<ApiResponse<CreatedShareLink>>, ApiError> {
+    let hours = payload.expires_in_hours.unwrap_or(DEFAULT_SHARE_LINK_HOURS);
+    if !(1..=MAX_SHARE_LINK_HOURS).contains(&hours) {
+        return Err(ApiError::BadRequest(format!(
+            "expires_in_hours must be between 1 and {MAX_SHARE_LINK_HOURS}"
+        )));
+    }
+
+    let token = generate_share_token();
+    let link = SessionShareLink::create(
+        &deployment.db().pool,
+        session.id,
+        &hash_share_token(&token),
+        Utc::now() + Duration::hours(hours.into()),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(CreatedShareLink {
+        link,
+        path: format!("/api/shared/{token}"),
+        token,
+    })))
+}
+
+pub async fn list_share_links(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<SessionShareLink>>>, ApiError> {
+    let links = SessionShareLink::find_by_session_id(&deployment.db().pool, session.id).await?;
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
+
+/// Stop a link from working.
+pub async fn revoke_share_link(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, link_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !SessionShareLink::delete(&deployment.db().pool, session.id, link_id).await? {
+        return Err(ApiError::BadRequest("Share link not found".to_string()));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    execution_process::ExecutionProcess, execution_process_repo_state::ExecutionProcessRepoState,
+    session::Session, workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use services::services::{
+    container::ContainerService,
+    session_timeline::{
+        SessionTimeline, TimelineCommit, TimelineProcess, duration_ms, events_from_patches,
+    },
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Processes dropped by a reset are left out, as in the conversation view.
+pub(super) async fn build_timeline(
+    deployment: &DeploymentImpl,
+    session: &Session,
+) -> Result<SessionTimeline, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo_names: HashMap<_, _> =
+        WorkspaceRepo::find_repos_for_workspace(pool, session.workspace_id)
+            .await?
+            .into_iter()
+            .map(|repo| (repo.id, repo.name))
+            .collect();
+
+    let mut processes = Vec::new();
+    for process in ExecutionProcess::find_by_session_id(pool, session.id, false).await? {
+        let commits = ExecutionProcessRepoState::find_by_execution_process_id(pool, process.id)
+            .await?
+            .into_iter()
+            .filter(|state| {
+                state.after_head_commit.is_some()
+                    && state.after_head_commit != state.before_head_commit
+            })
+            .map(|state| TimelineCommit {
+                repo_id: state.repo_id,
+                repo_name: repo_names.get(&state.repo_id).cloned().unwrap_or_default(),
+                before_sha: state.before_head_commit,
+                after_sha: state.after_head_commit,
+            })
+            .collect();
+
+        let patches = deployment
+            .container()
+            .normalized_log_patches(&process.id)
+            .await
+            .unwrap_or_default();
+
+        let executor = process
+            .executor_action()
+            .ok()
+            .and_then(|action| action.base_executor())
+            .map(|executor| executor.to_string());
+
+        processes.push(TimelineProcess {
+            execution_process_id: process.id,
+            run_reason: process.run_reason.clone(),
+            status: process.status.clone(),
+            exit_code: process.exit_code,
+            executor,
+            started_at: process.started_at,
+            completed_at: process.completed_at,
+            duration_ms: duration_ms(process.started_at, process.completed_at),
+            commits,
+            events: events_from_patches(&patches),
+        });
+    }
+
+    let started_at = processes.first().map(|process| process.started_at);
+    let completed_at = if processes.iter().all(|p| p.completed_at.is_some()) {
+        processes.iter().filter_map(|p| p.completed_at).max()
+    } else {
+        None
+    };
+    let timeline = SessionTimeline {
+        session_id: session.id,
+        started_at,
+        completed_at,
+        duration_ms: started_at
+            .map(|started_at| duration_ms(started_at, completed_at))
+            .unwrap_or(0),
+        processes,
+    };
+    Ok(timeline)
+}
+
+/// Everything that happened in a session, in order, for the replay view.
+pub async fn get_session_timeline(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SessionTimeline>>, ApiError> {
+    let timeline = build_timeline(&deployment, &session).await?;
+    Ok(ResponseJson(ApiResponse::success(timeline)))
+}
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    session::Session,
+    session_timeout::{ExecutionTimeouts, SessionTimeout},
+};
+use deployment::Deployment;
+use executors::executors::BaseCodingAgent;
+use serde::Serialize;
+use services::services::execution_timeout;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct SessionTimeoutsResponse {
+    /// The session's own overrides.
+    pub session: ExecutionTimeouts,
+    /// The limits its next coding agent run gets, after falling back to the
+    /// executor and global configuration.
+    pub effective: ExecutionTimeouts,
+}
+
+async fn timeouts_response(
+    deployment: &DeploymentImpl,
+    session: &Session,
+    overrides: ExecutionTimeouts,
+) -> SessionTimeoutsResponse {
+    let executor = session
+        .executor
+        .as_deref()
+        .and_then(|executor| BaseCodingAgent::from_str(executor).ok());
+    let effective = execution_timeout::effective_timeouts(
+        &deployment.config().read().await.executor_timeouts,
+        executor,
+        Some(overrides),
+    );
+    SessionTimeoutsResponse {
+        session: overrides,
+        effective,
+    }
+}
+
+pub async fn get_session_timeouts(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SessionTimeoutsResponse>>, ApiError> {
+    let overrides = SessionTimeout::find_by_session_id(&deployment.db().pool, session.id)
+        .await?
+        .map(|timeout| timeout.timeouts())
+        .unwrap_or_default();
+    Ok(ResponseJson(ApiResponse::success(
+        timeouts_response(&deployment, &session, overrides).await,
+    )))
+}
+
+/// Replace the session's overrides. Clearing both limits removes them, and
+/// runs already in progress keep the limits they started with.
+pub async fn set_session_timeouts(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ExecutionTimeouts>,
+) -> Result<ResponseJson<ApiResponse<SessionTimeoutsResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload == ExecutionTimeouts::default() {
+        SessionTimeout::delete(pool, session.id).await?;
+    } else {
+        SessionTimeout::set(pool, session.id, payload).await?;
+    }
+    Ok(ResponseJson(ApiResponse::success(
+        timeouts_response(&deployment, &session, payload).await,
+    )))
+}
@@ -0,0 +1,61 @@
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Query, State},
+    http,
+    response::Response,
+};
+use db::models::session::Session;
+use serde::Deserialize;
+use services::services::session_transcript;
+
+use super::timeline::build_timeline;
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Md,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    #[serde(default)]
+    pub format: TranscriptFormat,
+}
+
+/// The session's agent conversation as a document to attach to a PR or
+/// ticket: prompts, replies, tool calls with their diffs, and approvals.
+pub async fn get_session_transcript(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<Response, ApiError> {
+    let timeline = build_timeline(&deployment, &session).await?;
+    let title = session
+        .name
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| format!("Session {}", session.id));
+
+    let (content_type, body) = match query.format {
+        TranscriptFormat::Md => (
+            "text/markdown; charset=utf-8",
+            session_transcript::to_markdown(&title, &timeline),
+        ),
+        TranscriptFormat::Html => (
+            "text/html; charset=utf-8",
+            session_transcript::to_html(&title, &timeline),
+        ),
+    };
+    Ok(Response::builder()
+        .status(http::StatusCode::OK)
+        .header(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static(content_type),
+        )
+        .body(Body::from(body))
+        .unwrap())
+}
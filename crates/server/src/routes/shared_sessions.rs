@@ -0,0 +1,123 @@
+//! Read-only views of a single session for people holding a share link
+//! instead of a paired, trusted client. The link's token in the path is the
+//! only credential, so these routes sit outside the relay-signed routes and
+//! never accept anything but reads.
+
+use std::collections::HashMap;
+
+use axum::{
+    BoxError, Extension, Router,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::{Next, from_fn_with_state},
+    response::{Response, Sse, sse::Event},
+    routing::get,
+};
+use db::models::{
+    execution_process::ExecutionProcess, session::Session, session_share_link::SessionShareLink,
+};
+use deployment::Deployment;
+use futures_util::Stream;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    routes::{
+        execution_processes::{LogStreamQuery, stream_logs_sse},
+        sessions::{timeline, transcript},
+    },
+};
+
+pub(crate) fn hash_share_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Resolve the share token to its session and refuse anything that could
+/// change state, whatever routes sit behind it.
+async fn load_shared_session_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(params): Path<HashMap<String, String>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return Err(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let token = params.get("token").ok_or(StatusCode::NOT_FOUND)?;
+
+    let pool = &deployment.db().pool;
+    let link =
+        match SessionShareLink::find_active_by_token_hash(pool, &hash_share_token(token)).await {
+            Ok(Some(link)) => link,
+            Ok(None) => return Err(StatusCode::NOT_FOUND),
+            Err(e) => {
+                tracing::error!("Failed to look up share link: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+    let session = match Session::find_by_id(pool, link.session_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch shared session {}: {}", link.session_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(session);
+    Ok(next.run(request).await)
+}
+
+/// Follow one of the session's processes live. Processes of other sessions
+/// are reported as missing.
+async fn stream_shared_logs(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, process_id)): Path<(String, Uuid)>,
+    Query(query): Query<LogStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, StatusCode> {
+    let process = match ExecutionProcess::find_by_id(&deployment.db().pool, process_id).await {
+        Ok(Some(process)) if process.session_id == session.id => process,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch execution process {}: {}", process_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    Ok(stream_logs_sse(Extension(process), State(deployment), Query(query), headers).await)
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let shared_router = Router::new()
+        // The timeline also lists the processes whose logs can be followed.
+        .route("/", get(timeline::get_session_timeline))
+        .route("/transcript", get(transcript::get_session_transcript))
+        .route(
+            "/processes/{process_id}/logs/stream",
+            get(stream_shared_logs),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_shared_session_middleware,
+        ));
+
+    Router::new().nest("/shared/{token}", shared_router)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash_share_token;
+
+    #[test]
+    fn share_token_hash_is_hex_sha256() {
+        assert_eq!(
+            hash_share_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
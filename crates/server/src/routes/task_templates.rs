@@ -0,0 +1,133 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::get,
+};
+use db::models::{
+    repo::Repo,
+    task_template::{CreateTaskTemplate, TaskTemplate, UpdateTaskTemplate},
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_task_template_middleware,
+    routes::workspaces::create::validate_env_vars,
+};
+
+pub async fn get_task_templates(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskTemplate>>>, ApiError> {
+    let templates = TaskTemplate::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(templates)))
+}
+
+pub async fn get_task_template(
+    Extension(template): Extension<TaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn create_task_template(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    validate_template_text("name", &payload.name)?;
+    validate_template_text("prompt", &payload.prompt)?;
+    if let Some(env_vars) = &payload.env_vars {
+        validate_env_vars(env_vars)?;
+    }
+    if let Some(repo_ids) = &payload.repo_ids {
+        validate_repo_ids(&deployment, repo_ids).await?;
+    }
+
+    let template = TaskTemplate::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_template_created",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn update_task_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateTaskTemplate>,
+) -> Result<ResponseJson<ApiResponse<TaskTemplate>>, ApiError> {
+    if let Some(name) = &payload.name {
+        validate_template_text("name", name)?;
+    }
+    if let Some(prompt) = &payload.prompt {
+        validate_template_text("prompt", prompt)?;
+    }
+    if let Some(env_vars) = &payload.env_vars {
+        validate_env_vars(env_vars)?;
+    }
+    if let Some(repo_ids) = &payload.repo_ids {
+        validate_repo_ids(&deployment, repo_ids).await?;
+    }
+
+    let template = TaskTemplate::update(&deployment.db().pool, template.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn delete_task_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = TaskTemplate::delete(&deployment.db().pool, template.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+fn validate_template_text(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.trim().is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "Template {field} must not be empty"
+        )));
+    }
+    Ok(())
+}
+
+async fn validate_repo_ids(deployment: &DeploymentImpl, repo_ids: &[Uuid]) -> Result<(), ApiError> {
+    for repo_id in repo_ids {
+        if Repo::find_by_id(&deployment.db().pool, *repo_id)
+            .await?
+            .is_none()
+        {
+            return Err(ApiError::BadRequest(format!(
+                "Repository {repo_id} not found"
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let template_router = Router::new()
+        .route(
+            "/",
+            get(get_task_template)
+                .put(update_task_template)
+                .delete(delete_task_template),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_template_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_task_templates).post(create_task_template))
+        .nest("/{template_id}", template_router);
+
+    Router::new().nest("/task-templates", inner)
+}
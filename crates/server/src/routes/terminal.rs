@@ -2,14 +2,27 @@ use std::path::PathBuf;
 
 use axum::{
     Router,
-    extract::{Query, State, ws::Message},
-    response::IntoResponse,
+    body::Body,
+    extract::{Path, Query, State, ws::Message},
+    http,
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::get,
 };
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
+use services::services::{
+    config::{TerminalConfig, TerminalCwd},
+    container::ContainerService,
+};
+use utils::{
+    response::ApiResponse,
+    terminal_recordings::{
+        TerminalRecorder, TerminalRecordingInfo, list_recordings, recording_file_path,
+        workspace_recordings_dir,
+    },
+};
 use uuid::Uuid;
 
 use crate::{
@@ -58,20 +71,23 @@ async fn terminal_ws(
         .await?
         .ok_or_else(|| ApiError::BadRequest("Attempt not found".to_string()))?;
 
-    let container_ref = attempt
-        .container_ref
-        .ok_or_else(|| ApiError::BadRequest("Attempt has no workspace directory".to_string()))?;
-
-    let base_dir = PathBuf::from(&container_ref);
-    if !base_dir.exists() {
+    if attempt.container_ref.is_none() {
         return Err(ApiError::BadRequest(
-            "Workspace directory does not exist".to_string(),
+            "Attempt has no workspace directory".to_string(),
         ));
     }
 
+    // Recreates the worktree from its branch if it was removed out from under us.
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&attempt)
+        .await?;
+    let base_dir = PathBuf::from(&container_ref);
+    let terminal = deployment.config().read().await.terminal.clone();
+
     let mut working_dir = base_dir.clone();
     match WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, query.workspace_id).await {
-        Ok(repos) if repos.len() == 1 => {
+        Ok(repos) if repos.len() == 1 && terminal.cwd == TerminalCwd::Repo => {
             let repo_dir = base_dir.join(&repos[0].name);
             if repo_dir.exists() {
                 working_dir = repo_dir;
@@ -88,20 +104,43 @@ async fn terminal_ws(
     }
 
     Ok(ws.on_upgrade(move |socket| {
-        handle_terminal_ws(socket, deployment, working_dir, query.cols, query.rows)
+        handle_terminal_ws(
+            socket,
+            deployment,
+            query.workspace_id,
+            working_dir,
+            terminal,
+            query.cols,
+            query.rows,
+        )
     }))
 }
 
 async fn handle_terminal_ws(
     mut socket: MaybeSignedWebSocket,
     deployment: DeploymentImpl,
+    workspace_id: Uuid,
     working_dir: PathBuf,
+    terminal: TerminalConfig,
     cols: u16,
     rows: u16,
 ) {
+    let recorder = if terminal.record {
+        let path = recording_file_path(workspace_id, Uuid::new_v4());
+        match TerminalRecorder::create(&path, cols, rows) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                tracing::warn!("Failed to start terminal recording: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let (session_id, mut output_rx) = match deployment
         .pty()
-        .create_session(working_dir, cols, rows)
+        .create_session(working_dir, &terminal, recorder, cols, rows)
         .await
     {
         Ok(result) => result,
@@ -175,6 +214,51 @@ async fn send_error(socket: &mut MaybeSignedWebSocket, message: &str) -> anyhow:
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+struct RecordingsQuery {
+    pub workspace_id: Uuid,
+}
+
+/// The workspace's terminal recordings, newest first.
+async fn list_terminal_recordings(
+    Query(query): Query<RecordingsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TerminalRecordingInfo>>>, ApiError> {
+    let dir = workspace_recordings_dir(query.workspace_id);
+    let recordings = tokio::task::spawn_blocking(move || list_recordings(&dir))
+        .await
+        .map_err(std::io::Error::other)??;
+    Ok(ResponseJson(ApiResponse::success(recordings)))
+}
+
+/// A recording as an asciicast v2 file, for asciinema-compatible players.
+async fn get_terminal_recording(
+    Path(recording_id): Path<Uuid>,
+    Query(query): Query<RecordingsQuery>,
+) -> Result<Response, ApiError> {
+    let path = recording_file_path(query.workspace_id, recording_id);
+    let cast = match tokio::fs::read(&path).await {
+        Ok(cast) => cast,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ApiError::BadRequest("Recording not found".to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Response::builder()
+        .status(http::StatusCode::OK)
+        .header(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("application/x-asciicast"),
+        )
+        .body(Body::from(cast))
+        .unwrap())
+}
+
 pub(super) fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/terminal/ws", get(terminal_ws))
+    Router::new()
+        .route("/terminal/ws", get(terminal_ws))
+        .route("/terminal/recordings", get(list_terminal_recordings))
+        .route(
+            "/terminal/recordings/{recording_id}",
+            get(get_terminal_recording),
+        )
 }
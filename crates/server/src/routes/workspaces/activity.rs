@@ -0,0 +1,78 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    workspace::Workspace,
+    workspace_activity::{WorkspaceActivity, WorkspaceActivityCursor},
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_ACTIVITY_LIMIT: u32 = 50;
+const MAX_ACTIVITY_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct WorkspaceActivityQuery {
+    /// `next_cursor` of the previous page.
+    #[serde(default)]
+    #[ts(optional)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceActivityPage {
+    pub events: Vec<WorkspaceActivity>,
+    /// Set when there are older events.
+    pub next_cursor: Option<String>,
+}
+
+/// Process runs, approvals, commits, pull request changes and automatic
+/// rebases in the workspace, newest first.
+pub async fn get_workspace_activity(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorkspaceActivityQuery>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceActivityPage>>, ApiError> {
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => Some(
+            WorkspaceActivityCursor::decode(cursor)
+                .ok_or_else(|| ApiError::BadRequest("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ACTIVITY_LIMIT)
+        .clamp(1, MAX_ACTIVITY_LIMIT);
+
+    // One extra row tells whether there is another page.
+    let mut events = WorkspaceActivity::find_by_workspace_id(
+        &deployment.db().pool,
+        workspace.id,
+        cursor.as_ref(),
+        i64::from(limit) + 1,
+    )
+    .await?;
+    let next_cursor = if events.len() > limit as usize {
+        events.truncate(limit as usize);
+        events
+            .last()
+            .map(|last| WorkspaceActivityCursor::after(last).encode())
+    } else {
+        None
+    };
+
+    Ok(ResponseJson(ApiResponse::success(WorkspaceActivityPage {
+        events,
+        next_cursor,
+    })))
+}
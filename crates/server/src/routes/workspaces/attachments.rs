@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{path::Path, str::FromStr};
 
 use axum::{
     Extension, Router,
@@ -11,11 +11,13 @@ use axum::{
 };
 use db::models::{file::File, session::Session, workspace::Workspace};
 use deployment::Deployment;
+use executors::executors::BaseCodingAgent;
 use mime_guess::MimeGuess;
 use serde::{Deserialize, Serialize};
 use services::services::{
     container::ContainerService,
     file::{FileError, FileService},
+    image_processing::ImageLimits,
     remote_client::RemoteClient,
 };
 use tokio::fs::File as TokioFile;
@@ -30,7 +32,7 @@ use crate::{
     middleware::load_workspace_middleware,
     routes::attachments::{
         AttachmentMetadata, AttachmentResponse, content_type_and_disposition_for_attachment,
-        process_file_upload,
+        process_file_upload, store_upload,
     },
 };
 
@@ -85,18 +87,79 @@ pub async fn upload_file(
     Query(query): Query<SessionScopedQuery>,
     multipart: Multipart,
 ) -> Result<ResponseJson<ApiResponse<AttachmentResponse>>, ApiError> {
+    let image_limits = session_image_limits(&deployment, query.session_id).await?;
     let attachment_response =
-        process_file_upload(&deployment, multipart, Some(workspace.id)).await?;
+        process_file_upload(&deployment, multipart, Some(workspace.id), image_limits).await?;
+
+    copy_to_session_worktree(
+        &deployment,
+        &workspace,
+        query.session_id,
+        &attachment_response,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(attachment_response)))
+}
 
-    let base_path = resolve_session_base_path(&deployment, &workspace, query.session_id).await?;
-    deployment
+/// Finish a chunked upload started with `POST /attachments/uploads`, storing
+/// it as a workspace attachment like a regular upload.
+pub async fn complete_chunked_upload(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    axum::extract::Path((_, upload_id)): axum::extract::Path<(Uuid, Uuid)>,
+    Query(query): Query<SessionScopedQuery>,
+) -> Result<ResponseJson<ApiResponse<AttachmentResponse>>, ApiError> {
+    let image_limits = session_image_limits(&deployment, query.session_id).await?;
+    let upload = deployment
         .file()
-        .copy_files_by_ids_to_worktree(&base_path, &[attachment_response.id])
+        .chunked_uploads()
+        .complete(upload_id)
         .await?;
-
+    let attachment_response = store_upload(
+        &deployment,
+        &upload.data,
+        &upload.filename,
+        Some(workspace.id),
+        image_limits,
+    )
+    .await?;
+
+    copy_to_session_worktree(
+        &deployment,
+        &workspace,
+        query.session_id,
+        &attachment_response,
+    )
+    .await?;
     Ok(ResponseJson(ApiResponse::success(attachment_response)))
 }
 
+/// Size images for the session's executor, when it is already known.
+async fn session_image_limits(
+    deployment: &DeploymentImpl,
+    session_id: Uuid,
+) -> Result<ImageLimits, ApiError> {
+    let executor = Session::find_by_id(&deployment.db().pool, session_id)
+        .await?
+        .and_then(|session| session.executor)
+        .and_then(|executor| BaseCodingAgent::from_str(&executor).ok());
+    Ok(ImageLimits::for_executor(executor))
+}
+
+async fn copy_to_session_worktree(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    session_id: Uuid,
+    attachment: &AttachmentResponse,
+) -> Result<(), ApiError> {
+    let base_path = resolve_session_base_path(deployment, workspace, session_id).await?;
+    deployment
+        .file()
+        .copy_files_by_ids_to_worktree(&base_path, &[attachment.id])
+        .await?;
+    Ok(())
+}
+
 pub async fn associate_workspace_attachments(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -323,7 +386,7 @@ async fn resolve_session_base_path(
 }
 
 /// Middleware to load Workspace for routes with wildcard path params.
-async fn load_workspace_with_wildcard(
+pub(super) async fn load_workspace_with_wildcard(
     State(deployment): State<DeploymentImpl>,
     axum::extract::Path((id, _path)): axum::extract::Path<(Uuid, String)>,
     mut request: Request,
@@ -372,7 +435,11 @@ pub(crate) async fn import_issue_attachments_from_remote(
         };
 
         let file = match file_service
-            .store_file(&bytes, &entry.attachment.original_name)
+            .store_file(
+                &bytes,
+                &entry.attachment.original_name,
+                ImageLimits::default(),
+            )
             .await
         {
             Ok(file) => file,
@@ -418,5 +485,17 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 load_workspace_with_wildcard,
             ));
 
-    metadata_router.merge(file_router)
+    // The workspace loader takes the second path parameter as a string, so
+    // it also serves routes with an upload id.
+    let upload_router = Router::new()
+        .route(
+            "/uploads/{upload_id}/complete",
+            post(complete_chunked_upload),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_with_wildcard,
+        ));
+
+    metadata_router.merge(file_router).merge(upload_router)
 }
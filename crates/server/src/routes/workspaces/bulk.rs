@@ -0,0 +1,307 @@
+//! One request for an action on many workspaces, so cleaning up a backlog
+//! over a high-latency relay connection does not take a request per item.
+
+use std::collections::HashSet;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    audit_log::AuditActor, execution_process::ExecutionProcess, repo::Repo,
+    requests::UpdateWorkspace, workspace::Workspace, workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::{
+    core::{self, DeleteWorkspaceQuery},
+    git::{self, ChangeTargetBranchRequest, GitOperationError, RebaseWorkspaceRequest},
+};
+use crate::{DeploymentImpl, error::ApiError, middleware::RequestActor};
+
+const MAX_BULK_WORKSPACES: usize = 100;
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum BulkWorkspaceAction {
+    Delete {
+        #[serde(default)]
+        delete_branches: bool,
+        #[serde(default)]
+        delete_remote: bool,
+    },
+    Archive,
+    /// Rebase every repo of the workspace onto its target branch.
+    Rebase,
+    /// Point every repo of the workspace at `new_target_branch`.
+    ChangeTargetBranch {
+        new_target_branch: String,
+    },
+}
+
+impl BulkWorkspaceAction {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Delete { .. } => "delete",
+            Self::Archive => "archive",
+            Self::Rebase => "rebase",
+            Self::ChangeTargetBranch { .. } => "change_target_branch",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct BulkWorkspaceRequest {
+    pub workspace_ids: Vec<Uuid>,
+    pub action: BulkWorkspaceAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkItemStatus {
+    Succeeded,
+    Failed,
+    /// Not attempted because another item failed validation.
+    Skipped,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BulkWorkspaceItemResult {
+    pub workspace_id: Uuid,
+    pub status: BulkItemStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BulkWorkspaceResponse {
+    /// False when validation failed for some workspace and nothing was
+    /// changed.
+    pub applied: bool,
+    pub results: Vec<BulkWorkspaceItemResult>,
+}
+
+/// Validate the action for every workspace first and change nothing unless
+/// all pass. Git operations cannot be rolled back, so once applying has
+/// started a failing item does not undo the others; each item reports its
+/// own outcome.
+pub async fn bulk_workspace_action(
+    State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
+    Json(request): Json<BulkWorkspaceRequest>,
+) -> Result<ResponseJson<ApiResponse<BulkWorkspaceResponse>>, ApiError> {
+    let mut seen = HashSet::new();
+    let workspace_ids: Vec<Uuid> = request
+        .workspace_ids
+        .into_iter()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    if workspace_ids.is_empty() {
+        return Err(ApiError::BadRequest("No workspaces given".to_string()));
+    }
+    if workspace_ids.len() > MAX_BULK_WORKSPACES {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_BULK_WORKSPACES} workspaces can be changed at once"
+        )));
+    }
+
+    let mut validated = Vec::with_capacity(workspace_ids.len());
+    for workspace_id in &workspace_ids {
+        validated.push(validate(&deployment, *workspace_id, &request.action).await?);
+    }
+
+    if validated.iter().any(Result::is_err) {
+        let results = workspace_ids
+            .into_iter()
+            .zip(validated)
+            .map(|(workspace_id, validation)| match validation {
+                Ok(_) => item_result(workspace_id, BulkItemStatus::Skipped, None),
+                Err(error) => item_result(workspace_id, BulkItemStatus::Failed, Some(error)),
+            })
+            .collect();
+        return Ok(ResponseJson(ApiResponse::success(BulkWorkspaceResponse {
+            applied: false,
+            results,
+        })));
+    }
+
+    let mut results = Vec::with_capacity(workspace_ids.len());
+    for (workspace, repos) in validated.into_iter().flatten() {
+        let workspace_id = workspace.id;
+        let result = match apply(&deployment, &actor, workspace, repos, &request.action).await {
+            Ok(()) => item_result(workspace_id, BulkItemStatus::Succeeded, None),
+            Err(error) => {
+                tracing::warn!(
+                    "Bulk {} failed for workspace {}: {}",
+                    request.action.name(),
+                    workspace_id,
+                    error
+                );
+                item_result(workspace_id, BulkItemStatus::Failed, Some(error))
+            }
+        };
+        results.push(result);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspaces_bulk_action",
+            serde_json::json!({
+                "action": request.action.name(),
+                "count": results.len(),
+                "failed": results
+                    .iter()
+                    .filter(|r| r.status == BulkItemStatus::Failed)
+                    .count(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(BulkWorkspaceResponse {
+        applied: true,
+        results,
+    })))
+}
+
+pub(super) fn item_result(
+    workspace_id: Uuid,
+    status: BulkItemStatus,
+    error: Option<String>,
+) -> BulkWorkspaceItemResult {
+    BulkWorkspaceItemResult {
+        workspace_id,
+        status,
+        error,
+    }
+}
+
+/// The workspace and its repos if the action can be applied to it, or why
+/// not.
+async fn validate(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    action: &BulkWorkspaceAction,
+) -> Result<Result<(Workspace, Vec<Repo>), String>, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(workspace) = Workspace::find_by_id(pool, workspace_id).await? else {
+        return Ok(Err("Workspace not found".to_string()));
+    };
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace_id).await?;
+
+    if !matches!(action, BulkWorkspaceAction::Archive)
+        && ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+            .await?
+    {
+        return Ok(Err("Processes are running in this workspace".to_string()));
+    }
+
+    if let BulkWorkspaceAction::ChangeTargetBranch { new_target_branch } = action {
+        for repo in &repos {
+            if !deployment
+                .git()
+                .check_branch_exists(&repo.path, new_target_branch)?
+            {
+                return Ok(Err(format!(
+                    "Branch '{}' does not exist in repository '{}'",
+                    new_target_branch, repo.name
+                )));
+            }
+        }
+    }
+
+    Ok(Ok((workspace, repos)))
+}
+
+async fn apply(
+    deployment: &DeploymentImpl,
+    actor: &AuditActor,
+    workspace: Workspace,
+    repos: Vec<Repo>,
+    action: &BulkWorkspaceAction,
+) -> Result<(), String> {
+    match action {
+        BulkWorkspaceAction::Delete {
+            delete_branches,
+            delete_remote,
+        } => {
+            core::delete_workspace(
+                Extension(workspace),
+                State(deployment.clone()),
+                RequestActor(actor.clone()),
+                Query(DeleteWorkspaceQuery {
+                    delete_remote: *delete_remote,
+                    delete_branches: *delete_branches,
+                }),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        BulkWorkspaceAction::Archive => {
+            core::update_workspace(
+                Extension(workspace),
+                State(deployment.clone()),
+                Json(UpdateWorkspace {
+                    archived: Some(true),
+                    pinned: None,
+                    name: None,
+                }),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        BulkWorkspaceAction::Rebase => {
+            for repo in repos {
+                let ResponseJson(response) = git::rebase_workspace(
+                    Extension(workspace.clone()),
+                    State(deployment.clone()),
+                    Json(RebaseWorkspaceRequest {
+                        repo_id: repo.id,
+                        old_base_branch: None,
+                        new_base_branch: None,
+                    }),
+                )
+                .await
+                .map_err(|e| format!("{}: {e}", repo.name))?;
+                if !response.is_success() {
+                    let reason = match response.error_data() {
+                        Some(GitOperationError::MergeConflicts { message, .. }) => message.clone(),
+                        Some(GitOperationError::RebaseInProgress) => {
+                            "a rebase is already in progress".to_string()
+                        }
+                        None => response.message().unwrap_or("rebase failed").to_string(),
+                    };
+                    return Err(format!("{}: {reason}", repo.name));
+                }
+            }
+        }
+        BulkWorkspaceAction::ChangeTargetBranch { new_target_branch } => {
+            for repo in repos {
+                let ResponseJson(response) = git::change_target_branch(
+                    Extension(workspace.clone()),
+                    State(deployment.clone()),
+                    Json(ChangeTargetBranchRequest {
+                        repo_id: repo.id,
+                        new_target_branch: new_target_branch.clone(),
+                    }),
+                )
+                .await
+                .map_err(|e| format!("{}: {e}", repo.name))?;
+                if !response.is_success() {
+                    return Err(format!(
+                        "{}: {}",
+                        repo.name,
+                        response
+                            .message()
+                            .unwrap_or("changing the target branch failed")
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
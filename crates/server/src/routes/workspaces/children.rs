@@ -0,0 +1,329 @@
+//! Splitting a big piece of work across agent runs. Child workspaces branch
+//! off the parent's branch with the same repos, each gets the shared prompt
+//! plus its own fragment, and once done they are squash-merged back into the
+//! parent's branch one after another.
+
+use std::{collections::HashSet, path::Path};
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    execution_process::ExecutionProcess,
+    requests::{
+        CreateAndStartWorkspaceRequest, CreateAndStartWorkspaceResponse, WorkspaceRepoInput,
+    },
+    workspace::{Workspace, WorkspaceWithStatus},
+    workspace_child::{ChildWorkspaceProgress, WorkspaceChild},
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorConfig;
+use serde::{Deserialize, Serialize};
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::{
+    bulk::{BulkItemStatus, BulkWorkspaceItemResult, item_result},
+    create::create_and_start_workspace,
+    git::resolve_vibe_kanban_identifier,
+};
+use crate::{DeploymentImpl, error::ApiError};
+
+const MAX_CHILD_WORKSPACES: usize = 20;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ChildWorkspaceSpec {
+    /// Defaults to a name derived from the prompt.
+    #[serde(default)]
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// The part of the work this child does.
+    pub prompt: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SpawnChildWorkspacesRequest {
+    pub executor_config: ExecutorConfig,
+    /// Context every child gets before its own prompt, e.g. the overall goal.
+    #[serde(default)]
+    #[ts(optional)]
+    pub shared_prompt: Option<String>,
+    pub children: Vec<ChildWorkspaceSpec>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ChildWorkspace {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub link: WorkspaceChild,
+    pub workspace: WorkspaceWithStatus,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ChildWorkspacesResponse {
+    pub progress: ChildWorkspaceProgress,
+    pub children: Vec<ChildWorkspace>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct MergeChildWorkspacesRequest {
+    /// Children to merge. Defaults to every child not merged yet.
+    #[serde(default)]
+    #[ts(optional)]
+    pub child_workspace_ids: Option<Vec<Uuid>>,
+}
+
+/// Create and start a child workspace per spec. Each child's repos target the
+/// parent's branch, so they start from the parent's committed work.
+pub async fn spawn_child_workspaces(
+    Extension(parent): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SpawnChildWorkspacesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<CreateAndStartWorkspaceResponse>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload.children.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No child workspaces given".to_string(),
+        ));
+    }
+    if payload.children.len() > MAX_CHILD_WORKSPACES {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_CHILD_WORKSPACES} child workspaces can be created at once"
+        )));
+    }
+    if payload
+        .children
+        .iter()
+        .any(|child| child.prompt.trim().is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "Every child workspace needs a prompt".to_string(),
+        ));
+    }
+    if WorkspaceChild::find_by_child(pool, parent.id)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::BadRequest(
+            "Child workspaces cannot have children of their own".to_string(),
+        ));
+    }
+
+    // The parent's branch only exists once its worktrees have been created.
+    deployment
+        .container()
+        .ensure_container_exists(&parent)
+        .await?;
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, parent.id).await?;
+    if repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "The workspace has no repositories".to_string(),
+        ));
+    }
+
+    let shared_prompt = payload
+        .shared_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|prompt| !prompt.is_empty());
+    let mut created = Vec::with_capacity(payload.children.len());
+    for spec in payload.children {
+        let fragment = spec.prompt.trim().to_string();
+        let prompt = match shared_prompt {
+            Some(shared) => format!("{shared}\n\n{fragment}"),
+            None => fragment.clone(),
+        };
+        let ResponseJson(response) = create_and_start_workspace(
+            State(deployment.clone()),
+            Json(CreateAndStartWorkspaceRequest {
+                name: spec.name,
+                repos: repos
+                    .iter()
+                    .map(|repo| WorkspaceRepoInput {
+                        repo_id: repo.id,
+                        target_branch: parent.branch.clone(),
+                    })
+                    .collect(),
+                linked_issue: None,
+                executor_config: payload.executor_config.clone(),
+                prompt,
+                attachment_ids: None,
+                dev_server: None,
+                setup_script: None,
+                env_vars: None,
+            }),
+        )
+        .await?;
+        let Some(child) = response.into_data() else {
+            continue;
+        };
+        WorkspaceChild::create(pool, parent.id, child.workspace.id, &fragment).await?;
+        created.push(child);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "child_workspaces_spawned",
+            serde_json::json!({
+                "workspace_id": parent.id.to_string(),
+                "count": created.len(),
+                "executor": &payload.executor_config.executor,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(created)))
+}
+
+pub async fn get_child_workspaces(
+    Extension(parent): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ChildWorkspacesResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut children = Vec::new();
+    for link in WorkspaceChild::find_by_parent(pool, parent.id).await? {
+        if let Some(workspace) =
+            Workspace::find_by_id_with_status(pool, link.child_workspace_id).await?
+        {
+            children.push(ChildWorkspace { link, workspace });
+        }
+    }
+    let progress = WorkspaceChild::progress_for_parent(pool, parent.id)
+        .await?
+        .unwrap_or_default();
+
+    Ok(ResponseJson(ApiResponse::success(
+        ChildWorkspacesResponse { progress, children },
+    )))
+}
+
+/// Squash-merge children into the parent's branch in their original order,
+/// one commit per child and repo. Stops at the first child that fails, e.g.
+/// on conflicts, leaving the children merged before it in place and the
+/// rest skipped. Only committed work on a child's branch is merged.
+pub async fn merge_child_workspaces(
+    Extension(parent): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<MergeChildWorkspacesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<BulkWorkspaceItemResult>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let requested: Option<HashSet<Uuid>> = payload
+        .child_workspace_ids
+        .map(|ids| ids.into_iter().collect());
+    let links: Vec<WorkspaceChild> = WorkspaceChild::find_by_parent(pool, parent.id)
+        .await?
+        .into_iter()
+        .filter(|link| match &requested {
+            Some(ids) => ids.contains(&link.child_workspace_id),
+            None => link.merged_at.is_none(),
+        })
+        .collect();
+    if let Some(ids) = &requested
+        && ids.len() != links.len()
+    {
+        return Err(ApiError::BadRequest(
+            "Some workspaces are not children of this workspace".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, parent.id).await?
+    {
+        return Err(ApiError::Conflict(
+            "Processes are running in the parent workspace".to_string(),
+        ));
+    }
+
+    let parent_path = deployment
+        .container()
+        .ensure_container_exists(&parent)
+        .await?;
+    let mut results = Vec::with_capacity(links.len());
+    let mut failed = false;
+    for link in links {
+        let child_id = link.child_workspace_id;
+        if failed {
+            results.push(item_result(child_id, BulkItemStatus::Skipped, None));
+            continue;
+        }
+        match merge_child(&deployment, &parent, Path::new(&parent_path), child_id).await {
+            Ok(()) => results.push(item_result(child_id, BulkItemStatus::Succeeded, None)),
+            Err(error) => {
+                tracing::warn!(
+                    "Merging child workspace {} into {} failed: {}",
+                    child_id,
+                    parent.id,
+                    error
+                );
+                failed = true;
+                results.push(item_result(child_id, BulkItemStatus::Failed, Some(error)));
+            }
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "child_workspaces_merged",
+            serde_json::json!({
+                "workspace_id": parent.id.to_string(),
+                "count": results.len(),
+                "failed": failed,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
+async fn merge_child(
+    deployment: &DeploymentImpl,
+    parent: &Workspace,
+    parent_path: &Path,
+    child_id: Uuid,
+) -> Result<(), String> {
+    let pool = &deployment.db().pool;
+    let child = Workspace::find_by_id(pool, child_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Workspace not found".to_string())?;
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, child.id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Err("Processes are running in this workspace".to_string());
+    }
+
+    let parent_repos: HashSet<Uuid> = WorkspaceRepo::find_repos_for_workspace(pool, parent.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|repo| repo.id)
+        .collect();
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, child.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let child_label = child.name.as_deref().unwrap_or(&child.branch);
+    let vk_id = resolve_vibe_kanban_identifier(deployment, child.id).await;
+    let commit_message = format!("{child_label} (vibe-kanban {vk_id})");
+    for repo in repos.iter().filter(|repo| parent_repos.contains(&repo.id)) {
+        deployment
+            .git()
+            .merge_child_into_parent(
+                &parent_path.join(&repo.name),
+                &parent.branch,
+                &child.branch,
+                &commit_message,
+            )
+            .map_err(|e| format!("{}: {e}", repo.name))?;
+    }
+
+    WorkspaceChild::mark_merged(pool, child.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !child.pinned
+        && let Err(e) = deployment.container().archive_workspace(child.id).await
+    {
+        tracing::error!("Failed to archive workspace {}: {}", child.id, e);
+    }
+    Ok(())
+}
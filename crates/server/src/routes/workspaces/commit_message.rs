@@ -0,0 +1,80 @@
+use std::{path::Path, time::Duration};
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    repo::{Repo, RepoError},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::{one_shot::run_one_shot, profile::ExecutorConfig};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    commit_message::{build_commit_message_prompt, parse_commit_message},
+    container::ContainerService,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const GENERATE_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct GenerateCommitMessageRequest {
+    pub repo_id: Uuid,
+    /// Defaults to the configured executor profile.
+    pub executor_config: Option<ExecutorConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct GenerateCommitMessageResponse {
+    pub message: String,
+}
+
+/// Ask the executor for a conventional-commit message describing the repo's
+/// uncommitted changes. Nothing is committed.
+pub async fn generate_commit_message(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<GenerateCommitMessageRequest>,
+) -> Result<ResponseJson<ApiResponse<GenerateCommitMessageResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = Path::new(&container_ref).join(&repo.name);
+    let patch = deployment.git().get_uncommitted_patch(&worktree_path)?;
+    if patch.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "There are no uncommitted changes in this repository".to_string(),
+        ));
+    }
+
+    let executor_config = match payload.executor_config {
+        Some(executor_config) => executor_config,
+        None => {
+            let profile = deployment.config().read().await.executor_profile.clone();
+            ExecutorConfig {
+                variant: profile.variant,
+                ..ExecutorConfig::new(profile.executor)
+            }
+        }
+    };
+    let prompt = build_commit_message_prompt(&repo.name, &patch);
+    let reply = run_one_shot(&executor_config, &prompt, GENERATE_TIMEOUT).await?;
+    let message = parse_commit_message(&reply).map_err(ApiError::BadGateway)?;
+
+    Ok(ResponseJson(ApiResponse::success(
+        GenerateCommitMessageResponse { message },
+    )))
+}
@@ -5,18 +5,20 @@ use axum::{
     response::Json as ResponseJson,
 };
 use db::models::{
+    audit_log::{AuditAction, CreateAuditLogEntry},
     coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessStatus},
     workspace::{Workspace, WorkspaceError},
+    workspace_issue_link::WorkspaceIssueLink,
 };
 use deployment::Deployment;
 use serde::Deserialize;
-use services::services::{container::ContainerService, diff_stream, remote_sync};
+use services::services::{audit, container::ContainerService, diff_stream, remote_sync};
 use sqlx::Error as SqlxError;
 use utils::response::ApiResponse;
 use workspace_manager::WorkspaceManager;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{DeploymentImpl, error::ApiError, middleware::RequestActor};
 
 #[derive(Debug, Deserialize)]
 pub struct DeleteWorkspaceQuery {
@@ -96,9 +98,20 @@ pub async fn get_first_user_message(
     Ok(ResponseJson(ApiResponse::success(message)))
 }
 
+/// Issue tracker issues the workspace was imported from.
+pub async fn get_workspace_issue_links(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceIssueLink>>>, ApiError> {
+    let links =
+        WorkspaceIssueLink::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
+
 pub async fn delete_workspace(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
     Query(query): Query<DeleteWorkspaceQuery>,
 ) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
     let pool = &deployment.db().pool;
@@ -137,6 +150,7 @@ pub async fn delete_workspace(
             );
         }
     }
+    deployment.dev_servers().stop(workspace_id);
 
     let managed_workspace = workspace_manager.load_managed_workspace(workspace).await?;
     let deletion_context = managed_workspace.prepare_deletion_context().await?;
@@ -146,6 +160,21 @@ pub async fn delete_workspace(
         return Err(ApiError::Database(SqlxError::RowNotFound));
     }
 
+    audit::record(
+        pool,
+        CreateAuditLogEntry {
+            actor,
+            action: AuditAction::WorkspaceDeleted,
+            target_type: "workspace",
+            target_id: Some(workspace_id.to_string()),
+            details: serde_json::json!({
+                "delete_remote": query.delete_remote,
+                "delete_branches": query.delete_branches,
+            }),
+        },
+    )
+    .await;
+
     deployment
         .track_if_analytics_allowed(
             "workspace_deleted",
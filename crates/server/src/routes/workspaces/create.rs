@@ -1,29 +1,43 @@
 use std::collections::HashMap;
 
-use axum::{Json, extract::State, response::Json as ResponseJson};
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
 use db::models::{
+    repo::Repo,
     requests::{
         CreateAndStartWorkspaceRequest, CreateAndStartWorkspaceResponse, CreateWorkspaceApiRequest,
+        WorkspaceRepoInput,
     },
+    task_template::TaskTemplate,
     workspace::{CreateWorkspace, Workspace},
+    workspace_dev_server::{UpdateWorkspaceDevServer, WorkspaceDevServer},
+    workspace_env_var::WorkspaceEnvVars,
 };
 use deployment::Deployment;
+use executors::profile::ExecutorConfig;
+use serde::Deserialize;
 use services::services::container::ContainerService;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
-    routes::workspaces::attachments::{
-        ImportedIssueAttachment, import_issue_attachments_from_remote,
+    routes::workspaces::{
+        attachments::{ImportedIssueAttachment, import_issue_attachments_from_remote},
+        dev_server::validate_dev_server,
     },
 };
 
 pub(crate) async fn create_workspace_record(
     deployment: &DeploymentImpl,
     name: Option<String>,
+    dev_server: Option<&UpdateWorkspaceDevServer>,
 ) -> Result<Workspace, ApiError> {
+    if let Some(dev_server) = dev_server {
+        validate_dev_server(dev_server)?;
+    }
+
     let workspace_id = Uuid::new_v4();
     let branch_label = name
         .as_deref()
@@ -44,6 +58,10 @@ pub(crate) async fn create_workspace_record(
     )
     .await?;
 
+    if let Some(dev_server) = dev_server {
+        WorkspaceDevServer::set(&deployment.db().pool, workspace.id, dev_server).await?;
+    }
+
     Ok(workspace)
 }
 
@@ -51,7 +69,8 @@ pub async fn create_workspace(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateWorkspaceApiRequest>,
 ) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let workspace = create_workspace_record(&deployment, payload.name).await?;
+    let workspace =
+        create_workspace_record(&deployment, payload.name, payload.dev_server.as_ref()).await?;
 
     deployment
         .track_if_analytics_allowed(
@@ -65,6 +84,29 @@ pub async fn create_workspace(
     Ok(ResponseJson(ApiResponse::success(workspace)))
 }
 
+/// Names must be valid shell identifiers; `VK_` is reserved for the
+/// workspace context injected into every process.
+pub(crate) fn validate_env_vars(env_vars: &HashMap<String, String>) -> Result<(), ApiError> {
+    for name in env_vars.keys() {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid environment variable name '{name}'"
+            )));
+        }
+        if name.starts_with("VK_") {
+            return Err(ApiError::BadRequest(format!(
+                "Environment variable '{name}' uses the reserved VK_ prefix"
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn normalize_prompt(prompt: &str) -> Option<String> {
     let trimmed = prompt.trim();
     if trimmed.is_empty() {
@@ -220,6 +262,9 @@ pub async fn create_and_start_workspace(
         executor_config,
         prompt,
         attachment_ids,
+        dev_server,
+        setup_script,
+        env_vars,
     } = payload;
 
     let mut workspace_prompt = normalize_prompt(&prompt).ok_or_else(|| {
@@ -234,9 +279,15 @@ pub async fn create_and_start_workspace(
         ));
     }
 
+    if let Some(env_vars) = &env_vars {
+        validate_env_vars(env_vars)?;
+    }
+
     let mut managed_workspace = deployment
         .workspace_manager()
-        .load_managed_workspace(create_workspace_record(&deployment, name).await?)
+        .load_managed_workspace(
+            create_workspace_record(&deployment, name, dev_server.as_ref()).await?,
+        )
         .await?;
 
     for repo in &repos {
@@ -250,6 +301,15 @@ pub async fn create_and_start_workspace(
         managed_workspace.associate_attachments(ids).await?;
     }
 
+    if let Some(env_vars) = &env_vars {
+        WorkspaceEnvVars::set(
+            &deployment.db().pool,
+            managed_workspace.workspace.id,
+            env_vars,
+        )
+        .await?;
+    }
+
     if let Some(linked_issue) = &linked_issue
         && let Ok(client) = deployment.remote_client()
     {
@@ -297,7 +357,12 @@ pub async fn create_and_start_workspace(
 
     let execution_process = deployment
         .container()
-        .start_workspace(&workspace, executor_config.clone(), workspace_prompt)
+        .start_workspace(
+            &workspace,
+            executor_config.clone(),
+            workspace_prompt,
+            setup_script,
+        )
         .await?;
 
     deployment
@@ -319,6 +384,92 @@ pub async fn create_and_start_workspace(
     )))
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWorkspaceFromTemplateRequest {
+    /// Defaults to the template name.
+    #[serde(default)]
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// Replaces the template's repos.
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_ids: Option<Vec<Uuid>>,
+    /// Appended to the template prompt, e.g. the failing test's name.
+    #[serde(default)]
+    #[ts(optional)]
+    pub additional_prompt: Option<String>,
+}
+
+/// Create and start a workspace from a saved task template.
+pub async fn create_workspace_from_template(
+    Extension(template): Extension<TaskTemplate>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateWorkspaceFromTemplateRequest>,
+) -> Result<ResponseJson<ApiResponse<CreateAndStartWorkspaceResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let repo_ids = payload.repo_ids.unwrap_or(template.repo_ids.0);
+    let mut repos = Vec::with_capacity(repo_ids.len());
+    for repo_id in repo_ids {
+        let repo = Repo::find_by_id(pool, repo_id)
+            .await?
+            .ok_or_else(|| ApiError::BadRequest(format!("Repository {repo_id} not found")))?;
+        let target_branch = match template.base_branch.clone() {
+            Some(branch) => branch,
+            None => match repo.default_target_branch {
+                Some(branch) => branch,
+                None => deployment.git().get_current_branch(&repo.path)?,
+            },
+        };
+        repos.push(WorkspaceRepoInput {
+            repo_id,
+            target_branch,
+        });
+    }
+
+    let executor_config = match template.executor_config {
+        Some(executor_config) => executor_config.0,
+        None => {
+            let profile = deployment.config().read().await.executor_profile.clone();
+            ExecutorConfig {
+                variant: profile.variant,
+                ..ExecutorConfig::new(profile.executor)
+            }
+        }
+    };
+
+    let prompt = match normalize_prompt(payload.additional_prompt.as_deref().unwrap_or("")) {
+        Some(additional) => format!("{}\n\n{additional}", template.prompt),
+        None => template.prompt,
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_created_from_template",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+                "executor": &executor_config.executor,
+            }),
+        )
+        .await;
+
+    create_and_start_workspace(
+        State(deployment),
+        Json(CreateAndStartWorkspaceRequest {
+            name: payload.name.or(Some(template.name)),
+            repos,
+            linked_issue: None,
+            executor_config,
+            prompt,
+            attachment_ids: None,
+            dev_server: None,
+            setup_script: template.setup_script,
+            env_vars: Some(template.env_vars.0),
+        }),
+    )
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
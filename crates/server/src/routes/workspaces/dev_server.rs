@@ -0,0 +1,55 @@
+//! Per-workspace dev server override, e.g. a docs task that needs
+//! `npm run docs:dev` instead of the project's usual dev server.
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    workspace::Workspace,
+    workspace_dev_server::{UpdateWorkspaceDevServer, WorkspaceDevServer},
+};
+use deployment::Deployment;
+use services::services::dev_server::DevServerStatus;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub async fn get_workspace_dev_server(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<WorkspaceDevServer>>>, ApiError> {
+    let dev_server =
+        WorkspaceDevServer::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dev_server)))
+}
+
+/// Whether the workspace's dev server is up, and the port it was registered
+/// with the preview proxy on.
+pub async fn get_dev_server_status(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<DevServerStatus>> {
+    ResponseJson(ApiResponse::success(
+        deployment.dev_servers().status(workspace.id),
+    ))
+}
+
+/// Replace the override with `payload`; fields left out are cleared. Clearing
+/// the script, port and script injection falls back to the repos' dev server
+/// scripts. Takes effect the next time the dev server is started.
+pub async fn update_workspace_dev_server(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateWorkspaceDevServer>,
+) -> Result<ResponseJson<ApiResponse<Option<WorkspaceDevServer>>>, ApiError> {
+    validate_dev_server(&payload)?;
+    let dev_server = WorkspaceDevServer::set(&deployment.db().pool, workspace.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(dev_server)))
+}
+
+pub(crate) fn validate_dev_server(payload: &UpdateWorkspaceDevServer) -> Result<(), ApiError> {
+    if payload.port == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Dev server port must be between 1 and 65535".to_string(),
+        ));
+    }
+    Ok(())
+}
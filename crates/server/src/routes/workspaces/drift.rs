@@ -0,0 +1,98 @@
+use axum::{Json, extract::State, response::Json as ResponseJson};
+use db::models::workspace::Workspace;
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+use workspace_manager::WorkspaceDriftReport;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RepairWorkspaceDriftRequest {
+    /// Restrict repairs to these workspaces. All repairable workspaces when omitted.
+    #[serde(default)]
+    pub workspace_ids: Option<Vec<Uuid>>,
+    /// Also delete directories in the workspace base dir that no workspace
+    /// references. Ignored when `workspace_ids` is given, since orphans belong
+    /// to no workspace.
+    #[serde(default)]
+    pub remove_orphans: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceRepairFailure {
+    pub workspace_id: Uuid,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RepairWorkspaceDriftResponse {
+    pub repaired: Vec<Uuid>,
+    pub failed: Vec<WorkspaceRepairFailure>,
+    /// A fresh scan taken after the repairs ran.
+    pub report: WorkspaceDriftReport,
+}
+
+pub async fn get_workspace_drift(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceDriftReport>>, ApiError> {
+    let report = deployment.workspace_manager().scan_drift().await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Recreate missing worktrees from their workspace branch and optionally
+/// remove orphaned workspace directories.
+pub async fn repair_workspace_drift(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<RepairWorkspaceDriftRequest>,
+) -> Result<ResponseJson<ApiResponse<RepairWorkspaceDriftResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let report = deployment.workspace_manager().scan_drift().await?;
+
+    let mut repaired = Vec::new();
+    let mut failed = Vec::new();
+    for workspace_id in report.repairable_workspace_ids() {
+        if let Some(ids) = &request.workspace_ids
+            && !ids.contains(&workspace_id)
+        {
+            continue;
+        }
+        let Some(workspace) = Workspace::find_by_id(pool, workspace_id).await? else {
+            continue;
+        };
+        match deployment
+            .container()
+            .ensure_container_exists(&workspace)
+            .await
+        {
+            Ok(_) => repaired.push(workspace_id),
+            Err(e) => {
+                tracing::warn!("Failed to repair workspace {}: {}", workspace_id, e);
+                failed.push(WorkspaceRepairFailure {
+                    workspace_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    if request.remove_orphans {
+        let orphans = report.orphans_to_remove(request.workspace_ids.as_deref());
+        deployment
+            .workspace_manager()
+            .remove_orphans(&orphans)
+            .await;
+    }
+
+    let report = deployment.workspace_manager().scan_drift().await?;
+    Ok(ResponseJson(ApiResponse::success(
+        RepairWorkspaceDriftResponse {
+            repaired,
+            failed,
+            report,
+        },
+    )))
+}
@@ -1,22 +1,28 @@
-use axum::{Extension, Router, extract::State, response::Json as ResponseJson, routing::post};
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::post,
+};
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    repo::Repo,
     session::{CreateSession, Session},
     workspace::Workspace,
+    workspace_dev_server::WorkspaceDevServer,
     workspace_repo::WorkspaceRepo,
+    workspace_repo_command::{RepoCommandMode, WorkspaceRepoCommand},
 };
 use deployment::Deployment;
-use executors::actions::{
-    ExecutorAction, ExecutorActionType,
-    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
-};
 use serde::{Deserialize, Serialize};
 use services::services::container::ContainerService;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RateLimitedRoute, rate_limit},
+};
 
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -26,39 +32,97 @@ pub enum RunScriptError {
     ProcessAlreadyRunning,
 }
 
-pub fn router() -> Router<DeploymentImpl> {
+#[derive(Debug, Deserialize, TS)]
+pub struct RunRepoCommandRequest {
+    pub script: String,
+    pub mode: RepoCommandMode,
+    /// Repos to run in, in order. Defaults to all of the workspace's repos.
+    #[serde(default)]
+    #[ts(optional)]
+    pub repo_ids: Option<Vec<Uuid>>,
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     Router::new()
         .route("/dev-server/start", post(start_dev_server))
+        .route(
+            "/dev-server/stop",
+            post(stop_dev_server).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::ProcessControl),
+                rate_limit,
+            )),
+        )
+        .route("/dev-server/restart", post(restart_dev_server))
         .route("/cleanup", post(run_cleanup_script))
         .route("/archive", post(run_archive_script))
-        .route("/stop", post(stop_workspace_execution))
+        .route("/repo-command", post(run_repo_command))
+        .route(
+            "/stop",
+            post(stop_workspace_execution).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::ProcessControl),
+                rate_limit,
+            )),
+        )
 }
 
+/// Start the workspace's dev servers unless they are already running.
 #[axum::debug_handler]
 pub async fn start_dev_server(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
-    let pool = &deployment.db().pool;
+    let running = ExecutionProcess::find_running_dev_servers_by_workspace(
+        &deployment.db().pool,
+        workspace.id,
+    )
+    .await?;
+    if !running.is_empty() {
+        return Ok(ResponseJson(ApiResponse::success(running)));
+    }
+    launch_dev_servers(&deployment, &workspace).await
+}
 
-    let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id).await {
-            Ok(servers) => servers,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to find running dev servers for workspace {}: {}",
-                    workspace.id,
-                    e
-                );
-                return Err(ApiError::Workspace(
-                    db::models::workspace::WorkspaceError::ValidationError(e.to_string()),
-                ));
-            }
-        };
+pub async fn stop_dev_server(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    stop_dev_servers(&deployment, &workspace).await?;
 
-    for dev_server in existing_dev_servers {
+    deployment
+        .track_if_analytics_allowed(
+            "dev_server_stopped",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Stop any running dev servers and start them again, picking up changes to
+/// the configured scripts.
+#[axum::debug_handler]
+pub async fn restart_dev_server(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
+    stop_dev_servers(&deployment, &workspace).await?;
+    launch_dev_servers(&deployment, &workspace).await
+}
+
+async fn stop_dev_servers(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<(), ApiError> {
+    let running = ExecutionProcess::find_running_dev_servers_by_workspace(
+        &deployment.db().pool,
+        workspace.id,
+    )
+    .await?;
+    for dev_server in running {
         tracing::info!(
-            "Stopping existing dev server {} for workspace {}",
+            "Stopping dev server {} for workspace {}",
             dev_server.id,
             workspace.id
         );
@@ -71,14 +135,25 @@ pub async fn start_dev_server(
             tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
         }
     }
+    deployment.dev_servers().stop(workspace.id);
+    Ok(())
+}
 
+/// Start one dev server per configured repo, or the workspace's override, and
+/// hand them to the dev server monitor for health checks and port
+/// registration.
+async fn launch_dev_servers(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
+    let pool = &deployment.db().pool;
     let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
-    let repos_with_dev_script: Vec<_> = repos
-        .iter()
-        .filter(|r| r.dev_server_script.as_ref().is_some_and(|s| !s.is_empty()))
-        .collect();
+    let dev_server = WorkspaceDevServer::find_by_workspace_id(pool, workspace.id).await?;
+    let dev_server_actions = deployment
+        .container()
+        .dev_server_actions(&repos, dev_server.as_ref());
 
-    if repos_with_dev_script.is_empty() {
+    if dev_server_actions.is_empty() {
         return Ok(ResponseJson(ApiResponse::error(
             "No dev server script configured for any repository in this workspace",
         )));
@@ -101,29 +176,35 @@ pub async fn start_dev_server(
     };
 
     let mut execution_processes = Vec::new();
-    for repo in repos_with_dev_script {
-        let executor_action = ExecutorAction::new(
-            ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: repo.dev_server_script.clone().unwrap(),
-                language: ScriptRequestLanguage::Bash,
-                context: ScriptContext::DevServer,
-                working_dir: Some(repo.name.clone()),
-            }),
-            None,
-        );
-
+    let mut msg_stores = Vec::new();
+    for executor_action in dev_server_actions {
         let execution_process = deployment
             .container()
             .start_execution(
-                &workspace,
+                workspace,
                 &session,
                 &executor_action,
                 &ExecutionProcessRunReason::DevServer,
             )
             .await?;
+        if let Some(store) = deployment
+            .container()
+            .get_msg_store_by_id(&execution_process.id)
+            .await
+        {
+            msg_stores.push((execution_process.id, store));
+        }
         execution_processes.push(execution_process);
     }
 
+    if !msg_stores.is_empty() {
+        deployment.dev_servers().watch(
+            workspace.id,
+            msg_stores,
+            dev_server.and_then(|dev_server| dev_server.port),
+        );
+    }
+
     deployment
         .track_if_analytics_allowed(
             "dev_server_started",
@@ -286,3 +367,106 @@ pub async fn run_archive_script(
 
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
+
+/// Run a script in each of the workspace's repos. Progress is reported per
+/// repo in the workspace's `repo_command` field on the workspace stream.
+pub async fn run_repo_command(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RunRepoCommandRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>, RunScriptError>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let script = payload.script.trim();
+    if script.is_empty() {
+        return Err(ApiError::BadRequest("Script must not be empty".to_string()));
+    }
+
+    let workspace_repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+    let repos = match &payload.repo_ids {
+        Some(repo_ids) => {
+            let mut repos = Vec::with_capacity(repo_ids.len());
+            for repo_id in repo_ids {
+                let repo = workspace_repos
+                    .iter()
+                    .find(|repo| repo.id == *repo_id)
+                    .ok_or_else(|| {
+                        ApiError::BadRequest(format!(
+                            "Repository {repo_id} is not part of this workspace"
+                        ))
+                    })?;
+                if !repos.iter().any(|r: &Repo| r.id == repo.id) {
+                    repos.push(repo.clone());
+                }
+            }
+            repos
+        }
+        None => workspace_repos,
+    };
+    if repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No repositories to run the command in".to_string(),
+        ));
+    }
+
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            RunScriptError::ProcessAlreadyRunning,
+        )));
+    }
+
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    let session = match Session::find_latest_by_workspace_id(pool, workspace.id).await? {
+        Some(s) => s,
+        None => {
+            Session::create(
+                pool,
+                &CreateSession {
+                    executor: None,
+                    name: None,
+                },
+                Uuid::new_v4(),
+                workspace.id,
+            )
+            .await?
+        }
+    };
+
+    let repo_ids: Vec<Uuid> = repos.iter().map(|repo| repo.id).collect();
+    WorkspaceRepoCommand::start(pool, workspace.id, payload.mode, script, &repo_ids).await?;
+
+    let actions = deployment
+        .container()
+        .repo_command_actions(&repos, script, payload.mode);
+    let mut execution_processes = Vec::new();
+    for executor_action in actions {
+        let execution_process = deployment
+            .container()
+            .start_execution(
+                &workspace,
+                &session,
+                &executor_action,
+                &ExecutionProcessRunReason::RepoCommand,
+            )
+            .await?;
+        execution_processes.push(execution_process);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "repo_command_executed",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "mode": payload.mode,
+                "repo_count": repo_ids.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_processes)))
+}
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{DefaultBodyLimit, Path, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::workspace::Workspace;
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    worktree_files::{
+        self, MAX_EDITABLE_FILE_BYTES, WorktreeFile, WorktreeFileError, WorktreeFileVersion,
+    },
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::attachments::load_workspace_with_wildcard;
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RateLimitedRoute, rate_limit},
+};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct WriteWorkspaceFileRequest {
+    pub content: String,
+    /// `sha256` of the version the edit is based on; omit to create a new
+    /// file.
+    pub expected_sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum WriteWorkspaceFileError {
+    /// The file changed since it was read; `current` is what is on disk now.
+    Conflict { current: WorktreeFileVersion },
+}
+
+async fn workspace_dir(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<PathBuf, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(workspace)
+        .await?;
+    Ok(PathBuf::from(container_ref))
+}
+
+/// Read a text file, addressed relative to the workspace directory
+/// (`<repo name>/<path in repo>`).
+pub async fn read_workspace_file(
+    Path((_id, path)): Path<(Uuid, String)>,
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorktreeFile>>, ApiError> {
+    let root = workspace_dir(&deployment, &workspace).await?;
+    let file = worktree_files::read_file(&root, &path).await?;
+    Ok(ResponseJson(ApiResponse::success(file)))
+}
+
+/// Overwrite or create a text file. Refused with a `conflict` error if the
+/// file no longer matches `expected_sha256`.
+pub async fn write_workspace_file(
+    Path((_id, path)): Path<(Uuid, String)>,
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<WriteWorkspaceFileRequest>,
+) -> Result<ResponseJson<ApiResponse<WorktreeFile, WriteWorkspaceFileError>>, ApiError> {
+    let root = workspace_dir(&deployment, &workspace).await?;
+    match worktree_files::write_file(
+        &root,
+        &path,
+        &payload.content,
+        payload.expected_sha256.as_deref(),
+    )
+    .await
+    {
+        Ok(file) => Ok(ResponseJson(ApiResponse::success(file))),
+        Err(WorktreeFileError::Conflict(current)) => Ok(ResponseJson(
+            ApiResponse::error_with_data(WriteWorkspaceFileError::Conflict { current }),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    // JSON escaping can double the size of the content on the wire
+    let body_limit = 3 * MAX_EDITABLE_FILE_BYTES as usize;
+    Router::new()
+        .route(
+            "/{*path}",
+            get(read_workspace_file)
+                .put(write_workspace_file)
+                .layer(DefaultBodyLimit::max(body_limit))
+                .layer(from_fn_with_state(
+                    (deployment.clone(), RateLimitedRoute::FileWrites),
+                    rate_limit,
+                )),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_with_wildcard,
+        ))
+}
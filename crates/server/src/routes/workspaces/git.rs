@@ -10,21 +10,29 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    audit_log::{AuditAction, CreateAuditLogEntry},
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
     repo::{Repo, RepoError},
     workspace::Workspace,
     workspace_repo::WorkspaceRepo,
+    workspace_secret_finding::{CreateWorkspaceSecretFinding, WorkspaceSecretFinding},
 };
 use deployment::Deployment;
-use git::{ConflictOp, GitCliError, GitServiceError};
+use git::{ConflictOp, GitCliError, GitServiceError, vcs::vcs_for};
 use serde::{Deserialize, Serialize};
-use services::services::{container::ContainerService, diff_stream, remote_sync};
+use services::services::{
+    audit, container::ContainerService, diff_stream, remote_sync, secret_scan,
+};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use super::streams::{DiffStreamQuery, stream_workspace_diff_ws};
-use crate::{DeploymentImpl, error::ApiError, middleware::signed_ws::SignedWsUpgrade};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{RequestActor, signed_ws::SignedWsUpgrade},
+};
 
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct RebaseWorkspaceRequest {
@@ -64,6 +72,11 @@ pub struct MergeWorkspaceRequest {
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct PushWorkspaceRequest {
     pub repo_id: Uuid,
+    /// Push even though the secret scan found possible credentials,
+    /// confirming the findings.
+    #[serde(default)]
+    #[ts(optional)]
+    pub confirm_secrets: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -71,6 +84,9 @@ pub struct PushWorkspaceRequest {
 #[ts(tag = "type", rename_all = "snake_case")]
 pub enum PushError {
     ForcePushRequired,
+    SecretsDetected {
+        findings: Vec<WorkspaceSecretFinding>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -141,6 +157,7 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/merge", post(merge_workspace))
         .route("/push", post(push_workspace_branch))
         .route("/push/force", post(force_push_workspace_branch))
+        .route("/secret-findings", get(list_secret_findings))
         .route("/rebase", post(rebase_workspace))
         .route("/rebase/continue", post(continue_workspace_rebase))
         .route("/conflicts/abort", post(abort_workspace_conflicts))
@@ -148,7 +165,7 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/branch", axum::routing::put(rename_branch))
 }
 
-async fn resolve_vibe_kanban_identifier(
+pub(super) async fn resolve_vibe_kanban_identifier(
     deployment: &DeploymentImpl,
     local_workspace_id: Uuid,
 ) -> String {
@@ -165,6 +182,50 @@ async fn resolve_vibe_kanban_identifier(
     local_workspace_id.to_string()
 }
 
+/// Scan the changes the workspace branch adds to a repo on top of `base` for
+/// credentials and record the findings on the workspace. Returns those not
+/// yet confirmed; with `confirm`, confirms them instead.
+pub(super) async fn scan_for_secrets(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo_id: Uuid,
+    worktree_path: &Path,
+    base: &str,
+    confirm: bool,
+) -> Result<Vec<WorkspaceSecretFinding>, ApiError> {
+    let patch = deployment.git().get_branch_patch(worktree_path, base)?;
+    let findings: Vec<CreateWorkspaceSecretFinding> = secret_scan::scan_patch(&patch)
+        .into_iter()
+        .map(|found| CreateWorkspaceSecretFinding {
+            file_path: found.file_path,
+            line: found.line,
+            rule_id: found.rule_id,
+            redacted: found.redacted,
+        })
+        .collect();
+
+    let pool = &deployment.db().pool;
+    let recorded =
+        WorkspaceSecretFinding::replace_for_repo(pool, workspace.id, repo_id, &findings).await?;
+    if confirm {
+        WorkspaceSecretFinding::confirm_for_repo(pool, workspace.id, repo_id).await?;
+        return Ok(Vec::new());
+    }
+    Ok(recorded
+        .into_iter()
+        .filter(|finding| finding.confirmed_at.is_none())
+        .collect())
+}
+
+pub async fn list_secret_findings(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceSecretFinding>>>, ApiError> {
+    let findings =
+        WorkspaceSecretFinding::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(findings)))
+}
+
 #[axum::debug_handler]
 pub async fn stream_diff_ws(
     ws: SignedWsUpgrade,
@@ -288,10 +349,22 @@ pub async fn push_workspace_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    match deployment
-        .git()
-        .push_to_remote(&worktree_path, &workspace.branch, false)
-    {
+    let findings = scan_for_secrets(
+        &deployment,
+        &workspace,
+        repo.id,
+        &worktree_path,
+        &workspace_repo.target_branch,
+        request.confirm_secrets.unwrap_or(false),
+    )
+    .await?;
+    if !findings.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            PushError::SecretsDetected { findings },
+        )));
+    }
+
+    match vcs_for(&worktree_path).push(&worktree_path, &workspace.branch, false) {
         Ok(_) => {
             if let Ok(client) = deployment.remote_client() {
                 let pool = deployment.db().pool.clone();
@@ -322,6 +395,7 @@ pub async fn push_workspace_branch(
 pub async fn force_push_workspace_branch(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
+    RequestActor(actor): RequestActor,
     Json(request): Json<PushWorkspaceRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
@@ -342,9 +416,38 @@ pub async fn force_push_workspace_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    deployment
-        .git()
-        .push_to_remote(&worktree_path, &workspace.branch, true)?;
+    let findings = scan_for_secrets(
+        &deployment,
+        &workspace,
+        repo.id,
+        &worktree_path,
+        &workspace_repo.target_branch,
+        request.confirm_secrets.unwrap_or(false),
+    )
+    .await?;
+    if !findings.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            PushError::SecretsDetected { findings },
+        )));
+    }
+
+    vcs_for(&worktree_path).push(&worktree_path, &workspace.branch, true)?;
+
+    audit::record(
+        pool,
+        CreateAuditLogEntry {
+            actor,
+            action: AuditAction::BranchForcePushed,
+            target_type: "workspace",
+            target_id: Some(workspace.id.to_string()),
+            details: serde_json::json!({
+                "repo_id": repo.id,
+                "repo_name": &repo.name,
+                "branch": &workspace.branch,
+            }),
+        },
+    )
+    .await;
 
     if let Ok(client) = deployment.remote_client() {
         let pool = deployment.db().pool.clone();
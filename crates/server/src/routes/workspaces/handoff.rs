@@ -0,0 +1,465 @@
+//! Hand a workspace off to another vibe-kanban instance owned by the same
+//! user. The source pushes its branches and publishes the transcript and any
+//! queued follow-up through the remote backend, then becomes read-only. The
+//! receiving instance claims the handoff and recreates the workspace from the
+//! pushed branches.
+
+use std::path::Path;
+
+use api_types::{
+    CreateWorkspaceHandoffRequest, ListWorkspaceHandoffsResponse, WorkspaceHandoff,
+    WorkspaceHandoffPayload, WorkspaceHandoffQueuedMessage, WorkspaceHandoffRepo,
+    WorkspaceHandoffTurn,
+};
+use axum::{
+    Extension,
+    extract::{Path as AxumPath, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::ExecutionProcess,
+    repo::Repo,
+    scratch::{CreateScratch, DraftFollowUpData, Scratch, ScratchPayload, WorkspaceNotesData},
+    session::Session,
+    workspace::{CreateWorkspace, Workspace, WorkspaceError},
+    workspace_handoff::{WorkspaceHandoffDirection, WorkspaceHandoffLink},
+    workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+};
+use deployment::Deployment;
+use executors::profile::ExecutorConfig;
+use git::{GitCli, vcs::vcs_for};
+use services::services::{container::ContainerService, remote_client::RemoteClientError};
+use sqlx::SqlitePool;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Handoff state of a workspace, if any.
+pub async fn get_workspace_handoff(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<WorkspaceHandoffLink>>>, ApiError> {
+    let link = WorkspaceHandoffLink::find_by_workspace(
+        &deployment.db().pool,
+        workspace.id,
+        WorkspaceHandoffDirection::Outgoing,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(link)))
+}
+
+/// Push the workspace's branches and publish it for another instance.
+pub async fn export_workspace_handoff(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceHandoff>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let client = deployment.remote_client()?;
+
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Cannot hand off a workspace while processes are running. Stop all processes first."
+                .to_string(),
+        ));
+    }
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+
+    let mut repos = Vec::with_capacity(workspace_repos.len());
+    for workspace_repo in workspace_repos {
+        let worktree_path = Path::new(&container_ref).join(&workspace_repo.repo.name);
+        vcs_for(&worktree_path).push(&worktree_path, &workspace.branch, false)?;
+
+        let git = deployment.git();
+        repos.push(WorkspaceHandoffRepo {
+            repo_name: workspace_repo.repo.name.clone(),
+            remote_url: git
+                .resolve_remote_for_branch(&worktree_path, &workspace.branch)
+                .ok()
+                .map(|remote| remote.url),
+            branch: workspace.branch.clone(),
+            target_branch: workspace_repo.target_branch,
+            head_commit: git.get_head_info(&worktree_path).ok().map(|head| head.oid),
+        });
+    }
+
+    let transcript = CodingAgentTurn::find_by_workspace_id(pool, workspace.id)
+        .await?
+        .into_iter()
+        .map(|turn| WorkspaceHandoffTurn {
+            prompt: turn.prompt,
+            summary: turn.summary,
+            created_at: turn.created_at,
+        })
+        .collect();
+
+    // Queued follow-ups only run after the current execution on this
+    // instance, so they are taken out of the queue and travel with the
+    // handoff instead.
+    let queue = deployment.queued_message_service();
+    let mut queued_messages = Vec::new();
+    for session in Session::find_by_workspace_id(pool, workspace.id).await? {
+        for queued in queue.list_queued(session.id) {
+            queued_messages.push(WorkspaceHandoffQueuedMessage {
+                message: queued.data.message,
+                executor_config: serde_json::to_value(&queued.data.executor_config)
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    let handoff = client
+        .create_workspace_handoff(&CreateWorkspaceHandoffRequest {
+            source_local_workspace_id: workspace.id,
+            payload: WorkspaceHandoffPayload {
+                workspace_name: workspace.name.clone(),
+                repos,
+                transcript,
+                queued_messages,
+            },
+        })
+        .await?;
+
+    WorkspaceHandoffLink::create(
+        pool,
+        workspace.id,
+        handoff.id,
+        WorkspaceHandoffDirection::Outgoing,
+    )
+    .await?;
+    for session in Session::find_by_workspace_id(pool, workspace.id).await? {
+        queue.cancel_queued(session.id);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_handoff_exported",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "repo_count": handoff.payload.repos.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(handoff)))
+}
+
+/// Withdraw an unclaimed handoff and make the workspace editable again.
+pub async fn cancel_workspace_handoff(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let link = WorkspaceHandoffLink::find_by_workspace(
+        pool,
+        workspace.id,
+        WorkspaceHandoffDirection::Outgoing,
+    )
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("Workspace has not been handed off".to_string()))?;
+
+    match deployment
+        .remote_client()?
+        .cancel_workspace_handoff(link.remote_handoff_id)
+        .await
+    {
+        Ok(()) => {}
+        Err(RemoteClientError::Http { status: 409, .. }) => {
+            return Err(ApiError::Conflict(
+                "The handoff has already been claimed by another instance".to_string(),
+            ));
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    WorkspaceHandoffLink::delete(pool, link.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Handoffs published by other instances that have not been claimed yet.
+pub async fn list_workspace_handoffs(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ListWorkspaceHandoffsResponse>>, ApiError> {
+    let mut response = deployment
+        .remote_client()?
+        .list_workspace_handoffs()
+        .await?;
+
+    // Hide handoffs this instance published itself.
+    let pool = &deployment.db().pool;
+    let mut handoffs = Vec::with_capacity(response.handoffs.len());
+    for handoff in response.handoffs {
+        if Workspace::find_by_id(pool, handoff.source_local_workspace_id)
+            .await?
+            .is_none()
+        {
+            handoffs.push(handoff);
+        }
+    }
+    response.handoffs = handoffs;
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
+/// Claim a handoff and recreate its workspace on this instance.
+pub async fn import_workspace_handoff(
+    State(deployment): State<DeploymentImpl>,
+    AxumPath(handoff_id): AxumPath<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let client = deployment.remote_client()?;
+
+    let pending = client
+        .list_workspace_handoffs()
+        .await?
+        .handoffs
+        .into_iter()
+        .find(|handoff| handoff.id == handoff_id)
+        .ok_or_else(|| ApiError::Conflict("Handoff not found or already claimed".to_string()))?;
+
+    // Match every handed-off repo before claiming, so a handoff this
+    // instance can't open stays available to others.
+    let local_repos = Repo::list_all(pool).await?;
+    let mut matched = Vec::with_capacity(pending.payload.repos.len());
+    for handoff_repo in &pending.payload.repos {
+        let repo = find_local_repo(&deployment, &local_repos, handoff_repo).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "No local repository matches '{}'. Add it to this instance first.",
+                handoff_repo.repo_name
+            ))
+        })?;
+        matched.push((repo, handoff_repo));
+    }
+
+    let branch = pending
+        .payload
+        .repos
+        .first()
+        .map(|repo| repo.branch.clone())
+        .ok_or_else(|| ApiError::BadRequest("Handoff has no repositories".to_string()))?;
+
+    for (repo, handoff_repo) in &matched {
+        let remote_url = match &handoff_repo.remote_url {
+            Some(url) => url.clone(),
+            None => deployment.git().get_default_remote(&repo.path)?.url,
+        };
+        let refspec = format!("+refs/heads/{0}:refs/heads/{0}", handoff_repo.branch);
+        GitCli::new()
+            .fetch_with_refspec(&repo.path, &remote_url, &refspec)
+            .map_err(git::GitServiceError::from)?;
+    }
+
+    // Claim last, once the workspace exists locally, so a failed import
+    // leaves the handoff available to retry.
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch,
+            name: pending.payload.workspace_name.clone(),
+        },
+        Uuid::new_v4(),
+    )
+    .await?;
+    let workspace_repos: Vec<CreateWorkspaceRepo> = matched
+        .iter()
+        .map(|(repo, handoff_repo)| CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch: handoff_repo.target_branch.clone(),
+        })
+        .collect();
+    if let Err(e) = WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await {
+        Workspace::delete(pool, workspace.id).await?;
+        return Err(e.into());
+    }
+    let handoff = claim_or_discard(
+        pool,
+        workspace.id,
+        client.claim_workspace_handoff(handoff_id, workspace.id),
+    )
+    .await?;
+
+    WorkspaceHandoffLink::create(
+        pool,
+        workspace.id,
+        handoff.id,
+        WorkspaceHandoffDirection::Incoming,
+    )
+    .await?;
+
+    if let Err(e) = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await
+    {
+        tracing::error!("Failed to create worktrees for handed-off workspace: {e}");
+    }
+
+    if !handoff.payload.transcript.is_empty() {
+        Scratch::create(
+            pool,
+            workspace.id,
+            &CreateScratch {
+                payload: ScratchPayload::WorkspaceNotes(WorkspaceNotesData {
+                    content: render_transcript(&handoff.payload.transcript),
+                }),
+            },
+        )
+        .await?;
+    }
+
+    // The workspace has no session yet, so queued follow-ups become the
+    // draft for its first one.
+    if let Some(executor_config) = handoff.payload.queued_messages.last().and_then(|queued| {
+        serde_json::from_value::<ExecutorConfig>(queued.executor_config.clone()).ok()
+    }) {
+        let message = handoff
+            .payload
+            .queued_messages
+            .iter()
+            .map(|queued| queued.message.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        Scratch::create(
+            pool,
+            workspace.id,
+            &CreateScratch {
+                payload: ScratchPayload::DraftFollowUp(DraftFollowUpData {
+                    message,
+                    executor_config,
+                }),
+            },
+        )
+        .await?;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_handoff_imported",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "repo_count": matched.len(),
+            }),
+        )
+        .await;
+
+    let workspace = Workspace::find_by_id(pool, workspace.id)
+        .await?
+        .ok_or(WorkspaceError::WorkspaceNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Finish an import by claiming the handoff for the newly created workspace,
+/// deleting the workspace again if the claim fails.
+async fn claim_or_discard(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+    claim: impl Future<Output = Result<WorkspaceHandoff, RemoteClientError>>,
+) -> Result<WorkspaceHandoff, ApiError> {
+    let error = match claim.await {
+        Ok(handoff) => return Ok(handoff),
+        Err(RemoteClientError::Http { status: 409, .. }) => {
+            ApiError::Conflict("Handoff not found or already claimed".to_string())
+        }
+        Err(e) => e.into(),
+    };
+    Workspace::delete(pool, workspace_id).await?;
+    Err(error)
+}
+
+/// Find the local repo a handed-off repo refers to, by remote URL and then by
+/// name.
+fn find_local_repo(
+    deployment: &DeploymentImpl,
+    local_repos: &[Repo],
+    handoff_repo: &WorkspaceHandoffRepo,
+) -> Option<Repo> {
+    let normalize = |url: &str| {
+        url.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_string()
+    };
+    if let Some(remote_url) = &handoff_repo.remote_url {
+        let wanted = normalize(remote_url);
+        let by_remote = local_repos.iter().find(|repo| {
+            deployment
+                .git()
+                .list_remotes(&repo.path)
+                .is_ok_and(|remotes| {
+                    remotes
+                        .iter()
+                        .any(|remote| normalize(&remote.url) == wanted)
+                })
+        });
+        if let Some(repo) = by_remote {
+            return Some(repo.clone());
+        }
+    }
+    local_repos
+        .iter()
+        .find(|repo| repo.name == handoff_repo.repo_name)
+        .cloned()
+}
+
+fn render_transcript(transcript: &[WorkspaceHandoffTurn]) -> String {
+    let mut notes = String::from("# Handed-off transcript\n");
+    for turn in transcript {
+        notes.push_str(&format!(
+            "\n## {}\n",
+            turn.created_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+        if let Some(prompt) = &turn.prompt {
+            notes.push_str(&format!("\n**Prompt**\n\n{prompt}\n"));
+        }
+        if let Some(summary) = &turn.summary {
+            notes.push_str(&format!("\n**Summary**\n\n{summary}\n"));
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use db::test_utils::TestDb;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_claim_discards_the_imported_workspace() {
+        let db = TestDb::new().await;
+        let workspace = Workspace::create(
+            &db.pool,
+            &CreateWorkspace {
+                branch: "vk/handed-off".to_string(),
+                name: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+        let result = claim_or_discard(&db.pool, workspace.id, async {
+            Err(RemoteClientError::Http {
+                status: 409,
+                body: String::new(),
+            })
+        })
+        .await;
+
+        assert!(matches!(result, Err(ApiError::Conflict(_))));
+        assert!(
+            Workspace::find_by_id(&db.pool, workspace.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
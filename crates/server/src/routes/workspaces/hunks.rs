@@ -0,0 +1,252 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    hunk_review::{CreateHunkReviewDecision, HunkReviewAction, HunkReviewDecision},
+    repo::{Repo, RepoError},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use git::GitService;
+use serde::{Deserialize, Serialize};
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::{
+    diff::{DiffChangeKind, DiffHunk, diff_hunks, hunk_patch, revert_hunks},
+    response::ApiResponse,
+};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct FileHunksQuery {
+    pub repo_id: Uuid,
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct HunkDecision {
+    pub hunk_id: String,
+    pub action: HunkReviewAction,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ReviewHunksRequest {
+    pub repo_id: Uuid,
+    pub path: String,
+    pub decisions: Vec<HunkDecision>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ReviewHunksResponse {
+    pub decisions: Vec<HunkReviewDecision>,
+    /// Hunks left in the file after discarded hunks were reverted.
+    pub remaining_hunks: Vec<DiffHunk>,
+}
+
+/// A file's base and worktree contents, as the diff API sees them.
+struct FileChange {
+    worktree_path: PathBuf,
+    worktree_file: PathBuf,
+    old_content: String,
+    new_content: String,
+    is_added: bool,
+}
+
+async fn load_file_change(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo_id: Uuid,
+    path: &str,
+) -> Result<FileChange, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_repo = WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(workspace)
+        .await?;
+    let worktree_path = Path::new(&container_ref).join(&repo.name);
+
+    let base_commit = deployment.git().get_base_commit(
+        &repo.path,
+        &workspace.branch,
+        &workspace_repo.target_branch,
+    )?;
+    let diff = deployment
+        .git()
+        .get_diffs(&worktree_path, &base_commit, Some(&[path]))?
+        .into_iter()
+        .find(|diff| GitService::diff_path(diff) == path)
+        .ok_or_else(|| ApiError::BadRequest(format!("No changes in '{}'", path)))?;
+
+    if diff.content_omitted {
+        return Err(ApiError::BadRequest(format!(
+            "'{}' is too large or binary to review by hunk",
+            path
+        )));
+    }
+
+    Ok(FileChange {
+        worktree_file: worktree_path.join(path),
+        worktree_path,
+        old_content: diff.old_content.unwrap_or_default(),
+        new_content: diff.new_content.unwrap_or_default(),
+        is_added: matches!(diff.change, DiffChangeKind::Added),
+    })
+}
+
+pub async fn get_file_hunks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<FileHunksQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<DiffHunk>>>, ApiError> {
+    let change = load_file_change(&deployment, &workspace, query.repo_id, &query.path).await?;
+    Ok(ResponseJson(ApiResponse::success(diff_hunks(
+        &change.old_content,
+        &change.new_content,
+    ))))
+}
+
+/// Keep or discard individual hunks of a file. Kept hunks are staged in the
+/// git index and discarded hunks are reverted in the worktree; every decision
+/// is recorded against the latest coding agent run so reviews can be audited
+/// later.
+pub async fn review_hunks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<ReviewHunksRequest>,
+) -> Result<ResponseJson<ApiResponse<ReviewHunksResponse>>, ApiError> {
+    if request.decisions.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one hunk decision is required".to_string(),
+        ));
+    }
+
+    let change = load_file_change(&deployment, &workspace, request.repo_id, &request.path).await?;
+    let hunks = diff_hunks(&change.old_content, &change.new_content);
+
+    // Ids come from an earlier diff; reject the whole request if the file has
+    // moved on since, rather than applying a partial review.
+    let unknown: Vec<&str> = request
+        .decisions
+        .iter()
+        .map(|d| d.hunk_id.as_str())
+        .filter(|id| !hunks.iter().any(|h| h.id == *id))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Unknown hunks {}; the file has changed, reload the diff",
+            unknown.join(", ")
+        )));
+    }
+
+    let discard: HashSet<String> = request
+        .decisions
+        .iter()
+        .filter(|d| d.action == HunkReviewAction::Discard)
+        .map(|d| d.hunk_id.clone())
+        .collect();
+    let mut content = change.new_content.clone();
+    if !discard.is_empty() {
+        content = revert_hunks(&change.old_content, &change.new_content, &discard);
+        if change.is_added && content.is_empty() {
+            tokio::fs::remove_file(&change.worktree_file).await?;
+        } else {
+            tokio::fs::write(&change.worktree_file, &content).await?;
+        }
+    }
+
+    // Hunks are positioned against the base, so discarding others doesn't
+    // move them.
+    for hunk in hunks.iter().filter(|hunk| {
+        request
+            .decisions
+            .iter()
+            .any(|d| d.hunk_id == hunk.id && d.action == HunkReviewAction::Keep)
+    }) {
+        deployment.git().stage_patch(
+            &change.worktree_path,
+            &hunk_patch(&request.path, hunk, change.is_added),
+        )?;
+    }
+
+    let pool = &deployment.db().pool;
+    let execution_process_id = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        pool,
+        workspace.id,
+        &ExecutionProcessRunReason::CodingAgent,
+    )
+    .await?
+    .map(|process| process.id);
+
+    let mut decisions = Vec::with_capacity(request.decisions.len());
+    for decision in &request.decisions {
+        let Some(hunk) = hunks.iter().find(|h| h.id == decision.hunk_id) else {
+            continue;
+        };
+        decisions.push(
+            HunkReviewDecision::create(
+                pool,
+                &CreateHunkReviewDecision {
+                    workspace_id: workspace.id,
+                    execution_process_id,
+                    repo_id: request.repo_id,
+                    file_path: request.path.clone(),
+                    hunk_id: hunk.id.clone(),
+                    action: decision.action,
+                    patch: hunk.patch.clone(),
+                },
+            )
+            .await?,
+        );
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "hunks_reviewed",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "kept": decisions.len() - discard.len(),
+                "discarded": discard.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(ReviewHunksResponse {
+        decisions,
+        remaining_hunks: diff_hunks(&change.old_content, &content),
+    })))
+}
+
+pub async fn list_hunk_decisions(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<HunkReviewDecision>>>, ApiError> {
+    let decisions =
+        HunkReviewDecision::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(decisions)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/", get(get_file_hunks))
+        .route("/review", post(review_hunks))
+        .route("/decisions", get(list_hunk_decisions))
+}
@@ -6,14 +6,14 @@ use axum::{
     response::Json as ResponseJson,
     routing::{get, post},
 };
-use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
+use db::models::{repo::Repo, workspace::Workspace, workspace_repo::WorkspaceRepo};
 use deployment::Deployment;
 use executors::{
     executors::{CodingAgent, ExecutorError},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use serde::{Deserialize, Serialize};
-use services::services::container::ContainerService;
+use services::services::{config::EditorPosition, container::ContainerService};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 
@@ -31,7 +31,21 @@ pub struct RunAgentSetupResponse {}
 #[derive(Deserialize, TS)]
 pub struct OpenEditorRequest {
     editor_type: Option<String>,
+    /// Relative to the workspace or one of its repos, or absolute inside
+    /// the worktree or the original repo checkout, as agents report it in
+    /// logs. A `:line[:column]` suffix is honoured.
     file_path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+#[derive(Deserialize, TS)]
+pub struct OpenComponentSourceRequest {
+    /// Source file as reported by the component's stack frame.
+    file_name: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    editor_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -53,6 +67,7 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/editor/path", get(get_workspace_editor_path))
         .route("/editor/open", post(open_workspace_in_editor))
+        .route("/editor/open-component", post(open_component_source))
         .route("/agent/setup", post(run_agent_setup))
         .route("/github/cli/setup", post(gh_cli_setup_handler))
 }
@@ -94,16 +109,83 @@ pub async fn open_workspace_in_editor(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<OpenEditorRequest>,
 ) -> Result<ResponseJson<ApiResponse<OpenEditorResponse>>, ApiError> {
-    let path = resolve_workspace_editor_path(&deployment, &workspace, payload.file_path.as_deref())
+    let (file_path, suffix_position) = match payload.file_path.as_deref() {
+        Some(file_path) => {
+            let (file_path, position) = split_position(file_path);
+            (Some(file_path), position)
+        }
+        None => (None, None),
+    };
+    let position = match payload.line {
+        Some(line) => Some(EditorPosition {
+            line,
+            column: payload.column,
+        }),
+        None => suffix_position,
+    };
+    let path = resolve_workspace_editor_path(&deployment, &workspace, file_path).await?;
+    open_path_in_editor(
+        &deployment,
+        &workspace,
+        &path,
+        position,
+        payload.editor_type.as_deref(),
+        "task_attempt_editor_opened",
+    )
+    .await
+}
+
+/// Open the source file of a component picked with click-to-component in
+/// the preview. Bundlers report sources in many shapes (`webpack://app/./src/..`,
+/// absolute paths from a container, paths relative to the dev server), so
+/// the file is looked up in the workspace's worktrees.
+pub async fn open_component_source(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<OpenComponentSourceRequest>,
+) -> Result<ResponseJson<ApiResponse<OpenEditorResponse>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
         .await?;
+    let repos =
+        WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id).await?;
+    let path = resolve_component_source(Path::new(&container_ref), &repos, &payload.file_name)
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Could not find {} in the workspace",
+                payload.file_name
+            ))
+        })?;
+    let position = payload.line.map(|line| EditorPosition {
+        line,
+        column: payload.column,
+    });
+    open_path_in_editor(
+        &deployment,
+        &workspace,
+        &path,
+        position,
+        payload.editor_type.as_deref(),
+        "preview_component_source_opened",
+    )
+    .await
+}
 
+async fn open_path_in_editor(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    path: &Path,
+    position: Option<EditorPosition>,
+    editor_type: Option<&str>,
+    analytics_event: &str,
+) -> Result<ResponseJson<ApiResponse<OpenEditorResponse>>, ApiError> {
     let editor_config = {
         let config = deployment.config().read().await;
-        let editor_type_str = payload.editor_type.as_deref();
-        config.editor.with_override(editor_type_str)
+        config.editor.with_override(editor_type)
     };
 
-    match editor_config.open_file(path.as_path()).await {
+    match editor_config.open_file_at(path, position).await {
         Ok(url) => {
             tracing::info!(
                 "Opened editor for workspace {} at path: {}{}",
@@ -114,10 +196,10 @@ pub async fn open_workspace_in_editor(
 
             deployment
                 .track_if_analytics_allowed(
-                    "task_attempt_editor_opened",
+                    analytics_event,
                     serde_json::json!({
                         "workspace_id": workspace.id.to_string(),
-                        "editor_type": payload.editor_type.as_ref(),
+                        "editor_type": editor_type,
                         "remote_mode": url.is_some(),
                     }),
                 )
@@ -171,13 +253,146 @@ async fn resolve_workspace_editor_path(
         workspace_path.to_path_buf()
     };
 
-    Ok(if let Some(file_path) = file_path {
-        workspace_path.join(file_path)
-    } else {
-        workspace_path
+    match file_path {
+        Some(file_path) => translate_file_path(&workspace_path, &workspace_repos, file_path),
+        None => Ok(workspace_path),
+    }
+}
+
+/// Split a trailing `:line` or `:line:column` off a path from a log entry.
+fn split_position(file_path: &str) -> (&str, Option<EditorPosition>) {
+    fn split_number(s: &str) -> Option<(&str, u32)> {
+        let (rest, n) = s.rsplit_once(':')?;
+        let n = n.parse().ok().filter(|n| *n > 0)?;
+        (!rest.is_empty()).then_some((rest, n))
+    }
+    let Some((rest, last)) = split_number(file_path) else {
+        return (file_path, None);
+    };
+    match split_number(rest) {
+        Some((path, line)) => (
+            path,
+            Some(EditorPosition {
+                line,
+                column: Some(last),
+            }),
+        ),
+        None => (
+            rest,
+            Some(EditorPosition {
+                line: last,
+                column: None,
+            }),
+        ),
+    }
+}
+
+/// Bundler URL prefixes seen in React stack frames.
+const SOURCE_PREFIXES: &[&str] = &[
+    "webpack-internal:///",
+    "webpack://",
+    "turbopack://",
+    "rsc://React/Server/",
+    "file://",
+    "[project]/",
+    "/app-pages-browser/",
+];
+
+/// Find the worktree file a component source path refers to. After
+/// stripping bundler prefixes, leading path components are dropped one at a
+/// time until the rest exists below a repo's dev server directory, the
+/// repo or the workspace.
+fn resolve_component_source(
+    workspace_path: &Path,
+    repos: &[Repo],
+    file_name: &str,
+) -> Option<PathBuf> {
+    let mut source = file_name.split(['?', '#']).next().unwrap_or_default();
+    while let Some(rest) = SOURCE_PREFIXES
+        .iter()
+        .find_map(|prefix| source.strip_prefix(prefix))
+    {
+        source = rest;
+    }
+
+    if Path::new(source).is_absolute()
+        && let Ok(path) = translate_file_path(workspace_path, repos, source)
+        && path.is_file()
+    {
+        return Some(path);
+    }
+
+    let components: Vec<_> = Path::new(source)
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    let mut roots = Vec::new();
+    for repo in repos {
+        let worktree = workspace_path.join(&repo.name);
+        if let Some(dir) = repo
+            .default_working_dir
+            .as_deref()
+            .filter(|d| !d.is_empty())
+        {
+            roots.push(worktree.join(dir));
+        }
+        roots.push(worktree);
+    }
+    roots.push(workspace_path.to_path_buf());
+
+    (0..components.len()).find_map(|skip| {
+        let rel: PathBuf = components[skip..].iter().collect();
+        roots
+            .iter()
+            .map(|root| root.join(&rel))
+            .find(|candidate| candidate.is_file())
     })
 }
 
+/// Map a file path onto the worktrees under `workspace_path`. Absolute paths
+/// into a repo's original checkout are redirected to its worktree; relative
+/// paths are tried against the workspace and then each repo.
+fn translate_file_path(
+    workspace_path: &Path,
+    repos: &[Repo],
+    file_path: &str,
+) -> Result<PathBuf, ApiError> {
+    let path = Path::new(file_path);
+    if path.is_absolute() {
+        if path.starts_with(workspace_path) {
+            return Ok(path.to_path_buf());
+        }
+        return repos
+            .iter()
+            .find_map(|repo| {
+                let rest = path.strip_prefix(&repo.path).ok()?;
+                Some(workspace_path.join(&repo.name).join(rest))
+            })
+            .ok_or_else(|| ApiError::BadRequest(format!("{file_path} is outside the workspace")));
+    }
+    if path
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "{file_path} is outside the workspace"
+        )));
+    }
+
+    let direct = workspace_path.join(path);
+    if direct.exists() {
+        return Ok(direct);
+    }
+    Ok(repos
+        .iter()
+        .map(|repo| workspace_path.join(&repo.name).join(path))
+        .find(|candidate| candidate.exists())
+        .unwrap_or(direct))
+}
+
 #[axum::debug_handler]
 pub async fn gh_cli_setup_handler(
     Extension(workspace): Extension<Workspace>,
@@ -215,3 +430,104 @@ pub async fn gh_cli_setup_handler(
         Err(err) => Err(err),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_line_and_column_suffixes() {
+        assert_eq!(split_position("src/main.rs"), ("src/main.rs", None));
+        assert_eq!(
+            split_position("src/main.rs:42"),
+            (
+                "src/main.rs",
+                Some(EditorPosition {
+                    line: 42,
+                    column: None
+                })
+            )
+        );
+        assert_eq!(
+            split_position("/abs/lib.rs:3:9"),
+            (
+                "/abs/lib.rs",
+                Some(EditorPosition {
+                    line: 3,
+                    column: Some(9)
+                })
+            )
+        );
+        assert_eq!(split_position("notes:todo"), ("notes:todo", None));
+    }
+
+    fn test_repo(default_working_dir: Option<&str>) -> Repo {
+        Repo {
+            id: uuid::Uuid::new_v4(),
+            path: PathBuf::from("/src/repo"),
+            name: "repo".to_string(),
+            display_name: "repo".to_string(),
+            setup_script: None,
+            cleanup_script: None,
+            archive_script: None,
+            copy_files: None,
+            parallel_setup_script: false,
+            dev_server_script: None,
+            default_target_branch: None,
+            default_working_dir: default_working_dir.map(str::to_string),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn relative_paths_fall_back_to_repo_dirs_and_cannot_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("repo/src")).unwrap();
+        std::fs::write(dir.path().join("repo/src/lib.rs"), "").unwrap();
+        let repo = test_repo(None);
+
+        assert_eq!(
+            translate_file_path(dir.path(), std::slice::from_ref(&repo), "src/lib.rs").unwrap(),
+            dir.path().join("repo/src/lib.rs")
+        );
+        assert_eq!(
+            translate_file_path(
+                dir.path(),
+                std::slice::from_ref(&repo),
+                "/src/repo/src/lib.rs"
+            )
+            .unwrap(),
+            dir.path().join("repo/src/lib.rs")
+        );
+        assert!(translate_file_path(dir.path(), &[repo], "../etc/passwd").is_err());
+        assert!(translate_file_path(dir.path(), &[], "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn component_sources_resolve_from_bundler_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("repo/web/src/App.tsx");
+        std::fs::create_dir_all(app.parent().unwrap()).unwrap();
+        std::fs::write(&app, "").unwrap();
+        let repos = [test_repo(Some("web"))];
+
+        for source in [
+            "webpack://_N_E/./src/App.tsx?abc",
+            "/app-pages-browser/./src/App.tsx",
+            "/home/node/app/web/src/App.tsx",
+            "/src/repo/web/src/App.tsx",
+            "src/App.tsx",
+        ] {
+            assert_eq!(
+                resolve_component_source(dir.path(), &repos, source).as_deref(),
+                Some(app.as_path()),
+                "{source}"
+            );
+        }
+        assert_eq!(
+            resolve_component_source(dir.path(), &repos, "src/Missing.tsx"),
+            None
+        );
+    }
+}
@@ -1,16 +1,28 @@
+pub mod activity;
 pub mod attachments;
+pub mod bulk;
+pub mod children;
 pub mod codex_setup;
+pub mod commit_message;
 pub mod core;
 pub mod create;
 pub mod cursor_setup;
+pub mod dev_server;
+pub mod drift;
 pub mod execution;
+pub mod files;
 pub mod gh_cli_setup;
 pub mod git;
+pub mod handoff;
+pub mod hunks;
 pub mod integration;
 pub mod links;
 pub mod pr;
 pub mod repos;
+pub mod search;
+pub mod squash;
 pub mod streams;
+pub mod tree;
 pub mod workspace_summary;
 
 use axum::{
@@ -19,7 +31,13 @@ use axum::{
     routing::{get, post},
 };
 
-use crate::{DeploymentImpl, middleware::load_workspace_middleware};
+use crate::{
+    DeploymentImpl,
+    middleware::{
+        RateLimitedRoute, load_task_template_middleware, load_workspace_middleware, rate_limit,
+    },
+    routes::docs,
+};
 
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
@@ -30,9 +48,40 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .delete(core::delete_workspace),
         )
         .route("/messages/first", get(core::get_first_user_message))
+        .route("/issue-links", get(core::get_workspace_issue_links))
+        .route("/activity", get(activity::get_workspace_activity))
         .route("/seen", axum::routing::put(core::mark_seen))
+        .route(
+            "/handoff",
+            get(handoff::get_workspace_handoff)
+                .post(handoff::export_workspace_handoff)
+                .delete(handoff::cancel_workspace_handoff),
+        )
+        .route(
+            "/dev-server",
+            get(dev_server::get_workspace_dev_server).put(dev_server::update_workspace_dev_server),
+        )
+        .route("/dev-server/status", get(dev_server::get_dev_server_status))
+        .route("/search", get(search::search_workspace))
+        .route("/docs/search", get(docs::search_workspace_docs))
+        .route("/tree", get(tree::get_workspace_tree))
+        .route(
+            "/open-in-editor",
+            post(integration::open_workspace_in_editor),
+        )
+        .route(
+            "/children",
+            get(children::get_child_workspaces).post(children::spawn_child_workspaces),
+        )
+        .route("/children/merge", post(children::merge_child_workspaces))
+        .route(
+            "/generate-commit-message",
+            post(commit_message::generate_commit_message),
+        )
+        .route("/squash", post(squash::squash_workspace))
         .nest("/git", git::router())
-        .nest("/execution", execution::router())
+        .nest("/hunks", hunks::router())
+        .nest("/execution", execution::router(deployment))
         .nest("/integration", integration::router())
         .nest("/repos", repos::router())
         .nest("/pull-requests", pr::router())
@@ -44,17 +93,55 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspaces_router = Router::new()
         .route(
             "/",
-            get(core::get_workspaces).post(create::create_workspace),
+            get(core::get_workspaces)
+                .post(create::create_workspace)
+                .layer(from_fn_with_state(
+                    (deployment.clone(), RateLimitedRoute::TaskCreation),
+                    rate_limit,
+                )),
+        )
+        .route(
+            "/start",
+            post(create::create_and_start_workspace).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::TaskCreation),
+                rate_limit,
+            )),
+        )
+        .route("/bulk", post(bulk::bulk_workspace_action))
+        .route(
+            "/from-pr",
+            post(pr::create_workspace_from_pr).layer(from_fn_with_state(
+                (deployment.clone(), RateLimitedRoute::TaskCreation),
+                rate_limit,
+            )),
+        )
+        .route(
+            "/from-template/{template_id}",
+            post(create::create_workspace_from_template)
+                .layer(from_fn_with_state(
+                    deployment.clone(),
+                    load_task_template_middleware,
+                ))
+                .layer(from_fn_with_state(
+                    (deployment.clone(), RateLimitedRoute::TaskCreation),
+                    rate_limit,
+                )),
         )
-        .route("/start", post(create::create_and_start_workspace))
-        .route("/from-pr", post(pr::create_workspace_from_pr))
         .route("/streams/ws", get(streams::stream_workspaces_ws))
+        .route("/drift", get(drift::get_workspace_drift))
+        .route("/drift/repair", post(drift::repair_workspace_drift))
+        .route("/handoffs", get(handoff::list_workspace_handoffs))
+        .route(
+            "/handoffs/{handoff_id}/import",
+            post(handoff::import_workspace_handoff),
+        )
         .route(
             "/summaries",
             post(workspace_summary::get_workspace_summaries),
         )
         .nest("/{id}", workspace_id_router)
         .nest("/{id}/attachments", attachments::router(deployment))
+        .nest("/{id}/files", files::router(deployment))
         .nest("/{id}/links", links::router(deployment));
 
     Router::new().nest("/workspaces", workspaces_router)
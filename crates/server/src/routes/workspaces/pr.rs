@@ -16,6 +16,7 @@ use db::models::{
     session::{CreateSession, Session},
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    workspace_secret_finding::WorkspaceSecretFinding,
 };
 use deployment::Deployment;
 use executors::actions::{
@@ -47,18 +48,32 @@ pub struct CreatePrApiRequest {
     pub repo_id: Uuid,
     #[serde(default)]
     pub auto_generate_description: bool,
+    /// Push even though the secret scan found possible credentials,
+    /// confirming the findings.
+    #[serde(default)]
+    #[ts(optional)]
+    pub confirm_secrets: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(tag = "type", rename_all = "snake_case")]
 pub enum PrError {
-    CliNotInstalled { provider: ProviderKind },
-    CliNotLoggedIn { provider: ProviderKind },
+    CliNotInstalled {
+        provider: ProviderKind,
+    },
+    CliNotLoggedIn {
+        provider: ProviderKind,
+    },
     GitCliNotLoggedIn,
     GitCliNotInstalled,
-    TargetBranchNotFound { branch: String },
+    TargetBranchNotFound {
+        branch: String,
+    },
     UnsupportedProvider,
+    SecretsDetected {
+        findings: Vec<WorkspaceSecretFinding>,
+    },
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -253,6 +268,21 @@ pub async fn create_pr(
         Ok(true) => {}
     }
 
+    let findings = super::git::scan_for_secrets(
+        &deployment,
+        &workspace,
+        repo.id,
+        &worktree_path,
+        &target_branch,
+        request.confirm_secrets.unwrap_or(false),
+    )
+    .await?;
+    if !findings.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            PrError::SecretsDetected { findings },
+        )));
+    }
+
     if let Err(e) = git.push_to_remote(&worktree_path, &workspace.branch, false) {
         tracing::error!("Failed to push branch to remote: {}", e);
         match e {
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use axum::{
+    BoxError, Extension,
+    extract::{Query, State},
+    response::{
+        Sse,
+        sse::{Event, KeepAlive},
+    },
+};
+use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
+use deployment::Deployment;
+use futures_util::{Stream, StreamExt};
+use services::services::{
+    container::ContainerService,
+    content_search::{self, ContentSearchEvent, ContentSearchQuery, SearchRoot},
+};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Search file contents across the workspace's worktrees. Streams `match`
+/// events as they are found, then a single `done` event.
+pub async fn search_workspace(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ContentSearchQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    if query.q.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Query parameter 'q' is required and cannot be empty".to_string(),
+        ));
+    }
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_dir = PathBuf::from(&container_ref);
+    let roots = WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id)
+        .await?
+        .into_iter()
+        .map(|repo| SearchRoot {
+            path: workspace_dir.join(&repo.name),
+            repo_name: repo.name,
+        })
+        .collect();
+
+    let results =
+        content_search::search(roots, &query).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let events = results.map(|event| {
+        let name = match &event {
+            ContentSearchEvent::Match(_) => "match",
+            ContentSearchEvent::Done { .. } => "done",
+        };
+        Event::default()
+            .event(name)
+            .json_data(&event)
+            .map_err(BoxError::from)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    repo::{Repo, RepoError},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use git::SquashPlan;
+use serde::{Deserialize, Serialize};
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct SquashWorkspaceRequest {
+    pub repo_id: Uuid,
+    /// Message of the squashed commit. Defaults to the oldest commit's title
+    /// followed by every commit's summary.
+    pub message: Option<String>,
+    /// Return the resulting commit without changing the branch.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Squash the workspace branch's commits since it forked from the target
+/// branch into a single commit.
+pub async fn squash_workspace(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SquashWorkspaceRequest>,
+) -> Result<ResponseJson<ApiResponse<SquashPlan>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = Path::new(&container_ref).join(&repo.name);
+
+    let mut plan = deployment.git().plan_squash(
+        &worktree_path,
+        &workspace_repo.target_branch,
+        &workspace.branch,
+        payload.message.as_deref(),
+    )?;
+    if payload.dry_run {
+        return Ok(ResponseJson(ApiResponse::success(plan)));
+    }
+    if plan.commits.len() < 2 {
+        return Err(ApiError::BadRequest(
+            "The branch has fewer than two commits to squash".to_string(),
+        ));
+    }
+
+    let squashed = deployment
+        .git()
+        .apply_squash(&worktree_path, &workspace.branch, &plan)?;
+    plan.squashed_commit = Some(squashed);
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
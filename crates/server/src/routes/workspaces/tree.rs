@@ -0,0 +1,93 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    filesystem::{DirectoryTreePage, FilesystemError, TreeGitStatus, TreePageRequest},
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const MAX_DEPTH: usize = 8;
+const DEFAULT_LIMIT: usize = 500;
+const MAX_LIMIT: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceTreeQuery {
+    /// Directory relative to the workspace directory; the workspace itself
+    /// when empty.
+    #[serde(default)]
+    pub path: String,
+    pub depth: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// List the workspace directory tree, skipping git-ignored files, with each
+/// entry's size, modification time and git status.
+pub async fn get_workspace_tree(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorkspaceTreeQuery>,
+) -> Result<ResponseJson<ApiResponse<DirectoryTreePage>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = PathBuf::from(container_ref);
+    let repos =
+        WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id).await?;
+
+    let git = deployment.git().clone();
+    let filesystem = deployment.filesystem().clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut changes = HashMap::new();
+        for repo in repos {
+            let worktree_path = root.join(&repo.name);
+            if !worktree_path.exists() {
+                continue;
+            }
+            match git.get_worktree_status(&worktree_path) {
+                Ok(status) => {
+                    for entry in &status.entries {
+                        let path = String::from_utf8_lossy(&entry.path);
+                        changes.insert(
+                            format!("{}/{}", repo.name, path.trim_end_matches('/')),
+                            TreeGitStatus::from(entry),
+                        );
+                    }
+                }
+                Err(e) => tracing::debug!("No git status for {}: {}", repo.name, e),
+            }
+        }
+        let request = TreePageRequest {
+            path: query.path.trim_matches('/'),
+            depth: query.depth.unwrap_or(1).clamp(1, MAX_DEPTH),
+            offset: query.offset,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        };
+        filesystem.list_tree(&root, &request, &changes)
+    })
+    .await
+    .map_err(|e| ApiError::Io(std::io::Error::other(e)))?;
+
+    match result {
+        Ok(page) => Ok(ResponseJson(ApiResponse::success(page))),
+        Err(FilesystemError::DirectoryDoesNotExist) => {
+            Err(ApiError::BadRequest("Directory does not exist".to_string()))
+        }
+        Err(e @ (FilesystemError::PathIsNotDirectory | FilesystemError::InvalidPath(_))) => {
+            Err(ApiError::BadRequest(e.to_string()))
+        }
+        Err(FilesystemError::Io(e)) => Err(ApiError::Io(e)),
+    }
+}
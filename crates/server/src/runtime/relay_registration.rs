@@ -1,11 +1,15 @@
 //! Relay host connection — registers the local backend with the relay server
 //! so it can receive tunneled connections from remote browsers.
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::Context as _;
 use deployment::Deployment as _;
-use relay_tunnel_core::client::{RelayClientConfig, start_relay_client};
+use relay_control::RelayControl;
+use relay_tunnel_core::client::{
+    ConnectionObserver, RelayClientConfig, RelayTransport, TransportNegotiator, start_relay_client,
+};
+use relay_types::RelayTunnelTransport;
 use services::services::{config::Config, remote_client::RemoteClient};
 
 use crate::DeploymentImpl;
@@ -34,6 +38,28 @@ struct RelayParams {
     relay_base: String,
     machine_id: String,
     host_nickname: String,
+    relay_control: Arc<RelayControl>,
+}
+
+/// Feeds control channel health events into the relay quality tracker.
+struct QualityObserver(Arc<RelayControl>);
+
+impl ConnectionObserver for QualityObserver {
+    fn connected(&self, transport: RelayTransport) {
+        let transport = match transport {
+            RelayTransport::WebSocket => RelayTunnelTransport::WebSocket,
+            RelayTransport::LongPoll => RelayTunnelTransport::LongPoll,
+        };
+        self.0.quality().connected(transport);
+    }
+
+    fn probe_completed(&self, rtt: Option<Duration>) {
+        self.0.quality().probe_completed(rtt);
+    }
+
+    fn keepalive_interval_changed(&self, interval: Duration) {
+        self.0.quality().keepalive_interval_changed(interval);
+    }
 }
 
 /// Resolve all preconditions for starting the relay. Returns `None` if any
@@ -74,6 +100,7 @@ async fn resolve_relay_params(deployment: &DeploymentImpl) -> Option<RelayParams
         relay_base,
         machine_id: deployment.user_id().to_string(),
         host_nickname,
+        relay_control: deployment.relay_control().clone(),
     })
 }
 
@@ -91,10 +118,15 @@ pub async fn spawn_relay(deployment: &DeploymentImpl) {
 
         let mut delay = std::time::Duration::from_secs(RELAY_RECONNECT_INITIAL_DELAY_SECS);
         let max_delay = std::time::Duration::from_secs(RELAY_RECONNECT_MAX_DELAY_SECS);
+        let mut negotiator = TransportNegotiator::default();
 
         while !cancel_token.is_cancelled()
-            && let Err(error) = start_relay(&params, cancel_token.clone()).await
+            && let Err(error) = start_relay(&params, cancel_token.clone(), &mut negotiator).await
         {
+            params
+                .relay_control
+                .quality()
+                .disconnected(Some(format!("{error:#}")));
             tracing::debug!(
                 ?error,
                 retry_in_secs = delay.as_secs(),
@@ -109,6 +141,7 @@ pub async fn spawn_relay(deployment: &DeploymentImpl) {
             delay = std::cmp::min(delay.saturating_mul(2), max_delay);
         }
 
+        params.relay_control.quality().disconnected(None);
         tracing::debug!("Relay reconnect loop exited");
     });
 }
@@ -123,6 +156,7 @@ pub async fn stop_relay(deployment: &DeploymentImpl) {
 async fn start_relay(
     params: &RelayParams,
     shutdown: tokio_util::sync::CancellationToken,
+    negotiator: &mut TransportNegotiator,
 ) -> anyhow::Result<()> {
     let base_url = params.relay_base.trim_end_matches('/');
 
@@ -139,6 +173,7 @@ async fn start_relay(
     } else {
         anyhow::bail!("Unexpected base URL scheme: {base_url}");
     };
+    let long_poll_url = format!("{base_url}/v1/relay/poll?{encoded_name}");
 
     let access_token = params
         .remote_client
@@ -148,11 +183,16 @@ async fn start_relay(
 
     tracing::debug!(%ws_url, "Connecting relay control channel");
 
-    start_relay_client(RelayClientConfig {
-        ws_url,
-        bearer_token: access_token,
-        local_addr: params.server_addr,
-        shutdown,
-    })
+    start_relay_client(
+        RelayClientConfig {
+            ws_url,
+            long_poll_url,
+            bearer_token: access_token,
+            local_addr: params.server_addr,
+            shutdown,
+            observer: Arc::new(QualityObserver(params.relay_control.clone())),
+        },
+        negotiator,
+    )
     .await
 }
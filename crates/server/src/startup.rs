@@ -2,6 +2,7 @@ use std::{
     collections::HashSet,
     fs, io,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use deployment::{Deployment, DeploymentError};
@@ -182,13 +183,22 @@ pub async fn initialize_deployment(
     Ok(deployment)
 }
 
-/// Gracefully shut down running execution processes.
+/// Gracefully shut down running execution processes, giving coding agents
+/// the configured grace period to finish.
 pub async fn perform_cleanup_actions(deployment: &DeploymentImpl) {
+    let grace_period = Duration::from_secs(
+        deployment
+            .config()
+            .read()
+            .await
+            .shutdown_grace_period_secs
+            .into(),
+    );
     deployment
         .container()
-        .kill_all_running_processes()
+        .drain_running_processes(grace_period)
         .await
-        .expect("Failed to cleanly kill running execution processes");
+        .expect("Failed to cleanly stop running execution processes");
 }
 
 const LEGACY_ATTACHMENT_MIGRATION_MARKER: &str = ".attachment-directories-migrated-v1";
@@ -1,11 +1,21 @@
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{VecDeque, hash_map::DefaultHasher},
     hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
+use chrono::{DateTime, Utc};
 use os_info;
+use serde::Serialize;
 use serde_json::{Value, json};
+use tokio::sync::RwLock;
+use ts_rs::TS;
+
+use crate::services::config::Config;
+
+/// How many recent events are kept for `/api/analytics/recent`.
+const RECENT_EVENTS_CAPACITY: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct AnalyticsContext {
@@ -35,23 +45,73 @@ impl AnalyticsConfig {
     }
 }
 
+/// The consent category an event falls under. See `AnalyticsConsent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsCategory {
+    Usage,
+    Errors,
+    Performance,
+}
+
+/// An event as it was handed to the analytics backend, or as it would have
+/// been had the user consented to its category.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RecordedAnalyticsEvent {
+    pub category: AnalyticsCategory,
+    pub event: String,
+    pub sent: bool,
+    pub recorded_at: DateTime<Utc>,
+    /// The exact request body.
+    #[ts(type = "JsonValue")]
+    pub payload: Value,
+}
+
 #[derive(Clone, Debug)]
 pub struct AnalyticsService {
     config: AnalyticsConfig,
     client: reqwest::Client,
+    user_config: Arc<RwLock<Config>>,
+    recent: Arc<Mutex<VecDeque<RecordedAnalyticsEvent>>>,
 }
 
 impl AnalyticsService {
-    pub fn new(config: AnalyticsConfig) -> Self {
+    /// Events are only sent for the categories `user_config` consents to.
+    pub fn new(config: AnalyticsConfig, user_config: Arc<RwLock<Config>>) -> Self {
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
             .unwrap();
 
-        Self { config, client }
+        Self {
+            config,
+            client,
+            user_config,
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(RECENT_EVENTS_CAPACITY))),
+        }
+    }
+
+    /// Newest first, including events that were held back for lack of
+    /// consent.
+    pub fn recent_events(&self) -> Vec<RecordedAnalyticsEvent> {
+        self.recent.lock().unwrap().iter().rev().cloned().collect()
     }
 
-    pub fn track_event(&self, user_id: &str, event_name: &str, properties: Option<Value>) {
+    fn record(&self, event: RecordedAnalyticsEvent) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_EVENTS_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(event);
+    }
+
+    pub fn track_event(
+        &self,
+        category: AnalyticsCategory,
+        user_id: &str,
+        event_name: &str,
+        properties: Option<Value>,
+    ) {
         let endpoint = format!(
             "{}/capture/",
             self.config.posthog_api_endpoint.trim_end_matches('/')
@@ -82,11 +142,29 @@ impl AnalyticsService {
             payload["properties"] = event_properties;
         }
 
-        let client = self.client.clone();
+        let service = self.clone();
         let event_name = event_name.to_string();
 
         tokio::spawn(async move {
-            match client
+            let consent = service.user_config.read().await.analytics;
+            let sent = match category {
+                AnalyticsCategory::Usage => consent.usage,
+                AnalyticsCategory::Errors => consent.errors,
+                AnalyticsCategory::Performance => consent.performance,
+            };
+            service.record(RecordedAnalyticsEvent {
+                category,
+                event: event_name.clone(),
+                sent,
+                recorded_at: Utc::now(),
+                payload: payload.clone(),
+            });
+            if !sent {
+                return;
+            }
+
+            match service
+                .client
                 .post(&endpoint)
                 .header("Content-Type", "application/json")
                 .json(&payload)
@@ -194,6 +272,34 @@ mod tests {
         assert_eq!(id.len(), 25);
     }
 
+    #[test]
+    fn recent_events_are_capped_and_newest_first() {
+        let service = AnalyticsService::new(
+            AnalyticsConfig {
+                posthog_api_key: "key".to_string(),
+                posthog_api_endpoint: "http://localhost".to_string(),
+            },
+            Arc::new(RwLock::new(Config::default())),
+        );
+        for i in 0..RECENT_EVENTS_CAPACITY + 5 {
+            service.record(RecordedAnalyticsEvent {
+                category: AnalyticsCategory::Usage,
+                event: format!("event_{i}"),
+                sent: false,
+                recorded_at: Utc::now(),
+                payload: json!({}),
+            });
+        }
+
+        let recent = service.recent_events();
+        assert_eq!(recent.len(), RECENT_EVENTS_CAPACITY);
+        assert_eq!(
+            recent[0].event,
+            format!("event_{}", RECENT_EVENTS_CAPACITY + 4)
+        );
+        assert_eq!(recent.last().unwrap().event, "event_5");
+    }
+
     #[test]
     fn test_consistency() {
         let id1 = generate_user_id();
@@ -1,4 +1,5 @@
 pub mod executor_approvals;
+pub mod protected_paths;
 
 use std::{collections::HashSet, sync::Arc, time::Duration as StdDuration};
 
@@ -14,14 +15,20 @@ use thiserror::Error;
 use tokio::sync::{broadcast, oneshot};
 use tokio_stream::wrappers::BroadcastStream;
 use ts_rs::TS;
-use utils::approvals::{ApprovalOutcome, ApprovalRequest, ApprovalResponse};
+use utils::approvals::{
+    ApprovalOutcome, ApprovalQuestion, ApprovalRequest, ApprovalResponse, ApprovalToolInput,
+    QuestionAnswerError, validate_answers,
+};
 use uuid::Uuid;
 
 #[derive(Debug)]
 struct PendingApproval {
     execution_process_id: Uuid,
     tool_name: String,
+    tool_input: Option<ApprovalToolInput>,
     is_question: bool,
+    questions: Vec<ApprovalQuestion>,
+    delegated_to: Option<Uuid>,
     created_at: DateTime<Utc>,
     timeout_at: DateTime<Utc>,
     response_tx: oneshot::Sender<ApprovalOutcome>,
@@ -40,8 +47,14 @@ pub struct ToolContext {
 pub struct ApprovalInfo {
     pub approval_id: String,
     pub tool_name: String,
+    /// What the tool call will do, truncated. Not set for questions.
+    pub tool_input: Option<ApprovalToolInput>,
     pub execution_process_id: Uuid,
     pub is_question: bool,
+    /// What was asked, for questions. Answers must fit these.
+    pub questions: Vec<ApprovalQuestion>,
+    /// Trusted relay client the approval was handed to, if any.
+    pub delegated_to: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub timeout_at: DateTime<Utc>,
 }
@@ -63,6 +76,8 @@ pub enum ApprovalError {
     NoExecutorSession(String),
     #[error("invalid approval status for this tool type")]
     InvalidStatus,
+    #[error("invalid answer: {0}")]
+    InvalidAnswer(#[from] QuestionAnswerError),
     #[error(transparent)]
     Custom(#[from] anyhow::Error),
 }
@@ -99,8 +114,11 @@ impl Approvals {
         let info = ApprovalInfo {
             approval_id: req_id.clone(),
             tool_name: request.tool_name.clone(),
+            tool_input: request.tool_input.clone(),
             execution_process_id: request.execution_process_id,
             is_question,
+            questions: request.questions.clone(),
+            delegated_to: None,
             created_at: request.created_at,
             timeout_at: request.timeout_at,
         };
@@ -108,7 +126,10 @@ impl Approvals {
         let pending_approval = PendingApproval {
             execution_process_id: request.execution_process_id,
             tool_name: request.tool_name.clone(),
+            tool_input: request.tool_input.clone(),
             is_question,
+            questions: request.questions.clone(),
+            delegated_to: None,
             created_at: request.created_at,
             timeout_at: request.timeout_at,
             response_tx: tx,
@@ -126,16 +147,26 @@ impl Approvals {
         Ok((request, waiter))
     }
 
+    /// Check the response fits the request. Answers are returned in question
+    /// order with defaults filled in.
     fn validate_approval_response(
-        outcome: &ApprovalOutcome,
-        is_question: bool,
-    ) -> Result<(), ApprovalError> {
+        outcome: ApprovalOutcome,
+        pending: &PendingApproval,
+    ) -> Result<ApprovalOutcome, ApprovalError> {
         match outcome {
-            ApprovalOutcome::Approved | ApprovalOutcome::Denied { .. } if is_question => {
+            ApprovalOutcome::Approved | ApprovalOutcome::Denied { .. } if pending.is_question => {
+                Err(ApprovalError::InvalidStatus)
+            }
+            ApprovalOutcome::Answered { .. } if !pending.is_question => {
                 Err(ApprovalError::InvalidStatus)
             }
-            ApprovalOutcome::Answered { .. } if !is_question => Err(ApprovalError::InvalidStatus),
-            _ => Ok(()),
+            // Executors that don't describe their questions get answers as sent.
+            ApprovalOutcome::Answered { answers } if !pending.questions.is_empty() => {
+                Ok(ApprovalOutcome::Answered {
+                    answers: validate_answers(&pending.questions, &answers)?,
+                })
+            }
+            outcome => Ok(outcome),
         }
     }
 
@@ -146,12 +177,14 @@ impl Approvals {
         req: ApprovalResponse,
     ) -> Result<(ApprovalOutcome, ToolContext), ApprovalError> {
         if let Some((_, p)) = self.pending.remove(id) {
-            if let Err(e) = Self::validate_approval_response(&req.status, p.is_question) {
-                self.pending.insert(id.to_string(), p);
-                return Err(e);
-            }
+            let outcome = match Self::validate_approval_response(req.status, &p) {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    self.pending.insert(id.to_string(), p);
+                    return Err(e);
+                }
+            };
 
-            let outcome = req.status.clone();
             self.completed.insert(id.to_string(), outcome.clone());
             let _ = p.response_tx.send(outcome.clone());
 
@@ -252,6 +285,47 @@ impl Approvals {
         futures::stream::iter([snapshot]).chain(live).boxed()
     }
 
+    /// Approve every pending tool approval of the execution process, oldest
+    /// first. Questions are left pending since they need an answer.
+    pub async fn approve_all_pending(
+        &self,
+        execution_process_id: Uuid,
+    ) -> Vec<(
+        String,
+        Result<(ApprovalOutcome, ToolContext), ApprovalError>,
+    )> {
+        let mut ids: Vec<(DateTime<Utc>, String)> = self
+            .pending
+            .iter()
+            .filter(|entry| {
+                let p = entry.value();
+                p.execution_process_id == execution_process_id && !p.is_question
+            })
+            .map(|entry| (entry.value().created_at, entry.key().clone()))
+            .collect();
+        ids.sort();
+
+        let mut results = Vec::with_capacity(ids.len());
+        for (_, id) in ids {
+            let result = self
+                .respond(
+                    &id,
+                    ApprovalResponse {
+                        execution_process_id,
+                        status: ApprovalOutcome::Approved,
+                    },
+                )
+                .await;
+            results.push((id, result));
+        }
+        results
+    }
+
+    /// Approvals and questions waiting on a response.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
     /// Check which execution processes have pending approvals.
     /// Returns a set of execution_process_ids that have at least one pending approval.
     pub fn get_pending_execution_process_ids(
@@ -272,20 +346,64 @@ impl Approvals {
             .collect()
     }
 
+    /// A pending approval or question, if it is still waiting on a response.
+    pub fn pending_info(&self, id: &str) -> Option<ApprovalInfo> {
+        self.pending.get(id).map(|p| Self::info(id, p.value()))
+    }
+
+    /// Hand a pending approval to a trusted relay client, replacing any
+    /// earlier delegation. The approval can still be answered from here.
+    pub fn delegate(&self, id: &str, client_id: Uuid) -> Result<ApprovalInfo, ApprovalError> {
+        let info = {
+            let Some(mut p) = self.pending.get_mut(id) else {
+                return Err(if self.completed.contains_key(id) {
+                    ApprovalError::AlreadyCompleted
+                } else {
+                    ApprovalError::NotFound
+                });
+            };
+            if p.is_question {
+                return Err(ApprovalError::InvalidStatus);
+            }
+            p.delegated_to = Some(client_id);
+            Self::info(id, p.value())
+        };
+
+        let _ = self
+            .patches_tx
+            .send(crate::services::events::patches::approvals_patch::created(
+                &info,
+            ));
+        Ok(info)
+    }
+
+    /// Pending approvals delegated to the given relay client.
+    pub fn delegated_to(&self, client_id: Uuid) -> Vec<ApprovalInfo> {
+        self.pending
+            .iter()
+            .filter(|entry| entry.value().delegated_to == Some(client_id))
+            .map(|entry| Self::info(entry.key(), entry.value()))
+            .collect()
+    }
+
     fn pending_infos(&self) -> Vec<ApprovalInfo> {
         self.pending
             .iter()
-            .map(|entry| {
-                let p = entry.value();
-                ApprovalInfo {
-                    approval_id: entry.key().clone(),
-                    tool_name: p.tool_name.clone(),
-                    execution_process_id: p.execution_process_id,
-                    is_question: p.is_question,
-                    created_at: p.created_at,
-                    timeout_at: p.timeout_at,
-                }
-            })
+            .map(|entry| Self::info(entry.key(), entry.value()))
             .collect()
     }
+
+    fn info(id: &str, p: &PendingApproval) -> ApprovalInfo {
+        ApprovalInfo {
+            approval_id: id.to_string(),
+            tool_name: p.tool_name.clone(),
+            tool_input: p.tool_input.clone(),
+            execution_process_id: p.execution_process_id,
+            is_question: p.is_question,
+            questions: p.questions.clone(),
+            delegated_to: p.delegated_to,
+            created_at: p.created_at,
+            timeout_at: p.timeout_at,
+        }
+    }
 }
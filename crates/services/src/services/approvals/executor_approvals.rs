@@ -1,14 +1,33 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
-use db::{self, DBService, models::execution_process::ExecutionProcess};
-use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
+use db::{
+    self, DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        repo_protected_path::ProtectedPathAction,
+        session_plan::{SessionPlan, SessionPlanStatus},
+        session_question::SessionQuestion,
+    },
+};
+use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService, ToolCallPolicy};
 use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
-use utils::approvals::{ApprovalOutcome, ApprovalRequest, ApprovalStatus, QuestionStatus};
+use utils::approvals::{
+    ApprovalOutcome, ApprovalQuestion, ApprovalRequest, ApprovalStatus, ApprovalToolInput,
+    QuestionStatus,
+};
 use uuid::Uuid;
 
-use crate::services::{approvals::Approvals, notification::NotificationService};
+use crate::services::{
+    approvals::{Approvals, protected_paths::ProtectedPaths},
+    notification::{NotificationEvent, NotificationService},
+    notification_routing,
+};
 
 type ApprovalWaiter = futures::future::Shared<futures::future::BoxFuture<'static, ApprovalOutcome>>;
 
@@ -19,6 +38,11 @@ pub struct ExecutorApprovalBridge {
     execution_process_id: Uuid,
     /// Waiters stored between create and wait phases, keyed by approval_id.
     waiters: Mutex<HashMap<String, ApprovalWaiter>>,
+    /// Approvals gating a plan recorded in `session_plans`.
+    plan_approvals: Mutex<HashSet<String>>,
+    /// Calls refused for writing to a protected path, keyed by the
+    /// approval_id handed out for them, with the reason.
+    denied: Mutex<HashMap<String, String>>,
 }
 
 impl ExecutorApprovalBridge {
@@ -34,16 +58,59 @@ impl ExecutorApprovalBridge {
             notification_service,
             execution_process_id,
             waiters: Mutex::new(HashMap::new()),
+            plan_approvals: Mutex::new(HashSet::new()),
+            denied: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Escalate or refuse writes to the protected paths of the execution's
+    /// repos. Fails closed to an approval if the paths can't be loaded.
+    async fn protected_path_policy(&self, tool_input: &ApprovalToolInput) -> ToolCallPolicy {
+        let Some(file_path) = tool_input.file_path.as_deref() else {
+            return ToolCallPolicy::Default;
+        };
+        let pool = &self.db.pool;
+        let protected = match ExecutionProcess::load_context(pool, self.execution_process_id).await
+        {
+            Ok(ctx) => match ctx.workspace.container_ref.as_deref() {
+                Some(workspace_dir) => {
+                    ProtectedPaths::load(pool, Path::new(workspace_dir), &ctx.repos).await
+                }
+                None => return ToolCallPolicy::Default,
+            },
+            Err(e) => Err(e),
+        };
+        let protected = match protected {
+            Ok(protected) => protected,
+            Err(e) => {
+                tracing::warn!("Failed to load protected paths: {}", e);
+                return ToolCallPolicy::RequireApproval;
+            }
+        };
+
+        match protected.check(file_path) {
+            Some(m) if m.action == ProtectedPathAction::Deny => ToolCallPolicy::Deny {
+                reason: format!(
+                    "{file_path} is protected by `{}` and can't be changed by coding agents",
+                    m.pattern
+                ),
+            },
+            Some(_) => ToolCallPolicy::RequireApproval,
+            None => ToolCallPolicy::Default,
+        }
+    }
+
     async fn create_internal(
         &self,
         tool_name: &str,
-        is_question: bool,
-        question_count: Option<usize>,
+        tool_input: Option<ApprovalToolInput>,
+        questions: Option<Vec<ApprovalQuestion>>,
     ) -> Result<String, ExecutorApprovalError> {
-        let request = ApprovalRequest::new(tool_name.to_string(), self.execution_process_id);
+        let is_question = questions.is_some();
+        let question_count = questions.as_ref().map(|questions| questions.len().max(1));
+        let request =
+            ApprovalRequest::new(tool_name.to_string(), tool_input, self.execution_process_id)
+                .with_questions(questions.unwrap_or_default());
 
         let (request, waiter) = self
             .approvals
@@ -59,7 +126,7 @@ impl ExecutorApprovalBridge {
             .await
             .insert(approval_id.clone(), waiter);
 
-        let (workspace_name, workspace_id) =
+        let (workspace_name, workspace_id, repo_ids) =
             ExecutionProcess::load_context(&self.db.pool, self.execution_process_id)
                 .await
                 .map(|ctx| {
@@ -67,9 +134,10 @@ impl ExecutorApprovalBridge {
                         .workspace
                         .name
                         .unwrap_or_else(|| ctx.workspace.branch.clone());
-                    (name, Some(ctx.workspace.id))
+                    let repo_ids = ctx.repos.iter().map(|repo| repo.id).collect();
+                    (name, Some(ctx.workspace.id), repo_ids)
                 })
-                .unwrap_or_else(|_| ("Unknown workspace".to_string(), None));
+                .unwrap_or_else(|_| ("Unknown workspace".to_string(), None, Vec::new()));
 
         let (title, message) = if let Some(count) = question_count {
             if count == 1 {
@@ -84,15 +152,44 @@ impl ExecutorApprovalBridge {
                 )
             }
         } else {
-            (
-                format!("Approval Needed: {}", workspace_name),
-                format!("Tool '{}' requires approval", tool_name),
-            )
+            let message = match request
+                .tool_input
+                .as_ref()
+                .and_then(|input| input.summary())
+            {
+                Some(summary) => format!("Tool '{}' requires approval: {}", tool_name, summary),
+                None => format!("Tool '{}' requires approval", tool_name),
+            };
+            (format!("Approval Needed: {}", workspace_name), message)
         };
 
-        self.notification_service
-            .notify(&title, &message, workspace_id)
-            .await;
+        let event = if is_question {
+            NotificationEvent::Question
+        } else {
+            NotificationEvent::Approval
+        };
+        let config = self.notification_service.notification_config().await;
+        match notification_routing::route_approval(
+            &self.db.pool,
+            &config,
+            tool_name,
+            is_question,
+            &repo_ids,
+        )
+        .await
+        {
+            Ok(route) => {
+                self.notification_service
+                    .notify_channels(event, &route.channels, &title, &message, workspace_id)
+                    .await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to route approval notification: {}", e);
+                self.notification_service
+                    .notify(event, &title, &message, workspace_id)
+                    .await;
+            }
+        }
 
         Ok(approval_id)
     }
@@ -125,21 +222,110 @@ impl ExecutorApprovalBridge {
 
         Ok(outcome)
     }
+
+    async fn record_questions(
+        &self,
+        approval_id: &str,
+        questions: &[ApprovalQuestion],
+    ) -> Result<(), sqlx::Error> {
+        let pool = &self.db.pool;
+        let Some(process) = ExecutionProcess::find_by_id(pool, self.execution_process_id).await?
+        else {
+            return Ok(());
+        };
+        SessionQuestion::create(
+            pool,
+            process.session_id,
+            self.execution_process_id,
+            approval_id,
+            questions,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn record_plan(&self, plan: &str, approval_id: &str) -> Result<(), sqlx::Error> {
+        let pool = &self.db.pool;
+        let Some(process) = ExecutionProcess::find_by_id(pool, self.execution_process_id).await?
+        else {
+            return Ok(());
+        };
+        SessionPlan::propose(
+            pool,
+            process.session_id,
+            self.execution_process_id,
+            plan,
+            approval_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn settle_plan(&self, approval_id: &str, outcome: Option<&ApprovalOutcome>) {
+        let (status, feedback) = match outcome {
+            Some(ApprovalOutcome::Approved) => (SessionPlanStatus::Accepted, None),
+            Some(ApprovalOutcome::Denied { reason }) => {
+                (SessionPlanStatus::Rejected, reason.as_deref())
+            }
+            _ => (SessionPlanStatus::Expired, None),
+        };
+        if let Err(e) = SessionPlan::resolve(&self.db.pool, approval_id, status, feedback).await {
+            tracing::warn!("Failed to update plan for approval {}: {}", approval_id, e);
+        }
+    }
 }
 
 #[async_trait]
 impl ExecutorApprovalService for ExecutorApprovalBridge {
-    async fn create_tool_approval(&self, tool_name: &str) -> Result<String, ExecutorApprovalError> {
-        self.create_internal(tool_name, false, None).await
+    async fn tool_call_policy(
+        &self,
+        _tool_name: &str,
+        tool_input: &ApprovalToolInput,
+    ) -> ToolCallPolicy {
+        self.protected_path_policy(tool_input).await
+    }
+
+    async fn create_tool_approval(
+        &self,
+        tool_name: &str,
+        tool_input: Option<ApprovalToolInput>,
+    ) -> Result<String, ExecutorApprovalError> {
+        // Refused outright instead of asking someone who could approve it
+        if let Some(input) = &tool_input
+            && let ToolCallPolicy::Deny { reason } = self.protected_path_policy(input).await
+        {
+            let approval_id = Uuid::new_v4().to_string();
+            self.denied.lock().await.insert(approval_id.clone(), reason);
+            return Ok(approval_id);
+        }
+        self.create_internal(tool_name, tool_input, None).await
+    }
+
+    async fn create_plan_approval(
+        &self,
+        tool_name: &str,
+        plan: &str,
+    ) -> Result<String, ExecutorApprovalError> {
+        let approval_id = self.create_internal(tool_name, None, None).await?;
+        if let Err(e) = self.record_plan(plan, &approval_id).await {
+            tracing::warn!("Failed to record plan: {}", e);
+        }
+        self.plan_approvals.lock().await.insert(approval_id.clone());
+        Ok(approval_id)
     }
 
     async fn create_question_approval(
         &self,
         tool_name: &str,
-        question_count: usize,
+        questions: Vec<ApprovalQuestion>,
     ) -> Result<String, ExecutorApprovalError> {
-        self.create_internal(tool_name, true, Some(question_count))
-            .await
+        let approval_id = self
+            .create_internal(tool_name, None, Some(questions.clone()))
+            .await?;
+        if let Err(e) = self.record_questions(&approval_id, &questions).await {
+            tracing::warn!("Failed to record questions: {}", e);
+        }
+        Ok(approval_id)
     }
 
     async fn wait_tool_approval(
@@ -147,9 +333,17 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         approval_id: &str,
         cancel: CancellationToken,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
-        let outcome = self.wait_internal(approval_id, cancel).await?;
+        if let Some(reason) = self.denied.lock().await.remove(approval_id) {
+            return Ok(ApprovalStatus::Denied {
+                reason: Some(reason),
+            });
+        }
+        let outcome = self.wait_internal(approval_id, cancel).await;
+        if self.plan_approvals.lock().await.remove(approval_id) {
+            self.settle_plan(approval_id, outcome.as_ref().ok()).await;
+        }
 
-        match outcome {
+        match outcome? {
             ApprovalOutcome::Approved => Ok(ApprovalStatus::Approved),
             ApprovalOutcome::Denied { reason } => Ok(ApprovalStatus::Denied { reason }),
             ApprovalOutcome::TimedOut => Ok(ApprovalStatus::TimedOut),
@@ -164,9 +358,16 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         approval_id: &str,
         cancel: CancellationToken,
     ) -> Result<QuestionStatus, ExecutorApprovalError> {
-        let outcome = self.wait_internal(approval_id, cancel).await?;
+        let outcome = self.wait_internal(approval_id, cancel).await;
+        let answers = match &outcome {
+            Ok(ApprovalOutcome::Answered { answers }) => Some(answers.as_slice()),
+            _ => None,
+        };
+        if let Err(e) = SessionQuestion::resolve(&self.db.pool, approval_id, answers).await {
+            tracing::warn!("Failed to record answers for {}: {}", approval_id, e);
+        }
 
-        match outcome {
+        match outcome? {
             ApprovalOutcome::Answered { answers } => Ok(QuestionStatus::Answered { answers }),
             ApprovalOutcome::TimedOut => Ok(QuestionStatus::TimedOut),
             ApprovalOutcome::Approved | ApprovalOutcome::Denied { .. } => {
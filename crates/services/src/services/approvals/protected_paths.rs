@@ -0,0 +1,208 @@
+//! Protected paths of the repos an execution works in. Writes to a matching
+//! file need an approval or are refused, whatever the session's approval
+//! setting.
+
+use std::path::{Component, Path, PathBuf};
+
+use db::models::{
+    repo::Repo,
+    repo_protected_path::{ProtectedPathAction, RepoProtectedPath},
+};
+use ignore::{
+    Match,
+    gitignore::{Gitignore, GitignoreBuilder},
+};
+use sqlx::SqlitePool;
+
+/// Check that `pattern` is a valid gitignore-style pattern.
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let mut builder = GitignoreBuilder::new("");
+    builder.add_line(None, pattern).map_err(|e| e.to_string())?;
+    builder.build().map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedPathMatch {
+    pub action: ProtectedPathAction,
+    pub pattern: String,
+}
+
+struct RepoRules {
+    root: PathBuf,
+    deny: Gitignore,
+    require_approval: Gitignore,
+}
+
+impl RepoRules {
+    fn new(root: PathBuf, paths: &[RepoProtectedPath]) -> Self {
+        let build = |action: ProtectedPathAction| {
+            let mut builder = GitignoreBuilder::new(&root);
+            for path in paths.iter().filter(|path| path.action == action) {
+                if let Err(e) = builder.add_line(None, &path.pattern) {
+                    tracing::warn!("Skipping protected path '{}': {}", path.pattern, e);
+                }
+            }
+            builder.build().unwrap_or_else(|e| {
+                tracing::warn!("Failed to build protected paths: {}", e);
+                Gitignore::empty()
+            })
+        };
+        Self {
+            deny: build(ProtectedPathAction::Deny),
+            require_approval: build(ProtectedPathAction::RequireApproval),
+            root,
+        }
+    }
+
+    /// The rule `relative`, a path inside the repo, falls under. Denials win.
+    fn check(&self, relative: &Path) -> Option<ProtectedPathMatch> {
+        [
+            (&self.deny, ProtectedPathAction::Deny),
+            (&self.require_approval, ProtectedPathAction::RequireApproval),
+        ]
+        .into_iter()
+        .find_map(|(rules, action)| {
+            match rules.matched_path_or_any_parents(relative, false) {
+                Match::Ignore(glob) => Some(ProtectedPathMatch {
+                    action,
+                    pattern: glob.original().to_string(),
+                }),
+                Match::None | Match::Whitelist(_) => None,
+            }
+        })
+    }
+}
+
+/// The protected paths of the repos checked out in a workspace.
+pub struct ProtectedPaths {
+    workspace_dir: PathBuf,
+    repos: Vec<RepoRules>,
+}
+
+impl ProtectedPaths {
+    pub async fn load(
+        pool: &SqlitePool,
+        workspace_dir: &Path,
+        repos: &[Repo],
+    ) -> Result<Self, sqlx::Error> {
+        let mut rules = Vec::new();
+        for repo in repos {
+            let paths = RepoProtectedPath::find_by_repo_id(pool, repo.id).await?;
+            if !paths.is_empty() {
+                rules.push(RepoRules::new(workspace_dir.join(&repo.name), &paths));
+            }
+        }
+        Ok(Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            repos: rules,
+        })
+    }
+
+    /// The strictest rule `file_path` falls under. Relative paths are tried
+    /// against the workspace directory and each repo, since agents run in
+    /// either.
+    pub fn check(&self, file_path: &str) -> Option<ProtectedPathMatch> {
+        let path = Path::new(file_path);
+        let candidates: Vec<PathBuf> = if path.is_absolute() {
+            vec![normalize(path)]
+        } else {
+            std::iter::once(&self.workspace_dir)
+                .chain(self.repos.iter().map(|rules| &rules.root))
+                .map(|dir| normalize(&dir.join(path)))
+                .collect()
+        };
+
+        let mut found: Option<ProtectedPathMatch> = None;
+        for candidate in &candidates {
+            for rules in &self.repos {
+                let Ok(relative) = candidate.strip_prefix(&rules.root) else {
+                    continue;
+                };
+                match rules.check(relative) {
+                    Some(m) if m.action == ProtectedPathAction::Deny => return Some(m),
+                    Some(m) => {
+                        found.get_or_insert(m);
+                    }
+                    None => {}
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem, so `infra/../infra`
+/// can't slip past a pattern.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn protected(pattern: &str, action: ProtectedPathAction) -> RepoProtectedPath {
+        RepoProtectedPath {
+            id: Uuid::new_v4(),
+            repo_id: Uuid::nil(),
+            pattern: pattern.to_string(),
+            action,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn matches_patterns_inside_repos() {
+        let paths = ProtectedPaths {
+            workspace_dir: PathBuf::from("/ws"),
+            repos: vec![RepoRules::new(
+                PathBuf::from("/ws/app"),
+                &[
+                    protected("infra/**", ProtectedPathAction::Deny),
+                    protected("*.lock", ProtectedPathAction::RequireApproval),
+                ],
+            )],
+        };
+        let action = |path: &str| paths.check(path).map(|m| m.action);
+
+        assert_eq!(
+            action("/ws/app/infra/prod/main.tf"),
+            Some(ProtectedPathAction::Deny)
+        );
+        assert_eq!(
+            paths.check("/ws/app/infra/main.tf").unwrap().pattern,
+            "infra/**"
+        );
+        assert_eq!(
+            action("/ws/app/web/Cargo.lock"),
+            Some(ProtectedPathAction::RequireApproval)
+        );
+        assert_eq!(
+            action("/ws/app/src/../infra/x.tf"),
+            Some(ProtectedPathAction::Deny)
+        );
+        assert_eq!(action("app/infra/x.tf"), Some(ProtectedPathAction::Deny));
+        assert_eq!(action("infra/x.tf"), Some(ProtectedPathAction::Deny));
+        assert_eq!(action("/ws/app/src/main.rs"), None);
+        assert_eq!(action("/ws/other/infra/x.tf"), None);
+    }
+
+    #[test]
+    fn validates_patterns() {
+        assert!(validate_pattern("infra/**").is_ok());
+        assert!(validate_pattern("src/[a-").is_err());
+    }
+}
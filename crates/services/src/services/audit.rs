@@ -0,0 +1,18 @@
+//! Recording destructive operations in the audit log.
+
+use db::models::audit_log::{AuditLogEntry, CreateAuditLogEntry};
+use sqlx::SqlitePool;
+
+/// Record an audit log entry. The operation has already happened by the time
+/// this is called, so a failure to record it is logged rather than returned.
+pub async fn record(pool: &SqlitePool, entry: CreateAuditLogEntry) {
+    if let Err(e) = AuditLogEntry::create(pool, &entry).await {
+        tracing::warn!(
+            "Failed to record {:?} on {} {:?} in the audit log: {}",
+            entry.action,
+            entry.target_type,
+            entry.target_id,
+            e
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Background check of how far active workspace branches have fallen behind
+//! their target branches. Remote-tracking targets are fetched first; when a
+//! workspace's counts change its card is pushed to the workspace event stream
+//! so staleness shows before a merge is attempted.
+//!
+//! With `auto_rebase_idle_workspaces` enabled, branches that are behind are
+//! also rebased onto their target while nothing runs in the workspace. A
+//! rebase that conflicts is aborted and not retried until the target moves.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        workspace::Workspace,
+        workspace_branch_freshness::{AutoRebaseStatus, WorkspaceBranchFreshness},
+        workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
+    },
+};
+use git::{GitService, GitServiceError};
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info};
+use utils::msg_store::MsgStore;
+
+use super::{config::Config, events::workspace_patch};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Error)]
+pub enum BranchFreshnessError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Workspace(#[from] db::models::workspace::WorkspaceError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error("Branch freshness task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+#[derive(Clone)]
+pub struct BranchFreshnessService {
+    db: DBService,
+    git: GitService,
+    msg_store: Arc<MsgStore>,
+    config: Arc<RwLock<Config>>,
+}
+
+impl BranchFreshnessService {
+    pub fn new(
+        db: DBService,
+        git: GitService,
+        msg_store: Arc<MsgStore>,
+        config: Arc<RwLock<Config>>,
+    ) -> Self {
+        Self {
+            db,
+            git,
+            msg_store,
+            config,
+        }
+    }
+
+    /// Spawn a background loop re-checking every active workspace.
+    pub fn spawn_monitor_loop(
+        db: DBService,
+        git: GitService,
+        msg_store: Arc<MsgStore>,
+        config: Arc<RwLock<Config>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self::new(db, git, msg_store, config);
+        tokio::spawn(async move {
+            info!(
+                "Starting branch freshness monitor with interval {:?}",
+                CHECK_INTERVAL
+            );
+            let mut interval = interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.check_all().await {
+                    error!("Error checking workspace branch freshness: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Check every workspace that isn't archived and still has a worktree.
+    /// Each remote target is fetched once per pass.
+    pub async fn check_all(&self) -> Result<(), BranchFreshnessError> {
+        let workspaces: Vec<Workspace> = Workspace::fetch_all(&self.db.pool)
+            .await?
+            .into_iter()
+            .filter(|ws| !ws.archived && !ws.worktree_deleted && ws.container_ref.is_some())
+            .collect();
+        if workspaces.is_empty() {
+            return Ok(());
+        }
+
+        debug!(
+            "Checking branch freshness of {} workspaces",
+            workspaces.len()
+        );
+        let mut fetched: HashSet<(PathBuf, String)> = HashSet::new();
+        for workspace in &workspaces {
+            if let Err(e) = self.check_workspace(workspace, &mut fetched).await {
+                debug!(
+                    "Skipping branch freshness of workspace {}: {}",
+                    workspace.id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_workspace(
+        &self,
+        workspace: &Workspace,
+        fetched: &mut HashSet<(PathBuf, String)>,
+    ) -> Result<(), BranchFreshnessError> {
+        let repos =
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(&self.db.pool, workspace.id)
+                .await?;
+        let auto_rebase = self.config.read().await.auto_rebase_idle_workspaces;
+        let mut previous: HashMap<_, _> =
+            WorkspaceBranchFreshness::find_by_workspace_id(&self.db.pool, workspace.id)
+                .await?
+                .into_iter()
+                .map(|freshness| (freshness.repo_id, freshness))
+                .collect();
+
+        let mut changed = false;
+        for repo in repos {
+            let key = (repo.repo.path.clone(), repo.target_branch.clone());
+            let fetch = fetched.insert(key);
+            let (ahead, behind) = match self
+                .branch_status(
+                    &repo.repo.path,
+                    &workspace.branch,
+                    &repo.target_branch,
+                    fetch,
+                )
+                .await
+            {
+                Ok(counts) => counts,
+                Err(e) => {
+                    debug!(
+                        "Could not compare {} with {} in {}: {}",
+                        workspace.branch,
+                        repo.target_branch,
+                        repo.repo.path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            changed |= WorkspaceBranchFreshness::record(
+                &self.db.pool,
+                workspace.id,
+                repo.repo.id,
+                &repo.target_branch,
+                ahead as u32,
+                behind as u32,
+            )
+            .await?;
+
+            if auto_rebase && behind > 0 {
+                changed |= self
+                    .auto_rebase(workspace, &repo, previous.remove(&repo.repo.id))
+                    .await?;
+            }
+        }
+
+        if changed
+            && let Some(workspace_with_status) =
+                Workspace::find_by_id_with_status(&self.db.pool, workspace.id).await?
+        {
+            self.msg_store
+                .push_patch(workspace_patch::replace(&workspace_with_status));
+        }
+        Ok(())
+    }
+
+    /// Rebase an idle workspace branch onto the repo's target branch.
+    /// Returns whether a rebase was attempted; conflicting rebases are
+    /// aborted, and dirty worktrees or ongoing rebases are left alone.
+    async fn auto_rebase(
+        &self,
+        workspace: &Workspace,
+        repo: &RepoWithTargetBranch,
+        previous: Option<WorkspaceBranchFreshness>,
+    ) -> Result<bool, BranchFreshnessError> {
+        let Some(container_ref) = &workspace.container_ref else {
+            return Ok(false);
+        };
+        let worktree_path = Path::new(container_ref).join(&repo.repo.name);
+        if !worktree_path.exists() {
+            return Ok(false);
+        }
+
+        let target_oid = {
+            let git = self.git.clone();
+            let repo_path = repo.repo.path.clone();
+            let target_branch = repo.target_branch.clone();
+            tokio::task::spawn_blocking(move || git.get_branch_oid(&repo_path, &target_branch))
+                .await??
+        };
+        let conflicted_on_same_target = previous.is_some_and(|p| {
+            p.auto_rebase_status == Some(AutoRebaseStatus::Conflicts)
+                && p.auto_rebase_target_oid.as_deref() == Some(target_oid.as_str())
+        });
+        if conflicted_on_same_target
+            || ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                &self.db.pool,
+                workspace.id,
+            )
+            .await?
+        {
+            return Ok(false);
+        }
+
+        let status = {
+            let git = self.git.clone();
+            let repo_path = repo.repo.path.clone();
+            let target_branch = repo.target_branch.clone();
+            let branch = workspace.branch.clone();
+            tokio::task::spawn_blocking(move || -> Result<_, GitServiceError> {
+                match git.rebase_branch(
+                    &repo_path,
+                    &worktree_path,
+                    &target_branch,
+                    &target_branch,
+                    &branch,
+                ) {
+                    Ok(_) => Ok(Some(AutoRebaseStatus::Rebased)),
+                    Err(GitServiceError::MergeConflicts { .. }) => {
+                        git.abort_conflicts(&worktree_path)?;
+                        Ok(Some(AutoRebaseStatus::Conflicts))
+                    }
+                    Err(e) => {
+                        debug!("Skipping automatic rebase of {}: {}", branch, e);
+                        Ok(None)
+                    }
+                }
+            })
+            .await??
+        };
+        let Some(status) = status else {
+            return Ok(false);
+        };
+
+        WorkspaceBranchFreshness::record_auto_rebase(
+            &self.db.pool,
+            workspace.id,
+            repo.repo.id,
+            status,
+            &target_oid,
+        )
+        .await?;
+        match status {
+            AutoRebaseStatus::Rebased => {
+                info!(
+                    "Rebased idle workspace branch {} onto {}",
+                    workspace.branch, repo.target_branch
+                );
+                let (ahead, behind) = self
+                    .branch_status(
+                        &repo.repo.path,
+                        &workspace.branch,
+                        &repo.target_branch,
+                        false,
+                    )
+                    .await?;
+                WorkspaceBranchFreshness::record(
+                    &self.db.pool,
+                    workspace.id,
+                    repo.repo.id,
+                    &repo.target_branch,
+                    ahead as u32,
+                    behind as u32,
+                )
+                .await?;
+            }
+            AutoRebaseStatus::Conflicts => {
+                info!(
+                    "Automatic rebase of {} onto {} conflicts; aborted",
+                    workspace.branch, repo.target_branch
+                );
+            }
+        }
+        Ok(true)
+    }
+
+    async fn branch_status(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        target_branch: &str,
+        fetch: bool,
+    ) -> Result<(usize, usize), BranchFreshnessError> {
+        let git = self.git.clone();
+        let repo_path = repo_path.to_path_buf();
+        let branch = branch.to_string();
+        let target_branch = target_branch.to_string();
+        let counts = tokio::task::spawn_blocking(move || {
+            if fetch && let Err(e) = git.fetch_remote_tracking_branch(&repo_path, &target_branch) {
+                // Stale counts beat none, e.g. while offline
+                debug!("Fetching {} failed: {}", target_branch, e);
+            }
+            git.get_branch_status(&repo_path, &branch, &target_branch)
+        })
+        .await??;
+        Ok(counts)
+    }
+}
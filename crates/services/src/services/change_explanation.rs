@@ -0,0 +1,63 @@
+//! Prompt and output parsing for agent-written change explanations.
+
+use db::models::change_explanation::ChangeExplanationContent;
+
+/// Instructions appended to every explanation prompt. The agent's final
+/// message is stored as the turn summary, which is capped, so the answer has
+/// to be compact.
+const OUTPUT_INSTRUCTIONS: &str = r#"Do not modify any files. Reply with a single JSON object and nothing else, in this shape:
+
+{"summary": "<two or three sentences on what the change does and why>",
+ "files": [{"path": "<file path>", "rationale": "<why this file changed>"}],
+ "risks": [{"path": "<file path or null>", "severity": "low" | "medium" | "high", "description": "<what could break>"}]}
+
+Keep the whole reply under 3500 characters. Group trivial files rather than listing each one."#;
+
+pub fn build_explain_prompt(repo_name: &str, base_commit: &str, head_commit: &str) -> String {
+    format!(
+        "Explain the code changes in repository {repo_name} from commit {base_commit} to \
+         {head_commit} for a reviewer.\n\
+         Use `git diff {base_commit}..{head_commit}` and `git log {base_commit}..{head_commit}` \
+         to see the changes.\n\n{OUTPUT_INSTRUCTIONS}"
+    )
+}
+
+/// Parse the agent's final message. Agents often wrap JSON in a fenced code
+/// block or add a sentence around it, so the outermost `{...}` is used.
+pub fn parse_explanation(message: &str) -> Result<ChangeExplanationContent, String> {
+    let start = message.find('{');
+    let end = message.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &message[start..=end],
+        _ => return Err("The agent's reply did not contain a JSON object".to_string()),
+    };
+    serde_json::from_str(json).map_err(|e| format!("The agent's reply was not valid JSON: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::change_explanation::RiskSeverity;
+
+    use super::*;
+
+    #[test]
+    fn parses_fenced_json_reply() {
+        let reply = r#"Here is the explanation:
+
+```json
+{"summary": "Adds retries.", "files": [{"path": "src/client.rs", "rationale": "Retry loop"}],
+ "risks": [{"path": null, "severity": "medium", "description": "Retries are not idempotent"}]}
+```"#;
+        let explanation = parse_explanation(reply).unwrap();
+        assert_eq!(explanation.summary, "Adds retries.");
+        assert_eq!(explanation.files.len(), 1);
+        assert_eq!(explanation.risks[0].severity, RiskSeverity::Medium);
+        assert_eq!(explanation.risks[0].path, None);
+    }
+
+    #[test]
+    fn rejects_reply_without_json() {
+        assert!(parse_explanation("I could not find any changes.").is_err());
+        assert!(parse_explanation(r#"{"files": []}"#).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Resumable uploads sent in fixed-size chunks.
+//!
+//! Large pastes from a paired browser can outlast a slow relay link as a
+//! single multipart POST. Chunks are written at their offset in a part file,
+//! so they may arrive in any order and be resent; the status lists what is
+//! still missing. On completion the content is checked against the SHA-256
+//! the client declared up front. Pending uploads live in memory and expire
+//! after an hour without progress.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::file::FileError;
+
+pub const CHUNK_SIZE: u32 = 512 * 1024;
+const UPLOAD_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct InitChunkedUpload {
+    pub filename: String,
+    pub total_size: u64,
+    /// Hex SHA-256 of the whole file, verified on completion.
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ChunkedUploadStatus {
+    pub upload_id: Uuid,
+    /// Every chunk but the last must be exactly this size.
+    pub chunk_size: u32,
+    pub total_chunks: u32,
+    /// Chunks not received yet. Resume by sending these.
+    pub missing_chunks: Vec<u32>,
+}
+
+/// A fully received and verified upload, ready to be stored.
+#[derive(Debug)]
+pub struct CompletedUpload {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct PendingUpload {
+    filename: String,
+    total_size: u64,
+    sha256: String,
+    received: BTreeSet<u32>,
+    last_activity: Instant,
+}
+
+impl PendingUpload {
+    fn total_chunks(&self) -> u32 {
+        total_chunks(self.total_size)
+    }
+
+    fn expected_len(&self, index: u32) -> u64 {
+        let start = index as u64 * CHUNK_SIZE as u64;
+        (self.total_size - start).min(CHUNK_SIZE as u64)
+    }
+
+    fn status(&self, upload_id: Uuid) -> ChunkedUploadStatus {
+        ChunkedUploadStatus {
+            upload_id,
+            chunk_size: CHUNK_SIZE,
+            total_chunks: self.total_chunks(),
+            missing_chunks: (0..self.total_chunks())
+                .filter(|index| !self.received.contains(index))
+                .collect(),
+        }
+    }
+}
+
+fn total_chunks(total_size: u64) -> u32 {
+    total_size.div_ceil(CHUNK_SIZE as u64) as u32
+}
+
+#[derive(Clone)]
+pub struct ChunkedUploads {
+    dir: PathBuf,
+    max_size_bytes: u64,
+    pending: Arc<Mutex<HashMap<Uuid, PendingUpload>>>,
+}
+
+impl ChunkedUploads {
+    pub fn new(dir: PathBuf, max_size_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_size_bytes,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn part_path(&self, upload_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{upload_id}.part"))
+    }
+
+    pub async fn init(
+        &self,
+        request: &InitChunkedUpload,
+    ) -> Result<ChunkedUploadStatus, FileError> {
+        if request.total_size == 0 {
+            return Err(FileError::InvalidUpload("upload is empty".to_string()));
+        }
+        if request.total_size > self.max_size_bytes {
+            return Err(FileError::TooLarge(request.total_size, self.max_size_bytes));
+        }
+        let sha256 = request.sha256.trim().to_ascii_lowercase();
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(FileError::InvalidUpload(
+                "sha256 must be 64 hex characters".to_string(),
+            ));
+        }
+
+        self.prune_expired().await;
+        fs::create_dir_all(&self.dir).await?;
+        let upload_id = Uuid::new_v4();
+        let file = fs::File::create(self.part_path(upload_id)).await?;
+        file.set_len(request.total_size).await?;
+
+        let upload = PendingUpload {
+            filename: request.filename.clone(),
+            total_size: request.total_size,
+            sha256,
+            received: BTreeSet::new(),
+            last_activity: Instant::now(),
+        };
+        let status = upload.status(upload_id);
+        self.pending.lock().await.insert(upload_id, upload);
+        Ok(status)
+    }
+
+    pub async fn status(&self, upload_id: Uuid) -> Result<ChunkedUploadStatus, FileError> {
+        let pending = self.pending.lock().await;
+        let upload = pending.get(&upload_id).ok_or(FileError::UploadNotFound)?;
+        Ok(upload.status(upload_id))
+    }
+
+    /// Write one chunk. Resending a chunk overwrites it.
+    pub async fn write_chunk(
+        &self,
+        upload_id: Uuid,
+        index: u32,
+        data: &[u8],
+    ) -> Result<ChunkedUploadStatus, FileError> {
+        let expected_len = {
+            let pending = self.pending.lock().await;
+            let upload = pending.get(&upload_id).ok_or(FileError::UploadNotFound)?;
+            if index >= upload.total_chunks() {
+                return Err(FileError::InvalidUpload(format!(
+                    "chunk {index} is out of range (upload has {} chunks)",
+                    upload.total_chunks()
+                )));
+            }
+            upload.expected_len(index)
+        };
+        if data.len() as u64 != expected_len {
+            return Err(FileError::InvalidUpload(format!(
+                "chunk {index} is {} bytes, expected {expected_len}",
+                data.len()
+            )));
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .open(self.part_path(upload_id))
+            .await?;
+        file.seek(SeekFrom::Start(index as u64 * CHUNK_SIZE as u64))
+            .await?;
+        file.write_all(data).await?;
+        file.flush().await?;
+
+        let mut pending = self.pending.lock().await;
+        let upload = pending
+            .get_mut(&upload_id)
+            .ok_or(FileError::UploadNotFound)?;
+        upload.received.insert(index);
+        upload.last_activity = Instant::now();
+        Ok(upload.status(upload_id))
+    }
+
+    /// Take a fully received upload whose content matches the declared hash.
+    /// On a hash mismatch the upload is discarded and must start over.
+    pub async fn complete(&self, upload_id: Uuid) -> Result<CompletedUpload, FileError> {
+        let upload = {
+            let mut pending = self.pending.lock().await;
+            let upload = pending.get(&upload_id).ok_or(FileError::UploadNotFound)?;
+            let status = upload.status(upload_id);
+            if !status.missing_chunks.is_empty() {
+                return Err(FileError::InvalidUpload(format!(
+                    "{} of {} chunks are missing",
+                    status.missing_chunks.len(),
+                    status.total_chunks
+                )));
+            }
+            pending
+                .remove(&upload_id)
+                .ok_or(FileError::UploadNotFound)?
+        };
+
+        let part_path = self.part_path(upload_id);
+        let data = fs::read(&part_path).await;
+        remove_part_file(&part_path).await;
+        let data = data?;
+
+        let hash = format!("{:x}", Sha256::digest(&data));
+        if hash != upload.sha256 {
+            return Err(FileError::HashMismatch {
+                expected: upload.sha256,
+                actual: hash,
+            });
+        }
+        Ok(CompletedUpload {
+            filename: upload.filename,
+            data,
+        })
+    }
+
+    async fn prune_expired(&self) {
+        let expired: Vec<Uuid> = {
+            let mut pending = self.pending.lock().await;
+            let expired: Vec<Uuid> = pending
+                .iter()
+                .filter(|(_, upload)| upload.last_activity.elapsed() > UPLOAD_TTL)
+                .map(|(id, _)| *id)
+                .collect();
+            for id in &expired {
+                pending.remove(id);
+            }
+            expired
+        };
+        for upload_id in expired {
+            remove_part_file(&self.part_path(upload_id)).await;
+        }
+    }
+}
+
+async fn remove_part_file(path: &Path) {
+    if let Err(e) = fs::remove_file(path).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove upload part {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_request(data: &[u8]) -> InitChunkedUpload {
+        InitChunkedUpload {
+            filename: "screenshot.png".to_string(),
+            total_size: data.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(data)),
+        }
+    }
+
+    #[tokio::test]
+    async fn chunks_can_arrive_out_of_order_and_be_resent() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ChunkedUploads::new(dir.path().to_path_buf(), 20 * 1024 * 1024);
+        let data: Vec<u8> = (0..CHUNK_SIZE as usize * 2 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let chunks: Vec<&[u8]> = data.chunks(CHUNK_SIZE as usize).collect();
+
+        let status = uploads.init(&init_request(&data)).await.unwrap();
+        assert_eq!(status.missing_chunks, vec![0, 1, 2]);
+        let id = status.upload_id;
+
+        uploads.write_chunk(id, 2, chunks[2]).await.unwrap();
+        uploads.write_chunk(id, 0, chunks[0]).await.unwrap();
+        uploads.write_chunk(id, 0, chunks[0]).await.unwrap();
+        assert!(uploads.complete(id).await.is_err());
+
+        let status = uploads.write_chunk(id, 1, chunks[1]).await.unwrap();
+        assert!(status.missing_chunks.is_empty());
+        let completed = uploads.complete(id).await.unwrap();
+        assert_eq!(completed.data, data);
+        assert!(!dir.path().join(format!("{id}.part")).exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_chunk_sizes_and_hash_mismatches() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ChunkedUploads::new(dir.path().to_path_buf(), 20 * 1024 * 1024);
+        let mut request = init_request(b"hello");
+        request.sha256 = format!("{:x}", Sha256::digest(b"other"));
+
+        let id = uploads.init(&request).await.unwrap().upload_id;
+        assert!(uploads.write_chunk(id, 0, b"hell").await.is_err());
+        uploads.write_chunk(id, 0, b"hello").await.unwrap();
+        assert!(matches!(
+            uploads.complete(id).await,
+            Err(FileError::HashMismatch { .. })
+        ));
+        assert!(matches!(
+            uploads.status(id).await,
+            Err(FileError::UploadNotFound)
+        ));
+    }
+}
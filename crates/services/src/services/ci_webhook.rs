@@ -0,0 +1,190 @@
+//! Reads CI webhook payloads into the check states they report for a branch.
+//! GitHub's `workflow_run`, `check_run`, `check_suite` and `status` events are
+//! understood; other CI systems can post a [`GenericCiEvent`].
+
+use db::models::workspace_ci_check::{CiState, RecordCiCheck};
+use serde::Deserialize;
+use serde_json::Value;
+use ts_rs::TS;
+
+/// A CI check state in the form CI systems other than GitHub can post.
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct GenericCiEvent {
+    /// Name of the repository the check ran in. Narrows the match to
+    /// workspaces with a repo of that name.
+    pub repo: Option<String>,
+    pub branch: String,
+    /// Name of the check. Defaults to `ci`.
+    pub name: Option<String>,
+    pub state: CiState,
+    pub head_sha: Option<String>,
+    pub url: Option<String>,
+}
+
+/// A check state reported for one or more branches.
+#[derive(Debug, Clone)]
+pub struct CiEvent {
+    pub repo: Option<String>,
+    pub branches: Vec<String>,
+    pub check: RecordCiCheck,
+}
+
+/// The check state in a payload. `github_event` is the `X-GitHub-Event`
+/// header; without it the payload is read as a [`GenericCiEvent`]. Returns
+/// `None` for events that report no check state, like `ping`.
+pub fn parse_ci_event(
+    github_event: Option<&str>,
+    payload: &Value,
+) -> Result<Option<CiEvent>, serde_json::Error> {
+    let Some(github_event) = github_event else {
+        let event = GenericCiEvent::deserialize(payload)?;
+        return Ok(Some(CiEvent {
+            repo: event.repo,
+            branches: vec![event.branch],
+            check: RecordCiCheck {
+                name: event.name.unwrap_or_else(|| "ci".to_string()),
+                state: event.state,
+                head_sha: event.head_sha,
+                url: event.url,
+            },
+        }));
+    };
+
+    let text = |value: &Value| value.as_str().map(str::to_string);
+    let (run, branches, name, state, url) = match github_event {
+        "workflow_run" => {
+            let run = &payload["workflow_run"];
+            (
+                run,
+                text(&run["head_branch"]).into_iter().collect(),
+                text(&run["name"]),
+                github_check_state(&run["status"], &run["conclusion"]),
+                text(&run["html_url"]),
+            )
+        }
+        "check_run" => {
+            let run = &payload["check_run"];
+            (
+                run,
+                text(&run["check_suite"]["head_branch"])
+                    .into_iter()
+                    .collect(),
+                text(&run["name"]),
+                github_check_state(&run["status"], &run["conclusion"]),
+                text(&run["html_url"]),
+            )
+        }
+        "check_suite" => {
+            let suite = &payload["check_suite"];
+            (
+                suite,
+                text(&suite["head_branch"]).into_iter().collect(),
+                text(&suite["app"]["name"]),
+                github_check_state(&suite["status"], &suite["conclusion"]),
+                None,
+            )
+        }
+        "status" => (
+            payload,
+            payload["branches"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|branch| text(&branch["name"]))
+                .collect(),
+            text(&payload["context"]),
+            match payload["state"].as_str() {
+                Some("success") => CiState::Success,
+                Some("failure" | "error") => CiState::Failure,
+                _ => CiState::Pending,
+            },
+            text(&payload["target_url"]),
+        ),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(CiEvent {
+        repo: text(&payload["repository"]["name"]),
+        branches,
+        check: RecordCiCheck {
+            name: name.unwrap_or_else(|| github_event.to_string()),
+            state,
+            head_sha: text(&run["head_sha"]).or_else(|| text(&run["sha"])),
+            url,
+        },
+    }))
+}
+
+/// Check runs, suites and workflow runs are pending until `completed`;
+/// neutral and skipped conclusions don't fail the branch.
+fn github_check_state(status: &Value, conclusion: &Value) -> CiState {
+    if status.as_str() != Some("completed") {
+        return CiState::Pending;
+    }
+    match conclusion.as_str() {
+        Some("success" | "neutral" | "skipped") => CiState::Success,
+        _ => CiState::Failure,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reads_github_workflow_runs() {
+        let payload = json!({
+            "action": "completed",
+            "workflow_run": {
+                "name": "CI",
+                "head_branch": "vk/1a2b-fix-login",
+                "head_sha": "abc123",
+                "status": "completed",
+                "conclusion": "failure",
+                "html_url": "https://github.com/acme/app/actions/runs/1",
+            },
+            "repository": { "name": "app", "full_name": "acme/app" },
+        });
+        let event = parse_ci_event(Some("workflow_run"), &payload)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.repo.as_deref(), Some("app"));
+        assert_eq!(event.branches, ["vk/1a2b-fix-login"]);
+        assert_eq!(event.check.name, "CI");
+        assert_eq!(event.check.state, CiState::Failure);
+        assert_eq!(event.check.head_sha.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn reads_github_commit_statuses() {
+        let payload = json!({
+            "sha": "abc123",
+            "context": "ci/circleci",
+            "state": "pending",
+            "branches": [{ "name": "vk/1a2b-fix-login" }, { "name": "main" }],
+            "repository": { "name": "app" },
+        });
+        let event = parse_ci_event(Some("status"), &payload).unwrap().unwrap();
+        assert_eq!(event.branches, ["vk/1a2b-fix-login", "main"]);
+        assert_eq!(event.check.name, "ci/circleci");
+        assert_eq!(event.check.state, CiState::Pending);
+        assert_eq!(event.check.head_sha.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn reads_generic_events_and_ignores_pings() {
+        let payload = json!({ "branch": "vk/1a2b-fix-login", "state": "success" });
+        let event = parse_ci_event(None, &payload).unwrap().unwrap();
+        assert_eq!(event.check.name, "ci");
+        assert_eq!(event.check.state, CiState::Success);
+
+        assert!(parse_ci_event(None, &json!({ "branch": "x" })).is_err());
+        assert!(
+            parse_ci_event(Some("ping"), &json!({ "zen": "..." }))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Prompt and output parsing for agent-written commit messages.
+
+/// Diffs beyond this many bytes are cut off; the agent only sees the prompt,
+/// so very large diffs would otherwise blow past its context.
+const MAX_DIFF_BYTES: usize = 60_000;
+
+const OUTPUT_INSTRUCTIONS: &str = "Write a commit message for the diff below using the \
+Conventional Commits format: `<type>(<optional scope>): <summary>`, where type is one of feat, \
+fix, refactor, perf, docs, test, build, ci, style or chore. Keep the summary line under 72 \
+characters, in the imperative mood, without a trailing period. If the change needs more \
+explanation, add a blank line and a short body wrapped at 72 characters.\n\
+Do not run any tools or modify any files. Reply with the commit message only, with no \
+preamble, quotes or code fences.";
+
+pub fn build_commit_message_prompt(repo_name: &str, patch: &str) -> String {
+    let (patch, truncated) = truncate_patch(patch);
+    let note = if truncated {
+        "\n(The diff was truncated; describe the change from the visible part.)"
+    } else {
+        ""
+    };
+    format!(
+        "{OUTPUT_INSTRUCTIONS}\n\nUncommitted changes in repository {repo_name}:{note}\n\n\
+         ```diff\n{patch}\n```"
+    )
+}
+
+fn truncate_patch(patch: &str) -> (&str, bool) {
+    if patch.len() <= MAX_DIFF_BYTES {
+        return (patch, false);
+    }
+    let mut end = MAX_DIFF_BYTES;
+    while !patch.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&patch[..end], true)
+}
+
+/// Clean up the agent's reply. Agents sometimes wrap the message in a fenced
+/// code block or quotes despite being asked not to.
+pub fn parse_commit_message(reply: &str) -> Result<String, String> {
+    let mut message = reply.trim();
+    if let Some(rest) = message.strip_prefix("```") {
+        // Drop the info string (e.g. ```text) along with the fences
+        let rest = rest.split_once('\n').map_or("", |(_, body)| body);
+        message = rest.trim_end().strip_suffix("```").unwrap_or(rest).trim();
+    }
+    for quote in ['"', '\'', '`'] {
+        if message.len() > 1 && message.starts_with(quote) && message.ends_with(quote) {
+            message = message[1..message.len() - 1].trim();
+        }
+    }
+    if message.is_empty() {
+        return Err("The agent replied with an empty commit message".to_string());
+    }
+    Ok(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_code_fences_and_quotes() {
+        let reply = "```text\nfeat(api): add commit message generation\n\nUses the executor.\n```";
+        assert_eq!(
+            parse_commit_message(reply).unwrap(),
+            "feat(api): add commit message generation\n\nUses the executor."
+        );
+        assert_eq!(
+            parse_commit_message("  \"fix: handle empty diff\"\n").unwrap(),
+            "fix: handle empty diff"
+        );
+        assert!(parse_commit_message("```\n```").is_err());
+    }
+
+    #[test]
+    fn truncates_large_diffs_on_char_boundary() {
+        let patch = "é".repeat(MAX_DIFF_BYTES);
+        let prompt = build_commit_message_prompt("repo", &patch);
+        assert!(prompt.contains("The diff was truncated"));
+        assert!(prompt.len() < MAX_DIFF_BYTES + 2_000);
+    }
+}
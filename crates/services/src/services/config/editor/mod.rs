@@ -1,4 +1,4 @@
-use std::{path::Path, str::FromStr};
+use std::{ffi::OsString, path::Path, str::FromStr};
 
 use executors::{command::CommandBuilder, executors::ExecutorError};
 use serde::{Deserialize, Serialize};
@@ -60,6 +60,13 @@ pub enum EditorType {
     Custom,
 }
 
+/// Where to put the cursor in an opened file, 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditorPosition {
+    pub line: u32,
+    pub column: Option<u32>,
+}
+
 impl Default for EditorConfig {
     fn default() -> Self {
         Self {
@@ -162,17 +169,27 @@ impl EditorConfig {
     }
 
     pub async fn open_file(&self, path: &Path) -> Result<Option<String>, EditorOpenError> {
-        if let Some(url) = self.remote_url(path) {
+        self.open_file_at(path, None).await
+    }
+
+    /// Open `path`, placing the cursor at `position` where the editor's CLI
+    /// supports it. Returns a URL to open instead in remote SSH mode.
+    pub async fn open_file_at(
+        &self,
+        path: &Path,
+        position: Option<EditorPosition>,
+    ) -> Result<Option<String>, EditorOpenError> {
+        if let Some(url) = self.remote_url(path, position) {
             return Ok(Some(url));
         }
         if self.should_auto_install_extension() {
             self.try_install_extension().await;
         }
-        self.spawn_local(path).await?;
+        self.spawn_local_at(path, position).await?;
         Ok(None)
     }
 
-    fn remote_url(&self, path: &Path) -> Option<String> {
+    fn remote_url(&self, path: &Path, position: Option<EditorPosition>) -> Option<String> {
         let remote_host = self.remote_ssh_host.as_ref()?;
         let user_part = self
             .remote_ssh_user
@@ -194,18 +211,30 @@ impl EditorConfig {
         };
 
         // files must contain a line and column number
-        let line_col = if path.is_file() { ":1:1" } else { "" };
+        let line_col = match position {
+            Some(pos) => format!(":{}:{}", pos.line, pos.column.unwrap_or(1)),
+            None if path.is_file() => ":1:1".to_string(),
+            None => String::new(),
+        };
         Some(format!(
             "{scheme}://vscode-remote/ssh-remote+{user_part}{remote_host}{path_str}{line_col}?windowId=_blank"
         ))
     }
 
     pub async fn spawn_local(&self, path: &Path) -> Result<(), EditorOpenError> {
+        self.spawn_local_at(path, None).await
+    }
+
+    async fn spawn_local_at(
+        &self,
+        path: &Path,
+        position: Option<EditorPosition>,
+    ) -> Result<(), EditorOpenError> {
         let (executable, args) = self.resolve_command().await?;
 
         use utils::command_ext::NoWindowExt;
         let mut cmd = std::process::Command::new(&executable);
-        cmd.args(&args).arg(path);
+        cmd.args(self.location_args(args, path, position));
         cmd.no_window()
             .spawn()
             .map_err(|e| EditorOpenError::LaunchFailed {
@@ -216,6 +245,64 @@ impl EditorConfig {
         Ok(())
     }
 
+    /// Arguments that open `path` at `position`. A custom command may place
+    /// the file itself with `{path}`, `{line}` and `{column}` placeholders,
+    /// e.g. `rustrover --line {line} {path}`; otherwise the path is appended.
+    fn location_args(
+        &self,
+        args: Vec<String>,
+        path: &Path,
+        position: Option<EditorPosition>,
+    ) -> Vec<OsString> {
+        let line = position.map_or(1, |p| p.line);
+        let column = position.and_then(|p| p.column).unwrap_or(1);
+        if matches!(self.editor_type, EditorType::Custom)
+            && args.iter().any(|arg| arg.contains("{path}"))
+        {
+            let path = path.to_string_lossy();
+            return args
+                .iter()
+                .map(|arg| {
+                    arg.replace("{path}", &path)
+                        .replace("{line}", &line.to_string())
+                        .replace("{column}", &column.to_string())
+                        .into()
+                })
+                .collect();
+        }
+
+        let mut args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+        if position.is_none() {
+            args.push(path.into());
+            return args;
+        }
+        let with_position = format!("{}:{line}:{column}", path.display());
+        match self.editor_type {
+            EditorType::VsCode
+            | EditorType::VsCodeInsiders
+            | EditorType::Cursor
+            | EditorType::Windsurf
+            | EditorType::GoogleAntigravity => {
+                args.extend(["--goto".into(), with_position.into()]);
+            }
+            EditorType::Zed => args.push(with_position.into()),
+            EditorType::IntelliJ => {
+                args.extend([
+                    "--line".into(),
+                    line.to_string().into(),
+                    "--column".into(),
+                    column.to_string().into(),
+                    path.into(),
+                ]);
+            }
+            EditorType::Xcode => {
+                args.extend(["--line".into(), line.to_string().into(), path.into()]);
+            }
+            EditorType::Custom => args.push(path.into()),
+        }
+        args
+    }
+
     pub fn with_override(&self, editor_type_str: Option<&str>) -> Self {
         if let Some(editor_type_str) = editor_type_str {
             let editor_type =
@@ -232,3 +319,62 @@ impl EditorConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(editor_type: EditorType, custom_command: Option<&str>) -> EditorConfig {
+        EditorConfig::new(
+            editor_type,
+            custom_command.map(str::to_string),
+            None,
+            None,
+            false,
+        )
+    }
+
+    fn args(config: &EditorConfig, base: &[&str], position: Option<EditorPosition>) -> Vec<String> {
+        config
+            .location_args(
+                base.iter().map(|s| s.to_string()).collect(),
+                Path::new("/ws/repo/src/main.rs"),
+                position,
+            )
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn location_args_follow_each_editor_cli() {
+        let at = Some(EditorPosition {
+            line: 42,
+            column: Some(7),
+        });
+        assert_eq!(
+            args(&config(EditorType::Cursor, None), &[], at),
+            ["--goto", "/ws/repo/src/main.rs:42:7"]
+        );
+        assert_eq!(
+            args(&config(EditorType::Zed, None), &[], None),
+            ["/ws/repo/src/main.rs"]
+        );
+        assert_eq!(
+            args(&config(EditorType::IntelliJ, None), &[], at),
+            ["--line", "42", "--column", "7", "/ws/repo/src/main.rs"]
+        );
+        assert_eq!(
+            args(
+                &config(EditorType::Custom, Some("rustrover --line {line} {path}")),
+                &["--line", "{line}", "{path}"],
+                at
+            ),
+            ["--line", "42", "/ws/repo/src/main.rs"]
+        );
+        assert_eq!(
+            args(&config(EditorType::Custom, Some("subl -n")), &["-n"], at),
+            ["-n", "/ws/repo/src/main.rs"]
+        );
+    }
+}
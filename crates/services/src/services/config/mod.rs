@@ -1,11 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use thiserror::Error;
 
+use self::secrets::SecretsCipher;
+
 pub mod editor;
+pub mod secrets;
 mod versions;
 
-pub use editor::EditorOpenError;
+pub use editor::{EditorOpenError, EditorPosition};
 
 pub const DEFAULT_PR_DESCRIPTION_PROMPT: &str = r#"Update the PR that was just created with a better title and description.
 The PR number is #{pr_number} and the URL is {pr_url}.
@@ -28,6 +31,8 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Secrets(#[from] secrets::SecretsError),
     #[error("Validation error: {0}")]
     ValidationError(String),
 }
@@ -42,11 +47,47 @@ pub type GitHubConfig = versions::v8::GitHubConfig;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
 pub type SendMessageShortcut = versions::v8::SendMessageShortcut;
+pub type LogRetentionConfig = versions::v8::LogRetentionConfig;
+pub type DirtyWorktreePolicy = versions::v8::DirtyWorktreePolicy;
+pub type PromptSnippet = versions::v8::PromptSnippet;
+pub type ReportDeliveryConfig = versions::v8::ReportDeliveryConfig;
+pub type ExecutorTimeoutConfig = versions::v8::ExecutorTimeoutConfig;
+pub type WorktreeStrategy = versions::v8::WorktreeStrategy;
+pub type RouteRateLimitConfig = versions::v8::RouteRateLimitConfig;
+pub type NotificationDeliveryConfig = versions::v8::NotificationDeliveryConfig;
+pub type NotificationEventToggles = versions::v8::NotificationEventToggles;
+pub type QuietHours = versions::v8::QuietHours;
+pub type AnalyticsConsent = versions::v8::AnalyticsConsent;
+pub type TerminalConfig = versions::v8::TerminalConfig;
+pub type TerminalCwd = versions::v8::TerminalCwd;
+pub type ExecutionBackend = versions::v8::ExecutionBackend;
+pub type ExecutionTagLimits = versions::v8::ExecutionTagLimits;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
+    read_config(config_path, secrets::cipher())
+}
+
+/// Saves the config to the given path, encrypting secrets when a key is available
+pub async fn save_config_to_file(
+    config: &Config,
+    config_path: &PathBuf,
+) -> Result<(), ConfigError> {
+    write_config(config, config_path, secrets::cipher())
+}
+
+fn read_config(config_path: &Path, cipher: Option<&SecretsCipher>) -> Config {
     match std::fs::read_to_string(config_path) {
-        Ok(raw_config) => Config::from(raw_config),
+        Ok(raw_config) => {
+            let mut config = Config::from(raw_config);
+            if secrets::open_config(&mut config, cipher) {
+                tracing::info!("Encrypting plaintext secrets in config");
+                if let Err(e) = write_config(&config, config_path, cipher) {
+                    tracing::warn!("Failed to encrypt config secrets: {}", e);
+                }
+            }
+            config
+        }
         Err(_) => {
             tracing::info!("No config file found, creating one");
             Config::default()
@@ -54,12 +95,68 @@ pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
     }
 }
 
-/// Saves the config to the given path
-pub async fn save_config_to_file(
+fn write_config(
     config: &Config,
-    config_path: &PathBuf,
+    config_path: &Path,
+    cipher: Option<&SecretsCipher>,
 ) -> Result<(), ConfigError> {
-    let raw_config = serde_json::to_string_pretty(config)?;
+    let raw_config = match cipher {
+        Some(cipher) => {
+            let mut sealed = config.clone();
+            secrets::seal_config(&mut sealed, cipher)?;
+            serde_json::to_string_pretty(&sealed)?
+        }
+        None => serde_json::to_string_pretty(config)?,
+    };
     std::fs::write(config_path, raw_config)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_encrypted_on_disk_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let cipher = SecretsCipher::from_key_bytes([7; 32]);
+        let mut config = Config::default();
+        config.github.pat = Some("ghp_secret".to_string());
+
+        write_config(&config, &path, Some(&cipher)).unwrap();
+        assert!(
+            !std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("ghp_secret")
+        );
+        let loaded = read_config(&path, Some(&cipher));
+        assert_eq!(loaded.github.pat.as_deref(), Some("ghp_secret"));
+
+        // Without a key the secret can't be recovered and is dropped
+        assert!(read_config(&path, None).github.pat.is_none());
+    }
+
+    #[test]
+    fn plaintext_secrets_are_encrypted_when_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut config = Config::default();
+        config.github.pat = Some("ghp_secret".to_string());
+        write_config(&config, &path, None).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("ghp_secret")
+        );
+
+        let cipher = SecretsCipher::from_key_bytes([7; 32]);
+        let loaded = read_config(&path, Some(&cipher));
+        assert_eq!(loaded.github.pat.as_deref(), Some("ghp_secret"));
+        assert!(
+            !std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("ghp_secret")
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Encryption at rest for credentials stored in the config file.
+//!
+//! Sensitive fields are written as `enc:v1:<base64(nonce || ciphertext)>`,
+//! sealed with ChaCha20-Poly1305. The key lives in the OS keychain; where no
+//! keychain is available it is derived from `VK_SECRETS_PASSPHRASE` with
+//! Argon2id and a salt kept next to the config. Without either, secrets stay
+//! in plaintext as before.
+
+use std::{io::ErrorKind, path::Path, sync::OnceLock};
+
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use chacha20poly1305::{
+    AeadCore, ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, OsRng, rand_core::RngCore},
+};
+use thiserror::Error;
+use utils::assets::asset_dir;
+
+use super::Config;
+
+pub const SECRETS_PASSPHRASE_ENV: &str = "VK_SECRETS_PASSPHRASE";
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEYRING_SERVICE: &str = "vibe-kanban";
+const KEYRING_USER: &str = "config-secrets-key";
+const SALT_FILE: &str = "secrets.salt";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error(transparent)]
+    Keyring(#[from] keyring::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("no keychain available and {SECRETS_PASSPHRASE_ENV} is not set")]
+    NoKey,
+    #[error("stored secrets key is invalid")]
+    InvalidKey,
+    #[error("failed to derive secrets key: {0}")]
+    Kdf(String),
+    #[error("failed to encrypt secret")]
+    Encrypt,
+    #[error("failed to decrypt secret")]
+    Decrypt,
+}
+
+#[derive(Clone)]
+pub struct SecretsCipher {
+    key: Key,
+}
+
+impl SecretsCipher {
+    pub fn from_key_bytes(key: [u8; 32]) -> Self {
+        Self { key: key.into() }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretsError> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| SecretsError::Encrypt)?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + sealed.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(format!("{ENCRYPTED_PREFIX}{}", BASE64_STANDARD.encode(out)))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, SecretsError> {
+        let encoded = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or(SecretsError::Decrypt)?;
+        let bytes = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|_| SecretsError::Decrypt)?;
+        if bytes.len() < NONCE_LEN {
+            return Err(SecretsError::Decrypt);
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let plaintext = ChaCha20Poly1305::new(&self.key)
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| SecretsError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| SecretsError::Decrypt)
+    }
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// The process-wide cipher, resolved on first use. `None` when neither the
+/// keychain nor a passphrase is available.
+pub fn cipher() -> Option<&'static SecretsCipher> {
+    static CIPHER: OnceLock<Option<SecretsCipher>> = OnceLock::new();
+    CIPHER
+        .get_or_init(|| match resolve_cipher() {
+            Ok(cipher) => Some(cipher),
+            Err(e) => {
+                tracing::warn!("Config secrets will be stored unencrypted: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Where the generated secrets key is kept.
+trait KeyStore {
+    fn load(&self) -> Result<Option<String>, SecretsError>;
+    fn save(&self, encoded_key: &str) -> Result<(), SecretsError>;
+}
+
+/// The OS keychain: Keychain on macOS, Credential Manager on Windows and the
+/// Secret Service on Linux.
+struct OsKeychain;
+
+impl KeyStore for OsKeychain {
+    fn load(&self) -> Result<Option<String>, SecretsError> {
+        match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.get_password() {
+            Ok(encoded) => Ok(Some(encoded)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, encoded_key: &str) -> Result<(), SecretsError> {
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.set_password(encoded_key)?;
+        Ok(())
+    }
+}
+
+fn resolve_cipher() -> Result<SecretsCipher, SecretsError> {
+    match stored_cipher(&OsKeychain) {
+        Ok(cipher) => return Ok(cipher),
+        Err(e) => tracing::debug!("OS keychain unavailable for config secrets: {}", e),
+    }
+    let passphrase = std::env::var(SECRETS_PASSPHRASE_ENV)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+        .ok_or(SecretsError::NoKey)?;
+    passphrase_cipher(&passphrase, &asset_dir().join(SALT_FILE))
+}
+
+/// Load the key from a key store, generating it on first use.
+fn stored_cipher(store: &dyn KeyStore) -> Result<SecretsCipher, SecretsError> {
+    match store.load()? {
+        Some(encoded) => {
+            let key: [u8; 32] = BASE64_STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(SecretsError::InvalidKey)?;
+            Ok(SecretsCipher::from_key_bytes(key))
+        }
+        None => {
+            let mut key = [0u8; 32];
+            OsRng.fill_bytes(&mut key);
+            store.save(&BASE64_STANDARD.encode(key))?;
+            Ok(SecretsCipher::from_key_bytes(key))
+        }
+    }
+}
+
+/// Derive the key from a passphrase, creating the salt file on first use.
+fn passphrase_cipher(passphrase: &str, salt_path: &Path) -> Result<SecretsCipher, SecretsError> {
+    let salt = match std::fs::read(salt_path) {
+        Ok(salt) if salt.len() == SALT_LEN => salt,
+        Ok(_) => return Err(SecretsError::InvalidKey),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let mut salt = vec![0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            std::fs::write(salt_path, &salt)?;
+            salt
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| SecretsError::Kdf(e.to_string()))?;
+    Ok(SecretsCipher::from_key_bytes(key))
+}
+
+fn secret_fields(config: &mut Config) -> [&mut Option<String>; 3] {
+    [
+        &mut config.github.pat,
+        &mut config.github.oauth_token,
+        &mut config.webhook_token,
+    ]
+}
+
+/// Encrypt the sensitive fields of a config about to be written to disk.
+pub(super) fn seal_config(config: &mut Config, cipher: &SecretsCipher) -> Result<(), SecretsError> {
+    for field in secret_fields(config) {
+        if let Some(value) = field.as_mut()
+            && !is_encrypted(value)
+        {
+            *value = cipher.encrypt(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt the sensitive fields of a config read from disk. Secrets that
+/// can't be decrypted, e.g. because the keychain entry was removed, are
+/// cleared so they're never used as credentials; the user has to sign in
+/// again. Returns whether plaintext secrets were found that should be
+/// re-saved encrypted.
+pub(super) fn open_config(config: &mut Config, cipher: Option<&SecretsCipher>) -> bool {
+    let mut has_plaintext = false;
+    for field in secret_fields(config) {
+        let Some(value) = field.as_deref() else {
+            continue;
+        };
+        if !is_encrypted(value) {
+            has_plaintext = true;
+            continue;
+        }
+        *field = match cipher.map(|cipher| cipher.decrypt(value)) {
+            Some(Ok(plaintext)) => Some(plaintext),
+            Some(Err(e)) => {
+                tracing::warn!("Discarding config secret: {}", e);
+                None
+            }
+            None => {
+                tracing::warn!("Discarding encrypted config secret: no secrets key available");
+                None
+            }
+        };
+    }
+    has_plaintext && cipher.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryKeyStore(Mutex<Option<String>>);
+
+    impl KeyStore for MemoryKeyStore {
+        fn load(&self) -> Result<Option<String>, SecretsError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn save(&self, encoded_key: &str) -> Result<(), SecretsError> {
+            *self.0.lock().unwrap() = Some(encoded_key.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stored_key_is_generated_once_and_reused() {
+        let store = MemoryKeyStore::default();
+        let first = stored_cipher(&store).unwrap();
+        assert!(store.0.lock().unwrap().is_some());
+        let sealed = first.encrypt("token").unwrap();
+        assert_eq!(
+            stored_cipher(&store).unwrap().decrypt(&sealed).unwrap(),
+            "token"
+        );
+
+        *store.0.lock().unwrap() = Some("not a key".to_string());
+        assert!(matches!(
+            stored_cipher(&store),
+            Err(SecretsError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn round_trips_and_rejects_other_keys() {
+        let cipher = SecretsCipher::from_key_bytes([7; 32]);
+        let sealed = cipher.encrypt("ghp_secret").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("ghp_secret"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "ghp_secret");
+
+        let other = SecretsCipher::from_key_bytes([8; 32]);
+        assert!(other.decrypt(&sealed).is_err());
+        assert!(cipher.decrypt("ghp_secret").is_err());
+    }
+
+    #[test]
+    fn migrates_plaintext_and_clears_undecryptable_secrets() {
+        let cipher = SecretsCipher::from_key_bytes([7; 32]);
+        let mut config = Config::default();
+        config.github.pat = Some("ghp_secret".to_string());
+        config.webhook_token = Some("hook".to_string());
+
+        let mut opened = config.clone();
+        assert!(open_config(&mut opened, Some(&cipher)));
+        assert!(!open_config(&mut opened.clone(), None));
+
+        seal_config(&mut config, &cipher).unwrap();
+        assert!(is_encrypted(config.github.pat.as_deref().unwrap()));
+        assert!(config.github.oauth_token.is_none());
+
+        let mut opened = config.clone();
+        assert!(!open_config(&mut opened, Some(&cipher)));
+        assert_eq!(opened.github.pat.as_deref(), Some("ghp_secret"));
+        assert_eq!(opened.webhook_token.as_deref(), Some("hook"));
+
+        let mut opened = config.clone();
+        open_config(&mut opened, Some(&SecretsCipher::from_key_bytes([8; 32])));
+        assert!(opened.github.pat.is_none());
+        assert!(opened.webhook_token.is_none());
+    }
+
+    #[test]
+    fn passphrase_key_is_stable_for_the_same_salt() {
+        let dir = tempfile::tempdir().unwrap();
+        let salt_path = dir.path().join(SALT_FILE);
+        let first = passphrase_cipher("correct horse", &salt_path).unwrap();
+        let sealed = first.encrypt("token").unwrap();
+
+        let second = passphrase_cipher("correct horse", &salt_path).unwrap();
+        assert_eq!(second.decrypt(&sealed).unwrap(), "token");
+        let wrong = passphrase_cipher("battery staple", &salt_path).unwrap();
+        assert!(wrong.decrypt(&sealed).is_err());
+    }
+}
@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+
 use anyhow::Error;
+use db::models::{
+    repo_resource_tag::ResourceTag, session_timeout::ExecutionTimeouts,
+    workspace_dev_server::PreviewScriptInjection,
+};
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use uuid::Uuid;
 pub use v7::{
     EditorConfig, EditorType, GitHubConfig, NotificationConfig, ShowcaseState, SoundFile,
     ThemeMode, UiLanguage,
@@ -25,6 +32,10 @@ fn default_relay_enabled() -> bool {
     true
 }
 
+fn default_shutdown_grace_period_secs() -> u32 {
+    30
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
 pub enum SendMessageShortcut {
     #[default]
@@ -32,6 +43,301 @@ pub enum SendMessageShortcut {
     Enter,
 }
 
+/// How to handle uncommitted changes to tracked files when a coding agent is
+/// started, so the agent does not clobber manual edits.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DirtyWorktreePolicy {
+    #[default]
+    Allow,
+    Block,
+    Stash,
+    Confirm,
+}
+
+/// How git worktrees for new workspaces are created.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorktreeStrategy {
+    #[default]
+    Checkout,
+    Reflink,
+}
+
+/// Where executors, scripts and dev servers run.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionBackend {
+    /// Directly on this machine, in the workspace's worktrees.
+    #[default]
+    Local,
+    /// In a Docker container per process, using the image configured for
+    /// the repo, with the workspace mounted at the same path. Unix only, and
+    /// OpenCode, which is reached over a local port, can't run this way.
+    Docker,
+}
+
+/// Where a workspace terminal starts.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalCwd {
+    /// The repo's directory when the workspace has a single repo, otherwise
+    /// the workspace root.
+    #[default]
+    Repo,
+    WorkspaceRoot,
+}
+
+/// How workspace terminals are started.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct TerminalConfig {
+    /// Shell to run, as a path or a name on `PATH`. `None` uses the user's
+    /// login shell, or PowerShell on Windows.
+    #[serde(default)]
+    pub shell: Option<String>,
+    /// Replace the arguments the shell is otherwise started with, e.g.
+    /// `["-l"]` to load login profiles.
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Set on top of the server's environment. `$VAR` and `~` in values are
+    /// expanded, so `PATH` can be extended with `~/.cargo/bin:$PATH`.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub cwd: TerminalCwd,
+    /// Save each terminal's output so it can be replayed later. What is
+    /// typed isn't saved, but anything the terminal shows is.
+    #[serde(default)]
+    pub record: bool,
+}
+
+/// Retention policy for execution process logs on disk. Logs of running
+/// processes are never touched.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct LogRetentionConfig {
+    /// Compress logs older than this many days. `None` disables compression.
+    pub compress_after_days: Option<u32>,
+    /// Delete logs older than this many days. `None` keeps logs forever.
+    pub delete_after_days: Option<u32>,
+    /// Delete the oldest logs once all logs together exceed this many MB.
+    pub max_total_size_mb: Option<u64>,
+}
+
+impl Default for LogRetentionConfig {
+    fn default() -> Self {
+        Self {
+            compress_after_days: Some(7),
+            delete_after_days: None,
+            max_total_size_mb: None,
+        }
+    }
+}
+
+/// Where finished productivity reports are sent. Nothing is sent unless a
+/// webhook URL or email address is set.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct ReportDeliveryConfig {
+    /// Receives each report as a JSON `POST`.
+    pub webhook_url: Option<String>,
+    /// Receives each report as CSV, sent through the local `sendmail`.
+    pub email_to: Option<String>,
+    pub weekly: bool,
+    pub monthly: bool,
+}
+
+impl Default for ReportDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            email_to: None,
+            weekly: true,
+            monthly: false,
+        }
+    }
+}
+
+/// Coding agent executions allowed to run at once among those whose repos
+/// carry each resource tag. Unlimited when unset. An execution at its tag's
+/// limit waits while executions with other tags go ahead of it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct ExecutionTagLimits {
+    pub cpu_heavy: Option<usize>,
+    pub gpu: Option<usize>,
+    pub network: Option<usize>,
+}
+
+impl ExecutionTagLimits {
+    pub fn limit(&self, tag: ResourceTag) -> Option<usize> {
+        match tag {
+            ResourceTag::CpuHeavy => self.cpu_heavy,
+            ResourceTag::Gpu => self.gpu,
+            ResourceTag::Network => self.network,
+        }
+    }
+}
+
+/// Per-client request limits for routes a runaway client could abuse. Limits
+/// are requests per minute, counted separately for each relay signing
+/// session or API token.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct RouteRateLimitConfig {
+    pub enabled: bool,
+    /// Creating workspaces and sessions, sending follow-ups and webhook tasks.
+    pub task_creation_per_minute: u32,
+    /// Stopping execution processes and dev servers.
+    pub process_control_per_minute: u32,
+    /// Writing files in workspace worktrees.
+    pub file_writes_per_minute: u32,
+}
+
+impl Default for RouteRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            task_creation_per_minute: 30,
+            process_control_per_minute: 60,
+            file_writes_per_minute: 120,
+        }
+    }
+}
+
+/// Reusable prompt text that can be appended to follow-ups. `{{branch}}`,
+/// `{{repo}}`, `{{workspace}}` and `{{failing_test}}` are substituted when the
+/// follow-up is sent.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct PromptSnippet {
+    pub name: String,
+    pub content: String,
+    /// Only offered in workspaces of this project. Global when unset; a
+    /// project snippet shadows a global one with the same name.
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+/// Timeouts for coding agent runs. A session's own timeouts take precedence,
+/// then the entry for its executor, then `default`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct ExecutorTimeoutConfig {
+    #[serde(default)]
+    pub default: ExecutionTimeouts,
+    #[serde(default)]
+    pub per_executor: HashMap<BaseCodingAgent, ExecutionTimeouts>,
+}
+
+/// Which kinds of anonymous analytics the user agreed to send.
+#[derive(Clone, Copy, Debug, Serialize, TS, PartialEq, Eq)]
+pub struct AnalyticsConsent {
+    /// Which features are used, e.g. workspaces created or PRs opened.
+    pub usage: bool,
+    /// Error reports, and failed or timed out agent runs.
+    pub errors: bool,
+    /// How long agent runs take.
+    pub performance: bool,
+}
+
+impl AnalyticsConsent {
+    pub fn all(enabled: bool) -> Self {
+        Self {
+            usage: enabled,
+            errors: enabled,
+            performance: enabled,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.usage || self.errors || self.performance
+    }
+}
+
+impl Default for AnalyticsConsent {
+    fn default() -> Self {
+        Self::all(true)
+    }
+}
+
+// Earlier v8 configs stored a single `analytics_enabled` flag, which now
+// applies to every category.
+impl<'de> Deserialize<'de> for AnalyticsConsent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Enabled(bool),
+            Categories {
+                usage: bool,
+                errors: bool,
+                performance: bool,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Enabled(enabled) => Self::all(enabled),
+            Raw::Categories {
+                usage,
+                errors,
+                performance,
+            } => Self {
+                usage,
+                errors,
+                performance,
+            },
+        })
+    }
+}
+
+/// Which notifications are sent at all. Applies to every channel.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct NotificationEventToggles {
+    pub approvals: bool,
+    pub questions: bool,
+    /// A coding agent run in a workspace finished, failed or timed out.
+    pub workspace_complete: bool,
+}
+
+impl Default for NotificationEventToggles {
+    fn default() -> Self {
+        Self {
+            approvals: true,
+            questions: true,
+            workspace_complete: true,
+        }
+    }
+}
+
+/// Local times, as `HH:MM`, between which desktop and sound notifications
+/// are held back. A window may wrap past midnight, e.g. `22:00` to `07:00`.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// How desktop and sound notifications are paced. Slack and webhook
+/// channels are only subject to `events`.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct NotificationDeliveryConfig {
+    /// After a notification, further ones within this many seconds are
+    /// collapsed into one summary. `0` sends each on its own.
+    pub batch_window_secs: u32,
+    pub events: NotificationEventToggles,
+    /// Notifications held back during quiet hours are summarized once they
+    /// end.
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for NotificationDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            batch_window_secs: 30,
+            events: NotificationEventToggles::default(),
+            quiet_hours: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -44,7 +350,12 @@ pub struct Config {
     pub notifications: NotificationConfig,
     pub editor: EditorConfig,
     pub github: GitHubConfig,
-    pub analytics_enabled: bool,
+    #[serde(alias = "analytics_enabled")]
+    pub analytics: AnalyticsConsent,
+    /// Send error reports without message text or fields, for setups where
+    /// even scrubbed repo contents mustn't leave the machine.
+    #[serde(default)]
+    pub error_reports_metadata_only: bool,
     pub workspace_dir: Option<String>,
     pub last_app_version: Option<String>,
     pub show_release_notes: bool,
@@ -68,12 +379,64 @@ pub struct Config {
     pub relay_enabled: bool,
     #[serde(default)]
     pub host_nickname: Option<String>,
+    /// Shared secret for `POST /api/hooks/task`. Inbound webhooks are rejected
+    /// while this is unset. The config API returns it redacted.
+    #[serde(default)]
+    pub webhook_token: Option<String>,
+    #[serde(default)]
+    pub log_retention: LogRetentionConfig,
+    /// What to do when a coding agent is started on a worktree with
+    /// uncommitted changes. `stash` restores them once the run finishes;
+    /// `confirm` requires `allow_dirty_worktree` on the request.
+    #[serde(default)]
+    pub dirty_worktree_policy: DirtyWorktreePolicy,
+    /// Coding agent executions allowed to run at once across all projects.
+    /// Further executions wait and are admitted fairly between projects.
+    /// Unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent_executions: Option<usize>,
+    #[serde(default)]
+    pub execution_tag_limits: ExecutionTagLimits,
+    #[serde(default)]
+    pub prompt_snippets: Vec<PromptSnippet>,
+    #[serde(default)]
+    pub report_delivery: ReportDeliveryConfig,
+    /// Scripts the preview proxy injects into dev server pages. Workspaces
+    /// can override it for their dev server port.
+    #[serde(default)]
+    pub preview_script_injection: PreviewScriptInjection,
+    /// Coding agent runs exceeding these are killed and marked timed out.
+    #[serde(default)]
+    pub executor_timeouts: ExecutorTimeoutConfig,
+    /// On shutdown, how long running coding agents get to finish before
+    /// their work is committed and they are stopped as interrupted.
+    #[serde(default = "default_shutdown_grace_period_secs")]
+    pub shutdown_grace_period_secs: u32,
+    /// `reflink` clones new worktrees from the repo's checkout, including
+    /// ignored files such as `node_modules`, on filesystems with
+    /// copy-on-write support (APFS, Btrfs, XFS). Falls back to `checkout`
+    /// elsewhere.
+    #[serde(default)]
+    pub worktree_strategy: WorktreeStrategy,
+    /// Rebase workspace branches that have fallen behind their target onto
+    /// it while no process is running in the workspace. Rebases that would
+    /// conflict are aborted and left for a manual rebase.
+    #[serde(default)]
+    pub auto_rebase_idle_workspaces: bool,
+    #[serde(default)]
+    pub route_rate_limits: RouteRateLimitConfig,
+    #[serde(default)]
+    pub notification_delivery: NotificationDeliveryConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub execution_backend: ExecutionBackend,
 }
 
 impl Config {
     fn from_v7_config(old_config: v7::Config) -> Self {
-        // Convert Option<bool> to bool: None or Some(true) become true, Some(false) stays false
-        let analytics_enabled = old_config.analytics_enabled.unwrap_or(true);
+        // None or Some(true) consent to every category, Some(false) to none
+        let analytics = AnalyticsConsent::all(old_config.analytics_enabled.unwrap_or(true));
 
         Self {
             config_version: "v8".to_string(),
@@ -85,7 +448,8 @@ impl Config {
             notifications: old_config.notifications,
             editor: old_config.editor,
             github: old_config.github,
-            analytics_enabled,
+            analytics,
+            error_reports_metadata_only: false,
             workspace_dir: old_config.workspace_dir,
             last_app_version: old_config.last_app_version,
             show_release_notes: old_config.show_release_notes,
@@ -99,6 +463,22 @@ impl Config {
             send_message_shortcut: SendMessageShortcut::default(),
             relay_enabled: true,
             host_nickname: None,
+            webhook_token: None,
+            log_retention: LogRetentionConfig::default(),
+            dirty_worktree_policy: DirtyWorktreePolicy::default(),
+            max_concurrent_executions: None,
+            execution_tag_limits: ExecutionTagLimits::default(),
+            prompt_snippets: Vec::new(),
+            report_delivery: ReportDeliveryConfig::default(),
+            preview_script_injection: PreviewScriptInjection::default(),
+            executor_timeouts: ExecutorTimeoutConfig::default(),
+            shutdown_grace_period_secs: default_shutdown_grace_period_secs(),
+            worktree_strategy: WorktreeStrategy::default(),
+            auto_rebase_idle_workspaces: false,
+            route_rate_limits: RouteRateLimitConfig::default(),
+            notification_delivery: NotificationDeliveryConfig::default(),
+            terminal: TerminalConfig::default(),
+            execution_backend: ExecutionBackend::default(),
         }
     }
 
@@ -141,7 +521,8 @@ impl Default for Config {
             notifications: NotificationConfig::default(),
             editor: EditorConfig::default(),
             github: GitHubConfig::default(),
-            analytics_enabled: true,
+            analytics: AnalyticsConsent::default(),
+            error_reports_metadata_only: false,
             workspace_dir: None,
             last_app_version: None,
             show_release_notes: false,
@@ -155,6 +536,22 @@ impl Default for Config {
             send_message_shortcut: SendMessageShortcut::default(),
             relay_enabled: true,
             host_nickname: None,
+            webhook_token: None,
+            log_retention: LogRetentionConfig::default(),
+            dirty_worktree_policy: DirtyWorktreePolicy::default(),
+            max_concurrent_executions: None,
+            execution_tag_limits: ExecutionTagLimits::default(),
+            prompt_snippets: Vec::new(),
+            report_delivery: ReportDeliveryConfig::default(),
+            preview_script_injection: PreviewScriptInjection::default(),
+            executor_timeouts: ExecutorTimeoutConfig::default(),
+            shutdown_grace_period_secs: default_shutdown_grace_period_secs(),
+            worktree_strategy: WorktreeStrategy::default(),
+            auto_rebase_idle_workspaces: false,
+            route_rate_limits: RouteRateLimitConfig::default(),
+            notification_delivery: NotificationDeliveryConfig::default(),
+            terminal: TerminalConfig::default(),
+            execution_backend: ExecutionBackend::default(),
         }
     }
 }
@@ -2,6 +2,7 @@ use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Error as AnyhowError, anyhow};
@@ -18,9 +19,13 @@ use db::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
         repo::Repo,
+        repo_check_command::RepoCheckCommand,
         session::{CreateSession, Session, SessionError},
         workspace::{Workspace, WorkspaceError},
+        workspace_check_result::{WorkspaceCheckResult, WorkspaceCheckStatus},
+        workspace_dev_server::WorkspaceDevServer,
         workspace_repo::WorkspaceRepo,
+        workspace_repo_command::RepoCommandMode,
     },
 };
 #[cfg(feature = "qa-mode")]
@@ -36,6 +41,7 @@ use executors::{
     executors::{ExecutorError, StandardCodingAgentExecutor},
     logs::{
         NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        spans::spans_from_patches,
         utils::{
             ConversationPatch,
             patch::{fix_patch_ops, is_add_or_replace, patch_entry_path},
@@ -50,16 +56,48 @@ use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
 use utils::{
-    log_msg::LogMsg,
+    execution_logs::execution_logs_root,
+    log_msg::{LogMsg, LogSpan},
     msg_store::MsgStore,
+    process::ProcessSignal,
+    process_tree::ProcessTreeNode,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 use worktree_manager::WorktreeError;
 
-use crate::services::{execution_process, notification::NotificationService};
+use crate::services::{
+    config::DirtyWorktreePolicy,
+    execution_process,
+    notification::{NotificationEvent, NotificationService},
+};
 pub type ContainerRef = String;
 
+/// Written to the logs root once every stored log has its spans.
+const SPANS_BACKFILLED_MARKER: &str = ".spans-backfilled";
+
+/// Stash message for manual edits put aside before a session's coding agent
+/// ran; they are restored once the run finishes.
+pub fn pre_execution_stash_message(session_id: Uuid) -> String {
+    format!("vibe-kanban: before execution in session {session_id}")
+}
+
+/// Normalized patches of a store filled by [`ContainerService::normalize_stored_logs`],
+/// up to the `LogMsg::Ready` its normalizers push when done.
+async fn collect_normalized_patches(store: &MsgStore) -> Vec<Patch> {
+    store
+        .history_plus_stream()
+        .take_while(|msg| future::ready(!matches!(msg, Ok(LogMsg::Ready))))
+        .filter_map(|msg| async move {
+            match msg {
+                Ok(LogMsg::JsonPatch(patch)) => Some(patch),
+                _ => None,
+            }
+        })
+        .collect()
+        .await
+}
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -80,6 +118,8 @@ pub enum ContainerError {
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
     KillFailed(std::io::Error),
+    #[error("The server is shutting down")]
+    ShuttingDown,
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
@@ -194,18 +234,33 @@ pub trait ContainerService {
 
     async fn kill_all_running_processes(&self) -> Result<(), ContainerError>;
 
+    /// Stop starting executions and give running coding agents up to
+    /// `grace_period` to finish. Agents still running afterwards have their
+    /// work committed and are stopped as interrupted; other processes are
+    /// killed.
+    async fn drain_running_processes(&self, grace_period: Duration) -> Result<(), ContainerError>;
+
+    /// Whether shutdown has started and new executions are refused.
+    fn is_draining(&self) -> bool;
+
+    /// How uncommitted changes are handled when a coding agent starts.
+    async fn dirty_worktree_policy(&self) -> DirtyWorktreePolicy;
+
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError>;
 
     /// A context is finalized when
     /// - Always when the execution process has failed or been killed
-    /// - Never when the run reason is DevServer
+    /// - Never when it was interrupted by shutdown, since it will be resumed
+    /// - Never when the run reason is DevServer, RepoCommand or ChangeExplanation
     /// - Never when a setup script has no next_action (parallel mode)
     /// - The next action is None (no follow-up actions)
     fn should_finalize(&self, ctx: &ExecutionContext) -> bool {
-        // Never finalize DevServer processes
+        // Never finalize DevServer processes, repo commands or change explanations
         if matches!(
             ctx.execution_process.run_reason,
             ExecutionProcessRunReason::DevServer
+                | ExecutionProcessRunReason::RepoCommand
+                | ExecutionProcessRunReason::ChangeExplanation
         ) {
             return false;
         }
@@ -222,10 +277,24 @@ pub trait ContainerService {
             return false;
         }
 
-        // Always finalize failed or killed executions, regardless of next action
+        if ctx.execution_process.status == ExecutionProcessStatus::Interrupted {
+            return false;
+        }
+
+        // A failed check doesn't stop the checks after it
+        if ctx.execution_process.run_reason == ExecutionProcessRunReason::CheckCommand
+            && ctx.execution_process.status == ExecutionProcessStatus::Failed
+            && action.next_action.is_some()
+        {
+            return false;
+        }
+
+        // Always finalize failed, killed or timed out executions, regardless of next action
         if matches!(
             ctx.execution_process.status,
-            ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+            ExecutionProcessStatus::Failed
+                | ExecutionProcessStatus::Killed
+                | ExecutionProcessStatus::TimedOut
         ) {
             return true;
         }
@@ -234,8 +303,89 @@ pub trait ContainerService {
         action.next_action.is_none()
     }
 
+    /// Apply the configured [`DirtyWorktreePolicy`] before a coding agent
+    /// starts in `workspace_root`, so it does not clobber manual edits.
+    /// `confirmed` is the user's go-ahead under the `confirm` policy.
+    async fn guard_dirty_worktree(
+        &self,
+        workspace_id: Uuid,
+        workspace_root: &Path,
+        session_id: Uuid,
+        confirmed: bool,
+    ) -> Result<(), ContainerError> {
+        let policy = self.dirty_worktree_policy().await;
+        if policy == DirtyWorktreePolicy::Allow
+            || (policy == DirtyWorktreePolicy::Confirm && confirmed)
+        {
+            return Ok(());
+        }
+
+        let repos = WorkspaceRepo::find_repos_for_workspace(&self.db().pool, workspace_id).await?;
+        let stash_message = pre_execution_stash_message(session_id);
+        for repo in repos {
+            let repo_path = workspace_root.join(&repo.name);
+            if policy == DirtyWorktreePolicy::Stash {
+                self.git().stash_changes(&repo_path, &stash_message)?;
+            } else {
+                self.git().ensure_worktree_clean(&repo_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore changes stashed by the dirty worktree guard during this
+    /// session's runs. Queued follow-ups stash once per run, so every stash
+    /// of the session is popped, newest first.
+    fn restore_pre_execution_stash(&self, ctx: &ExecutionContext) {
+        let Some(workspace_root) = ctx.workspace.container_ref.as_ref().map(PathBuf::from) else {
+            return;
+        };
+        let message = pre_execution_stash_message(ctx.session.id);
+        for repo in &ctx.repos {
+            let repo_path = workspace_root.join(&repo.name);
+            loop {
+                match self.git().pop_stash(&repo_path, &message) {
+                    Ok(true) => tracing::info!(
+                        "Restored stashed changes in {} after execution",
+                        repo_path.display()
+                    ),
+                    Ok(false) => break,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to restore stashed changes in {}; they remain in `git stash list`: {}",
+                            repo_path.display(),
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
     /// Finalize workspace execution by sending notifications
     async fn finalize_task(&self, ctx: &ExecutionContext) {
+        // The workspace is only ready for review once the repo checks have
+        // run; the last check finalizes it.
+        if ctx.execution_process.status == ExecutionProcessStatus::Completed
+            && matches!(
+                ctx.execution_process.run_reason,
+                ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript
+            )
+        {
+            match self.start_check_commands(ctx).await {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => tracing::error!(
+                    "Failed to start checks for workspace {}: {}",
+                    ctx.workspace.id,
+                    e
+                ),
+            }
+        }
+
+        self.restore_pre_execution_stash(ctx);
+
         // Skip notification if process was intentionally killed by user
         if matches!(ctx.execution_process.status, ExecutionProcessStatus::Killed) {
             return;
@@ -247,29 +397,62 @@ pub trait ContainerService {
             .as_deref()
             .unwrap_or(&ctx.workspace.branch);
         let title = format!("Workspace Complete: {}", workspace_name);
-        let message = match ctx.execution_process.status {
-            ExecutionProcessStatus::Completed => format!(
-                "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {:?}",
-                workspace_name, ctx.workspace.branch, ctx.session.executor
-            ),
-            ExecutionProcessStatus::Failed => format!(
-                "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {:?}",
-                workspace_name, ctx.workspace.branch, ctx.session.executor
-            ),
-            _ => {
-                tracing::warn!(
-                    "Tried to notify workspace completion for {} but process is still running!",
-                    ctx.workspace.id
-                );
-                return;
+        let failed_checks: Vec<String> =
+            if ctx.execution_process.run_reason == ExecutionProcessRunReason::CheckCommand {
+                WorkspaceCheckResult::find_by_workspace_id(&self.db().pool, ctx.workspace.id)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|check| check.status == WorkspaceCheckStatus::Failed)
+                    .map(|check| check.name)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        let message = if !failed_checks.is_empty() {
+            format!(
+                "❌ '{}' failed checks: {}\nBranch: {:?}\nExecutor: {:?}",
+                workspace_name,
+                failed_checks.join(", "),
+                ctx.workspace.branch,
+                ctx.session.executor
+            )
+        } else {
+            match ctx.execution_process.status {
+                ExecutionProcessStatus::Completed => format!(
+                    "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {:?}",
+                    workspace_name, ctx.workspace.branch, ctx.session.executor
+                ),
+                ExecutionProcessStatus::Failed => format!(
+                    "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {:?}",
+                    workspace_name, ctx.workspace.branch, ctx.session.executor
+                ),
+                ExecutionProcessStatus::TimedOut => format!(
+                    "⏱️ '{}' execution timed out\nBranch: {:?}\nExecutor: {:?}",
+                    workspace_name, ctx.workspace.branch, ctx.session.executor
+                ),
+                _ => {
+                    tracing::warn!(
+                        "Tried to notify workspace completion for {} but process is still running!",
+                        ctx.workspace.id
+                    );
+                    return;
+                }
             }
         };
         self.notification_service()
-            .notify(&title, &message, Some(ctx.workspace.id))
+            .notify(
+                NotificationEvent::WorkspaceComplete,
+                &title,
+                &message,
+                Some(ctx.workspace.id),
+            )
             .await;
     }
 
-    /// Cleanup executions marked as running in the db, call at startup
+    /// Cleanup executions marked as running in the db, call at startup.
+    /// Coding agents cut off by a crash are marked interrupted so their
+    /// sessions can be resumed.
     async fn cleanup_orphan_executions(&self) -> Result<(), ContainerError> {
         let running_processes = ExecutionProcess::find_running(&self.db().pool).await?;
         for process in running_processes {
@@ -278,11 +461,16 @@ pub trait ContainerService {
                 process.id,
                 process.session_id
             );
+            let status = if process.run_reason == ExecutionProcessRunReason::CodingAgent {
+                ExecutionProcessStatus::Interrupted
+            } else {
+                ExecutionProcessStatus::Failed
+            };
             // Update the execution process status first
             if let Err(e) = ExecutionProcess::update_completion(
                 &self.db().pool,
                 process.id,
-                ExecutionProcessStatus::Failed,
+                status.clone(),
                 None, // No exit code for orphaned processes
             )
             .await
@@ -319,9 +507,13 @@ pub trait ContainerService {
                     }
                 }
             }
-            // Process marked as failed
-            tracing::info!("Marked orphaned execution process {} as failed", process.id);
+            tracing::info!(
+                "Marked orphaned execution process {} as {:?}",
+                process.id,
+                status
+            );
         }
+        Workspace::clear_all_dev_server_ports(&self.db().pool).await?;
         Ok(())
     }
 
@@ -440,6 +632,75 @@ pub trait ContainerService {
         Some(root_action)
     }
 
+    /// A chain running `checks` one after the other, each in its repo. Unlike
+    /// other chains, a failed check doesn't stop the ones after it.
+    fn check_command_actions(
+        repos: &[Repo],
+        checks: &[RepoCheckCommand],
+    ) -> Option<ExecutorAction> {
+        checks.iter().rev().fold(None, |next, check| {
+            let working_dir = repos
+                .iter()
+                .find(|repo| repo.id == check.repo_id)
+                .map(|repo| repo.name.clone());
+            Some(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: check.command.clone(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::CheckCommand,
+                    working_dir,
+                }),
+                next.map(Box::new),
+            ))
+        })
+    }
+
+    /// Start a check run of the repo checks of the workspace, replacing its
+    /// previous results. Returns false if its repos have no checks.
+    async fn start_check_commands(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
+        let pool = &self.db().pool;
+        let mut checks = Vec::new();
+        for repo in &ctx.repos {
+            checks.extend(RepoCheckCommand::find_by_repo_id(pool, repo.id).await?);
+        }
+        let Some(action) = Self::check_command_actions(&ctx.repos, &checks) else {
+            return Ok(false);
+        };
+
+        WorkspaceCheckResult::start(pool, ctx.workspace.id, &checks).await?;
+        self.start_execution(
+            &ctx.workspace,
+            &ctx.session,
+            &action,
+            &ExecutionProcessRunReason::CheckCommand,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Record the outcome of a finished check process. Stopping a check
+    /// skips it and the checks after it.
+    async fn record_check_result(&self, ctx: &ExecutionContext) {
+        let pool = &self.db().pool;
+        let process = &ctx.execution_process;
+        let result = match process.status {
+            ExecutionProcessStatus::Running => return,
+            ExecutionProcessStatus::Completed | ExecutionProcessStatus::Failed => {
+                let passed = process.status == ExecutionProcessStatus::Completed
+                    && process.exit_code == Some(0);
+                WorkspaceCheckResult::complete(pool, process.id, passed, process.exit_code).await
+            }
+            _ => WorkspaceCheckResult::skip_unfinished(pool, ctx.workspace.id).await,
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to record check result for {}: {}", process.id, e);
+        }
+        // The workspace stream carries the results
+        if let Err(e) = Workspace::touch(pool, ctx.workspace.id).await {
+            tracing::warn!("Failed to touch workspace {}: {}", ctx.workspace.id, e);
+        }
+    }
+
     fn archive_actions_for_repos(&self, repos: &[Repo]) -> Option<ExecutorAction> {
         let repos_with_archive: Vec<_> = repos
             .iter()
@@ -594,6 +855,87 @@ pub trait ContainerService {
         Some(root_action)
     }
 
+    /// One dev server action per repo with a dev server script, or a single
+    /// action running the workspace's override script instead. An override
+    /// port is exported as `PORT` so the server listens where the preview
+    /// expects it.
+    fn dev_server_actions(
+        &self,
+        repos: &[Repo],
+        dev_server: Option<&WorkspaceDevServer>,
+    ) -> Vec<ExecutorAction> {
+        let port_prefix = dev_server
+            .and_then(|d| d.port)
+            .map(|port| format!("export PORT={port}\n"))
+            .unwrap_or_default();
+        let action = |script: &str, working_dir: Option<String>| {
+            ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: format!("{port_prefix}{script}"),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::DevServer,
+                    working_dir,
+                }),
+                None,
+            )
+        };
+
+        if let Some(script) = dev_server
+            .and_then(|d| d.script.as_deref())
+            .filter(|s| !s.is_empty())
+        {
+            // Single-repo workspaces run the override inside the repo, like
+            // the script it replaces; otherwise it runs from the workspace root.
+            let working_dir = match repos {
+                [repo] => Some(repo.name.clone()),
+                _ => None,
+            };
+            return vec![action(script, working_dir)];
+        }
+
+        repos
+            .iter()
+            .filter_map(|repo| {
+                repo.dev_server_script
+                    .as_deref()
+                    .filter(|s| !s.is_empty())
+                    .map(|script| action(script, Some(repo.name.clone())))
+            })
+            .collect()
+    }
+
+    /// Actions running `script` in each of `repos`: a single chain for
+    /// sequential mode, where a failure stops the remaining repos, or one
+    /// action per repo to start side by side for parallel mode.
+    fn repo_command_actions(
+        &self,
+        repos: &[Repo],
+        script: &str,
+        mode: RepoCommandMode,
+    ) -> Vec<ExecutorAction> {
+        let action = |repo: &Repo, next_action: Option<Box<ExecutorAction>>| {
+            ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: script.to_string(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::RepoCommand,
+                    working_dir: Some(repo.name.clone()),
+                }),
+                next_action,
+            )
+        };
+
+        match mode {
+            RepoCommandMode::Parallel => repos.iter().map(|repo| action(repo, None)).collect(),
+            RepoCommandMode::Sequential => repos
+                .iter()
+                .rev()
+                .fold(None, |next, repo| Some(action(repo, next.map(Box::new))))
+                .into_iter()
+                .collect(),
+        }
+    }
+
     fn setup_action_for_repo(repo: &Repo) -> Option<ExecutorAction> {
         repo.setup_script.as_ref().map(|script| {
             ExecutorAction::new(
@@ -759,6 +1101,20 @@ pub trait ContainerService {
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError>;
 
+    /// Send `signal` to every process a running execution has started.
+    async fn signal_execution(
+        &self,
+        execution_process: &ExecutionProcess,
+        signal: ProcessSignal,
+    ) -> Result<(), ContainerError>;
+
+    /// The OS processes a running execution has started, rooted at the one
+    /// spawned for it. `None` once it has exited.
+    async fn process_tree(
+        &self,
+        execution_process: &ExecutionProcess,
+    ) -> Result<Option<ProcessTreeNode>, ContainerError>;
+
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
 
     async fn copy_project_files(
@@ -827,162 +1183,285 @@ pub trait ContainerService {
         }
     }
 
-    async fn stream_normalized_logs(
-        &self,
-        id: &Uuid,
-    ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
-        // First try in-memory store (existing behavior)
-        if let Some(store) = self.get_msg_store_by_id(id).await {
-            Some(
-                store
-                    .history_plus_stream() // BoxStream<Result<LogMsg, io::Error>>
-                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
-                    .chain(futures::stream::once(async {
-                        Ok::<_, std::io::Error>(LogMsg::Finished)
-                    }))
-                    .boxed(),
-            )
-        } else {
-            let raw_messages =
-                execution_process::load_raw_log_messages(&self.db().pool, *id).await?;
-
-            // Create temporary store and populate
-            // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
-            let temp_store = Arc::new(MsgStore::new());
-            for msg in raw_messages {
-                if matches!(
-                    msg,
-                    LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
-                ) {
-                    temp_store.push(msg);
-                }
+    /// Re-run log normalization for a process whose logs are no longer in
+    /// memory. The returned store receives `LogMsg::Ready` once every
+    /// normalizer has finished.
+    async fn normalize_stored_logs(&self, id: &Uuid) -> Option<Arc<MsgStore>> {
+        let raw_messages = execution_process::load_raw_log_messages(&self.db().pool, *id).await?;
+
+        let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
+            Ok(Some(process)) => process,
+            Ok(None) => {
+                tracing::error!("No execution process found for ID: {}", id);
+                return None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to fetch execution process {}: {}", id, e);
+                return None;
             }
-            temp_store.push_finished();
+        };
 
-            let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
-                Ok(Some(process)) => process,
+        // Get the workspace to determine correct directory
+        let (workspace, _session) =
+            match process.parent_workspace_and_session(&self.db().pool).await {
+                Ok(Some((workspace, session))) => (workspace, session),
                 Ok(None) => {
-                    tracing::error!("No execution process found for ID: {}", id);
+                    tracing::error!(
+                        "No workspace/session found for session ID: {}",
+                        process.session_id
+                    );
                     return None;
                 }
                 Err(e) => {
-                    tracing::error!("Failed to fetch execution process {}: {}", id, e);
+                    tracing::error!(
+                        "Failed to fetch workspace for session {}: {}",
+                        process.session_id,
+                        e
+                    );
                     return None;
                 }
             };
 
-            // Get the workspace to determine correct directory
-            let (workspace, _session) =
-                match process.parent_workspace_and_session(&self.db().pool).await {
-                    Ok(Some((workspace, session))) => (workspace, session),
-                    Ok(None) => {
-                        tracing::error!(
-                            "No workspace/session found for session ID: {}",
-                            process.session_id
-                        );
-                        return None;
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to fetch workspace for session {}: {}",
-                            process.session_id,
-                            e
-                        );
-                        return None;
-                    }
-                };
+        if let Err(err) = self.ensure_container_exists(&workspace).await {
+            tracing::warn!(
+                "Failed to recreate worktree before log normalization for workspace {}: {}",
+                workspace.id,
+                err
+            );
+        }
 
-            if let Err(err) = self.ensure_container_exists(&workspace).await {
-                tracing::warn!(
-                    "Failed to recreate worktree before log normalization for workspace {}: {}",
-                    workspace.id,
-                    err
-                );
+        self.normalize_log_messages(&process, &workspace, raw_messages)
+    }
+
+    /// Run the process's log normalizer over its stored raw messages. The
+    /// returned store receives `LogMsg::Ready` once every normalizer has
+    /// finished.
+    fn normalize_log_messages(
+        &self,
+        process: &ExecutionProcess,
+        workspace: &Workspace,
+        raw_messages: Vec<LogMsg>,
+    ) -> Option<Arc<MsgStore>> {
+        // Create temporary store and populate
+        // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
+        let temp_store = Arc::new(MsgStore::new());
+        for msg in raw_messages {
+            if matches!(
+                msg,
+                LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
+            ) {
+                temp_store.push(msg);
             }
+        }
+        temp_store.push_finished();
 
-            let current_dir = self.workspace_to_current_dir(&workspace);
+        let current_dir = self.workspace_to_current_dir(workspace);
 
-            let executor_action = if let Ok(executor_action) = process.executor_action() {
-                executor_action
-            } else {
-                tracing::error!(
-                    "Failed to parse executor action: {:?}",
-                    process.executor_action()
-                );
-                return None;
-            };
+        let executor_action = if let Ok(executor_action) = process.executor_action() {
+            executor_action
+        } else {
+            tracing::error!(
+                "Failed to parse executor action: {:?}",
+                process.executor_action()
+            );
+            return None;
+        };
 
-            // Spawn normalizer on populated store and collect JoinHandles
-            let handles = match executor_action.typ() {
-                ExecutorActionType::CodingAgentInitialRequest(request) => {
-                    #[cfg(feature = "qa-mode")]
-                    {
-                        let executor = QaMockExecutor;
-                        executor.normalize_logs(
-                            temp_store.clone(),
-                            &request.effective_dir(&current_dir),
-                        )
-                    }
-                    #[cfg(not(feature = "qa-mode"))]
-                    {
-                        let executor = ExecutorConfigs::get_cached()
-                            .get_coding_agent_or_default(&request.executor_config.profile_id());
-                        executor.normalize_logs(
-                            temp_store.clone(),
-                            &request.effective_dir(&current_dir),
-                        )
-                    }
+        // Spawn normalizer on populated store and collect JoinHandles
+        let handles = match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                #[cfg(feature = "qa-mode")]
+                {
+                    let executor = QaMockExecutor;
+                    executor
+                        .normalize_logs(temp_store.clone(), &request.effective_dir(&current_dir))
                 }
-                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-                    #[cfg(feature = "qa-mode")]
-                    {
-                        let executor = QaMockExecutor;
-                        executor.normalize_logs(
-                            temp_store.clone(),
-                            &request.effective_dir(&current_dir),
-                        )
-                    }
-                    #[cfg(not(feature = "qa-mode"))]
-                    {
-                        let executor = ExecutorConfigs::get_cached()
-                            .get_coding_agent_or_default(&request.executor_config.profile_id());
-                        executor.normalize_logs(
-                            temp_store.clone(),
-                            &request.effective_dir(&current_dir),
-                        )
-                    }
+                #[cfg(not(feature = "qa-mode"))]
+                {
+                    let executor = ExecutorConfigs::get_cached()
+                        .get_coding_agent_or_default(&request.executor_config.profile_id());
+                    executor
+                        .normalize_logs(temp_store.clone(), &request.effective_dir(&current_dir))
                 }
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
                 #[cfg(feature = "qa-mode")]
-                ExecutorActionType::ReviewRequest(_request) => {
+                {
                     let executor = QaMockExecutor;
-                    executor.normalize_logs(temp_store.clone(), &current_dir)
+                    executor
+                        .normalize_logs(temp_store.clone(), &request.effective_dir(&current_dir))
                 }
                 #[cfg(not(feature = "qa-mode"))]
-                ExecutorActionType::ReviewRequest(request) => {
+                {
                     let executor = ExecutorConfigs::get_cached()
                         .get_coding_agent_or_default(&request.executor_config.profile_id());
-                    executor.normalize_logs(temp_store.clone(), &current_dir)
+                    executor
+                        .normalize_logs(temp_store.clone(), &request.effective_dir(&current_dir))
                 }
-                _ => {
-                    tracing::debug!(
-                        "Executor action doesn't support log normalization: {:?}",
-                        process.executor_action()
-                    );
-                    return None;
+            }
+            #[cfg(feature = "qa-mode")]
+            ExecutorActionType::ReviewRequest(_request) => {
+                let executor = QaMockExecutor;
+                executor.normalize_logs(temp_store.clone(), &current_dir)
+            }
+            #[cfg(not(feature = "qa-mode"))]
+            ExecutorActionType::ReviewRequest(request) => {
+                let executor = ExecutorConfigs::get_cached()
+                    .get_coding_agent_or_default(&request.executor_config.profile_id());
+                executor.normalize_logs(temp_store.clone(), &current_dir)
+            }
+            _ => {
+                tracing::debug!(
+                    "Executor action doesn't support log normalization: {:?}",
+                    process.executor_action()
+                );
+                return None;
+            }
+        };
+
+        // Await all normalizer tasks, then push Ready so the dedup
+        // stream knows when to flush its buffer and terminate.
+        {
+            let store = temp_store.clone();
+            tokio::spawn(async move {
+                for handle in handles {
+                    let _ = handle.await;
                 }
-            };
+                store.push(LogMsg::Ready);
+            });
+        }
 
-            // Await all normalizer tasks, then push Ready so the dedup
-            // stream knows when to flush its buffer and terminate.
-            {
-                let store = temp_store.clone();
-                tokio::spawn(async move {
-                    for handle in handles {
-                        let _ = handle.await;
-                    }
-                    store.push(LogMsg::Ready);
-                });
+        Some(temp_store)
+    }
+
+    /// Every normalized log patch of a process, in order and without the
+    /// deduplication applied when streaming, so intermediate states such as
+    /// pending approvals are kept.
+    async fn normalized_log_patches(&self, id: &Uuid) -> Option<Vec<Patch>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(
+                store
+                    .get_history()
+                    .into_iter()
+                    .filter_map(|msg| match msg {
+                        LogMsg::JsonPatch(patch) => Some(patch),
+                        _ => None,
+                    })
+                    .collect(),
+            );
+        }
+
+        let temp_store = self.normalize_stored_logs(id).await?;
+        Some(collect_normalized_patches(&temp_store).await)
+    }
+
+    /// Structured spans of a process's log.
+    async fn log_spans(&self, id: &Uuid) -> Result<Vec<LogSpan>, ContainerError> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Ok(store
+                .get_history()
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    LogMsg::Span(span) => Some(span),
+                    _ => None,
+                })
+                .collect());
+        }
+        Ok(execution_process::load_log_spans(&self.db().pool, *id).await?)
+    }
+
+    /// One-shot migration that adds spans to logs written before spans
+    /// existed, whether they are plain files, compressed by log retention or
+    /// still in the legacy DB table. Runs at startup before log retention
+    /// starts compressing logs; a marker file records that it completed, and
+    /// it runs again on the next start if any log failed.
+    async fn backfill_log_spans(&self) -> Result<(), ContainerError> {
+        let marker = execution_logs_root().join(SPANS_BACKFILLED_MARKER);
+        if tokio::fs::try_exists(&marker).await? {
+            return Ok(());
+        }
+
+        let processes = ExecutionProcess::find_finished(&self.db().pool).await?;
+        tracing::info!("Backfilling log spans for {} processes", processes.len());
+        let mut failed = 0usize;
+        for process in &processes {
+            if let Err(e) = self.backfill_process_log_spans(process).await {
+                tracing::warn!(
+                    "Failed to backfill log spans for execution {}: {}",
+                    process.id,
+                    e
+                );
+                failed += 1;
             }
+        }
+        if failed > 0 {
+            return Err(ContainerError::Other(anyhow!(
+                "{failed} logs could not be backfilled with spans"
+            )));
+        }
+
+        tokio::fs::create_dir_all(execution_logs_root()).await?;
+        tokio::fs::write(&marker, b"").await?;
+        Ok(())
+    }
+
+    /// Derive and store the spans of one finished process whose log has none.
+    /// The worktree isn't recreated: normalizers only use its path.
+    async fn backfill_process_log_spans(
+        &self,
+        process: &ExecutionProcess,
+    ) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        let Some(raw_messages) = execution_process::load_raw_log_messages(pool, process.id).await
+        else {
+            return Ok(());
+        };
+        if raw_messages
+            .iter()
+            .any(|msg| matches!(msg, LogMsg::Span(_)))
+        {
+            return Ok(());
+        }
+        let Some((workspace, _session)) = process.parent_workspace_and_session(pool).await? else {
+            return Ok(());
+        };
+        let Some(temp_store) =
+            self.normalize_log_messages(process, &workspace, raw_messages.clone())
+        else {
+            return Ok(());
+        };
+
+        let spans = spans_from_patches(&collect_normalized_patches(&temp_store).await);
+        if spans.is_empty() {
+            return Ok(());
+        }
+        execution_process::append_backfilled_spans(
+            process.session_id,
+            process.id,
+            &raw_messages,
+            &spans,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn stream_normalized_logs(
+        &self,
+        id: &Uuid,
+    ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
+        // First try in-memory store (existing behavior)
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            Some(
+                store
+                    .history_plus_stream() // BoxStream<Result<LogMsg, io::Error>>
+                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
+                    .chain(futures::stream::once(async {
+                        Ok::<_, std::io::Error>(LogMsg::Finished)
+                    }))
+                    .boxed(),
+            )
+        } else {
+            let temp_store = self.normalize_stored_logs(id).await?;
 
             // Stream normalized patches, deduplicating consecutive patches
             // that target the same path (only the final state matters for
@@ -1044,11 +1523,14 @@ pub trait ContainerService {
         }
     }
 
+    /// Start the first coding agent run of a workspace after the repos'
+    /// setup scripts. `setup_script` runs in the workspace root before them.
     async fn start_workspace(
         &self,
         workspace: &Workspace,
         executor_config: ExecutorConfig,
         prompt: String,
+        setup_script: Option<String>,
     ) -> Result<ExecutionProcess, ContainerError> {
         // Create container
         self.create(workspace).await?;
@@ -1059,6 +1541,14 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        // A reused worktree may carry edits from before the workspace was
+        // started. Nobody is asked to confirm when starting a workspace.
+        let session_id = Uuid::new_v4();
+        if let Some(workspace_root) = workspace.container_ref.as_deref() {
+            self.guard_dirty_worktree(workspace.id, Path::new(workspace_root), session_id, false)
+                .await?;
+        }
+
         // Create a session for this workspace
         let session = Session::create(
             &self.db().pool,
@@ -1066,7 +1556,7 @@ pub trait ContainerService {
                 executor: Some(executor_config.executor.to_string()),
                 name: None,
             },
-            Uuid::new_v4(),
+            session_id,
             workspace.id,
         )
         .await?;
@@ -1091,6 +1581,20 @@ pub trait ContainerService {
             }),
             cleanup_action.map(Box::new),
         );
+        let workspace_setup_action =
+            setup_script
+                .filter(|script| !script.trim().is_empty())
+                .map(|script| {
+                    ExecutorAction::new(
+                        ExecutorActionType::ScriptRequest(ScriptRequest {
+                            script,
+                            language: ScriptRequestLanguage::Bash,
+                            context: ScriptContext::SetupScript,
+                            working_dir: None,
+                        }),
+                        None,
+                    )
+                });
 
         let execution_process = if all_parallel {
             // All parallel: start each setup independently, then start coding agent
@@ -1108,16 +1612,23 @@ pub trait ContainerService {
                     tracing::warn!(?e, "Failed to start setup script in parallel mode");
                 }
             }
-            self.start_execution(
-                &workspace,
-                &session,
-                &coding_action,
-                &ExecutionProcessRunReason::CodingAgent,
-            )
-            .await?
+            // The workspace setup script still runs before the coding agent
+            let (main_action, run_reason) = match workspace_setup_action {
+                Some(setup_action) => (
+                    setup_action.append_action(coding_action),
+                    ExecutionProcessRunReason::SetupScript,
+                ),
+                None => (coding_action, ExecutionProcessRunReason::CodingAgent),
+            };
+            self.start_execution(&workspace, &session, &main_action, &run_reason)
+                .await?
         } else {
             // Any sequential: chain ALL setups → coding agent via next_action
-            let main_action = Self::build_sequential_setup_chain(&repos_with_setup, coding_action);
+            let mut main_action =
+                Self::build_sequential_setup_chain(&repos_with_setup, coding_action);
+            if let Some(setup_action) = workspace_setup_action {
+                main_action = setup_action.append_action(main_action);
+            }
             self.start_execution(
                 &workspace,
                 &session,
@@ -1137,6 +1648,10 @@ pub trait ContainerService {
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
     ) -> Result<ExecutionProcess, ContainerError> {
+        if self.is_draining() {
+            return Err(ContainerError::ShuttingDown);
+        }
+
         // Create new execution process record
         // Capture current HEAD per repository as the "before" commit for this execution
         let repositories =
@@ -1180,6 +1695,10 @@ pub trait ContainerService {
         if *run_reason != ExecutionProcessRunReason::ArchiveScript {
             Workspace::set_archived(&self.db().pool, workspace.id, false).await?;
         }
+        if *run_reason == ExecutionProcessRunReason::CheckCommand {
+            WorkspaceCheckResult::claim_next(&self.db().pool, workspace.id, execution_process.id)
+                .await?;
+        }
 
         if let Some(prompt) = match executor_action.typ() {
             ExecutorActionType::CodingAgentInitialRequest(coding_agent_request) => {
@@ -1227,6 +1746,22 @@ pub trait ContainerService {
                     update_error
                 );
             }
+            // The checks after one that didn't start won't run either
+            if *run_reason == ExecutionProcessRunReason::CheckCommand {
+                let pool = &self.db().pool;
+                if let Err(e) =
+                    WorkspaceCheckResult::complete(pool, execution_process.id, false, None).await
+                {
+                    tracing::error!(
+                        "Failed to record check result for {}: {}",
+                        execution_process.id,
+                        e
+                    );
+                }
+                if let Err(e) = WorkspaceCheckResult::skip_unfinished(pool, workspace.id).await {
+                    tracing::error!("Failed to skip checks of workspace {}: {}", workspace.id, e);
+                }
+            }
             // Emit stderr error message
             let log_message = LogMsg::Stderr(format!("Failed to start execution: {start_error}"));
             if let Err(e) = execution_process::append_log_message(
@@ -1334,6 +1869,16 @@ pub trait ContainerService {
 
         // Determine the run reason of the next action
         let next_run_reason = match (action.typ(), next_action.typ()) {
+            (ExecutorActionType::ScriptRequest(_), ExecutorActionType::ScriptRequest(next))
+                if next.context == ScriptContext::RepoCommand =>
+            {
+                ExecutionProcessRunReason::RepoCommand
+            }
+            (ExecutorActionType::ScriptRequest(_), ExecutorActionType::ScriptRequest(next))
+                if next.context == ScriptContext::CheckCommand =>
+            {
+                ExecutionProcessRunReason::CheckCommand
+            }
             (ExecutorActionType::ScriptRequest(_), ExecutorActionType::ScriptRequest(_)) => {
                 ExecutionProcessRunReason::SetupScript
             }
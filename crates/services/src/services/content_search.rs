@@ -0,0 +1,373 @@
+//! "Find in project" over a workspace's worktrees.
+//!
+//! Walks each worktree with the `ignore` crate, so `.gitignore`, `.ignore`
+//! and global excludes apply as they do for ripgrep, and searches files in
+//! parallel. Matches are streamed as they are found; dropping the stream
+//! stops the search.
+
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use ignore::{WalkBuilder, WalkState, overrides::OverrideBuilder};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_stream::{Stream, wrappers::ReceiverStream};
+use ts_rs::TS;
+
+pub const DEFAULT_MAX_RESULTS: usize = 500;
+const MAX_RESULTS_LIMIT: usize = 5000;
+/// Files larger than this are skipped, like generated bundles and dumps.
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// Matched lines are cut to this many bytes in results.
+const MAX_LINE_LENGTH: usize = 500;
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ContentSearchError {
+    #[error("Invalid search pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+    #[error("Invalid glob: {0}")]
+    InvalidGlob(#[from] ignore::Error),
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ContentSearchQuery {
+    pub q: String,
+    /// Treat `q` as a regular expression instead of literal text.
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only search files matching this glob, e.g. `*.rs` or `src/**`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub glob: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub max_results: Option<usize>,
+}
+
+/// A worktree to search, labelled with its repo name.
+#[derive(Debug, Clone)]
+pub struct SearchRoot {
+    pub repo_name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ContentMatch {
+    pub repo_name: String,
+    /// Relative to the repo's worktree.
+    pub path: String,
+    /// 1-based.
+    pub line_number: u64,
+    pub line: String,
+    /// Byte ranges of the matches within `line`.
+    pub ranges: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentSearchEvent {
+    Match(ContentMatch),
+    Done {
+        files_searched: usize,
+        matches: usize,
+        /// Whether the search stopped at the result limit.
+        truncated: bool,
+    },
+}
+
+impl ContentSearchQuery {
+    fn matcher(&self) -> Result<Regex, ContentSearchError> {
+        let pattern = if self.regex {
+            self.q.clone()
+        } else {
+            regex::escape(&self.q)
+        };
+        Ok(RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()?)
+    }
+
+    fn limit(&self) -> usize {
+        self.max_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_RESULTS_LIMIT)
+    }
+}
+
+struct SearchState {
+    matcher: Regex,
+    limit: usize,
+    matches: AtomicUsize,
+    files_searched: AtomicUsize,
+    stopped: AtomicBool,
+    sender: mpsc::Sender<ContentSearchEvent>,
+}
+
+impl SearchState {
+    /// Reserve a result slot, stopping the search once the limit is hit.
+    fn take_slot(&self) -> bool {
+        let taken = self.matches.fetch_add(1, Ordering::Relaxed) < self.limit;
+        if !taken {
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+        taken
+    }
+
+    fn send(&self, event: ContentSearchEvent) {
+        if self.sender.blocking_send(event).is_err() {
+            // The client went away.
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Search `roots` for `query`, streaming matches followed by one
+/// [`ContentSearchEvent::Done`].
+pub fn search(
+    roots: Vec<SearchRoot>,
+    query: &ContentSearchQuery,
+) -> Result<impl Stream<Item = ContentSearchEvent> + use<>, ContentSearchError> {
+    let matcher = query.matcher()?;
+    // Validate the glob up front so a bad one is a request error.
+    if let Some(glob) = &query.glob {
+        OverrideBuilder::new("/").add(glob)?;
+    }
+
+    let (sender, receiver) = mpsc::channel(256);
+    let state = Arc::new(SearchState {
+        matcher,
+        limit: query.limit(),
+        matches: AtomicUsize::new(0),
+        files_searched: AtomicUsize::new(0),
+        stopped: AtomicBool::new(false),
+        sender,
+    });
+    let glob = query.glob.clone();
+
+    tokio::task::spawn_blocking(move || {
+        for root in &roots {
+            if state.stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            if let Err(e) = search_root(root, glob.as_deref(), &state) {
+                tracing::warn!("Content search skipped {}: {}", root.path.display(), e);
+            }
+        }
+        let matches = state.matches.load(Ordering::Relaxed);
+        state.send(ContentSearchEvent::Done {
+            files_searched: state.files_searched.load(Ordering::Relaxed),
+            matches: matches.min(state.limit),
+            truncated: matches > state.limit,
+        });
+    });
+
+    Ok(ReceiverStream::new(receiver))
+}
+
+fn search_root(
+    root: &SearchRoot,
+    glob: Option<&str>,
+    state: &Arc<SearchState>,
+) -> Result<(), ignore::Error> {
+    let mut walker = WalkBuilder::new(&root.path);
+    walker
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .require_git(false)
+        .max_filesize(Some(MAX_FILE_SIZE))
+        .filter_entry(|entry| entry.file_name() != ".git");
+    if let Some(glob) = glob {
+        let mut overrides = OverrideBuilder::new(&root.path);
+        overrides.add(glob)?;
+        walker.overrides(overrides.build()?);
+    }
+
+    walker.build_parallel().run(|| {
+        let state = state.clone();
+        Box::new(move |entry| {
+            if state.stopped.load(Ordering::Relaxed) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                return WalkState::Continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(&root.path).unwrap_or(path);
+            if let Err(e) = search_file(path, relative, &root.repo_name, &state) {
+                tracing::debug!("Content search could not read {}: {}", path.display(), e);
+            }
+            if state.stopped.load(Ordering::Relaxed) {
+                WalkState::Quit
+            } else {
+                WalkState::Continue
+            }
+        })
+    });
+    Ok(())
+}
+
+fn search_file(
+    path: &Path,
+    relative: &Path,
+    repo_name: &str,
+    state: &SearchState,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    if reader
+        .fill_buf()?
+        .iter()
+        .take(BINARY_SNIFF_BYTES)
+        .any(|b| *b == 0)
+    {
+        return Ok(());
+    }
+    state.files_searched.fetch_add(1, Ordering::Relaxed);
+
+    let mut bytes = Vec::new();
+    let mut line_number = 0u64;
+    loop {
+        bytes.clear();
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(());
+        }
+        line_number += 1;
+        let line = String::from_utf8_lossy(&bytes);
+        let line = line.trim_end_matches(['\n', '\r']);
+        let ranges: Vec<[usize; 2]> = state
+            .matcher
+            .find_iter(line)
+            .filter(|m| m.start() < MAX_LINE_LENGTH)
+            .map(|m| [m.start(), m.end().min(MAX_LINE_LENGTH)])
+            .collect();
+        if ranges.is_empty() {
+            continue;
+        }
+        if !state.take_slot() {
+            return Ok(());
+        }
+        state.send(ContentSearchEvent::Match(ContentMatch {
+            repo_name: repo_name.to_string(),
+            path: relative.to_string_lossy().to_string(),
+            line_number,
+            line: truncate_line(line),
+            ranges,
+        }));
+        if state.stopped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+    }
+}
+
+fn truncate_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_LENGTH {
+        return line.to_string();
+    }
+    let mut end = MAX_LINE_LENGTH;
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_stream::StreamExt;
+
+    use super::*;
+
+    fn query(q: &str) -> ContentSearchQuery {
+        ContentSearchQuery {
+            q: q.to_string(),
+            regex: false,
+            case_sensitive: false,
+            glob: None,
+            max_results: None,
+        }
+    }
+
+    async fn run(root: &Path, query: &ContentSearchQuery) -> Vec<ContentSearchEvent> {
+        let roots = vec![SearchRoot {
+            repo_name: "app".to_string(),
+            path: root.to_path_buf(),
+        }];
+        search(roots, query).unwrap().collect().await
+    }
+
+    fn matches(events: &[ContentSearchEvent]) -> Vec<(String, u64)> {
+        let mut found: Vec<(String, u64)> = events
+            .iter()
+            .filter_map(|event| match event {
+                ContentSearchEvent::Match(m) => Some((m.path.clone(), m.line_number)),
+                ContentSearchEvent::Done { .. } => None,
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[tokio::test]
+    async fn respects_ignore_files_and_skips_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/out.js"), "needle\n").unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n// NEEDLE here\n").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), b"needle\0\0").unwrap();
+
+        let events = run(dir.path(), &query("needle")).await;
+        assert_eq!(matches(&events), vec![("main.rs".to_string(), 2)]);
+        assert!(matches!(
+            events.last(),
+            Some(ContentSearchEvent::Done {
+                matches: 1,
+                truncated: false,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn supports_regex_glob_and_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "let x = 1;\nlet y = 22;\n").unwrap();
+        std::fs::write(dir.path().join("b.ts"), "let z = 333;\n").unwrap();
+
+        let mut regex = query(r"= \d{2,}");
+        regex.regex = true;
+        regex.glob = Some("*.rs".to_string());
+        let events = run(dir.path(), &regex).await;
+        assert_eq!(matches(&events), vec![("a.rs".to_string(), 2)]);
+
+        let mut limited = query("let");
+        limited.max_results = Some(2);
+        let events = run(dir.path(), &limited).await;
+        assert_eq!(matches(&events).len(), 2);
+        assert!(matches!(
+            events.last(),
+            Some(ContentSearchEvent::Done {
+                truncated: true,
+                ..
+            })
+        ));
+
+        let mut invalid = query("(");
+        invalid.regex = true;
+        assert!(search(Vec::new(), &invalid).is_err());
+    }
+}
@@ -0,0 +1,503 @@
+//! Supervision of running dev servers.
+//!
+//! Each started dev server is watched until it exits: the port it listens on
+//! is taken from the workspace configuration or detected in its output, the
+//! port is polled over HTTP, and once it answers it is registered with the
+//! preview proxy.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt, stream::select_all};
+use preview_proxy::DevServerPorts;
+use regex::Regex;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Output kept per process while waiting for the end of a line.
+const MAX_PARTIAL_LINE: usize = 4096;
+
+static ANSI_ESCAPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap());
+/// Addresses printed by dev servers, e.g. `http://localhost:5173/` or
+/// `Listening on 0.0.0.0:3000`.
+static LOCAL_ADDRESS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\b(?:localhost|127\.0\.0\.1|0\.0\.0\.0)|\[::1?\]):(\d{2,5})\b").unwrap()
+});
+/// Messages like `Server started on port 8080`.
+static PORT_MESSAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:listening|running|started|serving|ready)\b.*?\bport\s*:?\s*(\d{2,5})\b")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum DevServerState {
+    Stopped,
+    /// Running, but its port has not answered yet.
+    Starting,
+    Healthy,
+    /// Answered before, but the last health check failed.
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DevServerStatus {
+    pub state: DevServerState,
+    /// The port registered with the preview proxy.
+    pub port: Option<u16>,
+    pub execution_process_ids: Vec<Uuid>,
+    pub last_health_check_at: Option<DateTime<Utc>>,
+}
+
+impl DevServerStatus {
+    fn stopped() -> Self {
+        Self {
+            state: DevServerState::Stopped,
+            port: None,
+            execution_process_ids: Vec::new(),
+            last_health_check_at: None,
+        }
+    }
+}
+
+struct Supervised {
+    status: DevServerStatus,
+    cancel: CancellationToken,
+}
+
+#[derive(Clone)]
+pub struct DevServerMonitor {
+    workspaces: Arc<RwLock<HashMap<Uuid, Supervised>>>,
+    ports: DevServerPorts,
+    http_client: reqwest::Client,
+}
+
+impl DevServerMonitor {
+    pub fn new(ports: DevServerPorts) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("failed to build dev server health check client");
+        Self {
+            workspaces: Arc::new(RwLock::new(HashMap::new())),
+            ports,
+            http_client,
+        }
+    }
+
+    pub fn status(&self, workspace_id: Uuid) -> DevServerStatus {
+        self.workspaces
+            .read()
+            .unwrap()
+            .get(&workspace_id)
+            .map(|supervised| supervised.status.clone())
+            .unwrap_or_else(DevServerStatus::stopped)
+    }
+
+    /// Supervise the dev server processes just started for `workspace_id`,
+    /// replacing any earlier supervision. `configured_port` skips detection.
+    pub fn watch(
+        &self,
+        workspace_id: Uuid,
+        processes: Vec<(Uuid, Arc<MsgStore>)>,
+        configured_port: Option<u16>,
+    ) {
+        let cancel = CancellationToken::new();
+        let status = DevServerStatus {
+            state: DevServerState::Starting,
+            port: None,
+            execution_process_ids: processes.iter().map(|(id, _)| *id).collect(),
+            last_health_check_at: None,
+        };
+        if let Some(previous) = self.workspaces.write().unwrap().insert(
+            workspace_id,
+            Supervised {
+                status,
+                cancel: cancel.clone(),
+            },
+        ) {
+            previous.cancel.cancel();
+        }
+
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = monitor.supervise(workspace_id, processes, configured_port) => {}
+                _ = cancel.cancelled() => return,
+            }
+            monitor.finish(workspace_id, &cancel);
+        });
+    }
+
+    /// Stop supervising `workspace_id` and unregister its port.
+    pub fn stop(&self, workspace_id: Uuid) {
+        if let Some(supervised) = self.workspaces.write().unwrap().remove(&workspace_id) {
+            supervised.cancel.cancel();
+        }
+        self.ports.unregister(workspace_id);
+    }
+
+    fn finish(&self, workspace_id: Uuid, cancel: &CancellationToken) {
+        let mut workspaces = self.workspaces.write().unwrap();
+        // Replaced by a newer supervision or stopped in the meantime.
+        if cancel.is_cancelled() {
+            return;
+        }
+        workspaces.remove(&workspace_id);
+        self.ports.unregister(workspace_id);
+    }
+
+    fn update(&self, workspace_id: Uuid, f: impl FnOnce(&mut DevServerStatus)) {
+        if let Some(supervised) = self.workspaces.write().unwrap().get_mut(&workspace_id) {
+            f(&mut supervised.status);
+        }
+    }
+
+    /// Runs until every process has finished.
+    async fn supervise(
+        &self,
+        workspace_id: Uuid,
+        processes: Vec<(Uuid, Arc<MsgStore>)>,
+        configured_port: Option<u16>,
+    ) {
+        let mut running = processes.len();
+        let mut output = select_all(
+            processes
+                .into_iter()
+                .map(|(id, store)| store.history_plus_stream().map(move |msg| (id, msg))),
+        );
+        let mut candidates: Vec<u16> = configured_port.into_iter().collect();
+        let mut detectors: HashMap<Uuid, PortDetector> = HashMap::new();
+        let mut healthy_port: Option<u16> = None;
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+
+        while running > 0 {
+            tokio::select! {
+                item = output.next() => match item {
+                    Some((_, Ok(LogMsg::Finished))) => running -= 1,
+                    Some((id, Ok(LogMsg::Stdout(chunk) | LogMsg::Stderr(chunk)))) => {
+                        if configured_port.is_some() {
+                            continue;
+                        }
+                        for port in detectors.entry(id).or_default().feed(&chunk) {
+                            if !candidates.contains(&port) {
+                                candidates.push(port);
+                            }
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
+                },
+                _ = interval.tick() => {
+                    let port = self.first_answering(&candidates).await;
+                    let now = Utc::now();
+                    match port {
+                        Some(port) => {
+                            if healthy_port != Some(port) {
+                                tracing::info!(
+                                    "Dev server of workspace {} is up on port {}",
+                                    workspace_id,
+                                    port
+                                );
+                                self.ports.register(workspace_id, port);
+                                healthy_port = Some(port);
+                            }
+                            self.update(workspace_id, |status| {
+                                status.state = DevServerState::Healthy;
+                                status.port = Some(port);
+                                status.last_health_check_at = Some(now);
+                            });
+                        }
+                        None => self.update(workspace_id, |status| {
+                            if status.state == DevServerState::Healthy {
+                                status.state = DevServerState::Unhealthy;
+                            }
+                            status.last_health_check_at = Some(now);
+                        }),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The first candidate port with an HTTP server answering. Any response,
+    /// error statuses included, counts.
+    async fn first_answering(&self, candidates: &[u16]) -> Option<u16> {
+        for port in candidates {
+            if self
+                .http_client
+                .get(format!("http://127.0.0.1:{port}/"))
+                .send()
+                .await
+                .is_ok()
+            {
+                return Some(*port);
+            }
+        }
+        None
+    }
+}
+
+/// A change to the port a dev server is published on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortChange {
+    Allocated(u16),
+    Released(u16),
+}
+
+/// Follow a dev server process until it exits and publish the port it
+/// listens on: `configured_port` if the workspace sets one, otherwise the
+/// first port it reports in its output. `resolve` maps that port to the one
+/// reachable from the host, e.g. where a container publishes it, or `None`
+/// while it isn't reachable yet. An allocated port is released once the
+/// process finishes.
+pub async fn track_listening_port<R, RFut, P, PFut>(
+    store: Arc<MsgStore>,
+    configured_port: Option<u16>,
+    resolve: R,
+    mut publish: P,
+) where
+    R: Fn(u16) -> RFut,
+    RFut: Future<Output = Option<u16>>,
+    P: FnMut(PortChange) -> PFut,
+    PFut: Future<Output = ()>,
+{
+    let mut allocated = match configured_port {
+        Some(port) => resolve(port).await,
+        None => None,
+    };
+    if let Some(port) = allocated {
+        publish(PortChange::Allocated(port)).await;
+    }
+
+    let mut output = store.history_plus_stream();
+    let mut detector = PortDetector::default();
+    while let Ok(Some(msg)) = output.try_next().await {
+        match msg {
+            LogMsg::Stdout(chunk) | LogMsg::Stderr(chunk) if allocated.is_none() => {
+                // A configured port that wasn't reachable yet is retried as
+                // the server makes progress.
+                let candidate = match configured_port {
+                    Some(port) => Some(port),
+                    None => detector.feed(&chunk).into_iter().next(),
+                };
+                let Some(port) = candidate else {
+                    continue;
+                };
+                if let Some(port) = resolve(port).await {
+                    publish(PortChange::Allocated(port)).await;
+                    allocated = Some(port);
+                }
+            }
+            LogMsg::Finished => break,
+            _ => {}
+        }
+    }
+
+    if let Some(port) = allocated {
+        publish(PortChange::Released(port)).await;
+    }
+}
+
+/// Finds the ports a process reports listening on in its output, e.g. Vite's
+/// `Local: http://localhost:5173/`, Next's `started server on 0.0.0.0:3000`,
+/// Puma's `Listening on http://127.0.0.1:3000` or Flask's
+/// `Running on http://127.0.0.1:5000`.
+#[derive(Debug, Default)]
+pub struct PortDetector {
+    /// Output after the last newline, completed by the next chunk.
+    partial: String,
+}
+
+impl PortDetector {
+    /// Ports mentioned in the lines `chunk` completes.
+    pub fn feed(&mut self, chunk: &str) -> Vec<u16> {
+        self.partial.push_str(chunk);
+        let Some(end) = self.partial.rfind('\n') else {
+            if self.partial.len() > MAX_PARTIAL_LINE {
+                self.partial.clear();
+            }
+            return Vec::new();
+        };
+        let lines: String = self.partial.drain(..=end).collect();
+        lines.lines().filter_map(port_in_line).collect()
+    }
+}
+
+fn port_in_line(line: &str) -> Option<u16> {
+    let line = ANSI_ESCAPE.replace_all(line, "");
+    let captures = LOCAL_ADDRESS
+        .captures(&line)
+        .or_else(|| PORT_MESSAGE.captures(&line))?;
+    captures[1].parse().ok().filter(|port| *port >= 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_ports_in_common_dev_server_output() {
+        for (line, port) in [
+            ("  ➜  Local:   http://localhost:5173/", Some(5173)),
+            (
+                "\x1b[32m  - Local:\x1b[39m        http://127.0.0.1:\x1b[1m3000\x1b[22m",
+                Some(3000),
+            ),
+            (
+                "ready - started server on 0.0.0.0:3000, url: http://localhost:3000",
+                Some(3000),
+            ),
+            ("Server listening on port 8080", Some(8080)),
+            ("Listening on http://[::1]:4200", Some(4200)),
+            ("* Listening on http://127.0.0.1:3000", Some(3000)),
+            (" * Running on http://127.0.0.1:5000", Some(5000)),
+            ("Compiled 12 modules in 80ms", None),
+            ("Connected to postgres at localhost:80", None),
+        ] {
+            assert_eq!(port_in_line(line), port, "{line}");
+        }
+    }
+
+    #[test]
+    fn joins_lines_split_across_chunks() {
+        let mut detector = PortDetector::default();
+        assert!(detector.feed("Local: http://localh").is_empty());
+        assert_eq!(detector.feed("ost:5173/\nnext"), vec![5173]);
+        assert_eq!(detector.partial, "next");
+    }
+
+    #[tokio::test]
+    async fn registers_the_port_once_it_answers_and_unregisters_on_exit() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                use tokio::io::{AsyncReadExt, AsyncWriteExt};
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let ports = DevServerPorts::default();
+        let monitor = DevServerMonitor::new(ports.clone());
+        let workspace_id = Uuid::new_v4();
+        let store = Arc::new(MsgStore::new());
+        monitor.watch(workspace_id, vec![(Uuid::new_v4(), store.clone())], None);
+        store.push_stdout(format!("Local: http://localhost:{port}/\n"));
+
+        for _ in 0..50 {
+            if ports.port(workspace_id).is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(ports.port(workspace_id), Some(port));
+        assert_eq!(monitor.status(workspace_id).state, DevServerState::Healthy);
+
+        store.push_finished();
+        for _ in 0..50 {
+            if ports.port(workspace_id).is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(ports.port(workspace_id), None);
+        assert_eq!(monitor.status(workspace_id).state, DevServerState::Stopped);
+    }
+
+    async fn tracked_changes(
+        configured_port: Option<u16>,
+        output: &[&str],
+        resolve: fn(u16) -> Option<u16>,
+    ) -> Vec<PortChange> {
+        let store = Arc::new(MsgStore::new());
+        for chunk in output {
+            store.push_stdout(chunk.to_string());
+        }
+        store.push_finished();
+
+        let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        track_listening_port(
+            store,
+            configured_port,
+            |port| async move { resolve(port) },
+            move |change| {
+                recorded.lock().unwrap().push(change);
+                async {}
+            },
+        )
+        .await;
+        changes.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn allocates_the_configured_port_without_waiting_for_output() {
+        assert_eq!(
+            tracked_changes(Some(4000), &["Local: http://localhost:5173/\n"], Some).await,
+            vec![PortChange::Allocated(4000), PortChange::Released(4000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn allocates_the_first_detected_port_and_releases_it_on_exit() {
+        assert_eq!(
+            tracked_changes(
+                None,
+                &[
+                    "compiling\n",
+                    "Local: http://localhost:5173/\n",
+                    "Network: http://0.0.0.0:5174/\n"
+                ],
+                Some
+            )
+            .await,
+            vec![PortChange::Allocated(5173), PortChange::Released(5173)]
+        );
+    }
+
+    #[tokio::test]
+    async fn publishes_the_resolved_port() {
+        assert_eq!(
+            tracked_changes(None, &["Listening on port 3000\n"], |port| Some(port + 1)).await,
+            vec![PortChange::Allocated(3001), PortChange::Released(3001)]
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_a_configured_port_until_it_resolves() {
+        static ATTEMPTS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let resolve =
+            |port| (ATTEMPTS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0).then_some(port);
+        assert_eq!(
+            tracked_changes(Some(4000), &["starting\n"], resolve).await,
+            vec![PortChange::Allocated(4000), PortChange::Released(4000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn releases_nothing_if_no_port_was_allocated() {
+        assert!(
+            tracked_changes(None, &["Compiled 12 modules\n"], Some)
+                .await
+                .is_empty()
+        );
+        assert!(tracked_changes(Some(4000), &[], |_| None).await.is_empty());
+    }
+}
@@ -15,7 +15,10 @@ use db::{
 };
 use executors::logs::utils::ConversationPatch;
 use futures::StreamExt;
-use git::{Commit, GitService, GitServiceError, compute_line_change_counts};
+use git::{
+    Commit, GitService, GitServiceError, compute_line_change_counts,
+    vcs::{VcsKind, vcs_for},
+};
 use json_patch::Patch;
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{
@@ -28,7 +31,7 @@ use tokio_stream::wrappers::{IntervalStream, ReceiverStream};
 use utils::{diff::Diff, log_msg::LogMsg};
 use uuid::Uuid;
 
-use crate::services::filesystem_watcher::{self, FilesystemWatcherError};
+use crate::services::{filesystem_watcher::FilesystemWatcherError, shared_watcher};
 
 type SentFileStats = Arc<std::sync::RwLock<HashMap<String, (SystemTime, u64)>>>;
 
@@ -58,6 +61,24 @@ pub async fn compute_diff_stats(
         let worktree_path = PathBuf::from(container_ref).join(&repo_with_branch.repo.name);
         let repo_path = repo_with_branch.repo.path.clone();
 
+        if VcsKind::detect(&worktree_path) != VcsKind::Git {
+            let workspace_branch = workspace.branch.clone();
+            let target_branch = repo_with_branch.target_branch.clone();
+            let diffs_result = tokio::task::spawn_blocking(move || {
+                let base = vcs_for(&repo_path).base_commit(
+                    &repo_path,
+                    &workspace_branch,
+                    &target_branch,
+                )?;
+                vcs_for(&worktree_path).diff(&worktree_path, &base)
+            })
+            .await;
+            if let Ok(Ok(diffs)) = diffs_result {
+                stats.add(&diffs);
+            }
+            continue;
+        }
+
         let base_commit_result = tokio::task::spawn_blocking({
             let git = git.clone();
             let repo_path = repo_path.clone();
@@ -80,22 +101,32 @@ pub async fn compute_diff_stats(
         .await;
 
         if let Ok(Ok(diffs)) = diffs_result {
-            for diff in diffs {
-                stats.files_changed += 1;
-                stats.lines_added += diff.additions.unwrap_or(0);
-                stats.lines_removed += diff.deletions.unwrap_or(0);
-            }
+            stats.add(&diffs);
         }
     }
 
     Some(stats)
 }
 
+impl DiffStats {
+    fn add(&mut self, diffs: &[Diff]) {
+        for diff in diffs {
+            self.files_changed += 1;
+            self.lines_added += diff.additions.unwrap_or(0);
+            self.lines_removed += diff.deletions.unwrap_or(0);
+        }
+    }
+}
+
 /// Maximum cumulative diff bytes to stream before omitting content (200MB)
 pub const MAX_CUMULATIVE_DIFF_BYTES: usize = 200 * 1024 * 1024;
 
 const DIFF_STREAM_CHANNEL_CAPACITY: usize = 1000;
 
+/// Metadata directories of the experimental backends, whose churn (jj
+/// snapshots the working copy on every command) isn't a change to diff.
+const VCS_METADATA_DIRS: [&str; 2] = [".jj", ".hg"];
+
 /// Errors that can occur during diff stream creation and operation
 #[derive(Error, Debug)]
 pub enum DiffStreamError {
@@ -207,6 +238,107 @@ pub async fn create(args: DiffStreamArgs) -> Result<DiffStreamHandle, DiffStream
     ))
 }
 
+/// Arguments for the diff stream of a jj or hg working copy.
+#[derive(Clone)]
+pub struct VcsDiffStreamArgs {
+    pub repo_id: Uuid,
+    pub worktree_path: PathBuf,
+    /// Commit id of the fork point, see [`git::vcs::Vcs::base_commit`].
+    pub base_commit: String,
+    pub stats_only: bool,
+    pub path_prefix: Option<String>,
+}
+
+/// Diff stream for working copies of the experimental jj and hg backends.
+/// They have no per-file diffing, so each batch of file changes, and a
+/// periodic reconcile, re-diffs the whole working copy and sends it when it
+/// changed.
+pub async fn create_vcs(args: VcsDiffStreamArgs) -> Result<DiffStreamHandle, DiffStreamError> {
+    let (tx, rx) = mpsc::channel::<Result<LogMsg, io::Error>>(DIFF_STREAM_CHANNEL_CAPACITY);
+
+    let watcher_task = tokio::spawn(async move {
+        if let Err(e) = run_vcs_stream(&args, &tx).await {
+            tracing::warn!("Diff stream ended: {e}");
+            let _ = tx.send(Err(io::Error::other(e.to_string()))).await;
+        }
+    });
+
+    Ok(DiffStreamHandle::new(
+        ReceiverStream::new(rx).boxed(),
+        Some(watcher_task),
+    ))
+}
+
+async fn run_vcs_stream(
+    args: &VcsDiffStreamArgs,
+    tx: &mpsc::Sender<Result<LogMsg, io::Error>>,
+) -> Result<(), DiffStreamError> {
+    // Subscribe first so changes made while the snapshot is taken aren't lost.
+    let mut fs_subscription = shared_watcher::hub()
+        .subscribe(&args.worktree_path)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let canonical_worktree = fs_subscription.scope.clone();
+    let mut reconcile_interval = IntervalStream::new(tokio::time::interval(Duration::from_secs(5)));
+
+    let mut last_sent: Option<Patch> = None;
+    loop {
+        let patch = vcs_snapshot_patch(args).await?;
+        if last_sent.as_ref() != Some(&patch) {
+            if tx.send(Ok(LogMsg::JsonPatch(patch.clone()))).await.is_err() {
+                return Ok(());
+            }
+            if last_sent.is_none() {
+                let _ = tx.send(Ok(LogMsg::Ready)).await;
+            }
+            last_sent = Some(patch);
+        }
+
+        tokio::select! {
+            Some(res) = fs_subscription.rx.next() => {
+                let events = res.map_err(|e| io::Error::other(format!("{e:?}")))?;
+                let changed =
+                    extract_changed_paths(&events, &canonical_worktree, &args.worktree_path);
+                if !changed.iter().any(|path| !is_vcs_metadata_path(path)) {
+                    continue;
+                }
+            }
+            _ = reconcile_interval.next() => {}
+            else => return Ok(()),
+        }
+    }
+}
+
+async fn vcs_snapshot_patch(args: &VcsDiffStreamArgs) -> Result<Patch, DiffStreamError> {
+    let worktree = args.worktree_path.clone();
+    let base = args.base_commit.clone();
+    let diffs =
+        tokio::task::spawn_blocking(move || vcs_for(&worktree).diff(&worktree, &base)).await??;
+
+    let cumulative = Arc::new(AtomicUsize::new(0));
+    let mut entries = HashMap::new();
+    for mut diff in diffs {
+        apply_stream_omit_policy(&mut diff, &cumulative, args.stats_only);
+        let raw_path = GitService::diff_path(&diff);
+        diff.old_path = diff
+            .old_path
+            .map(|old| prefix_path(old, args.path_prefix.as_deref()));
+        diff.new_path = diff
+            .new_path
+            .map(|new| prefix_path(new, args.path_prefix.as_deref()));
+        diff.repo_id = Some(args.repo_id);
+        entries.insert(raw_path, diff);
+    }
+
+    let repo_key = args.path_prefix.clone().unwrap_or_else(|| "_".to_string());
+    Ok(ConversationPatch::replace_repo_diffs(&repo_key, entries))
+}
+
+fn is_vcs_metadata_path(path: &str) -> bool {
+    VCS_METADATA_DIRS
+        .iter()
+        .any(|dir| path == *dir || path.starts_with(&format!("{dir}/")))
+}
+
 impl DiffStreamManager {
     fn new(args: DiffStreamArgs, tx: mpsc::Sender<Result<LogMsg, io::Error>>) -> Self {
         Self {
@@ -231,10 +363,10 @@ impl DiffStreamManager {
         // Send Ready once the initial snapshot has been pushed.
         let _ready_error = self.tx.send(Ok(LogMsg::Ready)).await;
 
-        let (fs_debouncer, mut fs_rx, canonical_worktree) =
-            filesystem_watcher::async_watcher(self.args.worktree_path.clone())
-                .map_err(|e| io::Error::other(e.to_string()))?;
-        let _fs_guard = fs_debouncer;
+        let mut fs_subscription = shared_watcher::hub()
+            .subscribe(&self.args.worktree_path)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let canonical_worktree = fs_subscription.scope.clone();
 
         let (git_debouncer, mut git_rx) =
             match setup_git_watcher(&self.args.git_service, &self.args.worktree_path) {
@@ -250,7 +382,7 @@ impl DiffStreamManager {
 
         loop {
             let event = tokio::select! {
-                Some(res) = fs_rx.next() => DiffEvent::Filesystem(res),
+                Some(res) = fs_subscription.rx.next() => DiffEvent::Filesystem(res),
                 Ok(()) = async {
                     match git_rx.as_mut() {
                         Some(rx) => rx.changed().await,
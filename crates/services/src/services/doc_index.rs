@@ -0,0 +1,525 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::doc_source::{DocChunk, DocSource, DocSourceKind},
+};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Soft upper bound on the size of a single chunk, in bytes.
+const MAX_CHUNK_CHARS: usize = 2000;
+/// Local folders larger than this are truncated to keep indexing bounded.
+const MAX_LOCAL_FILES: usize = 500;
+const DOC_EXTENSIONS: &[&str] = &["md", "mdx", "markdown", "txt", "rst", "adoc"];
+
+#[derive(Debug, Error)]
+pub enum DocIndexError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid OpenAPI document: {0}")]
+    InvalidOpenApi(String),
+    #[error("Local doc source path must be absolute: {0}")]
+    RelativeLocalPath(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct DocSearchHit {
+    pub source_id: Uuid,
+    pub source_name: String,
+    pub title: String,
+    pub content: String,
+    pub score: f64,
+}
+
+/// Fetches, chunks and searches the documentation sources registered on a
+/// project.
+#[derive(Clone)]
+pub struct DocIndexService {
+    db: DBService,
+    client: reqwest::Client,
+}
+
+impl DocIndexService {
+    pub fn new(db: DBService) -> Self {
+        Self {
+            db,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Spawn a background loop that re-indexes sources whose refresh interval
+    /// has elapsed.
+    pub fn spawn_refresh_loop(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self::new(db);
+        tokio::spawn(async move {
+            info!("Starting documentation index refresh loop");
+            let mut interval = interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.refresh_due_sources().await {
+                    error!("Error refreshing documentation sources: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn refresh_due_sources(&self) -> Result<(), DocIndexError> {
+        let due = DocSource::find_due_for_refresh(&self.db.pool).await?;
+        if due.is_empty() {
+            return Ok(());
+        }
+        debug!("Refreshing {} documentation sources", due.len());
+        for source in &due {
+            if let Err(e) = self.index_source(source).await {
+                warn!("Failed to index doc source '{}': {}", source.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch and re-chunk a single source. Failures are recorded on the source
+    /// so they can be surfaced in the UI, and also returned to the caller.
+    pub async fn index_source(&self, source: &DocSource) -> Result<usize, DocIndexError> {
+        match self.collect_chunks(source).await {
+            Ok(chunks) => {
+                DocChunk::replace_for_source(&self.db.pool, source.id, &chunks).await?;
+                DocSource::mark_indexed(&self.db.pool, source.id, None).await?;
+                Ok(chunks.len())
+            }
+            Err(e) => {
+                DocSource::mark_indexed(&self.db.pool, source.id, Some(&e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn search(
+        &self,
+        project_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<DocSearchHit>, DocIndexError> {
+        let terms = tokenize(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sources = DocSource::find_by_project_id(&self.db.pool, project_id).await?;
+        let chunks = DocChunk::find_by_project_id(&self.db.pool, project_id).await?;
+
+        let mut hits: Vec<DocSearchHit> = chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                let score = score_chunk(&terms, &chunk.title, &chunk.content);
+                if score <= 0.0 {
+                    return None;
+                }
+                let source_name = sources
+                    .iter()
+                    .find(|s| s.id == chunk.source_id)
+                    .map(|s| s.name.clone())
+                    .unwrap_or_default();
+                Some(DocSearchHit {
+                    source_id: chunk.source_id,
+                    source_name,
+                    title: chunk.title,
+                    content: chunk.content,
+                    score,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    async fn collect_chunks(
+        &self,
+        source: &DocSource,
+    ) -> Result<Vec<(String, String)>, DocIndexError> {
+        match source.kind {
+            DocSourceKind::Url => {
+                let body = self.fetch_text(&source.location).await?;
+                let text = if looks_like_html(&body) {
+                    strip_html(&body)
+                } else {
+                    body
+                };
+                Ok(chunk_markdown(&source.name, &text))
+            }
+            DocSourceKind::LocalFolder => {
+                let root = resolve_local_path(source)?;
+                let chunks = tokio::task::spawn_blocking(move || read_local_folder(&root))
+                    .await
+                    .map_err(std::io::Error::other)??;
+                Ok(chunks)
+            }
+            DocSourceKind::Openapi => {
+                let body = if is_remote(&source.location) {
+                    self.fetch_text(&source.location).await?
+                } else {
+                    let path = resolve_local_path(source)?;
+                    tokio::fs::read_to_string(path).await?
+                };
+                chunk_openapi(&body)
+            }
+        }
+    }
+
+    async fn fetch_text(&self, url: &str) -> Result<String, DocIndexError> {
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+}
+
+/// A project spans several repos, so there is no single root to resolve a
+/// relative location against.
+fn resolve_local_path(source: &DocSource) -> Result<PathBuf, DocIndexError> {
+    let location = PathBuf::from(&source.location);
+    if !location.is_absolute() {
+        return Err(DocIndexError::RelativeLocalPath(source.location.clone()));
+    }
+    Ok(location)
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+fn read_local_folder(root: &Path) -> Result<Vec<(String, String)>, DocIndexError> {
+    let mut chunks = Vec::new();
+    let mut files = 0;
+    for entry in WalkBuilder::new(root).build().flatten() {
+        let path = entry.path();
+        let is_doc = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| DOC_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if !entry.file_type().is_some_and(|t| t.is_file()) || !is_doc {
+            continue;
+        }
+        files += 1;
+        if files > MAX_LOCAL_FILES {
+            warn!(
+                "Doc folder {} has more than {} files, truncating",
+                root.display(),
+                MAX_LOCAL_FILES
+            );
+            break;
+        }
+        let content = std::fs::read_to_string(path)?;
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        chunks.extend(chunk_markdown(&rel.to_string_lossy(), &content));
+    }
+    Ok(chunks)
+}
+
+/// The fence marker (``` or ~~~) a line opens or closes, if any.
+fn fence_marker(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| line.starts_with(marker))
+}
+
+/// Split a section body at blank lines, keeping fenced code blocks whole.
+fn paragraphs(body: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut fence: Option<&str> = None;
+    for line in body.lines() {
+        match (fence, fence_marker(line)) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            _ => {}
+        }
+        if fence.is_none() && line.trim().is_empty() {
+            if !current.trim().is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs
+}
+
+/// Split markdown into sections at headings, further splitting oversized
+/// sections at paragraph boundaries. Lines inside fenced code blocks are
+/// never treated as headings or paragraph breaks.
+fn chunk_markdown(default_title: &str, text: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    let mut title = default_title.to_string();
+    let mut body = String::new();
+    let mut fence: Option<&str> = None;
+
+    for line in text.lines() {
+        match (fence, fence_marker(line)) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            _ => {}
+        }
+        if let Some(heading) = line.strip_prefix('#').filter(|_| fence.is_none()) {
+            if !body.trim().is_empty() {
+                sections.push((title.clone(), std::mem::take(&mut body)));
+            }
+            title = heading.trim_start_matches('#').trim().to_string();
+            continue;
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
+    if !body.trim().is_empty() {
+        sections.push((title, body));
+    }
+
+    let mut chunks = Vec::new();
+    for (title, body) in sections {
+        let mut current = String::new();
+        for paragraph in paragraphs(&body) {
+            if !current.is_empty() && current.len() + paragraph.len() > MAX_CHUNK_CHARS {
+                chunks.push((title.clone(), current.trim().to_string()));
+                current.clear();
+            }
+            current.push_str(&paragraph);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            chunks.push((title, current.trim().to_string()));
+        }
+    }
+    chunks
+}
+
+/// One chunk per operation, titled `METHOD /path`.
+fn chunk_openapi(body: &str) -> Result<Vec<(String, String)>, DocIndexError> {
+    let spec: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| DocIndexError::InvalidOpenApi(e.to_string()))?;
+    let paths = spec
+        .get("paths")
+        .and_then(|p| p.as_object())
+        .ok_or_else(|| DocIndexError::InvalidOpenApi("missing `paths` object".to_string()))?;
+
+    let mut chunks = Vec::new();
+    for (path, item) in paths {
+        let Some(operations) = item.as_object() else {
+            continue;
+        };
+        for (method, op) in operations {
+            if !matches!(
+                method.as_str(),
+                "get" | "put" | "post" | "delete" | "patch" | "head" | "options"
+            ) {
+                continue;
+            }
+            let mut content = String::new();
+            for field in ["summary", "description"] {
+                if let Some(text) = op.get(field).and_then(|v| v.as_str()) {
+                    content.push_str(text);
+                    content.push_str("\n\n");
+                }
+            }
+            if let Some(params) = op.get("parameters").and_then(|v| v.as_array()) {
+                for param in params {
+                    let name = param.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                    let location = param.get("in").and_then(|v| v.as_str()).unwrap_or("?");
+                    content.push_str(&format!("- parameter `{name}` ({location})\n"));
+                }
+            }
+            if let Some(request_body) = op.get("requestBody") {
+                content.push_str(&format!("\nRequest body: {request_body}\n"));
+            }
+            chunks.push((format!("{} {}", method.to_uppercase(), path), content));
+        }
+    }
+    Ok(chunks)
+}
+
+fn looks_like_html(body: &str) -> bool {
+    let head = body.trim_start();
+    head.starts_with("<!") || head.to_ascii_lowercase().starts_with("<html")
+}
+
+/// Drop tags, and the contents of `<script>` and `<style>` elements, which
+/// would otherwise be indexed as prose.
+fn strip_html(html: &str) -> String {
+    // ASCII lowercasing keeps byte offsets aligned with `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut i = 0;
+    while let Some(c) = html[i..].chars().next() {
+        if c == '<' {
+            let skipped = ["script", "style"].into_iter().find_map(|name| {
+                let rest = &lower[i + 1..];
+                let is_open = rest.starts_with(name)
+                    && !rest[name.len()..].starts_with(|c: char| c.is_ascii_alphanumeric());
+                is_open.then(|| {
+                    let close = format!("</{name}");
+                    lower[i..]
+                        .find(&close)
+                        .map(|end| i + end + close.len())
+                        .unwrap_or(html.len())
+                })
+            });
+            if let Some(end) = skipped {
+                i = end;
+                in_tag = true;
+                continue;
+            }
+        }
+        match c {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    out
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.len() > 1)
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Term-frequency score with a boost for title matches, normalised by chunk
+/// length so long chunks don't dominate.
+fn score_chunk(terms: &[String], title: &str, content: &str) -> f64 {
+    let title_tokens = tokenize(title);
+    let content_tokens = tokenize(content);
+    let mut score = 0.0;
+    for term in terms {
+        let title_hits = title_tokens.iter().filter(|t| *t == term).count() as f64;
+        let content_hits = content_tokens.iter().filter(|t| *t == term).count() as f64;
+        score += title_hits * 3.0 + content_hits;
+    }
+    score / (1.0 + (content_tokens.len() as f64).ln_1p())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_markdown_by_heading() {
+        let text = "intro text\n# Setup\ninstall deps\n## Running\nrun it\n";
+        let chunks = chunk_markdown("README.md", text);
+        assert_eq!(
+            chunks,
+            vec![
+                ("README.md".to_string(), "intro text".to_string()),
+                ("Setup".to_string(), "install deps".to_string()),
+                ("Running".to_string(), "run it".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_oversized_sections_on_paragraphs() {
+        let paragraph = "word ".repeat(300);
+        let text = format!("# Big\n{paragraph}\n\n{paragraph}\n\n{paragraph}\n");
+        let chunks = chunk_markdown("doc", &text);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|(title, _)| title == "Big"));
+    }
+
+    #[test]
+    fn ignores_headings_and_blank_lines_inside_code_fences() {
+        let paragraph = "word ".repeat(300);
+        let text = format!(
+            "# Usage\n{paragraph}\n\n```sh\n# not a heading\n\necho hi\n```\n\n~~~\n## nor this\n~~~\n"
+        );
+        let chunks = chunk_markdown("doc", &text);
+        assert!(chunks.iter().all(|(title, _)| title == "Usage"));
+        let fenced = chunks
+            .iter()
+            .find(|(_, body)| body.contains("```sh"))
+            .unwrap();
+        assert!(fenced.1.contains("```sh\n# not a heading\n\necho hi\n```"));
+    }
+
+    #[test]
+    fn strip_html_drops_script_and_style_contents() {
+        let html = "<html><head><STYLE>body { color: red }</STYLE>\
+            <script type=\"text/javascript\">var x = 1 < 2;</script></head>\
+            <body><p>Hello</p><scripts-note>kept</scripts-note></body></html>";
+        let text = strip_html(html);
+        assert!(text.contains("Hello"));
+        assert!(text.contains("kept"));
+        assert!(!text.contains("color"));
+        assert!(!text.contains("var x"));
+    }
+
+    #[test]
+    fn rejects_relative_local_paths() {
+        let source = DocSource {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            name: "docs".to_string(),
+            kind: DocSourceKind::LocalFolder,
+            location: "docs".to_string(),
+            refresh_interval_minutes: None,
+            last_indexed_at: None,
+            last_error: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        assert!(matches!(
+            resolve_local_path(&source),
+            Err(DocIndexError::RelativeLocalPath(_))
+        ));
+    }
+
+    #[test]
+    fn chunks_openapi_operations() {
+        let spec = r#"{"paths": {"/users": {"get": {"summary": "List users",
+            "parameters": [{"name": "limit", "in": "query"}]}}}}"#;
+        let chunks = chunk_openapi(spec).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].0, "GET /users");
+        assert!(chunks[0].1.contains("List users"));
+        assert!(chunks[0].1.contains("`limit` (query)"));
+    }
+
+    #[test]
+    fn title_matches_score_higher() {
+        let terms = tokenize("migrations");
+        let in_title = score_chunk(&terms, "Migrations", "how to run them");
+        let in_body = score_chunk(&terms, "Database", "how to run migrations");
+        assert!(in_title > in_body);
+        assert_eq!(score_chunk(&terms, "Other", "unrelated"), 0.0);
+    }
+}
@@ -3,16 +3,20 @@ use std::{str::FromStr, sync::Arc};
 use db::{
     DBService,
     models::{
-        execution_process::ExecutionProcess, scratch::Scratch, session::Session,
-        workspace::Workspace,
+        board::BoardState, execution_process::ExecutionProcess, scratch::Scratch, session::Session,
+        workspace::Workspace, workspace_child::WorkspaceChild,
     },
 };
 use serde_json::json;
 use sqlx::{Error as SqlxError, Sqlite, SqlitePool, decode::Decode, sqlite::SqliteOperation};
 use tokio::sync::RwLock;
-use utils::msg_store::MsgStore;
+use utils::msg_store::{MsgStore, SequencedMsg};
 use uuid::Uuid;
 
+#[path = "events/checkpoint.rs"]
+mod checkpoint;
+#[path = "events/compaction.rs"]
+mod compaction;
 #[path = "events/patches.rs"]
 pub mod patches;
 #[path = "events/streams.rs"]
@@ -20,8 +24,11 @@ mod streams;
 #[path = "events/types.rs"]
 pub mod types;
 
-pub use patches::{execution_process_patch, scratch_patch, workspace_patch};
-pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
+pub use patches::{board_patch, execution_process_patch, scratch_patch, workspace_patch};
+pub use types::{
+    EntityChecksum, EventError, EventPatch, EventPatchInner, EventsCheckpoint, HookTables,
+    RecordTypes,
+};
 
 #[derive(Clone)]
 pub struct EventService {
@@ -51,6 +58,14 @@ impl EventService {
                 Workspace::find_by_id_with_status(pool, session.workspace_id).await?
         {
             msg_store.push_patch(workspace_patch::replace(&workspace_with_status));
+
+            // The parent's card shows its children's progress.
+            if let Some(link) = WorkspaceChild::find_by_child(pool, session.workspace_id).await?
+                && let Some(parent_with_status) =
+                    Workspace::find_by_id_with_status(pool, link.parent_workspace_id).await?
+            {
+                msg_store.push_patch(workspace_patch::replace(&parent_with_status));
+            }
         }
         Ok(())
     }
@@ -315,4 +330,25 @@ impl EventService {
     pub fn msg_store(&self) -> &Arc<MsgStore> {
         &self.msg_store
     }
+
+    /// History plus live event patches, tagged with sequence numbers. A
+    /// reconnecting client passes the last `seq` it received as `since_seq`
+    /// and gets only the patches after it.
+    pub fn resume_stream(
+        &self,
+        since_seq: Option<u64>,
+    ) -> futures::stream::BoxStream<'static, Result<SequencedMsg, std::io::Error>> {
+        self.msg_store.sequenced_stream_after(since_seq)
+    }
+
+    /// Send a board's state to event stream clients. Boards change only
+    /// through the API, so route handlers call this after each change rather
+    /// than relying on a DB hook.
+    pub fn push_board_state(&self, state: &BoardState) {
+        self.msg_store.push_patch(board_patch::replace(state));
+    }
+
+    pub fn push_board_removed(&self, board_id: Uuid) {
+        self.msg_store.push_patch(board_patch::remove(board_id));
+    }
 }
@@ -0,0 +1,77 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::{
+    EventService,
+    types::{EntityChecksum, EventsCheckpoint},
+};
+
+impl EventService {
+    /// Current sequence number of the event stream plus a checksum of each
+    /// entity collection it carries. A client whose checksum differs after
+    /// applying patches up to `seq` has drifted and should refetch that
+    /// collection.
+    pub async fn checkpoint(&self) -> Result<EventsCheckpoint, sqlx::Error> {
+        // Read the sequence first: rows can only be newer than it, so a
+        // mismatch is never hidden, at worst reported spuriously.
+        let seq = self.msg_store.last_seq();
+        let pool = &self.db.pool;
+
+        let workspaces = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            "SELECT id, updated_at FROM workspaces ORDER BY id",
+        )
+        .fetch_all(pool)
+        .await?;
+        let execution_processes = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            "SELECT id, updated_at FROM execution_processes ORDER BY id",
+        )
+        .fetch_all(pool)
+        .await?;
+        let scratch = sqlx::query_as::<_, (Uuid, String, DateTime<Utc>)>(
+            "SELECT id, scratch_type, updated_at FROM scratch ORDER BY id, scratch_type",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(EventsCheckpoint {
+            seq,
+            workspaces: entity_checksum(
+                workspaces
+                    .iter()
+                    .map(|(id, updated_at)| (id.to_string(), *updated_at)),
+            ),
+            execution_processes: entity_checksum(
+                execution_processes
+                    .iter()
+                    .map(|(id, updated_at)| (id.to_string(), *updated_at)),
+            ),
+            scratch: entity_checksum(scratch.iter().map(|(id, scratch_type, updated_at)| {
+                (format!("{id}/{scratch_type}"), *updated_at)
+            })),
+        })
+    }
+}
+
+/// SHA-256 over `"{key} {updated_at}\n"` lines sorted by key, with
+/// `updated_at` formatted as it is serialized in patches.
+fn entity_checksum(rows: impl Iterator<Item = (String, DateTime<Utc>)>) -> EntityChecksum {
+    let mut lines: Vec<String> = rows
+        .map(|(key, updated_at)| {
+            format!(
+                "{key} {}\n",
+                updated_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            )
+        })
+        .collect();
+    lines.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    for line in &lines {
+        hasher.update(line.as_bytes());
+    }
+    EntityChecksum {
+        count: lines.len(),
+        checksum: format!("{:x}", hasher.finalize()),
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use json_patch::PatchOperation;
+use tokio::task::JoinHandle;
+
+use super::{EventService, patches::scratch_patch};
+
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Patches newer than this are left alone so briefly disconnected clients
+/// can still resume from their cursor.
+const KEEP_RECENT_PATCHES: usize = 2_000;
+
+impl EventService {
+    /// Periodically collapse old event patches into a snapshot of current
+    /// state, bounding what a new subscriber has to replay.
+    pub fn spawn_compaction_loop(&self) -> JoinHandle<()> {
+        let msg_store = self.msg_store.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(COMPACTION_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let compacted = msg_store.compact_patches(KEEP_RECENT_PATCHES, shared_key);
+                if compacted > 0 {
+                    tracing::debug!(compacted, "Compacted event stream history");
+                }
+            }
+        })
+    }
+}
+
+/// Scratch patches all target one path; clients tell them apart by id and
+/// payload type.
+fn shared_key(op: &PatchOperation) -> Option<String> {
+    let (path, value) = match op {
+        PatchOperation::Add(op) => (&op.path, &op.value),
+        PatchOperation::Replace(op) => (&op.path, &op.value),
+        _ => return None,
+    };
+    (*path == scratch_patch::SCRATCH_PATH)
+        .then(|| format!("{}:{}", value["id"], value["payload"]["type"]))
+}
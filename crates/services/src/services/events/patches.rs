@@ -1,5 +1,6 @@
 use db::models::{
-    execution_process::ExecutionProcess, scratch::Scratch, workspace::WorkspaceWithStatus,
+    board::BoardState, execution_process::ExecutionProcess, scratch::Scratch,
+    workspace::WorkspaceWithStatus,
 };
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use uuid::Uuid;
@@ -92,12 +93,73 @@ pub mod workspace_patch {
     }
 }
 
+/// Helper functions for creating board patches. Each board is sent whole, so
+/// a client applying a patch always holds a consistent column and card order.
+pub mod board_patch {
+    use super::*;
+
+    fn board_path(board_id: Uuid) -> String {
+        format!("/boards/{}", escape_pointer_segment(&board_id.to_string()))
+    }
+
+    /// Set the board's state. An add rather than a replace, so clients that
+    /// missed the board's creation still pick it up.
+    pub fn replace(state: &BoardState) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: board_path(state.board.id)
+                .try_into()
+                .expect("Board path should be valid"),
+            value: serde_json::to_value(state).expect("Board serialization should not fail"),
+        })])
+    }
+
+    pub fn remove(board_id: Uuid) -> Patch {
+        Patch(vec![PatchOperation::Remove(RemoveOperation {
+            path: board_path(board_id)
+                .try_into()
+                .expect("Board path should be valid"),
+        })])
+    }
+}
+
+/// Helper functions for creating queued follow-up message patches. A
+/// session's whole queue is sent on every change, keeping the order intact.
+pub mod queued_messages_patch {
+    use super::*;
+    use crate::services::queued_message::QueuedMessage;
+
+    fn queue_path(session_id: Uuid) -> String {
+        format!(
+            "/queued_messages/{}",
+            escape_pointer_segment(&session_id.to_string())
+        )
+    }
+
+    pub fn replace(session_id: Uuid, messages: &[QueuedMessage]) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: queue_path(session_id)
+                .try_into()
+                .expect("Queued messages path should be valid"),
+            value: serde_json::to_value(messages)
+                .expect("Queued message serialization should not fail"),
+        })])
+    }
+
+    pub fn remove(session_id: Uuid) -> Patch {
+        Patch(vec![PatchOperation::Remove(RemoveOperation {
+            path: queue_path(session_id)
+                .try_into()
+                .expect("Queued messages path should be valid"),
+        })])
+    }
+}
+
 /// Helper functions for creating scratch-specific patches.
 /// All patches use path "/scratch" - filtering is done by matching id and payload type in the value.
 pub mod scratch_patch {
     use super::*;
 
-    const SCRATCH_PATH: &str = "/scratch";
+    pub(crate) const SCRATCH_PATH: &str = "/scratch";
 
     /// Create patch for adding a new scratch
     pub fn add(scratch: &Scratch) -> Patch {
@@ -60,3 +60,21 @@ pub struct EventPatch {
     pub(crate) path: String,
     pub(crate) value: EventPatchInner,
 }
+
+/// Count and checksum of one entity collection in the event stream.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct EntityChecksum {
+    pub count: usize,
+    /// Hex SHA-256 over `"{id} {updated_at}\n"` lines sorted by id. Scratch
+    /// rows use `{id}/{scratch_type}` as the id.
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct EventsCheckpoint {
+    /// Sequence number of the last event, matching SSE event ids.
+    pub seq: u64,
+    pub workspaces: EntityChecksum,
+    pub execution_processes: EntityChecksum,
+    pub scratch: EntityChecksum,
+}
@@ -12,6 +12,7 @@ use db::{
         execution_process_logs::ExecutionProcessLogs,
     },
 };
+use executors::logs::spans::LogSpanTracker;
 use futures::{StreamExt, TryStreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 use sqlx::SqlitePool;
@@ -19,14 +20,16 @@ use tokio::{io::AsyncWriteExt, sync::RwLock, task::JoinHandle};
 use utils::{
     assets::prod_asset_dir_path,
     execution_logs::{
-        ExecutionLogWriter, process_log_file_path, process_log_file_path_in_root,
-        read_execution_log_file,
+        ExecutionLogWriter, append_to_execution_log_file, parse_log_jsonl_lossy,
+        process_log_file_path, process_log_file_path_in_root, read_execution_log_file,
     },
-    log_msg::LogMsg,
+    log_msg::{LogMsg, LogSpan},
     msg_store::MsgStore,
 };
 use uuid::Uuid;
 
+use super::log_search::LogSearchService;
+
 pub async fn migrate_execution_logs_to_files() -> Result<()> {
     let pool = DBService::new_migration_pool()
         .await
@@ -205,7 +208,7 @@ pub async fn load_raw_log_messages(pool: &SqlitePool, execution_id: Uuid) -> Opt
         .ok()
         .flatten()
     {
-        let messages = utils::execution_logs::parse_log_jsonl_lossy(execution_id, &jsonl);
+        let messages = parse_log_jsonl_lossy(execution_id, &jsonl);
         if !messages.is_empty() {
             return Some(messages);
         }
@@ -238,18 +241,77 @@ pub async fn load_raw_log_messages(pool: &SqlitePool, execution_id: Uuid) -> Opt
     }
 }
 
+/// Spans stored in a finished process's log. Unlike
+/// [`load_raw_log_messages`], a log that can't be read is an error rather
+/// than an empty log.
+pub async fn load_log_spans(pool: &SqlitePool, execution_id: Uuid) -> Result<Vec<LogSpan>> {
+    let Some(jsonl) = read_execution_logs_for_execution(pool, execution_id).await? else {
+        return Ok(Vec::new());
+    };
+    Ok(parse_log_jsonl_lossy(execution_id, &jsonl)
+        .into_iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Span(span) => Some(span),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Add spans derived for a log written before spans existed. Logs compressed
+/// by retention are recompressed with the spans; logs only found in the
+/// legacy DB table are written out to a file along with their raw messages.
+pub async fn append_backfilled_spans(
+    session_id: Uuid,
+    execution_id: Uuid,
+    raw_messages: &[LogMsg],
+    spans: &[LogSpan],
+) -> Result<()> {
+    let spans: Vec<LogMsg> = spans.iter().cloned().map(LogMsg::Span).collect();
+    let mut jsonl = String::new();
+    for msg in &spans {
+        jsonl.push_str(
+            &serde_json::to_string(msg)
+                .with_context(|| format!("serialize span for execution {}", execution_id))?,
+        );
+        jsonl.push('\n');
+    }
+
+    let path = process_log_file_path(session_id, execution_id);
+    match append_to_execution_log_file(&path, &jsonl).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let messages: Vec<LogMsg> = raw_messages.iter().cloned().chain(spans).collect();
+            append_log_messages(session_id, execution_id, &messages).await
+        }
+        result => result.with_context(|| format!("append spans to log at {}", path.display())),
+    }
+}
+
 pub async fn append_log_message(session_id: Uuid, execution_id: Uuid, msg: &LogMsg) -> Result<()> {
+    append_log_messages(session_id, execution_id, std::slice::from_ref(msg)).await
+}
+
+pub async fn append_log_messages(
+    session_id: Uuid,
+    execution_id: Uuid,
+    msgs: &[LogMsg],
+) -> Result<()> {
     let mut log_writer = ExecutionLogWriter::new_for_execution(session_id, execution_id)
         .await
         .with_context(|| format!("create log writer for execution {}", execution_id))?;
-    let json_line = serde_json::to_string(msg)
-        .with_context(|| format!("serialize log message for execution {}", execution_id))?;
-    let mut json_line_with_newline = json_line;
-    json_line_with_newline.push('\n');
+    for msg in msgs {
+        let json_line = serde_json::to_string(msg)
+            .with_context(|| format!("serialize log message for execution {}", execution_id))?;
+        let mut json_line_with_newline = json_line;
+        json_line_with_newline.push('\n');
+        log_writer
+            .append_jsonl_line(&json_line_with_newline)
+            .await
+            .with_context(|| format!("append log message for execution {}", execution_id))?;
+    }
     log_writer
-        .append_jsonl_line(&json_line_with_newline)
+        .flush()
         .await
-        .with_context(|| format!("append log message for execution {}", execution_id))?;
+        .with_context(|| format!("flush log file for execution {}", execution_id))?;
     Ok(())
 }
 
@@ -280,32 +342,35 @@ pub fn spawn_stream_raw_logs_to_storage(
 
         if let Some(store) = store {
             let mut stream = store.history_plus_stream();
+            let mut spans = LogSpanTracker::new();
 
             while let Some(Ok(msg)) = stream.next().await {
                 match &msg {
-                    LogMsg::Stdout(_) | LogMsg::Stderr(_) => match serde_json::to_string(&msg) {
-                        Ok(jsonl_line) => {
-                            let mut jsonl_line_with_newline = jsonl_line;
-                            jsonl_line_with_newline.push('\n');
-
-                            if let Err(e) =
-                                log_writer.append_jsonl_line(&jsonl_line_with_newline).await
-                            {
+                    LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::Span(_) => {
+                        match serde_json::to_string(&msg) {
+                            Ok(jsonl_line) => {
+                                let mut jsonl_line_with_newline = jsonl_line;
+                                jsonl_line_with_newline.push('\n');
+
+                                if let Err(e) =
+                                    log_writer.append_jsonl_line(&jsonl_line_with_newline).await
+                                {
+                                    tracing::error!(
+                                        "Failed to append log line for execution {}: {}",
+                                        execution_id,
+                                        e
+                                    );
+                                }
+                            }
+                            Err(e) => {
                                 tracing::error!(
-                                    "Failed to append log line for execution {}: {}",
+                                    "Failed to serialize log message for execution {}: {}",
                                     execution_id,
                                     e
                                 );
                             }
                         }
-                        Err(e) => {
-                            tracing::error!(
-                                "Failed to serialize log message for execution {}: {}",
-                                execution_id,
-                                e
-                            );
-                        }
-                    },
+                    }
                     LogMsg::SessionId(agent_session_id) => {
                         if let Err(e) = CodingAgentTurn::update_agent_session_id(
                             &db.pool,
@@ -341,10 +406,31 @@ pub fn spawn_stream_raw_logs_to_storage(
                     LogMsg::Finished => {
                         break;
                     }
-                    LogMsg::JsonPatch(_) | LogMsg::Ready => continue,
+                    // Spans go through the store so live subscribers see
+                    // them too; they come back round here to be written.
+                    LogMsg::JsonPatch(patch) => {
+                        for span in spans.push_patch(patch) {
+                            store.push(LogMsg::Span(span));
+                        }
+                    }
+                    LogMsg::Ready => continue,
                 }
             }
         }
+
+        if let Err(e) = log_writer.flush().await {
+            tracing::error!(
+                "Failed to flush log file for execution {}: {}",
+                execution_id,
+                e
+            );
+        }
+        if let Err(e) = LogSearchService::new(db)
+            .index_execution(session_id, execution_id)
+            .await
+        {
+            tracing::warn!("Failed to index logs for execution {}: {}", execution_id, e);
+        }
     })
 }
 
@@ -360,10 +446,8 @@ async fn read_execution_logs_for_execution(
     };
     let path = process_log_file_path(session_id, execution_id);
 
-    match tokio::fs::metadata(&path).await {
-        Ok(_) => Ok(Some(read_execution_log_file(&path).await.with_context(
-            || format!("read execution log file for execution {execution_id}"),
-        )?)),
+    match read_execution_log_file(&path).await {
+        Ok(contents) => Ok(Some(contents)),
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             if cfg!(debug_assertions) {
                 // Convenience for local development with a clone of a prod db. Read only access to prod logs.
@@ -386,7 +470,7 @@ async fn read_execution_logs_for_execution(
         }
         Err(e) => Err(e).with_context(|| {
             format!(
-                "check execution log file exists for execution {execution_id} at {}",
+                "read execution log file for execution {execution_id} at {}",
                 path.display()
             )
         }),
@@ -0,0 +1,503 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use db::models::repo_resource_tag::ResourceTag;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::config::ExecutionTagLimits;
+
+/// Admits coding agent executions when `max_concurrent_executions` or
+/// `execution_tag_limits` is set.
+///
+/// An execution carries the resource tags of its workspace's repos and is
+/// only admitted while every one of its tags is under its limit. Waiters
+/// held back by a tag are passed over, so a queue of GPU runs doesn't stop
+/// other work from using the free slots.
+///
+/// When capacity is exhausted, requests wait and freed slots are handed out
+/// in weighted round-robin order between projects (stride scheduling), so a
+/// project that queues many executions cannot starve the others; a project
+/// with weight 3 is admitted three times for every admission of a project
+/// with weight 1. Waiters of the same project are admitted in arrival order.
+/// Workspaces without a project share a single bucket.
+#[derive(Clone, Default)]
+pub struct ExecutionScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+/// Pass increment of a weight-1 project per admission. Divisible by every
+/// weight up to 16 so common weights interleave exactly.
+const STRIDE: u64 = 720_720;
+
+#[derive(Default)]
+struct SchedulerState {
+    capacity: Option<usize>,
+    tag_limits: ExecutionTagLimits,
+    total_running: usize,
+    running: HashMap<Option<Uuid>, usize>,
+    running_tags: HashMap<ResourceTag, usize>,
+    waiting: VecDeque<Waiter>,
+    /// Virtual time of each project's next admission.
+    pass: HashMap<Option<Uuid>, u64>,
+    /// Pass of the latest admission. Projects that were idle resume from
+    /// here instead of catching up on the turns they did not use.
+    virtual_time: u64,
+    /// Set on shutdown; no further executions are admitted.
+    draining: bool,
+}
+
+struct Waiter {
+    project_id: Option<Uuid>,
+    weight: u32,
+    tags: Vec<ResourceTag>,
+    tx: oneshot::Sender<ExecutionPermit>,
+}
+
+/// A running execution's slot. Dropping it frees the slot for the next
+/// waiter.
+pub struct ExecutionPermit {
+    project_id: Option<Uuid>,
+    tags: Vec<ResourceTag>,
+    state: Option<Arc<Mutex<SchedulerState>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ExecutionQueueStatus {
+    /// `None` when executions are not capped.
+    pub capacity: Option<usize>,
+    pub running: usize,
+    pub waiting: usize,
+    pub projects: Vec<ProjectQueueStatus>,
+    /// Tags with a limit or with running or waiting executions.
+    pub tags: Vec<TagQueueStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectQueueStatus {
+    /// `None` for workspaces that do not belong to a project.
+    pub project_id: Option<Uuid>,
+    pub running: usize,
+    pub waiting: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TagQueueStatus {
+    pub tag: ResourceTag,
+    pub limit: Option<usize>,
+    pub running: usize,
+    pub waiting: usize,
+}
+
+impl ExecutionScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for a slot for an execution in `project_id` using the resources
+    /// in `tags`. `capacity` and `tag_limits` are the current limits from the
+    /// config; an execution under all of them is admitted immediately.
+    pub async fn acquire(
+        &self,
+        project_id: Option<Uuid>,
+        weight: u32,
+        tags: Vec<ResourceTag>,
+        capacity: Option<usize>,
+        tag_limits: ExecutionTagLimits,
+    ) -> ExecutionPermit {
+        let rx = {
+            let mut state = self.state.lock().unwrap();
+            state.capacity = capacity;
+            state.tag_limits = tag_limits;
+            if state.waiting.is_empty() && state.fits(&tags) {
+                return state.admit(project_id, weight.max(1), tags, &self.state);
+            }
+
+            let (tx, rx) = oneshot::channel();
+            state.waiting.push_back(Waiter {
+                project_id,
+                weight: weight.max(1),
+                tags,
+                tx,
+            });
+            // A raised limit may free slots for waiters queued earlier.
+            state.dispatch(&self.state);
+            rx
+        };
+
+        rx.await
+            .expect("execution scheduler dropped a waiter without admitting it")
+    }
+
+    /// Stop admitting executions, for shutdown. Waiting executions stay
+    /// queued until the process exits.
+    pub fn start_draining(&self) {
+        self.state.lock().unwrap().draining = true;
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.state.lock().unwrap().draining
+    }
+
+    pub fn status(&self) -> ExecutionQueueStatus {
+        let state = self.state.lock().unwrap();
+        let mut projects: HashMap<Option<Uuid>, ProjectQueueStatus> = state
+            .running
+            .iter()
+            .map(|(project_id, running)| {
+                (
+                    *project_id,
+                    ProjectQueueStatus {
+                        project_id: *project_id,
+                        running: *running,
+                        waiting: 0,
+                    },
+                )
+            })
+            .collect();
+        for waiter in state.waiting.iter().filter(|w| !w.tx.is_closed()) {
+            projects
+                .entry(waiter.project_id)
+                .or_insert(ProjectQueueStatus {
+                    project_id: waiter.project_id,
+                    running: 0,
+                    waiting: 0,
+                })
+                .waiting += 1;
+        }
+
+        let mut projects: Vec<_> = projects.into_values().collect();
+        projects.sort_by_key(|p| p.project_id);
+
+        let tags = [
+            ResourceTag::CpuHeavy,
+            ResourceTag::Gpu,
+            ResourceTag::Network,
+        ]
+        .into_iter()
+        .map(|tag| TagQueueStatus {
+            tag,
+            limit: state.tag_limits.limit(tag),
+            running: state.running_tags.get(&tag).copied().unwrap_or(0),
+            waiting: state
+                .waiting
+                .iter()
+                .filter(|w| !w.tx.is_closed() && w.tags.contains(&tag))
+                .count(),
+        })
+        .filter(|t| t.limit.is_some() || t.running > 0 || t.waiting > 0)
+        .collect();
+        ExecutionQueueStatus {
+            capacity: state.capacity,
+            running: state.total_running,
+            waiting: projects.iter().map(|p| p.waiting).sum(),
+            projects,
+            tags,
+        }
+    }
+}
+
+impl SchedulerState {
+    fn has_capacity(&self) -> bool {
+        !self.draining && self.capacity.is_none_or(|cap| self.total_running < cap)
+    }
+
+    /// Whether an execution using `tags` can be admitted now.
+    fn fits(&self, tags: &[ResourceTag]) -> bool {
+        self.has_capacity()
+            && tags.iter().all(|tag| {
+                self.tag_limits
+                    .limit(*tag)
+                    .is_none_or(|limit| self.running_tags.get(tag).copied().unwrap_or(0) < limit)
+            })
+    }
+
+    fn admit(
+        &mut self,
+        project_id: Option<Uuid>,
+        weight: u32,
+        tags: Vec<ResourceTag>,
+        handle: &Arc<Mutex<SchedulerState>>,
+    ) -> ExecutionPermit {
+        let pass = self.pass_of(project_id);
+        self.virtual_time = pass;
+        self.pass
+            .insert(project_id, pass + STRIDE / u64::from(weight));
+        self.total_running += 1;
+        *self.running.entry(project_id).or_default() += 1;
+        for tag in &tags {
+            *self.running_tags.entry(*tag).or_default() += 1;
+        }
+        ExecutionPermit {
+            project_id,
+            tags,
+            state: Some(handle.clone()),
+        }
+    }
+
+    fn release(&mut self, project_id: Option<Uuid>, tags: &[ResourceTag]) {
+        self.total_running = self.total_running.saturating_sub(1);
+        if let Some(running) = self.running.get_mut(&project_id) {
+            *running = running.saturating_sub(1);
+            if *running == 0 {
+                self.running.remove(&project_id);
+            }
+        }
+        for tag in tags {
+            if let Some(running) = self.running_tags.get_mut(tag) {
+                *running = running.saturating_sub(1);
+                if *running == 0 {
+                    self.running_tags.remove(tag);
+                }
+            }
+        }
+    }
+
+    /// Hand free slots to waiters, most under-served project first.
+    fn dispatch(&mut self, handle: &Arc<Mutex<SchedulerState>>) {
+        self.waiting.retain(|w| !w.tx.is_closed());
+        while self.has_capacity() {
+            let Some(index) = self.next_waiter() else {
+                return;
+            };
+            let waiter = self.waiting.remove(index).expect("index from next_waiter");
+            let permit = self.admit(waiter.project_id, waiter.weight, waiter.tags, handle);
+            if let Err(mut permit) = waiter.tx.send(permit) {
+                // The request went away while queued; take the slot back
+                // here since dropping the permit would re-lock the state.
+                permit.state = None;
+                self.release(permit.project_id, &permit.tags);
+            }
+        }
+    }
+
+    fn pass_of(&self, project_id: Option<Uuid>) -> u64 {
+        self.pass
+            .get(&project_id)
+            .copied()
+            .unwrap_or(0)
+            .max(self.virtual_time)
+    }
+
+    /// Of the waiters whose tags are under their limits, the one whose
+    /// project has the lowest pass; the strict comparison keeps arrival
+    /// order on ties.
+    fn next_waiter(&self) -> Option<usize> {
+        let mut best: Option<(usize, u64)> = None;
+        for (index, waiter) in self.waiting.iter().enumerate() {
+            if !self.fits(&waiter.tags) {
+                continue;
+            }
+            let pass = self.pass_of(waiter.project_id);
+            if best.is_none_or(|(_, best_pass)| pass < best_pass) {
+                best = Some((index, pass));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        if let Some(handle) = self.state.take() {
+            let mut state = handle.lock().unwrap();
+            state.release(self.project_id, &self.tags);
+            state.dispatch(&handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn queued(
+        scheduler: &ExecutionScheduler,
+        project_id: Option<Uuid>,
+        weight: u32,
+        capacity: usize,
+    ) -> tokio::task::JoinHandle<ExecutionPermit> {
+        queued_tagged(
+            scheduler,
+            project_id,
+            weight,
+            Vec::new(),
+            capacity,
+            no_tag_limits(),
+        )
+        .await
+    }
+
+    async fn queued_tagged(
+        scheduler: &ExecutionScheduler,
+        project_id: Option<Uuid>,
+        weight: u32,
+        tags: Vec<ResourceTag>,
+        capacity: usize,
+        tag_limits: ExecutionTagLimits,
+    ) -> tokio::task::JoinHandle<ExecutionPermit> {
+        let scheduler = scheduler.clone();
+        let handle = tokio::spawn(async move {
+            scheduler
+                .acquire(project_id, weight, tags, Some(capacity), tag_limits)
+                .await
+        });
+        // Let the task enqueue before the next one.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        handle
+    }
+
+    fn no_tag_limits() -> ExecutionTagLimits {
+        ExecutionTagLimits::default()
+    }
+
+    #[tokio::test]
+    async fn unlimited_capacity_admits_immediately() {
+        let scheduler = ExecutionScheduler::new();
+        let _a = scheduler
+            .acquire(None, 1, Vec::new(), None, no_tag_limits())
+            .await;
+        let _b = scheduler
+            .acquire(None, 1, Vec::new(), None, no_tag_limits())
+            .await;
+        assert_eq!(scheduler.status().running, 2);
+    }
+
+    #[tokio::test]
+    async fn draining_admits_nothing() {
+        let scheduler = ExecutionScheduler::new();
+        let running = scheduler
+            .acquire(None, 1, Vec::new(), None, no_tag_limits())
+            .await;
+        scheduler.start_draining();
+
+        let next = queued(&scheduler, None, 1, 2).await;
+        drop(running);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!next.is_finished());
+        assert_eq!(scheduler.status().waiting, 1);
+    }
+
+    #[tokio::test]
+    async fn freed_slot_goes_to_starved_project() {
+        let scheduler = ExecutionScheduler::new();
+        let busy = Some(Uuid::new_v4());
+        let other = Some(Uuid::new_v4());
+
+        let first = scheduler
+            .acquire(busy, 1, Vec::new(), Some(1), no_tag_limits())
+            .await;
+        let busy_next = queued(&scheduler, busy, 1, 1).await;
+        let other_next = queued(&scheduler, other, 1, 1).await;
+        assert_eq!(scheduler.status().waiting, 2);
+
+        // `busy` queued first, but it already had a turn.
+        drop(first);
+        let permit = other_next.await.unwrap();
+        assert!(!busy_next.is_finished());
+        assert_eq!(permit.project_id, other);
+
+        drop(permit);
+        let permit = busy_next.await.unwrap();
+        assert_eq!(permit.project_id, busy);
+    }
+
+    #[test]
+    fn weights_scale_share() {
+        let scheduler = ExecutionScheduler::new();
+        let heavy = Some(Uuid::new_v4());
+        let light = Some(Uuid::new_v4());
+
+        let mut receivers = Vec::new();
+        {
+            let mut state = scheduler.state.lock().unwrap();
+            for (project_id, weight, count) in [(light, 1, 4), (heavy, 3, 8)] {
+                for _ in 0..count {
+                    let (tx, rx) = oneshot::channel();
+                    state.waiting.push_back(Waiter {
+                        project_id,
+                        weight,
+                        tags: Vec::new(),
+                        tx,
+                    });
+                    receivers.push(rx);
+                }
+            }
+            state.capacity = Some(1);
+            state.dispatch(&scheduler.state);
+        }
+
+        // Each admitted run finishes right away, handing its slot on.
+        let mut order = Vec::new();
+        for _ in 0..8 {
+            let permit = receivers
+                .iter_mut()
+                .find_map(|rx| rx.try_recv().ok())
+                .expect("one waiter admitted");
+            order.push(permit.project_id);
+        }
+
+        assert_eq!(
+            order,
+            [light, heavy, heavy, heavy, light, heavy, heavy, heavy]
+        );
+    }
+
+    #[tokio::test]
+    async fn tag_at_limit_does_not_block_other_executions() {
+        let scheduler = ExecutionScheduler::new();
+        let limits = ExecutionTagLimits {
+            gpu: Some(1),
+            ..Default::default()
+        };
+        let gpu_run = scheduler
+            .acquire(None, 1, vec![ResourceTag::Gpu], Some(3), limits.clone())
+            .await;
+
+        let gpu_next = queued_tagged(
+            &scheduler,
+            None,
+            1,
+            vec![ResourceTag::Gpu],
+            3,
+            limits.clone(),
+        )
+        .await;
+        let plain = queued_tagged(&scheduler, None, 1, Vec::new(), 3, limits).await;
+        let _plain = plain.await.unwrap();
+        assert!(!gpu_next.is_finished());
+
+        let status = scheduler.status();
+        assert_eq!(status.running, 2);
+        assert_eq!(status.tags.len(), 1);
+        assert_eq!(status.tags[0].limit, Some(1));
+        assert_eq!((status.tags[0].running, status.tags[0].waiting), (1, 1));
+
+        drop(gpu_run);
+        let permit = gpu_next.await.unwrap();
+        assert_eq!(permit.tags, [ResourceTag::Gpu]);
+    }
+
+    #[tokio::test]
+    async fn abandoned_waiter_does_not_hold_slot() {
+        let scheduler = ExecutionScheduler::new();
+        let first = scheduler
+            .acquire(None, 1, Vec::new(), Some(1), no_tag_limits())
+            .await;
+        let abandoned = queued(&scheduler, None, 1, 1).await;
+        abandoned.abort();
+        let _ = abandoned.await;
+
+        drop(first);
+        assert_eq!(scheduler.status().running, 0);
+        let _next = scheduler
+            .acquire(None, 1, Vec::new(), Some(1), no_tag_limits())
+            .await;
+    }
+}
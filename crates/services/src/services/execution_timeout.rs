@@ -0,0 +1,215 @@
+//! Wall-clock and idle-output timeouts for coding agent runs.
+//!
+//! Limits come from the session's overrides, then the executor's entry in
+//! [`ExecutorTimeoutConfig`], then its default, field by field. The container
+//! service watches each run with a [`TimeoutWatch`] and kills it once a limit
+//! is exceeded.
+
+use std::time::{Duration, Instant};
+
+use db::models::session_timeout::{ExecutionTimeouts, SessionTimeout};
+use executors::executors::BaseCodingAgent;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::services::config::ExecutorTimeoutConfig;
+
+/// The timeouts a run of `executor` in a session with `session` overrides gets.
+pub fn effective_timeouts(
+    config: &ExecutorTimeoutConfig,
+    executor: Option<BaseCodingAgent>,
+    session: Option<ExecutionTimeouts>,
+) -> ExecutionTimeouts {
+    let per_executor = executor
+        .and_then(|executor| config.per_executor.get(&executor).copied())
+        .unwrap_or_default();
+    session
+        .unwrap_or_default()
+        .or(per_executor)
+        .or(config.default)
+}
+
+/// [`effective_timeouts`] with the session's overrides loaded from the
+/// database.
+pub async fn effective_timeouts_for_session(
+    pool: &SqlitePool,
+    config: &ExecutorTimeoutConfig,
+    session_id: Uuid,
+    executor: Option<BaseCodingAgent>,
+) -> Result<ExecutionTimeouts, sqlx::Error> {
+    let session = SessionTimeout::find_by_session_id(pool, session_id)
+        .await?
+        .map(|timeout| timeout.timeouts());
+    Ok(effective_timeouts(config, executor, session))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    WallClock,
+    Idle,
+}
+
+/// Resolved limits; unset and zero limits are disabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeoutLimits {
+    pub wall_clock: Option<Duration>,
+    pub idle: Option<Duration>,
+}
+
+impl From<ExecutionTimeouts> for TimeoutLimits {
+    fn from(timeouts: ExecutionTimeouts) -> Self {
+        let minutes = |value: Option<u32>| {
+            value
+                .filter(|minutes| *minutes > 0)
+                .map(|minutes| Duration::from_secs(u64::from(minutes) * 60))
+        };
+        Self {
+            wall_clock: minutes(timeouts.wall_clock_minutes),
+            idle: minutes(timeouts.idle_minutes),
+        }
+    }
+}
+
+impl TimeoutLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.wall_clock.is_none() && self.idle.is_none()
+    }
+
+    /// The line written to the run's log when it is killed.
+    pub fn message(&self, kind: TimeoutKind) -> String {
+        let (limit, what) = match kind {
+            TimeoutKind::WallClock => (self.wall_clock, "running for"),
+            TimeoutKind::Idle => (self.idle, "producing no output for"),
+        };
+        let minutes = limit.map(|limit| limit.as_secs() / 60).unwrap_or_default();
+        format!("Execution timed out after {what} {minutes} minute(s) and was stopped.")
+    }
+}
+
+/// Tracks one run against its limits.
+#[derive(Debug, Clone)]
+pub struct TimeoutWatch {
+    limits: TimeoutLimits,
+    started_at: Instant,
+    last_activity: Instant,
+}
+
+impl TimeoutWatch {
+    pub fn new(limits: TimeoutLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            started_at: now,
+            last_activity: now,
+        }
+    }
+
+    pub fn limits(&self) -> TimeoutLimits {
+        self.limits
+    }
+
+    /// Output was produced, or the run is waiting on the user.
+    pub fn record_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// When the next limit runs out, or `None` if there are no limits.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let wall_clock = self.limits.wall_clock.map(|limit| self.started_at + limit);
+        let idle = self.limits.idle.map(|limit| self.last_activity + limit);
+        match (wall_clock, idle) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    pub fn expired(&self, now: Instant) -> Option<TimeoutKind> {
+        if self
+            .limits
+            .wall_clock
+            .is_some_and(|limit| now >= self.started_at + limit)
+        {
+            return Some(TimeoutKind::WallClock);
+        }
+        if self
+            .limits
+            .idle
+            .is_some_and(|limit| now >= self.last_activity + limit)
+        {
+            return Some(TimeoutKind::Idle);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeouts(wall_clock_minutes: Option<u32>, idle_minutes: Option<u32>) -> ExecutionTimeouts {
+        ExecutionTimeouts {
+            wall_clock_minutes,
+            idle_minutes,
+        }
+    }
+
+    #[test]
+    fn session_overrides_executor_overrides_default() {
+        let mut config = ExecutorTimeoutConfig {
+            default: timeouts(Some(120), Some(15)),
+            ..Default::default()
+        };
+        config
+            .per_executor
+            .insert(BaseCodingAgent::Codex, timeouts(Some(60), None));
+
+        let codex = Some(BaseCodingAgent::Codex);
+        assert_eq!(
+            effective_timeouts(&config, codex, None),
+            timeouts(Some(60), Some(15))
+        );
+        assert_eq!(
+            effective_timeouts(&config, codex, Some(timeouts(None, Some(0)))),
+            timeouts(Some(60), Some(0))
+        );
+        assert_eq!(
+            effective_timeouts(&config, Some(BaseCodingAgent::Amp), None),
+            timeouts(Some(120), Some(15))
+        );
+
+        // A zero limit disables it even when a fallback is set.
+        let limits = TimeoutLimits::from(timeouts(Some(60), Some(0)));
+        assert_eq!(limits.wall_clock, Some(Duration::from_secs(3600)));
+        assert_eq!(limits.idle, None);
+    }
+
+    #[test]
+    fn activity_defers_only_the_idle_timeout() {
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let mut watch = TimeoutWatch::new(
+            TimeoutLimits {
+                wall_clock: Some(10 * minute),
+                idle: Some(3 * minute),
+            },
+            start,
+        );
+
+        assert_eq!(watch.next_deadline(), Some(start + 3 * minute));
+        assert_eq!(watch.expired(start + 3 * minute), Some(TimeoutKind::Idle));
+
+        watch.record_activity(start + 2 * minute);
+        assert_eq!(watch.expired(start + 3 * minute), None);
+        assert_eq!(watch.next_deadline(), Some(start + 5 * minute));
+
+        watch.record_activity(start + 9 * minute);
+        assert_eq!(watch.next_deadline(), Some(start + 10 * minute));
+        assert_eq!(
+            watch.expired(start + 10 * minute),
+            Some(TimeoutKind::WallClock)
+        );
+
+        let unlimited = TimeoutWatch::new(TimeoutLimits::default(), start);
+        assert_eq!(unlimited.next_deadline(), None);
+        assert_eq!(unlimited.expired(start + 1000 * minute), None);
+    }
+}
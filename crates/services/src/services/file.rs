@@ -9,6 +9,11 @@ use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use super::{
+    chunked_upload::ChunkedUploads,
+    image_processing::{self, ImageLimits, ImageProcessingError},
+};
+
 #[derive(Debug, thiserror::Error)]
 pub enum FileError {
     #[error("IO error: {0}")]
@@ -23,6 +28,18 @@ pub enum FileError {
     #[error("File not found")]
     NotFound,
 
+    #[error(transparent)]
+    Image(#[from] ImageProcessingError),
+
+    #[error("Upload not found or expired")]
+    UploadNotFound,
+
+    #[error("Invalid upload: {0}")]
+    InvalidUpload(String),
+
+    #[error("Upload hash mismatch: expected {expected}, got {actual}")]
+    HashMismatch { expected: String, actual: String },
+
     #[error("Failed to build response: {0}")]
     ResponseBuildError(String),
 }
@@ -62,6 +79,7 @@ pub struct FileService {
     legacy_cache_dir: PathBuf,
     pool: SqlitePool,
     max_size_bytes: u64,
+    chunked_uploads: ChunkedUploads,
 }
 
 impl FileService {
@@ -69,25 +87,50 @@ impl FileService {
         let cache_dir = utils::cache_dir().join("attachments");
         let legacy_cache_dir = utils::cache_dir().join("images");
         fs::create_dir_all(&cache_dir)?;
+        let max_size_bytes = 20 * 1024 * 1024; // 20MB default
         Ok(Self {
             cache_dir,
             legacy_cache_dir,
             pool,
-            max_size_bytes: 20 * 1024 * 1024, // 20MB default
+            max_size_bytes,
+            chunked_uploads: ChunkedUploads::new(
+                utils::cache_dir().join("uploads"),
+                max_size_bytes,
+            ),
         })
     }
 
+    pub fn chunked_uploads(&self) -> &ChunkedUploads {
+        &self.chunked_uploads
+    }
+
+    /// Store an upload, deduplicated by content hash. Images are normalised
+    /// to `image_limits` first, so the hash is of what is actually stored.
     pub async fn store_file(
         &self,
         data: &[u8],
         original_filename: &str,
+        image_limits: ImageLimits,
     ) -> Result<File, FileError> {
-        let file_size = data.len() as u64;
-
-        if file_size > self.max_size_bytes {
-            return Err(FileError::TooLarge(file_size, self.max_size_bytes));
+        if data.len() as u64 > self.max_size_bytes {
+            return Err(FileError::TooLarge(data.len() as u64, self.max_size_bytes));
         }
 
+        let processed = {
+            let data = data.to_vec();
+            let filename = original_filename.to_string();
+            tokio::task::spawn_blocking(move || {
+                image_processing::process_upload(&data, &filename, image_limits)
+            })
+            .await
+            .map_err(|e| FileError::Io(std::io::Error::other(e)))??
+        };
+        let (data, original_filename) = match &processed {
+            Some(processed) => (processed.data.as_slice(), processed.filename.as_str()),
+            None => (data, original_filename),
+        };
+        let file_size = data.len() as u64;
+
         let hash = format!("{:x}", Sha256::digest(data));
 
         let extension = Path::new(original_filename)
@@ -1,10 +1,13 @@
 #[cfg(not(feature = "qa-mode"))]
 use std::collections::HashSet;
 use std::{
+    collections::HashMap,
     fs,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
+use chrono::{DateTime, Utc};
+use git::StatusEntry;
 #[cfg(not(feature = "qa-mode"))]
 use ignore::WalkBuilder;
 use serde::Serialize;
@@ -22,6 +25,8 @@ pub enum FilesystemError {
     DirectoryDoesNotExist,
     #[error("Path is not a directory")]
     PathIsNotDirectory,
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
     #[error("Failed to read directory: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -40,6 +45,70 @@ pub struct DirectoryEntry {
     pub last_modified: Option<u64>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum TreeGitStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+impl From<&StatusEntry> for TreeGitStatus {
+    fn from(entry: &StatusEntry) -> Self {
+        let codes = (entry.staged, entry.unstaged);
+        if entry.is_untracked {
+            TreeGitStatus::Untracked
+        } else if codes.0 == 'U' || codes.1 == 'U' || codes == ('A', 'A') || codes == ('D', 'D') {
+            TreeGitStatus::Conflicted
+        } else if codes.0 == 'D' || codes.1 == 'D' {
+            TreeGitStatus::Deleted
+        } else if codes.0 == 'R' {
+            TreeGitStatus::Renamed
+        } else if codes.0 == 'A' {
+            TreeGitStatus::Added
+        } else {
+            TreeGitStatus::Modified
+        }
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TreeEntry {
+    pub name: String,
+    /// Path relative to the listed root, `/`-separated.
+    pub path: String,
+    /// 1 for direct children of the requested directory.
+    pub depth: u32,
+    pub is_directory: bool,
+    pub size_bytes: Option<u64>,
+    pub modified_at: Option<DateTime<Utc>>,
+    /// For directories, `modified` when anything below them changed.
+    pub git_status: Option<TreeGitStatus>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct DirectoryTreePage {
+    pub path: String,
+    /// Depth-first, sorted by name within each directory.
+    pub entries: Vec<TreeEntry>,
+    /// Pass as `offset` to get the next page; `None` on the last page.
+    pub next_offset: Option<u32>,
+}
+
+/// Which part of a directory tree to list.
+#[derive(Debug, Clone)]
+pub struct TreePageRequest<'a> {
+    /// Directory to list, relative to the root.
+    pub path: &'a str,
+    pub depth: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
 impl Default for FilesystemService {
     fn default() -> Self {
         Self::new()
@@ -303,6 +372,84 @@ impl FilesystemService {
             })
     }
 
+    /// List the tree below `request.path` inside `root`, skipping files
+    /// ignored by git and `.git` directories. `changes` maps changed paths,
+    /// relative to `root`, to their git status.
+    pub fn list_tree(
+        &self,
+        root: &Path,
+        request: &TreePageRequest<'_>,
+        changes: &HashMap<String, TreeGitStatus>,
+    ) -> Result<DirectoryTreePage, FilesystemError> {
+        let rel = Path::new(request.path);
+        if rel
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(FilesystemError::InvalidPath(
+                "Path must be relative and stay inside the workspace".to_string(),
+            ));
+        }
+        let base = root.join(rel);
+        Self::verify_directory(&base)?;
+
+        let mut walker = ignore::WalkBuilder::new(&base);
+        walker
+            .max_depth(Some(request.depth))
+            .hidden(false)
+            .parents(true)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .sort_by_file_name(|a, b| a.cmp(b));
+
+        let mut entries = Vec::new();
+        let mut next_offset = None;
+        for (index, entry) in walker
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.depth() > 0)
+            .enumerate()
+            .skip(request.offset)
+        {
+            if entries.len() == request.limit {
+                next_offset = Some(index as u32);
+                break;
+            }
+            let Ok(path) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            let path = path.to_string_lossy().replace('\\', "/");
+            let metadata = entry.metadata().ok();
+            let is_directory = metadata.as_ref().is_some_and(|m| m.is_dir());
+            let git_status = if is_directory {
+                let prefix = format!("{path}/");
+                changes
+                    .keys()
+                    .any(|changed| changed.starts_with(&prefix))
+                    .then_some(TreeGitStatus::Modified)
+            } else {
+                changes.get(&path).copied()
+            };
+            entries.push(TreeEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                path,
+                depth: entry.depth() as u32,
+                is_directory,
+                size_bytes: metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len()),
+                modified_at: metadata
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .map(DateTime::<Utc>::from),
+                git_status,
+            });
+        }
+
+        Ok(DirectoryTreePage {
+            path: request.path.to_string(),
+            entries,
+            next_offset,
+        })
+    }
+
     fn verify_directory(path: &Path) -> Result<(), FilesystemError> {
         if !path.exists() {
             return Err(FilesystemError::DirectoryDoesNotExist);
@@ -363,3 +510,63 @@ impl FilesystemService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_tree_respects_gitignore_and_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::create_dir_all(repo.join("target")).unwrap();
+        fs::write(repo.join(".gitignore"), "target/\n").unwrap();
+        fs::write(repo.join("src/a.rs"), "a").unwrap();
+        fs::write(repo.join("src/b.rs"), "bb").unwrap();
+        fs::write(repo.join("target/out"), "x").unwrap();
+
+        let changes = HashMap::from([("repo/src/b.rs".to_string(), TreeGitStatus::Modified)]);
+        let request = TreePageRequest {
+            path: "",
+            depth: 3,
+            offset: 0,
+            limit: 3,
+        };
+        let service = FilesystemService::new();
+        let first = service.list_tree(dir.path(), &request, &changes).unwrap();
+        let paths: Vec<_> = first.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["repo", "repo/.gitignore", "repo/src"]);
+        assert_eq!(first.entries[2].git_status, Some(TreeGitStatus::Modified));
+        assert_eq!(first.next_offset, Some(3));
+
+        let rest = service
+            .list_tree(
+                dir.path(),
+                &TreePageRequest {
+                    offset: 3,
+                    ..request.clone()
+                },
+                &changes,
+            )
+            .unwrap();
+        let paths: Vec<_> = rest.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["repo/src/a.rs", "repo/src/b.rs"]);
+        assert_eq!(rest.entries[1].size_bytes, Some(2));
+        assert_eq!(rest.entries[1].git_status, Some(TreeGitStatus::Modified));
+        assert_eq!(rest.next_offset, None);
+
+        assert!(matches!(
+            service.list_tree(
+                dir.path(),
+                &TreePageRequest {
+                    path: "../",
+                    ..request
+                },
+                &changes
+            ),
+            Err(FilesystemError::InvalidPath(_))
+        ));
+    }
+}
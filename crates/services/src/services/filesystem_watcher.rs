@@ -7,28 +7,105 @@ use std::{
 
 use futures::{
     SinkExt, StreamExt,
-    channel::mpsc::{Receiver, channel},
+    channel::mpsc::{Receiver, Sender, channel},
 };
 use ignore::{
     WalkBuilder,
     gitignore::{Gitignore, GitignoreBuilder},
 };
 use notify::{
-    RecommendedWatcher, RecursiveMode,
+    PollWatcher, RecommendedWatcher, RecursiveMode,
     event::{EventKind, ModifyKind, RenameMode},
 };
 use notify_debouncer_full::{
-    DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
+    DebounceEventResult, DebouncedEvent, Debouncer, FileIdMap, RecommendedCache, new_debouncer,
+    new_debouncer_opt,
 };
 use thiserror::Error;
 use utils::path::ALWAYS_SKIP_DIRS;
 
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(200);
+/// Scan interval once a watcher has fallen back to polling.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 pub type WatcherComponents = (
-    Arc<Mutex<Debouncer<RecommendedWatcher, RecommendedCache>>>,
+    Arc<Mutex<WatcherBackend>>,
     Receiver<DebounceEventResult>,
     PathBuf,
 );
 
+/// Native watchers need one inotify watch per directory on Linux. When the
+/// system limit is hit, or the native watcher fails later on, the watcher
+/// falls back to polling the whole root.
+pub enum WatcherBackend {
+    Native(Debouncer<RecommendedWatcher, RecommendedCache>),
+    Polling(Debouncer<PollWatcher, FileIdMap>),
+}
+
+impl WatcherBackend {
+    pub fn is_polling(&self) -> bool {
+        matches!(self, Self::Polling(_))
+    }
+
+    fn watch(&mut self, path: &Path, mode: RecursiveMode) -> notify::Result<()> {
+        match self {
+            Self::Native(debouncer) => debouncer.watch(path, mode),
+            Self::Polling(debouncer) => debouncer.watch(path, mode),
+        }
+    }
+
+    fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+        match self {
+            Self::Native(debouncer) => debouncer.unwatch(path),
+            Self::Polling(debouncer) => debouncer.unwatch(path),
+        }
+    }
+}
+
+fn is_watch_limit_error(error: &notify::Error) -> bool {
+    matches!(error.kind, notify::ErrorKind::MaxFilesWatch)
+}
+
+/// Errors after which a native watcher can no longer be trusted to report
+/// every change: running out of watches, or failing to read its event queue.
+fn requires_polling(error: &notify::Error) -> bool {
+    is_watch_limit_error(error) || matches!(error.kind, notify::ErrorKind::Io(_))
+}
+
+fn polling_backend(
+    root: &Path,
+    mut tx: Sender<DebounceEventResult>,
+) -> notify::Result<WatcherBackend> {
+    let mut polling = WatcherBackend::Polling(new_debouncer_opt(
+        DEBOUNCE_TIMEOUT,
+        None,
+        move |res: DebounceEventResult| {
+            futures::executor::block_on(async {
+                tx.send(res).await.ok();
+            });
+        },
+        FileIdMap::new(),
+        notify::Config::default().with_poll_interval(POLL_INTERVAL),
+    )?);
+    polling.watch(root, RecursiveMode::Recursive)?;
+    Ok(polling)
+}
+
+/// Replace a running native backend with a poller on `root`. Returns the
+/// channel the poller reports on; dropping the native debouncer releases its
+/// watches and closes the channel it reported on.
+fn switch_to_polling(
+    backend: &mut WatcherBackend,
+    watched: &mut WatchedDirs,
+    root: &Path,
+) -> notify::Result<Receiver<DebounceEventResult>> {
+    let (tx, rx) = channel::<DebounceEventResult>(64);
+    *backend = polling_backend(root, tx)?;
+    *watched = WatchedDirs::default();
+    watched.insert(root.to_path_buf(), RecursiveMode::Recursive);
+    Ok(rx)
+}
+
 #[derive(Debug, Error)]
 pub enum FilesystemWatcherError {
     #[error(transparent)]
@@ -402,27 +479,31 @@ fn determine_watch_mode(path: &Path, gi: &Gitignore, canonical_root: &Path) -> R
     RecursiveMode::Recursive
 }
 
-/// Add a watch for a newly created directory
+/// Add a watch for a newly created directory. Returns true when the watch
+/// limit was hit and the directory is left unwatched.
 fn add_directory_watch(
-    debouncer: &mut Debouncer<RecommendedWatcher, RecommendedCache>,
+    debouncer: &mut WatcherBackend,
     watched_dirs: &mut WatchedDirs,
     dir_path: &Path,
     gi: &Gitignore,
     canonical_root: &Path,
-) {
+) -> bool {
     let canonical_dir = canonicalize_lossy(dir_path);
 
     if !path_allowed(&canonical_dir, gi, canonical_root) {
-        return;
+        return false;
     }
 
     if watched_dirs.contains(&canonical_dir) || watched_dirs.has_recursive_cover(&canonical_dir) {
-        return;
+        return false;
     }
 
     let mode = determine_watch_mode(&canonical_dir, gi, canonical_root);
 
     if let Err(e) = debouncer.watch(&canonical_dir, mode) {
+        if is_watch_limit_error(&e) {
+            return true;
+        }
         tracing::warn!("Failed to watch new directory {:?}: {}", canonical_dir, e);
     } else {
         if matches!(mode, RecursiveMode::Recursive) {
@@ -435,11 +516,12 @@ fn add_directory_watch(
 
         watched_dirs.insert(canonical_dir, mode);
     }
+    false
 }
 
 /// Remove a watch for a deleted directory
 fn remove_directory_watch(
-    debouncer: &mut Debouncer<RecommendedWatcher, RecommendedCache>,
+    debouncer: &mut WatcherBackend,
     watched_dirs: &mut WatchedDirs,
     dir_path: &Path,
 ) {
@@ -464,45 +546,59 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
     let gi_clone = gi_set.clone();
     let root_for_task = canonical_root.clone();
 
-    let debouncer_unwrapped = new_debouncer(
-        Duration::from_millis(200),
+    let poll_tx = raw_tx.clone();
+    let mut native = WatcherBackend::Native(new_debouncer(
+        DEBOUNCE_TIMEOUT,
         None,
         move |res: DebounceEventResult| {
             futures::executor::block_on(async {
                 raw_tx.send(res).await.ok();
             });
         },
-    )?;
-
-    let debouncer = Arc::new(Mutex::new(debouncer_unwrapped));
-    let debouncer_for_init = debouncer.clone();
-    let debouncer_for_task = Arc::downgrade(&debouncer);
-
-    let watched_dirs: Arc<Mutex<WatchedDirs>> = Arc::new(Mutex::new(WatchedDirs::default()));
-    let watched_dirs_for_task = watched_dirs.clone();
-
-    let watch_targets = collect_watch_directories(&canonical_root, &gi_set);
-    {
-        let mut debouncer_guard = debouncer_for_init.lock().unwrap();
-        let mut watched = watched_dirs.lock().unwrap();
-
-        for target in &watch_targets {
-            if let Err(e) = debouncer_guard.watch(&target.path, target.recursive) {
-                tracing::warn!("Failed to watch {:?}: {}", target.path, e);
-            } else {
-                watched.insert(target.path.clone(), target.recursive);
+    )?);
+
+    let mut watched = WatchedDirs::default();
+    let mut hit_watch_limit = false;
+    for target in collect_watch_directories(&canonical_root, &gi_set) {
+        match native.watch(&target.path, target.recursive) {
+            Ok(()) => watched.insert(target.path, target.recursive),
+            Err(e) if is_watch_limit_error(&e) => {
+                hit_watch_limit = true;
+                break;
             }
+            Err(e) => tracing::warn!("Failed to watch {:?}: {}", target.path, e),
         }
     }
 
+    let backend = if hit_watch_limit {
+        tracing::warn!(
+            "Watch limit reached for {:?}, falling back to polling every {:?}",
+            canonical_root,
+            POLL_INTERVAL
+        );
+        // Release the watches taken so far before starting the poller.
+        drop(native);
+        watched = WatchedDirs::default();
+        watched.insert(canonical_root.clone(), RecursiveMode::Recursive);
+        polling_backend(&canonical_root, poll_tx)?
+    } else {
+        drop(poll_tx);
+        native
+    };
+
+    let debouncer = Arc::new(Mutex::new(backend));
+    let debouncer_for_task = Arc::downgrade(&debouncer);
+    let watched_dirs_for_task = Arc::new(Mutex::new(watched));
+
     std::thread::spawn(move || {
         while let Some(result) = futures::executor::block_on(async { raw_rx.next().await }) {
             let Some(debouncer_arc) = debouncer_for_task.upgrade() else {
                 break;
             };
 
-            match result {
+            let needs_polling = match result {
                 Ok(events) => {
+                    let mut hit_watch_limit = false;
                     let mut debouncer_guard = debouncer_arc.lock().unwrap();
                     let mut watched = watched_dirs_for_task.lock().unwrap();
 
@@ -510,7 +606,7 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
                         if event.kind.is_create() {
                             for path in &event.paths {
                                 if path.is_dir() {
-                                    add_directory_watch(
+                                    hit_watch_limit |= add_directory_watch(
                                         &mut debouncer_guard,
                                         &mut watched,
                                         path,
@@ -537,7 +633,7 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
                                 RenameMode::To => {
                                     for path in &event.paths {
                                         if path.is_dir() {
-                                            add_directory_watch(
+                                            hit_watch_limit |= add_directory_watch(
                                                 &mut debouncer_guard,
                                                 &mut watched,
                                                 path,
@@ -558,7 +654,7 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
                                         if let Some(to) = rest.last()
                                             && to.is_dir()
                                         {
-                                            add_directory_watch(
+                                            hit_watch_limit |= add_directory_watch(
                                                 &mut debouncer_guard,
                                                 &mut watched,
                                                 to,
@@ -579,7 +675,7 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
 
                                     for path in &event.paths {
                                         if path.is_dir() {
-                                            add_directory_watch(
+                                            hit_watch_limit |= add_directory_watch(
                                                 &mut debouncer_guard,
                                                 &mut watched,
                                                 path,
@@ -606,11 +702,39 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
                             filtered_tx.send(Ok(filtered_events)).await.ok();
                         });
                     }
+                    hit_watch_limit
                 }
                 Err(errors) => {
+                    let needs_polling = errors.iter().any(requires_polling);
                     futures::executor::block_on(async {
                         filtered_tx.send(Err(errors)).await.ok();
                     });
+                    needs_polling
+                }
+            };
+
+            // A native watcher that failed at runtime is replaced in place, so
+            // holders of the backend see the switch. Events still queued from
+            // the native watcher are dropped along with its channel.
+            if needs_polling {
+                let mut backend = debouncer_arc.lock().unwrap();
+                if !backend.is_polling() {
+                    let mut watched = watched_dirs_for_task.lock().unwrap();
+                    match switch_to_polling(&mut backend, &mut watched, &root_for_task) {
+                        Ok(rx) => {
+                            tracing::warn!(
+                                "Native watcher for {:?} failed, falling back to polling every {:?}",
+                                root_for_task,
+                                POLL_INTERVAL
+                            );
+                            raw_rx = rx;
+                        }
+                        Err(e) => tracing::error!(
+                            "Failed to fall back to polling for {:?}: {}",
+                            root_for_task,
+                            e
+                        ),
+                    }
                 }
             }
         }
@@ -618,3 +742,52 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
 
     Ok((debouncer, filtered_rx, canonical_root))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_limits_and_io_failures_require_polling() {
+        assert!(requires_polling(&notify::Error::new(
+            notify::ErrorKind::MaxFilesWatch
+        )));
+        assert!(requires_polling(&notify::Error::io(std::io::Error::other(
+            "inotify read failed"
+        ))));
+        assert!(!requires_polling(&notify::Error::path_not_found()));
+        assert!(!requires_polling(&notify::Error::generic("transient")));
+    }
+
+    #[tokio::test]
+    async fn a_native_watcher_switched_to_polling_keeps_reporting_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (backend, _native_rx, root) = async_watcher(dir.path().to_path_buf()).unwrap();
+
+        let mut watched = WatchedDirs::default();
+        let mut rx = {
+            let mut backend = backend.lock().unwrap();
+            let rx = switch_to_polling(&mut backend, &mut watched, &root).unwrap();
+            assert!(backend.is_polling());
+            rx
+        };
+        assert!(watched.contains(&root));
+
+        let file = root.join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let reported = tokio::time::timeout(Duration::from_secs(10), async {
+            while let Some(result) = rx.next().await {
+                if let Ok(events) = result
+                    && events.iter().any(|event| event.paths.contains(&file))
+                {
+                    return true;
+                }
+            }
+            false
+        })
+        .await
+        .expect("the poller reported nothing");
+        assert!(reported);
+    }
+}
@@ -0,0 +1,253 @@
+//! Dependency checks behind `GET /api/health/checks` and `/api/health/ready`,
+//! for uptime monitors, service managers and the first-run wizard. Each check
+//! reports its own status and latency; the report is `failed` if any check
+//! failed.
+
+use std::{
+    future::Future,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use db::DBService;
+use executors::profile::{ExecutorConfigs, ExecutorProfileId};
+use git::GitCli;
+use serde::Serialize;
+use ts_rs::TS;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Below this much free space in the asset dir the check is degraded.
+const DISK_SPACE_WARN_BYTES: u64 = 1024 * 1024 * 1024;
+/// Below this much free space logs and the database may fail to write.
+const DISK_SPACE_FAIL_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Skipped,
+    Ok,
+    Degraded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct HealthReport {
+    /// Worst status of the individual checks.
+    pub status: HealthStatus,
+    pub version: String,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// What the relay check needs to know about the relay setup.
+#[derive(Debug, Clone)]
+pub struct RelayProbe {
+    pub enabled: bool,
+    pub api_base: Option<String>,
+    pub tunnel_active: bool,
+}
+
+pub async fn run_health_checks(
+    db: &DBService,
+    asset_dir: PathBuf,
+    relay: RelayProbe,
+) -> HealthReport {
+    let (database, asset_dir_writable, disk_space, git, executors, relay) = tokio::join!(
+        timed("database", check_database(db)),
+        timed("asset_dir", check_asset_dir_writable(asset_dir.clone())),
+        timed("disk_space", check_disk_space(asset_dir)),
+        timed("git", check_git()),
+        timed("executors", check_executors()),
+        timed("relay", check_relay(relay)),
+    );
+    let checks = vec![
+        database,
+        asset_dir_writable,
+        disk_space,
+        git,
+        executors,
+        relay,
+    ];
+    let status = checks
+        .iter()
+        .map(|check| check.status)
+        .max()
+        .unwrap_or(HealthStatus::Ok)
+        .max(HealthStatus::Ok);
+
+    HealthReport {
+        status,
+        version: utils::version::APP_VERSION.to_string(),
+        checks,
+    }
+}
+
+/// The report for liveness probes: the process answers requests, nothing
+/// else is checked so a slow dependency never gets the server restarted.
+pub fn liveness_report() -> HealthReport {
+    HealthReport {
+        status: HealthStatus::Ok,
+        version: utils::version::APP_VERSION.to_string(),
+        checks: Vec::new(),
+    }
+}
+
+async fn timed(
+    name: &str,
+    check: impl Future<Output = (HealthStatus, Option<String>)>,
+) -> HealthCheck {
+    let started = Instant::now();
+    let (status, message) = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => (
+            HealthStatus::Failed,
+            Some(format!("timed out after {}s", CHECK_TIMEOUT.as_secs())),
+        ),
+    };
+    HealthCheck {
+        name: name.to_string(),
+        status,
+        latency_ms: started.elapsed().as_millis() as u64,
+        message,
+    }
+}
+
+/// Read a row, then take the write lock by creating a table inside a
+/// transaction that is rolled back.
+async fn check_database(db: &DBService) -> (HealthStatus, Option<String>) {
+    let probe = async {
+        sqlx::query("SELECT 1").execute(&db.pool).await?;
+        let mut tx = db.pool.begin().await?;
+        sqlx::query("CREATE TABLE _health_probe (id INTEGER)")
+            .execute(&mut *tx)
+            .await?;
+        tx.rollback().await
+    };
+    match probe.await {
+        Ok(()) => (HealthStatus::Ok, None),
+        Err(e) => (HealthStatus::Failed, Some(e.to_string())),
+    }
+}
+
+/// Config, credentials and logs are written to the asset dir.
+async fn check_asset_dir_writable(asset_dir: PathBuf) -> (HealthStatus, Option<String>) {
+    let probe = asset_dir.join(format!(".health_probe_{}", std::process::id()));
+    let result = async {
+        tokio::fs::write(&probe, b"ok").await?;
+        tokio::fs::remove_file(&probe).await
+    }
+    .await;
+    match result {
+        Ok(()) => (HealthStatus::Ok, None),
+        Err(e) => (
+            HealthStatus::Failed,
+            Some(format!("{} is not writable: {e}", asset_dir.display())),
+        ),
+    }
+}
+
+async fn check_disk_space(asset_dir: PathBuf) -> (HealthStatus, Option<String>) {
+    let available =
+        tokio::task::spawn_blocking(move || utils::path::available_space(&asset_dir)).await;
+    match available {
+        Ok(Ok(bytes)) => {
+            let status = if bytes < DISK_SPACE_FAIL_BYTES {
+                HealthStatus::Failed
+            } else if bytes < DISK_SPACE_WARN_BYTES {
+                HealthStatus::Degraded
+            } else {
+                HealthStatus::Ok
+            };
+            (
+                status,
+                Some(format!("{} MiB available", bytes / (1024 * 1024))),
+            )
+        }
+        Ok(Err(e)) => (HealthStatus::Failed, Some(e.to_string())),
+        Err(e) => (HealthStatus::Failed, Some(e.to_string())),
+    }
+}
+
+async fn check_git() -> (HealthStatus, Option<String>) {
+    match tokio::task::spawn_blocking(|| GitCli::new().version()).await {
+        Ok(Ok(version)) => (HealthStatus::Ok, Some(version)),
+        Ok(Err(e)) => (HealthStatus::Failed, Some(e.to_string())),
+        Err(e) => (HealthStatus::Failed, Some(e.to_string())),
+    }
+}
+
+/// At least one configured coding agent must be installed.
+async fn check_executors() -> (HealthStatus, Option<String>) {
+    let found = tokio::task::spawn_blocking(|| {
+        let profiles = ExecutorConfigs::get_cached();
+        let mut found: Vec<String> = profiles
+            .executors
+            .keys()
+            .filter(|agent| {
+                profiles
+                    .get_coding_agent(&ExecutorProfileId::new(**agent))
+                    .is_some_and(|agent| agent.get_availability_info().is_available())
+            })
+            .map(ToString::to_string)
+            .collect();
+        found.sort();
+        found
+    })
+    .await;
+
+    match found {
+        Ok(found) if found.is_empty() => (
+            HealthStatus::Degraded,
+            Some("no coding agent CLI found".to_string()),
+        ),
+        Ok(found) => (HealthStatus::Ok, Some(found.join(", "))),
+        Err(e) => (HealthStatus::Failed, Some(e.to_string())),
+    }
+}
+
+async fn check_relay(relay: RelayProbe) -> (HealthStatus, Option<String>) {
+    if !relay.enabled {
+        return (
+            HealthStatus::Skipped,
+            Some("relay disabled in config".to_string()),
+        );
+    }
+    let Some(api_base) = relay.api_base else {
+        return (
+            HealthStatus::Skipped,
+            Some("relay not configured".to_string()),
+        );
+    };
+
+    let url = format!("{}/health", api_base.trim_end_matches('/'));
+    let response = match reqwest::Client::new()
+        .get(&url)
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return (HealthStatus::Failed, Some(e.to_string())),
+    };
+    if !response.status().is_success() {
+        return (
+            HealthStatus::Failed,
+            Some(format!("relay returned {}", response.status())),
+        );
+    }
+    if relay.tunnel_active {
+        (HealthStatus::Ok, None)
+    } else {
+        (
+            HealthStatus::Degraded,
+            Some("relay reachable but not connected; sign in to connect".to_string()),
+        )
+    }
+}
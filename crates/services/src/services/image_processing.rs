@@ -0,0 +1,257 @@
+//! Normalisation of uploaded images before they are hashed and stored.
+//!
+//! Images are re-encoded, which drops EXIF and other metadata (after the
+//! EXIF orientation has been applied), and scaled down to what the target
+//! executor accepts. HEIC is converted to PNG when built with the `heic`
+//! feature. Anything that is not a recognised image is stored verbatim.
+
+use std::{io::Cursor, path::Path};
+
+use executors::executors::BaseCodingAgent;
+use image::{
+    DynamicImage, ImageDecoder, ImageFormat, ImageReader, codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+};
+
+const JPEG_QUALITY: u8 = 85;
+/// Images are not scaled below this to fit the byte limit.
+const MIN_DIMENSION: u32 = 256;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageProcessingError {
+    #[error("Failed to decode image: {0}")]
+    Decode(String),
+    #[error("Failed to encode image: {0}")]
+    Encode(#[from] image::ImageError),
+    #[error("Image is still {0} bytes after resizing (max: {1} bytes)")]
+    TooLarge(u64, u64),
+}
+
+/// What an executor accepts per image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    /// Longest edge in pixels.
+    pub max_dimension: u32,
+    pub max_bytes: u64,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_dimension: 2048,
+            max_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+impl ImageLimits {
+    /// Limits of the model APIs behind each executor. Images beyond them are
+    /// either rejected or silently downscaled by the provider, so sending
+    /// more only inflates the prompt.
+    pub fn for_executor(executor: Option<BaseCodingAgent>) -> Self {
+        match executor {
+            Some(BaseCodingAgent::ClaudeCode | BaseCodingAgent::Amp | BaseCodingAgent::Droid) => {
+                Self {
+                    max_dimension: 1568,
+                    max_bytes: 5 * 1024 * 1024,
+                }
+            }
+            Some(
+                BaseCodingAgent::Codex | BaseCodingAgent::Copilot | BaseCodingAgent::CursorAgent,
+            ) => Self {
+                max_dimension: 2048,
+                max_bytes: 20 * 1024 * 1024,
+            },
+            Some(BaseCodingAgent::Gemini | BaseCodingAgent::QwenCode) => Self {
+                max_dimension: 3072,
+                max_bytes: 20 * 1024 * 1024,
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProcessedUpload {
+    pub data: Vec<u8>,
+    /// The original name, with the extension changed if the format was.
+    pub filename: String,
+}
+
+/// Normalise an upload if it is an image in a format we re-encode. Returns
+/// `None` for anything else, which should be stored unchanged.
+pub fn process_upload(
+    data: &[u8],
+    filename: &str,
+    limits: ImageLimits,
+) -> Result<Option<ProcessedUpload>, ImageProcessingError> {
+    let (image, format) = if is_heic(data, filename) {
+        match decode_heic(data)? {
+            Some(image) => (image, ImageFormat::Png),
+            None => return Ok(None),
+        }
+    } else {
+        let reader = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|e| ImageProcessingError::Decode(e.to_string()))?;
+        let format = match reader.format() {
+            Some(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) => format,
+            // GIFs may be animated and other formats are rare; keep as is.
+            _ => return Ok(None),
+        };
+        let mut decoder = reader
+            .into_decoder()
+            .map_err(|e| ImageProcessingError::Decode(e.to_string()))?;
+        let orientation = decoder
+            .orientation()
+            .map_err(|e| ImageProcessingError::Decode(e.to_string()))?;
+        let mut image = DynamicImage::from_decoder(decoder)
+            .map_err(|e| ImageProcessingError::Decode(e.to_string()))?;
+        image.apply_orientation(orientation);
+        (image, format)
+    };
+
+    let mut image = fit_within(image, limits.max_dimension);
+    let mut encoded = encode(&image, format)?;
+    while encoded.len() as u64 > limits.max_bytes {
+        let (width, height) = (image.width(), image.height());
+        if width.max(height) <= MIN_DIMENSION {
+            return Err(ImageProcessingError::TooLarge(
+                encoded.len() as u64,
+                limits.max_bytes,
+            ));
+        }
+        image = image.resize(width * 3 / 4, height * 3 / 4, FilterType::Lanczos3);
+        encoded = encode(&image, format)?;
+    }
+
+    Ok(Some(ProcessedUpload {
+        data: encoded,
+        filename: with_extension(filename, format),
+    }))
+}
+
+fn fit_within(image: DynamicImage, max_dimension: u32) -> DynamicImage {
+    if image.width().max(image.height()) <= max_dimension {
+        image
+    } else {
+        image.resize(max_dimension, max_dimension, FilterType::Lanczos3)
+    }
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, ImageProcessingError> {
+    let mut buffer = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY);
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+        }
+        _ => image.write_to(&mut Cursor::new(&mut buffer), format)?,
+    }
+    Ok(buffer)
+}
+
+fn with_extension(filename: &str, format: ImageFormat) -> String {
+    let extension = match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::WebP => "webp",
+        _ => "png",
+    };
+    let keep_extension = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| format.extensions_str().contains(&e.to_lowercase().as_str()));
+    if keep_extension {
+        filename.to_string()
+    } else {
+        Path::new(filename)
+            .with_extension(extension)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+fn is_heic(data: &[u8], filename: &str) -> bool {
+    let by_name = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("heic") || e.eq_ignore_ascii_case("heif"));
+    // ISO BMFF `ftyp` box with a HEIF brand
+    let by_content = data.len() >= 12
+        && &data[4..8] == b"ftyp"
+        && matches!(
+            &data[8..12],
+            b"heic" | b"heix" | b"mif1" | b"msf1" | b"heim" | b"heis"
+        );
+    by_name || by_content
+}
+
+#[cfg(feature = "heic")]
+fn decode_heic(data: &[u8]) -> Result<Option<DynamicImage>, ImageProcessingError> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let decode_error = |e: libheif_rs::HeifError| ImageProcessingError::Decode(e.to_string());
+    let lib = LibHeif::new();
+    let context = HeifContext::read_from_bytes(data).map_err(decode_error)?;
+    let handle = context.primary_image_handle().map_err(decode_error)?;
+    let decoded = lib
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(decode_error)?;
+    let Some(plane) = decoded.planes().interleaved else {
+        return Err(ImageProcessingError::Decode(
+            "HEIC image has no interleaved plane".to_string(),
+        ));
+    };
+
+    let row_len = plane.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+    let image = image::RgbaImage::from_raw(plane.width, plane.height, pixels).ok_or_else(|| {
+        ImageProcessingError::Decode("HEIC image has an unexpected size".to_string())
+    })?;
+    Ok(Some(DynamicImage::ImageRgba8(image)))
+}
+
+#[cfg(not(feature = "heic"))]
+fn decode_heic(_data: &[u8]) -> Result<Option<DynamicImage>, ImageProcessingError> {
+    tracing::warn!("HEIC support is not compiled in; storing the upload unchanged");
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(RgbImage::new(width, height));
+        encode(&image, ImageFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn large_images_are_scaled_to_the_executor_limit() {
+        let limits = ImageLimits::for_executor(Some(BaseCodingAgent::ClaudeCode));
+        let processed = process_upload(&png(4000, 1000), "shot.png", limits)
+            .unwrap()
+            .unwrap();
+        let image = image::load_from_memory(&processed.data).unwrap();
+        assert_eq!((image.width(), image.height()), (1568, 392));
+        assert_eq!(processed.filename, "shot.png");
+    }
+
+    #[test]
+    fn non_images_are_left_alone() {
+        let result = process_upload(b"plain text", "notes.txt", ImageLimits::default()).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn heic_is_recognised_by_brand() {
+        let header = [0, 0, 0, 24, b'f', b't', b'y', b'p', b'h', b'e', b'i', b'c'];
+        assert!(is_heic(&header, "upload.bin"));
+        assert!(!is_heic(&png(1, 1), "image.png"));
+    }
+}
@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::{IssueTrackerError, IssueTrackerProvider, LIST_LIMIT, TrackerIssue, send_json, text};
+
+const API_URL: &str = "https://api.github.com";
+
+/// GitHub Issues of the `owner/name` repository. The token is optional for
+/// listing public repositories.
+pub struct GithubIssues {
+    http: reqwest::Client,
+    api_url: String,
+    repository: String,
+    token: Option<String>,
+}
+
+impl GithubIssues {
+    pub fn new(http: reqwest::Client, repository: String, token: Option<String>) -> Self {
+        Self {
+            http,
+            api_url: API_URL.to_string(),
+            repository,
+            token,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_api_url(self, api_url: String) -> Self {
+        Self { api_url, ..self }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{path}", self.api_url))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl IssueTrackerProvider for GithubIssues {
+    async fn list_open_issues(
+        &self,
+        search: Option<&str>,
+    ) -> Result<Vec<TrackerIssue>, IssueTrackerError> {
+        let issues = match search.map(str::trim).filter(|search| !search.is_empty()) {
+            Some(search) => {
+                let query = format!("repo:{} is:issue is:open {search}", self.repository);
+                let mut results = send_json(
+                    self.request(reqwest::Method::GET, "/search/issues")
+                        .query(&[("q", query.as_str()), ("sort", "updated")])
+                        .query(&[("per_page", LIST_LIMIT)]),
+                )
+                .await?;
+                results["items"].take()
+            }
+            None => {
+                send_json(
+                    self.request(
+                        reqwest::Method::GET,
+                        &format!("/repos/{}/issues", self.repository),
+                    )
+                    .query(&[("state", "open"), ("sort", "updated")])
+                    .query(&[("per_page", LIST_LIMIT)]),
+                )
+                .await?
+            }
+        };
+
+        Ok(issues
+            .as_array()
+            .into_iter()
+            .flatten()
+            // The issues endpoint lists pull requests too
+            .filter(|issue| issue.get("pull_request").is_none())
+            .filter_map(parse_issue)
+            .collect())
+    }
+
+    async fn get_issue(&self, key: &str) -> Result<TrackerIssue, IssueTrackerError> {
+        let number = issue_number(key)?;
+        let issue = send_json(self.request(
+            reqwest::Method::GET,
+            &format!("/repos/{}/issues/{number}", self.repository),
+        ))
+        .await?;
+        if issue.get("pull_request").is_some() {
+            return Err(IssueTrackerError::IssueNotFound(key.to_string()));
+        }
+        parse_issue(&issue).ok_or_else(|| {
+            IssueTrackerError::UnexpectedResponse(format!("issue {key} has no number or title"))
+        })
+    }
+
+    async fn close_issue(&self, key: &str) -> Result<(), IssueTrackerError> {
+        let number = issue_number(key)?;
+        send_json(
+            self.request(
+                reqwest::Method::PATCH,
+                &format!("/repos/{}/issues/{number}", self.repository),
+            )
+            .json(&json!({ "state": "closed", "state_reason": "completed" })),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// The issue number of a key such as `42` or `#42`. Keys end up in request
+/// paths, so anything else is rejected.
+fn issue_number(key: &str) -> Result<u64, IssueTrackerError> {
+    key.strip_prefix('#')
+        .unwrap_or(key)
+        .parse()
+        .map_err(|_| IssueTrackerError::InvalidKey(key.to_string()))
+}
+
+fn parse_issue(issue: &Value) -> Option<TrackerIssue> {
+    Some(TrackerIssue {
+        key: issue["number"].as_i64()?.to_string(),
+        title: text(&issue["title"])?,
+        body: text(&issue["body"]),
+        url: text(&issue["html_url"])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::mock_tracker, *};
+
+    fn issues(api_url: String) -> GithubIssues {
+        GithubIssues::new(reqwest::Client::new(), "acme/app".to_string(), None)
+            .with_api_url(api_url)
+    }
+
+    #[tokio::test]
+    async fn gets_an_issue_by_number() {
+        let (url, requests) = mock_tracker(vec![(
+            200,
+            r#"{"number": 42, "title": "Crash", "body": null, "html_url": "https://github.com/acme/app/issues/42"}"#,
+        )])
+        .await;
+        let issue = issues(url).get_issue("#42").await.unwrap();
+        assert_eq!(issue.key, "42");
+        assert_eq!(issue.title, "Crash");
+        assert_eq!(*requests.lock().unwrap(), ["GET /repos/acme/app/issues/42"]);
+    }
+
+    #[tokio::test]
+    async fn pull_requests_are_not_issues() {
+        let (url, _requests) = mock_tracker(vec![(
+            200,
+            r#"{"number": 7, "title": "Fix", "html_url": "https://github.com/acme/app/pull/7", "pull_request": {}}"#,
+        )])
+        .await;
+        let err = issues(url).get_issue("7").await.unwrap_err();
+        assert!(matches!(err, IssueTrackerError::IssueNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn closes_an_issue_as_completed() {
+        let (url, requests) = mock_tracker(vec![(200, r#"{"number": 42}"#)]).await;
+        issues(url).close_issue("42").await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [r#"PATCH /repos/acme/app/issues/42 {"state":"closed","state_reason":"completed"}"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_that_are_not_issue_numbers() {
+        let (url, requests) = mock_tracker(vec![]).await;
+        let issues = issues(url);
+        for key in ["42/../../../user", "##42", "-1", ""] {
+            let err = issues.close_issue(key).await.unwrap_err();
+            assert!(matches!(err, IssueTrackerError::InvalidKey(_)), "{key}");
+        }
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn error_statuses_carry_the_response_body() {
+        let (url, _requests) = mock_tracker(vec![(404, r#"{"message": "Not Found"}"#)]).await;
+        let err = issues(url).get_issue("1").await.unwrap_err();
+        assert!(matches!(
+            err,
+            IssueTrackerError::Api { status: 404, ref message } if message.contains("Not Found")
+        ));
+    }
+}
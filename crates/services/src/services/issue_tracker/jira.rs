@@ -0,0 +1,223 @@
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{Value, json};
+
+use super::{IssueTrackerError, IssueTrackerProvider, LIST_LIMIT, TrackerIssue, send_json, text};
+
+static ISSUE_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z][A-Z0-9]+-\d+$").unwrap());
+
+/// Jira issues of a project, read through the v2 REST API so descriptions
+/// come back as plain text.
+pub struct JiraIssues {
+    http: reqwest::Client,
+    project_key: String,
+    base_url: String,
+    username: Option<String>,
+    token: Option<String>,
+}
+
+impl JiraIssues {
+    pub fn new(
+        http: reqwest::Client,
+        project_key: String,
+        base_url: String,
+        username: Option<String>,
+        token: Option<String>,
+    ) -> Self {
+        Self {
+            http,
+            project_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username,
+            token,
+        }
+    }
+
+    /// Jira Cloud takes the account email and an API token; Data Center
+    /// takes a personal access token as a bearer token.
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}/rest/api/2{path}", self.base_url));
+        match (&self.username, &self.token) {
+            (Some(username), token) => request.basic_auth(username, token.as_ref()),
+            (None, Some(token)) => request.bearer_auth(token),
+            (None, None) => request,
+        }
+    }
+
+    /// Jira Cloud has replaced `/search` with `/search/jql`.
+    fn search_path(&self) -> &'static str {
+        if self.base_url.ends_with(".atlassian.net") {
+            "/search/jql"
+        } else {
+            "/search"
+        }
+    }
+
+    fn parse_issue(&self, issue: &Value) -> Option<TrackerIssue> {
+        let key = text(&issue["key"])?;
+        Some(TrackerIssue {
+            title: text(&issue["fields"]["summary"])?,
+            body: text(&issue["fields"]["description"]),
+            url: format!("{}/browse/{key}", self.base_url),
+            key,
+        })
+    }
+}
+
+#[async_trait]
+impl IssueTrackerProvider for JiraIssues {
+    async fn list_open_issues(
+        &self,
+        search: Option<&str>,
+    ) -> Result<Vec<TrackerIssue>, IssueTrackerError> {
+        let mut jql = format!(
+            "project = \"{}\" AND statusCategory != Done",
+            jql_escape(&self.project_key)
+        );
+        if let Some(search) = search.map(str::trim).filter(|search| !search.is_empty()) {
+            jql.push_str(&format!(" AND text ~ \"{}\"", jql_escape(search)));
+        }
+        jql.push_str(" ORDER BY updated DESC");
+
+        let response = send_json(
+            self.request(reqwest::Method::GET, self.search_path())
+                .query(&[("jql", jql.as_str()), ("fields", "summary,description")])
+                .query(&[("maxResults", LIST_LIMIT)]),
+        )
+        .await?;
+        Ok(response["issues"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|issue| self.parse_issue(issue))
+            .collect())
+    }
+
+    async fn get_issue(&self, key: &str) -> Result<TrackerIssue, IssueTrackerError> {
+        let key = issue_key(key)?;
+        let issue = send_json(
+            self.request(reqwest::Method::GET, &format!("/issue/{key}"))
+                .query(&[("fields", "summary,description")]),
+        )
+        .await?;
+        self.parse_issue(&issue)
+            .ok_or_else(|| IssueTrackerError::IssueNotFound(key.to_string()))
+    }
+
+    async fn close_issue(&self, key: &str) -> Result<(), IssueTrackerError> {
+        let key = issue_key(key)?;
+        let path = format!("/issue/{key}/transitions");
+        let transitions = send_json(self.request(reqwest::Method::GET, &path)).await?;
+        let transition_id = transitions["transitions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|transition| transition["to"]["statusCategory"]["key"].as_str() == Some("done"))
+            .and_then(|transition| text(&transition["id"]))
+            .ok_or_else(|| IssueTrackerError::NoCloseTransition(key.to_string()))?;
+
+        send_json(
+            self.request(reqwest::Method::POST, &path)
+                .json(&json!({ "transition": { "id": transition_id } })),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Keys such as `ENG-42` end up in request paths, so anything else is
+/// rejected.
+fn issue_key(key: &str) -> Result<&str, IssueTrackerError> {
+    if ISSUE_KEY.is_match(key) {
+        Ok(key)
+    } else {
+        Err(IssueTrackerError::InvalidKey(key.to_string()))
+    }
+}
+
+fn jql_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::mock_tracker, *};
+
+    fn issues(base_url: String) -> JiraIssues {
+        JiraIssues::new(
+            reqwest::Client::new(),
+            "ENG".to_string(),
+            format!("{base_url}/"),
+            Some("dev@example.com".to_string()),
+            Some("token".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn gets_an_issue_by_key() {
+        let (url, requests) = mock_tracker(vec![(
+            200,
+            r#"{"key": "ENG-42", "fields": {"summary": "Crash", "description": "Steps"}}"#,
+        )])
+        .await;
+        let issue = issues(url.clone()).get_issue("ENG-42").await.unwrap();
+        assert_eq!(issue.title, "Crash");
+        assert_eq!(issue.body.as_deref(), Some("Steps"));
+        assert_eq!(issue.url, format!("{url}/browse/ENG-42"));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["GET /rest/api/2/issue/ENG-42?fields=summary%2Cdescription"]
+        );
+    }
+
+    #[tokio::test]
+    async fn closes_an_issue_through_its_done_transition() {
+        let (url, requests) = mock_tracker(vec![
+            (
+                200,
+                r#"{"transitions": [
+                    {"id": "11", "to": {"statusCategory": {"key": "indeterminate"}}},
+                    {"id": "31", "to": {"statusCategory": {"key": "done"}}}
+                ]}"#,
+            ),
+            (204, ""),
+        ])
+        .await;
+        issues(url).close_issue("ENG-42").await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /rest/api/2/issue/ENG-42/transitions",
+                r#"POST /rest/api/2/issue/ENG-42/transitions {"transition":{"id":"31"}}"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_without_a_done_transition() {
+        let (url, _requests) = mock_tracker(vec![(200, r#"{"transitions": []}"#)]).await;
+        let err = issues(url).close_issue("ENG-42").await.unwrap_err();
+        assert!(matches!(err, IssueTrackerError::NoCloseTransition(_)));
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_that_are_not_issue_keys() {
+        let (url, requests) = mock_tracker(vec![]).await;
+        let issues = issues(url);
+        for key in [
+            "ENG-42/../../../myself",
+            "eng-42",
+            "ENG42",
+            "E-1",
+            "ENG-42?x=1",
+        ] {
+            let err = issues.get_issue(key).await.unwrap_err();
+            assert!(matches!(err, IssueTrackerError::InvalidKey(_)), "{key}");
+        }
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::{IssueTrackerError, IssueTrackerProvider, LIST_LIMIT, TrackerIssue, send_json, text};
+
+const API_URL: &str = "https://api.linear.app/graphql";
+
+const ISSUE_FIELDS: &str = "identifier title description url";
+
+/// Linear issues of the team with the given key, e.g. `ENG`. The token is a
+/// personal API key.
+pub struct LinearIssues {
+    http: reqwest::Client,
+    api_url: String,
+    team_key: String,
+    token: Option<String>,
+}
+
+impl LinearIssues {
+    pub fn new(http: reqwest::Client, team_key: String, token: Option<String>) -> Self {
+        Self {
+            http,
+            api_url: API_URL.to_string(),
+            team_key,
+            token,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_api_url(self, api_url: String) -> Self {
+        Self { api_url, ..self }
+    }
+
+    async fn graphql(&self, query: &str, variables: Value) -> Result<Value, IssueTrackerError> {
+        let mut request = self
+            .http
+            .post(&self.api_url)
+            .json(&json!({ "query": query, "variables": variables }));
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", token);
+        }
+        let mut response = send_json(request).await?;
+        if let Some(message) = response["errors"][0]["message"].as_str() {
+            return Err(IssueTrackerError::Api {
+                status: 200,
+                message: message.to_string(),
+            });
+        }
+        Ok(response["data"].take())
+    }
+}
+
+#[async_trait]
+impl IssueTrackerProvider for LinearIssues {
+    async fn list_open_issues(
+        &self,
+        search: Option<&str>,
+    ) -> Result<Vec<TrackerIssue>, IssueTrackerError> {
+        let mut filter = json!({
+            "team": { "key": { "eq": self.team_key } },
+            "state": { "type": { "nin": ["completed", "canceled"] } },
+        });
+        if let Some(search) = search.map(str::trim).filter(|search| !search.is_empty()) {
+            filter["title"] = json!({ "containsIgnoreCase": search });
+        }
+        let data = self
+            .graphql(
+                &format!(
+                    "query($filter: IssueFilter, $first: Int) {{
+                        issues(filter: $filter, first: $first, orderBy: updatedAt) {{
+                            nodes {{ {ISSUE_FIELDS} }}
+                        }}
+                    }}"
+                ),
+                json!({ "filter": filter, "first": LIST_LIMIT }),
+            )
+            .await?;
+        Ok(data["issues"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(parse_issue)
+            .collect())
+    }
+
+    async fn get_issue(&self, key: &str) -> Result<TrackerIssue, IssueTrackerError> {
+        let data = self
+            .graphql(
+                &format!("query($id: String!) {{ issue(id: $id) {{ {ISSUE_FIELDS} }} }}"),
+                json!({ "id": key }),
+            )
+            .await?;
+        parse_issue(&data["issue"]).ok_or_else(|| IssueTrackerError::IssueNotFound(key.to_string()))
+    }
+
+    async fn close_issue(&self, key: &str) -> Result<(), IssueTrackerError> {
+        let data = self
+            .graphql(
+                "query($id: String!) {
+                    issue(id: $id) {
+                        id
+                        team {
+                            states(filter: { type: { eq: \"completed\" } }) {
+                                nodes { id position }
+                            }
+                        }
+                    }
+                }",
+                json!({ "id": key }),
+            )
+            .await?;
+        let issue = &data["issue"];
+        let issue_id =
+            text(&issue["id"]).ok_or_else(|| IssueTrackerError::IssueNotFound(key.to_string()))?;
+        // The first completed state of the team's workflow, usually "Done"
+        let state_id = issue["team"]["states"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| {
+                let position = |state: &Value| state["position"].as_f64().unwrap_or_default();
+                position(a).total_cmp(&position(b))
+            })
+            .and_then(|state| text(&state["id"]))
+            .ok_or_else(|| IssueTrackerError::NoCloseTransition(key.to_string()))?;
+
+        let data = self
+            .graphql(
+                "mutation($id: String!, $stateId: String!) {
+                    issueUpdate(id: $id, input: { stateId: $stateId }) { success }
+                }",
+                json!({ "id": issue_id, "stateId": state_id }),
+            )
+            .await?;
+        if data["issueUpdate"]["success"].as_bool() != Some(true) {
+            return Err(IssueTrackerError::UnexpectedResponse(format!(
+                "issue {key} was not updated"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn parse_issue(issue: &Value) -> Option<TrackerIssue> {
+    Some(TrackerIssue {
+        key: text(&issue["identifier"])?,
+        title: text(&issue["title"])?,
+        body: text(&issue["description"]),
+        url: text(&issue["url"])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::mock_tracker, *};
+
+    fn issues(api_url: String) -> LinearIssues {
+        LinearIssues::new(reqwest::Client::new(), "ENG".to_string(), None).with_api_url(api_url)
+    }
+
+    #[tokio::test]
+    async fn closes_an_issue_in_the_first_completed_state() {
+        let (url, requests) = mock_tracker(vec![
+            (
+                200,
+                r#"{"data": {"issue": {"id": "uuid-42", "team": {"states": {"nodes": [
+                    {"id": "shipped", "position": 5.0},
+                    {"id": "done", "position": 2.0}
+                ]}}}}}"#,
+            ),
+            (200, r#"{"data": {"issueUpdate": {"success": true}}}"#),
+        ])
+        .await;
+        issues(url).close_issue("ENG-42").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains(r#""variables":{"id":"ENG-42"}"#));
+        assert!(requests[1].contains(r#""variables":{"id":"uuid-42","stateId":"done"}"#));
+    }
+
+    #[tokio::test]
+    async fn graphql_errors_are_api_errors() {
+        let (url, _requests) = mock_tracker(vec![(
+            200,
+            r#"{"errors": [{"message": "Entity not found: Issue"}], "data": null}"#,
+        )])
+        .await;
+        let err = issues(url).get_issue("ENG-404").await.unwrap_err();
+        assert!(matches!(
+            err,
+            IssueTrackerError::Api { ref message, .. } if message == "Entity not found: Issue"
+        ));
+    }
+
+    #[tokio::test]
+    async fn missing_issues_are_not_found() {
+        let (url, _requests) = mock_tracker(vec![(200, r#"{"data": {"issue": null}}"#)]).await;
+        let err = issues(url).get_issue("ENG-404").await.unwrap_err();
+        assert!(matches!(err, IssueTrackerError::IssueNotFound(_)));
+    }
+}
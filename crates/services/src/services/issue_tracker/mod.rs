@@ -0,0 +1,278 @@
+//! Imports issues from a repo's issue tracker (GitHub Issues, Linear or Jira)
+//! as workspaces, and closes them again once the workspace's PRs are merged.
+
+mod github;
+mod jira;
+mod linear;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use db::models::{
+    repo_issue_tracker::{IssueTrackerKind, RepoIssueTracker},
+    workspace_issue_link::WorkspaceIssueLink,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use self::{github::GithubIssues, jira::JiraIssues, linear::LinearIssues};
+use super::config::secrets::{self, SecretsError};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Most issues listed for picking which to import.
+const LIST_LIMIT: usize = 50;
+
+#[derive(Debug, Error)]
+pub enum IssueTrackerError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+    #[error("Issue tracker returned {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("Unexpected response from the issue tracker: {0}")]
+    UnexpectedResponse(String),
+    #[error("Jira trackers need the site URL")]
+    MissingBaseUrl,
+    #[error("Issue {0} not found")]
+    IssueNotFound(String),
+    #[error("{0} is not a valid issue key")]
+    InvalidKey(String),
+    #[error("Issue {0} has no transition to a done status")]
+    NoCloseTransition(String),
+}
+
+/// An issue as listed by the tracker.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TrackerIssue {
+    /// Number or key shown in the tracker, e.g. `42` or `ENG-42`.
+    pub key: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub url: String,
+}
+
+impl TrackerIssue {
+    /// The prompt a workspace imported from the issue starts with.
+    pub fn prompt(&self) -> String {
+        let mut prompt = format!("{}\n\n", self.title);
+        if let Some(body) = self.body.as_deref().map(str::trim)
+            && !body.is_empty()
+        {
+            prompt.push_str(body);
+            prompt.push_str("\n\n");
+        }
+        prompt.push_str(&format!("Resolves issue {}: {}", self.key, self.url));
+        prompt
+    }
+}
+
+#[async_trait]
+pub trait IssueTrackerProvider: Send + Sync {
+    /// Open issues, most recently updated first, optionally narrowed to those
+    /// matching `search`.
+    async fn list_open_issues(
+        &self,
+        search: Option<&str>,
+    ) -> Result<Vec<TrackerIssue>, IssueTrackerError>;
+
+    async fn get_issue(&self, key: &str) -> Result<TrackerIssue, IssueTrackerError>;
+
+    /// Move the issue to a closed or done state.
+    async fn close_issue(&self, key: &str) -> Result<(), IssueTrackerError>;
+}
+
+/// The adapter for a repo's tracker, with its token decrypted.
+pub fn provider(
+    tracker: &RepoIssueTracker,
+) -> Result<Box<dyn IssueTrackerProvider>, IssueTrackerError> {
+    let token = match tracker.token.as_deref() {
+        Some(token) if secrets::is_encrypted(token) => Some(
+            secrets::cipher()
+                .ok_or(SecretsError::Decrypt)?
+                .decrypt(token)?,
+        ),
+        token => token.map(str::to_string),
+    };
+    let http = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent("vibe-kanban")
+        .build()
+        .unwrap_or_default();
+    let project = tracker.project.clone();
+
+    let provider: Box<dyn IssueTrackerProvider> = match tracker.provider {
+        IssueTrackerKind::Github => Box::new(GithubIssues::new(http, project, token)),
+        IssueTrackerKind::Linear => Box::new(LinearIssues::new(http, project, token)),
+        IssueTrackerKind::Jira => Box::new(JiraIssues::new(
+            http,
+            project,
+            tracker
+                .base_url
+                .clone()
+                .ok_or(IssueTrackerError::MissingBaseUrl)?,
+            tracker.username.clone(),
+            token,
+        )),
+    };
+    Ok(provider)
+}
+
+/// Encrypt a tracker token for storage when the config secrets key is
+/// available.
+pub fn seal_token(token: &str) -> Result<String, SecretsError> {
+    match secrets::cipher() {
+        Some(cipher) => cipher.encrypt(token),
+        None => Ok(token.to_string()),
+    }
+}
+
+/// Close the issues imported as the workspace, for repos whose tracker is
+/// set to close issues on merge. Failures are logged and left for the next
+/// merged PR to retry.
+pub async fn close_linked_issues(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+    let links = WorkspaceIssueLink::find_by_workspace_id(pool, workspace_id).await?;
+    for link in links.iter().filter(|link| link.closed_at.is_none()) {
+        let Some(tracker) = RepoIssueTracker::find_by_repo_id(pool, link.repo_id).await? else {
+            continue;
+        };
+        if !tracker.close_on_merge || tracker.provider != link.provider {
+            continue;
+        }
+        let closed = match provider(&tracker) {
+            Ok(provider) => provider.close_issue(&link.issue_key).await,
+            Err(e) => Err(e),
+        };
+        match closed {
+            Ok(()) => {
+                tracing::info!(
+                    "Closed issue {} of merged workspace {}",
+                    link.issue_key,
+                    workspace_id
+                );
+                WorkspaceIssueLink::mark_closed(pool, link.id).await?;
+            }
+            Err(e) => tracing::warn!("Failed to close issue {}: {}", link.issue_key, e),
+        }
+    }
+    Ok(())
+}
+
+/// Send a request and read its JSON response, turning error statuses into
+/// [`IssueTrackerError::Api`].
+async fn send_json(request: reqwest::RequestBuilder) -> Result<Value, IssueTrackerError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(IssueTrackerError::Api {
+            status: status.as_u16(),
+            message: message.chars().take(500).collect(),
+        });
+    }
+    if status == reqwest::StatusCode::NO_CONTENT {
+        return Ok(Value::Null);
+    }
+    Ok(response.json().await?)
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+/// A stand-in tracker API for adapter tests: answers each request with the
+/// next canned `(status, body)` and records `"METHOD /path body"` lines.
+#[cfg(test)]
+async fn mock_tracker(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        for (status, body) in responses {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            let (head_len, content_length) = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    return;
+                }
+                request.extend_from_slice(&buf[..n]);
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    break (end + 4, length);
+                }
+            };
+            while request.len() < head_len + content_length {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    return;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let request = String::from_utf8_lossy(&request).to_string();
+            let request_line = request.lines().next().unwrap_or_default();
+            let target = request_line
+                .rsplit_once(' ')
+                .map_or(request_line, |(t, _)| t);
+            recorded.lock().unwrap().push(
+                format!("{target} {}", &request[head_len..])
+                    .trim()
+                    .to_string(),
+            );
+            let response = format!(
+                "HTTP/1.1 {status} Mock\r\ncontent-type: application/json\r\n\
+                 content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_scaffolds_title_body_and_link() {
+        let issue = TrackerIssue {
+            key: "ENG-42".to_string(),
+            title: "Login fails with SSO".to_string(),
+            body: Some("Steps:\n1. Sign in with Okta\n".to_string()),
+            url: "https://linear.app/acme/issue/ENG-42".to_string(),
+        };
+        assert_eq!(
+            issue.prompt(),
+            "Login fails with SSO\n\nSteps:\n1. Sign in with Okta\n\n\
+             Resolves issue ENG-42: https://linear.app/acme/issue/ENG-42"
+        );
+
+        let issue = TrackerIssue {
+            body: Some("  ".to_string()),
+            ..issue
+        };
+        assert_eq!(
+            issue.prompt(),
+            "Login fails with SSO\n\nResolves issue ENG-42: https://linear.app/acme/issue/ENG-42"
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Age- and size-based retention for execution process logs.
+//!
+//! Logs are JSONL files under `sessions/<prefix>/<session_id>/processes/`.
+//! Old logs are compressed to `.jsonl.zst` (readers fall back to the
+//! compressed file transparently), logs past the age or size budget are
+//! deleted, and session directories whose session no longer exists are
+//! pruned.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use db::{
+    DBService,
+    models::{
+        execution_log_search::ExecutionLogIndex, execution_process::ExecutionProcess,
+        session::Session,
+    },
+};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info, warn};
+use ts_rs::TS;
+use utils::execution_logs::{
+    COMPRESSED_LOG_EXTENSION, compress_execution_log_file, execution_logs_root,
+};
+use uuid::Uuid;
+
+use super::config::{Config, LogRetentionConfig};
+
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Orphaned session directories younger than this are left alone, in case
+/// the session row is still being created.
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Error)]
+pub enum LogRetentionError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LogCompactionReport {
+    pub compressed_files: usize,
+    pub deleted_files: usize,
+    pub pruned_sessions: usize,
+    /// Bytes freed by compression and deletion combined.
+    pub bytes_reclaimed: u64,
+}
+
+struct LogFile {
+    path: PathBuf,
+    execution_id: Uuid,
+    compressed: bool,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl LogFile {
+    fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.modified).unwrap_or_default()
+    }
+}
+
+#[derive(Clone)]
+pub struct LogRetentionService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+}
+
+impl LogRetentionService {
+    pub fn new(db: DBService, config: Arc<RwLock<Config>>) -> Self {
+        Self { db, config }
+    }
+
+    /// Spawn a background loop that applies the configured retention policy
+    /// every hour.
+    pub fn spawn_compaction_loop(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self::new(db, config);
+        tokio::spawn(async move {
+            info!("Starting execution log retention loop");
+            let mut interval = interval(COMPACTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                match service.compact().await {
+                    Ok(report) if report.bytes_reclaimed > 0 => info!(
+                        "Log retention compressed {} and deleted {} log files, pruned {} sessions, reclaimed {} bytes",
+                        report.compressed_files,
+                        report.deleted_files,
+                        report.pruned_sessions,
+                        report.bytes_reclaimed
+                    ),
+                    Ok(_) => {}
+                    Err(e) => error!("Error applying execution log retention: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Apply the current retention policy once.
+    pub async fn compact(&self) -> Result<LogCompactionReport, LogRetentionError> {
+        self.compact_in(&execution_logs_root()).await
+    }
+
+    async fn compact_in(&self, root: &Path) -> Result<LogCompactionReport, LogRetentionError> {
+        let policy = self.config.read().await.log_retention.clone();
+        let mut report = LogCompactionReport::default();
+        if !tokio::fs::try_exists(root).await? {
+            return Ok(report);
+        }
+
+        let live_sessions: HashSet<Uuid> = Session::find_all_ids(&self.db.pool)
+            .await?
+            .into_iter()
+            .collect();
+        let running: HashSet<Uuid> = ExecutionProcess::find_running(&self.db.pool)
+            .await?
+            .into_iter()
+            .map(|process| process.id)
+            .collect();
+
+        let mut files = Vec::new();
+        for (session_id, session_dir) in session_dirs(root).await? {
+            if !live_sessions.contains(&session_id) {
+                let modified = tokio::fs::metadata(&session_dir).await?.modified()?;
+                if SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or_default()
+                    < ORPHAN_GRACE_PERIOD
+                {
+                    continue;
+                }
+                report.bytes_reclaimed += dir_size(&session_dir).await?;
+                tokio::fs::remove_dir_all(&session_dir).await?;
+                report.pruned_sessions += 1;
+                continue;
+            }
+            files.extend(
+                log_files(&session_dir.join("processes"))
+                    .await?
+                    .into_iter()
+                    .filter(|file| !running.contains(&file.execution_id)),
+            );
+        }
+
+        self.apply_policy(&policy, files, &mut report).await?;
+        Ok(report)
+    }
+
+    async fn apply_policy(
+        &self,
+        policy: &LogRetentionConfig,
+        mut files: Vec<LogFile>,
+        report: &mut LogCompactionReport,
+    ) -> Result<(), LogRetentionError> {
+        let now = SystemTime::now();
+        let days = |d: u32| Duration::from_secs(u64::from(d) * SECONDS_PER_DAY);
+
+        // Oldest first, so the size budget deletes the oldest logs.
+        files.sort_by_key(|file| file.modified);
+
+        let mut kept = Vec::with_capacity(files.len());
+        for file in files {
+            if policy
+                .delete_after_days
+                .is_some_and(|d| file.age(now) > days(d))
+            {
+                self.delete(&file, report).await?;
+            } else {
+                kept.push(file);
+            }
+        }
+
+        if let Some(compress_after) = policy.compress_after_days.map(days) {
+            for file in kept
+                .iter_mut()
+                .filter(|file| !file.compressed && file.age(now) > compress_after)
+            {
+                match compress_execution_log_file(&file.path).await {
+                    Ok(size) => {
+                        report.bytes_reclaimed += file.size.saturating_sub(size);
+                        report.compressed_files += 1;
+                        file.size = size;
+                        file.compressed = true;
+                    }
+                    Err(e) => warn!("Failed to compress {}: {}", file.path.display(), e),
+                }
+            }
+        }
+
+        if let Some(max_mb) = policy.max_total_size_mb {
+            let budget = max_mb * 1024 * 1024;
+            let mut total: u64 = kept.iter().map(|file| file.size).sum();
+            for file in &kept {
+                if total <= budget {
+                    break;
+                }
+                total -= file.size;
+                self.delete(file, report).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        file: &LogFile,
+        report: &mut LogCompactionReport,
+    ) -> Result<(), LogRetentionError> {
+        let path = if file.compressed {
+            file.path.with_extension(COMPRESSED_LOG_EXTENSION)
+        } else {
+            file.path.clone()
+        };
+        tokio::fs::remove_file(&path).await?;
+        // Search results would point at lines that no longer exist.
+        ExecutionLogIndex::replace_for_execution(&self.db.pool, file.execution_id, &[]).await?;
+        report.deleted_files += 1;
+        report.bytes_reclaimed += file.size;
+        Ok(())
+    }
+}
+
+/// `sessions/<prefix>/<session_id>` directories, skipping anything that
+/// isn't named by a session id.
+async fn session_dirs(root: &Path) -> std::io::Result<Vec<(Uuid, PathBuf)>> {
+    let mut dirs = Vec::new();
+    let mut prefixes = tokio::fs::read_dir(root).await?;
+    while let Some(prefix) = prefixes.next_entry().await? {
+        if !prefix.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut sessions = tokio::fs::read_dir(prefix.path()).await?;
+        while let Some(session) = sessions.next_entry().await? {
+            if let Some(id) = session
+                .file_name()
+                .to_str()
+                .and_then(|name| Uuid::parse_str(name).ok())
+                && session.file_type().await?.is_dir()
+            {
+                dirs.push((id, session.path()));
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// Process logs in a session's `processes` directory. `path` is always the
+/// plain `.jsonl` path, even for compressed logs.
+async fn log_files(dir: &Path) -> std::io::Result<Vec<LogFile>> {
+    let mut files = Vec::new();
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(e) => return Err(e),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let (stem, compressed) = if let Some(stem) = name.strip_suffix(".jsonl.zst") {
+            (stem, true)
+        } else if let Some(stem) = name.strip_suffix(".jsonl") {
+            (stem, false)
+        } else {
+            continue;
+        };
+        let Ok(execution_id) = Uuid::parse_str(stem) else {
+            continue;
+        };
+        let metadata = entry.metadata().await?;
+        files.push(LogFile {
+            path: dir.join(format!("{stem}.jsonl")),
+            execution_id,
+            compressed,
+            size: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+    Ok(files)
+}
+
+async fn dir_size(dir: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::{
+            session::CreateSession,
+            workspace::{CreateWorkspace, Workspace},
+        },
+        test_utils::TestDb,
+    };
+    use utils::execution_logs::{
+        EXECUTION_LOGS_DIRNAME, compressed_log_file_path, process_log_file_path_in_root,
+        read_execution_log_file,
+    };
+
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    struct Fixture {
+        db: TestDb,
+        service: LogRetentionService,
+        session_id: Uuid,
+    }
+
+    impl Fixture {
+        fn logs_root(&self) -> PathBuf {
+            self.db.dir.path().join(EXECUTION_LOGS_DIRNAME)
+        }
+
+        /// Write a process log of the live session, last modified `age` ago.
+        fn write_log(&self, contents: &str, age: Duration) -> PathBuf {
+            let path =
+                process_log_file_path_in_root(self.db.dir.path(), self.session_id, Uuid::new_v4());
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, contents).unwrap();
+            set_age(&path, age);
+            path
+        }
+
+        async fn compact(&self) -> LogCompactionReport {
+            self.service.compact_in(&self.logs_root()).await.unwrap()
+        }
+    }
+
+    fn set_age(path: &Path, age: Duration) {
+        std::fs::File::open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
+
+    async fn fixture(policy: LogRetentionConfig) -> Fixture {
+        let db = TestDb::new().await;
+        let workspace = Workspace::create(
+            &db.pool,
+            &CreateWorkspace {
+                branch: "vk/logs".to_string(),
+                name: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let session = Session::create(
+            &db.pool,
+            &CreateSession {
+                executor: None,
+                name: None,
+            },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await
+        .unwrap();
+
+        let config = Config {
+            log_retention: policy,
+            ..Default::default()
+        };
+        Fixture {
+            service: LogRetentionService::new(db.service(), Arc::new(RwLock::new(config))),
+            session_id: session.id,
+            db,
+        }
+    }
+
+    #[tokio::test]
+    async fn old_logs_are_compressed_and_read_back() {
+        let fixture = fixture(LogRetentionConfig {
+            compress_after_days: Some(1),
+            delete_after_days: None,
+            max_total_size_mb: None,
+        })
+        .await;
+        let contents = "{\"Stdout\":\"building\"}\n".repeat(100);
+        let old = fixture.write_log(&contents, 2 * DAY);
+        let fresh = fixture.write_log(&contents, Duration::ZERO);
+        let old_modified = std::fs::metadata(&old).unwrap().modified().unwrap();
+
+        let report = fixture.compact().await;
+
+        assert_eq!(report.compressed_files, 1);
+        assert_eq!(report.deleted_files, 0);
+        assert!(report.bytes_reclaimed > 0);
+        assert!(!old.exists());
+        let compressed = compressed_log_file_path(&old);
+        // Compression keeps the log's age for the next pass.
+        assert_eq!(
+            std::fs::metadata(&compressed).unwrap().modified().unwrap(),
+            old_modified
+        );
+        assert_eq!(read_execution_log_file(&old).await.unwrap(), contents);
+        assert!(fresh.exists());
+
+        // Already compressed logs are left as they are.
+        assert_eq!(fixture.compact().await.compressed_files, 0);
+    }
+
+    #[tokio::test]
+    async fn expired_logs_and_orphaned_sessions_are_pruned() {
+        let fixture = fixture(LogRetentionConfig {
+            compress_after_days: Some(1),
+            delete_after_days: Some(30),
+            max_total_size_mb: None,
+        })
+        .await;
+        let expired = fixture.write_log("old\n", 31 * DAY);
+        let kept = fixture.write_log("recent\n", Duration::ZERO);
+
+        let orphan_log =
+            process_log_file_path_in_root(fixture.db.dir.path(), Uuid::new_v4(), Uuid::new_v4());
+        let orphan_dir = orphan_log.parent().unwrap().parent().unwrap().to_path_buf();
+        let new_orphan_log =
+            process_log_file_path_in_root(fixture.db.dir.path(), Uuid::new_v4(), Uuid::new_v4());
+        for log in [&orphan_log, &new_orphan_log] {
+            std::fs::create_dir_all(log.parent().unwrap()).unwrap();
+            std::fs::write(log, "orphaned\n").unwrap();
+        }
+        set_age(&orphan_dir, 2 * ORPHAN_GRACE_PERIOD);
+
+        let report = fixture.compact().await;
+
+        assert_eq!(report.deleted_files, 1);
+        assert_eq!(report.compressed_files, 0);
+        assert_eq!(report.pruned_sessions, 1);
+        assert!(!expired.exists());
+        assert!(!compressed_log_file_path(&expired).exists());
+        assert!(kept.exists());
+        assert!(!orphan_dir.exists());
+        // The session row may still be on its way.
+        assert!(new_orphan_log.exists());
+    }
+
+    #[tokio::test]
+    async fn size_budget_deletes_the_oldest_logs_first() {
+        let fixture = fixture(LogRetentionConfig {
+            compress_after_days: None,
+            delete_after_days: None,
+            max_total_size_mb: Some(1),
+        })
+        .await;
+        let megabyte = "x".repeat(1024 * 1024);
+        let oldest = fixture.write_log(&megabyte, 3 * DAY);
+        let older = fixture.write_log(&megabyte, 2 * DAY);
+        let newest = fixture.write_log("small\n", DAY);
+
+        let report = fixture.compact().await;
+
+        assert_eq!(report.deleted_files, 2);
+        assert!(!oldest.exists());
+        assert!(!older.exists());
+        assert!(newest.exists());
+    }
+}
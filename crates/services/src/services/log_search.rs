@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use db::{
+    DBService,
+    models::execution_log_search::{ExecutionLogIndex, IndexedLogLine, LogSearchHit, LogStream},
+};
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use utils::{
+    execution_logs::{parse_log_jsonl_lossy, process_log_file_path, read_execution_log_file},
+    log_msg::LogMsg,
+};
+use uuid::Uuid;
+
+/// How many unindexed processes the backfill loop handles per tick.
+const BACKFILL_BATCH_SIZE: i64 = 20;
+/// Lines longer than this are cut before indexing; agents occasionally emit
+/// multi-megabyte JSON lines that are useless as search results.
+const MAX_LINE_CHARS: usize = 4000;
+
+#[derive(Debug, Error)]
+pub enum LogSearchError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Search query is empty")]
+    EmptyQuery,
+}
+
+/// Maintains the full-text index over execution process logs.
+#[derive(Clone)]
+pub struct LogSearchService {
+    db: DBService,
+}
+
+impl LogSearchService {
+    pub fn new(db: DBService) -> Self {
+        Self { db }
+    }
+
+    /// Spawn a background loop that indexes finished processes that were
+    /// never indexed, e.g. ones that ran before the index existed.
+    pub fn spawn_backfill_loop(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self::new(db);
+        tokio::spawn(async move {
+            info!("Starting execution log index backfill loop");
+            let mut interval = interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.backfill().await {
+                    error!("Error backfilling execution log index: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn backfill(&self) -> Result<(), LogSearchError> {
+        let pending = ExecutionLogIndex::find_unindexed(&self.db.pool, BACKFILL_BATCH_SIZE).await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        debug!("Indexing logs for {} execution processes", pending.len());
+        for process in pending {
+            if let Err(e) = self
+                .index_execution(process.session_id, process.execution_id)
+                .await
+            {
+                warn!(
+                    "Failed to index logs for execution {}: {}",
+                    process.execution_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-index all stdout/stderr lines of an execution process from its log
+    /// file. A missing file indexes as empty so the backfill doesn't retry it.
+    pub async fn index_execution(
+        &self,
+        session_id: Uuid,
+        execution_id: Uuid,
+    ) -> Result<usize, LogSearchError> {
+        let path = process_log_file_path(session_id, execution_id);
+        let jsonl = match read_execution_log_file(&path).await {
+            Ok(jsonl) => jsonl,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let lines = log_lines(&parse_log_jsonl_lossy(execution_id, &jsonl));
+        ExecutionLogIndex::replace_for_execution(&self.db.pool, execution_id, &lines).await?;
+        Ok(lines.len())
+    }
+
+    pub async fn search(
+        &self,
+        query: &str,
+        workspace_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<LogSearchHit>, LogSearchError> {
+        let fts_query = to_fts_query(query).ok_or(LogSearchError::EmptyQuery)?;
+        Ok(ExecutionLogIndex::search(&self.db.pool, &fts_query, workspace_id, limit).await?)
+    }
+}
+
+/// Flatten stdout/stderr messages into numbered lines. A single message can
+/// carry several lines, or a partial one, depending on how the process
+/// flushed; a partial line is joined with the rest of it from the next
+/// message on the same stream.
+fn log_lines(messages: &[LogMsg]) -> Vec<IndexedLogLine> {
+    let mut lines = Vec::new();
+    let mut line_number = 0i64;
+    let mut partial_stdout = String::new();
+    let mut partial_stderr = String::new();
+    for msg in messages {
+        let (stream, text, partial) = match msg {
+            LogMsg::Stdout(text) => (LogStream::Stdout, text, &mut partial_stdout),
+            LogMsg::Stderr(text) => (LogStream::Stderr, text, &mut partial_stderr),
+            _ => continue,
+        };
+        let mut rest = text.as_str();
+        while let Some((line, after)) = rest.split_once('\n') {
+            partial.push_str(line);
+            push_line(&mut lines, &mut line_number, stream, partial);
+            partial.clear();
+            rest = after;
+        }
+        partial.push_str(rest);
+    }
+    for (stream, partial) in [
+        (LogStream::Stdout, &partial_stdout),
+        (LogStream::Stderr, &partial_stderr),
+    ] {
+        if !partial.is_empty() {
+            push_line(&mut lines, &mut line_number, stream, partial);
+        }
+    }
+    lines
+}
+
+fn push_line(
+    lines: &mut Vec<IndexedLogLine>,
+    line_number: &mut i64,
+    stream: LogStream,
+    line: &str,
+) {
+    *line_number += 1;
+    let line = line.trim_end();
+    if line.is_empty() {
+        return;
+    }
+    lines.push(IndexedLogLine {
+        line_number: *line_number,
+        stream,
+        content: line.chars().take(MAX_LINE_CHARS).collect(),
+    });
+}
+
+/// Turn free text into an FTS5 query that matches lines containing every
+/// term. Terms are quoted so punctuation in file paths and error messages
+/// (`src/main.rs`, `E0308:`) isn't parsed as query syntax.
+fn to_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fts_query_quotes_each_term() {
+        assert_eq!(to_fts_query("   "), None);
+        assert_eq!(
+            to_fts_query("src/main.rs error"),
+            Some("\"src/main.rs\" \"error\"".to_string())
+        );
+        assert_eq!(
+            to_fts_query("say \"hi\""),
+            Some("\"say\" \"\"\"hi\"\"\"".to_string())
+        );
+    }
+
+    #[test]
+    fn log_lines_split_messages_and_skip_non_output() {
+        let messages = vec![
+            LogMsg::Stdout("first\nsecond\n".to_string()),
+            LogMsg::Ready,
+            LogMsg::Stderr("\nboom".to_string()),
+        ];
+        let lines = log_lines(&messages);
+        let summary: Vec<_> = lines
+            .iter()
+            .map(|l| (l.line_number, l.stream, l.content.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, LogStream::Stdout, "first"),
+                (2, LogStream::Stdout, "second"),
+                (4, LogStream::Stderr, "boom"),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_lines_join_a_line_split_across_messages() {
+        let messages = vec![
+            LogMsg::Stdout("compiling cr".to_string()),
+            LogMsg::Stderr("warn".to_string()),
+            LogMsg::Stdout("ate\nerror[E0308]: mism".to_string()),
+            LogMsg::Stderr("ing: unused\n".to_string()),
+            LogMsg::Stdout("atched types\n".to_string()),
+        ];
+        let lines = log_lines(&messages);
+        let summary: Vec<_> = lines
+            .iter()
+            .map(|l| (l.line_number, l.stream, l.content.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, LogStream::Stdout, "compiling crate"),
+                (2, LogStream::Stderr, "warning: unused"),
+                (3, LogStream::Stdout, "error[E0308]: mismatched types"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Server metrics behind `GET /metrics`, rendered in the Prometheus text
+//! exposition format for self-hosters to scrape and alert on.
+
+use std::fmt::Write as _;
+
+use crate::services::execution_scheduler::ExecutionQueueStatus;
+
+const RUN_REASONS: [&str; 8] = [
+    "setupscript",
+    "cleanupscript",
+    "archivescript",
+    "codingagent",
+    "devserver",
+    "repocommand",
+    "checkcommand",
+    "changeexplanation",
+];
+
+/// A point-in-time snapshot of everything `/metrics` reports.
+#[derive(Debug, Clone)]
+pub struct ServerMetrics {
+    /// Running execution processes per run reason.
+    pub running_processes: Vec<(String, i64)>,
+    pub execution_queue: ExecutionQueueStatus,
+    pub pending_approvals: usize,
+    pub event_stream_subscribers: usize,
+    pub relay_enabled: bool,
+    /// A relay session was started and not stopped; it may be reconnecting.
+    pub relay_active: bool,
+    pub db_pool_size: u32,
+    pub db_pool_idle: usize,
+    pub uptime_seconds: u64,
+}
+
+impl ServerMetrics {
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        };
+        let single = |value: String| [(String::new(), value)];
+
+        // Every reason is reported so alerts can tell zero from missing.
+        let processes: Vec<(String, String)> = RUN_REASONS
+            .iter()
+            .map(|reason| {
+                let count = self
+                    .running_processes
+                    .iter()
+                    .find(|(r, _)| r == reason)
+                    .map_or(0, |(_, count)| *count);
+                (format!("{{run_reason=\"{reason}\"}}"), count.to_string())
+            })
+            .collect();
+        metric(
+            "vibe_kanban_execution_processes_running",
+            "gauge",
+            "Execution processes currently running, by run reason.",
+            &processes,
+        );
+        metric(
+            "vibe_kanban_executions_active",
+            "gauge",
+            "Coding agent executions holding a scheduler slot.",
+            &single(self.execution_queue.running.to_string()),
+        );
+        metric(
+            "vibe_kanban_executions_waiting",
+            "gauge",
+            "Coding agent executions waiting for a scheduler slot.",
+            &single(self.execution_queue.waiting.to_string()),
+        );
+        if let Some(capacity) = self.execution_queue.capacity {
+            metric(
+                "vibe_kanban_executions_capacity",
+                "gauge",
+                "Maximum concurrent coding agent executions.",
+                &single(capacity.to_string()),
+            );
+        }
+        metric(
+            "vibe_kanban_approvals_pending",
+            "gauge",
+            "Tool approvals and questions waiting on a response.",
+            &single(self.pending_approvals.to_string()),
+        );
+        metric(
+            "vibe_kanban_event_stream_subscribers",
+            "gauge",
+            "Clients subscribed to the live event stream.",
+            &single(self.event_stream_subscribers.to_string()),
+        );
+        metric(
+            "vibe_kanban_relay_enabled",
+            "gauge",
+            "1 if the relay is enabled in the config.",
+            &single(u8::from(self.relay_enabled).to_string()),
+        );
+        metric(
+            "vibe_kanban_relay_active",
+            "gauge",
+            "1 if a relay session is running or reconnecting.",
+            &single(u8::from(self.relay_active).to_string()),
+        );
+        metric(
+            "vibe_kanban_db_pool_connections",
+            "gauge",
+            "Open database connections.",
+            &single(self.db_pool_size.to_string()),
+        );
+        metric(
+            "vibe_kanban_db_pool_idle_connections",
+            "gauge",
+            "Open database connections not in use.",
+            &single(self.db_pool_idle.to_string()),
+        );
+        metric(
+            "vibe_kanban_uptime_seconds",
+            "counter",
+            "Seconds since the server started.",
+            &single(self.uptime_seconds.to_string()),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_text_format() {
+        let metrics = ServerMetrics {
+            running_processes: vec![("codingagent".to_string(), 2)],
+            execution_queue: ExecutionQueueStatus {
+                capacity: None,
+                running: 2,
+                waiting: 1,
+                projects: Vec::new(),
+                tags: Vec::new(),
+            },
+            pending_approvals: 3,
+            event_stream_subscribers: 1,
+            relay_enabled: true,
+            relay_active: false,
+            db_pool_size: 4,
+            db_pool_idle: 3,
+            uptime_seconds: 60,
+        };
+        let out = metrics.render();
+
+        assert!(out.contains(
+            "# TYPE vibe_kanban_execution_processes_running gauge\n\
+             vibe_kanban_execution_processes_running{run_reason=\"setupscript\"} 0\n"
+        ));
+        assert!(
+            out.contains("vibe_kanban_execution_processes_running{run_reason=\"codingagent\"} 2\n")
+        );
+        assert!(out.contains("vibe_kanban_approvals_pending 3\n"));
+        assert!(out.contains("vibe_kanban_relay_active 0\n"));
+        assert!(!out.contains("vibe_kanban_executions_capacity"));
+    }
+}
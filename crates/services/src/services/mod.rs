@@ -1,19 +1,41 @@
 pub mod analytics;
 pub mod approvals;
+pub mod audit;
 pub mod auth;
+pub mod branch_freshness;
+pub mod change_explanation;
+pub mod chunked_upload;
+pub mod ci_webhook;
+pub mod commit_message;
 pub mod config;
 pub mod container;
+pub mod content_search;
+pub mod db_maintenance;
+pub mod dev_server;
 pub mod diff_stream;
+pub mod doc_index;
 pub mod events;
 pub mod execution_process;
+pub mod execution_scheduler;
+pub mod execution_timeout;
 pub mod file;
 pub mod file_ranker;
 pub mod file_search;
 pub mod filesystem;
 pub mod filesystem_watcher;
+pub mod health;
+pub mod image_processing;
+pub mod issue_tracker;
+pub mod log_retention;
+pub mod log_search;
+pub mod metrics;
 pub mod notification;
+pub mod notification_routing;
 pub mod oauth_credentials;
+pub mod package_manager;
 pub mod pr_monitor;
+pub mod prompt_snippet;
+pub mod provider_credentials;
 
 #[cfg(feature = "qa-mode")]
 pub mod qa_repos;
@@ -21,3 +43,10 @@ pub mod queued_message;
 pub mod remote_client;
 pub mod remote_sync;
 pub mod repo;
+pub mod reports;
+pub mod secret_scan;
+pub mod session_timeline;
+pub mod session_transcript;
+pub mod setup_cache;
+pub mod shared_watcher;
+pub mod worktree_files;
@@ -1,11 +1,69 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use chrono::{Local, NaiveTime};
+use db::models::approval_notification_rule::NotificationChannel;
+use serde_json::json;
+use tokio::{
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
 use utils::{self, command_ext::NoWindowExt};
 use uuid::Uuid;
 
-use crate::services::config::{Config, SoundFile};
+use crate::services::config::{
+    Config, NotificationConfig, NotificationEventToggles, QuietHours, SoundFile,
+};
+
+/// What a notification is about, for the per-event toggles and summaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    Approval,
+    Question,
+    WorkspaceComplete,
+    /// Raised by the desktop app; always sent.
+    Other,
+}
+
+impl NotificationEvent {
+    fn enabled(self, toggles: &NotificationEventToggles) -> bool {
+        match self {
+            NotificationEvent::Approval => toggles.approvals,
+            NotificationEvent::Question => toggles.questions,
+            NotificationEvent::WorkspaceComplete => toggles.workspace_complete,
+            NotificationEvent::Other => true,
+        }
+    }
+}
+
+/// The local channels a notification goes to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LocalChannels {
+    sound: bool,
+    push: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HeldNotification {
+    event: NotificationEvent,
+    channels: LocalChannels,
+    title: String,
+    message: String,
+    workspace_id: Option<Uuid>,
+}
+
+/// Desktop and sound notifications waiting for a batch window or quiet
+/// hours to end.
+#[derive(Debug, Default)]
+struct LocalDelivery {
+    /// Notifications before this are held for the next summary.
+    batch_until: Option<Instant>,
+    held: Vec<HeldNotification>,
+    flush_scheduled: bool,
+}
 
 /// Trait for sending push notifications. Implementations can use
 /// platform-specific OS commands, Tauri's notification plugin, etc.
@@ -58,6 +116,8 @@ impl PushNotifier for DefaultPushNotifier {
 pub struct NotificationService {
     config: Arc<RwLock<Config>>,
     push_notifier: Arc<dyn PushNotifier>,
+    http_client: reqwest::Client,
+    local_delivery: Arc<Mutex<LocalDelivery>>,
 }
 
 impl std::fmt::Debug for NotificationService {
@@ -73,21 +133,210 @@ impl NotificationService {
         Self {
             config,
             push_notifier: get_global_push_notifier(),
+            http_client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            local_delivery: Arc::new(Mutex::new(LocalDelivery::default())),
         }
     }
 
-    /// Send both sound and push notifications if enabled.
-    /// `workspace_id` is forwarded to the push notifier so Tauri can emit a
-    /// navigation event when the notification is clicked.
-    pub async fn notify(&self, title: &str, message: &str, workspace_id: Option<Uuid>) {
-        let config = self.config.read().await.notifications.clone();
+    pub async fn notification_config(&self) -> NotificationConfig {
+        self.config.read().await.notifications.clone()
+    }
+
+    /// Send both sound and push notifications if enabled, unless the event
+    /// is turned off. `workspace_id` is forwarded to the push notifier so
+    /// Tauri can emit a navigation event when the notification is clicked.
+    pub async fn notify(
+        &self,
+        event: NotificationEvent,
+        title: &str,
+        message: &str,
+        workspace_id: Option<Uuid>,
+    ) {
+        let config = self.config.read().await;
+        if !event.enabled(&config.notification_delivery.events) {
+            return;
+        }
+        let channels = LocalChannels {
+            sound: config.notifications.sound_enabled,
+            push: config.notifications.push_enabled,
+        };
+        drop(config);
+
+        self.deliver_local(HeldNotification {
+            event,
+            channels,
+            title: title.to_string(),
+            message: message.to_string(),
+            workspace_id,
+        })
+        .await;
+    }
 
-        if config.sound_enabled {
-            Self::play_sound_notification(&config.sound_file).await;
+    /// Send to the given channels, regardless of the configured sound and
+    /// push settings. Used for approvals routed by notification rules.
+    pub async fn notify_channels(
+        &self,
+        event: NotificationEvent,
+        channels: &[NotificationChannel],
+        title: &str,
+        message: &str,
+        workspace_id: Option<Uuid>,
+    ) {
+        let events = self
+            .config
+            .read()
+            .await
+            .notification_delivery
+            .events
+            .clone();
+        if !event.enabled(&events) {
+            return;
         }
 
-        if config.push_enabled {
-            self.push_notifier.send(title, message, workspace_id).await;
+        let mut local = LocalChannels::default();
+        for channel in channels {
+            match channel {
+                NotificationChannel::Desktop => local.push = true,
+                NotificationChannel::Sound => local.sound = true,
+                NotificationChannel::Slack {
+                    webhook_url,
+                    channel,
+                } => {
+                    let mut payload = json!({ "text": format!("*{title}*\n{message}") });
+                    if let Some(channel) = channel {
+                        payload["channel"] = json!(channel);
+                    }
+                    self.post_json(webhook_url, &payload).await;
+                }
+                NotificationChannel::Webhook { url } => {
+                    let payload = json!({
+                        "title": title,
+                        "message": message,
+                        "workspace_id": workspace_id,
+                    });
+                    self.post_json(url, &payload).await;
+                }
+            }
+        }
+
+        self.deliver_local(HeldNotification {
+            event,
+            channels: local,
+            title: title.to_string(),
+            message: message.to_string(),
+            workspace_id,
+        })
+        .await;
+    }
+
+    /// Time left until quiet hours end, if they are on now.
+    async fn quiet_hours_remaining(&self) -> Option<Duration> {
+        let quiet_hours = self
+            .config
+            .read()
+            .await
+            .notification_delivery
+            .quiet_hours
+            .clone()?;
+        quiet_hours_remaining(&quiet_hours, Local::now().time())
+    }
+
+    async fn batch_window(&self) -> Duration {
+        let secs = self
+            .config
+            .read()
+            .await
+            .notification_delivery
+            .batch_window_secs;
+        Duration::from_secs(secs.into())
+    }
+
+    /// Send right away, or hold the notification for a summary while a batch
+    /// window is open or during quiet hours.
+    async fn deliver_local(&self, notification: HeldNotification) {
+        if notification.channels == LocalChannels::default() {
+            return;
+        }
+        let quiet_for = self.quiet_hours_remaining().await;
+        let batch_window = self.batch_window().await;
+
+        let now = Instant::now();
+        let mut delivery = self.local_delivery.lock().await;
+        let hold_until = match quiet_for {
+            Some(remaining) => Some(now + remaining),
+            None => delivery.batch_until.filter(|until| *until > now),
+        };
+        if let Some(until) = hold_until {
+            delivery.held.push(notification);
+            if !delivery.flush_scheduled {
+                delivery.flush_scheduled = true;
+                self.schedule_flush(until);
+            }
+            return;
+        }
+        delivery.batch_until = Some(now + batch_window);
+        drop(delivery);
+
+        self.send_local(&notification).await;
+    }
+
+    fn schedule_flush(&self, at: Instant) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until(at).await;
+            service.flush_held().await;
+        });
+    }
+
+    /// Send what was held as one summary and start a new batch window.
+    async fn flush_held(&self) {
+        let quiet_for = self.quiet_hours_remaining().await;
+        let batch_window = self.batch_window().await;
+
+        let mut delivery = self.local_delivery.lock().await;
+        if let Some(remaining) = quiet_for {
+            self.schedule_flush(Instant::now() + remaining);
+            return;
+        }
+        let held = std::mem::take(&mut delivery.held);
+        delivery.flush_scheduled = false;
+        delivery.batch_until = Some(Instant::now() + batch_window);
+        drop(delivery);
+
+        if let Some(summary) = summarize(held) {
+            self.send_local(&summary).await;
+        }
+    }
+
+    async fn send_local(&self, notification: &HeldNotification) {
+        if notification.channels.sound {
+            let sound_file = self.config.read().await.notifications.sound_file.clone();
+            Self::play_sound_notification(&sound_file).await;
+        }
+        if notification.channels.push {
+            self.push_notifier
+                .send(
+                    &notification.title,
+                    &notification.message,
+                    notification.workspace_id,
+                )
+                .await;
+        }
+    }
+
+    async fn post_json(&self, url: &str, payload: &serde_json::Value) {
+        let result = self
+            .http_client
+            .post(url)
+            .json(payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("Failed to deliver notification webhook: {}", e);
         }
     }
 
@@ -271,6 +520,80 @@ async fn get_wsl_root_path() -> Option<String> {
     None
 }
 
+/// Time left until quiet hours end, if `now` falls within them. Windows
+/// with unparseable times are ignored.
+fn quiet_hours_remaining(quiet_hours: &QuietHours, now: NaiveTime) -> Option<Duration> {
+    let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+    let (Some(start), Some(end)) = (parse(&quiet_hours.start), parse(&quiet_hours.end)) else {
+        return None;
+    };
+    let quiet = if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    };
+    if !quiet {
+        return None;
+    }
+    let mut remaining = end.signed_duration_since(now);
+    if remaining < chrono::Duration::zero() {
+        remaining += chrono::Duration::days(1);
+    }
+    remaining.to_std().ok()
+}
+
+/// Collapse held notifications into one, counting them by event. A single
+/// notification is passed through as is.
+fn summarize(mut held: Vec<HeldNotification>) -> Option<HeldNotification> {
+    if held.len() <= 1 {
+        return held.pop();
+    }
+
+    let count = |event| held.iter().filter(|n| n.event == event).count();
+    let parts: Vec<String> = [
+        (
+            NotificationEvent::Approval,
+            "approval needed",
+            "approvals needed",
+        ),
+        (
+            NotificationEvent::Question,
+            "question asked",
+            "questions asked",
+        ),
+        (
+            NotificationEvent::WorkspaceComplete,
+            "workspace finished",
+            "workspaces finished",
+        ),
+        (
+            NotificationEvent::Other,
+            "other notification",
+            "other notifications",
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(event, one, many)| match count(event) {
+        0 => None,
+        1 => Some(format!("1 {one}")),
+        n => Some(format!("{n} {many}")),
+    })
+    .collect();
+
+    let workspace_id = held[0].workspace_id;
+    let same_workspace = held.iter().all(|n| n.workspace_id == workspace_id);
+    Some(HeldNotification {
+        event: NotificationEvent::Other,
+        channels: LocalChannels {
+            sound: held.iter().any(|n| n.channels.sound),
+            push: held.iter().any(|n| n.channels.push),
+        },
+        title: format!("{} notifications", held.len()),
+        message: parts.join(", "),
+        workspace_id: workspace_id.filter(|_| same_workspace),
+    })
+}
+
 /// Convert WSL path to Windows UNC path for PowerShell
 async fn wsl_to_windows_path(wsl_path: &std::path::Path) -> Option<String> {
     let path_str = wsl_path.to_string_lossy();
@@ -295,3 +618,84 @@ async fn wsl_to_windows_path(wsl_path: &std::path::Path) -> Option<String> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn held(event: NotificationEvent, workspace_id: Option<Uuid>) -> HeldNotification {
+        HeldNotification {
+            event,
+            channels: LocalChannels {
+                sound: false,
+                push: true,
+            },
+            title: "Approval Needed: ws".to_string(),
+            message: "Tool 'Bash' requires approval".to_string(),
+            workspace_id,
+        }
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let overnight = quiet("22:00", "07:00");
+        assert_eq!(
+            quiet_hours_remaining(&overnight, time(3, 0)),
+            Some(Duration::from_secs(4 * 3600))
+        );
+        assert_eq!(
+            quiet_hours_remaining(&overnight, time(23, 30)),
+            Some(Duration::from_secs(7 * 3600 + 30 * 60))
+        );
+        assert_eq!(quiet_hours_remaining(&overnight, time(7, 0)), None);
+        assert_eq!(quiet_hours_remaining(&overnight, time(12, 0)), None);
+
+        let lunch = quiet("12:00", "13:00");
+        assert_eq!(
+            quiet_hours_remaining(&lunch, time(12, 15)),
+            Some(Duration::from_secs(45 * 60))
+        );
+        assert_eq!(
+            quiet_hours_remaining(&quiet("late", "07:00"), time(3, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn summary_counts_held_notifications_by_event() {
+        let workspace_id = Some(Uuid::new_v4());
+        let single = held(NotificationEvent::Approval, workspace_id);
+        assert_eq!(summarize(vec![single.clone()]), Some(single));
+        assert_eq!(summarize(Vec::new()), None);
+
+        let summary = summarize(vec![
+            held(NotificationEvent::Approval, workspace_id),
+            held(NotificationEvent::Approval, workspace_id),
+            held(NotificationEvent::Question, workspace_id),
+        ])
+        .unwrap();
+        assert_eq!(summary.title, "3 notifications");
+        assert_eq!(summary.message, "2 approvals needed, 1 question asked");
+        assert_eq!(summary.workspace_id, workspace_id);
+        assert!(summary.channels.push && !summary.channels.sound);
+
+        let mixed = summarize(vec![
+            held(NotificationEvent::WorkspaceComplete, workspace_id),
+            held(NotificationEvent::WorkspaceComplete, None),
+        ])
+        .unwrap();
+        assert_eq!(mixed.message, "2 workspaces finished");
+        assert_eq!(mixed.workspace_id, None);
+    }
+}
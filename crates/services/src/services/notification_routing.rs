@@ -0,0 +1,187 @@
+//! Routing of approval notifications to channels by tool category and repo.
+//!
+//! Rules are evaluated in position order and the first one matching both the
+//! tool category and one of the workspace's repos decides the channels. With
+//! no match the configured sound and push settings apply, as before rules.
+
+use db::models::approval_notification_rule::{
+    ApprovalNotificationRule, NotificationChannel, ToolCategory,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::config::NotificationConfig;
+
+/// Where an approval notification goes and why.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ApprovalRoute {
+    pub tool_category: ToolCategory,
+    /// The rule that matched, `None` when the configured defaults apply.
+    pub rule_id: Option<Uuid>,
+    pub channels: Vec<NotificationChannel>,
+}
+
+/// Categorise a tool by the name the executor reports. Names differ between
+/// executors (`Bash`, `exec_command`, `apply_patch`, `mcp__server__tool`...),
+/// so this matches on the words they have in common.
+pub fn tool_category(tool_name: &str, is_question: bool) -> ToolCategory {
+    if is_question {
+        return ToolCategory::Question;
+    }
+
+    let name = tool_name.trim().to_ascii_lowercase();
+    if name.starts_with("mcp__") || name.starts_with("mcp.") || name.starts_with("mcp:") {
+        return ToolCategory::Mcp;
+    }
+
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let has = |candidates: &[&str]| words.iter().any(|word| candidates.contains(word));
+
+    if has(&[
+        "bash", "shell", "sh", "exec", "command", "terminal", "run", "execute",
+    ]) {
+        ToolCategory::Shell
+    } else if has(&["webfetch", "websearch", "fetch", "web", "browser", "url"]) {
+        ToolCategory::Web
+    } else if has(&[
+        "edit",
+        "multiedit",
+        "notebookedit",
+        "write",
+        "patch",
+        "replace",
+        "create",
+        "delete",
+        "move",
+        "rename",
+    ]) {
+        ToolCategory::FileEdit
+    } else if has(&[
+        "read", "view", "glob", "grep", "ls", "list", "search", "find", "cat",
+    ]) {
+        ToolCategory::FileRead
+    } else {
+        ToolCategory::Other
+    }
+}
+
+/// The first rule matching the category and any of `repo_ids`.
+pub fn matching_rule<'a>(
+    rules: &'a [ApprovalNotificationRule],
+    category: ToolCategory,
+    repo_ids: &[Uuid],
+) -> Option<&'a ApprovalNotificationRule> {
+    rules.iter().find(|rule| {
+        rule.tool_category.is_none_or(|c| c == category)
+            && rule.repo_id.is_none_or(|id| repo_ids.contains(&id))
+    })
+}
+
+/// The channels notifications go to when no rule matches.
+pub fn default_channels(config: &NotificationConfig) -> Vec<NotificationChannel> {
+    let mut channels = Vec::new();
+    if config.sound_enabled {
+        channels.push(NotificationChannel::Sound);
+    }
+    if config.push_enabled {
+        channels.push(NotificationChannel::Desktop);
+    }
+    channels
+}
+
+/// Route an approval for `tool_name` in a workspace with `repo_ids`.
+pub async fn route_approval(
+    pool: &SqlitePool,
+    config: &NotificationConfig,
+    tool_name: &str,
+    is_question: bool,
+    repo_ids: &[Uuid],
+) -> Result<ApprovalRoute, sqlx::Error> {
+    let tool_category = tool_category(tool_name, is_question);
+    let rules = ApprovalNotificationRule::find_all(pool).await?;
+    Ok(match matching_rule(&rules, tool_category, repo_ids) {
+        Some(rule) => ApprovalRoute {
+            tool_category,
+            rule_id: Some(rule.id),
+            channels: rule.channels.0.clone(),
+        },
+        None => ApprovalRoute {
+            tool_category,
+            rule_id: None,
+            channels: default_channels(config),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn rule(
+        tool_category: Option<ToolCategory>,
+        repo_id: Option<Uuid>,
+        channels: Vec<NotificationChannel>,
+    ) -> ApprovalNotificationRule {
+        ApprovalNotificationRule {
+            id: Uuid::new_v4(),
+            position: 0,
+            tool_category,
+            repo_id,
+            channels: Json(channels),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn categorises_tool_names_across_executors() {
+        assert_eq!(tool_category("Bash", false), ToolCategory::Shell);
+        assert_eq!(
+            tool_category("codex.exec_command", false),
+            ToolCategory::Shell
+        );
+        assert_eq!(tool_category("MultiEdit", false), ToolCategory::FileEdit);
+        assert_eq!(tool_category("apply_patch", false), ToolCategory::FileEdit);
+        assert_eq!(tool_category("Read", false), ToolCategory::FileRead);
+        assert_eq!(tool_category("WebFetch", false), ToolCategory::Web);
+        assert_eq!(
+            tool_category("mcp__github__create_pr", false),
+            ToolCategory::Mcp
+        );
+        assert_eq!(tool_category("plan", false), ToolCategory::Other);
+        assert_eq!(tool_category("Bash", true), ToolCategory::Question);
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let repo = Uuid::new_v4();
+        let slack = NotificationChannel::Slack {
+            webhook_url: "https://hooks.slack.com/services/x".to_string(),
+            channel: Some("#infra".to_string()),
+        };
+        let rules = vec![
+            rule(Some(ToolCategory::Shell), Some(repo), vec![slack.clone()]),
+            rule(
+                Some(ToolCategory::FileEdit),
+                None,
+                vec![NotificationChannel::Desktop],
+            ),
+            rule(None, None, vec![]),
+        ];
+
+        let shell_in_repo = matching_rule(&rules, ToolCategory::Shell, &[repo]).unwrap();
+        assert_eq!(shell_in_repo.channels.0, vec![slack]);
+        let shell_elsewhere = matching_rule(&rules, ToolCategory::Shell, &[]).unwrap();
+        assert!(shell_elsewhere.channels.0.is_empty());
+        let edit = matching_rule(&rules, ToolCategory::FileEdit, &[repo]).unwrap();
+        assert_eq!(edit.channels.0, vec![NotificationChannel::Desktop]);
+    }
+}
@@ -0,0 +1,171 @@
+//! Lockfile-based JavaScript package manager detection, used to suggest
+//! setup and dev server scripts for repos that don't have any configured.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    /// Lockfiles in priority order. A repo migrating between managers often
+    /// has more than one checked in; the less common manager is usually the
+    /// one being migrated to.
+    const LOCKFILES: &[(&str, PackageManager)] = &[
+        ("bun.lock", PackageManager::Bun),
+        ("bun.lockb", PackageManager::Bun),
+        ("pnpm-lock.yaml", PackageManager::Pnpm),
+        ("yarn.lock", PackageManager::Yarn),
+        ("package-lock.json", PackageManager::Npm),
+        ("npm-shrinkwrap.json", PackageManager::Npm),
+    ];
+
+    fn from_package_manager_field(value: &str) -> Option<Self> {
+        // Corepack format: "<name>@<version>[+<hash>]"
+        match value.split('@').next()? {
+            "npm" => Some(Self::Npm),
+            "pnpm" => Some(Self::Pnpm),
+            "yarn" => Some(Self::Yarn),
+            "bun" => Some(Self::Bun),
+            _ => None,
+        }
+    }
+
+    pub fn install_command(self) -> &'static str {
+        match self {
+            Self::Npm => "npm install",
+            Self::Pnpm => "pnpm install",
+            Self::Yarn => "yarn install",
+            Self::Bun => "bun install",
+        }
+    }
+
+    pub fn run_command(self, script: &str) -> String {
+        match self {
+            Self::Npm => format!("npm run {script}"),
+            Self::Pnpm => format!("pnpm run {script}"),
+            Self::Yarn => format!("yarn {script}"),
+            Self::Bun => format!("bun run {script}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct ScriptSuggestions {
+    /// None when the repo has no `package.json` at its root.
+    pub package_manager: Option<PackageManager>,
+    pub setup_script: Option<String>,
+    pub dev_server_script: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJson {
+    package_manager: Option<String>,
+    #[serde(default)]
+    scripts: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Scripts tried, in order, when choosing the dev server command.
+const DEV_SCRIPTS: &[&str] = &["dev", "start", "serve"];
+
+fn detect(repo_path: &Path, package_json: &PackageJson) -> PackageManager {
+    package_json
+        .package_manager
+        .as_deref()
+        .and_then(PackageManager::from_package_manager_field)
+        .or_else(|| {
+            PackageManager::LOCKFILES
+                .iter()
+                .find(|(lockfile, _)| repo_path.join(lockfile).is_file())
+                .map(|(_, manager)| *manager)
+        })
+        .unwrap_or(PackageManager::Npm)
+}
+
+fn read_package_json(repo_path: &Path) -> Option<PackageJson> {
+    let content = std::fs::read_to_string(repo_path.join("package.json")).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(package_json) => Some(package_json),
+        Err(e) => {
+            tracing::debug!(
+                "Ignoring unparseable package.json in {}: {}",
+                repo_path.display(),
+                e
+            );
+            Some(PackageJson::default())
+        }
+    }
+}
+
+/// Suggest setup and dev server scripts for the repo at `repo_path`.
+pub fn suggest_scripts(repo_path: &Path) -> ScriptSuggestions {
+    let Some(package_json) = read_package_json(repo_path) else {
+        return ScriptSuggestions::default();
+    };
+    let manager = detect(repo_path, &package_json);
+    let dev_server_script = DEV_SCRIPTS
+        .iter()
+        .find(|script| package_json.scripts.contains_key(**script))
+        .map(|script| manager.run_command(script));
+
+    ScriptSuggestions {
+        package_manager: Some(manager),
+        setup_script: Some(manager.install_command().to_string()),
+        dev_server_script,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_package_json_means_no_suggestions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        assert_eq!(suggest_scripts(dir.path()), ScriptSuggestions::default());
+    }
+
+    #[test]
+    fn lockfile_selects_manager_and_dev_script() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"start": "node index.js", "dev": "vite"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let suggestions = suggest_scripts(dir.path());
+        assert_eq!(suggestions.package_manager, Some(PackageManager::Pnpm));
+        assert_eq!(suggestions.setup_script.as_deref(), Some("pnpm install"));
+        assert_eq!(
+            suggestions.dev_server_script.as_deref(),
+            Some("pnpm run dev")
+        );
+    }
+
+    #[test]
+    fn package_manager_field_wins_over_lockfiles() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "bun@1.1.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+
+        let suggestions = suggest_scripts(dir.path());
+        assert_eq!(suggestions.package_manager, Some(PackageManager::Bun));
+        assert_eq!(suggestions.dev_server_script, None);
+    }
+}
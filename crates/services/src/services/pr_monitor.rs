@@ -18,8 +18,9 @@ use tokio::{sync::Notify, time::interval};
 use tracing::{debug, error, info, warn};
 
 use crate::services::{
-    analytics::AnalyticsContext,
+    analytics::{AnalyticsCategory, AnalyticsContext},
     container::ContainerService,
+    issue_tracker,
     remote_client::{RemoteClient, RemoteClientError},
     remote_sync,
 };
@@ -167,7 +168,8 @@ impl<C: ContainerService + Send + Sync + 'static> PrMonitorService<C> {
         Ok(())
     }
 
-    /// Archive workspace if all its PRs are merged/closed
+    /// Archive workspace if all its PRs are merged/closed, and close its
+    /// imported issues if at least one of them was merged
     async fn try_archive_workspace(
         &self,
         workspace_id: uuid::Uuid,
@@ -191,8 +193,23 @@ impl<C: ContainerService + Send + Sync + 'static> PrMonitorService<C> {
                 error!("Failed to archive workspace {}: {}", workspace.id, e);
             }
 
+            // Issues are only resolved by a merged PR, not by PRs closed
+            // unmerged
+            let merged_pr_count =
+                PullRequest::count_merged_for_workspace(&self.db.pool, workspace_id).await?;
+            if merged_pr_count > 0
+                && let Err(e) =
+                    issue_tracker::close_linked_issues(&self.db.pool, workspace.id).await
+            {
+                error!(
+                    "Failed to close issues of workspace {}: {}",
+                    workspace.id, e
+                );
+            }
+
             if let Some(analytics) = &self.analytics {
                 analytics.analytics_service.track_event(
+                    AnalyticsCategory::Usage,
                     &analytics.user_id,
                     "pr_merged",
                     Some(json!({
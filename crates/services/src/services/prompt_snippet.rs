@@ -0,0 +1,131 @@
+//! Selection and variable substitution for reusable prompt snippets.
+
+use std::collections::HashMap;
+
+use db::models::{task::Task, workspace::Workspace, workspace_repo::WorkspaceRepo};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::services::config::PromptSnippet;
+
+/// Snippets offered in a project: the project's own plus the global ones,
+/// with project snippets shadowing global snippets of the same name.
+pub fn snippets_for_project(
+    snippets: &[PromptSnippet],
+    project_id: Option<Uuid>,
+) -> Vec<PromptSnippet> {
+    let scoped: Vec<&PromptSnippet> = snippets
+        .iter()
+        .filter(|s| project_id.is_some() && s.project_id == project_id)
+        .collect();
+    let mut result: Vec<PromptSnippet> = scoped.iter().map(|s| (*s).clone()).collect();
+    result.extend(
+        snippets
+            .iter()
+            .filter(|s| s.project_id.is_none() && !scoped.iter().any(|p| p.name == s.name))
+            .cloned(),
+    );
+    result.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    result
+}
+
+/// Replace `{{name}}` placeholders with their values. Placeholders without a
+/// value are left as written so a missing variable is visible in the prompt.
+pub fn render(content: &str, variables: &HashMap<String, String>) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after_open[..end].trim();
+        match variables.get(name) {
+            Some(value) => output.push_str(value),
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after_open[end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The project a workspace belongs to, through its task.
+pub async fn project_id_for_workspace(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    match workspace.task_id {
+        Some(task_id) => Ok(Task::find_by_id(pool, task_id).await?.map(|t| t.project_id)),
+        None => Ok(None),
+    }
+}
+
+/// Variables known from the workspace itself. `failing_test` and any other
+/// variable has to be supplied by the caller.
+pub async fn workspace_variables(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<HashMap<String, String>, sqlx::Error> {
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+    let repo_names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+
+    let mut variables = HashMap::new();
+    variables.insert("branch".to_string(), workspace.branch.clone());
+    variables.insert("repo".to_string(), repo_names.join(", "));
+    if let Some(name) = &workspace.name {
+        variables.insert("workspace".to_string(), name.clone());
+    }
+    Ok(variables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(name: &str, content: &str, project_id: Option<Uuid>) -> PromptSnippet {
+        PromptSnippet {
+            name: name.to_string(),
+            content: content.to_string(),
+            project_id,
+        }
+    }
+
+    #[test]
+    fn renders_known_variables_and_keeps_unknown_ones() {
+        let variables = HashMap::from([
+            ("branch".to_string(), "vk/fix-login".to_string()),
+            ("failing_test".to_string(), "auth::login_works".to_string()),
+        ]);
+        let rendered = render(
+            "Fix {{ failing_test }} on {{branch}} in {{repo}}. Unclosed {{",
+            &variables,
+        );
+        assert_eq!(
+            rendered,
+            "Fix auth::login_works on vk/fix-login in {{repo}}. Unclosed {{"
+        );
+    }
+
+    #[test]
+    fn project_snippets_shadow_global_ones() {
+        let project = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let snippets = vec![
+            snippet("review", "global review", None),
+            snippet("tests", "run tests", None),
+            snippet("review", "project review", Some(project)),
+            snippet("deploy", "other project", Some(other)),
+        ];
+
+        let for_project = snippets_for_project(&snippets, Some(project));
+        let contents: Vec<&str> = for_project.iter().map(|s| s.content.as_str()).collect();
+        assert_eq!(contents, vec!["project review", "run tests"]);
+
+        let global = snippets_for_project(&snippets, None);
+        let contents: Vec<&str> = global.iter().map(|s| s.content.as_str()).collect();
+        assert_eq!(contents, vec!["global review", "run tests"]);
+    }
+}
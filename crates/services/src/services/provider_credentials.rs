@@ -0,0 +1,374 @@
+//! API keys and OAuth tokens for the model providers behind the coding
+//! agents, injected into executor processes so agents don't depend on
+//! whatever happens to be exported in the shell that started the server.
+//!
+//! Secrets are kept in their own file, encrypted with the config secrets key
+//! when one is available, and are only ever returned masked.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+
+use super::config::secrets::{self, SecretsError};
+
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Error)]
+pub enum ProviderCredentialsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+    #[error("{provider:?} does not support {kind:?} credentials")]
+    UnsupportedKind {
+        provider: ApiProvider,
+        kind: ProviderCredentialKind,
+    },
+    #[error("Credential must not be empty")]
+    Empty,
+    #[error("No credential stored for {0:?}")]
+    NotConfigured(ApiProvider),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiProvider {
+    Anthropic,
+    Openai,
+    Google,
+}
+
+impl ApiProvider {
+    pub const ALL: [ApiProvider; 3] = [Self::Anthropic, Self::Openai, Self::Google];
+
+    /// Environment variable the agents read the credential from, or `None`
+    /// if the provider has no such variable for this kind.
+    pub fn env_var(self, kind: ProviderCredentialKind) -> Option<&'static str> {
+        match (self, kind) {
+            (Self::Anthropic, ProviderCredentialKind::ApiKey) => Some("ANTHROPIC_API_KEY"),
+            (Self::Anthropic, ProviderCredentialKind::OauthToken) => {
+                Some("CLAUDE_CODE_OAUTH_TOKEN")
+            }
+            (Self::Openai, ProviderCredentialKind::ApiKey) => Some("OPENAI_API_KEY"),
+            (Self::Google, ProviderCredentialKind::ApiKey) => Some("GEMINI_API_KEY"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderCredentialKind {
+    ApiKey,
+    OauthToken,
+}
+
+/// A provider's credential as shown to clients.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProviderCredentialInfo {
+    pub provider: ApiProvider,
+    pub configured: bool,
+    pub kind: Option<ProviderCredentialKind>,
+    /// Only the last characters of the secret, e.g. `••••a1b2`.
+    pub masked: Option<String>,
+    pub env_var: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProviderCredentialValidation {
+    pub provider: ApiProvider,
+    pub valid: bool,
+    /// HTTP status of the validation request, if the provider answered.
+    pub status: Option<u16>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCredential {
+    kind: ProviderCredentialKind,
+    secret: String,
+    updated_at: DateTime<Utc>,
+}
+
+/// Provider credentials in memory, persisted to disk on every change.
+#[derive(Clone)]
+pub struct ProviderCredentials {
+    path: PathBuf,
+    inner: Arc<RwLock<HashMap<ApiProvider, StoredCredential>>>,
+    http: reqwest::Client,
+}
+
+impl ProviderCredentials {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            inner: Arc::new(RwLock::new(HashMap::new())),
+            http: reqwest::Client::builder()
+                .timeout(VALIDATION_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub async fn load(&self) -> Result<(), ProviderCredentialsError> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut stored: HashMap<ApiProvider, StoredCredential> = serde_json::from_slice(&bytes)?;
+        stored.retain(|provider, credential| {
+            if !secrets::is_encrypted(&credential.secret) {
+                return true;
+            }
+            match secrets::cipher().map(|cipher| cipher.decrypt(&credential.secret)) {
+                Some(Ok(secret)) => {
+                    credential.secret = secret;
+                    true
+                }
+                _ => {
+                    tracing::warn!("Discarding undecryptable {:?} credential", provider);
+                    false
+                }
+            }
+        });
+        *self.inner.write().await = stored;
+        Ok(())
+    }
+
+    pub async fn list(&self) -> Vec<ProviderCredentialInfo> {
+        let stored = self.inner.read().await;
+        ApiProvider::ALL
+            .into_iter()
+            .map(|provider| info(provider, stored.get(&provider)))
+            .collect()
+    }
+
+    pub async fn set(
+        &self,
+        provider: ApiProvider,
+        kind: ProviderCredentialKind,
+        secret: &str,
+    ) -> Result<ProviderCredentialInfo, ProviderCredentialsError> {
+        if provider.env_var(kind).is_none() {
+            return Err(ProviderCredentialsError::UnsupportedKind { provider, kind });
+        }
+        let secret = secret.trim();
+        if secret.is_empty() {
+            return Err(ProviderCredentialsError::Empty);
+        }
+
+        let mut stored = self.inner.write().await;
+        let mut updated = stored.clone();
+        updated.insert(
+            provider,
+            StoredCredential {
+                kind,
+                secret: secret.to_string(),
+                updated_at: Utc::now(),
+            },
+        );
+        self.save(&updated)?;
+        *stored = updated;
+        Ok(info(provider, stored.get(&provider)))
+    }
+
+    /// Returns whether a credential was stored.
+    pub async fn remove(&self, provider: ApiProvider) -> Result<bool, ProviderCredentialsError> {
+        let mut stored = self.inner.write().await;
+        if !stored.contains_key(&provider) {
+            return Ok(false);
+        }
+        let mut updated = stored.clone();
+        updated.remove(&provider);
+        self.save(&updated)?;
+        *stored = updated;
+        Ok(true)
+    }
+
+    /// Environment variables for executor processes.
+    pub async fn env_vars(&self) -> HashMap<String, String> {
+        self.inner
+            .read()
+            .await
+            .iter()
+            .filter_map(|(provider, credential)| {
+                let var = provider.env_var(credential.kind)?;
+                Some((var.to_string(), credential.secret.clone()))
+            })
+            .collect()
+    }
+
+    /// Check the stored credential with a cheap authenticated request to the
+    /// provider's model list.
+    pub async fn validate(
+        &self,
+        provider: ApiProvider,
+    ) -> Result<ProviderCredentialValidation, ProviderCredentialsError> {
+        let credential = self
+            .inner
+            .read()
+            .await
+            .get(&provider)
+            .cloned()
+            .ok_or(ProviderCredentialsError::NotConfigured(provider))?;
+
+        let request = match (provider, credential.kind) {
+            (ApiProvider::Anthropic, ProviderCredentialKind::ApiKey) => self
+                .http
+                .get("https://api.anthropic.com/v1/models")
+                .header("x-api-key", &credential.secret)
+                .header("anthropic-version", "2023-06-01"),
+            (ApiProvider::Anthropic, ProviderCredentialKind::OauthToken) => self
+                .http
+                .get("https://api.anthropic.com/v1/models")
+                .bearer_auth(&credential.secret)
+                .header("anthropic-version", "2023-06-01")
+                .header("anthropic-beta", "oauth-2025-04-20"),
+            (ApiProvider::Openai, _) => self
+                .http
+                .get("https://api.openai.com/v1/models")
+                .bearer_auth(&credential.secret),
+            (ApiProvider::Google, _) => self
+                .http
+                .get("https://generativelanguage.googleapis.com/v1beta/models")
+                .header("x-goog-api-key", &credential.secret),
+        };
+
+        let validation = match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                let message = match status.as_u16() {
+                    _ if status.is_success() => None,
+                    401 | 403 => Some("Credential was rejected by the provider".to_string()),
+                    _ => Some(format!("Provider answered with {status}")),
+                };
+                ProviderCredentialValidation {
+                    provider,
+                    valid: status.is_success(),
+                    status: Some(status.as_u16()),
+                    message,
+                }
+            }
+            Err(e) => ProviderCredentialValidation {
+                provider,
+                valid: false,
+                status: None,
+                message: Some(format!("Could not reach the provider: {e}")),
+            },
+        };
+        Ok(validation)
+    }
+
+    fn save(
+        &self,
+        credentials: &HashMap<ApiProvider, StoredCredential>,
+    ) -> Result<(), ProviderCredentialsError> {
+        let mut sealed = credentials.clone();
+        if let Some(cipher) = secrets::cipher() {
+            for credential in sealed.values_mut() {
+                credential.secret = cipher.encrypt(&credential.secret)?;
+            }
+        }
+
+        let tmp = self.path.with_extension("tmp");
+        let file = {
+            let mut opts = std::fs::OpenOptions::new();
+            opts.create(true).truncate(true).write(true);
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                opts.mode(0o600);
+            }
+
+            opts.open(&tmp)?
+        };
+        serde_json::to_writer_pretty(&file, &sealed)?;
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+fn info(provider: ApiProvider, credential: Option<&StoredCredential>) -> ProviderCredentialInfo {
+    ProviderCredentialInfo {
+        provider,
+        configured: credential.is_some(),
+        kind: credential.map(|credential| credential.kind),
+        masked: credential.map(|credential| mask(&credential.secret)),
+        env_var: credential
+            .and_then(|credential| provider.env_var(credential.kind))
+            .map(str::to_string),
+        updated_at: credential.map(|credential| credential.updated_at),
+    }
+}
+
+/// Show the last four characters of secrets long enough that doing so
+/// reveals little; shorter ones are fully hidden.
+fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < 16 {
+        return "••••".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("••••{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_all_but_the_tail_of_long_secrets() {
+        assert_eq!(mask("sk-ant-api03-abcdefgh1234"), "••••1234");
+        assert_eq!(mask("short"), "••••");
+    }
+
+    #[tokio::test]
+    async fn stores_credentials_and_exposes_them_as_env_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("provider_credentials.json");
+        let credentials = ProviderCredentials::new(path.clone());
+
+        let info = credentials
+            .set(
+                ApiProvider::Anthropic,
+                ProviderCredentialKind::ApiKey,
+                " sk-ant-api03-abcdefgh1234 ",
+            )
+            .await
+            .unwrap();
+        assert_eq!(info.masked.as_deref(), Some("••••1234"));
+        assert_eq!(info.env_var.as_deref(), Some("ANTHROPIC_API_KEY"));
+        assert!(matches!(
+            credentials
+                .set(
+                    ApiProvider::Openai,
+                    ProviderCredentialKind::OauthToken,
+                    "token"
+                )
+                .await,
+            Err(ProviderCredentialsError::UnsupportedKind { .. })
+        ));
+
+        let reloaded = ProviderCredentials::new(path);
+        reloaded.load().await.unwrap();
+        assert_eq!(
+            reloaded.env_vars().await.get("ANTHROPIC_API_KEY").unwrap(),
+            "sk-ant-api03-abcdefgh1234"
+        );
+        assert!(reloaded.remove(ApiProvider::Anthropic).await.unwrap());
+        assert!(!reloaded.remove(ApiProvider::Anthropic).await.unwrap());
+        assert!(reloaded.env_vars().await.is_empty());
+    }
+}
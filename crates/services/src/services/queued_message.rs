@@ -1,15 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use db::models::scratch::DraftFollowUpData;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use utils::msg_store::MsgStore;
 use uuid::Uuid;
 
+use crate::services::events::patches::queued_messages_patch;
+
 /// Represents a queued follow-up message for a session
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct QueuedMessage {
+    pub id: Uuid,
     /// The session this message is queued for
     pub session_id: Uuid,
     /// The follow-up data (message + variant)
@@ -24,54 +28,148 @@ pub struct QueuedMessage {
 pub enum QueueStatus {
     /// No message queued
     Empty,
-    /// Message is queued and waiting for execution to complete
+    /// Message is queued and waiting for execution to complete. This is the
+    /// next message to be sent; more may be queued behind it.
     Queued { message: QueuedMessage },
 }
 
 /// In-memory service for managing queued follow-up messages.
-/// One queued message per session.
+/// Messages are sent one per finished execution, in queue order. Every change
+/// to a session's queue is sent to event stream clients.
 #[derive(Clone)]
 pub struct QueuedMessageService {
-    queue: Arc<DashMap<Uuid, QueuedMessage>>,
+    queue: Arc<DashMap<Uuid, Vec<QueuedMessage>>>,
+    msg_store: Arc<MsgStore>,
 }
 
 impl QueuedMessageService {
-    pub fn new() -> Self {
+    pub fn new(msg_store: Arc<MsgStore>) -> Self {
         Self {
             queue: Arc::new(DashMap::new()),
+            msg_store,
         }
     }
 
-    /// Queue a message for a session. Replaces any existing queued message.
+    /// Queue a message for a session after any messages already queued.
     pub fn queue_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
         let queued = QueuedMessage {
+            id: Uuid::new_v4(),
             session_id,
             data,
             queued_at: Utc::now(),
         };
-        self.queue.insert(session_id, queued.clone());
+        let messages = {
+            let mut messages = self.queue.entry(session_id).or_default();
+            messages.push(queued.clone());
+            messages.clone()
+        };
+        self.publish(session_id, &messages);
         queued
     }
 
-    /// Cancel/remove a queued message for a session
-    pub fn cancel_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+    /// Cancel/remove all queued messages for a session
+    pub fn cancel_queued(&self, session_id: Uuid) -> Vec<QueuedMessage> {
+        let removed = self
+            .queue
+            .remove(&session_id)
+            .map(|(_, v)| v)
+            .unwrap_or_default();
+        if !removed.is_empty() {
+            self.publish(session_id, &[]);
+        }
+        removed
     }
 
-    /// Get the queued message for a session (if any)
+    /// Get the next queued message for a session (if any)
     pub fn get_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.get(&session_id).map(|r| r.clone())
+        self.queue
+            .get(&session_id)
+            .and_then(|messages| messages.first().cloned())
+    }
+
+    /// All queued messages for a session, in the order they will be sent.
+    pub fn list_queued(&self, session_id: Uuid) -> Vec<QueuedMessage> {
+        self.queue
+            .get(&session_id)
+            .map(|messages| messages.clone())
+            .unwrap_or_default()
     }
 
-    /// Take (remove and return) the queued message for a session.
+    /// Take (remove and return) the next queued message for a session.
     /// Used by finalization flow to consume the queued message.
     pub fn take_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+        let (taken, messages) = {
+            let mut messages = self.queue.get_mut(&session_id)?;
+            if messages.is_empty() {
+                return None;
+            }
+            (messages.remove(0), messages.clone())
+        };
+        self.queue
+            .remove_if(&session_id, |_, messages| messages.is_empty());
+        self.publish(session_id, &messages);
+        Some(taken)
+    }
+
+    /// Replace the contents of a queued message. Returns `None` if it is no
+    /// longer queued, e.g. because it was already sent.
+    pub fn update_queued(
+        &self,
+        session_id: Uuid,
+        message_id: Uuid,
+        data: DraftFollowUpData,
+    ) -> Option<QueuedMessage> {
+        let (updated, messages) = {
+            let mut messages = self.queue.get_mut(&session_id)?;
+            let message = messages.iter_mut().find(|m| m.id == message_id)?;
+            message.data = data;
+            (message.clone(), messages.clone())
+        };
+        self.publish(session_id, &messages);
+        Some(updated)
+    }
+
+    /// Remove a single queued message. Returns `None` if it is no longer
+    /// queued.
+    pub fn remove_queued(&self, session_id: Uuid, message_id: Uuid) -> Option<QueuedMessage> {
+        let (removed, messages) = {
+            let mut messages = self.queue.get_mut(&session_id)?;
+            let index = messages.iter().position(|m| m.id == message_id)?;
+            (messages.remove(index), messages.clone())
+        };
+        self.queue
+            .remove_if(&session_id, |_, messages| messages.is_empty());
+        self.publish(session_id, &messages);
+        Some(removed)
+    }
+
+    /// Put a session's queued messages in the given order. `message_ids` must
+    /// list every queued message exactly once, otherwise the queue is left
+    /// unchanged and `None` is returned.
+    pub fn reorder_queued(
+        &self,
+        session_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> Option<Vec<QueuedMessage>> {
+        let messages = {
+            let mut messages = self.queue.get_mut(&session_id)?;
+            let requested: HashSet<Uuid> = message_ids.iter().copied().collect();
+            let existing: HashSet<Uuid> = messages.iter().map(|m| m.id).collect();
+            if requested.len() != message_ids.len() || requested != existing {
+                return None;
+            }
+            messages.sort_by_key(|m| message_ids.iter().position(|id| *id == m.id));
+            messages.clone()
+        };
+        self.publish(session_id, &messages);
+        Some(messages)
     }
 
     /// Check if a session has a queued message
     pub fn has_queued(&self, session_id: Uuid) -> bool {
-        self.queue.contains_key(&session_id)
+        self.queue
+            .get(&session_id)
+            .is_some_and(|messages| !messages.is_empty())
     }
 
     /// Get queue status for frontend display
@@ -81,10 +179,13 @@ impl QueuedMessageService {
             None => QueueStatus::Empty,
         }
     }
-}
 
-impl Default for QueuedMessageService {
-    fn default() -> Self {
-        Self::new()
+    fn publish(&self, session_id: Uuid, messages: &[QueuedMessage]) {
+        let patch = if messages.is_empty() {
+            queued_messages_patch::remove(session_id)
+        } else {
+            queued_messages_patch::replace(session_id, messages)
+        };
+        self.msg_store.push_patch(patch);
     }
 }
@@ -3,20 +3,22 @@
 use std::time::Duration;
 
 use api_types::{
-    AcceptInvitationResponse, AuthMethodsResponse, CreateInvitationRequest,
-    CreateInvitationResponse, CreateIssueAssigneeRequest, CreateIssueRelationshipRequest,
-    CreateIssueRequest, CreateIssueTagRequest, CreateOrganizationRequest,
-    CreateOrganizationResponse, CreateWorkspaceRequest, DeleteResponse, DeleteWorkspaceRequest,
-    GetInvitationResponse, GetOrganizationResponse, HandoffInitRequest, HandoffInitResponse,
-    HandoffRedeemRequest, HandoffRedeemResponse, Issue, IssueAssignee, IssueRelationship, IssueTag,
+    AcceptInvitationResponse, AuthMethodsResponse, ClaimWorkspaceHandoffRequest,
+    CreateInvitationRequest, CreateInvitationResponse, CreateIssueAssigneeRequest,
+    CreateIssueRelationshipRequest, CreateIssueRequest, CreateIssueTagRequest,
+    CreateOrganizationRequest, CreateOrganizationResponse, CreateWorkspaceHandoffRequest,
+    CreateWorkspaceRequest, DeleteResponse, DeleteWorkspaceRequest, GetInvitationResponse,
+    GetOrganizationResponse, HandoffInitRequest, HandoffInitResponse, HandoffRedeemRequest,
+    HandoffRedeemResponse, Issue, IssueAssignee, IssueRelationship, IssueTag,
     ListAttachmentsResponse, ListInvitationsResponse, ListIssueAssigneesResponse,
     ListIssueRelationshipsResponse, ListIssueTagsResponse, ListIssuesResponse, ListMembersResponse,
     ListOrganizationsResponse, ListProjectStatusesResponse, ListProjectsResponse,
-    ListPullRequestsResponse, ListTagsResponse, LocalLoginRequest, LocalLoginResponse,
-    MutationResponse, Organization, ProfileResponse, PullRequest, RevokeInvitationRequest,
-    SearchIssuesRequest, Tag, TokenRefreshRequest, TokenRefreshResponse, UpdateIssueRequest,
-    UpdateMemberRoleRequest, UpdateMemberRoleResponse, UpdateOrganizationRequest,
-    UpdatePullRequestApiRequest, UpdateWorkspaceRequest, UpsertPullRequestRequest, Workspace,
+    ListPullRequestsResponse, ListTagsResponse, ListWorkspaceHandoffsResponse, LocalLoginRequest,
+    LocalLoginResponse, MutationResponse, Organization, ProfileResponse, PullRequest,
+    RevokeInvitationRequest, SearchIssuesRequest, Tag, TokenRefreshRequest, TokenRefreshResponse,
+    UpdateIssueRequest, UpdateMemberRoleRequest, UpdateMemberRoleResponse,
+    UpdateOrganizationRequest, UpdatePullRequestApiRequest, UpdateWorkspaceRequest,
+    UpsertPullRequestRequest, Workspace, WorkspaceHandoff,
 };
 use backon::{ExponentialBuilder, Retryable};
 use chrono::Duration as ChronoDuration;
@@ -751,6 +753,47 @@ impl RemoteClient {
         Ok(())
     }
 
+    // ── Workspace handoffs ──────────────────────────────────────────────
+
+    /// Publishes a workspace for another instance to pick up.
+    pub async fn create_workspace_handoff(
+        &self,
+        request: &CreateWorkspaceHandoffRequest,
+    ) -> Result<WorkspaceHandoff, RemoteClientError> {
+        self.post_authed("/v1/workspace-handoffs", Some(request))
+            .await
+    }
+
+    /// Lists handoffs that no instance has claimed yet.
+    pub async fn list_workspace_handoffs(
+        &self,
+    ) -> Result<ListWorkspaceHandoffsResponse, RemoteClientError> {
+        self.get_authed("/v1/workspace-handoffs").await
+    }
+
+    /// Claims a handoff for the given local workspace. Fails with HTTP 409 if
+    /// another instance claimed it first.
+    pub async fn claim_workspace_handoff(
+        &self,
+        handoff_id: Uuid,
+        local_workspace_id: Uuid,
+    ) -> Result<WorkspaceHandoff, RemoteClientError> {
+        self.post_authed(
+            &format!("/v1/workspace-handoffs/{handoff_id}/claim"),
+            Some(&ClaimWorkspaceHandoffRequest { local_workspace_id }),
+        )
+        .await
+    }
+
+    /// Withdraws a handoff that has not been claimed.
+    pub async fn cancel_workspace_handoff(
+        &self,
+        handoff_id: Uuid,
+    ) -> Result<(), RemoteClientError> {
+        self.delete_authed(&format!("/v1/workspace-handoffs/{handoff_id}"))
+            .await
+    }
+
     // ── Issues ──────────────────────────────────────────────────────────
 
     /// Lists issues for a project.
@@ -0,0 +1,362 @@
+//! Weekly and monthly productivity reports.
+//!
+//! Reports hold only counts and durations, never names, prompts or code.
+//! Totals are kept per period in `report_periods` and updated by folding in
+//! activity events past a cursor, so the history is read once.
+
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::Entry},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use db::{
+    DBService,
+    models::{
+        activity_event::{ActivityEvent, ActivityEventKind},
+        report_period::{ReportPeriod, ReportPeriodKind},
+    },
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command, sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+
+use super::config::Config;
+
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const EVENT_BATCH_SIZE: i64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("webhook delivery failed: {0}")]
+    Webhook(#[from] reqwest::Error),
+    #[error("email delivery failed: {0}")]
+    Email(String),
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProductivityReport {
+    pub period: ReportPeriodKind,
+    pub period_start: NaiveDate,
+    /// First day after the period.
+    pub period_end: NaiveDate,
+    /// Workspaces merged for the first time, directly or through a PR.
+    pub tasks_completed: i64,
+    /// Median seconds from workspace creation to its first merge.
+    pub median_time_to_merge_seconds: Option<i64>,
+    pub approvals_approved: i64,
+    pub approvals_denied: i64,
+    /// Coding agent executions per executor.
+    pub executor_mix: BTreeMap<String, i64>,
+}
+
+impl From<&ReportPeriod> for ProductivityReport {
+    fn from(period: &ReportPeriod) -> Self {
+        Self {
+            period: period.period,
+            period_start: period.period_start,
+            period_end: next_period_start(period.period, period.period_start),
+            tasks_completed: period.tasks_completed,
+            median_time_to_merge_seconds: median(&period.merge_durations),
+            approvals_approved: period.approvals_approved,
+            approvals_denied: period.approvals_denied,
+            executor_mix: period.executor_counts.0.clone(),
+        }
+    }
+}
+
+pub fn period_start(kind: ReportPeriodKind, date: NaiveDate) -> NaiveDate {
+    match kind {
+        ReportPeriodKind::Week => {
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+        ReportPeriodKind::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+fn next_period_start(kind: ReportPeriodKind, start: NaiveDate) -> NaiveDate {
+    match kind {
+        ReportPeriodKind::Week => start + chrono::Duration::days(7),
+        ReportPeriodKind::Month => start.checked_add_months(Months::new(1)).unwrap_or(start),
+    }
+}
+
+fn previous_period_start(kind: ReportPeriodKind, start: NaiveDate) -> NaiveDate {
+    match kind {
+        ReportPeriodKind::Week => start - chrono::Duration::days(7),
+        ReportPeriodKind::Month => start.checked_sub_months(Months::new(1)).unwrap_or(start),
+    }
+}
+
+fn median(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+fn apply_event(period: &mut ReportPeriod, event: &ActivityEvent) {
+    match event.kind {
+        ActivityEventKind::WorkspaceMerged => {
+            period.tasks_completed += 1;
+            if let Some(duration) = event.duration_seconds {
+                period.merge_durations.push(duration);
+            }
+        }
+        ActivityEventKind::ApprovalApproved => period.approvals_approved += 1,
+        ActivityEventKind::ApprovalDenied => period.approvals_denied += 1,
+        ActivityEventKind::CodingAgentStarted => {
+            if let Some(executor) = &event.executor {
+                *period.executor_counts.entry(executor.clone()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Fold activity events recorded since the last refresh into the period
+/// totals.
+pub async fn refresh(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut cursor = ReportPeriod::cursor(pool).await?;
+    loop {
+        let events = ActivityEvent::find_after(pool, cursor, EVENT_BATCH_SIZE).await?;
+        let Some(last_event_id) = events.last().map(|event| event.id) else {
+            return Ok(());
+        };
+
+        let mut periods: HashMap<(ReportPeriodKind, NaiveDate), ReportPeriod> = HashMap::new();
+        for event in &events {
+            for kind in [ReportPeriodKind::Week, ReportPeriodKind::Month] {
+                let start = period_start(kind, event.occurred_at.date_naive());
+                let period = match periods.entry((kind, start)) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(
+                        ReportPeriod::find(pool, kind, start)
+                            .await?
+                            .unwrap_or_else(|| ReportPeriod::empty(kind, start)),
+                    ),
+                };
+                apply_event(period, event);
+            }
+        }
+
+        let periods: Vec<ReportPeriod> = periods.into_values().collect();
+        if !ReportPeriod::save_batch(pool, &periods, cursor, last_event_id).await? {
+            // Another refresh got there first; continue from where it stopped.
+            cursor = ReportPeriod::cursor(pool).await?;
+            continue;
+        }
+        cursor = last_event_id;
+        if (events.len() as i64) < EVENT_BATCH_SIZE {
+            return Ok(());
+        }
+    }
+}
+
+/// The most recent reports of a kind, newest first.
+pub async fn recent_reports(
+    pool: &SqlitePool,
+    kind: ReportPeriodKind,
+    limit: i64,
+) -> Result<Vec<ProductivityReport>, sqlx::Error> {
+    refresh(pool).await?;
+    Ok(ReportPeriod::find_recent(pool, kind, limit)
+        .await?
+        .iter()
+        .map(ProductivityReport::from)
+        .collect())
+}
+
+pub fn to_csv(reports: &[ProductivityReport]) -> String {
+    let mut csv = String::from(
+        "period,period_start,period_end,tasks_completed,median_time_to_merge_seconds,\
+         approvals_approved,approvals_denied,executor_mix\n",
+    );
+    for report in reports {
+        let period = match report.period {
+            ReportPeriodKind::Week => "week",
+            ReportPeriodKind::Month => "month",
+        };
+        let median = report
+            .median_time_to_merge_seconds
+            .map(|seconds| seconds.to_string())
+            .unwrap_or_default();
+        let executor_mix = report
+            .executor_mix
+            .iter()
+            .map(|(executor, count)| format!("{executor}={count}"))
+            .collect::<Vec<_>>()
+            .join(";");
+        csv.push_str(&format!(
+            "{period},{},{},{},{median},{},{},{executor_mix}\n",
+            report.period_start,
+            report.period_end,
+            report.tasks_completed,
+            report.approvals_approved,
+            report.approvals_denied,
+        ));
+    }
+    csv
+}
+
+#[derive(Clone)]
+pub struct ReportService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    client: reqwest::Client,
+}
+
+impl ReportService {
+    pub fn new(db: DBService, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            db,
+            config,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Spawn a background loop that sends each finished period's report to
+    /// the configured webhook and email address once.
+    pub fn spawn_delivery_loop(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self::new(db, config);
+        tokio::spawn(async move {
+            info!("Starting productivity report delivery loop");
+            let mut interval = interval(DELIVERY_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.deliver_due().await {
+                    error!("Error delivering productivity report: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Deliver the report of the last finished week and month, if enabled and
+    /// not delivered yet. Older periods are not sent retroactively.
+    pub async fn deliver_due(&self) -> Result<(), ReportError> {
+        let delivery = self.config.read().await.report_delivery.clone();
+        if delivery.webhook_url.is_none() && delivery.email_to.is_none() {
+            return Ok(());
+        }
+
+        let pool = &self.db.pool;
+        refresh(pool).await?;
+
+        let today = Utc::now().date_naive();
+        for (kind, enabled) in [
+            (ReportPeriodKind::Week, delivery.weekly),
+            (ReportPeriodKind::Month, delivery.monthly),
+        ] {
+            if !enabled {
+                continue;
+            }
+            let start = previous_period_start(kind, period_start(kind, today));
+            let period = ReportPeriod::find(pool, kind, start)
+                .await?
+                .unwrap_or_else(|| ReportPeriod::empty(kind, start));
+            if period.delivered_at.is_some() {
+                continue;
+            }
+
+            let report = ProductivityReport::from(&period);
+            if let Some(url) = &delivery.webhook_url {
+                self.client
+                    .post(url)
+                    .json(&report)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            if let Some(to) = &delivery.email_to {
+                send_email(to, &report).await?;
+            }
+            ReportPeriod::mark_delivered(pool, kind, start).await?;
+            info!("Delivered {:?} productivity report for {}", kind, start);
+        }
+        Ok(())
+    }
+}
+
+async fn send_email(to: &str, report: &ProductivityReport) -> Result<(), ReportError> {
+    if to.contains(['\r', '\n']) {
+        return Err(ReportError::Email("invalid recipient address".to_string()));
+    }
+    let message = format!(
+        "To: {to}\nSubject: Vibe Kanban {:?} report from {}\nContent-Type: text/csv; charset=utf-8\n\n{}",
+        report.period,
+        report.period_start,
+        to_csv(std::slice::from_ref(report)),
+    );
+
+    let mut child = Command::new("sendmail")
+        .arg("-t")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| ReportError::Email(format!("could not run sendmail: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(message.as_bytes())
+            .await
+            .map_err(|e| ReportError::Email(e.to_string()))?;
+    }
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| ReportError::Email(e.to_string()))?;
+    if !status.success() {
+        return Err(ReportError::Email(format!("sendmail exited with {status}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn periods_start_on_monday_and_first_of_month() {
+        // 2026-03-19 is a Thursday
+        assert_eq!(
+            period_start(ReportPeriodKind::Week, date(2026, 3, 19)),
+            date(2026, 3, 16)
+        );
+        assert_eq!(
+            period_start(ReportPeriodKind::Month, date(2026, 3, 19)),
+            date(2026, 3, 1)
+        );
+        assert_eq!(
+            previous_period_start(ReportPeriodKind::Month, date(2026, 1, 1)),
+            date(2025, 12, 1)
+        );
+    }
+
+    #[test]
+    fn median_handles_even_and_odd_counts() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[30, 10, 20]), Some(20));
+        assert_eq!(median(&[40, 10, 20, 30]), Some(25));
+    }
+}
@@ -0,0 +1,242 @@
+//! Replay timeline of a session: its execution processes with durations and
+//! commits, and the tool calls, approvals and messages from their logs.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use db::models::execution_process::{ExecutionProcessRunReason, ExecutionProcessStatus};
+use executors::logs::{
+    ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus,
+    utils::patch::extract_normalized_entry_from_patch,
+};
+use json_patch::Patch;
+use serde::Serialize;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SessionTimeline {
+    pub session_id: Uuid,
+    pub started_at: Option<DateTime<Utc>>,
+    /// `None` while a process is still running.
+    pub completed_at: Option<DateTime<Utc>>,
+    pub duration_ms: i64,
+    pub processes: Vec<TimelineProcess>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TimelineProcess {
+    pub execution_process_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub executor: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Until completion, or until now while running.
+    pub duration_ms: i64,
+    /// Repos whose HEAD moved during the process.
+    pub commits: Vec<TimelineCommit>,
+    pub events: Vec<TimelineEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TimelineCommit {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub before_sha: Option<String>,
+    pub after_sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TimelineEvent {
+    /// Position of the entry in the process's conversation.
+    pub entry_index: usize,
+    pub timestamp: Option<String>,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub kind: TimelineEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    UserMessage {
+        content: String,
+    },
+    AssistantMessage {
+        content: String,
+    },
+    ToolCall {
+        tool_name: String,
+        action_type: ActionType,
+        status: ToolStatus,
+        content: String,
+        approval: Option<TimelineApproval>,
+    },
+    Error {
+        content: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum TimelineApproval {
+    Pending,
+    Approved,
+    Denied { reason: Option<String> },
+    TimedOut,
+}
+
+pub fn duration_ms(started_at: DateTime<Utc>, completed_at: Option<DateTime<Utc>>) -> i64 {
+    (completed_at.unwrap_or_else(Utc::now) - started_at)
+        .num_milliseconds()
+        .max(0)
+}
+
+/// Fold a process's normalized log patches into timeline events. Patches are
+/// replayed in order so a tool call that waited for approval is recognised
+/// even after its status moved on.
+pub fn events_from_patches(patches: &[Patch]) -> Vec<TimelineEvent> {
+    let mut entries: BTreeMap<usize, (NormalizedEntry, bool)> = BTreeMap::new();
+    for patch in patches {
+        let Some((index, entry)) = extract_normalized_entry_from_patch(patch) else {
+            continue;
+        };
+        let was_pending = entries.get(&index).is_some_and(|(_, pending)| *pending);
+        let pending = matches!(
+            &entry.entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::PendingApproval { .. },
+                ..
+            }
+        );
+        entries.insert(index, (entry, was_pending || pending));
+    }
+
+    entries
+        .into_iter()
+        .filter_map(|(entry_index, (entry, asked_approval))| {
+            let kind = match entry.entry_type {
+                NormalizedEntryType::UserMessage | NormalizedEntryType::UserFeedback { .. } => {
+                    TimelineEventKind::UserMessage {
+                        content: entry.content,
+                    }
+                }
+                NormalizedEntryType::AssistantMessage => TimelineEventKind::AssistantMessage {
+                    content: entry.content,
+                },
+                NormalizedEntryType::ToolUse {
+                    tool_name,
+                    action_type,
+                    status,
+                } => {
+                    let approval = match &status {
+                        ToolStatus::PendingApproval { .. } => Some(TimelineApproval::Pending),
+                        ToolStatus::Denied { reason } => Some(TimelineApproval::Denied {
+                            reason: reason.clone(),
+                        }),
+                        ToolStatus::TimedOut => Some(TimelineApproval::TimedOut),
+                        _ if asked_approval => Some(TimelineApproval::Approved),
+                        _ => None,
+                    };
+                    TimelineEventKind::ToolCall {
+                        tool_name,
+                        action_type,
+                        status,
+                        content: entry.content,
+                        approval,
+                    }
+                }
+                NormalizedEntryType::ErrorMessage { .. } => TimelineEventKind::Error {
+                    content: entry.content,
+                },
+                _ => return None,
+            };
+            Some(TimelineEvent {
+                entry_index,
+                timestamp: entry.timestamp,
+                kind,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::utils::patch::ConversationPatch;
+
+    use super::*;
+
+    fn tool_use(status: ToolStatus) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::Other {
+                    description: "rm -rf target".to_string(),
+                },
+                status,
+            },
+            content: "rm -rf target".to_string(),
+            metadata: None,
+        }
+    }
+
+    fn message(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn approved_tool_call_keeps_its_approval() {
+        let patches = vec![
+            ConversationPatch::add_normalized_entry(
+                0,
+                message(NormalizedEntryType::UserMessage, "Clean the build"),
+            ),
+            ConversationPatch::add_normalized_entry(
+                1,
+                tool_use(ToolStatus::PendingApproval {
+                    approval_id: "a1".to_string(),
+                }),
+            ),
+            ConversationPatch::replace(1, tool_use(ToolStatus::Success)),
+            ConversationPatch::add_normalized_entry(2, message(NormalizedEntryType::Thinking, "…")),
+        ];
+
+        let events = events_from_patches(&patches);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[1].kind,
+            TimelineEventKind::ToolCall {
+                approval: Some(TimelineApproval::Approved),
+                status: ToolStatus::Success,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn denied_tool_call_reports_reason() {
+        let patches = vec![ConversationPatch::add_normalized_entry(
+            0,
+            tool_use(ToolStatus::Denied {
+                reason: Some("not now".to_string()),
+            }),
+        )];
+
+        let events = events_from_patches(&patches);
+        match &events[0].kind {
+            TimelineEventKind::ToolCall {
+                approval: Some(TimelineApproval::Denied { reason }),
+                ..
+            } => assert_eq!(reason.as_deref(), Some("not now")),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
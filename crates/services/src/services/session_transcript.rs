@@ -0,0 +1,316 @@
+//! Shareable transcript of a session's coding agent runs, rendered from its
+//! replay timeline as Markdown or a standalone HTML page.
+
+use db::models::execution_process::{ExecutionProcessRunReason, ExecutionProcessStatus};
+use executors::logs::{ActionType, FileChange};
+
+use super::session_timeline::{
+    SessionTimeline, TimelineApproval, TimelineEventKind, TimelineProcess,
+};
+
+/// A piece of the transcript, before it is rendered to a format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    /// Message text written by a person or an agent, usually Markdown.
+    Message(String),
+    Code {
+        language: &'static str,
+        text: String,
+    },
+}
+
+fn status_label(status: &ExecutionProcessStatus) -> &'static str {
+    match status {
+        ExecutionProcessStatus::Running => "running",
+        ExecutionProcessStatus::Completed => "completed",
+        ExecutionProcessStatus::Failed => "failed",
+        ExecutionProcessStatus::Killed => "killed",
+        ExecutionProcessStatus::TimedOut => "timed out",
+        ExecutionProcessStatus::Interrupted => "interrupted",
+    }
+}
+
+fn approval_label(approval: &TimelineApproval) -> String {
+    match approval {
+        TimelineApproval::Pending => "Waiting for approval".to_string(),
+        TimelineApproval::Approved => "Approved".to_string(),
+        TimelineApproval::Denied {
+            reason: Some(reason),
+        } => format!("Denied: {reason}"),
+        TimelineApproval::Denied { reason: None } => "Denied".to_string(),
+        TimelineApproval::TimedOut => "Approval timed out".to_string(),
+    }
+}
+
+fn tool_call_blocks(
+    tool_name: &str,
+    action_type: &ActionType,
+    content: &str,
+    blocks: &mut Vec<Block>,
+) {
+    match action_type {
+        ActionType::CommandRun { command, .. } => {
+            blocks.push(Block::Paragraph(format!("{tool_name}: ran a command")));
+            blocks.push(Block::Code {
+                language: "sh",
+                text: command.clone(),
+            });
+        }
+        ActionType::FileEdit { path, changes } => {
+            blocks.push(Block::Paragraph(format!("{tool_name}: edited {path}")));
+            for change in changes {
+                match change {
+                    FileChange::Edit { unified_diff, .. } => blocks.push(Block::Code {
+                        language: "diff",
+                        text: unified_diff.clone(),
+                    }),
+                    FileChange::Write { content } => blocks.push(Block::Code {
+                        language: "",
+                        text: content.clone(),
+                    }),
+                    FileChange::Delete => blocks.push(Block::Paragraph("Deleted".to_string())),
+                    FileChange::Rename { new_path } => {
+                        blocks.push(Block::Paragraph(format!("Renamed to {new_path}")))
+                    }
+                }
+            }
+        }
+        ActionType::PlanPresentation { plan } => {
+            blocks.push(Block::Paragraph(format!("{tool_name}: proposed a plan")));
+            blocks.push(Block::Message(plan.clone()));
+        }
+        _ => blocks.push(Block::Paragraph(format!("{tool_name}: {content}"))),
+    }
+}
+
+fn process_blocks(process: &TimelineProcess, blocks: &mut Vec<Block>) {
+    let executor = process.executor.as_deref().unwrap_or("Agent");
+    blocks.push(Block::Heading(
+        2,
+        format!(
+            "{executor} run, {} ({})",
+            process.started_at.format("%Y-%m-%d %H:%M UTC"),
+            status_label(&process.status)
+        ),
+    ));
+
+    for event in &process.events {
+        match &event.kind {
+            TimelineEventKind::UserMessage { content } => {
+                blocks.push(Block::Heading(3, "Prompt".to_string()));
+                blocks.push(Block::Message(content.clone()));
+            }
+            TimelineEventKind::AssistantMessage { content } => {
+                blocks.push(Block::Heading(3, "Agent".to_string()));
+                blocks.push(Block::Message(content.clone()));
+            }
+            TimelineEventKind::ToolCall {
+                tool_name,
+                action_type,
+                content,
+                approval,
+                ..
+            } => {
+                tool_call_blocks(tool_name, action_type, content, blocks);
+                if let Some(approval) = approval {
+                    blocks.push(Block::Paragraph(approval_label(approval)));
+                }
+            }
+            TimelineEventKind::Error { content } => {
+                blocks.push(Block::Paragraph(format!("Error: {content}")));
+            }
+        }
+    }
+
+    for commit in &process.commits {
+        if let Some(after_sha) = &commit.after_sha {
+            let short = &after_sha[..after_sha.len().min(7)];
+            blocks.push(Block::Paragraph(format!(
+                "Committed to {} ({short})",
+                commit.repo_name
+            )));
+        }
+    }
+}
+
+/// Coding agent runs only; script output isn't part of the conversation.
+fn transcript_blocks(title: &str, timeline: &SessionTimeline) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(1, title.to_string())];
+    for process in &timeline.processes {
+        if process.run_reason == ExecutionProcessRunReason::CodingAgent {
+            process_blocks(process, &mut blocks);
+        }
+    }
+    blocks
+}
+
+/// A fence longer than any backtick run in `text`, so code can't close it.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+pub fn to_markdown(title: &str, timeline: &SessionTimeline) -> String {
+    let mut out = String::new();
+    for block in transcript_blocks(title, timeline) {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("{} {text}\n\n", "#".repeat(level.into())))
+            }
+            Block::Paragraph(text) => out.push_str(&format!("*{}*\n\n", text.trim())),
+            Block::Message(text) => out.push_str(&format!("{}\n\n", text.trim())),
+            Block::Code { language, text } => {
+                let fence = code_fence(&text);
+                out.push_str(&format!(
+                    "{fence}{language}\n{}\n{fence}\n\n",
+                    text.trim_end()
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:50rem;margin:2rem auto;\
+padding:0 1rem;line-height:1.5}pre{background:#f4f4f4;padding:.75rem;overflow-x:auto}\
+.message{white-space:pre-wrap}.note{color:#555;font-style:italic}";
+
+/// A standalone page. Messages are shown as written, not rendered as
+/// Markdown.
+pub fn to_html(title: &str, timeline: &SessionTimeline) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{HTML_STYLE}</style>\n</head>\n<body>\n",
+        escape_html(title)
+    );
+    for block in transcript_blocks(title, timeline) {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("<h{level}>{}</h{level}>\n", escape_html(&text)))
+            }
+            Block::Paragraph(text) => {
+                out.push_str(&format!("<p class=\"note\">{}</p>\n", escape_html(&text)))
+            }
+            Block::Message(text) => out.push_str(&format!(
+                "<div class=\"message\">{}</div>\n",
+                escape_html(text.trim())
+            )),
+            Block::Code { text, .. } => {
+                out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&text)))
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use executors::logs::ToolStatus;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::services::session_timeline::TimelineEvent;
+
+    fn timeline(events: Vec<TimelineEventKind>) -> SessionTimeline {
+        let started_at = Utc::now();
+        SessionTimeline {
+            session_id: Uuid::new_v4(),
+            started_at: Some(started_at),
+            completed_at: Some(started_at),
+            duration_ms: 0,
+            processes: vec![TimelineProcess {
+                execution_process_id: Uuid::new_v4(),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+                status: ExecutionProcessStatus::Completed,
+                exit_code: Some(0),
+                executor: Some("CLAUDE_CODE".to_string()),
+                started_at,
+                completed_at: Some(started_at),
+                duration_ms: 0,
+                commits: Vec::new(),
+                events: events
+                    .into_iter()
+                    .enumerate()
+                    .map(|(entry_index, kind)| TimelineEvent {
+                        entry_index,
+                        timestamp: None,
+                        kind,
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    fn edit(diff: &str) -> TimelineEventKind {
+        TimelineEventKind::ToolCall {
+            tool_name: "Edit".to_string(),
+            action_type: ActionType::FileEdit {
+                path: "src/main.rs".to_string(),
+                changes: vec![FileChange::Edit {
+                    unified_diff: diff.to_string(),
+                    has_line_numbers: true,
+                }],
+            },
+            status: ToolStatus::Success,
+            content: "src/main.rs".to_string(),
+            approval: Some(TimelineApproval::Denied {
+                reason: Some("wrong file".to_string()),
+            }),
+        }
+    }
+
+    #[test]
+    fn markdown_includes_prompt_diff_and_approval() {
+        let markdown = to_markdown(
+            "Fix the build",
+            &timeline(vec![
+                TimelineEventKind::UserMessage {
+                    content: "Fix the build".to_string(),
+                },
+                edit("-a\n+b"),
+            ]),
+        );
+        assert!(markdown.starts_with("# Fix the build\n\n## CLAUDE_CODE run"));
+        assert!(markdown.contains("### Prompt\n\nFix the build\n\n"));
+        assert!(markdown.contains("```diff\n-a\n+b\n```\n"));
+        assert!(markdown.contains("*Denied: wrong file*"));
+    }
+
+    #[test]
+    fn code_fence_outlasts_backticks_in_code() {
+        let markdown = to_markdown("t", &timeline(vec![edit("+```rust")]));
+        assert!(markdown.contains("````diff\n+```rust\n````\n"));
+    }
+
+    #[test]
+    fn html_escapes_agent_output() {
+        let html = to_html(
+            "<script>",
+            &timeline(vec![TimelineEventKind::AssistantMessage {
+                content: "<b>done</b> & dusted".to_string(),
+            }]),
+        );
+        assert!(html.contains("<title>&lt;script&gt;</title>"));
+        assert!(html.contains("&lt;b&gt;done&lt;/b&gt; &amp; dusted"));
+        assert!(!html.contains("<b>"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Cache of directories warmed up by repo setup scripts (`node_modules`,
+//! `target`, ...). After a setup script succeeds in a worktree the configured
+//! directories are copied into the cache, keyed by a hash of the setup script
+//! and the repo's lockfiles; new worktrees with the same key start from a copy
+//! so the setup script only has to catch up.
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+use db::models::repo_setup_cache::RepoSetupCache;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Files hashed into the cache key when a repo doesn't configure its own.
+pub const DEFAULT_KEY_FILES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lock",
+    "bun.lockb",
+    "Cargo.lock",
+    "go.sum",
+    "uv.lock",
+    "poetry.lock",
+    "Gemfile.lock",
+];
+
+/// Cached snapshots kept per repo; the least recently used are dropped first.
+const MAX_ENTRIES_PER_REPO: usize = 3;
+const LAST_USED_MARKER: &str = ".last_used";
+
+#[derive(Debug, Error)]
+pub enum SetupCacheError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Setup cache task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+#[derive(Clone)]
+pub struct SetupCacheService {
+    root: PathBuf,
+}
+
+impl Default for SetupCacheService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SetupCacheService {
+    pub fn new() -> Self {
+        Self {
+            root: utils::cache_dir().join("setup-cache"),
+        }
+    }
+
+    fn repo_dir(&self, repo_id: Uuid) -> PathBuf {
+        self.root.join(repo_id.to_string())
+    }
+
+    /// Copy the cached directories matching the worktree's key into it.
+    /// Directories that already exist in the worktree are left alone.
+    /// Returns whether there was a matching cache entry.
+    pub async fn restore(
+        &self,
+        repo_id: Uuid,
+        worktree: &Path,
+        setup_script: &str,
+        config: &RepoSetupCache,
+    ) -> Result<bool, SetupCacheError> {
+        let repo_dir = self.repo_dir(repo_id);
+        let worktree = worktree.to_path_buf();
+        let setup_script = setup_script.to_string();
+        let config = config.clone();
+        let restored = tokio::task::spawn_blocking(move || -> io::Result<bool> {
+            let key = cache_key(&worktree, &setup_script, &config)?;
+            let entry = repo_dir.join(&key);
+            if !entry.is_dir() {
+                return Ok(false);
+            }
+            for path in valid_paths(&config) {
+                let source = entry.join(&path);
+                let target = worktree.join(&path);
+                if source.exists() && !target.exists() {
+                    copy_tree(&source, &target)?;
+                }
+            }
+            fs::write(entry.join(LAST_USED_MARKER), b"")?;
+            Ok(true)
+        })
+        .await??;
+        Ok(restored)
+    }
+
+    /// Store the worktree's configured directories under its key, unless an
+    /// entry for the key already exists. Returns whether anything was stored.
+    pub async fn save(
+        &self,
+        repo_id: Uuid,
+        worktree: &Path,
+        setup_script: &str,
+        config: &RepoSetupCache,
+    ) -> Result<bool, SetupCacheError> {
+        let repo_dir = self.repo_dir(repo_id);
+        let worktree = worktree.to_path_buf();
+        let setup_script = setup_script.to_string();
+        let config = config.clone();
+        let saved = tokio::task::spawn_blocking(move || -> io::Result<bool> {
+            let key = cache_key(&worktree, &setup_script, &config)?;
+            let entry = repo_dir.join(&key);
+            if entry.is_dir() {
+                return Ok(false);
+            }
+
+            // Build the entry next to its final location and move it into
+            // place, so a restore never sees a half-written snapshot.
+            let staging = repo_dir.join(format!(".{key}-{}", Uuid::new_v4()));
+            fs::create_dir_all(&staging)?;
+            let mut copied = false;
+            for path in valid_paths(&config) {
+                let source = worktree.join(&path);
+                if source.exists() {
+                    copy_tree(&source, &staging.join(&path))?;
+                    copied = true;
+                }
+            }
+            if !copied {
+                fs::remove_dir_all(&staging)?;
+                return Ok(false);
+            }
+            fs::write(staging.join(LAST_USED_MARKER), b"")?;
+            if fs::rename(&staging, &entry).is_err() {
+                // Another worktree stored the same key first.
+                fs::remove_dir_all(&staging)?;
+                return Ok(false);
+            }
+            evict(&repo_dir)?;
+            Ok(true)
+        })
+        .await??;
+        Ok(saved)
+    }
+
+    /// Drop every cached snapshot of a repo.
+    pub async fn clear(&self, repo_id: Uuid) -> Result<(), SetupCacheError> {
+        match tokio::fs::remove_dir_all(self.repo_dir(repo_id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Whether `path` may be cached: relative and staying inside the repo.
+pub fn is_valid_cache_path(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn valid_paths(config: &RepoSetupCache) -> Vec<String> {
+    config
+        .paths()
+        .into_iter()
+        .filter(|path| {
+            let valid = is_valid_cache_path(path);
+            if !valid {
+                tracing::warn!("Ignoring setup cache path outside the repo: {}", path);
+            }
+            valid
+        })
+        .collect()
+}
+
+/// Hash of the setup script and the contents of the key files. A missing key
+/// file hashes differently from an empty one.
+fn cache_key(worktree: &Path, setup_script: &str, config: &RepoSetupCache) -> io::Result<String> {
+    let key_files = config
+        .key_files()
+        .unwrap_or_else(|| DEFAULT_KEY_FILES.iter().map(|f| f.to_string()).collect());
+    let mut hasher = Sha256::new();
+    hasher.update(setup_script.as_bytes());
+    for file in &key_files {
+        hasher.update(b"\0");
+        hasher.update(file.as_bytes());
+        match fs::read(worktree.join(file)) {
+            Ok(contents) => {
+                hasher.update(b"\x01");
+                hasher.update(&contents);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => hasher.update(b"\x02"),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Copy a file or directory tree, keeping symlinks as links; package
+/// managers such as pnpm rely on them.
+fn copy_tree(source: &Path, target: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if metadata.file_type().is_symlink() {
+        copy_symlink(source, target)
+    } else if metadata.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, target).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    }
+}
+
+/// Remove the least recently used snapshots beyond the per-repo limit.
+fn evict(repo_dir: &Path) -> io::Result<()> {
+    let mut entries: Vec<(SystemTime, PathBuf)> = fs::read_dir(repo_dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| {
+            let path = entry.path();
+            let last_used = fs::metadata(path.join(LAST_USED_MARKER))
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (last_used, path)
+        })
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, path) in entries.into_iter().skip(MAX_ENTRIES_PER_REPO) {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn config() -> RepoSetupCache {
+        RepoSetupCache {
+            repo_id: Uuid::new_v4(),
+            cache_paths: "node_modules, target".to_string(),
+            key_files: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn worktree(root: &Path, name: &str, lockfile: &str) -> PathBuf {
+        let worktree = root.join(name);
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(worktree.join("pnpm-lock.yaml"), lockfile).unwrap();
+        worktree
+    }
+
+    #[tokio::test]
+    async fn restores_what_a_matching_worktree_saved() {
+        let temp = tempfile::tempdir().unwrap();
+        let service = SetupCacheService {
+            root: temp.path().join("cache"),
+        };
+        let config = config();
+        let repo_id = config.repo_id;
+
+        let first = worktree(temp.path(), "first", "lock v1");
+        fs::create_dir_all(first.join("node_modules/left-pad")).unwrap();
+        fs::write(first.join("node_modules/left-pad/index.js"), "pad").unwrap();
+        assert!(
+            service
+                .save(repo_id, &first, "pnpm install", &config)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !service
+                .save(repo_id, &first, "pnpm install", &config)
+                .await
+                .unwrap()
+        );
+
+        let second = worktree(temp.path(), "second", "lock v1");
+        assert!(
+            service
+                .restore(repo_id, &second, "pnpm install", &config)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            fs::read_to_string(second.join("node_modules/left-pad/index.js")).unwrap(),
+            "pad"
+        );
+
+        let changed = worktree(temp.path(), "changed", "lock v2");
+        assert!(
+            !service
+                .restore(repo_id, &changed, "pnpm install", &config)
+                .await
+                .unwrap()
+        );
+        assert!(!changed.join("node_modules").exists());
+    }
+
+    #[test]
+    fn cache_paths_must_stay_inside_the_repo() {
+        assert!(is_valid_cache_path("node_modules"));
+        assert!(is_valid_cache_path("./packages/web/node_modules"));
+        assert!(!is_valid_cache_path("../shared"));
+        assert!(!is_valid_cache_path("/tmp/target"));
+        assert!(!is_valid_cache_path(""));
+    }
+}
@@ -0,0 +1,311 @@
+//! Shares filesystem watchers between subscribers.
+//!
+//! Every diff stream used to start its own watcher, so a worktree open in
+//! several tabs (or streamed both in full and as stats) held several copies
+//! of the same inotify watches. The hub keeps one watcher per root and fans
+//! its events out to subscribers, each filtered to the subtree it asked for.
+//! A subscription inside an already-watched root reuses that root's watcher.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, OnceLock, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::{
+    StreamExt,
+    channel::mpsc::{Receiver, Sender, channel},
+};
+use notify_debouncer_full::{DebounceEventResult, DebouncedEvent};
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::filesystem_watcher::{self, FilesystemWatcherError, WatcherBackend};
+
+/// Per-subscriber buffer. A subscriber that falls this far behind loses
+/// event batches; diff streams recover through their periodic reconcile.
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 64;
+
+static WATCHER_HUB: OnceLock<WatcherHub> = OnceLock::new();
+
+/// The process-wide watcher hub.
+pub fn hub() -> &'static WatcherHub {
+    WATCHER_HUB.get_or_init(WatcherHub::default)
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WatcherHubStats {
+    pub watchers: usize,
+    pub polling_watchers: usize,
+    pub subscribers: usize,
+    /// Event batches dropped because a subscriber's buffer was full.
+    pub dropped_event_batches: u64,
+}
+
+#[derive(Default)]
+pub struct WatcherHub {
+    watchers: Mutex<HashMap<PathBuf, Arc<SharedWatcher>>>,
+    dropped_event_batches: Arc<AtomicU64>,
+}
+
+struct Subscriber {
+    id: u64,
+    scope: PathBuf,
+    tx: Sender<DebounceEventResult>,
+}
+
+struct SharedWatcher {
+    root: PathBuf,
+    backend: Arc<Mutex<WatcherBackend>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    next_id: AtomicU64,
+}
+
+/// A live subscription. Dropping the last subscription for a root stops its
+/// watcher.
+pub struct WatchSubscription {
+    pub rx: Receiver<DebounceEventResult>,
+    /// Canonical path events are scoped to.
+    pub scope: PathBuf,
+    hub: &'static WatcherHub,
+    watcher: Arc<SharedWatcher>,
+    id: u64,
+}
+
+impl WatcherHub {
+    /// Subscribe to changes under `path`, reusing a watcher on `path` or any
+    /// of its ancestors when one exists.
+    pub fn subscribe(
+        &'static self,
+        path: &Path,
+    ) -> Result<WatchSubscription, FilesystemWatcherError> {
+        let scope = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        if let Some(watcher) = self.find_covering(&scope) {
+            return Ok(watcher.subscribe(self, scope));
+        }
+
+        // Building a watcher walks the whole tree, so do it unlocked and
+        // resolve a concurrent build for the same root afterwards.
+        let (backend, rx, root) = filesystem_watcher::async_watcher(scope.clone())?;
+        let watcher = {
+            let mut watchers = self.watchers.lock().unwrap();
+            if let Some(existing) = watchers
+                .values()
+                .find(|watcher| scope.starts_with(&watcher.root))
+            {
+                existing.clone()
+            } else {
+                let watcher = Arc::new(SharedWatcher {
+                    root: root.clone(),
+                    backend,
+                    subscribers: Mutex::new(Vec::new()),
+                    next_id: AtomicU64::new(0),
+                });
+                spawn_fan_out(
+                    rx,
+                    Arc::downgrade(&watcher),
+                    self.dropped_event_batches.clone(),
+                );
+                watchers.insert(root, watcher.clone());
+                watcher
+            }
+        };
+        Ok(watcher.subscribe(self, scope))
+    }
+
+    pub fn stats(&self) -> WatcherHubStats {
+        let watchers = self.watchers.lock().unwrap();
+        WatcherHubStats {
+            watchers: watchers.len(),
+            polling_watchers: watchers
+                .values()
+                .filter(|watcher| watcher.backend.lock().unwrap().is_polling())
+                .count(),
+            subscribers: watchers
+                .values()
+                .map(|watcher| watcher.subscribers.lock().unwrap().len())
+                .sum(),
+            dropped_event_batches: self.dropped_event_batches.load(Ordering::Relaxed),
+        }
+    }
+
+    fn find_covering(&self, scope: &Path) -> Option<Arc<SharedWatcher>> {
+        self.watchers
+            .lock()
+            .unwrap()
+            .values()
+            .find(|watcher| scope.starts_with(&watcher.root))
+            .cloned()
+    }
+
+    fn release(&self, watcher: &Arc<SharedWatcher>) {
+        let mut watchers = self.watchers.lock().unwrap();
+        if watcher.subscribers.lock().unwrap().is_empty()
+            && watchers
+                .get(&watcher.root)
+                .is_some_and(|current| Arc::ptr_eq(current, watcher))
+        {
+            watchers.remove(&watcher.root);
+        }
+    }
+}
+
+impl SharedWatcher {
+    fn subscribe(self: &Arc<Self>, hub: &'static WatcherHub, scope: PathBuf) -> WatchSubscription {
+        let (tx, rx) = channel(SUBSCRIBER_CHANNEL_CAPACITY);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.subscribers.lock().unwrap().push(Subscriber {
+            id,
+            scope: scope.clone(),
+            tx,
+        });
+        WatchSubscription {
+            rx,
+            scope,
+            hub,
+            watcher: self.clone(),
+            id,
+        }
+    }
+}
+
+impl Drop for WatchSubscription {
+    fn drop(&mut self) {
+        self.watcher
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.id != self.id);
+        self.hub.release(&self.watcher);
+    }
+}
+
+fn scoped_events(events: &[DebouncedEvent], scope: &Path) -> Vec<DebouncedEvent> {
+    events
+        .iter()
+        .filter(|event| event.paths.iter().any(|path| path.starts_with(scope)))
+        .cloned()
+        .collect()
+}
+
+/// Forward a watcher's events to its subscribers until the watcher is
+/// dropped, which closes `rx`.
+fn spawn_fan_out(
+    mut rx: Receiver<DebounceEventResult>,
+    watcher: Weak<SharedWatcher>,
+    dropped_event_batches: Arc<AtomicU64>,
+) {
+    std::thread::spawn(move || {
+        while let Some(result) = futures::executor::block_on(rx.next()) {
+            let Some(watcher) = watcher.upgrade() else {
+                break;
+            };
+            let mut subscribers = watcher.subscribers.lock().unwrap();
+            for subscriber in subscribers.iter_mut() {
+                let scoped = match &result {
+                    Ok(events) => {
+                        let events = scoped_events(events, &subscriber.scope);
+                        if events.is_empty() {
+                            continue;
+                        }
+                        Ok(events)
+                    }
+                    // notify::Error isn't Clone; every subscriber gets a copy
+                    // with the same message and paths.
+                    Err(errors) => Err(errors
+                        .iter()
+                        .map(|e| notify::Error::generic(&e.to_string()).set_paths(e.paths.clone()))
+                        .collect()),
+                };
+                if let Err(e) = subscriber.tx.try_send(scoped)
+                    && e.is_full()
+                {
+                    let total = dropped_event_batches.fetch_add(1, Ordering::Relaxed) + 1;
+                    tracing::debug!(
+                        "Dropped filesystem event batch for {:?} ({} dropped in total)",
+                        subscriber.scope,
+                        total
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use notify::{EventKind, event::CreateKind};
+
+    use super::*;
+
+    fn test_hub() -> &'static WatcherHub {
+        Box::leak(Box::default())
+    }
+
+    fn created(path: &Path) -> DebouncedEvent {
+        DebouncedEvent::new(
+            notify::Event::new(EventKind::Create(CreateKind::File)).add_path(path.to_path_buf()),
+            Instant::now(),
+        )
+    }
+
+    #[test]
+    fn events_are_scoped_to_the_subscribed_subtree() {
+        let events = vec![
+            created(Path::new("/repo/app/main.rs")),
+            created(Path::new("/repo/docs/readme.md")),
+        ];
+
+        let scoped = scoped_events(&events, Path::new("/repo/app"));
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].paths, vec![PathBuf::from("/repo/app/main.rs")]);
+        assert!(scoped_events(&events, Path::new("/repo/lib")).is_empty());
+    }
+
+    #[test]
+    fn subscriptions_inside_a_watched_root_share_its_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("app")).unwrap();
+        let hub = test_hub();
+
+        let root = hub.subscribe(dir.path()).unwrap();
+        let app = hub.subscribe(&dir.path().join("app")).unwrap();
+        let stats = hub.stats();
+        assert_eq!(stats.watchers, 1);
+        assert_eq!(stats.subscribers, 2);
+
+        drop(root);
+        assert_eq!(hub.stats().watchers, 1);
+        drop(app);
+        assert_eq!(hub.stats().watchers, 0);
+    }
+
+    #[tokio::test]
+    async fn changes_reach_only_the_subscribers_in_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("app")).unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        let hub = test_hub();
+
+        let _root = hub.subscribe(dir.path()).unwrap();
+        let mut app = hub.subscribe(&dir.path().join("app")).unwrap();
+        let mut docs = hub.subscribe(&dir.path().join("docs")).unwrap();
+
+        let file = app.scope.join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let events = tokio::time::timeout(Duration::from_secs(10), app.rx.next())
+            .await
+            .expect("no event for the app subscription")
+            .unwrap()
+            .unwrap();
+        assert!(events.iter().any(|event| event.paths.contains(&file)));
+        assert!(docs.rx.try_next().is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Read and write single text files inside a workspace directory for quick
+//! manual edits. Writes carry the hash of the version the editor started
+//! from and are refused if the file changed since, e.g. because the agent
+//! edited it in the meantime.
+
+use std::{
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use ts_rs::TS;
+
+/// Larger files are better edited in an IDE.
+pub const MAX_EDITABLE_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum WorktreeFileError {
+    #[error("File not found")]
+    NotFound,
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    #[error("File too large: {0} bytes (max: {1} bytes)")]
+    TooLarge(u64, u64),
+    #[error("File is not valid UTF-8 text")]
+    NotText,
+    #[error("File changed since it was read")]
+    Conflict(WorktreeFileVersion),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Identifies the content a file had when it was read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct WorktreeFileVersion {
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorktreeFile {
+    /// Path relative to the workspace directory.
+    pub path: String,
+    pub content: String,
+    pub version: WorktreeFileVersion,
+}
+
+pub async fn read_file(root: &Path, rel_path: &str) -> Result<WorktreeFile, WorktreeFileError> {
+    let path = resolve(root, rel_path).await?;
+    let (content, version) = read_versioned(&path)
+        .await?
+        .ok_or(WorktreeFileError::NotFound)?;
+    Ok(WorktreeFile {
+        path: rel_path.to_string(),
+        content,
+        version,
+    })
+}
+
+/// Write `content` to `rel_path`. `expected_sha256` is the hash of the
+/// version the edit is based on, or `None` to create a file that must not
+/// exist yet.
+pub async fn write_file(
+    root: &Path,
+    rel_path: &str,
+    content: &str,
+    expected_sha256: Option<&str>,
+) -> Result<WorktreeFile, WorktreeFileError> {
+    if content.len() as u64 > MAX_EDITABLE_FILE_BYTES {
+        return Err(WorktreeFileError::TooLarge(
+            content.len() as u64,
+            MAX_EDITABLE_FILE_BYTES,
+        ));
+    }
+    let path = resolve(root, rel_path).await?;
+    match (read_versioned(&path).await?, expected_sha256) {
+        (Some((_, current)), Some(expected)) if current.sha256 == expected => {}
+        (None, None) => {}
+        (Some((_, current)), _) => return Err(WorktreeFileError::Conflict(current)),
+        (None, Some(_)) => return Err(WorktreeFileError::NotFound),
+    }
+
+    tokio::fs::write(&path, content).await?;
+    let metadata = tokio::fs::metadata(&path).await?;
+    Ok(WorktreeFile {
+        path: rel_path.to_string(),
+        content: content.to_string(),
+        version: version_of(content.as_bytes(), &metadata),
+    })
+}
+
+async fn read_versioned(
+    path: &Path,
+) -> Result<Option<(String, WorktreeFileVersion)>, WorktreeFileError> {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if !metadata.is_file() {
+        return Err(WorktreeFileError::InvalidPath(
+            "Path is not a regular file".to_string(),
+        ));
+    }
+    if metadata.len() > MAX_EDITABLE_FILE_BYTES {
+        return Err(WorktreeFileError::TooLarge(
+            metadata.len(),
+            MAX_EDITABLE_FILE_BYTES,
+        ));
+    }
+    let bytes = tokio::fs::read(path).await?;
+    let version = version_of(&bytes, &metadata);
+    let content = String::from_utf8(bytes).map_err(|_| WorktreeFileError::NotText)?;
+    Ok(Some((content, version)))
+}
+
+fn version_of(bytes: &[u8], metadata: &std::fs::Metadata) -> WorktreeFileVersion {
+    WorktreeFileVersion {
+        sha256: format!("{:x}", Sha256::digest(bytes)),
+        size_bytes: bytes.len() as u64,
+        modified_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+    }
+}
+
+/// Resolve `rel_path` under `root`, refusing anything that escapes it
+/// (through `..` or symlinks) or points into git metadata. The parent
+/// directory must already exist.
+async fn resolve(root: &Path, rel_path: &str) -> Result<PathBuf, WorktreeFileError> {
+    let rel = Path::new(rel_path);
+    let mut has_name = false;
+    for component in rel.components() {
+        match component {
+            Component::Normal(name) if name == ".git" => {
+                return Err(WorktreeFileError::InvalidPath(
+                    "Git metadata cannot be edited".to_string(),
+                ));
+            }
+            Component::Normal(_) => has_name = true,
+            Component::CurDir => {}
+            _ => {
+                return Err(WorktreeFileError::InvalidPath(
+                    "Path must be relative to the workspace".to_string(),
+                ));
+            }
+        }
+    }
+    let file_name = rel
+        .file_name()
+        .filter(|_| has_name)
+        .ok_or_else(|| WorktreeFileError::InvalidPath("Path has no file name".to_string()))?;
+
+    let root = tokio::fs::canonicalize(root).await?;
+    let parent = match rel.parent() {
+        Some(parent) => root.join(parent),
+        None => root.clone(),
+    };
+    let parent = match tokio::fs::canonicalize(&parent).await {
+        Ok(parent) => parent,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Err(WorktreeFileError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let path = parent.join(file_name);
+    // A symlinked file may point anywhere; follow it and check again
+    let resolved = match tokio::fs::canonicalize(&path).await {
+        Ok(resolved) => resolved,
+        Err(e) if e.kind() == ErrorKind::NotFound => path,
+        Err(e) => return Err(e.into()),
+    };
+    if !resolved.starts_with(&root) {
+        return Err(WorktreeFileError::InvalidPath(
+            "Path is outside the workspace".to_string(),
+        ));
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn write_requires_the_version_it_was_based_on() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("repo")).unwrap();
+
+        let created = write_file(dir.path(), "repo/a.txt", "one\n", None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            write_file(dir.path(), "repo/a.txt", "again\n", None).await,
+            Err(WorktreeFileError::Conflict(_))
+        ));
+
+        let read = read_file(dir.path(), "repo/a.txt").await.unwrap();
+        assert_eq!(read.content, "one\n");
+        assert_eq!(read.version.sha256, created.version.sha256);
+
+        let updated = write_file(
+            dir.path(),
+            "repo/a.txt",
+            "two\n",
+            Some(&read.version.sha256),
+        )
+        .await
+        .unwrap();
+        match write_file(
+            dir.path(),
+            "repo/a.txt",
+            "three\n",
+            Some(&read.version.sha256),
+        )
+        .await
+        {
+            Err(WorktreeFileError::Conflict(current)) => assert_eq!(current, updated.version),
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_outside_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ws");
+        std::fs::create_dir_all(root.join("repo/.git")).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "x").unwrap();
+
+        for path in [
+            "../secret.txt",
+            "/etc/passwd",
+            "repo/.git/config",
+            "repo/..",
+        ] {
+            assert!(
+                matches!(
+                    read_file(&root, path).await,
+                    Err(WorktreeFileError::InvalidPath(_))
+                ),
+                "{path} should be rejected"
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("link")).unwrap();
+            assert!(matches!(
+                read_file(&root, "link").await,
+                Err(WorktreeFileError::InvalidPath(_))
+            ));
+        }
+    }
+}
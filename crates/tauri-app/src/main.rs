@@ -6,7 +6,9 @@ use std::{sync::Arc, time::Duration};
 use async_trait::async_trait;
 use services::services::{
     config::load_config_from_file,
-    notification::{NotificationService, PushNotifier, set_global_push_notifier},
+    notification::{
+        NotificationEvent, NotificationService, PushNotifier, set_global_push_notifier,
+    },
 };
 #[cfg(target_os = "macos")]
 use tauri::Manager;
@@ -75,7 +77,9 @@ async fn show_system_notification(
     // Fallback: generic NotificationService (e.g. macOS dev mode).
     let config = load_config_from_file(&config_path()).await;
     let notification_service = NotificationService::new(Arc::new(tokio::sync::RwLock::new(config)));
-    notification_service.notify(&title, &body, None).await;
+    notification_service
+        .notify(NotificationEvent::Other, &title, &body, None)
+        .await;
     Ok(())
 }
 
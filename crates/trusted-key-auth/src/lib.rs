@@ -2,6 +2,7 @@ pub mod error;
 pub mod key_confirmation;
 pub mod refresh;
 pub mod request_signature;
+pub mod rotation;
 pub mod runtime;
 pub mod spake2;
 pub mod trusted_keys;
@@ -0,0 +1,117 @@
+use ed25519_dalek::VerifyingKey;
+use uuid::Uuid;
+
+use crate::{
+    error::TrustedKeyAuthError,
+    refresh::{validate_refresh_timestamp, verify_refresh_signature},
+};
+
+/// Message a paired client signs to replace its key. The current key signs
+/// it to authorize the rotation and the new key signs it to prove the client
+/// holds the matching private key.
+pub fn build_key_rotation_message(
+    timestamp: i64,
+    nonce: &str,
+    client_id: Uuid,
+    new_public_key_b64: &str,
+) -> String {
+    format!("v1|rotate-key|{timestamp}|{nonce}|{client_id}|{new_public_key_b64}")
+}
+
+pub fn verify_key_rotation(
+    current_public_key: &VerifyingKey,
+    new_public_key: &VerifyingKey,
+    message: &str,
+    signature_b64: &str,
+    new_key_signature_b64: &str,
+) -> Result<(), TrustedKeyAuthError> {
+    if current_public_key == new_public_key {
+        return Err(TrustedKeyAuthError::BadRequest(
+            "New key must differ from the current key".to_string(),
+        ));
+    }
+    verify_refresh_signature(current_public_key, message, signature_b64)?;
+    verify_refresh_signature(new_public_key, message, new_key_signature_b64)
+}
+
+pub fn validate_key_rotation_timestamp(timestamp: i64) -> Result<(), TrustedKeyAuthError> {
+    validate_refresh_timestamp(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn sign(key: &SigningKey, message: &str) -> String {
+        BASE64_STANDARD.encode(key.sign(message.as_bytes()).to_bytes())
+    }
+
+    fn rotation_message(new_key: &SigningKey) -> String {
+        build_key_rotation_message(
+            1_700_000_000,
+            "nonce",
+            Uuid::parse_str("11111111-1111-1111-1111-111111111111").unwrap(),
+            &BASE64_STANDARD.encode(new_key.verifying_key().as_bytes()),
+        )
+    }
+
+    #[test]
+    fn accepts_rotation_signed_by_both_keys() {
+        let current = signing_key(3);
+        let new = signing_key(5);
+        let message = rotation_message(&new);
+
+        verify_key_rotation(
+            &current.verifying_key(),
+            &new.verifying_key(),
+            &message,
+            &sign(&current, &message),
+            &sign(&new, &message),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rejects_rotation_without_current_key() {
+        let current = signing_key(3);
+        let new = signing_key(5);
+        let attacker = signing_key(7);
+        let message = rotation_message(&new);
+
+        assert!(
+            verify_key_rotation(
+                &current.verifying_key(),
+                &new.verifying_key(),
+                &message,
+                &sign(&attacker, &message),
+                &sign(&new, &message),
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_rotation_without_new_key_proof() {
+        let current = signing_key(3);
+        let new = signing_key(5);
+        let message = rotation_message(&new);
+
+        assert!(
+            verify_key_rotation(
+                &current.verifying_key(),
+                &new.verifying_key(),
+                &message,
+                &sign(&current, &message),
+                &sign(&current, &message),
+            )
+            .is_err()
+        );
+    }
+}
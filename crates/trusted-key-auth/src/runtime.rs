@@ -5,19 +5,22 @@ use std::{
     time::{Duration, Instant},
 };
 
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
 use crate::{
     error::TrustedKeyAuthError,
     trusted_keys::{
-        TrustedRelayClient, list_trusted_clients, remove_trusted_client, upsert_trusted_client,
+        TrustedRelayClient, list_trusted_clients, remove_trusted_client, rotate_trusted_client_key,
+        upsert_trusted_client,
     },
 };
 
 #[derive(Clone)]
 pub struct TrustedKeyAuthRuntime {
     trusted_keys_path: PathBuf,
+    /// Serializes read-modify-write updates of the trusted keys file.
+    trusted_keys_write_lock: Arc<Mutex<()>>,
     pake_enrollments: Arc<RwLock<HashMap<Uuid, PendingPakeEnrollment>>>,
     enrollment_code: Arc<RwLock<Option<String>>>,
     rate_limit_windows: Arc<RwLock<HashMap<String, Vec<Instant>>>>,
@@ -37,6 +40,7 @@ impl TrustedKeyAuthRuntime {
     pub fn new(trusted_keys_path: PathBuf) -> Self {
         Self {
             trusted_keys_path,
+            trusted_keys_write_lock: Default::default(),
             pake_enrollments: Default::default(),
             enrollment_code: Default::default(),
             rate_limit_windows: Default::default(),
@@ -48,6 +52,7 @@ impl TrustedKeyAuthRuntime {
         &self,
         client: TrustedRelayClient,
     ) -> Result<bool, TrustedKeyAuthError> {
+        let _guard = self.trusted_keys_write_lock.lock().await;
         upsert_trusted_client(&self.trusted_keys_path, client).await
     }
 
@@ -61,9 +66,26 @@ impl TrustedKeyAuthRuntime {
         &self,
         client_id: Uuid,
     ) -> Result<bool, TrustedKeyAuthError> {
+        let _guard = self.trusted_keys_write_lock.lock().await;
         remove_trusted_client(&self.trusted_keys_path, client_id).await
     }
 
+    pub async fn rotate_trusted_client_key(
+        &self,
+        client_id: Uuid,
+        current_public_key_b64: &str,
+        new_public_key_b64: &str,
+    ) -> Result<Option<TrustedRelayClient>, TrustedKeyAuthError> {
+        let _guard = self.trusted_keys_write_lock.lock().await;
+        rotate_trusted_client_key(
+            &self.trusted_keys_path,
+            client_id,
+            current_public_key_b64,
+            new_public_key_b64,
+        )
+        .await
+    }
+
     pub async fn find_trusted_client(
         &self,
         client_id: Uuid,
@@ -92,6 +92,40 @@ pub async fn remove_trusted_client(
     Ok(true)
 }
 
+/// Replace a client's key, provided it still has `current_public_key_b64`.
+/// Returns `None` if the client is unknown or its key changed meanwhile.
+pub async fn rotate_trusted_client_key(
+    trusted_keys_path: &Path,
+    client_id: Uuid,
+    current_public_key_b64: &str,
+    new_public_key_b64: &str,
+) -> Result<Option<TrustedRelayClient>, TrustedKeyAuthError> {
+    parse_public_key_base64(new_public_key_b64)
+        .map_err(|_| TrustedKeyAuthError::BadRequest("Invalid new public key".to_string()))?;
+    let mut trusted_clients_file = read_trusted_clients_file(trusted_keys_path).await?;
+
+    if trusted_clients_file
+        .clients
+        .iter()
+        .any(|client| client.client_id != client_id && client.public_key_b64 == new_public_key_b64)
+    {
+        return Err(TrustedKeyAuthError::BadRequest(
+            "New public key is already trusted for another client".to_string(),
+        ));
+    }
+
+    let Some(client) = trusted_clients_file.clients.iter_mut().find(|client| {
+        client.client_id == client_id && client.public_key_b64 == current_public_key_b64
+    }) else {
+        return Ok(None);
+    };
+    client.public_key_b64 = new_public_key_b64.to_string();
+    let rotated = client.clone();
+
+    write_trusted_clients_file(trusted_keys_path, &trusted_clients_file).await?;
+    Ok(Some(rotated))
+}
+
 pub fn parse_public_key_base64(raw_public_key: &str) -> Result<VerifyingKey, TrustedKeyAuthError> {
     let public_key_bytes = decode_base64(raw_public_key)?;
     let public_key_bytes: [u8; 32] = public_key_bytes
@@ -131,7 +165,11 @@ async fn write_trusted_clients_file(
     let serialized = serde_json::to_string_pretty(trusted_clients_file).map_err(|error| {
         TrustedKeyAuthError::BadRequest(format!("Failed to serialize trusted keys: {error}"))
     })?;
-    fs::write(trusted_keys_path, format!("{serialized}\n")).await?;
+    // Write a sibling file and rename it over the original so a crash or a
+    // concurrent reader never sees a partially written key list.
+    let tmp_path = trusted_keys_path.with_extension("json.tmp");
+    fs::write(&tmp_path, format!("{serialized}\n")).await?;
+    fs::rename(&tmp_path, trusted_keys_path).await?;
     Ok(())
 }
 
@@ -228,6 +266,49 @@ mod tests {
         let _ = fs::remove_file(&trusted_keys_path).await;
     }
 
+    #[tokio::test]
+    async fn rotates_key_only_from_current_key() {
+        let trusted_keys_path = temp_trusted_keys_path();
+        let old_key_b64 = BASE64_STANDARD.encode(test_public_key().as_bytes());
+        let new_key_b64 =
+            BASE64_STANDARD.encode(SigningKey::from_bytes(&[8; 32]).verifying_key().as_bytes());
+        let client_id = Uuid::new_v4();
+
+        upsert_trusted_client(
+            &trusted_keys_path,
+            TrustedRelayClient {
+                client_id,
+                client_name: "Firefox on Linux (Desktop)".to_string(),
+                client_browser: "Firefox".to_string(),
+                client_os: "Linux".to_string(),
+                client_device: "desktop".to_string(),
+                public_key_b64: old_key_b64.clone(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let rotated =
+            rotate_trusted_client_key(&trusted_keys_path, client_id, &old_key_b64, &new_key_b64)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(rotated.public_key_b64, new_key_b64);
+
+        // The old key no longer matches, so a replayed rotation is a no-op.
+        let replayed =
+            rotate_trusted_client_key(&trusted_keys_path, client_id, &old_key_b64, &new_key_b64)
+                .await
+                .unwrap();
+        assert!(replayed.is_none());
+
+        let clients = list_trusted_clients(&trusted_keys_path).await.unwrap();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].public_key_b64, new_key_b64);
+
+        let _ = fs::remove_file(&trusted_keys_path).await;
+    }
+
     fn temp_trusted_keys_path() -> PathBuf {
         let mut path = std::env::temp_dir();
         path.push(format!("vk-trusted-keys-{}.json", Uuid::new_v4()));
@@ -0,0 +1,121 @@
+//! A thin wrapper around the system allocator that keeps running allocation
+//! counters, so the server can report heap usage without an external profiler.
+//!
+//! Install it in a binary with:
+//! ```ignore
+//! #[global_allocator]
+//! static GLOBAL: utils::alloc_stats::CountingAllocator = utils::alloc_stats::CountingAllocator;
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static FREED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record_alloc(size: usize) {
+        INSTALLED.store(true, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        let allocated = ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        let live = allocated.saturating_sub(FREED_BYTES.load(Ordering::Relaxed));
+        PEAK_LIVE_BYTES.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        FREED_BYTES.fetch_add(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Point-in-time view of the allocation counters. All counts are cumulative
+/// since process start except `live_bytes`.
+#[derive(Debug, Clone, Copy, Serialize, TS)]
+pub struct AllocStats {
+    /// False when the counting allocator isn't the global allocator, in which
+    /// case every other field is zero.
+    pub enabled: bool,
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub allocated_bytes: usize,
+    pub freed_bytes: usize,
+    pub live_bytes: usize,
+    pub peak_live_bytes: usize,
+}
+
+pub fn snapshot() -> AllocStats {
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let freed_bytes = FREED_BYTES.load(Ordering::Relaxed);
+    AllocStats {
+        enabled: INSTALLED.load(Ordering::Relaxed),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        allocated_bytes,
+        freed_bytes,
+        live_bytes: allocated_bytes.saturating_sub(freed_bytes),
+        peak_live_bytes: PEAK_LIVE_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_track_alloc_and_dealloc() {
+        let before = snapshot();
+        CountingAllocator::record_alloc(128);
+        CountingAllocator::record_dealloc(64);
+        let after = snapshot();
+
+        assert!(after.enabled);
+        assert!(after.allocations > before.allocations);
+        assert!(after.deallocations > before.deallocations);
+        assert!(after.allocated_bytes >= before.allocated_bytes + 128);
+        assert!(after.freed_bytes >= before.freed_bytes + 64);
+        assert!(after.peak_live_bytes >= before.peak_live_bytes);
+    }
+}
@@ -1,30 +1,113 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
+use crate::text::truncate_to_char_boundary;
+
 pub const APPROVAL_TIMEOUT_SECONDS: i64 = 36000; // 10 hours
 
+const MAX_COMMAND_LEN: usize = 2_000;
+const MAX_FILE_PATH_LEN: usize = 1_000;
+const MAX_DIFF_LEN: usize = 4_000;
+const MAX_RAW_INPUT_LEN: usize = 2_000;
+const MAX_SUMMARY_LEN: usize = 120;
+/// Agents mark the option they suggest by suffixing its label with this.
+const RECOMMENDED_SUFFIX: &str = "(Recommended)";
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct ApprovalRequest {
     pub id: String,
     pub tool_name: String,
+    pub tool_input: Option<ApprovalToolInput>,
+    /// What an agent asked, for question requests.
+    #[serde(default)]
+    pub questions: Vec<ApprovalQuestion>,
     pub execution_process_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub timeout_at: DateTime<Utc>,
 }
 
 impl ApprovalRequest {
-    pub fn new(tool_name: String, execution_process_id: Uuid) -> Self {
+    pub fn new(
+        tool_name: String,
+        tool_input: Option<ApprovalToolInput>,
+        execution_process_id: Uuid,
+    ) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4().to_string(),
             tool_name,
+            tool_input: tool_input.map(ApprovalToolInput::truncate),
+            questions: Vec::new(),
             execution_process_id,
             created_at: now,
             timeout_at: now + Duration::seconds(APPROVAL_TIMEOUT_SECONDS),
         }
     }
+
+    pub fn with_questions(mut self, questions: Vec<ApprovalQuestion>) -> Self {
+        self.questions = questions;
+        self
+    }
+}
+
+/// What a tool call waiting for approval is about to do, so it can be judged
+/// without opening the full log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ApprovalToolInput {
+    /// Shell command the tool runs.
+    pub command: Option<String>,
+    /// File the tool reads or changes.
+    pub file_path: Option<String>,
+    /// Proposed change, as removed (`-`) and added (`+`) lines.
+    pub diff: Option<String>,
+    /// The tool input as JSON, for tools without a command, path or diff.
+    pub raw_input: Option<String>,
+    /// True if any of the fields was cut short.
+    pub truncated: bool,
+}
+
+impl ApprovalToolInput {
+    /// Cut every field down to a size that is safe to store and send to
+    /// clients and notification channels.
+    pub fn truncate(mut self) -> Self {
+        let mut truncated = self.truncated;
+        for (field, max_len) in [
+            (&mut self.command, MAX_COMMAND_LEN),
+            (&mut self.file_path, MAX_FILE_PATH_LEN),
+            (&mut self.diff, MAX_DIFF_LEN),
+            (&mut self.raw_input, MAX_RAW_INPUT_LEN),
+        ] {
+            if let Some(value) = field
+                && value.len() > max_len
+            {
+                let cut = truncate_to_char_boundary(value, max_len).len();
+                value.truncate(cut);
+                truncated = true;
+            }
+        }
+        self.truncated = truncated;
+        self
+    }
+
+    /// One line describing the tool call, for notification messages.
+    pub fn summary(&self) -> Option<String> {
+        let line = self
+            .command
+            .as_deref()
+            .or(self.file_path.as_deref())?
+            .lines()
+            .find(|line| !line.trim().is_empty())?
+            .trim();
+        let short = truncate_to_char_boundary(line, MAX_SUMMARY_LEN);
+        if short.len() < line.len() {
+            Some(format!("{short}…"))
+        } else {
+            Some(short.to_string())
+        }
+    }
 }
 
 /// Status of a tool permission request (approve/deny for tool execution).
@@ -40,13 +123,143 @@ pub enum ApprovalStatus {
     TimedOut,
 }
 
+/// A question an agent asked, as shown to the user and used to check the
+/// answers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ApprovalQuestion {
+    pub question: String,
+    pub header: Option<String>,
+    pub options: Vec<ApprovalQuestionOption>,
+    pub multi_select: bool,
+    /// Whether answers other than the listed options are accepted.
+    pub allow_free_text: bool,
+    /// Label of the option chosen when the question is left unanswered.
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ApprovalQuestionOption {
+    pub label: String,
+    pub description: Option<String>,
+}
+
+impl ApprovalQuestion {
+    /// A question that also accepts free text, defaulting to the option the
+    /// agent marked as recommended, if any.
+    pub fn new(
+        question: String,
+        header: Option<String>,
+        options: Vec<ApprovalQuestionOption>,
+        multi_select: bool,
+    ) -> Self {
+        let default = options
+            .iter()
+            .find(|option| option.label.trim_end().ends_with(RECOMMENDED_SUFFIX))
+            .map(|option| option.label.clone());
+        Self {
+            question,
+            header: header.filter(|header| !header.is_empty()),
+            options,
+            multi_select,
+            allow_free_text: true,
+            default,
+        }
+    }
+}
+
+impl ApprovalQuestionOption {
+    pub fn new(label: String, description: String) -> Self {
+        Self {
+            label,
+            description: Some(description).filter(|description| !description.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QuestionAnswerError {
+    #[error("no question \"{0}\" was asked")]
+    UnknownQuestion(String),
+    #[error("question \"{0}\" was answered more than once")]
+    Duplicate(String),
+    #[error("question \"{0}\" needs an answer")]
+    Unanswered(String),
+    #[error("question \"{0}\" takes a single answer")]
+    TooManyAnswers(String),
+    #[error("\"{answer}\" is not an option for question \"{question}\"")]
+    UnknownOption { question: String, answer: String },
+}
+
 /// A question–answer pair. `answer` holds one or more selected labels/values.
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+/// Values that aren't option labels are free text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
 pub struct QuestionAnswer {
     pub question: String,
     pub answer: Vec<String>,
 }
 
+/// Check answers against the questions asked and return them in question
+/// order, with defaults filled in for questions left unanswered.
+pub fn validate_answers(
+    questions: &[ApprovalQuestion],
+    answers: &[QuestionAnswer],
+) -> Result<Vec<QuestionAnswer>, QuestionAnswerError> {
+    let mut given: Vec<Option<Vec<String>>> = vec![None; questions.len()];
+    for answer in answers {
+        let index = questions
+            .iter()
+            .position(|question| question.question == answer.question)
+            .ok_or_else(|| QuestionAnswerError::UnknownQuestion(answer.question.clone()))?;
+        if given[index].is_some() {
+            return Err(QuestionAnswerError::Duplicate(answer.question.clone()));
+        }
+        let values = answer
+            .answer
+            .iter()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect();
+        given[index] = Some(values);
+    }
+
+    questions
+        .iter()
+        .zip(given)
+        .map(|(question, values)| {
+            let mut values = values.unwrap_or_default();
+            if values.is_empty() {
+                values.extend(question.default.clone());
+            }
+            if values.is_empty() {
+                return Err(QuestionAnswerError::Unanswered(question.question.clone()));
+            }
+            if values.len() > 1 && !question.multi_select {
+                return Err(QuestionAnswerError::TooManyAnswers(
+                    question.question.clone(),
+                ));
+            }
+            if !question.allow_free_text
+                && let Some(value) = values.iter().find(|value| {
+                    !question
+                        .options
+                        .iter()
+                        .any(|option| &option.label == *value)
+                })
+            {
+                return Err(QuestionAnswerError::UnknownOption {
+                    question: question.question.clone(),
+                    answer: value.clone(),
+                });
+            }
+            Ok(QuestionAnswer {
+                question: question.question.clone(),
+                answer: values,
+            })
+        })
+        .collect()
+}
+
 /// Status of a question answer request.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -75,3 +288,124 @@ pub struct ApprovalResponse {
     pub execution_process_id: Uuid,
     pub status: ApprovalOutcome,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_long_fields_and_flags_them() {
+        let input = ApprovalToolInput {
+            command: Some("echo ok".to_string()),
+            diff: Some("+🔥".repeat(MAX_DIFF_LEN)),
+            ..Default::default()
+        }
+        .truncate();
+        assert_eq!(input.command.as_deref(), Some("echo ok"));
+        assert!(input.diff.unwrap().len() <= MAX_DIFF_LEN);
+        assert!(input.truncated);
+
+        let short = ApprovalToolInput {
+            file_path: Some("src/main.rs".to_string()),
+            ..Default::default()
+        }
+        .truncate();
+        assert!(!short.truncated);
+    }
+
+    fn question(multi_select: bool, allow_free_text: bool) -> ApprovalQuestion {
+        ApprovalQuestion {
+            allow_free_text,
+            ..ApprovalQuestion::new(
+                "Which language?".to_string(),
+                Some("Language".to_string()),
+                vec![
+                    ApprovalQuestionOption::new("Rust (Recommended)".to_string(), String::new()),
+                    ApprovalQuestionOption::new("Go".to_string(), "Simpler".to_string()),
+                ],
+                multi_select,
+            )
+        }
+    }
+
+    fn answer(values: &[&str]) -> QuestionAnswer {
+        QuestionAnswer {
+            question: "Which language?".to_string(),
+            answer: values.iter().map(|value| value.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn recommended_option_becomes_the_default() {
+        let question = question(false, true);
+        assert_eq!(question.default.as_deref(), Some("Rust (Recommended)"));
+        assert_eq!(question.options[0].description, None);
+        assert_eq!(
+            validate_answers(std::slice::from_ref(&question), &[]).unwrap(),
+            vec![answer(&["Rust (Recommended)"])]
+        );
+        assert_eq!(
+            validate_answers(&[question], &[answer(&[" Go "])]).unwrap(),
+            vec![answer(&["Go"])]
+        );
+    }
+
+    #[test]
+    fn rejects_answers_that_do_not_fit_the_question() {
+        let single = question(false, false);
+        assert_eq!(
+            validate_answers(
+                std::slice::from_ref(&single),
+                &[answer(&["Rust (Recommended)", "Go"])]
+            ),
+            Err(QuestionAnswerError::TooManyAnswers(
+                "Which language?".to_string()
+            ))
+        );
+        assert!(matches!(
+            validate_answers(std::slice::from_ref(&single), &[answer(&["Zig"])]),
+            Err(QuestionAnswerError::UnknownOption { .. })
+        ));
+        assert!(matches!(
+            validate_answers(
+                std::slice::from_ref(&single),
+                &[answer(&["Go"]), answer(&["Go"])]
+            ),
+            Err(QuestionAnswerError::Duplicate(_))
+        ));
+        let other = QuestionAnswer {
+            question: "Which editor?".to_string(),
+            answer: vec!["vim".to_string()],
+        };
+        assert!(matches!(
+            validate_answers(std::slice::from_ref(&single), &[other]),
+            Err(QuestionAnswerError::UnknownQuestion(_))
+        ));
+
+        let no_default = ApprovalQuestion {
+            default: None,
+            ..single
+        };
+        assert!(matches!(
+            validate_answers(&[no_default], &[answer(&[" "])]),
+            Err(QuestionAnswerError::Unanswered(_))
+        ));
+
+        let multi = question(true, true);
+        assert_eq!(
+            validate_answers(&[multi], &[answer(&["Go", "Zig"])]).unwrap(),
+            vec![answer(&["Go", "Zig"])]
+        );
+    }
+
+    #[test]
+    fn summary_uses_first_command_line() {
+        let input = ApprovalToolInput {
+            command: Some("\ncargo test\ncargo build".to_string()),
+            file_path: Some("Cargo.toml".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(input.summary().as_deref(), Some("cargo test"));
+        assert_eq!(ApprovalToolInput::default().summary(), None);
+    }
+}
@@ -1,9 +1,26 @@
 use directories::ProjectDirs;
 use rust_embed::RustEmbed;
 
+use crate::config_profile;
+
 const PROJECT_ROOT: &str = env!("CARGO_MANIFEST_DIR");
 
+/// The asset directory of the active config profile.
 pub fn asset_dir() -> std::path::PathBuf {
+    let base = base_asset_dir();
+    let path = config_profile::profile_dir(&base, config_profile::active(&base));
+
+    // Ensure the directory exists
+    if !path.exists() {
+        std::fs::create_dir_all(&path).expect("Failed to create asset directory");
+    }
+
+    path
+}
+
+/// The asset directory shared by all config profiles, which is also the
+/// default profile's.
+pub fn base_asset_dir() -> std::path::PathBuf {
     let path = if cfg!(debug_assertions) {
         std::path::PathBuf::from(PROJECT_ROOT).join("../../dev_assets")
     } else {
@@ -40,6 +57,10 @@ pub fn credentials_path() -> std::path::PathBuf {
     asset_dir().join("credentials.json")
 }
 
+pub fn provider_credentials_path() -> std::path::PathBuf {
+    asset_dir().join("provider_credentials.json")
+}
+
 pub fn trusted_keys_path() -> std::path::PathBuf {
     asset_dir().join("trusted_ed25519_public_keys.json")
 }
@@ -48,6 +69,10 @@ pub fn server_signing_key_path() -> std::path::PathBuf {
     asset_dir().join("server_ed25519_signing_key")
 }
 
+pub fn relay_signing_sessions_path() -> std::path::PathBuf {
+    asset_dir().join("relay_signing_sessions.json")
+}
+
 pub fn relay_host_credentials_path() -> std::path::PathBuf {
     asset_dir().join("relay_host_credentials.json")
 }
@@ -0,0 +1,231 @@
+//! Named config profiles, e.g. "work" and "personal".
+//!
+//! Each profile has its own asset directory, so its config, credentials and
+//! database stay separate. The default profile uses the asset directory
+//! itself; others live under `config_profiles/<name>` inside it. The profile
+//! is chosen once at startup from `--config-profile`, then
+//! `VK_CONFIG_PROFILE`, then the profile saved to start next.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use thiserror::Error;
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const PROFILE_ENV_VAR: &str = "VK_CONFIG_PROFILE";
+pub const PROFILE_FLAG: &str = "--config-profile";
+
+const PROFILES_DIRNAME: &str = "config_profiles";
+/// Holds the name of the profile to start with when none is given.
+const STARTUP_PROFILE_FILENAME: &str = "startup_config_profile";
+const MAX_NAME_LEN: usize = 64;
+
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum ConfigProfileError {
+    #[error("Invalid profile name '{0}': use up to 64 letters, digits, '-' or '_'")]
+    InvalidName(String),
+    #[error("Profile '{0}' does not exist")]
+    NotFound(String),
+    #[error("Profile '{0}' already exists")]
+    AlreadyExists(String),
+    #[error("The config profile was already chosen")]
+    AlreadySelected,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub fn validate_name(name: &str) -> Result<(), ConfigProfileError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigProfileError::InvalidName(name.to_string()))
+    }
+}
+
+/// The value of `--config-profile <name>` or `--config-profile=<name>`.
+pub fn profile_from_args(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == PROFILE_FLAG {
+            return args.next();
+        }
+        if let Some(name) = arg
+            .strip_prefix(PROFILE_FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Choose the profile for this run. Must be called before anything reads the
+/// asset directory; `requested` overrides the environment and saved choice.
+pub fn select(
+    base_dir: &Path,
+    requested: Option<String>,
+) -> Result<&'static str, ConfigProfileError> {
+    let name = match requested {
+        Some(name) => name,
+        None => resolve(base_dir),
+    };
+    validate_name(&name)?;
+    ACTIVE_PROFILE
+        .set(name)
+        .map_err(|_| ConfigProfileError::AlreadySelected)?;
+    Ok(active(base_dir))
+}
+
+/// The profile of this run. Falls back to the environment and saved choice
+/// when [`select`] was never called.
+pub fn active(base_dir: &Path) -> &'static str {
+    ACTIVE_PROFILE.get_or_init(|| {
+        let name = resolve(base_dir);
+        if let Err(e) = validate_name(&name) {
+            tracing::warn!("{e}; using the default profile");
+            return DEFAULT_PROFILE.to_string();
+        }
+        name
+    })
+}
+
+fn resolve(base_dir: &Path) -> String {
+    std::env::var(PROFILE_ENV_VAR)
+        .ok()
+        .filter(|name| !name.is_empty())
+        .or_else(|| startup_profile(base_dir))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// The profile saved to start with when none is given.
+pub fn startup_profile(base_dir: &Path) -> Option<String> {
+    fs::read_to_string(base_dir.join(STARTUP_PROFILE_FILENAME))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_name(name).is_ok())
+}
+
+/// Start with `name` from the next launch on.
+pub fn set_startup_profile(base_dir: &Path, name: &str) -> Result<(), ConfigProfileError> {
+    if !exists(base_dir, name) {
+        return Err(ConfigProfileError::NotFound(name.to_string()));
+    }
+    let path = base_dir.join(STARTUP_PROFILE_FILENAME);
+    if name == DEFAULT_PROFILE {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => return Ok(()),
+        }
+    }
+    fs::write(path, name)?;
+    Ok(())
+}
+
+/// The asset directory of profile `name`.
+pub fn profile_dir(base_dir: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        base_dir.to_path_buf()
+    } else {
+        base_dir.join(PROFILES_DIRNAME).join(name)
+    }
+}
+
+pub fn exists(base_dir: &Path, name: &str) -> bool {
+    validate_name(name).is_ok() && profile_dir(base_dir, name).is_dir()
+}
+
+/// All profiles, the default first and the rest by name.
+pub fn list(base_dir: &Path) -> Result<Vec<String>, ConfigProfileError> {
+    let mut names = Vec::new();
+    match fs::read_dir(base_dir.join(PROFILES_DIRNAME)) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir()
+                    && let Some(name) = entry.file_name().to_str()
+                    && name != DEFAULT_PROFILE
+                    && validate_name(name).is_ok()
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(names)
+}
+
+/// Create an empty profile; it starts with the default config.
+pub fn create(base_dir: &Path, name: &str) -> Result<PathBuf, ConfigProfileError> {
+    validate_name(name)?;
+    if exists(base_dir, name) {
+        return Err(ConfigProfileError::AlreadyExists(name.to_string()));
+    }
+    let dir = profile_dir(base_dir, name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_live_in_separate_directories() {
+        let base = tempfile::tempdir().unwrap();
+        let base = base.path();
+
+        assert_eq!(list(base).unwrap(), vec![DEFAULT_PROFILE]);
+        assert_eq!(profile_dir(base, DEFAULT_PROFILE), base);
+
+        let work = create(base, "work").unwrap();
+        assert_eq!(work, base.join(PROFILES_DIRNAME).join("work"));
+        create(base, "personal").unwrap();
+        assert!(matches!(
+            create(base, "work"),
+            Err(ConfigProfileError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            create(base, "../db"),
+            Err(ConfigProfileError::InvalidName(_))
+        ));
+        assert_eq!(list(base).unwrap(), vec!["default", "personal", "work"]);
+
+        assert!(startup_profile(base).is_none());
+        set_startup_profile(base, "work").unwrap();
+        assert_eq!(startup_profile(base).as_deref(), Some("work"));
+        set_startup_profile(base, DEFAULT_PROFILE).unwrap();
+        assert!(startup_profile(base).is_none());
+        assert!(matches!(
+            set_startup_profile(base, "missing"),
+            Err(ConfigProfileError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reads_the_profile_flag() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            profile_from_args(args(&["server", "--config-profile", "work"])).as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            profile_from_args(args(&["server", "--config-profile=personal"])).as_deref(),
+            Some("personal")
+        );
+        assert_eq!(profile_from_args(args(&["server", "--port", "3000"])), None);
+    }
+}
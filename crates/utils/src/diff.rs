@@ -1,7 +1,7 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet};
 
 use serde::{Deserialize, Serialize};
-use similar::TextDiff;
+use similar::{DiffTag, TextDiff};
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -223,3 +223,177 @@ pub fn normalize_unified_diff(file_path: &str, unified_diff: &str) -> String {
     let hunks = extract_unified_diff_hunks(unified_diff);
     concatenate_diff_hunks(file_path, &hunks)
 }
+
+// ==============================
+// Hunk-level review
+// ==============================
+
+const HUNK_CONTEXT_LINES: usize = 3;
+
+/// A single hunk of a file diff, addressable by `id` so individual changes
+/// can be kept or discarded during review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct DiffHunk {
+    /// Derived from the hunk's position in the old file and its changed
+    /// lines, so it stays the same while other hunks are discarded.
+    pub id: String,
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// The hunk in unified diff format, including the `@@` header.
+    pub patch: String,
+}
+
+/// A hunk together with the change ops it covers, keyed by their
+/// `(old_start, new_start)` offsets in the full op list.
+struct HunkGroup {
+    hunk: DiffHunk,
+    change_keys: Vec<(usize, usize)>,
+}
+
+fn hunk_groups(diff: &TextDiff<'_, '_, '_, str>) -> Vec<HunkGroup> {
+    let old_lines = diff.old_slices();
+    let new_lines = diff.new_slices();
+
+    diff.grouped_ops(HUNK_CONTEXT_LINES)
+        .into_iter()
+        .filter_map(|group| {
+            let (_, first_old, first_new) = group.first()?.as_tag_tuple();
+            let (_, last_old, last_new) = group.last()?.as_tag_tuple();
+
+            let mut body = String::new();
+            let mut changed = String::new();
+            let mut change_keys = Vec::new();
+            let push_lines = |out: &mut String, prefix: char, lines: &[&str]| {
+                for line in lines {
+                    out.push(prefix);
+                    out.push_str(line);
+                    if !line.ends_with('\n') {
+                        out.push('\n');
+                    }
+                }
+            };
+            for op in &group {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                let old = &old_lines[old_range.clone()];
+                let new = &new_lines[new_range.clone()];
+                match tag {
+                    DiffTag::Equal => push_lines(&mut body, ' ', new),
+                    DiffTag::Delete | DiffTag::Insert | DiffTag::Replace => {
+                        change_keys.push((old_range.start, new_range.start));
+                        let start = body.len();
+                        push_lines(&mut body, '-', old);
+                        push_lines(&mut body, '+', new);
+                        changed.push_str(&body[start..]);
+                    }
+                }
+            }
+
+            let old_count = last_old.end - first_old.start;
+            let new_count = last_new.end - first_new.start;
+            // Unified diff convention: an empty range starts at the line before.
+            let old_start = first_old.start + usize::from(old_count > 0);
+            let new_start = first_new.start + usize::from(new_count > 0);
+            let patch = format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@\n{body}");
+
+            Some(HunkGroup {
+                hunk: DiffHunk {
+                    id: hunk_id(first_old.start, &changed),
+                    old_start,
+                    old_lines: old_count,
+                    new_start,
+                    new_lines: new_count,
+                    patch,
+                },
+                change_keys,
+            })
+        })
+        .collect()
+}
+
+/// FNV-1a, so ids are stable across processes and toolchain versions.
+fn hunk_id(old_start: usize, changed: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in old_start.to_le_bytes().iter().chain(changed.as_bytes()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Split the diff between `old` and `new` into reviewable hunks.
+pub fn diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    hunk_groups(&TextDiff::from_lines(old, new))
+        .into_iter()
+        .map(|group| group.hunk)
+        .collect()
+}
+
+/// Rebuild `new` with the hunks in `hunk_ids` reverted to their `old` lines.
+/// Unknown ids are ignored.
+pub fn revert_hunks(old: &str, new: &str, hunk_ids: &HashSet<String>) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let reverted: HashSet<(usize, usize)> = hunk_groups(&diff)
+        .into_iter()
+        .filter(|group| hunk_ids.contains(&group.hunk.id))
+        .flat_map(|group| group.change_keys)
+        .collect();
+
+    let old_lines = diff.old_slices();
+    let new_lines = diff.new_slices();
+    let mut out = String::with_capacity(new.len());
+    for op in diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let revert = reverted.contains(&(old_range.start, new_range.start));
+        let lines = match (tag, revert) {
+            (DiffTag::Equal, _) | (DiffTag::Insert | DiffTag::Replace, false) => {
+                &new_lines[new_range]
+            }
+            (DiffTag::Delete | DiffTag::Replace, true) => &old_lines[old_range],
+            (DiffTag::Delete, false) | (DiffTag::Insert, true) => &[][..],
+        };
+        for line in lines {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+/// A patch of a single hunk of the file at `path`, as `git apply` expects
+/// it. `is_new_file` marks a file the old side doesn't have.
+pub fn hunk_patch(path: &str, hunk: &DiffHunk, is_new_file: bool) -> String {
+    let header = if is_new_file {
+        format!("diff --git a/{path} b/{path}\nnew file mode 100644\n--- /dev/null\n+++ b/{path}\n")
+    } else {
+        format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n")
+    };
+    format!("{header}{}", hunk.patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverting_one_hunk_keeps_the_others() {
+        let old: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line 18\nadded\n");
+
+        let hunks = diff_hunks(&old, &new);
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].patch.starts_with("@@ -1,5 +1,5 @@\n"));
+
+        let first: HashSet<String> = [hunks[0].id.clone()].into();
+        let partial = revert_hunks(&old, &new, &first);
+        assert_eq!(partial, old.replace("line 18\n", "line 18\nadded\n"));
+
+        // The surviving hunk keeps its id after the other one is discarded.
+        assert_eq!(diff_hunks(&old, &partial), vec![hunks[1].clone()]);
+
+        let all: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+        assert_eq!(revert_hunks(&old, &new, &all), old);
+    }
+}
@@ -0,0 +1,399 @@
+//! Running execution processes inside Docker containers. Each process gets
+//! its own `docker run`, with the workspace mounted at its host path so the
+//! paths in prompts, logs and diffs mean the same inside and outside.
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::resource_limits::ResourceLimits;
+
+/// Left to the image's own `PATH`, which the host's would only break.
+const HOST_ONLY_VARS: &[&str] = &["PATH"];
+/// Credentials the agents read from the environment. Host processes inherit
+/// them from the server; a container only sees what is passed in.
+const CREDENTIAL_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "ANTHROPIC_BASE_URL",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "OPENAI_API_KEY",
+    "OPENAI_BASE_URL",
+    "GEMINI_API_KEY",
+    "GOOGLE_API_KEY",
+    "GH_TOKEN",
+    "GITHUB_TOKEN",
+];
+/// Agent settings and logins in the user's home directory.
+const AGENT_CONFIG_DIRS: &[&str] = &[".claude", ".codex"];
+/// `HOME` inside the container, where the user has no home directory.
+const CONTAINER_HOME: &str = "/tmp";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerSandbox {
+    pub image: String,
+    /// Container name, used to look up published ports and to remove it.
+    pub name: String,
+    /// Host directories mounted read-write at the same path.
+    pub mounts: Vec<PathBuf>,
+    /// Host directories mounted read-only in the container's home under
+    /// their own name, e.g. `~/.claude` at `/tmp/.claude`.
+    pub home_mounts: Vec<PathBuf>,
+    /// Container ports published on a free port of 127.0.0.1.
+    pub ports: Vec<u16>,
+    /// Container ports published on the same port of 127.0.0.1, for servers
+    /// the preview expects at a known address.
+    pub fixed_ports: Vec<u16>,
+}
+
+impl DockerSandbox {
+    pub fn container_name(execution_id: Uuid) -> String {
+        format!("vk-exec-{execution_id}")
+    }
+
+    /// The agent config directories in the user's home that exist, to mount
+    /// as `home_mounts`.
+    pub fn agent_config_dirs() -> Vec<PathBuf> {
+        let Some(home) = dirs::home_dir() else {
+            return Vec::new();
+        };
+        AGENT_CONFIG_DIRS
+            .iter()
+            .map(|dir| home.join(dir))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    /// The `docker run` invocation that runs `command` in the container with
+    /// the same arguments, working directory and explicitly set environment,
+    /// plus the agent credentials of the server's own environment. Stdio and
+    /// other spawn options are not carried over.
+    pub fn wrap(&self, command: &Command, limits: &ResourceLimits) -> Command {
+        self.wrap_with_host_env(command, limits, |key| std::env::var_os(key))
+    }
+
+    fn wrap_with_host_env(
+        &self,
+        command: &Command,
+        limits: &ResourceLimits,
+        host_env: impl Fn(&str) -> Option<OsString>,
+    ) -> Command {
+        let std = command.as_std();
+        let mut envs: Vec<(OsString, OsString)> = std
+            .get_envs()
+            .filter_map(|(key, value)| Some((key.to_os_string(), value?.to_os_string())))
+            .filter(|(key, _)| !HOST_ONLY_VARS.iter().any(|var| key == var))
+            .collect();
+        // Variables set or removed on the command take precedence.
+        for var in CREDENTIAL_VARS {
+            if std.get_envs().any(|(key, _)| key == *var) {
+                continue;
+            }
+            if let Some(value) = host_env(var) {
+                envs.push((var.into(), value));
+            }
+        }
+
+        let mut wrapped = Command::new("docker");
+        wrapped.args(self.run_args(
+            std.get_program(),
+            std.get_args(),
+            std.get_current_dir(),
+            envs.iter().map(|(key, _)| key.as_os_str()),
+            limits,
+        ));
+        // Values are passed through the client's environment rather than the
+        // command line, where anyone listing processes could read them.
+        for (key, value) in envs {
+            wrapped.env(key, value);
+        }
+        wrapped
+    }
+
+    fn run_args<'a>(
+        &self,
+        program: &OsStr,
+        args: impl IntoIterator<Item = &'a OsStr>,
+        current_dir: Option<&Path>,
+        env_keys: impl IntoIterator<Item = &'a OsStr>,
+        limits: &ResourceLimits,
+    ) -> Vec<OsString> {
+        let mut run: Vec<OsString> = ["run", "--rm", "-i", "--init", "--name"]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+        run.push(self.name.clone().into());
+
+        // Files the process creates in the worktree stay owned by the user.
+        #[cfg(unix)]
+        {
+            let (uid, gid) = (nix::unistd::getuid(), nix::unistd::getgid());
+            run.push("--user".into());
+            run.push(format!("{uid}:{gid}").into());
+            // That user has no home directory in the image.
+            run.push("-e".into());
+            run.push(format!("HOME={CONTAINER_HOME}").into());
+        }
+
+        for mount in &self.mounts {
+            let mut volume = mount.clone().into_os_string();
+            volume.push(":");
+            volume.push(mount);
+            run.push("-v".into());
+            run.push(volume);
+        }
+        for mount in &self.home_mounts {
+            let Some(name) = mount.file_name() else {
+                continue;
+            };
+            let mut volume = mount.clone().into_os_string();
+            volume.push(":");
+            volume.push(Path::new(CONTAINER_HOME).join(name));
+            volume.push(":ro");
+            run.push("-v".into());
+            run.push(volume);
+        }
+        if let Some(dir) = current_dir {
+            run.push("-w".into());
+            run.push(dir.into());
+        }
+        for key in env_keys {
+            run.push("-e".into());
+            run.push(key.into());
+        }
+        for port in &self.ports {
+            run.push("-p".into());
+            run.push(format!("127.0.0.1::{port}").into());
+        }
+        for port in &self.fixed_ports {
+            run.push("-p".into());
+            run.push(format!("127.0.0.1:{port}:{port}").into());
+        }
+        if let Some(mb) = limits.memory_mb {
+            run.push("--memory".into());
+            run.push(format!("{mb}m").into());
+        }
+        if let Some(percent) = limits.cpu_percent {
+            run.push("--cpus".into());
+            run.push(format!("{:.2}", f64::from(percent) / 100.0).into());
+        }
+
+        run.push(self.image.clone().into());
+        // Host executables are resolved to absolute paths the image won't
+        // have, so the image's `PATH` finds its own copy by name.
+        run.push(
+            Path::new(program)
+                .file_name()
+                .unwrap_or(program)
+                .to_os_string(),
+        );
+        run.extend(args.into_iter().map(OsStr::to_os_string));
+        run
+    }
+}
+
+async fn docker(args: &[&str]) -> std::io::Result<String> {
+    let output = Command::new("docker").args(args).output().await?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "docker {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The 127.0.0.1 port `container_port` of container `name` is published on.
+pub async fn published_port(name: &str, container_port: u16) -> std::io::Result<Option<u16>> {
+    let output = docker(&["port", name, &format!("{container_port}/tcp")]).await?;
+    Ok(parse_published_port(&output))
+}
+
+/// Stop and remove container `name`. Killing the `docker run` client leaves
+/// the container running, so this has to follow every kill.
+pub async fn remove_container(name: &str) -> std::io::Result<()> {
+    docker(&["rm", "--force", name]).await.map(|_| ())
+}
+
+/// Run a `docker pause`, `unpause` or `kill` style command on container
+/// `name`, e.g. `["kill", "--signal", "INT"]`.
+pub async fn control_container(command: &[&str], name: &str) -> std::io::Result<()> {
+    let mut args = command.to_vec();
+    args.push(name);
+    docker(&args).await.map(|_| ())
+}
+
+/// The host port in `docker port` output, e.g. `127.0.0.1:49153`.
+fn parse_published_port(output: &str) -> Option<u16> {
+    output
+        .lines()
+        .find_map(|line| line.trim().rsplit_once(':')?.1.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_command_in_docker_run() {
+        let sandbox = DockerSandbox {
+            image: "node:22".to_string(),
+            name: "vk-exec-test".to_string(),
+            mounts: vec![PathBuf::from("/work/ws")],
+            home_mounts: Vec::new(),
+            ports: vec![3000],
+            fixed_ports: Vec::new(),
+        };
+        let mut command = Command::new("/usr/local/bin/npx");
+        command
+            .args(["-y", "agent", "--flag value"])
+            .current_dir("/work/ws/repo")
+            .env("API_KEY", "secret")
+            .env("PATH", "/host/bin");
+        let limits = ResourceLimits {
+            memory_mb: Some(2048),
+            cpu_percent: Some(150),
+        };
+
+        let wrapped = sandbox.wrap_with_host_env(&command, &limits, |_| None);
+        let std = wrapped.as_std();
+        assert_eq!(std.get_program(), "docker");
+        let args: Vec<_> = std
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let joined = args.join(" ");
+        assert!(joined.starts_with("run --rm -i --init --name vk-exec-test"));
+        assert!(joined.contains("-v /work/ws:/work/ws -w /work/ws/repo -e API_KEY"));
+        assert!(joined.contains("-p 127.0.0.1::3000 --memory 2048m --cpus 1.50"));
+        assert!(joined.ends_with("node:22 npx -y agent --flag value"));
+        assert!(!joined.contains("secret"));
+        assert!(!joined.contains("-e PATH"));
+        assert!(
+            std.get_envs()
+                .any(|(key, value)| key == "API_KEY" && value == Some(OsStr::new("secret")))
+        );
+    }
+
+    fn sandbox() -> DockerSandbox {
+        DockerSandbox {
+            image: "node:22".to_string(),
+            name: "vk-exec-test".to_string(),
+            mounts: vec![PathBuf::from("/work/ws")],
+            home_mounts: Vec::new(),
+            ports: Vec::new(),
+            fixed_ports: Vec::new(),
+        }
+    }
+
+    fn args(command: &Command) -> String {
+        command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn env<'a>(command: &'a Command, key: &str) -> Option<&'a OsStr> {
+        command
+            .as_std()
+            .get_envs()
+            .find(|(k, _)| *k == key)
+            .and_then(|(_, value)| value)
+    }
+
+    #[test]
+    fn forwards_agent_credentials_from_the_host_environment() {
+        let mut command = Command::new("claude");
+        command
+            .env("OPENAI_API_KEY", "workspace-key")
+            .env_remove("GH_TOKEN");
+        let host_env = |key: &str| match key {
+            "ANTHROPIC_API_KEY" => Some(OsString::from("host-anthropic")),
+            "OPENAI_API_KEY" => Some(OsString::from("host-openai")),
+            "GH_TOKEN" => Some(OsString::from("host-gh")),
+            _ => None,
+        };
+
+        let wrapped = sandbox().wrap_with_host_env(&command, &ResourceLimits::default(), host_env);
+        let joined = args(&wrapped);
+        assert!(joined.contains("-e ANTHROPIC_API_KEY"));
+        assert!(!joined.contains("host-anthropic"));
+        assert_eq!(
+            env(&wrapped, "ANTHROPIC_API_KEY"),
+            Some(OsStr::new("host-anthropic"))
+        );
+        // Set or removed on the command wins over the host.
+        assert_eq!(
+            env(&wrapped, "OPENAI_API_KEY"),
+            Some(OsStr::new("workspace-key"))
+        );
+        assert!(!joined.contains("-e GH_TOKEN"));
+        assert_eq!(env(&wrapped, "GH_TOKEN"), None);
+        assert!(!joined.contains("-e GEMINI_API_KEY"));
+    }
+
+    #[test]
+    fn mounts_agent_config_read_only_in_the_container_home() {
+        let sandbox = DockerSandbox {
+            home_mounts: vec![
+                PathBuf::from("/home/me/.claude"),
+                PathBuf::from("/home/me/.codex"),
+            ],
+            ..sandbox()
+        };
+
+        let joined = args(&sandbox.wrap_with_host_env(
+            &Command::new("codex"),
+            &ResourceLimits::default(),
+            |_| None,
+        ));
+        assert!(joined.contains("-v /home/me/.claude:/tmp/.claude:ro"));
+        assert!(joined.contains("-v /home/me/.codex:/tmp/.codex:ro"));
+        assert!(joined.contains("-v /work/ws:/work/ws "));
+    }
+
+    #[test]
+    fn publishes_fixed_ports_on_the_same_host_port() {
+        let sandbox = DockerSandbox {
+            ports: vec![3000],
+            fixed_ports: vec![4173],
+            ..sandbox()
+        };
+
+        let joined = args(&sandbox.wrap_with_host_env(
+            &Command::new("npm"),
+            &ResourceLimits::default(),
+            |_| None,
+        ));
+        assert!(joined.contains("-p 127.0.0.1::3000 -p 127.0.0.1:4173:4173"));
+    }
+
+    #[test]
+    fn runs_without_limits_by_default() {
+        let joined = args(&sandbox().wrap_with_host_env(
+            &Command::new("npm"),
+            &ResourceLimits::default(),
+            |_| None,
+        ));
+        assert!(!joined.contains("--memory"));
+        assert!(!joined.contains("--cpus"));
+        assert!(joined.ends_with("node:22 npm"));
+    }
+
+    #[test]
+    fn parses_published_port() {
+        assert_eq!(parse_published_port("127.0.0.1:49153\n"), Some(49153));
+        assert_eq!(
+            parse_published_port("0.0.0.0:8080\n[::]:8080\n"),
+            Some(8080)
+        );
+        assert_eq!(parse_published_port(""), None);
+    }
+}
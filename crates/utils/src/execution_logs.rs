@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    io::Write as _,
+    path::{Path, PathBuf},
+};
 
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
@@ -6,6 +9,13 @@ use uuid::Uuid;
 use crate::{assets::asset_dir, log_msg::LogMsg};
 
 pub const EXECUTION_LOGS_DIRNAME: &str = "sessions";
+/// Extension of log files compressed by log retention.
+pub const COMPRESSED_LOG_EXTENSION: &str = "jsonl.zst";
+const COMPRESSION_LEVEL: i32 = 3;
+
+pub fn execution_logs_root() -> PathBuf {
+    asset_dir().join(EXECUTION_LOGS_DIRNAME)
+}
 
 pub fn process_logs_session_dir(session_id: Uuid) -> PathBuf {
     resolve_process_logs_session_dir(&asset_dir(), session_id)
@@ -50,10 +60,91 @@ impl ExecutionLogWriter {
     pub async fn append_jsonl_line(&mut self, jsonl_line: &str) -> std::io::Result<()> {
         self.file.write_all(jsonl_line.as_bytes()).await
     }
+
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush().await
+    }
 }
 
+pub fn compressed_log_file_path(path: &Path) -> PathBuf {
+    path.with_extension(COMPRESSED_LOG_EXTENSION)
+}
+
+/// Read a process log, falling back to its zstd-compressed form when the
+/// plain file has been compacted away.
 pub async fn read_execution_log_file(path: &Path) -> std::io::Result<String> {
-    tokio::fs::read_to_string(path).await
+    match tokio::fs::read_to_string(path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let compressed = tokio::fs::read(compressed_log_file_path(path)).await?;
+            let bytes = tokio::task::spawn_blocking(move || zstd::decode_all(&compressed[..]))
+                .await
+                .map_err(std::io::Error::other)??;
+            String::from_utf8(bytes)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        }
+        result => result,
+    }
+}
+
+/// Replace a plain `.jsonl` log with a `.jsonl.zst` copy, keeping the
+/// original modification time so age-based retention still sees the log's
+/// real age. Returns the compressed size in bytes.
+pub async fn compress_execution_log_file(path: &Path) -> std::io::Result<u64> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let modified = std::fs::metadata(&path)?.modified()?;
+        let compressed = zstd::encode_all(std::fs::File::open(&path)?, COMPRESSION_LEVEL)?;
+
+        write_compressed_log_file(&compressed_log_file_path(&path), &compressed, modified)?;
+        std::fs::remove_file(&path)?;
+        Ok(compressed.len() as u64)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Append JSONL lines to a finished process log in whichever form it is
+/// stored, recompressing logs that retention has compressed. The log keeps
+/// its modification time so retention doesn't mistake it for a new one.
+pub async fn append_to_execution_log_file(path: &Path, jsonl: &str) -> std::io::Result<()> {
+    let path = path.to_path_buf();
+    let jsonl = jsonl.to_string();
+    tokio::task::spawn_blocking(move || {
+        match std::fs::OpenOptions::new().append(true).open(&path) {
+            Ok(mut file) => {
+                let modified = file.metadata()?.modified()?;
+                file.write_all(jsonl.as_bytes())?;
+                return file.set_modified(modified);
+            }
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            Err(_) => {}
+        }
+
+        let target = compressed_log_file_path(&path);
+        let modified = std::fs::metadata(&target)?.modified()?;
+        let mut contents = zstd::decode_all(std::fs::File::open(&target)?)?;
+        contents.extend_from_slice(jsonl.as_bytes());
+        let compressed = zstd::encode_all(&contents[..], COMPRESSION_LEVEL)?;
+        write_compressed_log_file(&target, &compressed, modified)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Write a compressed log through a temporary file so readers never see a
+/// partial one.
+fn write_compressed_log_file(
+    target: &Path,
+    compressed: &[u8],
+    modified: std::time::SystemTime,
+) -> std::io::Result<()> {
+    let temp = target.with_extension("zst.tmp");
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(compressed)?;
+    file.set_modified(modified)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp, target)
 }
 
 pub fn parse_log_jsonl_lossy(execution_id: Uuid, jsonl: &str) -> Vec<LogMsg> {
@@ -101,3 +192,42 @@ fn resolve_process_logs_session_dir(root: &Path, session_id: Uuid) -> PathBuf {
         .join(uuid_prefix2(session_id))
         .join(session_id.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn appends_to_plain_and_compressed_logs_keeping_their_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("process.jsonl");
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+
+        std::fs::write(&path, "a\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        append_to_execution_log_file(&path, "b\n").await.unwrap();
+        assert_eq!(read_execution_log_file(&path).await.unwrap(), "a\nb\n");
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), old);
+
+        compress_execution_log_file(&path).await.unwrap();
+        append_to_execution_log_file(&path, "c\n").await.unwrap();
+        assert!(!path.exists());
+        assert_eq!(read_execution_log_file(&path).await.unwrap(), "a\nb\nc\n");
+        let compressed = compressed_log_file_path(&path);
+        assert_eq!(
+            std::fs::metadata(&compressed).unwrap().modified().unwrap(),
+            old
+        );
+
+        let missing = dir.path().join("missing.jsonl");
+        let err = append_to_execution_log_file(&missing, "d\n")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,344 @@
+//! Incremental decoding of newline-delimited JSON from executor stdout.
+//!
+//! Executors write one compact JSON object per line, but a single line can
+//! grow to megabytes (command output, long assistant replies) and arrive in
+//! many chunks. [`JsonLineDecoder`] splits chunks into lines without rescanning
+//! the buffered tail, and [`scan_string_field`] decodes the prefix of a string
+//! field from a line that is still arriving so it can be shown early.
+
+use futures::{Stream, StreamExt, stream::BoxStream};
+
+/// Splits chunked stdout into complete lines in linear time.
+#[derive(Debug, Default)]
+pub struct JsonLineDecoder {
+    buffer: String,
+    /// Bytes of `buffer` already known not to contain a newline.
+    scanned: usize,
+}
+
+impl JsonLineDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk and return the lines it completed, without their
+    /// trailing newline.
+    pub fn push(&mut self, chunk: &str) -> Vec<String> {
+        self.buffer.push_str(chunk);
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut search_from = self.scanned;
+        while let Some(offset) = self.buffer[search_from..].find('\n') {
+            let end = search_from + offset;
+            lines.push(self.buffer[start..end].to_owned());
+            start = end + 1;
+            search_from = start;
+        }
+
+        if start > 0 {
+            self.buffer.drain(..start);
+        }
+        self.scanned = self.buffer.len();
+        lines
+    }
+
+    /// The incomplete line received so far.
+    pub fn pending(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Take the incomplete line, e.g. once the stream has ended.
+    pub fn finish(&mut self) -> String {
+        self.scanned = 0;
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+/// Item of [`json_line_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonLineEvent {
+    Line(String),
+    /// Snapshot of a long line that is still arriving.
+    Partial(String),
+}
+
+/// Split chunked stdout into lines, interleaved with snapshots of any line
+/// that has grown past `preview_min_bytes` without ending. Snapshots are
+/// spaced geometrically so scanning them stays linear in the line length.
+pub fn json_line_events<S>(chunks: S, preview_min_bytes: usize) -> BoxStream<'static, JsonLineEvent>
+where
+    S: Stream<Item = Result<String, std::io::Error>> + Send + 'static,
+{
+    let state = (
+        chunks.boxed(),
+        JsonLineDecoder::new(),
+        preview_min_bytes,
+        false,
+    );
+    futures::stream::unfold(
+        state,
+        move |(mut chunks, mut decoder, mut next_preview_at, finished)| async move {
+            if finished {
+                return None;
+            }
+            let mut events = Vec::new();
+            let finished = match chunks.next().await {
+                Some(Ok(chunk)) => {
+                    events.extend(decoder.push(&chunk).into_iter().map(JsonLineEvent::Line));
+                    if !events.is_empty() {
+                        next_preview_at = preview_min_bytes;
+                    }
+                    let pending_len = decoder.pending().len();
+                    if pending_len >= next_preview_at {
+                        next_preview_at = pending_len + pending_len / 2;
+                        events.push(JsonLineEvent::Partial(decoder.pending().to_owned()));
+                    }
+                    false
+                }
+                Some(Err(_)) => false,
+                None => {
+                    let remaining = decoder.finish();
+                    if !remaining.is_empty() {
+                        events.push(JsonLineEvent::Line(remaining));
+                    }
+                    true
+                }
+            };
+            Some((
+                futures::stream::iter(events),
+                (chunks, decoder, next_preview_at, finished),
+            ))
+        },
+    )
+    .flatten()
+    .boxed()
+}
+
+/// A string field decoded from a possibly truncated line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringField {
+    /// The decoded value, or the decodable prefix of it.
+    pub value: String,
+    /// Whether the closing quote has been received.
+    pub complete: bool,
+    /// Byte offset in the line just past the decoded part.
+    pub end: usize,
+}
+
+/// Decode the string value of `"key":"..."` at or after byte offset `from` of
+/// a line of compact JSON that may be cut off at any point.
+///
+/// Inside a JSON string every quote is escaped, so the unescaped `"key":"`
+/// pattern only matches object keys. Returns `None` if the key has not been
+/// received yet, its value is not a string, or the value is malformed.
+pub fn scan_string_field(line: &str, key: &str, from: usize) -> Option<StringField> {
+    let pattern = format!("\"{key}\":\"");
+    let value_start = from + line.get(from..)?.find(&pattern)? + pattern.len();
+    decode_partial_string(line, value_start)
+}
+
+fn decode_partial_string(line: &str, start: usize) -> Option<StringField> {
+    let bytes = line.as_bytes();
+    let mut value = String::new();
+    let mut run_start = start;
+    let mut i = start;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                value.push_str(&line[run_start..i]);
+                return Some(StringField {
+                    value,
+                    complete: true,
+                    end: i + 1,
+                });
+            }
+            b'\\' => {
+                value.push_str(&line[run_start..i]);
+                let Some(&escape) = bytes.get(i + 1) else {
+                    return Some(truncated(value, i));
+                };
+                let consumed = match escape {
+                    b'"' => push_char(&mut value, '"'),
+                    b'\\' => push_char(&mut value, '\\'),
+                    b'/' => push_char(&mut value, '/'),
+                    b'b' => push_char(&mut value, '\u{8}'),
+                    b'f' => push_char(&mut value, '\u{c}'),
+                    b'n' => push_char(&mut value, '\n'),
+                    b'r' => push_char(&mut value, '\r'),
+                    b't' => push_char(&mut value, '\t'),
+                    b'u' => match decode_unicode_escape(bytes, i) {
+                        UnicodeEscape::Char(ch, len) => {
+                            value.push(ch);
+                            len
+                        }
+                        UnicodeEscape::Incomplete => return Some(truncated(value, i)),
+                        UnicodeEscape::Invalid => return None,
+                    },
+                    _ => return None,
+                };
+                i += consumed;
+                run_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    value.push_str(&line[run_start..]);
+    Some(truncated(value, bytes.len()))
+}
+
+fn truncated(value: String, end: usize) -> StringField {
+    StringField {
+        value,
+        complete: false,
+        end,
+    }
+}
+
+fn push_char(value: &mut String, ch: char) -> usize {
+    value.push(ch);
+    2
+}
+
+enum UnicodeEscape {
+    /// The decoded character and the number of bytes consumed.
+    Char(char, usize),
+    Incomplete,
+    Invalid,
+}
+
+/// Decode a `\uXXXX` escape at `at`, joining a surrogate pair if present.
+fn decode_unicode_escape(bytes: &[u8], at: usize) -> UnicodeEscape {
+    let Some(high) = read_hex4(bytes, at + 2) else {
+        return incomplete_or_invalid(bytes, at + 2, 4);
+    };
+
+    if !(0xD800..0xDC00).contains(&high) {
+        return match char::from_u32(high) {
+            Some(ch) => UnicodeEscape::Char(ch, 6),
+            // A lone low surrogate.
+            None => UnicodeEscape::Char(char::REPLACEMENT_CHARACTER, 6),
+        };
+    }
+
+    // A high surrogate must be followed by `\u` and a low surrogate.
+    let next = at + 6;
+    match (bytes.get(next), bytes.get(next + 1)) {
+        (None, _) | (Some(b'\\'), None) => return UnicodeEscape::Incomplete,
+        (Some(b'\\'), Some(b'u')) => {}
+        _ => return UnicodeEscape::Char(char::REPLACEMENT_CHARACTER, 6),
+    }
+    let Some(low) = read_hex4(bytes, next + 2) else {
+        return incomplete_or_invalid(bytes, next + 2, 4);
+    };
+    if !(0xDC00..0xE000).contains(&low) {
+        return UnicodeEscape::Char(char::REPLACEMENT_CHARACTER, 6);
+    }
+    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+    match char::from_u32(code) {
+        Some(ch) => UnicodeEscape::Char(ch, 12),
+        None => UnicodeEscape::Invalid,
+    }
+}
+
+fn read_hex4(bytes: &[u8], at: usize) -> Option<u32> {
+    let digits = bytes.get(at..at + 4)?;
+    let digits = std::str::from_utf8(digits).ok()?;
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Whether fewer than `len` hex digits at `at` is just a cut-off line.
+fn incomplete_or_invalid(bytes: &[u8], at: usize, len: usize) -> UnicodeEscape {
+    let available = bytes.get(at..).unwrap_or_default();
+    if available.len() < len && available.iter().all(u8::is_ascii_hexdigit) {
+        UnicodeEscape::Incomplete
+    } else {
+        UnicodeEscape::Invalid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoder_splits_lines_across_chunks() {
+        let mut decoder = JsonLineDecoder::new();
+        assert!(decoder.push("{\"a\":").is_empty());
+        assert_eq!(decoder.push("1}\n{\"b\""), vec!["{\"a\":1}"]);
+        assert_eq!(decoder.pending(), "{\"b\"");
+        assert_eq!(decoder.push(":2}\n\n"), vec!["{\"b\":2}", ""]);
+        assert_eq!(decoder.pending(), "");
+        assert!(decoder.push("tail").is_empty());
+        assert_eq!(decoder.finish(), "tail");
+        assert_eq!(decoder.pending(), "");
+    }
+
+    #[test]
+    fn line_events_preview_long_lines() {
+        let chunks = ["{\"t\":\"", "abcd", "efgh", "\"}\n{}", ""].map(|chunk| Ok(chunk.to_owned()));
+        let events: Vec<_> =
+            crate::tokio::block_on(json_line_events(futures::stream::iter(chunks), 8).collect());
+        assert_eq!(
+            events,
+            vec![
+                JsonLineEvent::Partial("{\"t\":\"abcd".to_owned()),
+                JsonLineEvent::Line("{\"t\":\"abcdefgh\"}".to_owned()),
+                JsonLineEvent::Line("{}".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_complete_and_truncated_fields() {
+        let line = r#"{"id":"abc","content":"line one\nline \"two\""#;
+        let id = scan_string_field(line, "id", 0).unwrap();
+        assert_eq!(id.value, "abc");
+        assert!(id.complete);
+
+        let content = scan_string_field(line, "content", id.end).unwrap();
+        assert_eq!(content.value, "line one\nline \"two\"");
+        assert!(!content.complete);
+
+        assert!(scan_string_field(line, "missing", 0).is_none());
+    }
+
+    #[test]
+    fn ignores_keys_inside_string_values() {
+        let line = r#"{"text":"say \"id\":\"no\"","id":"yes"}"#;
+        assert_eq!(scan_string_field(line, "id", 0).unwrap().value, "yes");
+    }
+
+    #[test]
+    fn stops_before_cut_off_escapes() {
+        assert_eq!(
+            scan_string_field(r#"{"t":"ab\"#, "t", 0).unwrap().value,
+            "ab"
+        );
+        assert_eq!(
+            scan_string_field(r#"{"t":"ab\u00"#, "t", 0).unwrap().value,
+            "ab"
+        );
+        // Half of a surrogate pair waits for the other half.
+        assert_eq!(
+            scan_string_field(r#"{"t":"ab\ud83d"#, "t", 0)
+                .unwrap()
+                .value,
+            "ab"
+        );
+        assert_eq!(
+            scan_string_field(r#"{"t":"ab\ud83d\ude00c"#, "t", 0)
+                .unwrap()
+                .value,
+            "ab\u{1F600}c"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_escapes() {
+        assert!(scan_string_field(r#"{"t":"\q"}"#, "t", 0).is_none());
+        assert!(scan_string_field(r#"{"t":"\uzzzz"}"#, "t", 0).is_none());
+    }
+}
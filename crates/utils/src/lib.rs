@@ -2,23 +2,30 @@ use std::{env, sync::OnceLock};
 
 use directories::ProjectDirs;
 
+pub mod alloc_stats;
 pub mod approvals;
 pub mod assets;
 pub mod browser;
 pub mod command_ext;
+pub mod config_profile;
 pub mod diff;
+pub mod docker;
 pub mod execution_logs;
 pub mod http_headers;
+pub mod json_stream;
 pub mod jwt;
 pub mod log_msg;
 pub mod msg_store;
 pub mod path;
 pub mod port_file;
 pub mod process;
+pub mod process_tree;
+pub mod resource_limits;
 pub mod response;
 pub mod sentry;
 pub mod shell;
 pub mod stream_lines;
+pub mod terminal_recordings;
 pub mod text;
 pub mod tokio;
 pub mod version;
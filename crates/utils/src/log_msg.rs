@@ -1,6 +1,7 @@
 use axum::{extract::ws::Message, response::sse::Event};
 use json_patch::Patch;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 pub const EV_STDOUT: &str = "stdout";
 pub const EV_STDERR: &str = "stderr";
@@ -9,6 +10,51 @@ pub const EV_SESSION_ID: &str = "session_id";
 pub const EV_MESSAGE_ID: &str = "message_id";
 pub const EV_READY: &str = "ready";
 pub const EV_FINISHED: &str = "finished";
+/// Sent instead of messages a slow subscriber missed.
+pub const EV_RESYNC: &str = "resync";
+pub const EV_SPAN: &str = "span";
+
+/// Structured record of an agent action, derived from the normalized
+/// conversation and stored alongside the raw output. Logs written before
+/// spans existed (format v1) only hold raw output; a one-shot migration at
+/// startup adds their spans.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "span", rename_all = "snake_case")]
+pub enum LogSpan {
+    /// A tool call started.
+    ToolStart {
+        /// Identifies the tool call; matches the `ToolEnd` span that closes it.
+        id: String,
+        tool_name: String,
+        /// Kind of action, e.g. `command_run` or `file_edit`.
+        action: String,
+        command: Option<String>,
+        /// Files the tool reads or changes.
+        files: Vec<String>,
+        timestamp: Option<String>,
+    },
+    /// A tool call finished, failed or was refused.
+    ToolEnd {
+        id: String,
+        status: LogSpanStatus,
+        exit_code: Option<i32>,
+        timestamp: Option<String>,
+    },
+    /// Context window usage reported by the agent.
+    Tokens {
+        total_tokens: u32,
+        model_context_window: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSpanStatus {
+    Success,
+    Failed,
+    Denied,
+    TimedOut,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LogMsg {
@@ -17,6 +63,7 @@ pub enum LogMsg {
     JsonPatch(Patch),
     SessionId(String),
     MessageId(String),
+    Span(LogSpan),
     Ready,
     Finished,
 }
@@ -29,6 +76,7 @@ impl LogMsg {
             LogMsg::JsonPatch(_) => EV_JSON_PATCH,
             LogMsg::SessionId(_) => EV_SESSION_ID,
             LogMsg::MessageId(_) => EV_MESSAGE_ID,
+            LogMsg::Span(_) => EV_SPAN,
             LogMsg::Ready => EV_READY,
             LogMsg::Finished => EV_FINISHED,
         }
@@ -44,6 +92,10 @@ impl LogMsg {
             }
             LogMsg::SessionId(s) => Event::default().event(EV_SESSION_ID).data(s.clone()),
             LogMsg::MessageId(s) => Event::default().event(EV_MESSAGE_ID).data(s.clone()),
+            LogMsg::Span(span) => {
+                let data = serde_json::to_string(span).unwrap_or_else(|_| "{}".to_string());
+                Event::default().event(EV_SPAN).data(data)
+            }
             LogMsg::Ready => Event::default().event(EV_READY).data(""),
             LogMsg::Finished => Event::default().event(EV_FINISHED).data(""),
         }
@@ -77,8 +129,188 @@ impl LogMsg {
             }
             LogMsg::SessionId(s) => EV_SESSION_ID.len() + s.len() + OVERHEAD,
             LogMsg::MessageId(s) => EV_MESSAGE_ID.len() + s.len() + OVERHEAD,
+            LogMsg::Span(span) => {
+                let json_len = serde_json::to_string(span).map(|s| s.len()).unwrap_or(2);
+                EV_SPAN.len() + json_len + OVERHEAD
+            }
             LogMsg::Ready => EV_READY.len() + OVERHEAD,
             LogMsg::Finished => EV_FINISHED.len() + OVERHEAD,
         }
     }
 }
+
+/// Index of the conversation entry a patch operation targets, for paths like
+/// `/entries/3` or `/entries/3/content`.
+fn entry_index(path: &str) -> Option<usize> {
+    path.strip_prefix("/entries/")?
+        .split('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Resumes a normalized log stream for a client that reconnects.
+///
+/// Event ids are the highest entry index sent so far. Entry indexes are the
+/// same in the live stream and in the deduplicated replay of stored logs, and
+/// don't shift when old messages are evicted from history, so an id from one
+/// connection is still meaningful on the next.
+#[derive(Debug, Default)]
+pub struct LogResume {
+    since: Option<usize>,
+    last: Option<usize>,
+}
+
+impl LogResume {
+    /// `since` is the id of the last event the client received, if any.
+    pub fn new(since: Option<usize>) -> Self {
+        Self { since, last: since }
+    }
+
+    /// Id for the next event, once any entry has been sent.
+    pub fn last_event_id(&self) -> Option<String> {
+        self.last.map(|last| last.to_string())
+    }
+
+    /// Returns the patch to send, or `None` if it only touches entries the
+    /// client already has in full. Everything from entry `since` on is sent
+    /// again, since the client may have seen only part of its updates; adds of
+    /// entries it already has become replaces so they aren't duplicated.
+    pub fn resume(&mut self, mut patch: Patch) -> Option<Patch> {
+        let indexes: Vec<usize> = patch
+            .0
+            .iter()
+            .filter_map(|op| entry_index(op.path().as_str()))
+            .collect();
+
+        if let Some(since) = self.since {
+            if !indexes.is_empty() && indexes.iter().all(|index| *index < since) {
+                return None;
+            }
+            for op in &mut patch.0 {
+                if let json_patch::PatchOperation::Add(add) = op
+                    && entry_index(add.path.as_str()).is_some_and(|index| index <= since)
+                {
+                    *op = json_patch::PatchOperation::Replace(json_patch::ReplaceOperation {
+                        path: add.path.clone(),
+                        value: add.value.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(max) = indexes.into_iter().max() {
+            self.last = Some(self.last.map_or(max, |last| last.max(max)));
+        }
+        Some(patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn add(index: usize, text: &str) -> Patch {
+        serde_json::from_value(json!([
+            {"op": "add", "path": format!("/entries/{index}"), "value": text}
+        ]))
+        .unwrap()
+    }
+
+    fn replace(index: usize, text: &str) -> Patch {
+        serde_json::from_value(json!([
+            {"op": "replace", "path": format!("/entries/{index}"), "value": text}
+        ]))
+        .unwrap()
+    }
+
+    /// Streams `patches` to a client holding `doc`, returning the id of the
+    /// last event it received.
+    fn stream(
+        doc: &mut Value,
+        resume: &mut LogResume,
+        patches: impl IntoIterator<Item = Patch>,
+    ) -> Option<String> {
+        for patch in patches {
+            if let Some(patch) = resume.resume(patch) {
+                json_patch::patch(doc, &patch).unwrap();
+            }
+        }
+        resume.last_event_id()
+    }
+
+    fn live_patches() -> Vec<Patch> {
+        vec![
+            add(0, "user prompt"),
+            add(1, "thinking"),
+            replace(1, "thinking harder"),
+            add(2, "running"),
+            replace(2, "ran"),
+            add(3, "done"),
+        ]
+    }
+
+    fn final_state() -> Value {
+        json!({"entries": ["user prompt", "thinking harder", "ran", "done"]})
+    }
+
+    #[test]
+    fn resuming_mid_run_picks_up_the_rest_of_the_live_stream() {
+        let mut doc = json!({"entries": []});
+        let patches = live_patches();
+        let id = stream(&mut doc, &mut LogResume::new(None), patches[..2].to_vec());
+        assert_eq!(id.as_deref(), Some("1"));
+        assert_eq!(doc, json!({"entries": ["user prompt", "thinking"]}));
+
+        let since = id.unwrap().parse().ok();
+        let id = stream(&mut doc, &mut LogResume::new(since), patches);
+        assert_eq!(id.as_deref(), Some("3"));
+        assert_eq!(doc, final_state());
+    }
+
+    #[test]
+    fn resuming_after_completion_replays_only_the_missing_entries() {
+        let mut doc = json!({"entries": []});
+        let id = stream(
+            &mut doc,
+            &mut LogResume::new(None),
+            live_patches()[..2].to_vec(),
+        );
+
+        // Once the process has finished, stored logs replay one patch per
+        // entry with its final value.
+        let replay = vec![
+            add(0, "user prompt"),
+            add(1, "thinking harder"),
+            add(2, "ran"),
+            add(3, "done"),
+        ];
+        let since = id.unwrap().parse().ok();
+        let mut resume = LogResume::new(since);
+        let id = stream(&mut doc, &mut resume, replay.clone());
+        assert_eq!(id.as_deref(), Some("3"));
+        assert_eq!(doc, final_state());
+        assert!(resume.resume(add(0, "user prompt")).is_none());
+
+        // A client that was fully caught up gets nothing it already has.
+        let mut resume = LogResume::new(Some(3));
+        let sent: Vec<Patch> = replay
+            .into_iter()
+            .filter_map(|patch| resume.resume(patch))
+            .collect();
+        assert_eq!(sent, vec![replace(3, "done")]);
+    }
+
+    #[test]
+    fn patches_outside_the_entries_are_always_sent() {
+        let options: Patch = serde_json::from_value(json!([
+            {"op": "replace", "path": "/options", "value": {}}
+        ]))
+        .unwrap();
+        let mut resume = LogResume::new(Some(5));
+        assert_eq!(resume.resume(options.clone()), Some(options));
+        assert_eq!(resume.last_event_id().as_deref(), Some("5"));
+    }
+}
@@ -1,26 +1,88 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     sync::{Arc, RwLock},
 };
 
+use axum::{extract::ws::Message, response::sse::Event};
 use futures::{StreamExt, future};
+use json_patch::{Patch, PatchOperation};
 use tokio::{sync::broadcast, task::JoinHandle};
 use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
 
-use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
+use crate::{
+    log_msg::{EV_RESYNC, LogMsg},
+    stream_lines::LinesStreamExt,
+};
 
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
 
 #[derive(Clone)]
 struct StoredMsg {
+    seq: u64,
     msg: LogMsg,
     bytes: usize,
+    /// Produced by [`MsgStore::compact_patches`]; stands for every message
+    /// up to and including `seq`.
+    snapshot: bool,
 }
 
 struct Inner {
     history: VecDeque<StoredMsg>,
     total_bytes: usize,
+    /// Sequence number of the last pushed message; the first is 1.
+    last_seq: u64,
+}
+
+/// Item of [`MsgStore::sequenced_history_plus_stream`].
+#[derive(Clone, Debug)]
+pub enum SequencedMsg {
+    Msg {
+        seq: u64,
+        msg: LogMsg,
+    },
+    /// The subscriber fell behind and messages `from_seq..=to_seq` were
+    /// dropped for it.
+    Missed {
+        from_seq: u64,
+        to_seq: u64,
+    },
+}
+
+impl SequencedMsg {
+    /// SSE event carrying the sequence number as its id, or a `resync`
+    /// event naming the missed range.
+    pub fn to_sse_event(&self) -> Event {
+        match self {
+            SequencedMsg::Msg { seq, msg } => msg.to_sse_event().id(seq.to_string()),
+            SequencedMsg::Missed { from_seq, to_seq } => Event::default()
+                .event(EV_RESYNC)
+                .id(to_seq.to_string())
+                .data(serde_json::json!({ "from_seq": from_seq, "to_seq": to_seq }).to_string()),
+        }
+    }
+
+    /// The message's WebSocket JSON with its sequence number added as `seq`,
+    /// or `{"resync": {"from_seq", "to_seq"}}` for a missed range.
+    pub fn to_ws_message(&self) -> Message {
+        let value = match self {
+            SequencedMsg::Msg { seq, msg } => {
+                let mut value = match msg {
+                    LogMsg::Ready => serde_json::json!({ "Ready": true }),
+                    LogMsg::Finished => serde_json::json!({ "finished": true }),
+                    _ => serde_json::to_value(msg)
+                        .unwrap_or_else(|_| serde_json::json!({ "error": "serialization_failed" })),
+                };
+                value["seq"] = (*seq).into();
+                value
+            }
+            SequencedMsg::Missed { from_seq, to_seq } => serde_json::json!({
+                "seq": to_seq,
+                EV_RESYNC: { "from_seq": from_seq, "to_seq": to_seq },
+            }),
+        };
+        Message::Text(value.to_string().into())
+    }
 }
 
 pub struct MsgStore {
@@ -41,16 +103,20 @@ impl MsgStore {
             inner: RwLock::new(Inner {
                 history: VecDeque::with_capacity(32),
                 total_bytes: 0,
+                last_seq: 0,
             }),
             sender,
         }
     }
 
     pub fn push(&self, msg: LogMsg) {
-        let _ = self.sender.send(msg.clone()); // live listeners
         let bytes = msg.approx_bytes();
 
+        // Send under the lock so sequence numbers match broadcast order.
         let mut inner = self.inner.write().unwrap();
+        inner.last_seq += 1;
+        let seq = inner.last_seq;
+        let _ = self.sender.send(msg.clone()); // live listeners
         while inner.total_bytes.saturating_add(bytes) > HISTORY_BYTES {
             if let Some(front) = inner.history.pop_front() {
                 inner.total_bytes = inner.total_bytes.saturating_sub(front.bytes);
@@ -58,7 +124,12 @@ impl MsgStore {
                 break;
             }
         }
-        inner.history.push_back(StoredMsg { msg, bytes });
+        inner.history.push_back(StoredMsg {
+            seq,
+            msg,
+            bytes,
+            snapshot: false,
+        });
         inner.total_bytes = inner.total_bytes.saturating_add(bytes);
     }
 
@@ -83,6 +154,11 @@ impl MsgStore {
         self.push(LogMsg::Finished);
     }
 
+    /// Live listeners currently subscribed.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
     pub fn get_receiver(&self) -> broadcast::Receiver<LogMsg> {
         self.sender.subscribe()
     }
@@ -97,6 +173,90 @@ impl MsgStore {
             .collect()
     }
 
+    /// Sequence number of the last pushed message, 0 if none.
+    pub fn last_seq(&self) -> u64 {
+        self.inner.read().unwrap().last_seq
+    }
+
+    /// History then live, each message tagged with its sequence number.
+    /// Unlike [`Self::history_plus_stream`], dropped messages are reported
+    /// so the subscriber can resync.
+    pub fn sequenced_history_plus_stream(
+        &self,
+    ) -> futures::stream::BoxStream<'static, Result<SequencedMsg, std::io::Error>> {
+        self.sequenced_stream_after(None)
+    }
+
+    /// Like [`Self::sequenced_history_plus_stream`], but replays only the
+    /// history after `after_seq`, the last sequence number the subscriber
+    /// saw. History trimmed or compacted since then is reported as missed. A
+    /// cursor ahead of the store (e.g. from before a restart) replays
+    /// everything, which the subscriber notices by sequence numbers going
+    /// backwards.
+    pub fn sequenced_stream_after(
+        &self,
+        after_seq: Option<u64>,
+    ) -> futures::stream::BoxStream<'static, Result<SequencedMsg, std::io::Error>> {
+        // Subscribe under the lock so the first live message directly
+        // follows the history snapshot.
+        let (history, last_seq, rx) = {
+            let inner = self.inner.read().unwrap();
+            let after_seq = after_seq.filter(|after| *after <= inner.last_seq);
+            let mut history = Vec::new();
+            if let Some(after) = after_seq {
+                // A snapshot can't be applied on top of older state, so only
+                // the messages after it can be replayed individually.
+                let first_replayable = match inner.history.front() {
+                    Some(front) if front.snapshot => front.seq + 1,
+                    Some(front) => front.seq,
+                    None => inner.last_seq + 1,
+                };
+                if first_replayable > after + 1 {
+                    history.push(SequencedMsg::Missed {
+                        from_seq: after + 1,
+                        to_seq: first_replayable - 1,
+                    });
+                }
+            }
+            history.extend(
+                inner
+                    .history
+                    .iter()
+                    .filter(|s| after_seq.is_none_or(|after| s.seq > after && !s.snapshot))
+                    .map(|s| SequencedMsg::Msg {
+                        seq: s.seq,
+                        msg: s.msg.clone(),
+                    }),
+            );
+            (history, inner.last_seq, self.sender.subscribe())
+        };
+
+        let hist = futures::stream::iter(history.into_iter().map(Ok::<_, std::io::Error>));
+        let live = BroadcastStream::new(rx).scan(last_seq, |seq, res| {
+            let item = match res {
+                Ok(msg) => {
+                    *seq += 1;
+                    SequencedMsg::Msg { seq: *seq, msg }
+                }
+                Err(BroadcastStreamRecvError::Lagged(n)) => {
+                    tracing::warn!(
+                        skipped = n,
+                        "MsgStore broadcast lagged. {n} messages dropped for this subscriber"
+                    );
+                    let from_seq = *seq + 1;
+                    *seq += n;
+                    SequencedMsg::Missed {
+                        from_seq,
+                        to_seq: *seq,
+                    }
+                }
+            };
+            future::ready(Some(Ok(item)))
+        });
+
+        Box::pin(hist.chain(live))
+    }
+
     /// History then live, as `LogMsg`.
     pub fn history_plus_stream(
         &self,
@@ -154,6 +314,52 @@ impl MsgStore {
             .boxed()
     }
 
+    /// Collapse all but the newest `keep_recent` messages into one snapshot
+    /// patch, so new subscribers replay current state instead of every
+    /// change. Only for stores holding nothing but JSON patches; returns the
+    /// number of messages collapsed.
+    ///
+    /// Of the operations on a path only the last is kept, and an operation
+    /// on a path drops earlier ones below it. Removals are dropped once
+    /// nothing they remove is left. Operations for which `shared_key`
+    /// returns a key are deduplicated by that key instead, for paths whose
+    /// values clients tell apart by content.
+    pub fn compact_patches(
+        &self,
+        keep_recent: usize,
+        shared_key: impl Fn(&PatchOperation) -> Option<String>,
+    ) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        let count = inner.history.len().saturating_sub(keep_recent);
+        if count < 2 {
+            return 0;
+        }
+
+        let mut ops = Vec::new();
+        for stored in inner.history.range(..count) {
+            let LogMsg::JsonPatch(patch) = &stored.msg else {
+                return 0;
+            };
+            ops.extend(patch.0.iter().cloned());
+        }
+        let Some(ops) = collapse_operations(ops, shared_key) else {
+            return 0;
+        };
+
+        let seq = inner.history[count - 1].seq;
+        let freed: usize = inner.history.drain(..count).map(|s| s.bytes).sum();
+        let msg = LogMsg::JsonPatch(Patch(ops));
+        let bytes = msg.approx_bytes();
+        inner.total_bytes = inner.total_bytes.saturating_sub(freed) + bytes;
+        inner.history.push_front(StoredMsg {
+            seq,
+            msg,
+            bytes,
+            snapshot: true,
+        });
+        count
+    }
+
     /// Forward a stream of typed log messages into this store.
     pub fn spawn_forwarder<S, E>(self: Arc<Self>, stream: S) -> JoinHandle<()>
     where
@@ -172,3 +378,202 @@ impl MsgStore {
         })
     }
 }
+
+/// See [`MsgStore::compact_patches`]. `None` if an operation can't be
+/// collapsed safely.
+fn collapse_operations(
+    ops: Vec<PatchOperation>,
+    shared_key: impl Fn(&PatchOperation) -> Option<String>,
+) -> Option<Vec<PatchOperation>> {
+    let mut covered_paths: Vec<String> = Vec::new();
+    let mut covered_keys = HashSet::new();
+    let mut kept = Vec::new();
+
+    for op in ops.into_iter().rev() {
+        match &op {
+            PatchOperation::Add(_) | PatchOperation::Replace(_) | PatchOperation::Remove(_) => {}
+            // Depend on other values; rare enough not to bother.
+            PatchOperation::Move(_) | PatchOperation::Copy(_) | PatchOperation::Test(_) => {
+                return None;
+            }
+        }
+
+        if let Some(key) = shared_key(&op) {
+            if covered_keys.insert(key) {
+                kept.push(op);
+            }
+            continue;
+        }
+
+        let path = op.path().to_string();
+        let is_covered = covered_paths.iter().any(|covered| {
+            path == *covered
+                || covered.is_empty()
+                || path
+                    .strip_prefix(covered.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        });
+        if is_covered {
+            continue;
+        }
+        covered_paths.push(path);
+        if !matches!(op, PatchOperation::Remove(_)) {
+            kept.push(op);
+        }
+    }
+
+    kept.reverse();
+    Some(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sequences_history_and_live_messages() {
+        let store = MsgStore::new();
+        store.push_stdout("a");
+        store.push_stdout("b");
+
+        let mut stream = store.sequenced_history_plus_stream();
+        store.push_stdout("c");
+        assert_eq!(store.last_seq(), 3);
+
+        let mut seqs = Vec::new();
+        for _ in 0..3 {
+            match stream.next().await.unwrap().unwrap() {
+                SequencedMsg::Msg { seq, .. } => seqs.push(seq),
+                SequencedMsg::Missed { .. } => panic!("unexpected gap"),
+            }
+        }
+        assert_eq!(seqs, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn replays_after_cursor() {
+        let store = MsgStore::new();
+        for line in ["a", "b", "c"] {
+            store.push_stdout(line);
+        }
+
+        let mut stream = store.sequenced_stream_after(Some(2));
+        store.push_stdout("d");
+        let mut seqs = Vec::new();
+        for _ in 0..2 {
+            match stream.next().await.unwrap().unwrap() {
+                SequencedMsg::Msg { seq, .. } => seqs.push(seq),
+                SequencedMsg::Missed { .. } => panic!("unexpected gap"),
+            }
+        }
+        assert_eq!(seqs, vec![3, 4]);
+
+        // A cursor from before a restart replays the whole history.
+        let mut stream = store.sequenced_stream_after(Some(100));
+        assert!(matches!(
+            stream.next().await.unwrap().unwrap(),
+            SequencedMsg::Msg { seq: 1, .. }
+        ));
+    }
+
+    fn patch(ops: serde_json::Value) -> LogMsg {
+        LogMsg::JsonPatch(serde_json::from_value(ops).unwrap())
+    }
+
+    #[test]
+    fn compaction_keeps_latest_state() {
+        let store = MsgStore::new();
+        store.push(patch(serde_json::json!([
+            { "op": "add", "path": "/items/a", "value": 1 },
+            { "op": "add", "path": "/items/b", "value": 1 },
+        ])));
+        store.push(patch(serde_json::json!([
+            { "op": "replace", "path": "/items/a", "value": 2 },
+        ])));
+        store.push(patch(serde_json::json!([
+            { "op": "remove", "path": "/items/b" },
+        ])));
+        store.push(patch(serde_json::json!([
+            { "op": "replace", "path": "/shared", "value": { "id": 1, "v": 1 } },
+            { "op": "replace", "path": "/shared", "value": { "id": 2, "v": 1 } },
+            { "op": "replace", "path": "/shared", "value": { "id": 1, "v": 2 } },
+        ])));
+        store.push(patch(serde_json::json!([
+            { "op": "add", "path": "/items/c", "value": 1 },
+        ])));
+
+        let shared_key = |op: &PatchOperation| match op {
+            PatchOperation::Replace(op) if op.path == "/shared" => Some(op.value["id"].to_string()),
+            _ => None,
+        };
+        assert_eq!(store.compact_patches(1, shared_key), 4);
+
+        let history = store.get_history();
+        assert_eq!(history.len(), 2);
+        let LogMsg::JsonPatch(snapshot) = &history[0] else {
+            panic!("expected snapshot patch");
+        };
+        assert_eq!(
+            serde_json::to_value(snapshot).unwrap(),
+            serde_json::json!([
+                { "op": "replace", "path": "/items/a", "value": 2 },
+                { "op": "replace", "path": "/shared", "value": { "id": 2, "v": 1 } },
+                { "op": "replace", "path": "/shared", "value": { "id": 1, "v": 2 } },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_before_snapshot_reports_missed_range() {
+        let store = MsgStore::new();
+        for name in ["a", "b", "c"] {
+            store.push(patch(serde_json::json!([
+                { "op": "add", "path": format!("/{name}"), "value": 1 },
+            ])));
+        }
+        store.compact_patches(1, |_| None);
+
+        let mut stream = store.sequenced_stream_after(Some(1));
+        assert!(matches!(
+            stream.next().await.unwrap().unwrap(),
+            SequencedMsg::Missed {
+                from_seq: 2,
+                to_seq: 2
+            }
+        ));
+        assert!(matches!(
+            stream.next().await.unwrap().unwrap(),
+            SequencedMsg::Msg { seq: 3, .. }
+        ));
+
+        let mut stream = store.sequenced_history_plus_stream();
+        assert!(matches!(
+            stream.next().await.unwrap().unwrap(),
+            SequencedMsg::Msg { seq: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn ws_message_carries_sequence_number() {
+        let msg = SequencedMsg::Msg {
+            seq: 7,
+            msg: LogMsg::Stdout("hi".to_string()),
+        };
+        let Message::Text(text) = msg.to_ws_message() else {
+            panic!("expected text frame");
+        };
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["seq"], 7);
+        assert_eq!(value["Stdout"], "hi");
+
+        let missed = SequencedMsg::Missed {
+            from_seq: 3,
+            to_seq: 5,
+        };
+        let Message::Text(text) = missed.to_ws_message() else {
+            panic!("expected text frame");
+        };
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value[EV_RESYNC]["from_seq"], 3);
+    }
+}
@@ -124,15 +124,72 @@ pub fn get_vibe_kanban_temp_dir() -> std::path::PathBuf {
     }
 }
 
+/// Bytes available to the current user on the filesystem containing `path`.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).map_err(std::io::Error::from)?;
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+/// Bytes available to the current user on the filesystem containing `path`.
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut available = 0u64;
+    // SAFETY: `wide` is NUL-terminated and outlives the call; the unused
+    // out-parameters may be null.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(available)
+}
+
 /// Expand leading ~ to user's home directory.
 pub fn expand_tilde(path_str: &str) -> std::path::PathBuf {
     shellexpand::tilde(path_str).as_ref().into()
 }
 
+/// Expand `$VAR`, `${VAR}` and a leading ~ from this process's environment.
+/// Unset variables are left as written.
+pub fn expand_env_vars(value: &str) -> String {
+    let value = shellexpand::env_with_context_no_errors(value, |var| std::env::var(var).ok());
+    shellexpand::tilde(value.as_ref()).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn expand_env_vars_leaves_unset_variables() {
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(
+            expand_env_vars("/opt/bin:$PATH"),
+            format!("/opt/bin:{path}")
+        );
+        assert_eq!(
+            expand_env_vars("${VK_SURELY_UNSET_VAR}/bin"),
+            "${VK_SURELY_UNSET_VAR}/bin"
+        );
+    }
+
     #[test]
     fn test_make_path_relative() {
         // Test with relative path (should remain unchanged)
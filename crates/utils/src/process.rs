@@ -1,6 +1,43 @@
 use command_group::AsyncGroupChild;
+use serde::{Deserialize, Serialize};
 #[cfg(unix)]
 use tokio::time::Duration;
+use ts_rs::TS;
+
+/// A signal sent to every process in an execution's process group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSignal {
+    /// SIGSTOP: freeze the group without ending it.
+    Pause,
+    /// SIGCONT: continue a paused group.
+    Resume,
+    /// SIGINT: what Ctrl-C sends, which most agents take as "stop this turn".
+    Interrupt,
+}
+
+#[cfg(unix)]
+pub fn signal_process_group(child: &AsyncGroupChild, signal: ProcessSignal) -> std::io::Result<()> {
+    use command_group::{Signal, UnixChildExt};
+
+    let signal = match signal {
+        ProcessSignal::Pause => Signal::SIGSTOP,
+        ProcessSignal::Resume => Signal::SIGCONT,
+        ProcessSignal::Interrupt => Signal::SIGINT,
+    };
+    child.signal(signal)
+}
+
+#[cfg(not(unix))]
+pub fn signal_process_group(
+    _child: &AsyncGroupChild,
+    _signal: ProcessSignal,
+) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "process signals are only supported on Unix",
+    ))
+}
 
 pub async fn kill_process_group(child: &mut AsyncGroupChild) -> std::io::Result<()> {
     #[cfg(unix)]
@@ -10,6 +47,8 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> std::io::Result<
         // group leader has exited, unlike getpgid() which would fail.
         use command_group::{Signal, UnixChildExt};
 
+        // A paused group would not act on SIGINT or SIGTERM until resumed.
+        let _ = child.signal(Signal::SIGCONT);
         for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
             tracing::info!("Sending {:?} to process group", sig);
             if let Err(e) = child.signal(sig) {
@@ -0,0 +1,171 @@
+//! Snapshots of the processes an execution has started, so a busy agent
+//! (compiling, running tests) can be told apart from a hung one.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub command: String,
+    /// Percent of one CPU, averaged over the process lifetime on Linux and
+    /// over recent activity on macOS.
+    pub cpu_percent: f32,
+    #[ts(type = "number")]
+    pub rss_kb: u64,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PsRow {
+    pid: u32,
+    ppid: u32,
+    cpu_percent: f32,
+    rss_kb: u64,
+    command: String,
+}
+
+/// The process `root_pid` and all its descendants. `None` if it has exited.
+#[cfg(unix)]
+pub async fn process_tree(root_pid: u32) -> std::io::Result<Option<ProcessTreeNode>> {
+    let output = tokio::process::Command::new("ps")
+        .args(["-axww", "-o", "pid=,ppid=,pcpu=,rss=,command="])
+        .env("LC_ALL", "C")
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "ps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let rows = parse_ps_output(&String::from_utf8_lossy(&output.stdout));
+    Ok(build_tree(rows, root_pid))
+}
+
+#[cfg(not(unix))]
+pub async fn process_tree(_root_pid: u32) -> std::io::Result<Option<ProcessTreeNode>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "process trees are not supported on this platform",
+    ))
+}
+
+/// Rows of `ps -o pid=,ppid=,pcpu=,rss=,command=`. Malformed lines are
+/// skipped; the command keeps its inner spacing.
+fn parse_ps_output(output: &str) -> Vec<PsRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut rest = line.trim_start();
+            let mut fields = [""; 4];
+            for field in &mut fields {
+                let end = rest.find(char::is_whitespace)?;
+                *field = &rest[..end];
+                rest = rest[end..].trim_start();
+            }
+            Some(PsRow {
+                pid: fields[0].parse().ok()?,
+                ppid: fields[1].parse().ok()?,
+                cpu_percent: fields[2].parse().ok()?,
+                rss_kb: fields[3].parse().ok()?,
+                command: rest.trim_end().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn build_tree(rows: Vec<PsRow>, root_pid: u32) -> Option<ProcessTreeNode> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut by_pid = HashMap::new();
+    for row in rows {
+        children.entry(row.ppid).or_default().push(row.pid);
+        by_pid.insert(row.pid, row);
+    }
+
+    fn node(
+        pid: u32,
+        by_pid: &mut HashMap<u32, PsRow>,
+        children: &HashMap<u32, Vec<u32>>,
+    ) -> Option<ProcessTreeNode> {
+        // Removing the row guards against pid cycles from a racy snapshot.
+        let row = by_pid.remove(&pid)?;
+        let mut child_nodes: Vec<_> = children
+            .get(&pid)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| node(*child, by_pid, children))
+            .collect();
+        child_nodes.sort_by_key(|child| child.pid);
+        Some(ProcessTreeNode {
+            pid: row.pid,
+            command: row.command,
+            cpu_percent: row.cpu_percent,
+            rss_kb: row.rss_kb,
+            children: child_nodes,
+        })
+    }
+
+    node(root_pid, &mut by_pid, &children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_tree_from_ps_output() {
+        let output = "\
+    1     0  0.0  1234 /sbin/init
+  100     1  0.5  2048 node  agent.js --flag
+  101   100 98.7 512000 cargo build
+  102   101 45.0 30000 rustc --crate-name  foo
+  103   100  0.0   900 sh -c sleep 10
+  200     1  0.0   100 unrelated
+garbage line
+";
+        let rows = parse_ps_output(output);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[1].command, "node  agent.js --flag");
+
+        let tree = build_tree(rows, 100).unwrap();
+        assert_eq!(tree.command, "node  agent.js --flag");
+        assert_eq!(tree.cpu_percent, 0.5);
+        assert_eq!(
+            tree.children.iter().map(|c| c.pid).collect::<Vec<_>>(),
+            [101, 103]
+        );
+        assert_eq!(tree.children[0].rss_kb, 512000);
+        assert_eq!(
+            tree.children[0].children[0].command,
+            "rustc --crate-name  foo"
+        );
+        assert!(build_tree(parse_ps_output(output), 999).is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn finds_spawned_children() {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg("sleep 30 & wait")
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let tree = process_tree(pid).await.unwrap().unwrap();
+        assert_eq!(tree.pid, pid);
+        assert!(tree.children.iter().any(|c| c.command.contains("sleep 30")));
+
+        child.kill().await.unwrap();
+        for grandchild in tree.children {
+            let _ = nix::sys::signal::kill(
+                nix::unistd::Pid::from_raw(grandchild.pid as i32),
+                nix::sys::signal::Signal::SIGKILL,
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! CPU and memory caps for spawned processes on Unix.
+//!
+//! Every process gets an `RLIMIT_DATA` cap on its own heap. On Linux, when
+//! the server runs in a cgroup v2 hierarchy it may manage (a delegated
+//! systemd unit or a container), each execution additionally gets its own
+//! cgroup so the memory cap covers the whole process tree and the CPU cap is
+//! enforced. Without cgroups the CPU cap is not applied.
+
+/// Limits for one process tree. `None` leaves a resource uncapped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub memory_mb: Option<u64>,
+    /// Percent of one CPU; 200 allows two full cores.
+    pub cpu_percent: Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.memory_mb.is_none() && self.cpu_percent.is_none()
+    }
+
+    /// The tighter of both limits for each resource.
+    pub fn strictest(self, other: Self) -> Self {
+        fn min<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            memory_mb: min(self.memory_mb, other.memory_mb),
+            cpu_percent: min(self.cpu_percent, other.cpu_percent),
+        }
+    }
+
+    fn memory_bytes(&self) -> Option<u64> {
+        self.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// Cap the memory of the process `command` spawns and, through
+    /// inheritance, of everything it starts.
+    #[cfg(unix)]
+    pub fn apply_to_command(&self, command: &mut tokio::process::Command) {
+        let Some(bytes) = self.memory_bytes() else {
+            return;
+        };
+        // SAFETY: the hook only calls setrlimit, which is async-signal-safe.
+        unsafe {
+            command.pre_exec(move || {
+                use nix::sys::resource::{Resource, setrlimit};
+                setrlimit(Resource::RLIMIT_DATA, bytes, bytes).map_err(std::io::Error::from)
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply_to_command(&self, _command: &mut tokio::process::Command) {}
+}
+
+#[cfg(target_os = "linux")]
+pub use cgroup::ExecutionCgroup;
+
+/// Cgroups are Linux-only; elsewhere only the memory rlimit applies.
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug)]
+pub struct ExecutionCgroup;
+
+#[cfg(not(target_os = "linux"))]
+impl ExecutionCgroup {
+    pub fn attach(_name: &str, _limits: &ResourceLimits, _pid: u32) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+        sync::OnceLock,
+    };
+
+    use super::ResourceLimits;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    const SERVER_LEAF: &str = "vibe-kanban";
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    /// A cgroup holding one execution's process tree, removed on drop.
+    #[derive(Debug)]
+    pub struct ExecutionCgroup {
+        path: PathBuf,
+    }
+
+    impl ExecutionCgroup {
+        /// Create a cgroup named `name` with `limits` and move the process
+        /// group led by `pid` into it. `None` if cgroups are unavailable.
+        pub fn attach(name: &str, limits: &ResourceLimits, pid: u32) -> Option<Self> {
+            let parent = parent_cgroup()?;
+            match Self::create(parent, name, limits, pid) {
+                Ok(cgroup) => Some(cgroup),
+                Err(e) => {
+                    tracing::warn!("Failed to create cgroup {name}: {e}");
+                    let _ = fs::remove_dir(parent.join(name));
+                    None
+                }
+            }
+        }
+
+        fn create(
+            parent: &Path,
+            name: &str,
+            limits: &ResourceLimits,
+            pid: u32,
+        ) -> io::Result<Self> {
+            let path = parent.join(name);
+            fs::create_dir_all(&path)?;
+            if let Some(bytes) = limits.memory_bytes() {
+                fs::write(path.join("memory.max"), bytes.to_string())?;
+            }
+            if let Some(percent) = limits.cpu_percent {
+                fs::write(path.join("cpu.max"), cpu_max(percent))?;
+            }
+            // Children forked before the move stay behind unless moved too.
+            let mut members = process_group_members(pid);
+            if !members.contains(&pid) {
+                members.push(pid);
+            }
+            for member in members {
+                match fs::write(path.join("cgroup.procs"), member.to_string()) {
+                    // The process exited in the meantime.
+                    Err(e) if e.raw_os_error() == Some(nix::libc::ESRCH) => {}
+                    result => result?,
+                }
+            }
+            Ok(Self { path })
+        }
+    }
+
+    impl Drop for ExecutionCgroup {
+        fn drop(&mut self) {
+            // Only succeeds once every process in it has exited.
+            if let Err(e) = fs::remove_dir(&self.path) {
+                tracing::debug!("Failed to remove cgroup {}: {e}", self.path.display());
+            }
+        }
+    }
+
+    /// The cgroup executions are created under, set up on first use.
+    fn parent_cgroup() -> Option<&'static Path> {
+        static PARENT: OnceLock<Option<PathBuf>> = OnceLock::new();
+        PARENT
+            .get_or_init(|| match prepare_parent() {
+                Ok(parent) => Some(parent),
+                Err(e) => {
+                    tracing::info!(
+                        "cgroup v2 resource limits unavailable, only memory rlimits apply: {e}"
+                    );
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    /// Enable the cpu and memory controllers for children of our own cgroup.
+    /// A cgroup with processes cannot pass controllers down, so the server
+    /// first moves itself into a leaf.
+    fn prepare_parent() -> io::Result<PathBuf> {
+        let own = fs::read_to_string("/proc/self/cgroup")?;
+        let relative = parse_unified_cgroup(&own)
+            .ok_or_else(|| io::Error::other("not in a cgroup v2 hierarchy"))?;
+        let parent = Path::new(CGROUP_ROOT).join(relative.trim_start_matches('/'));
+
+        let available = fs::read_to_string(parent.join("cgroup.controllers"))?;
+        if !has_controllers(&available) {
+            return Err(io::Error::other(
+                "cpu and memory controllers are not delegated",
+            ));
+        }
+        if has_controllers(&fs::read_to_string(parent.join("cgroup.subtree_control"))?) {
+            return Ok(parent);
+        }
+
+        let leaf = parent.join(SERVER_LEAF);
+        fs::create_dir_all(&leaf)?;
+        fs::write(leaf.join("cgroup.procs"), std::process::id().to_string())?;
+        fs::write(parent.join("cgroup.subtree_control"), "+cpu +memory")?;
+        Ok(parent)
+    }
+
+    fn has_controllers(list: &str) -> bool {
+        let controllers: Vec<&str> = list.split_whitespace().collect();
+        controllers.contains(&"cpu") && controllers.contains(&"memory")
+    }
+
+    /// The path of the unified hierarchy entry (`0::/path`) in
+    /// `/proc/<pid>/cgroup`.
+    fn parse_unified_cgroup(contents: &str) -> Option<&str> {
+        contents.lines().find_map(|line| line.strip_prefix("0::"))
+    }
+
+    fn cpu_max(percent: u32) -> String {
+        let quota = CPU_PERIOD_US * u64::from(percent.max(1)) / 100;
+        format!("{quota} {CPU_PERIOD_US}")
+    }
+
+    fn process_group_members(pgid: u32) -> Vec<u32> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| {
+                fs::read_to_string(format!("/proc/{pid}/stat"))
+                    .ok()
+                    .and_then(|stat| parse_process_group(&stat))
+                    == Some(pgid)
+            })
+            .collect()
+    }
+
+    /// The process group in a `/proc/<pid>/stat` line. The command name may
+    /// contain spaces and parentheses, so fields are counted from its end.
+    fn parse_process_group(stat: &str) -> Option<u32> {
+        let (_, rest) = stat.rsplit_once(')')?;
+        rest.split_whitespace().nth(2)?.parse().ok()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parses_proc_files() {
+            let cgroup = "1:name=systemd:/ignored\n0::/user.slice/app.scope\n";
+            assert_eq!(parse_unified_cgroup(cgroup), Some("/user.slice/app.scope"));
+            assert_eq!(parse_unified_cgroup("1:cpu:/legacy\n"), None);
+
+            let stat = "4242 (node (worker) x) S 4200 4201 4201 0 -1 4194560";
+            assert_eq!(parse_process_group(stat), Some(4201));
+            assert_eq!(
+                parse_process_group(&fs::read_to_string("/proc/self/stat").unwrap()),
+                Some(nix::unistd::getpgrp().as_raw() as u32)
+            );
+        }
+
+        #[test]
+        fn formats_cpu_quota() {
+            assert_eq!(cpu_max(50), "50000 100000");
+            assert_eq!(cpu_max(250), "250000 100000");
+            assert!(has_controllers("cpuset cpu io memory pids"));
+            assert!(!has_controllers("cpuset io memory"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strictest_takes_the_lower_limit_per_resource() {
+        let repo = ResourceLimits {
+            memory_mb: Some(2048),
+            cpu_percent: None,
+        };
+        let other = ResourceLimits {
+            memory_mb: Some(4096),
+            cpu_percent: Some(150),
+        };
+        assert_eq!(
+            repo.strictest(other),
+            ResourceLimits {
+                memory_mb: Some(2048),
+                cpu_percent: Some(150),
+            }
+        );
+        assert!(ResourceLimits::default().is_unlimited());
+        assert!(!repo.is_unlimited());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn memory_limit_applies_to_the_spawned_process() {
+        let limits = ResourceLimits {
+            memory_mb: Some(64),
+            cpu_percent: None,
+        };
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg("ulimit -d");
+        limits.apply_to_command(&mut command);
+        let output = command.output().await.unwrap();
+        // `ulimit -d` reports KiB.
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "65536");
+    }
+}
@@ -1,12 +1,53 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+/// Stable, machine-readable reason for a failed request. Clients should branch
+/// on this rather than on the HTTP status or message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[ts(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiErrorCode {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Gone,
+    PayloadTooLarge,
+    RateLimited,
+    Internal,
+    BadGateway,
+    Timeout,
+    ShuttingDown,
+    RepoNotFound,
+    WorkspaceNotFound,
+    SessionNotFound,
+    ExecutionProcessNotFound,
+    BranchNotFound,
+    ExecutorMismatch,
+    WorktreeDirty,
+    MergeConflict,
+    RebaseInProgress,
+    BranchesDiverged,
+    GitAuthFailed,
+    ExecutorNotInstalled,
+    ExecutorAuthRequired,
+    FileTooLarge,
+    UploadNotFound,
+    InvalidUpload,
+    EditorNotFound,
+    RemoteAuthRequired,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct ApiResponse<T, E = T> {
     success: bool,
     data: Option<T>,
     error_data: Option<E>,
     message: Option<String>,
+    /// Set on every failure returned for an `ApiError`.
+    #[serde(default)]
+    error_code: Option<ApiErrorCode>,
 }
 
 impl<T, E> ApiResponse<T, E> {
@@ -17,6 +58,7 @@ impl<T, E> ApiResponse<T, E> {
             data: Some(data),
             message: None,
             error_data: None,
+            error_code: None,
         }
     }
 
@@ -27,6 +69,18 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             message: Some(message.to_string()),
             error_data: None,
+            error_code: None,
+        }
+    }
+
+    /// Creates an error response with `message` and a machine-readable `code`.
+    pub fn error_with_code(message: &str, code: ApiErrorCode) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            message: Some(message.to_string()),
+            error_data: None,
+            error_code: Some(code),
         }
     }
     /// Creates an error response, with no `data`, no `message`, but with arbitrary `error_data`.
@@ -36,6 +90,7 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             error_data: Some(data),
             message: None,
+            error_code: None,
         }
     }
 
@@ -49,6 +104,16 @@ impl<T, E> ApiResponse<T, E> {
         self.message.as_deref()
     }
 
+    /// Returns the machine-readable error code if present.
+    pub fn error_code(&self) -> Option<ApiErrorCode> {
+        self.error_code
+    }
+
+    /// Returns a reference to the error data if present.
+    pub fn error_data(&self) -> Option<&E> {
+        self.error_data.as_ref()
+    }
+
     /// Consumes the response, returning the data payload if present.
     pub fn into_data(self) -> Option<T> {
         self.data
@@ -1,10 +1,42 @@
-use std::sync::OnceLock;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, LazyLock, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
+use regex::Regex;
+use sentry::protocol::{Breadcrumb, Context, Event};
 use sentry_tracing::{EventFilter, SentryLayer};
+use serde_json::Value;
 use tracing::Level;
 
 static INIT_GUARD: OnceLock<sentry::ClientInitGuard> = OnceLock::new();
 
+static REPORTING_ENABLED: AtomicBool = AtomicBool::new(true);
+static METADATA_ONLY: AtomicBool = AtomicBool::new(false);
+
+const REDACTED: &str = "[redacted]";
+
+/// `KEY=value` pairs, as in environment dumps and command lines.
+static ENV_ASSIGNMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b([A-Z][A-Z0-9_]*)=("[^"]*"|'[^']*'|[^\s"']+)"#).unwrap());
+/// Keeps the scheme and host, drops the path and query.
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b([a-zA-Z][a-zA-Z0-9+.-]*://[^/\s"'<>]+)[^\s"'<>]*"#).unwrap());
+/// Absolute and relative paths with at least two components, which also
+/// covers `owner/repo` names.
+static PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:[A-Za-z]:)?(?:~|\.{1,2})?[/\\]?[\w.@+-]+(?:[/\\][\w.@+-]+)+[/\\]?").unwrap()
+});
+
+/// Field names whose values are replaced outright rather than scrubbed.
+const SENSITIVE_FIELDS: &[&str] = &[
+    "prompt", "message", "content", "text", "body", "path", "dir", "file", "repo", "branch",
+    "command", "args", "env", "cwd",
+];
+
 #[derive(Clone, Copy, Debug)]
 pub enum SentrySource {
     Backend,
@@ -55,6 +87,8 @@ pub fn init_once(source: SentrySource) {
             sentry::ClientOptions {
                 release: sentry::release_name!(),
                 environment: Some(environment().into()),
+                before_send: Some(Arc::new(scrub_event)),
+                before_breadcrumb: Some(Arc::new(scrub_breadcrumb)),
                 ..Default::default()
             },
         ))
@@ -65,6 +99,124 @@ pub fn init_once(source: SentrySource) {
     });
 }
 
+/// Whether errors are reported at all, and if so whether only metadata is
+/// sent: levels, error types, stack frames and tags, without any message
+/// text or fields. Otherwise paths, repo names, prompts and env values are
+/// scrubbed from messages and fields.
+pub fn set_error_reporting(enabled: bool, metadata_only: bool) {
+    REPORTING_ENABLED.store(enabled, Ordering::Relaxed);
+    METADATA_ONLY.store(metadata_only, Ordering::Relaxed);
+}
+
+fn scrub_text(text: &str) -> String {
+    let text = ENV_ASSIGNMENT.replace_all(text, format!("${{1}}={REDACTED}"));
+    let text = URL.replace_all(&text, "${1}");
+    PATH.replace_all(&text, "[path]").into_owned()
+}
+
+fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_FIELDS.iter().any(|field| name.contains(field))
+}
+
+fn scrub_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = scrub_text(text),
+        Value::Array(values) => values.iter_mut().for_each(scrub_value),
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                if is_sensitive_field(name) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    scrub_value(value);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn scrub_fields(fields: &mut BTreeMap<String, Value>) {
+    for (name, value) in fields.iter_mut() {
+        if is_sensitive_field(name) {
+            *value = Value::String(REDACTED.to_string());
+        } else {
+            scrub_value(value);
+        }
+    }
+}
+
+fn scrub_event(mut event: Event<'static>) -> Option<Event<'static>> {
+    if !REPORTING_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let metadata_only = METADATA_ONLY.load(Ordering::Relaxed);
+
+    if metadata_only {
+        event.message = None;
+        event.logentry = None;
+        event.extra.clear();
+        // Tracing fields and spans; device, OS and runtime contexts stay.
+        event
+            .contexts
+            .retain(|_, context| !matches!(context, Context::Other(_)));
+        event.request = None;
+        if let Some(user) = &mut event.user {
+            user.email = None;
+            user.username = None;
+            user.ip_address = None;
+            user.other.clear();
+        }
+    } else {
+        event.message = event.message.as_deref().map(scrub_text);
+        if let Some(logentry) = &mut event.logentry {
+            logentry.message = scrub_text(&logentry.message);
+            logentry.params.iter_mut().for_each(scrub_value);
+        }
+        scrub_fields(&mut event.extra);
+        for context in event.contexts.values_mut() {
+            if let Context::Other(fields) = context {
+                scrub_fields(fields);
+            }
+        }
+        if let Some(request) = &mut event.request {
+            request.data = None;
+            request.query_string = None;
+            request.cookies = None;
+        }
+    }
+
+    for exception in event.exception.values.iter_mut() {
+        exception.value = if metadata_only {
+            None
+        } else {
+            exception.value.as_deref().map(scrub_text)
+        };
+        if let Some(stacktrace) = &mut exception.stacktrace {
+            for frame in stacktrace.frames.iter_mut() {
+                frame.vars.clear();
+            }
+        }
+    }
+    event.breadcrumbs.values = std::mem::take(&mut event.breadcrumbs.values)
+        .into_iter()
+        .filter_map(scrub_breadcrumb)
+        .collect();
+
+    Some(event)
+}
+
+fn scrub_breadcrumb(mut breadcrumb: Breadcrumb) -> Option<Breadcrumb> {
+    if METADATA_ONLY.load(Ordering::Relaxed) {
+        breadcrumb.message = None;
+        breadcrumb.data.clear();
+    } else {
+        breadcrumb.message = breadcrumb.message.as_deref().map(scrub_text);
+        scrub_fields(&mut breadcrumb.data);
+    }
+    Some(breadcrumb)
+}
+
 pub fn configure_user_scope(user_id: &str, username: Option<&str>, email: Option<&str>) {
     let mut sentry_user = sentry::User {
         id: Some(user_id.to_string()),
@@ -102,3 +254,53 @@ where
             Level::TRACE => EventFilter::Ignore,
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrubs_paths_urls_and_env_values() {
+        assert_eq!(
+            scrub_text("Failed to open /home/alice/work/secret-repo/src/main.rs: not found"),
+            "Failed to open [path]: not found"
+        );
+        assert_eq!(
+            scrub_text(r"cannot read C:\Users\alice\repo\Cargo.toml"),
+            "cannot read [path]"
+        );
+        assert_eq!(
+            scrub_text("push to acme/payments failed"),
+            "push to [path] failed"
+        );
+        assert_eq!(
+            scrub_text("GET https://api.github.com/repos/acme/payments?page=2 returned 404"),
+            "GET https://api.github.com returned 404"
+        );
+        assert_eq!(
+            scrub_text("spawned with API_KEY=sk-123 DEBUG=\"1 2\""),
+            "spawned with API_KEY=[redacted] DEBUG=[redacted]"
+        );
+    }
+
+    #[test]
+    fn redacts_sensitive_fields_and_scrubs_the_rest() {
+        let mut fields = BTreeMap::from([
+            (
+                "prompt".to_string(),
+                Value::from("refactor the billing code"),
+            ),
+            ("repo_name".to_string(), Value::from("payments")),
+            (
+                "error".to_string(),
+                Value::from("missing ./config/app.toml"),
+            ),
+            ("attempts".to_string(), Value::from(3)),
+        ]);
+        scrub_fields(&mut fields);
+        assert_eq!(fields["prompt"], Value::from(REDACTED));
+        assert_eq!(fields["repo_name"], Value::from(REDACTED));
+        assert_eq!(fields["error"], Value::from("missing [path]"));
+        assert_eq!(fields["attempts"], Value::from(3));
+    }
+}
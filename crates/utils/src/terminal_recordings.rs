@@ -0,0 +1,186 @@
+//! Recordings of workspace terminal sessions in the asciicast v2 format: a
+//! JSON header line, then one `[seconds, "o" | "r", data]` line per output
+//! chunk or resize. What is typed into the terminal isn't recorded.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::assets::asset_dir;
+
+pub const TERMINAL_RECORDINGS_DIRNAME: &str = "terminal_recordings";
+pub const RECORDING_EXTENSION: &str = "cast";
+
+pub fn workspace_recordings_dir(workspace_id: Uuid) -> PathBuf {
+    asset_dir()
+        .join(TERMINAL_RECORDINGS_DIRNAME)
+        .join(workspace_id.to_string())
+}
+
+pub fn recording_file_path(workspace_id: Uuid, recording_id: Uuid) -> PathBuf {
+    workspace_recordings_dir(workspace_id).join(format!("{recording_id}.{RECORDING_EXTENSION}"))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    /// Unix time the recording started.
+    timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TerminalRecordingInfo {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub cols: u16,
+    pub rows: u16,
+    #[ts(type = "number")]
+    pub size_bytes: u64,
+}
+
+/// Appends a terminal's output to a recording. Each event is flushed, so a
+/// recording can be replayed while its terminal is still open.
+pub struct TerminalRecorder {
+    file: BufWriter<File>,
+    started: Instant,
+}
+
+impl TerminalRecorder {
+    pub fn create(path: &Path, cols: u16, rows: u16) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = BufWriter::new(File::create(path)?);
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: Utc::now().timestamp(),
+        };
+        serde_json::to_writer(&mut file, &header)?;
+        file.write_all(b"\n")?;
+        file.flush()?;
+        Ok(Self {
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn output(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.event("o", &String::from_utf8_lossy(data))
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.event("r", &format!("{cols}x{rows}"))
+    }
+
+    fn event(&mut self, kind: &str, data: &str) -> std::io::Result<()> {
+        // Millisecond precision is plenty for playback.
+        let elapsed = (self.started.elapsed().as_secs_f64() * 1000.0).round() / 1000.0;
+        serde_json::to_writer(&mut self.file, &(elapsed, kind, data))?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+/// Details of the recording at `path`, from its header.
+pub fn read_recording_info(path: &Path) -> std::io::Result<TerminalRecordingInfo> {
+    let id = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| Uuid::parse_str(stem).ok())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a recording"))?;
+    let file = File::open(path)?;
+    let size_bytes = file.metadata()?.len();
+
+    let mut header = String::new();
+    BufReader::new(file).read_line(&mut header)?;
+    let header: CastHeader = serde_json::from_str(&header)?;
+    Ok(TerminalRecordingInfo {
+        id,
+        started_at: DateTime::from_timestamp(header.timestamp, 0).unwrap_or_default(),
+        cols: header.width,
+        rows: header.height,
+        size_bytes,
+    })
+}
+
+/// The recordings in `dir`, newest first. Unreadable files are skipped.
+pub fn list_recordings(dir: &Path) -> std::io::Result<Vec<TerminalRecordingInfo>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut recordings: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == RECORDING_EXTENSION)
+        })
+        .filter_map(|path| read_recording_info(&path).ok())
+        .collect();
+    recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(recordings)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn records_output_and_resizes_as_asciicast() {
+        let dir = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        let path = dir.path().join(format!("{id}.{RECORDING_EXTENSION}"));
+
+        let mut recorder = TerminalRecorder::create(&path, 80, 24).unwrap();
+        recorder
+            .output("$ ls\r\n\u{1b}[32mé\u{1b}[0m".as_bytes())
+            .unwrap();
+        recorder.resize(120, 40).unwrap();
+        drop(recorder);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "$ ls\r\n\u{1b}[32mé\u{1b}[0m");
+        assert_eq!(lines[2][1], "r");
+        assert_eq!(lines[2][2], "120x40");
+
+        let recordings = list_recordings(dir.path()).unwrap();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].id, id);
+        assert_eq!((recordings[0].cols, recordings[0].rows), (80, 24));
+    }
+
+    #[test]
+    fn missing_directory_has_no_recordings() {
+        let dir = TempDir::new().unwrap();
+        assert!(
+            list_recordings(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
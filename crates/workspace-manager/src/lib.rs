@@ -1,5 +1,7 @@
+mod reconcile;
 mod workspace_manager;
 
+pub use reconcile::{WorkspaceDrift, WorkspaceDriftKind, WorkspaceDriftReport};
 pub use workspace_manager::{
     ManagedWorkspace, RepoWorkspaceInput, RepoWorktree, WorkspaceDeletionContext, WorkspaceError,
     WorkspaceManager, WorktreeContainer,
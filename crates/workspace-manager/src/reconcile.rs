@@ -0,0 +1,434 @@
+//! Detection of drift between workspace records and what is actually on disk.
+//!
+//! Worktrees can disappear underneath us (manual `rm -rf`, `git worktree prune`,
+//! a wiped temp dir) or be switched to another branch by hand. A scan compares
+//! every live workspace against the filesystem and reports anything that no
+//! longer lines up, flagging which issues can be repaired automatically.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use db::models::{workspace::Workspace as DbWorkspace, workspace_repo::WorkspaceRepo};
+use git::GitService;
+use serde::Serialize;
+use tracing::{info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+use worktree_manager::WorktreeManager;
+
+use crate::{WorkspaceError, WorkspaceManager};
+
+/// Directories younger than this are never reported as orphans: a workspace
+/// being created has its directory on disk before its record points at it.
+pub(crate) const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkspaceDriftKind {
+    /// The workspace's container directory no longer exists.
+    MissingWorkspaceDir,
+    /// The container directory exists but a repo's worktree is gone.
+    MissingWorktree { repo_id: Uuid, repo_name: String },
+    /// Something other than a git worktree sits where a repo's worktree
+    /// should be.
+    NotAWorktree { repo_id: Uuid, repo_name: String },
+    /// The worktree is checked out on a different branch than the workspace records.
+    BranchMismatch {
+        repo_id: Uuid,
+        repo_name: String,
+        expected: String,
+        actual: String,
+    },
+    /// A directory in the workspace base dir that no workspace points at.
+    OrphanedWorkspaceDir,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceDrift {
+    pub workspace_id: Option<Uuid>,
+    pub path: String,
+    pub kind: WorkspaceDriftKind,
+    /// Whether the repair endpoint can fix this issue without user input.
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct WorkspaceDriftReport {
+    pub workspaces_scanned: usize,
+    pub issues: Vec<WorkspaceDrift>,
+}
+
+impl WorkspaceDriftReport {
+    /// Workspaces with at least one repairable issue, deduplicated.
+    pub fn repairable_workspace_ids(&self) -> Vec<Uuid> {
+        let mut seen = HashSet::new();
+        self.issues
+            .iter()
+            .filter(|issue| issue.repairable)
+            .filter_map(|issue| issue.workspace_id)
+            .filter(|id| seen.insert(*id))
+            .collect()
+    }
+
+    pub fn has_orphans(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| matches!(issue.kind, WorkspaceDriftKind::OrphanedWorkspaceDir))
+    }
+
+    /// Orphaned directories a repair may remove. Orphans belong to no
+    /// workspace, so a repair restricted to `workspace_ids` removes none.
+    pub fn orphans_to_remove(&self, workspace_ids: Option<&[Uuid]>) -> Vec<PathBuf> {
+        if workspace_ids.is_some() {
+            return Vec::new();
+        }
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue.kind, WorkspaceDriftKind::OrphanedWorkspaceDir))
+            .map(|issue| PathBuf::from(&issue.path))
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum WorktreeState {
+    Present,
+    /// Absent, or an empty directory left by an interrupted creation.
+    Missing,
+    /// A plain directory or a standalone repository rather than a linked
+    /// worktree, which has a `.git` file.
+    Foreign,
+}
+
+fn worktree_state(path: &Path) -> WorktreeState {
+    if path.join(".git").is_file() {
+        return WorktreeState::Present;
+    }
+    let has_contents = std::fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_some());
+    if has_contents {
+        WorktreeState::Foreign
+    } else {
+        WorktreeState::Missing
+    }
+}
+
+/// Whether an unreferenced directory in a workspace base dir was left behind
+/// by us: it is empty or holds at least one linked worktree, and is older
+/// than `grace` so an in-progress creation isn't mistaken for an orphan.
+/// Anything else is not ours to report or delete.
+pub(crate) fn is_orphan_candidate(path: &Path, grace: Duration) -> bool {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return false;
+    };
+    let recently_modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age < grace);
+    if recently_modified {
+        return false;
+    }
+    let mut entries = entries.filter_map(|e| e.ok()).peekable();
+    entries.peek().is_none()
+        || entries.any(|entry| worktree_state(&entry.path()) == WorktreeState::Present)
+}
+
+impl WorkspaceManager {
+    /// Compare every workspace that should have a worktree on disk against the
+    /// filesystem. Workspaces whose worktrees were cleaned up deliberately
+    /// (`worktree_deleted`) are skipped.
+    pub async fn scan_drift(&self) -> Result<WorkspaceDriftReport, WorkspaceError> {
+        let workspaces = DbWorkspace::fetch_all(&self.db.pool).await?;
+        let git = GitService::new();
+        let mut report = WorkspaceDriftReport::default();
+
+        for workspace in &workspaces {
+            let Some(container_ref) = &workspace.container_ref else {
+                continue;
+            };
+            if workspace.worktree_deleted {
+                continue;
+            }
+            report.workspaces_scanned += 1;
+
+            let workspace_dir = PathBuf::from(container_ref);
+            if !workspace_dir.exists() {
+                report.issues.push(WorkspaceDrift {
+                    workspace_id: Some(workspace.id),
+                    path: container_ref.clone(),
+                    kind: WorkspaceDriftKind::MissingWorkspaceDir,
+                    repairable: true,
+                });
+                continue;
+            }
+
+            let repos =
+                WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+            for repo in repos {
+                let worktree_path = workspace_dir.join(&repo.name);
+                match worktree_state(&worktree_path) {
+                    WorktreeState::Present => {}
+                    WorktreeState::Missing => {
+                        report.issues.push(WorkspaceDrift {
+                            workspace_id: Some(workspace.id),
+                            path: worktree_path.to_string_lossy().to_string(),
+                            kind: WorkspaceDriftKind::MissingWorktree {
+                                repo_id: repo.id,
+                                repo_name: repo.name.clone(),
+                            },
+                            repairable: true,
+                        });
+                        continue;
+                    }
+                    WorktreeState::Foreign => {
+                        report.issues.push(WorkspaceDrift {
+                            workspace_id: Some(workspace.id),
+                            path: worktree_path.to_string_lossy().to_string(),
+                            kind: WorkspaceDriftKind::NotAWorktree {
+                                repo_id: repo.id,
+                                repo_name: repo.name.clone(),
+                            },
+                            // Recreating the worktree would overwrite whatever
+                            // is there, so this is left for the user.
+                            repairable: false,
+                        });
+                        continue;
+                    }
+                }
+
+                match git.get_current_branch(&worktree_path) {
+                    Ok(actual) if actual != workspace.branch => {
+                        report.issues.push(WorkspaceDrift {
+                            workspace_id: Some(workspace.id),
+                            path: worktree_path.to_string_lossy().to_string(),
+                            kind: WorkspaceDriftKind::BranchMismatch {
+                                repo_id: repo.id,
+                                repo_name: repo.name.clone(),
+                                expected: workspace.branch.clone(),
+                                actual,
+                            },
+                            // Switching branches could discard the user's work,
+                            // so this is left for them to resolve.
+                            repairable: false,
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!(
+                            "Could not read HEAD of worktree {}: {}",
+                            worktree_path.display(),
+                            e
+                        );
+                    }
+                }
+            }
+        }
+
+        let known_dirs: HashSet<PathBuf> = workspaces
+            .iter()
+            .filter_map(|w| w.container_ref.as_ref().map(PathBuf::from))
+            .collect();
+        let default_dir = WorktreeManager::get_default_worktree_base_dir();
+        let current_dir = Self::get_workspace_base_dir();
+        Self::collect_orphans(&default_dir, &known_dirs, &mut report);
+        if current_dir != default_dir {
+            Self::collect_orphans(&current_dir, &known_dirs, &mut report);
+        }
+
+        Ok(report)
+    }
+
+    fn collect_orphans(
+        base_dir: &Path,
+        known_dirs: &HashSet<PathBuf>,
+        report: &mut WorkspaceDriftReport,
+    ) {
+        let Ok(entries) = std::fs::read_dir(base_dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir()
+                && !known_dirs.contains(&path)
+                && is_orphan_candidate(&path, ORPHAN_GRACE_PERIOD)
+            {
+                report.issues.push(WorkspaceDrift {
+                    workspace_id: None,
+                    path: path.to_string_lossy().to_string(),
+                    kind: WorkspaceDriftKind::OrphanedWorkspaceDir,
+                    repairable: true,
+                });
+            }
+        }
+    }
+
+    /// Remove the orphaned directories a scan reported, re-checking each one
+    /// so a directory claimed since the scan is left alone. Returns the
+    /// directories that were removed.
+    pub async fn remove_orphans(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut removed = Vec::new();
+        for path in paths {
+            let path_str = path.to_string_lossy();
+            match DbWorkspace::container_ref_exists(&self.db.pool, &path_str).await {
+                Ok(false) if is_orphan_candidate(path, ORPHAN_GRACE_PERIOD) => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Could not check orphan {}: {}", path.display(), e);
+                    continue;
+                }
+            }
+            match Self::cleanup_workspace_without_repos(path).await {
+                Ok(()) => removed.push(path.clone()),
+                Err(e) => warn!("Failed to remove orphan {}: {}", path.display(), e),
+            }
+        }
+        removed
+    }
+
+    /// Run a scan and log a summary. Used at startup so drift shows up in the
+    /// server logs even if nobody asks for a report.
+    pub async fn log_drift_scan(&self) {
+        match self.scan_drift().await {
+            Ok(report) if report.issues.is_empty() => {
+                info!(
+                    "Workspace drift scan: {} workspaces consistent",
+                    report.workspaces_scanned
+                );
+            }
+            Ok(report) => {
+                warn!(
+                    "Workspace drift scan found {} issues across {} workspaces ({} repairable)",
+                    report.issues.len(),
+                    report.workspaces_scanned,
+                    report.issues.iter().filter(|i| i.repairable).count()
+                );
+            }
+            Err(e) => warn!("Workspace drift scan failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(
+        workspace_id: Option<Uuid>,
+        kind: WorkspaceDriftKind,
+        repairable: bool,
+    ) -> WorkspaceDrift {
+        WorkspaceDrift {
+            workspace_id,
+            path: String::new(),
+            kind,
+            repairable,
+        }
+    }
+
+    #[test]
+    fn repairable_ids_are_deduplicated_and_skip_unrepairable() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let report = WorkspaceDriftReport {
+            workspaces_scanned: 2,
+            issues: vec![
+                issue(Some(a), WorkspaceDriftKind::MissingWorkspaceDir, true),
+                issue(
+                    Some(a),
+                    WorkspaceDriftKind::MissingWorktree {
+                        repo_id: Uuid::new_v4(),
+                        repo_name: "x".into(),
+                    },
+                    true,
+                ),
+                issue(
+                    Some(b),
+                    WorkspaceDriftKind::BranchMismatch {
+                        repo_id: Uuid::new_v4(),
+                        repo_name: "y".into(),
+                        expected: "vk/1".into(),
+                        actual: "main".into(),
+                    },
+                    false,
+                ),
+                issue(None, WorkspaceDriftKind::OrphanedWorkspaceDir, true),
+            ],
+        };
+
+        assert_eq!(report.repairable_workspace_ids(), vec![a]);
+        assert!(report.has_orphans());
+    }
+
+    #[test]
+    fn orphans_are_only_removed_by_unfiltered_repairs() {
+        let mut orphan = issue(None, WorkspaceDriftKind::OrphanedWorkspaceDir, true);
+        orphan.path = "/tmp/vk/orphan".to_string();
+        let report = WorkspaceDriftReport {
+            workspaces_scanned: 1,
+            issues: vec![
+                issue(
+                    Some(Uuid::new_v4()),
+                    WorkspaceDriftKind::MissingWorkspaceDir,
+                    true,
+                ),
+                orphan,
+            ],
+        };
+
+        assert_eq!(
+            report.orphans_to_remove(None),
+            vec![PathBuf::from("/tmp/vk/orphan")]
+        );
+        assert!(report.orphans_to_remove(Some(&[Uuid::new_v4()])).is_empty());
+    }
+
+    #[test]
+    fn classifies_worktrees_by_their_git_file() {
+        let td = tempfile::TempDir::new().unwrap();
+        let worktree = td.path().join("linked");
+        std::fs::create_dir(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: /repo/.git/worktrees/linked").unwrap();
+        let clone = td.path().join("clone");
+        std::fs::create_dir_all(clone.join(".git")).unwrap();
+        let plain = td.path().join("plain");
+        std::fs::create_dir(&plain).unwrap();
+        std::fs::write(plain.join("notes.txt"), "mine").unwrap();
+        let half_created = td.path().join("half");
+        std::fs::create_dir(&half_created).unwrap();
+
+        assert_eq!(worktree_state(&worktree), WorktreeState::Present);
+        assert_eq!(worktree_state(&clone), WorktreeState::Foreign);
+        assert_eq!(worktree_state(&plain), WorktreeState::Foreign);
+        assert_eq!(worktree_state(&half_created), WorktreeState::Missing);
+        assert_eq!(
+            worktree_state(&td.path().join("absent")),
+            WorktreeState::Missing
+        );
+    }
+
+    #[test]
+    fn only_workspace_like_dirs_are_orphan_candidates() {
+        let td = tempfile::TempDir::new().unwrap();
+        let workspace = td.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("repo")).unwrap();
+        std::fs::write(
+            workspace.join("repo/.git"),
+            "gitdir: /repo/.git/worktrees/x",
+        )
+        .unwrap();
+        let empty = td.path().join("empty");
+        std::fs::create_dir(&empty).unwrap();
+        let unrelated = td.path().join("unrelated");
+        std::fs::create_dir_all(unrelated.join("src")).unwrap();
+
+        assert!(is_orphan_candidate(&workspace, Duration::ZERO));
+        assert!(is_orphan_candidate(&empty, Duration::ZERO));
+        assert!(!is_orphan_candidate(&unrelated, Duration::ZERO));
+        // Freshly created directories may belong to a workspace still being
+        // set up.
+        assert!(!is_orphan_candidate(&workspace, ORPHAN_GRACE_PERIOD));
+    }
+}
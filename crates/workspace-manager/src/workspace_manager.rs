@@ -7,7 +7,7 @@ use db::{
         repo::{Repo, RepoError},
         requests::WorkspaceRepoInput,
         session::Session,
-        workspace::Workspace as DbWorkspace,
+        workspace::{Workspace as DbWorkspace, WorkspaceError as DbWorkspaceError},
         workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
     },
 };
@@ -17,6 +17,8 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 use worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
 
+use crate::reconcile::{ORPHAN_GRACE_PERIOD, is_orphan_candidate};
+
 #[derive(Debug, Clone)]
 pub struct RepoWorkspaceInput {
     pub repo: Repo,
@@ -39,6 +41,8 @@ pub enum WorkspaceError {
     #[error(transparent)]
     Repo(#[from] RepoError),
     #[error(transparent)]
+    DbWorkspace(#[from] DbWorkspaceError),
+    #[error(transparent)]
     Worktree(#[from] WorktreeError),
     #[error(transparent)]
     GitService(#[from] GitServiceError),
@@ -196,7 +200,7 @@ impl ManagedWorkspace {
 
 #[derive(Clone)]
 pub struct WorkspaceManager {
-    db: DBService,
+    pub(crate) db: DBService,
 }
 
 impl WorkspaceManager {
@@ -237,6 +241,16 @@ impl WorkspaceManager {
                 }
             }
 
+            let recordings_dir = utils::terminal_recordings::workspace_recordings_dir(workspace_id);
+            match tokio::fs::remove_dir_all(&recordings_dir).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!(
+                    "Failed to remove terminal recordings for workspace {}: {}",
+                    workspace_id, e
+                ),
+            }
+
             if let Some(workspace_dir) = workspace_dir {
                 info!(
                     "Starting background cleanup for workspace {} at {}",
@@ -585,7 +599,7 @@ impl WorkspaceManager {
             };
 
             let path = entry.path();
-            if !path.is_dir() {
+            if !path.is_dir() || !is_orphan_candidate(&path, ORPHAN_GRACE_PERIOD) {
                 continue;
             }
 
@@ -609,7 +623,9 @@ impl WorkspaceManager {
         }
     }
 
-    async fn cleanup_workspace_without_repos(workspace_dir: &Path) -> Result<(), WorkspaceError> {
+    pub(crate) async fn cleanup_workspace_without_repos(
+        workspace_dir: &Path,
+    ) -> Result<(), WorkspaceError> {
         info!(
             "Cleaning up orphaned workspace at {}",
             workspace_dir.display()
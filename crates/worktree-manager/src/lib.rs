@@ -1,3 +1,4 @@
+mod reflink;
 mod worktree_manager;
 
 pub use worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
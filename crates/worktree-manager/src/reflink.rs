@@ -0,0 +1,107 @@
+//! Copy-on-write cloning of a checkout. On filesystems with reflink support
+//! (APFS, Btrfs, XFS) a new worktree can start as a clone of the repo's main
+//! checkout, including ignored directories such as `node_modules`, without
+//! duplicating the data on disk.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{LazyLock, Mutex},
+};
+
+use tracing::debug;
+
+// Probe results keyed by (source checkout, target parent directory)
+static SUPPORT_CACHE: LazyLock<Mutex<HashMap<(PathBuf, PathBuf), bool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether files under `source_dir` can be reflinked into `target_parent`.
+/// Both must be on the same reflink-capable filesystem; the answer is probed
+/// once per pair by cloning a small file.
+pub(crate) fn is_supported(source_dir: &Path, target_parent: &Path) -> bool {
+    let key = (source_dir.to_path_buf(), target_parent.to_path_buf());
+    if let Some(supported) = SUPPORT_CACHE.lock().unwrap().get(&key) {
+        return *supported;
+    }
+    let supported = probe(source_dir, target_parent);
+    debug!(
+        "Reflink copies from {} to {} supported: {}",
+        source_dir.display(),
+        target_parent.display(),
+        supported
+    );
+    SUPPORT_CACHE.lock().unwrap().insert(key, supported);
+    supported
+}
+
+fn probe(source_dir: &Path, target_parent: &Path) -> bool {
+    let dot_git = source_dir.join(".git");
+    let sample = if dot_git.is_dir() {
+        dot_git.join("HEAD")
+    } else {
+        dot_git
+    };
+    if !sample.is_file() || fs::create_dir_all(target_parent).is_err() {
+        return false;
+    }
+    let target = target_parent.join(format!(".vk-reflink-probe-{}", std::process::id()));
+    let supported = clone_entries(&[sample], &target).is_ok();
+    let _ = fs::remove_file(&target);
+    supported
+}
+
+/// Clone every top-level entry of `source_dir` except `.git` into the
+/// existing directory `target_dir`, failing rather than falling back to a
+/// full copy when the filesystem can't share extents.
+pub(crate) fn clone_checkout(source_dir: &Path, target_dir: &Path) -> io::Result<()> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(source_dir)? {
+        let entry = entry?;
+        if entry.file_name() != ".git" {
+            entries.push(entry.path());
+        }
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+    clone_entries(&entries, target_dir)
+}
+
+#[cfg(target_os = "linux")]
+fn clone_command() -> Command {
+    let mut cmd = Command::new("cp");
+    cmd.args(["-a", "--reflink=always"]);
+    cmd
+}
+
+#[cfg(target_os = "macos")]
+fn clone_command() -> Command {
+    // `-c` clones with clonefile(2) and fails if that isn't possible
+    let mut cmd = Command::new("cp");
+    cmd.args(["-c", "-a"]);
+    cmd
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn clone_entries(sources: &[PathBuf], target: &Path) -> io::Result<()> {
+    let output = clone_command().args(sources).arg(target).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "cp exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn clone_entries(_sources: &[PathBuf], _target: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflink copies are not supported on this platform",
+    ))
+}
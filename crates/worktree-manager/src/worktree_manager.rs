@@ -2,16 +2,28 @@ use std::{
     collections::HashMap,
     fs,
     path::{Path, PathBuf},
-    sync::{Arc, LazyLock, Mutex, OnceLock},
+    sync::{
+        Arc, LazyLock, Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 static WORKSPACE_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
-use git::{GitService, GitServiceError};
+// Create git worktrees as copy-on-write clones of the main checkout when the
+// filesystem allows it
+static REFLINK_WORKTREES: AtomicBool = AtomicBool::new(false);
+
+use git::{
+    GitService, GitServiceError,
+    vcs::{VcsKind, vcs_for},
+};
 use thiserror::Error;
 use tracing::{debug, info, trace};
 use utils::{path::normalize_macos_private_alias, shell::resolve_executable_path};
 
+use crate::reflink;
+
 // Global synchronization for worktree creation to prevent race conditions
 static WORKTREE_CREATION_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
@@ -56,6 +68,13 @@ impl WorktreeManager {
         let _ = WORKSPACE_DIR_OVERRIDE.set(path);
     }
 
+    /// Clone new git worktrees from the repo's main checkout (reflink copy)
+    /// instead of checking every file out. Falls back to a regular checkout
+    /// when the filesystem doesn't support it.
+    pub fn set_reflink_worktrees(enabled: bool) {
+        REFLINK_WORKTREES.store(enabled, Ordering::Relaxed);
+    }
+
     /// Create a worktree with a new branch
     pub async fn create_worktree(
         repo_path: &Path,
@@ -70,7 +89,7 @@ impl WorktreeManager {
             let base_branch_owned = base_branch.to_string();
 
             tokio::task::spawn_blocking(move || {
-                GitService::new().create_branch(
+                vcs_for(&repo_path_owned).create_branch(
                     &repo_path_owned,
                     &branch_name_owned,
                     &base_branch_owned,
@@ -104,6 +123,10 @@ impl WorktreeManager {
         // Acquire the lock for this specific worktree path
         let _guard = lock.lock().await;
 
+        if VcsKind::detect(repo_path) != VcsKind::Git {
+            return Self::ensure_vcs_worktree(repo_path, branch_name, worktree_path).await;
+        }
+
         // Check if worktree already exists and is properly set up
         if Self::is_worktree_properly_set_up(repo_path, worktree_path).await? {
             trace!("Worktree already properly set up at path: {}", path_str);
@@ -115,6 +138,39 @@ impl WorktreeManager {
         Self::recreate_worktree_internal(repo_path, branch_name, worktree_path).await
     }
 
+    /// Create a working copy for a repository managed by an experimental
+    /// non-git backend. These have no worktree metadata to validate, so an
+    /// existing directory is reused as is.
+    async fn ensure_vcs_worktree(
+        repo_path: &Path,
+        branch_name: &str,
+        worktree_path: &Path,
+    ) -> Result<(), WorktreeError> {
+        let repo_path = repo_path.to_path_buf();
+        let branch_name = branch_name.to_string();
+        let worktree_path = worktree_path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> Result<(), WorktreeError> {
+            if worktree_path.exists() {
+                return Ok(());
+            }
+            if let Some(parent) = worktree_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let vcs = vcs_for(&repo_path);
+            vcs.add_worktree(&repo_path, &worktree_path, &branch_name)?;
+            info!(
+                "Created {:?} working copy {} at {}",
+                vcs.kind(),
+                branch_name,
+                worktree_path.display()
+            );
+            Ok(())
+        })
+        .await
+        .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
+    }
+
     /// Internal worktree recreation function (always recreates)
     async fn recreate_worktree_internal(
         repo_path: &Path,
@@ -142,7 +198,15 @@ impl WorktreeManager {
                 .map_err(WorktreeError::Io)?;
         }
 
-        // Step 3: Create the worktree with retry logic for metadata conflicts (non-blocking)
+        // Step 3: Prefer a copy-on-write clone of the main checkout when enabled
+        if REFLINK_WORKTREES.load(Ordering::Relaxed)
+            && Self::create_reflinked_worktree(repo_path, &branch_name_owned, &worktree_path_owned)
+                .await?
+        {
+            return Ok(());
+        }
+
+        // Step 4: Create the worktree with retry logic for metadata conflicts (non-blocking)
         Self::create_worktree_with_retry(
             repo_path,
             &branch_name_owned,
@@ -299,6 +363,68 @@ impl WorktreeManager {
         }
     }
 
+    /// Create a worktree by cloning the files of the repo's main checkout and
+    /// resetting them to the branch. Ignored files (dependencies, build
+    /// output) come along, so setup scripts mostly have nothing to do.
+    /// Returns `false`, leaving nothing behind, when the filesystem can't
+    /// clone or any step fails, so the caller can do a regular checkout.
+    async fn create_reflinked_worktree(
+        git_repo_path: &Path,
+        branch_name: &str,
+        worktree_path: &Path,
+    ) -> Result<bool, WorktreeError> {
+        let git_repo_path = git_repo_path.to_path_buf();
+        let branch_name = branch_name.to_string();
+        let worktree_path = worktree_path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            let Some(parent) = worktree_path.parent() else {
+                return false;
+            };
+            if !reflink::is_supported(&git_repo_path, parent) {
+                return false;
+            }
+
+            let git_service = GitService::new();
+            let result = git_service
+                .add_worktree_without_checkout(&git_repo_path, &worktree_path, &branch_name)
+                .map_err(WorktreeError::from)
+                .and_then(|()| {
+                    reflink::clone_checkout(&git_repo_path, &worktree_path)
+                        .map_err(WorktreeError::from)
+                })
+                .and_then(|()| {
+                    git_service
+                        .sync_copied_worktree_to_head(&worktree_path)
+                        .map_err(WorktreeError::from)
+                });
+            match result {
+                Ok(()) => {
+                    info!(
+                        "Successfully created worktree {} at {} (reflink clone)",
+                        branch_name,
+                        worktree_path.display()
+                    );
+                    true
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Reflink worktree creation failed, falling back to checkout: {}",
+                        e
+                    );
+                    if let Err(e) =
+                        Self::comprehensive_worktree_cleanup(&git_repo_path, &worktree_path)
+                    {
+                        debug!("Cleanup after failed reflink clone failed: {}", e);
+                    }
+                    false
+                }
+            }
+        })
+        .await
+        .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))
+    }
+
     /// Create worktree with retry logic in non-blocking manner
     async fn create_worktree_with_retry(
         git_repo_path: &Path,
@@ -423,6 +549,18 @@ impl WorktreeManager {
         };
 
         if let Some(repo_path) = resolved_repo_path {
+            if VcsKind::detect(&repo_path) != VcsKind::Git {
+                let vcs = vcs_for(&repo_path);
+                if let Err(e) = vcs.remove_worktree(&repo_path, &worktree.worktree_path) {
+                    debug!(
+                        "{:?} working copy removal non-fatal error: {}",
+                        vcs.kind(),
+                        e
+                    );
+                }
+                Self::simple_worktree_cleanup(&worktree.worktree_path).await?;
+                return Ok(());
+            }
             Self::comprehensive_worktree_cleanup_async(&repo_path, &worktree.worktree_path).await?;
         } else {
             // Can't determine repo path, just clean up the worktree directory
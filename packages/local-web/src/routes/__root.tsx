@@ -19,7 +19,7 @@ function RootRouteComponent() {
   useEffect(() => {
     if (!posthog || !machineId) return;
 
-    if (config?.analytics_enabled) {
+    if (config?.analytics.usage) {
       posthog.opt_in_capturing();
       posthog.identify(machineId);
       console.log('[Analytics] Analytics enabled and user identified');
@@ -27,7 +27,7 @@ function RootRouteComponent() {
       posthog.opt_out_capturing();
       console.log('[Analytics] Analytics disabled by user preference');
     }
-  }, [config?.analytics_enabled, machineId, posthog]);
+  }, [config?.analytics.usage, machineId, posthog]);
 
   return (
     <I18nextProvider i18n={i18n}>
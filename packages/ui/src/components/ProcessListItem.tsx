@@ -28,6 +28,8 @@ function getRunReasonLabel(runReason: string): string {
       return 'Archive Script';
     case 'devserver':
       return 'Dev Server';
+    case 'changeexplanation':
+      return 'Change Explanation';
     default:
       return runReason;
   }
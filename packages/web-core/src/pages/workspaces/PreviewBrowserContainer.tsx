@@ -13,7 +13,10 @@ import {
   PHONE_FRAME_PADDING,
 } from '@vibe/ui/components/PreviewBrowser';
 import { usePreviewDevServer } from '@/features/workspace/model/hooks/usePreviewDevServer';
-import { usePreviewUrl } from '@/shared/hooks/usePreviewUrl';
+import {
+  usePreviewUrl,
+  type PreviewUrlInfo,
+} from '@/shared/hooks/usePreviewUrl';
 import { useIsMobile } from '@/shared/hooks/useIsMobile';
 import {
   usePreviewSettings,
@@ -185,7 +188,11 @@ export function PreviewBrowserContainer({
   const triggerPreviewRefresh = useUiPreferencesStore(
     (s) => s.triggerPreviewRefresh
   );
-  const { repos, workspaceId: activeWorkspaceId } = useWorkspaceContext();
+  const {
+    repos,
+    workspaceId: activeWorkspaceId,
+    activeWorkspaces,
+  } = useWorkspaceContext();
   const { previewProxyPort } = useUserSystem();
   const hostId = useHostId();
 
@@ -209,7 +216,27 @@ export function PreviewBrowserContainer({
     );
   }, [runningDevServers]);
   const { logs } = useLogStream(primaryDevServer?.id ?? '');
-  const urlInfo = usePreviewUrl(logs, previewProxyPort ?? undefined);
+  const detectedUrlInfo = usePreviewUrl(logs, previewProxyPort ?? undefined);
+  // The port the backend published for the dev server (the workspace's
+  // configured port, or the one it reported) wins over URLs parsed from the
+  // logs, which may name a port inside a container. A logged URL on the same
+  // port still supplies the path.
+  const publishedPort = activeWorkspaces.find(
+    (w) => w.id === (activeWorkspaceId ?? workspaceId)
+  )?.devServerPort;
+  const urlInfo = useMemo<PreviewUrlInfo | undefined>(() => {
+    if (publishedPort == null || runningDevServers.length === 0) {
+      return detectedUrlInfo;
+    }
+    if (detectedUrlInfo?.port === publishedPort) {
+      return detectedUrlInfo;
+    }
+    return {
+      url: `http://localhost:${publishedPort}/`,
+      port: publishedPort,
+      scheme: 'http',
+    };
+  }, [detectedUrlInfo, publishedPort, runningDevServers.length]);
 
   // Detect failed dev server process (failed status or completed with non-zero exit code)
   const failedDevServerProcess = devServerProcesses.find(
@@ -801,8 +801,40 @@ export function GeneralSettingsSection() {
           id="analytics-enabled"
           label={t('settings.general.privacy.telemetry.label')}
           description={t('settings.general.privacy.telemetry.helper')}
-          checked={draft?.analytics_enabled ?? false}
-          onChange={(checked) => updateDraft({ analytics_enabled: checked })}
+          checked={draft?.analytics.usage ?? false}
+          onChange={(checked) =>
+            updateDraft({ analytics: { ...draft!.analytics, usage: checked } })
+          }
+        />
+        <SettingsCheckbox
+          id="analytics-errors"
+          label={t('settings.general.privacy.errorReports.label')}
+          description={t('settings.general.privacy.errorReports.helper')}
+          checked={draft?.analytics.errors ?? false}
+          onChange={(checked) =>
+            updateDraft({ analytics: { ...draft!.analytics, errors: checked } })
+          }
+        />
+        <SettingsCheckbox
+          id="error-reports-metadata-only"
+          label={t('settings.general.privacy.errorMetadataOnly.label')}
+          description={t('settings.general.privacy.errorMetadataOnly.helper')}
+          checked={draft?.error_reports_metadata_only ?? false}
+          disabled={!draft?.analytics.errors}
+          onChange={(checked) =>
+            updateDraft({ error_reports_metadata_only: checked })
+          }
+        />
+        <SettingsCheckbox
+          id="analytics-performance"
+          label={t('settings.general.privacy.performance.label')}
+          description={t('settings.general.privacy.performance.helper')}
+          checked={draft?.analytics.performance ?? false}
+          onChange={(checked) =>
+            updateDraft({
+              analytics: { ...draft!.analytics, performance: checked },
+            })
+          }
         />
       </SettingsCard>
 
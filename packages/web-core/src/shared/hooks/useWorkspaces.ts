@@ -27,6 +27,7 @@ export interface SidebarWorkspace {
   isArchived?: boolean;
   hasPendingApproval?: boolean;
   hasRunningDevServer?: boolean;
+  devServerPort?: number;
   hasUnseenActivity?: boolean;
   latestProcessCompletedAt?: string;
   latestProcessStatus?: 'running' | 'completed' | 'failed' | 'killed';
@@ -71,6 +72,7 @@ function toSidebarWorkspace(
     isRunning: ws.is_running,
     isPinned: ws.pinned,
     isArchived: ws.archived,
+    devServerPort: ws.dev_server_port ?? undefined,
     // Additional data from summary
     hasPendingApproval: summary?.has_pending_approval,
     hasRunningDevServer: summary?.has_running_dev_server,
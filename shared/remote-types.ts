@@ -75,6 +75,14 @@ export type RelayHost = { id: string, owner_user_id: string, machine_id: string,
 
 export type ListRelayHostsResponse = { hosts: Array<RelayHost>, };
 
+export type RelayHostRole = "viewer" | "operator" | "admin";
+
+export type RelayHostShare = { host_id: string, user_id: string, role: RelayHostRole, granted_by_user_id: string | null, created_at: string, updated_at: string, };
+
+export type ListRelayHostSharesResponse = { shares: Array<RelayHostShare>, };
+
+export type UpsertRelayHostShareRequest = { role: RelayHostRole, };
+
 export type CreateRemoteSessionResponse = { session_id: string, };
 
 export enum MemberRole { ADMIN = "ADMIN", MEMBER = "MEMBER" }
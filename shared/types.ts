@@ -10,6 +10,123 @@ export type Project = { id: string, name: string, default_agent_working_dir: str
 
 export type UpdateRepo = { display_name?: string | null, setup_script?: string | null, cleanup_script?: string | null, archive_script?: string | null, copy_files?: string | null, parallel_setup_script?: boolean | null, dev_server_script?: string | null, default_target_branch?: string | null, default_working_dir?: string | null, };
 
+/**
+ * CPU and memory caps for executor, script and dev server processes started
+ * in a repo. A coding agent spanning several repos gets the strictest caps
+ * of all of them.
+ */
+export type RepoResourceLimits = { repo_id: string, memory_limit_mb: number | null, 
+/**
+ * Percent of one CPU; 200 allows two full cores. Needs cgroup v2 on
+ * Linux and is ignored elsewhere.
+ */
+cpu_limit_percent: number | null, updated_at: string, };
+
+export type SetRepoResourceLimits = { memory_limit_mb: number | null, cpu_limit_percent: number | null, };
+
+/**
+ * A workspace started on a paired host from this instance. The remote ids
+ * refer to records on that host, reached through the relay.
+ */
+export type RemoteExecution = { id: string, host_id: string, remote_workspace_id: string, 
+/**
+ * The coding agent run the workspace was started with.
+ */
+remote_execution_process_id: string, name: string | null, prompt: string, created_at: string, };
+
+/**
+ * The Docker image a repo's processes run in under the Docker execution
+ * backend. The image needs the coding agent CLIs the repo's tasks use.
+ */
+export type RepoContainerImage = { repo_id: string, image: string, 
+/**
+ * Comma-separated container ports dev servers listen on, e.g.
+ * `3000, 5173`. Each is published on a free port of 127.0.0.1, so the
+ * server inside has to listen on all interfaces.
+ */
+ports: string | null, updated_at: string, };
+
+export type SetRepoContainerImage = { image: string, ports: string | null, };
+
+export type ProtectedPathAction = "require_approval" | "deny";
+
+/**
+ * A gitignore-style pattern, relative to the repo root, for files coding
+ * agents may not change freely, e.g. `infra/**` or `*.lock`.
+ */
+export type RepoProtectedPath = { id: string, repo_id: string, pattern: string, action: ProtectedPathAction, created_at: string, };
+
+export type CreateRepoProtectedPath = { pattern: string, action: ProtectedPathAction, };
+
+/**
+ * A command run in the repo when a coding agent session completes, e.g.
+ * `pnpm lint` or `cargo test`. It passes when it exits with status 0.
+ */
+export type RepoCheckCommand = { id: string, repo_id: string, name: string, command: string, 
+/**
+ * Checks run one after the other in this order.
+ */
+position: bigint, created_at: string, };
+
+export type RepoCheckCommandInput = { name: string, command: string, };
+
+export type SetRepoCheckCommands = { checks: Array<RepoCheckCommandInput>, };
+
+export type IssueTrackerKind = "github" | "linear" | "jira";
+
+/**
+ * The issue tracker a repo's work is planned in, for importing issues as
+ * workspaces.
+ */
+export type RepoIssueTracker = { repo_id: string, provider: IssueTrackerKind, 
+/**
+ * GitHub `owner/name`, Linear team key or Jira project key.
+ */
+project: string, 
+/**
+ * Jira site, e.g. `https://acme.atlassian.net`.
+ */
+base_url: string | null, 
+/**
+ * Jira account email the token belongs to. Without it the token is sent
+ * as a bearer token, as Jira Data Center personal access tokens are.
+ */
+username: string | null, has_token: boolean, 
+/**
+ * Close imported issues once all PRs of their workspace are merged.
+ */
+close_on_merge: boolean, updated_at: string, };
+
+export type SetRepoIssueTracker = { provider: IssueTrackerKind, project: string, base_url: string | null, username: string | null, 
+/**
+ * Leave unset to keep the stored token of the same provider.
+ */
+token: string | null, close_on_merge: boolean, };
+
+/**
+ * A resource a repo's coding agents use heavily. Executions holding a tag
+ * count against that tag's limit in `execution_tag_limits`.
+ */
+export type ResourceTag = "cpu_heavy" | "gpu" | "network";
+
+export type RepoResourceTags = { repo_id: string, tags: Array<ResourceTag>, };
+
+export type SetRepoResourceTags = { tags: Array<ResourceTag>, };
+
+export type RepoSetupCache = { repo_id: string, 
+/**
+ * Comma-separated directories relative to the repo root, e.g.
+ * `node_modules, target`.
+ */
+cache_paths: string, 
+/**
+ * Comma-separated files whose contents key the cache. Defaults to the
+ * common lockfiles when empty.
+ */
+key_files: string | null, updated_at: string, };
+
+export type SetRepoSetupCache = { cache_paths: string, key_files: string | null, };
+
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, 
 /**
  * Ranking score based on git history (higher = more recently/frequently edited)
@@ -24,12 +141,159 @@ export type CreateWorkspaceRepo = { repo_id: string, target_branch: string, };
 
 export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, setup_script: string | null, cleanup_script: string | null, archive_script: string | null, copy_files: string | null, parallel_setup_script: boolean, dev_server_script: string | null, default_target_branch: string | null, default_working_dir: string | null, created_at: Date, updated_at: Date, };
 
+export type DocSource = { id: string, project_id: string, name: string, kind: DocSourceKind, location: string, 
+/**
+ * How often the source is re-indexed. `None` means manual refresh only.
+ */
+refresh_interval_minutes: bigint | null, last_indexed_at: string | null, last_error: string | null, created_at: string, updated_at: string, };
+
+export type DocSourceKind = "url" | "local_folder" | "openapi";
+
+export type CreateDocSource = { name: string, kind: DocSourceKind, 
+/**
+ * A URL, or an absolute path for local sources.
+ */
+location: string, refresh_interval_minutes: bigint | null, };
+
+export type DocChunk = { id: string, source_id: string, ordinal: bigint, title: string, content: string, created_at: string, };
+
+export type DocSearchHit = { source_id: string, source_name: string, title: string, content: string, score: number, };
+
+export type PackageManager = "npm" | "pnpm" | "yarn" | "bun";
+
+export type ScriptSuggestions = { 
+/**
+ * None when the repo has no `package.json` at its root.
+ */
+package_manager: PackageManager | null, setup_script: string | null, dev_server_script: string | null, };
+
 export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
 
 export type CreateTag = { tag_name: string, content: string, };
 
 export type UpdateTag = { tag_name: string | null, content: string | null, };
 
+export type TaskTemplate = { id: string, name: string, prompt: string, 
+/**
+ * `None` uses the configured default executor.
+ */
+executor_config: ExecutorConfig | null, 
+/**
+ * Target branch for every repo. `None` uses each repo's default.
+ */
+base_branch: string | null, 
+/**
+ * Extra environment variables for every process run in the workspace.
+ */
+env_vars: { [key in string]?: string }, 
+/**
+ * Runs in the workspace root before the repos' setup scripts.
+ */
+setup_script: string | null, repo_ids: Array<string>, created_at: string, updated_at: string, };
+
+export type CreateTaskTemplate = { name: string, prompt: string, executor_config?: ExecutorConfig, base_branch?: string, env_vars?: { [key in string]?: string }, setup_script?: string, repo_ids?: Array<string>, };
+
+export type UpdateTaskTemplate = { name?: string, prompt?: string, executor_config?: ExecutorConfig | null, base_branch?: string | null, env_vars?: { [key in string]?: string }, setup_script?: string | null, repo_ids?: Array<string>, };
+
+export type ToolCategory = "shell" | "file_edit" | "file_read" | "web" | "mcp" | "question" | "other";
+
+export type NotificationChannel = { "type": "desktop" } | { "type": "sound" } | { "type": "slack", webhook_url: string, channel: string | null, } | { "type": "webhook", url: string, };
+
+export type ApprovalNotificationRule = { id: string, 
+/**
+ * Rules are evaluated in ascending position; the first match wins.
+ */
+position: bigint, 
+/**
+ * `None` matches any category.
+ */
+tool_category: ToolCategory | null, 
+/**
+ * `None` matches any repo. Otherwise the workspace must include it.
+ */
+repo_id: string | null, 
+/**
+ * An empty list silences matching approvals.
+ */
+channels: Array<NotificationChannel>, created_at: string, updated_at: string, };
+
+export type CreateApprovalNotificationRule = { position?: bigint, tool_category?: ToolCategory, repo_id?: string, channels: Array<NotificationChannel>, };
+
+export type UpdateApprovalNotificationRule = { position?: bigint, tool_category?: ToolCategory | null, repo_id?: string | null, channels?: Array<NotificationChannel>, };
+
+export type AuditAction = "workspace_deleted" | "process_killed" | "branch_force_pushed" | "approval_policy_changed" | "relay_client_removed";
+
+export type AuditActorKind = "local" | "relay";
+
+export type AuditLogEntry = { id: string, action: AuditAction, actor_kind: AuditActorKind, 
+/**
+ * Paired relay client that made the request, if known.
+ */
+actor_client_id: string | null, 
+/**
+ * Name of the paired relay client.
+ */
+actor_name: string | null, 
+/**
+ * Kind of thing acted on, e.g. `workspace` or `execution_process`.
+ */
+target_type: string, target_id: string | null, details: JsonValue, created_at: string, };
+
+export type AuditLogQuery = { action?: AuditAction | null, actor_kind?: AuditActorKind | null, actor_client_id?: string | null, target_id?: string | null, 
+/**
+ * Only entries at or after this time.
+ */
+since?: string | null, 
+/**
+ * Only entries before this time, for paging back from the last entry
+ * of the previous page.
+ */
+before?: string | null, limit?: number | null, };
+
+export type Board = { id: string, name: string, created_at: string, updated_at: string, };
+
+export type BoardColumn = { id: string, board_id: string, name: string, 
+/**
+ * Columns are shown in ascending position, starting at 0.
+ */
+position: bigint, created_at: string, updated_at: string, };
+
+export type BoardCard = { board_id: string, workspace_id: string, column_id: string, 
+/**
+ * Position within the column, starting at 0.
+ */
+position: bigint, updated_at: string, };
+
+export type BoardState = { board: Board, columns: Array<BoardColumn>, cards: Array<BoardCard>, };
+
+export type CreateBoard = { name: string, 
+/**
+ * Names of the columns to create, in order.
+ */
+columns?: Array<string>, };
+
+export type UpdateBoard = { name: string, };
+
+export type CreateBoardColumn = { name: string, 
+/**
+ * Where to insert the column. Defaults to the end.
+ */
+position?: bigint, };
+
+export type UpdateBoardColumn = { name: string, };
+
+export type ReorderBoardColumns = { 
+/**
+ * Every column of the board, in the new order.
+ */
+column_ids: Array<string>, };
+
+export type MoveBoardCard = { column_id: string, 
+/**
+ * Where to insert the card in the column. Defaults to the end.
+ */
+position?: bigint, };
+
 export type DraftFollowUpData = { message: string, executor_config: ExecutorConfig, };
 
 export type DraftWorkspaceData = { message: string, repos: Array<DraftWorkspaceRepo>, executor_config: ExecutorConfig | null, linked_issue: DraftWorkspaceLinkedIssue | null, attachments: Array<DraftWorkspaceAttachment>, };
@@ -152,12 +416,145 @@ export type CreateScratch = { payload: ScratchPayload, };
 
 export type UpdateScratch = { payload: ScratchPayload, };
 
-export type Workspace = { id: string, task_id: string | null, container_ref: string | null, branch: string, setup_completed_at: string | null, created_at: string, updated_at: string, archived: boolean, pinned: boolean, name: string | null, worktree_deleted: boolean, };
+export type Workspace = { id: string, task_id: string | null, container_ref: string | null, branch: string, setup_completed_at: string | null, created_at: string, updated_at: string, archived: boolean, pinned: boolean, name: string | null, worktree_deleted: boolean, 
+/**
+ * Port the running dev server is published on: the workspace's
+ * configured port, or else the one detected in its output.
+ */
+dev_server_port: number | null, };
 
-export type WorkspaceWithStatus = { is_running: boolean, is_errored: boolean, id: string, task_id: string | null, container_ref: string | null, branch: string, setup_completed_at: string | null, created_at: string, updated_at: string, archived: boolean, pinned: boolean, name: string | null, worktree_deleted: boolean, };
+export type WorkspaceWithStatus = { is_running: boolean, is_errored: boolean, 
+/**
+ * Per-repo progress of the latest command run across the workspace's
+ * repos, if any.
+ */
+repo_command: RepoCommandStatus | null, 
+/**
+ * Progress of the child workspaces split off from this one, if any.
+ */
+child_progress: ChildWorkspaceProgress | null, 
+/**
+ * Drift of the workspace branch from each repo's target branch, as last
+ * checked in the background. Empty until the first check.
+ */
+branch_freshness: Array<WorkspaceBranchFreshness>, 
+/**
+ * Results of the repo checks run when the latest coding agent session
+ * completed. Empty if the workspace's repos have no checks.
+ */
+checks: Array<WorkspaceCheckResult>, 
+/**
+ * CI checks of the branch's latest commit, as reported by CI webhooks.
+ */
+ci_status: WorkspaceCiStatus | null, id: string, task_id: string | null, container_ref: string | null, branch: string, setup_completed_at: string | null, created_at: string, updated_at: string, archived: boolean, pinned: boolean, name: string | null, worktree_deleted: boolean, 
+/**
+ * Port the running dev server is published on: the workspace's
+ * configured port, or else the one detected in its output.
+ */
+dev_server_port: number | null, };
 
 export type Session = { id: string, workspace_id: string, name: string | null, executor: string | null, agent_working_dir: string | null, created_at: string, updated_at: string, };
 
+/**
+ * Limits for a coding agent run, in minutes. `None` inherits the next
+ * configured level; `0` disables the limit.
+ */
+export type ExecutionTimeouts = { 
+/**
+ * Kill the run once it has been running this long.
+ */
+wall_clock_minutes: number | null, 
+/**
+ * Kill the run once it has produced no output for this long. Time spent
+ * waiting on an approval does not count.
+ */
+idle_minutes: number | null, };
+
+/**
+ * Timeout overrides for the coding agent runs of one session.
+ */
+export type SessionTimeout = { session_id: string, wall_clock_minutes: number | null, idle_minutes: number | null, updated_at: string, };
+
+export type SessionTimeoutsResponse = { 
+/**
+ * The session's own overrides.
+ */
+session: ExecutionTimeouts, 
+/**
+ * The limits its next coding agent run gets, after falling back to the
+ * executor and global configuration.
+ */
+effective: ExecutionTimeouts, };
+
+export type SessionPlanStatus = "pending" | "accepted" | "rejected" | "expired";
+
+export type SessionPlan = { session_id: string, execution_process_id: string, plan: string, 
+/**
+ * Starts at 1 and goes up every time the agent revises the plan.
+ */
+revision: bigint, status: SessionPlanStatus, 
+/**
+ * Approval the agent waits on while the plan is pending.
+ */
+approval_id: string | null, 
+/**
+ * Reason given when the plan was rejected.
+ */
+feedback: string | null, created_at: string, updated_at: string, };
+
+export type RejectPlanRequest = { 
+/**
+ * Told to the agent so it can revise the plan.
+ */
+feedback?: string, };
+
+export type SessionQuestionStatus = "pending" | "answered" | "unanswered";
+
+export type SessionQuestion = { id: string, session_id: string, execution_process_id: string, approval_id: string, questions: Array<ApprovalQuestion>, 
+/**
+ * As forwarded to the agent, in question order.
+ */
+answers: Array<QuestionAnswer> | null, status: SessionQuestionStatus, created_at: string, resolved_at: string | null, };
+
+export type SessionShareLink = { id: string, session_id: string, expires_at: string, created_at: string, };
+
+export type CreateShareLinkRequest = { 
+/**
+ * How long the link works for. Defaults to a day; at most 30 days.
+ */
+expires_in_hours?: number, };
+
+export type CreatedShareLink = { link: SessionShareLink, 
+/**
+ * Only returned here; it can't be recovered later.
+ */
+token: string, 
+/**
+ * API path of the shared session, to open through the relay.
+ */
+path: string, };
+
+export type InterruptedRun = { execution_process_id: string, interrupted_at: string | null, 
+/**
+ * Whether the agent's own session is continued. Otherwise the run's
+ * prompt is sent again in a new agent session.
+ */
+resumes_agent_session: boolean, };
+
+export type ResumeSessionRequest = { 
+/**
+ * Sent to the agent instead of the default instruction to carry on.
+ */
+prompt: string | null, };
+
+export type UpdateQueuedMessageRequest = { message: string, 
+/**
+ * Defaults to the message's current executor config.
+ */
+executor_config?: ExecutorConfig | null, };
+
+export type ReorderQueuedMessagesRequest = { message_ids: Array<string>, };
+
 export type ExecutionProcess = { id: string, session_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, 
 /**
  * dropped: true if this process is excluded from the current
@@ -166,9 +563,17 @@ export type ExecutionProcess = { id: string, session_id: string, run_reason: Exe
  */
 dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+export type LogStream = "stdout" | "stderr";
+
+export type LogSearchHit = { execution_process_id: string, session_id: string, workspace_id: string, run_reason: ExecutionProcessRunReason, line_number: bigint, stream: LogStream, 
+/**
+ * Excerpt of the matching line with matches wrapped in `**`.
+ */
+snippet: string, started_at: string, };
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "archivescript" | "codingagent" | "devserver";
+export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed", timedout = "timedout", interrupted = "interrupted" }
+
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "archivescript" | "codingagent" | "devserver" | "repocommand" | "checkcommand" | "changeexplanation";
 
 export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
 
@@ -182,7 +587,53 @@ export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
 export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
 
-export type ApprovalInfo = { approval_id: string, tool_name: string, execution_process_id: string, is_question: boolean, created_at: string, timeout_at: string, };
+export type ApprovalToolInput = { 
+/**
+ * Shell command the tool runs.
+ */
+command: string | null, 
+/**
+ * File the tool reads or changes.
+ */
+file_path: string | null, 
+/**
+ * Proposed change, as removed (`-`) and added (`+`) lines.
+ */
+diff: string | null, 
+/**
+ * The tool input as JSON, for tools without a command, path or diff.
+ */
+raw_input: string | null, 
+/**
+ * True if any of the fields was cut short.
+ */
+truncated: boolean, };
+
+export type ApprovalQuestion = { question: string, header: string | null, options: Array<ApprovalQuestionOption>, multi_select: boolean, 
+/**
+ * Whether answers other than the listed options are accepted.
+ */
+allow_free_text: boolean, 
+/**
+ * Label of the option chosen when the question is left unanswered.
+ */
+default: string | null, };
+
+export type ApprovalQuestionOption = { label: string, description: string | null, };
+
+export type ApprovalInfo = { approval_id: string, tool_name: string, 
+/**
+ * What the tool call will do, truncated. Not set for questions.
+ */
+tool_input: ApprovalToolInput | null, execution_process_id: string, is_question: boolean, 
+/**
+ * What was asked, for questions. Answers must fit these.
+ */
+questions: Array<ApprovalQuestion>, 
+/**
+ * Trusted relay client the approval was handed to, if any.
+ */
+delegated_to: string | null, created_at: string, timeout_at: string, };
 
 export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
 
@@ -194,6 +645,35 @@ export type ApprovalOutcome = { "status": "approved" } | { "status": "denied", r
 
 export type ApprovalResponse = { execution_process_id: string, status: ApprovalOutcome, };
 
+export type BatchApprovalItem = { approval_id: string, execution_process_id: string, status: ApprovalOutcome, };
+
+export type BatchApprovalRequest = { "type": "responses", responses: Array<BatchApprovalItem>, } | { "type": "approve_all_pending", execution_process_id: string, };
+
+export type BatchApprovalResult = { approval_id: string, 
+/**
+ * Set if the response was accepted.
+ */
+outcome: ApprovalOutcome | null, error: string | null, };
+
+export type DelegateApprovalRequest = { 
+/**
+ * Trusted relay client to hand the approval to.
+ */
+client_id: string, };
+
+export type ApprovalDelegation = { approval: ApprovalInfo, client_name: string, 
+/**
+ * Opens the approval on the delegated device.
+ */
+deeplink_path: string, 
+/**
+ * Host signature to send back with the response. Valid only for the
+ * delegated device and until the approval times out.
+ */
+signature: string, };
+
+export type DelegatedApprovalResponse = { signature: string, status: ApprovalOutcome, };
+
 export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
 /**
  * True when file contents are intentionally omitted (e.g., too large)
@@ -206,7 +686,40 @@ additions: number | null, deletions: number | null, repoId: string | null, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
-export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+export type DiffHunk = { 
+/**
+ * Derived from the hunk's position in the old file and its changed
+ * lines, so it stays the same while other hunks are discarded.
+ */
+id: string, old_start: number, old_lines: number, new_start: number, new_lines: number, 
+/**
+ * The hunk in unified diff format, including the `@@` header.
+ */
+patch: string, };
+
+export type LogSpan = { "span": "tool_start", 
+/**
+ * Identifies the tool call; matches the `ToolEnd` span that closes it.
+ */
+id: string, tool_name: string, 
+/**
+ * Kind of action, e.g. `command_run` or `file_edit`.
+ */
+action: string, command: string | null, 
+/**
+ * Files the tool reads or changes.
+ */
+files: Array<string>, timestamp: string | null, } | { "span": "tool_end", id: string, status: LogSpanStatus, exit_code: number | null, timestamp: string | null, } | { "span": "tokens", total_tokens: number, model_context_window: number, };
+
+export type LogSpanStatus = "success" | "failed" | "denied" | "timed_out";
+
+export type ApiErrorCode = "BAD_REQUEST" | "UNAUTHORIZED" | "FORBIDDEN" | "NOT_FOUND" | "CONFLICT" | "GONE" | "PAYLOAD_TOO_LARGE" | "RATE_LIMITED" | "INTERNAL" | "BAD_GATEWAY" | "TIMEOUT" | "SHUTTING_DOWN" | "REPO_NOT_FOUND" | "WORKSPACE_NOT_FOUND" | "SESSION_NOT_FOUND" | "EXECUTION_PROCESS_NOT_FOUND" | "BRANCH_NOT_FOUND" | "EXECUTOR_MISMATCH" | "WORKTREE_DIRTY" | "MERGE_CONFLICT" | "REBASE_IN_PROGRESS" | "BRANCHES_DIVERGED" | "GIT_AUTH_FAILED" | "EXECUTOR_NOT_INSTALLED" | "EXECUTOR_AUTH_REQUIRED" | "FILE_TOO_LARGE" | "UPLOAD_NOT_FOUND" | "INVALID_UPLOAD" | "EDITOR_NOT_FOUND" | "REMOTE_AUTH_REQUIRED";
+
+export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, 
+/**
+ * Set on every failure returned for an `ApiError`.
+ */
+error_code: ApiErrorCode | null, };
 
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse | null, };
 
@@ -288,15 +801,48 @@ export type CheckAgentAvailabilityQuery = { executor: BaseCodingAgent, };
 
 export type AgentPresetOptionsQuery = { executor: BaseCodingAgent, variant: string | null, };
 
+export type ConfigProfiles = { 
+/**
+ * The profile this server runs with.
+ */
+active: string, 
+/**
+ * The profile the next start uses unless `--config-profile` or
+ * `VK_CONFIG_PROFILE` names another.
+ */
+startup: string, profiles: Array<string>, 
+/**
+ * Whether a restart is needed to run with the startup profile.
+ */
+restart_required: boolean, };
+
+export type ConfigProfileRequest = { name: string, };
+
 export type CurrentUserResponse = { user_id: string, };
 
 export type StartSpake2EnrollmentRequest = { enrollment_code: string, client_message_b64: string, };
 
-export type FinishSpake2EnrollmentRequest = { enrollment_id: string, client_id: string, client_name: string, client_browser: string, client_os: string, client_device: string, public_key_b64: string, client_proof_b64: string, };
+export type FinishSpake2EnrollmentRequest = { enrollment_id: string, client_id: string, client_name: string, client_browser: string, client_os: string, client_device: string, public_key_b64: string, client_proof_b64: string, 
+/**
+ * X25519 key requesting end-to-end encryption for the new session.
+ */
+encryption_public_key_b64?: string, 
+/**
+ * Client signature over `encryption_public_key_b64`.
+ */
+encryption_key_signature_b64?: string, };
 
 export type StartSpake2EnrollmentResponse = { enrollment_id: string, server_message_b64: string, };
 
-export type FinishSpake2EnrollmentResponse = { signing_session_id: string, server_public_key_b64: string, server_proof_b64: string, };
+export type FinishSpake2EnrollmentResponse = { signing_session_id: string, server_public_key_b64: string, server_proof_b64: string, 
+/**
+ * Server X25519 key, set when end-to-end encryption was negotiated.
+ */
+encryption_public_key_b64: string | null, 
+/**
+ * Server signature over `encryption_public_key_b64`.
+ */
+encryption_key_signature_b64: string | null, };
 
 export type RelayPairedClient = { client_id: string, client_name: string, client_browser: string, client_os: string, client_device: string, };
 
@@ -304,14 +850,94 @@ export type ListRelayPairedClientsResponse = { clients: Array<RelayPairedClient>
 
 export type RemoveRelayPairedClientResponse = { removed: boolean, };
 
-export type RefreshRelaySigningSessionRequest = { client_id: string, timestamp: bigint, nonce: string, signature_b64: string, };
+export type RefreshRelaySigningSessionRequest = { client_id: string, timestamp: bigint, nonce: string, signature_b64: string, 
+/**
+ * X25519 key requesting end-to-end encryption for the new session.
+ */
+encryption_public_key_b64?: string, 
+/**
+ * Client signature over `encryption_public_key_b64`.
+ */
+encryption_key_signature_b64?: string, };
+
+export type RefreshRelaySigningSessionResponse = { signing_session_id: string, 
+/**
+ * Server X25519 key, set when end-to-end encryption was negotiated.
+ */
+encryption_public_key_b64: string | null, 
+/**
+ * Server signature over `encryption_public_key_b64`.
+ */
+encryption_key_signature_b64: string | null, };
+
+export type RotateRelayClientKeyRequest = { client_id: string, timestamp: bigint, nonce: string, new_public_key_b64: string, signature_b64: string, new_key_signature_b64: string, };
+
+export type RotateRelayClientKeyResponse = { 
+/**
+ * Signing session for the new key; sessions of the old key are revoked.
+ */
+signing_session_id: string, };
 
-export type RefreshRelaySigningSessionResponse = { signing_session_id: string, };
+export type RelayTunnelTransport = "websocket" | "long_poll";
 
-export type CreateFollowUpAttempt = { prompt: string, executor_config: ExecutorConfig, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+export type RelayConnectionQuality = "good" | "degraded" | "poor" | "disconnected";
+
+export type RelayConnectionStatus = { 
+/**
+ * Whether the relay is enabled and a session has been started.
+ */
+active: boolean, connected: boolean, quality: RelayConnectionQuality, transport: RelayTunnelTransport | null, connected_since: string | null, 
+/**
+ * Round trip time of the most recent successful probe.
+ */
+latency_ms: number | null, 
+/**
+ * Mean round trip time over the recent probe window.
+ */
+avg_latency_ms: number | null, 
+/**
+ * Mean absolute difference between consecutive probe round trips.
+ */
+jitter_ms: number | null, 
+/**
+ * Share of recent probes that got no answer, between 0 and 1.
+ */
+probe_loss: number, 
+/**
+ * Current interval between keepalive probes.
+ */
+keepalive_interval_secs: number, reconnects_last_hour: number, last_error: string | null, };
+
+export type CreateFollowUpAttempt = { prompt: string, executor_config: ExecutorConfig, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, 
+/**
+ * Start even though the worktree has uncommitted changes. Required when
+ * the dirty worktree policy is `confirm`.
+ */
+allow_dirty_worktree?: boolean, 
+/**
+ * Names of prompt snippets to append to the prompt, in order.
+ */
+snippets?: Array<string>, 
+/**
+ * Values for snippet variables, e.g. `failing_test`. These override the
+ * ones derived from the workspace.
+ */
+snippet_variables?: { [key in string]?: string }, };
 
 export type ResetProcessRequest = { process_id: string, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
 
+export type RenderPromptSnippetRequest = { workspace_id: string, name: string, variables?: { [key in string]?: string }, };
+
+export type SetProviderCredentialRequest = { kind: ProviderCredentialKind, secret: string, };
+
+export type TestApprovalRouteRequest = { tool_name: string, repo_id?: string, is_question?: boolean, };
+
+export type ApprovalRoute = { tool_category: ToolCategory, 
+/**
+ * The rule that matched, `None` when the configured defaults apply.
+ */
+rule_id: string | null, channels: Array<NotificationChannel>, };
+
 export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: string, };
 
 export type ChangeTargetBranchResponse = { repo_id: string, new_target_branch: string, status: [number, number], };
@@ -322,17 +948,405 @@ export type AddWorkspaceRepoResponse = { workspace: Workspace, repo: RepoWithTar
 
 export type MergeWorkspaceRequest = { repo_id: string, };
 
-export type PushWorkspaceRequest = { repo_id: string, };
+export type PushWorkspaceRequest = { repo_id: string, 
+/**
+ * Push even though the secret scan found possible credentials,
+ * confirming the findings.
+ */
+confirm_secrets?: boolean, };
 
 export type RenameBranchRequest = { new_branch_name: string, };
 
 export type RenameBranchResponse = { branch: string, };
 
+export type WebhookTaskRequest = { 
+/**
+ * Repository ID or repository name.
+ */
+repo: string, 
+/**
+ * Target branch for the workspace. Defaults to the repo's default target
+ * branch, then its current branch.
+ */
+branch: string | null, prompt: string, 
+/**
+ * Executor to run. Defaults to the executor profile from config.
+ */
+executor: ExecutorConfig | null, 
+/**
+ * Workspace name, also used for the git branch label.
+ */
+name: string | null, };
+
+/**
+ * A CI check state in the form CI systems other than GitHub can post.
+ */
+export type GenericCiEvent = { 
+/**
+ * Name of the repository the check ran in. Narrows the match to
+ * workspaces with a repo of that name.
+ */
+repo: string | null, branch: string, 
+/**
+ * Name of the check. Defaults to `ci`.
+ */
+name: string | null, state: CiState, head_sha: string | null, url: string | null, };
+
+/**
+ * An issue as listed by the tracker.
+ */
+export type TrackerIssue = { 
+/**
+ * Number or key shown in the tracker, e.g. `42` or `ENG-42`.
+ */
+key: string, title: string, body: string | null, url: string, };
+
+export type AllocStats = { 
+/**
+ * False when the counting allocator isn't the global allocator, in which
+ * case every other field is zero.
+ */
+enabled: boolean, allocations: number, deallocations: number, allocated_bytes: number, freed_bytes: number, live_bytes: number, peak_live_bytes: number, };
+
+export type WatcherHubStats = { watchers: number, polling_watchers: number, subscribers: number, 
+/**
+ * Event batches dropped because a subscriber's buffer was full.
+ */
+dropped_event_batches: bigint, };
+
+export type EntityChecksum = { count: number, 
+/**
+ * Hex SHA-256 over `"{id} {updated_at}\n"` lines sorted by id. Scratch
+ * rows use `{id}/{scratch_type}` as the id.
+ */
+checksum: string, };
+
+export type EventsCheckpoint = { 
+/**
+ * Sequence number of the last event, matching SSE event ids.
+ */
+seq: bigint, workspaces: EntityChecksum, execution_processes: EntityChecksum, scratch: EntityChecksum, };
+
+export type RepairWorkspaceDriftRequest = { 
+/**
+ * Restrict repairs to these workspaces. All repairable workspaces when omitted.
+ */
+workspace_ids: Array<string> | null, 
+/**
+ * Also delete directories in the workspace base dir that no workspace
+ * references. Ignored when `workspace_ids` is given, since orphans belong
+ * to no workspace.
+ */
+remove_orphans: boolean, };
+
+export type RepairWorkspaceDriftResponse = { repaired: Array<string>, failed: Array<WorkspaceRepairFailure>, 
+/**
+ * A fresh scan taken after the repairs ran.
+ */
+report: WorkspaceDriftReport, };
+
+export type WorkspaceRepairFailure = { workspace_id: string, error: string, };
+
+export type BulkWorkspaceAction = { "type": "delete", delete_branches: boolean, delete_remote: boolean, } | { "type": "archive" } | { "type": "rebase" } | { "type": "change_target_branch", new_target_branch: string, };
+
+export type BulkWorkspaceRequest = { workspace_ids: Array<string>, action: BulkWorkspaceAction, };
+
+export type BulkItemStatus = "succeeded" | "failed" | "skipped";
+
+export type BulkWorkspaceItemResult = { workspace_id: string, status: BulkItemStatus, error: string | null, };
+
+export type BulkWorkspaceResponse = { 
+/**
+ * False when validation failed for some workspace and nothing was
+ * changed.
+ */
+applied: boolean, results: Array<BulkWorkspaceItemResult>, };
+
+export type HunkDecision = { hunk_id: string, action: HunkReviewAction, };
+
+export type ReviewHunksRequest = { repo_id: string, path: string, decisions: Array<HunkDecision>, };
+
+export type ReviewHunksResponse = { decisions: Array<HunkReviewDecision>, 
+/**
+ * Hunks left in the file after discarded hunks were reverted.
+ */
+remaining_hunks: Array<DiffHunk>, };
+
+export type HunkReviewAction = "keep" | "discard";
+
+export type HunkReviewDecision = { id: string, workspace_id: string, execution_process_id: string | null, repo_id: string, file_path: string, hunk_id: string, action: HunkReviewAction, patch: string, created_at: string, };
+
+export type WorkspaceDriftKind = { "type": "missing_workspace_dir" } | { "type": "missing_worktree", repo_id: string, repo_name: string, } | { "type": "not_a_worktree", repo_id: string, repo_name: string, } | { "type": "branch_mismatch", repo_id: string, repo_name: string, expected: string, actual: string, } | { "type": "orphaned_workspace_dir" };
+
+export type WorkspaceDrift = { workspace_id: string | null, path: string, kind: WorkspaceDriftKind, 
+/**
+ * Whether the repair endpoint can fix this issue without user input.
+ */
+repairable: boolean, };
+
+export type WorkspaceDriftReport = { workspaces_scanned: number, issues: Array<WorkspaceDrift>, };
+
 export type StartReviewRequest = { executor_config: ExecutorConfig, additional_prompt: string | null, use_all_workspace_commits: boolean, };
 
 export type ReviewError = { "type": "process_already_running" };
 
-export type OpenEditorRequest = { editor_type: string | null, file_path: string | null, };
+export type ExplainChangeRequest = { executor_config: ExecutorConfig, repo_id: string, 
+/**
+ * Explain the commits made by this execution process. Takes precedence
+ * over `base_commit` and `head_commit`.
+ */
+execution_process_id: string | null, 
+/**
+ * Any revision git understands. Defaults to the fork point from the
+ * target branch.
+ */
+base_commit: string | null, 
+/**
+ * Any revision git understands. Defaults to the worktree's HEAD.
+ */
+head_commit: string | null, };
+
+export type RiskSeverity = "low" | "medium" | "high";
+
+export type FileRationale = { path: string, rationale: string, };
+
+export type RiskCallout = { path: string | null, severity: RiskSeverity, description: string, };
+
+export type ChangeExplanationContent = { summary: string, files: Array<FileRationale>, risks: Array<RiskCallout>, };
+
+export type ChangeExplanation = { id: string, workspace_id: string, repo_id: string, base_commit: string, head_commit: string, execution_process_id: string | null, 
+/**
+ * Set once the summarization run has finished and its output parsed.
+ */
+explanation: ChangeExplanationContent | null, 
+/**
+ * Why no explanation could be produced, e.g. the agent's output wasn't
+ * valid JSON.
+ */
+error: string | null, created_at: string, updated_at: string, };
+
+export type WorkspaceHandoffRepo = { repo_name: string, remote_url: string | null, branch: string, target_branch: string, head_commit: string | null, };
+
+export type WorkspaceHandoffTurn = { prompt: string | null, summary: string | null, created_at: string, };
+
+export type WorkspaceHandoffQueuedMessage = { message: string, executor_config: JsonValue, };
+
+export type WorkspaceHandoffPayload = { workspace_name: string | null, repos: Array<WorkspaceHandoffRepo>, transcript: Array<WorkspaceHandoffTurn>, queued_messages: Array<WorkspaceHandoffQueuedMessage>, };
+
+export type WorkspaceHandoff = { id: string, owner_user_id: string, source_local_workspace_id: string, payload: WorkspaceHandoffPayload, claimed_at: string | null, claimed_local_workspace_id: string | null, created_at: string, };
+
+export type ListWorkspaceHandoffsResponse = { handoffs: Array<WorkspaceHandoff>, };
+
+export type WorkspaceHandoffDirection = "outgoing" | "incoming";
+
+export type WorkspaceHandoffLink = { id: string, workspace_id: string, remote_handoff_id: string, direction: WorkspaceHandoffDirection, created_at: string, };
+
+export type PreviewScriptInjection = "none" | "local" | "full";
+
+export type WorkspaceDevServer = { workspace_id: string, 
+/**
+ * Runs instead of the repos' dev server scripts.
+ */
+script: string | null, 
+/**
+ * Port the dev server is expected to listen on, exported as `PORT`.
+ * Published as the workspace's `dev_server_port` while it runs instead
+ * of a port detected in its output.
+ */
+port: number | null, 
+/**
+ * Overrides the configured preview script injection for this
+ * workspace's dev server port.
+ */
+script_injection: PreviewScriptInjection | null, updated_at: string, };
+
+export type UpdateWorkspaceDevServer = { script: string | null, port: number | null, script_injection?: PreviewScriptInjection, };
+
+export type RepoCommandMode = "sequential" | "parallel";
+
+export type RepoCommandStepStatus = "pending" | "running" | "completed" | "failed" | "killed" | "skipped";
+
+export type RepoCommandRepoStatus = { repo_id: string, repo_name: string, status: RepoCommandStepStatus, execution_process_id: string | null, exit_code: bigint | null, };
+
+export type RepoCommandStatus = { mode: RepoCommandMode, script: string, started_at: string, repos: Array<RepoCommandRepoStatus>, };
+
+/**
+ * A possible credential in the changes a workspace branch adds to a repo.
+ */
+export type WorkspaceSecretFinding = { id: string, workspace_id: string, repo_id: string, file_path: string, line: number | null, 
+/**
+ * The scanner rule that matched, e.g. `aws-access-key-id`.
+ */
+rule_id: string, 
+/**
+ * The start of the matched text, with the rest masked.
+ */
+redacted: string, 
+/**
+ * When someone chose to push with this finding anyway.
+ */
+confirmed_at: string | null, created_at: string, };
+
+export type WorkspaceChild = { child_workspace_id: string, parent_workspace_id: string, 
+/**
+ * The part of the prompt specific to this child.
+ */
+prompt_fragment: string, 
+/**
+ * Children are merged into the parent in ascending position.
+ */
+position: bigint, merged_at: string | null, created_at: string, };
+
+export type ChildWorkspaceProgress = { total: number, running: number, completed: number, 
+/**
+ * Failed, killed, timed out or interrupted.
+ */
+failed: number, 
+/**
+ * Merged into the parent's branch.
+ */
+merged: number, };
+
+export type AutoRebaseStatus = "rebased" | "conflicts";
+
+export type WorkspaceBranchFreshness = { workspace_id: string, repo_id: string, target_branch: string, commits_ahead: number, 
+/**
+ * Commits on the target branch the workspace branch doesn't have yet.
+ */
+commits_behind: number, checked_at: string, 
+/**
+ * Outcome of the latest automatic rebase onto the target branch, if any.
+ */
+auto_rebase_status: AutoRebaseStatus | null, 
+/**
+ * Target branch commit the latest automatic rebase was attempted onto.
+ */
+auto_rebase_target_oid: string | null, auto_rebased_at: string | null, };
+
+export type WorkspaceCheckStatus = "pending" | "running" | "passed" | "failed" | "skipped";
+
+/**
+ * The outcome of one repo check in the latest check run of a workspace.
+ */
+export type WorkspaceCheckResult = { id: string, workspace_id: string, repo_id: string, name: string, command: string, position: bigint, status: WorkspaceCheckStatus, 
+/**
+ * The process the check ran as; its logs hold the check's output.
+ */
+execution_process_id: string | null, exit_code: bigint | null, started_at: string | null, completed_at: string | null, };
+
+export type CiState = "pending" | "success" | "failure";
+
+/**
+ * The latest state of one CI check (a workflow, a check run, a commit
+ * status context) on a workspace branch, as reported by a CI webhook.
+ */
+export type WorkspaceCiCheck = { workspace_id: string, name: string, state: CiState, 
+/**
+ * Commit the check ran on.
+ */
+head_sha: string | null, 
+/**
+ * Page with the check's details on the CI provider.
+ */
+url: string | null, updated_at: string, };
+
+/**
+ * The CI checks of a workspace branch's latest reported commit.
+ */
+export type WorkspaceCiStatus = { 
+/**
+ * Failure if any check failed, pending if any is still running.
+ */
+state: CiState, checks: Array<WorkspaceCiCheck>, };
+
+/**
+ * An issue imported from a repo's issue tracker as a workspace.
+ */
+export type WorkspaceIssueLink = { id: string, workspace_id: string, repo_id: string, provider: IssueTrackerKind, 
+/**
+ * Number or key shown in the tracker, e.g. `42` or `ENG-42`.
+ */
+issue_key: string, title: string, url: string, 
+/**
+ * When the issue was closed after the workspace's PRs were merged.
+ */
+closed_at: string | null, created_at: string, };
+
+export type WorkspaceActivityKind = "process_started" | "process_finished" | "approval_approved" | "approval_denied" | "commit" | "pr_opened" | "pr_merged" | "pr_closed" | "auto_rebase";
+
+export type WorkspaceActivity = { 
+/**
+ * Stable across requests; part of the paging cursor.
+ */
+id: string, kind: WorkspaceActivityKind, occurred_at: string, execution_process_id: string | null, run_reason: ExecutionProcessRunReason | null, process_status: ExecutionProcessStatus | null, repo_id: string | null, before_sha: string | null, 
+/**
+ * New HEAD for commits, target branch commit for rebases.
+ */
+after_sha: string | null, pr_number: bigint | null, pr_url: string | null, pr_status: MergeStatus | null, rebase_status: AutoRebaseStatus | null, };
+
+export type WorkspaceActivityQuery = { 
+/**
+ * `next_cursor` of the previous page.
+ */
+cursor?: string | null, limit?: number | null, };
+
+export type WorkspaceActivityPage = { events: Array<WorkspaceActivity>, 
+/**
+ * Set when there are older events.
+ */
+next_cursor: string | null, };
+
+export type ChildWorkspaceSpec = { 
+/**
+ * Defaults to a name derived from the prompt.
+ */
+name?: string, 
+/**
+ * The part of the work this child does.
+ */
+prompt: string, };
+
+export type SpawnChildWorkspacesRequest = { executor_config: ExecutorConfig, 
+/**
+ * Context every child gets before its own prompt, e.g. the overall goal.
+ */
+shared_prompt?: string, children: Array<ChildWorkspaceSpec>, };
+
+export type ChildWorkspace = { workspace: WorkspaceWithStatus, child_workspace_id: string, parent_workspace_id: string, 
+/**
+ * The part of the prompt specific to this child.
+ */
+prompt_fragment: string, 
+/**
+ * Children are merged into the parent in ascending position.
+ */
+position: bigint, merged_at: string | null, created_at: string, };
+
+export type ChildWorkspacesResponse = { progress: ChildWorkspaceProgress, children: Array<ChildWorkspace>, };
+
+export type MergeChildWorkspacesRequest = { 
+/**
+ * Children to merge. Defaults to every child not merged yet.
+ */
+child_workspace_ids?: Array<string>, };
+
+export type ProjectExecutionWeight = { project_id: string, weight: number, updated_at: string, };
+
+export type SetProjectExecutionWeightRequest = { weight: number, };
+
+export type OpenEditorRequest = { editor_type: string | null, 
+/**
+ * Relative to the workspace or one of its repos, or absolute inside
+ * the worktree or the original repo checkout, as agents report it in
+ * logs. A `:line[:column]` suffix is honoured.
+ */
+file_path: string | null, line: number | null, column: number | null, };
+
+export type OpenComponentSourceRequest = { 
+/**
+ * Source file as reported by the component's stack frame.
+ */
+file_name: string, line: number | null, column: number | null, editor_type: string | null, };
 
 export type OpenEditorResponse = { url: string | null, };
 
@@ -340,6 +1354,13 @@ export type OpenRemoteEditorResponse = { url: string, local_port: number, ssh_al
 
 export type OpenRemoteWorkspaceInEditorRequest = { host_id: string, workspace_id: string, editor_type: string | null, file_path: string | null, };
 
+export type DispatchRemoteExecutionRequest = { host_id: string, 
+/**
+ * Repos are given by their ids on this instance and matched to the
+ * host's repos by name.
+ */
+workspace: CreateAndStartWorkspaceRequest, };
+
 export type PairRelayHostRequest = { host_id: string, host_name: string, enrollment_code: string, };
 
 export type PairRelayHostResponse = { paired: boolean, };
@@ -350,16 +1371,96 @@ export type ListRelayPairedHostsResponse = { hosts: Array<RelayPairedHost>, };
 
 export type RemoveRelayPairedHostResponse = { removed: boolean, };
 
-export type CreateWorkspaceApiRequest = { name: string | null, };
+export type CreateWorkspaceApiRequest = { name: string | null, 
+/**
+ * Override the repos' dev server script and expected port.
+ */
+dev_server?: UpdateWorkspaceDevServer, };
 
 export type LinkedIssueInfo = { remote_project_id: string, issue_id: string, };
 
-export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
+export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, 
+/**
+ * Push even though the secret scan found possible credentials,
+ * confirming the findings.
+ */
+confirm_secrets?: boolean, };
 
 export type AttachmentResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
 export type AttachmentMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
 
+export type InitChunkedUpload = { filename: string, total_size: bigint, 
+/**
+ * Hex SHA-256 of the whole file, verified on completion.
+ */
+sha256: string, };
+
+export type ChunkedUploadStatus = { upload_id: string, 
+/**
+ * Every chunk but the last must be exactly this size.
+ */
+chunk_size: number, total_chunks: number, 
+/**
+ * Chunks not received yet. Resume by sending these.
+ */
+missing_chunks: Array<number>, };
+
+export type ContentSearchQuery = { q: string, 
+/**
+ * Treat `q` as a regular expression instead of literal text.
+ */
+regex: boolean, case_sensitive: boolean, 
+/**
+ * Only search files matching this glob, e.g. `*.rs` or `src/**`.
+ */
+glob?: string, max_results?: number, };
+
+export type ContentMatch = { repo_name: string, 
+/**
+ * Relative to the repo's worktree.
+ */
+path: string, 
+/**
+ * 1-based.
+ */
+line_number: bigint, line: string, 
+/**
+ * Byte ranges of the matches within `line`.
+ */
+ranges: Array<[number, number]>, };
+
+export type ContentSearchEvent = { "type": "match" } & ContentMatch | { "type": "done", files_searched: number, matches: number, 
+/**
+ * Whether the search stopped at the result limit.
+ */
+truncated: boolean, };
+
+export type WorktreeFileVersion = { sha256: string, size_bytes: bigint, modified_at: string | null, };
+
+export type WorktreeFile = { 
+/**
+ * Path relative to the workspace directory.
+ */
+path: string, content: string, version: WorktreeFileVersion, };
+
+export type WriteWorkspaceFileRequest = { content: string, 
+/**
+ * `sha256` of the version the edit is based on; omit to create a new
+ * file.
+ */
+expected_sha256: string | null, };
+
+export type WriteWorkspaceFileError = { "type": "conflict", current: WorktreeFileVersion, };
+
+export type DevServerState = "stopped" | "starting" | "healthy" | "unhealthy";
+
+export type DevServerStatus = { state: DevServerState, 
+/**
+ * The port registered with the preview proxy.
+ */
+port: number | null, execution_process_ids: Array<string>, last_health_check_at: string | null, };
+
 export type WorkspaceRepoInput = { repo_id: string, target_branch: string, };
 
 export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
@@ -368,6 +1469,25 @@ export type RunAgentSetupResponse = Record<string, never>;
 
 export type GhCliSetupError = "BREW_MISSING" | "SETUP_HELPER_NOT_SUPPORTED" | { "OTHER": { message: string, } };
 
+export type GenerateCommitMessageRequest = { repo_id: string, 
+/**
+ * Defaults to the configured executor profile.
+ */
+executor_config: ExecutorConfig | null, };
+
+export type GenerateCommitMessageResponse = { message: string, };
+
+export type SquashWorkspaceRequest = { repo_id: string, 
+/**
+ * Message of the squashed commit. Defaults to the oldest commit's title
+ * followed by every commit's summary.
+ */
+message: string | null, 
+/**
+ * Return the resulting commit without changing the branch.
+ */
+dry_run: boolean, };
+
 export type RebaseWorkspaceRequest = { repo_id: string, old_base_branch: string | null, new_base_branch: string | null, };
 
 export type ContinueRebaseRequest = { repo_id: string, };
@@ -376,12 +1496,18 @@ export type AbortConflictsRequest = { repo_id: string, };
 
 export type GitOperationError = { "type": "merge_conflicts", message: string, op: ConflictOp, conflicted_files: Array<string>, target_branch: string, } | { "type": "rebase_in_progress" };
 
-export type PushError = { "type": "force_push_required" };
+export type PushError = { "type": "force_push_required" } | { "type": "secrets_detected", findings: Array<WorkspaceSecretFinding>, };
 
-export type PrError = { "type": "cli_not_installed", provider: ProviderKind, } | { "type": "cli_not_logged_in", provider: ProviderKind, } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, } | { "type": "unsupported_provider" };
+export type PrError = { "type": "cli_not_installed", provider: ProviderKind, } | { "type": "cli_not_logged_in", provider: ProviderKind, } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, } | { "type": "unsupported_provider" } | { "type": "secrets_detected", findings: Array<WorkspaceSecretFinding>, };
 
 export type RunScriptError = { "type": "no_script_configured" } | { "type": "process_already_running" };
 
+export type RunRepoCommandRequest = { script: string, mode: RepoCommandMode, 
+/**
+ * Repos to run in, in order. Defaults to all of the workspace's repos.
+ */
+repo_ids?: Array<string>, };
+
 export type AssociateWorkspaceAttachmentsRequest = { attachment_ids: Array<string>, };
 
 export type ImportIssueAttachmentsRequest = { issue_id: string, };
@@ -398,10 +1524,36 @@ export type GetPrCommentsError = { "type": "no_pr_attached" } | { "type": "cli_n
 
 export type GetPrCommentsQuery = { repo_id: string, };
 
-export type CreateAndStartWorkspaceRequest = { name: string | null, repos: Array<WorkspaceRepoInput>, linked_issue: LinkedIssueInfo | null, executor_config: ExecutorConfig, prompt: string, attachment_ids: Array<string> | null, };
+export type CreateAndStartWorkspaceRequest = { name: string | null, repos: Array<WorkspaceRepoInput>, linked_issue: LinkedIssueInfo | null, executor_config: ExecutorConfig, prompt: string, attachment_ids: Array<string> | null, 
+/**
+ * Override the repos' dev server script and expected port.
+ */
+dev_server?: UpdateWorkspaceDevServer, 
+/**
+ * Runs in the workspace root before the repos' setup scripts.
+ */
+setup_script?: string, 
+/**
+ * Extra environment variables for every process run in the workspace.
+ */
+env_vars?: { [key in string]?: string }, };
 
 export type CreateAndStartWorkspaceResponse = { workspace: Workspace, execution_process: ExecutionProcess, };
 
+export type CreateWorkspaceFromTemplateRequest = { 
+/**
+ * Defaults to the template name.
+ */
+name?: string, 
+/**
+ * Replaces the template's repos.
+ */
+repo_ids?: Array<string>, 
+/**
+ * Appended to the template prompt, e.g. the failing test's name.
+ */
+additional_prompt?: string, };
+
 export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: string | null, body: string, created_at: string, url: string | null, } | { "comment_type": "review", id: bigint, author: string, author_association: string | null, body: string, created_at: string, url: string | null, path: string, line: bigint | null, side: string | null, diff_hunk: string | null, };
 
 export type ProviderKind = "git_hub" | "azure_dev_ops" | "unknown";
@@ -412,6 +1564,20 @@ export type GitRemote = { name: string, url: string, };
 
 export type ListPrsError = { "type": "cli_not_installed", provider: ProviderKind, } | { "type": "auth_failed", message: string, } | { "type": "unsupported_provider" };
 
+export type ImportIssuesRequest = { 
+/**
+ * Issue numbers or keys, e.g. `42` or `ENG-42`.
+ */
+issue_keys: Array<string>, 
+/**
+ * Executor to run. Defaults to the executor profile from config.
+ */
+executor_config: ExecutorConfig | null, 
+/**
+ * Defaults to the repo's default target branch, then its current branch.
+ */
+target_branch: string | null, };
+
 export type LinkPrToIssueRequest = { pr_url: string, pr_number: number, base_branch: string, };
 
 export type CreateWorkspaceFromPrBody = { repo_id: string, pr_number: bigint, pr_title: string, pr_url: string, head_branch: string, base_branch: string, run_setup: boolean, remote_name: string | null, };
@@ -486,9 +1652,79 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
+export type TreeGitStatus = "modified" | "added" | "deleted" | "renamed" | "untracked" | "conflicted";
+
+export type TreeEntry = { name: string, 
+/**
+ * Path relative to the listed root, `/`-separated.
+ */
+path: string, 
+/**
+ * 1 for direct children of the requested directory.
+ */
+depth: number, is_directory: boolean, size_bytes: bigint | null, modified_at: string | null, 
+/**
+ * For directories, `modified` when anything below them changed.
+ */
+git_status: TreeGitStatus | null, };
+
+export type DirectoryTreePage = { path: string, 
+/**
+ * Depth-first, sorted by name within each directory.
+ */
+entries: Array<TreeEntry>, 
+/**
+ * Pass as `offset` to get the next page; `None` on the last page.
+ */
+next_offset: number | null, };
+
 export type SearchMode = "taskform" | "settings";
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, remote_onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, commit_reminder_enabled: boolean, commit_reminder_prompt: string | null, send_message_shortcut: SendMessageShortcut, relay_enabled: boolean, host_nickname: string | null, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, remote_onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics: AnalyticsConsent, 
+/**
+ * Send error reports without message text or fields, for setups where
+ * even scrubbed repo contents mustn't leave the machine.
+ */
+error_reports_metadata_only: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, commit_reminder_enabled: boolean, commit_reminder_prompt: string | null, send_message_shortcut: SendMessageShortcut, relay_enabled: boolean, host_nickname: string | null, 
+/**
+ * Shared secret for `POST /api/hooks/task`. Inbound webhooks are rejected
+ * while this is unset. The config API returns it redacted.
+ */
+webhook_token: string | null, log_retention: LogRetentionConfig, 
+/**
+ * What to do when a coding agent is started on a worktree with
+ * uncommitted changes. `stash` restores them once the run finishes;
+ * `confirm` requires `allow_dirty_worktree` on the request.
+ */
+dirty_worktree_policy: DirtyWorktreePolicy, 
+/**
+ * Coding agent executions allowed to run at once across all projects.
+ * Further executions wait and are admitted fairly between projects.
+ * Unlimited when unset.
+ */
+max_concurrent_executions: number | null, execution_tag_limits: ExecutionTagLimits, prompt_snippets: Array<PromptSnippet>, report_delivery: ReportDeliveryConfig, preview_script_injection: PreviewScriptInjection, 
+/**
+ * Coding agent runs exceeding these are killed and marked timed out.
+ */
+executor_timeouts: ExecutorTimeoutConfig, 
+/**
+ * On shutdown, how long running coding agents get to finish before
+ * their work is committed and they are stopped as interrupted.
+ */
+shutdown_grace_period_secs: number, 
+/**
+ * `reflink` clones new worktrees from the repo's checkout, including
+ * ignored files such as `node_modules`, on filesystems with
+ * copy-on-write support (APFS, Btrfs, XFS). Falls back to `checkout`
+ * elsewhere.
+ */
+worktree_strategy: WorktreeStrategy, 
+/**
+ * Rebase workspace branches that have fallen behind their target onto
+ * it while no process is running in the workspace. Rebases that would
+ * conflict are aborted and left for a manual rebase.
+ */
+auto_rebase_idle_workspaces: boolean, route_rate_limits: RouteRateLimitConfig, notification_delivery: NotificationDeliveryConfig, terminal: TerminalConfig, execution_backend: ExecutionBackend, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -510,9 +1746,286 @@ export type ShowcaseState = { seen_features: Array<string>, };
 
 export type SendMessageShortcut = "ModifierEnter" | "Enter";
 
+export type LogRetentionConfig = { 
+/**
+ * Compress logs older than this many days. `None` disables compression.
+ */
+compress_after_days: number | null, 
+/**
+ * Delete logs older than this many days. `None` keeps logs forever.
+ */
+delete_after_days: number | null, 
+/**
+ * Delete the oldest logs once all logs together exceed this many MB.
+ */
+max_total_size_mb: bigint | null, };
+
+export type DirtyWorktreePolicy = "allow" | "block" | "stash" | "confirm";
+
+export type PromptSnippet = { name: string, content: string, 
+/**
+ * Only offered in workspaces of this project. Global when unset; a
+ * project snippet shadows a global one with the same name.
+ */
+project_id: string | null, };
+
+export type ReportDeliveryConfig = { 
+/**
+ * Receives each report as a JSON `POST`.
+ */
+webhook_url: string | null, 
+/**
+ * Receives each report as CSV, sent through the local `sendmail`.
+ */
+email_to: string | null, weekly: boolean, monthly: boolean, };
+
+/**
+ * Timeouts for coding agent runs. A session's own timeouts take precedence,
+ * then the entry for its executor, then `default`.
+ */
+export type ExecutorTimeoutConfig = { default: ExecutionTimeouts, per_executor: { [key in BaseCodingAgent]?: ExecutionTimeouts }, };
+
+export type WorktreeStrategy = "checkout" | "reflink";
+
+export type RouteRateLimitConfig = { enabled: boolean, 
+/**
+ * Creating workspaces and sessions, sending follow-ups and webhook tasks.
+ */
+task_creation_per_minute: number, 
+/**
+ * Stopping execution processes and dev servers.
+ */
+process_control_per_minute: number, 
+/**
+ * Writing files in workspace worktrees.
+ */
+file_writes_per_minute: number, };
+
+/**
+ * Which notifications are sent at all. Applies to every channel.
+ */
+export type NotificationEventToggles = { approvals: boolean, questions: boolean, 
+/**
+ * A coding agent run in a workspace finished, failed or timed out.
+ */
+workspace_complete: boolean, };
+
+/**
+ * Local times, as `HH:MM`, between which desktop and sound notifications
+ * are held back. A window may wrap past midnight, e.g. `22:00` to `07:00`.
+ */
+export type QuietHours = { start: string, end: string, };
+
+/**
+ * How desktop and sound notifications are paced. Slack and webhook
+ * channels are only subject to `events`.
+ */
+export type NotificationDeliveryConfig = { 
+/**
+ * After a notification, further ones within this many seconds are
+ * collapsed into one summary. `0` sends each on its own.
+ */
+batch_window_secs: number, events: NotificationEventToggles, 
+/**
+ * Notifications held back during quiet hours are summarized once they
+ * end.
+ */
+quiet_hours: QuietHours | null, };
+
+export type TerminalRecordingInfo = { id: string, started_at: string, cols: number, rows: number, size_bytes: number, };
+
+export type ProcessTreeNode = { pid: number, command: string, 
+/**
+ * Percent of one CPU, averaged over the process lifetime on Linux and
+ * over recent activity on macOS.
+ */
+cpu_percent: number, rss_kb: number, children: Array<ProcessTreeNode>, };
+
+/**
+ * A signal sent to every process in an execution's process group.
+ */
+export type ProcessSignal = "pause" | "resume" | "interrupt";
+
+export type SignalExecutionProcessRequest = { signal: ProcessSignal, };
+
+/**
+ * Where a workspace terminal starts.
+ */
+export type TerminalCwd = "repo" | "workspace_root";
+
+/**
+ * How workspace terminals are started.
+ */
+export type TerminalConfig = { 
+/**
+ * Shell to run, as a path or a name on `PATH`. `None` uses the user's
+ * login shell, or PowerShell on Windows.
+ */
+shell: string | null, 
+/**
+ * Replace the arguments the shell is otherwise started with, e.g.
+ * `["-l"]` to load login profiles.
+ */
+args: Array<string> | null, 
+/**
+ * Set on top of the server's environment. `$VAR` and `~` in values are
+ * expanded, so `PATH` can be extended with `~/.cargo/bin:$PATH`.
+ */
+env: { [key in string]?: string }, cwd: TerminalCwd, 
+/**
+ * Save each terminal's output so it can be replayed later. What is
+ * typed isn't saved, but anything the terminal shows is.
+ */
+record: boolean, };
+
+/**
+ * Where executors, scripts and dev servers run.
+ */
+export type ExecutionBackend = "local" | "docker";
+
+/**
+ * Coding agent executions allowed to run at once among those whose repos
+ * carry each resource tag. Unlimited when unset. An execution at its tag's
+ * limit waits while executions with other tags go ahead of it.
+ */
+export type ExecutionTagLimits = { cpu_heavy: number | null, gpu: number | null, network: number | null, };
+
+/**
+ * Which kinds of anonymous analytics the user agreed to send.
+ */
+export type AnalyticsConsent = { 
+/**
+ * Which features are used, e.g. workspaces created or PRs opened.
+ */
+usage: boolean, 
+/**
+ * Error reports, and failed or timed out agent runs.
+ */
+errors: boolean, 
+/**
+ * How long agent runs take.
+ */
+performance: boolean, };
+
+/**
+ * The consent category an event falls under. See `AnalyticsConsent`.
+ */
+export type AnalyticsCategory = "usage" | "errors" | "performance";
+
+/**
+ * An event as it was handed to the analytics backend, or as it would have
+ * been had the user consented to its category.
+ */
+export type RecordedAnalyticsEvent = { category: AnalyticsCategory, event: string, sent: boolean, recorded_at: string, 
+/**
+ * The exact request body.
+ */
+payload: JsonValue, };
+
+export type LogCompactionReport = { compressed_files: number, deleted_files: number, pruned_sessions: number, 
+/**
+ * Bytes freed by compression and deletion combined.
+ */
+bytes_reclaimed: bigint, };
+
+export type DbVacuumKind = "none" | "incremental" | "full";
+
+export type DbMaintenanceReport = { 
+/**
+ * Database file plus WAL, before and after.
+ */
+size_before_bytes: bigint, size_after_bytes: bigint, 
+/**
+ * Unused pages left in the database file afterwards.
+ */
+free_bytes_after: bigint, 
+/**
+ * Skipped outside WAL mode and while a replica is configured, since the
+ * replicator must checkpoint itself.
+ */
+wal_checkpointed: boolean, vacuum: DbVacuumKind, integrity_ok: boolean, 
+/**
+ * Problems reported by the integrity check, if any.
+ */
+integrity_errors: Array<string>, };
+
+export type ApiProvider = "anthropic" | "openai" | "google";
+
+export type ProviderCredentialKind = "api_key" | "oauth_token";
+
+export type ProviderCredentialInfo = { provider: ApiProvider, configured: boolean, kind: ProviderCredentialKind | null, 
+/**
+ * Only the last characters of the secret, e.g. `••••a1b2`.
+ */
+masked: string | null, env_var: string | null, updated_at: string | null, };
+
+export type ProviderCredentialValidation = { provider: ApiProvider, valid: boolean, 
+/**
+ * HTTP status of the validation request, if the provider answered.
+ */
+status: number | null, message: string | null, };
+
+export type ReportPeriodKind = "week" | "month";
+
+export type ProductivityReport = { period: ReportPeriodKind, period_start: string, 
+/**
+ * First day after the period.
+ */
+period_end: string, 
+/**
+ * Workspaces merged for the first time, directly or through a PR.
+ */
+tasks_completed: bigint, 
+/**
+ * Median seconds from workspace creation to its first merge.
+ */
+median_time_to_merge_seconds: bigint | null, approvals_approved: bigint, approvals_denied: bigint, 
+/**
+ * Coding agent executions per executor.
+ */
+executor_mix: { [key in string]?: bigint }, };
+
+export type HealthStatus = "skipped" | "ok" | "degraded" | "failed";
+
+export type HealthCheck = { name: string, status: HealthStatus, latency_ms: bigint, message: string | null, };
+
+export type HealthReport = { 
+/**
+ * Worst status of the individual checks.
+ */
+status: HealthStatus, version: string, checks: Array<HealthCheck>, };
+
+export type SessionTimeline = { session_id: string, started_at: string | null, 
+/**
+ * `None` while a process is still running.
+ */
+completed_at: string | null, duration_ms: bigint, processes: Array<TimelineProcess>, };
+
+export type TimelineProcess = { execution_process_id: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, exit_code: bigint | null, executor: string | null, started_at: string, completed_at: string | null, 
+/**
+ * Until completion, or until now while running.
+ */
+duration_ms: bigint, 
+/**
+ * Repos whose HEAD moved during the process.
+ */
+commits: Array<TimelineCommit>, events: Array<TimelineEvent>, };
+
+export type TimelineCommit = { repo_id: string, repo_name: string, before_sha: string | null, after_sha: string | null, };
+
+export type TimelineEvent = { 
+/**
+ * Position of the entry in the process's conversation.
+ */
+entry_index: number, timestamp: string | null, } & TimelineEventKind;
+
+export type TimelineEventKind = { "type": "user_message", content: string, } | { "type": "assistant_message", content: string, } | { "type": "tool_call", tool_name: string, action_type: ActionType, status: ToolStatus, content: string, approval: TimelineApproval | null, } | { "type": "error", content: string, };
+
+export type TimelineApproval = { "outcome": "pending" } | { "outcome": "approved" } | { "outcome": "denied", reason: string | null, } | { "outcome": "timed_out" };
+
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
-export type QueuedMessage = { 
+export type QueuedMessage = { id: string, 
 /**
  * The session this message is queued for
  */
@@ -528,8 +2041,50 @@ queued_at: string, };
 
 export type QueueStatus = { "status": "empty" } | { "status": "queued", message: QueuedMessage, };
 
+export type ExecutionQueueStatus = { 
+/**
+ * `None` when executions are not capped.
+ */
+capacity: number | null, running: number, waiting: number, projects: Array<ProjectQueueStatus>, 
+/**
+ * Tags with a limit or with running or waiting executions.
+ */
+tags: Array<TagQueueStatus>, };
+
+export type ProjectQueueStatus = { 
+/**
+ * `None` for workspaces that do not belong to a project.
+ */
+project_id: string | null, running: number, waiting: number, };
+
+export type TagQueueStatus = { tag: ResourceTag, limit: number | null, running: number, waiting: number, };
+
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
+export type SquashedCommit = { sha: string, summary: string, author: string, };
+
+export type SquashPlan = { 
+/**
+ * Fork point from the target branch; parent of the squashed commit.
+ */
+base_commit: string, 
+/**
+ * Branch head the plan was made from.
+ */
+head_commit: string, 
+/**
+ * Commits being squashed, oldest first.
+ */
+commits: Array<SquashedCommit>, 
+/**
+ * Message of the squashed commit, co-author trailers included.
+ */
+message: string, 
+/**
+ * Set once the squash has been applied.
+ */
+squashed_commit: string | null, };
+
 export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
@@ -562,7 +2117,7 @@ reasoning_id?: string | null,
  */
 permission_policy?: PermissionPolicy | null, };
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "ArchiveScript" | "DevServer" | "ToolInstallScript";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "ArchiveScript" | "DevServer" | "ToolInstallScript" | "RepoCommand" | "CheckCommand";
 
 export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, 
 /**